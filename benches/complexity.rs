@@ -0,0 +1,147 @@
+//! Benchmarks for the growth of the time to plan with the number of batches. Run with `cargo bench`.
+//!
+//! Each case plans a number of batches, and then a multiple of that number of batches, and reports by which
+//! factor the time to plan grew. When the time grows linearly the factor is about the same as the factor by
+//! which the number of batches grew, while quadratic growth shows as the square of that factor.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use pack_planner::model::{
+    ItemAttributes, ItemTemplate, LengthMode, NestingRule, PackRule, PackSortOrder, PackTemplate,
+};
+use pack_planner::planner::plan_packs;
+
+// The number of batches of the smaller of the two inputs, and the factor by which the larger input is larger
+const NUMBER_OF_BATCHES: usize = 2_000;
+
+const GROWTH: usize = 8;
+
+const NUMBER_OF_RUNS: u32 = 3;
+
+fn create_item(id: String, length: f64, weight: f64, count: i32, tags: &[&str]) -> ItemTemplate {
+    ItemTemplate {
+        id: id.into(),
+        length,
+        weight,
+        count,
+        attributes: Arc::new(ItemAttributes {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..ItemAttributes::default()
+        }),
+    }
+}
+
+/// Returns the fastest of a number of runs that plan the batches created by 'create_item'.
+fn time_to_plan(
+    number_of_batches: usize,
+    create_item: &impl Fn(usize) -> ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Duration {
+    let items: Vec<ItemTemplate> = (0..number_of_batches).map(create_item).collect();
+    (0..NUMBER_OF_RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(plan_packs(&items, pack_template, rules).expect("Failed to plan the packs."));
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Plans the small and the large input, and reports by which factor the time to plan grew.
+fn measure_growth(
+    name: &str,
+    create_item: impl Fn(usize) -> ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) {
+    let small = time_to_plan(NUMBER_OF_BATCHES, &create_item, pack_template, rules);
+    let large = time_to_plan(
+        NUMBER_OF_BATCHES * GROWTH,
+        &create_item,
+        pack_template,
+        rules,
+    );
+    let growth = large.as_secs_f64() / small.as_secs_f64().max(1e-6);
+    println!(
+        "{name}: {small:?} -> {large:?}, {growth:.1}x the time for {GROWTH}x the batches (linear: {GROWTH}x, quadratic: {}x)",
+        GROWTH * GROWTH
+    );
+}
+
+fn main() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 500.0,
+        hard_maximum_weight: Some(520.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    measure_growth(
+        "hard_maximum_weight",
+        |i| create_item(i.to_string(), 100.0, 9.5, 7, &[]),
+        &pack_template,
+        &[],
+    );
+
+    // A single pack that takes all the items, so that the rules are checked against a pack that keeps
+    // growing.
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 1_000_000,
+        maximum_weight: 1_000_000.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let rules = vec![
+        PackRule::MaximumItemsWithTag {
+            tag: "FRAGILE".to_string(),
+            maximum: 1_000_000,
+        },
+        PackRule::MaximumWeightWithTag {
+            tag: "FRAGILE".to_string(),
+            maximum_weight: 1_000_000.0,
+        },
+        PackRule::MaximumHeavyItems {
+            threshold: 5.0,
+            maximum: 1_000_000,
+        },
+        PackRule::ExclusiveTag {
+            tag: "FRAGILE".to_string(),
+        },
+    ];
+    measure_growth(
+        "pack_rules",
+        |i| create_item(i.to_string(), 100.0, (i % 10 + 1) as f64, 1, &["FRAGILE"]),
+        &pack_template,
+        &rules,
+    );
+
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 1_000_000,
+        maximum_weight: 1_000_000.0,
+        sort_order: PackSortOrder::Natural,
+        length_mode: LengthMode::Sum,
+        maximum_length: Some(1e12),
+        nesting_rules: vec![NestingRule {
+            inner_item_id: "rod".to_string(),
+            outer_item_id: "tube".to_string(),
+        }],
+        ..PackTemplate::new()
+    };
+    measure_growth(
+        "nesting_rules",
+        |i| {
+            let (id, length) = if i % 2 == 0 {
+                ("tube", 100.0)
+            } else {
+                ("rod", 80.0)
+            };
+            create_item(id.to_string(), length, 1.0, 1, &[])
+        },
+        &pack_template,
+        &[],
+    );
+}
@@ -0,0 +1,63 @@
+//! Benchmarks for sorting and planning large inputs. Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use pack_planner::model::{ItemAttributes, ItemTemplate, PackSortOrder, PackTemplate, TieBreak};
+use pack_planner::planner::{plan_packs, sort_items};
+
+const NUMBER_OF_BATCHES: usize = 1_000_000;
+
+const NUMBER_OF_RUNS: u32 = 5;
+
+/// Creates a large set of small batches, so that many batches are split over several packs and every
+/// placement copies the item template.
+fn create_items() -> Vec<ItemTemplate> {
+    (0..NUMBER_OF_BATCHES)
+        .map(|i| ItemTemplate {
+            id: format!("item-{i}").into(),
+            length: (1000 + (i * 7919) % 9000) as f64,
+            weight: 1.0 + (i % 13) as f64,
+            count: 1 + (i % 25) as i32,
+            attributes: Arc::new(ItemAttributes {
+                tags: vec!["STANDARD".to_string()],
+                ..ItemAttributes::default()
+            }),
+        })
+        .collect()
+}
+
+/// Runs the function a number of times and reports the fastest run.
+fn measure<F: FnMut() -> Duration>(name: &str, mut run: F) {
+    let fastest = (0..NUMBER_OF_RUNS).map(|_| run()).min().unwrap_or_default();
+    println!("{name}: {fastest:?} (fastest of {NUMBER_OF_RUNS} runs, {NUMBER_OF_BATCHES} batches)");
+}
+
+fn main() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 500.0,
+        sort_order: PackSortOrder::LongToShort,
+        ..PackTemplate::new()
+    };
+    let items = create_items();
+
+    measure("sort_items", || {
+        let items = items.clone();
+        let start = Instant::now();
+        black_box(
+            sort_items(items, pack_template.sort_order, TieBreak::Input)
+                .expect("Failed to sort the items."),
+        );
+        start.elapsed()
+    });
+
+    let sorted = sort_items(items, pack_template.sort_order, TieBreak::Input)
+        .expect("Failed to sort the items.");
+    measure("plan_packs", || {
+        let start = Instant::now();
+        black_box(plan_packs(&sorted, &pack_template, &[]).expect("Failed to plan the packs."));
+        start.elapsed()
+    });
+}
@@ -0,0 +1,256 @@
+//! An algorithm that inspects the items and chooses the algorithm and the sort order that suit them, for the
+//! users who don't want to choose a strategy themselves.
+//!
+//! Bin completion is chosen for at most 1,000 pieces that only the maximum weight and the maximum number of
+//! pieces limit, when the weights of the pieces differ and a pack holds fewer than 20 average pieces. Those are
+//! the orders for which filling one pack at a time leaves the most room unused, and which the search still
+//! finishes quickly. All other orders are planned with the greedy algorithm. Packs with a maximum length are
+//! filled from the longest item to the shortest item when the lengths of the items differ, so that the short
+//! items fill the length that the long items leave.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::completion::{is_single_capacity, BinCompletionAlgorithm};
+use crate::config::StrategyConfig;
+use crate::errors::Error;
+use crate::model::{ItemTemplate, PackRule, PackSortOrder, PackTemplate, PlanOutcome, TieBreak};
+use crate::planner::{sort_items, GreedyAlgorithm, NoObserver, PackingAlgorithm, PlanObserver};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "adaptive_tests.rs"]
+mod tests;
+
+// The largest number of pieces that is planned with bin completion
+const MAXIMUM_BIN_COMPLETION_PIECES: i64 = 1000;
+
+// The coefficient of variation of the weights below which the pieces are considered to weigh the same
+const MINIMUM_WEIGHT_VARIATION: f64 = 0.05;
+
+// The smallest share of a pack that an average piece takes for bin completion to be worth the search
+const MINIMUM_RELATIVE_SIZE: f64 = 0.05;
+
+/// The statistics of the items on which the strategy is chosen. The weights and the lengths are those of the
+/// pieces, i.e. each batch counts as often as it has pieces.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ItemStatistics {
+    /// The number of pieces.
+    pub pieces: i64,
+
+    /// The coefficient of variation of the weights, i.e. the standard deviation divided by the mean. Zero when
+    /// all the pieces weigh the same.
+    pub weight_variation: f64,
+
+    /// The coefficient of variation of the lengths. Zero when all the pieces have the same length.
+    pub length_variation: f64,
+
+    /// The share of an empty pack that an average piece takes, by weight or by number of pieces, whichever is
+    /// larger.
+    pub relative_size: f64,
+}
+
+impl ItemStatistics {
+    /// Returns the statistics of the items in packs that match the pack template.
+    pub fn of(items: &[ItemTemplate], pack_template: &PackTemplate) -> ItemStatistics {
+        let batches: Vec<&ItemTemplate> = items.iter().filter(|t| t.count > 0).collect();
+        let pieces: i64 = batches.iter().map(|t| i64::from(t.count)).sum();
+        if pieces == 0 {
+            return ItemStatistics::default();
+        }
+
+        let variation = |value: fn(&ItemTemplate) -> f64| {
+            let mean = batches
+                .iter()
+                .map(|t| value(t) * f64::from(t.count))
+                .sum::<f64>()
+                / pieces as f64;
+            let variance = batches
+                .iter()
+                .map(|t| (value(t) - mean).powi(2) * f64::from(t.count))
+                .sum::<f64>()
+                / pieces as f64;
+            let variation = if mean > 0.0 {
+                variance.sqrt() / mean
+            } else {
+                0.0
+            };
+            (mean, variation)
+        };
+        let (mean_weight, weight_variation) = variation(|t| t.weight);
+        let (_, length_variation) = variation(|t| t.length);
+
+        let size_by_weight = if pack_template.maximum_weight > 0.0 {
+            mean_weight / pack_template.maximum_weight
+        } else {
+            0.0
+        };
+        let size_by_pieces = if pack_template.maximum_number_of_pieces > 0 {
+            1.0 / f64::from(pack_template.maximum_number_of_pieces)
+        } else {
+            0.0
+        };
+
+        ItemStatistics {
+            pieces,
+            weight_variation,
+            length_variation,
+            relative_size: size_by_weight.max(size_by_pieces),
+        }
+    }
+}
+
+/// The algorithms from which the adaptive algorithm chooses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChosenAlgorithm {
+    /// The greedy algorithm, see [`GreedyAlgorithm`].
+    Greedy,
+
+    /// The bin completion search, see [`BinCompletionAlgorithm`].
+    BinCompletion,
+}
+
+impl fmt::Display for ChosenAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChosenAlgorithm::Greedy => "greedy",
+            ChosenAlgorithm::BinCompletion => "bin-completion",
+        })
+    }
+}
+
+/// The strategy that the adaptive algorithm chose for a set of items.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrategyDecision {
+    /// The algorithm that plans the items.
+    pub algorithm: ChosenAlgorithm,
+
+    /// The order in which the items are placed.
+    pub sort_order: PackSortOrder,
+
+    /// The statistics of the items on which the strategy was chosen.
+    pub statistics: ItemStatistics,
+}
+
+impl fmt::Display for StrategyDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Chose the {} algorithm and the {} sort order for {} pieces with a weight variation of {:.2}, a length variation of {:.2} and an average size of {:.2} of a pack",
+            self.algorithm,
+            self.sort_order,
+            self.statistics.pieces,
+            self.statistics.weight_variation,
+            self.statistics.length_variation,
+            self.statistics.relative_size
+        )
+    }
+}
+
+/// A function that is called with the strategy that the adaptive algorithm chose, e.g. to log the decision.
+pub type StrategyDecisionHook = Arc<dyn Fn(&StrategyDecision) + Send + Sync>;
+
+/// Chooses the algorithm and the sort order for the items, see the [module](self) documentation. The sort order
+/// of the pack template is kept when `keep_sort_order` is set, or when the items hold `---CLOSE---` directives,
+/// which items may not be moved past.
+pub fn choose_strategy(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    keep_sort_order: bool,
+) -> StrategyDecision {
+    let statistics = ItemStatistics::of(items, pack_template);
+
+    let algorithm = if is_single_capacity(items, pack_template, rules)
+        && statistics.pieces <= MAXIMUM_BIN_COMPLETION_PIECES
+        && statistics.weight_variation >= MINIMUM_WEIGHT_VARIATION
+        && statistics.relative_size >= MINIMUM_RELATIVE_SIZE
+    {
+        ChosenAlgorithm::BinCompletion
+    } else {
+        ChosenAlgorithm::Greedy
+    };
+
+    let has_close_directives = items.iter().any(|t| t.attributes.close_pack_before);
+    let sort_order = if !keep_sort_order
+        && !has_close_directives
+        && pack_template.maximum_length.is_some()
+        && statistics.length_variation > 0.0
+    {
+        PackSortOrder::LongToShort
+    } else {
+        pack_template.sort_order
+    };
+
+    StrategyDecision {
+        algorithm,
+        sort_order,
+        statistics,
+    }
+}
+
+/// The algorithm that chooses the algorithm and the sort order from the statistics of the items, see
+/// [`choose_strategy`], and plans the items with them.
+#[derive(Clone, Default)]
+pub struct AdaptiveAlgorithm {
+    /// Indicates if the sort order of the pack template is kept, e.g. because it was chosen explicitly.
+    pub keep_sort_order: bool,
+
+    /// The hook that is called with each decision, if any.
+    pub decision_hook: Option<StrategyDecisionHook>,
+}
+
+impl PackingAlgorithm for AdaptiveAlgorithm {
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error> {
+        self.plan_with_observer(items, pack_template, rules, &mut NoObserver)
+    }
+
+    fn plan_with_observer(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+        observer: &mut dyn PlanObserver,
+    ) -> Result<PlanOutcome, Error> {
+        let decision = choose_strategy(items, pack_template, rules, self.keep_sort_order);
+        if let Some(hook) = &self.decision_hook {
+            hook(&decision);
+        }
+
+        let (items, pack_template) = if decision.sort_order == pack_template.sort_order {
+            (Cow::Borrowed(items), Cow::Borrowed(pack_template))
+        } else {
+            // The items are already in the order of their tie break, which the stable sort keeps.
+            let items = sort_items(items.to_vec(), decision.sort_order, TieBreak::Input)?;
+            let pack_template = PackTemplate {
+                sort_order: decision.sort_order,
+                ..pack_template.clone()
+            };
+            (Cow::Owned(items), Cow::Owned(pack_template))
+        };
+
+        match decision.algorithm {
+            ChosenAlgorithm::Greedy => {
+                GreedyAlgorithm.plan_with_observer(&items, &pack_template, rules, observer)
+            }
+            ChosenAlgorithm::BinCompletion => BinCompletionAlgorithm::default().plan_with_observer(
+                &items,
+                &pack_template,
+                rules,
+                observer,
+            ),
+        }
+    }
+
+    fn strategy(&self) -> StrategyConfig {
+        StrategyConfig {
+            auto: true,
+            ..StrategyConfig::default()
+        }
+    }
+}
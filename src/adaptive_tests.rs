@@ -0,0 +1,147 @@
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use super::*;
+
+fn pack_template(maximum_number_of_pieces: i32, maximum_weight: f64) -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces,
+        maximum_weight,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+fn varied_items() -> Vec<ItemTemplate> {
+    vec![
+        ItemTemplate::from_str("1,100,1,6.0").unwrap(),
+        ItemTemplate::from_str("2,100,1,5.0").unwrap(),
+        ItemTemplate::from_str("3,100,1,4.0").unwrap(),
+        ItemTemplate::from_str("4,100,1,3.0").unwrap(),
+        ItemTemplate::from_str("5,100,1,2.0").unwrap(),
+    ]
+}
+
+// ItemStatistics::of()
+
+#[test]
+fn when_computing_the_statistics_it_should_count_each_piece() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,3,2.0").unwrap(),
+        ItemTemplate::from_str("2,300,1,6.0").unwrap(),
+    ];
+
+    let statistics = ItemStatistics::of(&items, &pack_template(10, 12.0));
+    assert_eq!(statistics.pieces, 4);
+    // The mean weight is 3.0 with a standard deviation of sqrt(3).
+    assert!((statistics.weight_variation - 3f64.sqrt() / 3.0).abs() < 1e-9);
+    assert!((statistics.length_variation - 3f64.sqrt() / 3.0).abs() < 1e-9);
+    assert_eq!(statistics.relative_size, 0.25);
+}
+
+// choose_strategy()
+
+#[test]
+fn when_choosing_a_strategy_for_few_pieces_of_different_weights_it_should_choose_bin_completion() {
+    let decision = choose_strategy(&varied_items(), &pack_template(10, 10.0), &[], false);
+
+    assert_eq!(decision.algorithm, ChosenAlgorithm::BinCompletion);
+    assert_eq!(decision.sort_order, PackSortOrder::Natural);
+}
+
+#[test]
+fn when_choosing_a_strategy_for_pieces_of_the_same_weight_it_should_choose_greedy() {
+    let items = vec![ItemTemplate::from_str("1,100,40,2.0").unwrap()];
+
+    let decision = choose_strategy(&items, &pack_template(10, 10.0), &[], false);
+    assert_eq!(decision.algorithm, ChosenAlgorithm::Greedy);
+}
+
+#[test]
+fn when_choosing_a_strategy_with_rules_it_should_choose_greedy() {
+    let rules = vec![PackRule::ExclusiveTag {
+        tag: "HAZMAT".to_string(),
+    }];
+
+    let decision = choose_strategy(&varied_items(), &pack_template(10, 10.0), &rules, false);
+    assert_eq!(decision.algorithm, ChosenAlgorithm::Greedy);
+}
+
+#[test]
+fn when_choosing_a_strategy_for_packs_with_a_maximum_length_it_should_place_the_longest_items_first(
+) {
+    let items = vec![
+        ItemTemplate::from_str("1,100,2,1.0").unwrap(),
+        ItemTemplate::from_str("2,400,2,1.0").unwrap(),
+    ];
+    let pack_template = PackTemplate {
+        maximum_length: Some(1000.0),
+        ..pack_template(10, 10.0)
+    };
+
+    let decision = choose_strategy(&items, &pack_template, &[], false);
+    assert_eq!(decision.algorithm, ChosenAlgorithm::Greedy);
+    assert_eq!(decision.sort_order, PackSortOrder::LongToShort);
+
+    let decision = choose_strategy(&items, &pack_template, &[], true);
+    assert_eq!(decision.sort_order, PackSortOrder::Natural);
+}
+
+// AdaptiveAlgorithm
+
+#[test]
+fn when_planning_it_should_report_the_decision_and_plan_with_the_chosen_algorithm() {
+    let decisions = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&decisions);
+    let algorithm = AdaptiveAlgorithm {
+        keep_sort_order: false,
+        decision_hook: Some(Arc::new(move |decision| {
+            recorded.lock().unwrap().push(*decision)
+        })),
+    };
+
+    let items = varied_items();
+    let pack_template = pack_template(10, 10.0);
+    let outcome = algorithm.plan(&items, &pack_template, &[]).unwrap();
+    let expected = BinCompletionAlgorithm::default()
+        .plan(&items, &pack_template, &[])
+        .unwrap();
+    assert_eq!(outcome.packs.len(), expected.packs.len());
+
+    let decisions = decisions.lock().unwrap();
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].algorithm, ChosenAlgorithm::BinCompletion);
+    assert!(decisions[0]
+        .to_string()
+        .starts_with("Chose the bin-completion algorithm and the NATURAL sort order for 5 pieces"));
+}
+
+#[test]
+fn when_planning_with_a_new_sort_order_it_should_sort_the_items() {
+    let items = vec![
+        ItemTemplate::from_str("1,400,1,1.0").unwrap(),
+        ItemTemplate::from_str("2,700,1,1.0").unwrap(),
+        ItemTemplate::from_str("3,400,1,1.0").unwrap(),
+    ];
+    let pack_template = PackTemplate {
+        maximum_length: Some(1000.0),
+        length_mode: crate::model::LengthMode::Sum,
+        ..pack_template(10, 10.0)
+    };
+
+    // The longest item is placed first, so the two short items share the second pack.
+    let outcome = AdaptiveAlgorithm::default()
+        .plan(&items, &pack_template, &[])
+        .unwrap();
+    let packs: Vec<Vec<&str>> = outcome
+        .packs
+        .iter()
+        .map(|p| p.items.iter().map(|i| &*i.item.id).collect())
+        .collect();
+    assert_eq!(packs, vec![vec!["2"], vec!["1", "3"]]);
+}
+
+#[test]
+fn when_describing_the_strategy_it_should_enable_the_auto_strategy() {
+    assert!(AdaptiveAlgorithm::default().strategy().auto);
+}
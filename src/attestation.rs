@@ -0,0 +1,55 @@
+//! Attestations of the plans, which record the version of the pack planner, the hash of the input, the
+//! configuration and the time with which a plan was made, so that an audit can verify how the plan was
+//! produced.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::PlannerConfig;
+
+#[cfg(test)]
+#[path = "attestation_tests.rs"]
+mod tests;
+
+/// Describes how a plan was made.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Attestation {
+    /// The version of the pack planner that made the plan.
+    pub tool_version: String,
+
+    /// The SHA-256 hash, as lowercase hexadecimal string, of the input from which the plan was made.
+    pub input_sha256: String,
+
+    /// The time at which the plan was made, in seconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// The effective configuration with which the plan was made, which can be read back with
+    /// [`PlannerConfig::from_toml`] to make the plan again.
+    pub configuration: PlannerConfig,
+}
+
+impl Attestation {
+    /// Attests a plan that is made now, by this version of the pack planner, from the input with the given
+    /// hash and with the given configuration.
+    pub fn new(input_sha256: &str, configuration: PlannerConfig) -> Attestation {
+        Attestation {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            input_sha256: input_sha256.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            configuration,
+        }
+    }
+}
+
+/// Returns the SHA-256 hash, as lowercase hexadecimal string, of the input.
+pub fn input_hash(input: &[u8]) -> String {
+    Sha256::digest(input)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,21 @@
+use super::*;
+
+// input_hash()
+
+#[test]
+fn when_hashing_the_input_it_should_return_the_sha256_as_hexadecimal_string() {
+    assert_eq!(
+        input_hash(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+// Attestation::new()
+
+#[test]
+fn when_attesting_a_plan_it_should_record_the_version_of_the_pack_planner() {
+    let attestation = Attestation::new("0123", PlannerConfig::default());
+    assert_eq!(attestation.tool_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(attestation.input_sha256, "0123");
+    assert!(attestation.timestamp > 0);
+}
@@ -0,0 +1,448 @@
+//! Running many plans in one go, as described by a batch manifest.
+
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use serde::Deserialize;
+
+use crate::compression::{open_input, ExistingOutput, OutputFile};
+use crate::errors::Error;
+use crate::model::{
+    BillOfMaterials, DuplicateIdPolicy, InfeasibleItemPolicy, ItemAliases, PlanWarning,
+    SubstitutionGroups, TieBreak,
+};
+use crate::parser::{
+    parse_bill_of_materials, parse_cartons, parse_class_limits, parse_delivery_stops,
+    parse_id_patterns, parse_item_aliases, parse_items, parse_nesting_rules, parse_pack_rules,
+    parse_substitution_groups, parse_weight_bands,
+};
+use crate::remote::{JobSummary, Notification, NotificationSource};
+use crate::render::{OutputFormat, RenderOptions};
+use crate::sequence::PackSequence;
+use crate::split::SplitOutput;
+use crate::stats::StatisticsFile;
+use crate::{plan_input, Configuration};
+
+#[cfg(test)]
+#[path = "batch_tests.rs"]
+mod tests;
+
+/// Contains the settings of a job that can be provided for all jobs and overridden by a single job. The paths
+/// are relative to the directory that contains the manifest.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct JobOptions {
+    /// The path to the file containing the pack rules.
+    pub rules: Option<String>,
+
+    /// The path to the file containing the carrier weight bands.
+    pub weight_bands: Option<String>,
+
+    /// The path to the file containing the cartons from which a carton is suggested for each pack.
+    pub cartons: Option<String>,
+
+    /// The path to the file containing the additional items that should be checked against the plan.
+    pub check_fit: Option<String>,
+
+    /// The path to the file containing the pairs of items that nest inside each other.
+    pub nesting: Option<String>,
+
+    /// The path to the file containing the limits of the packs that contain items of a class.
+    pub class_limits: Option<String>,
+
+    /// The path to the file containing the IDs, or the patterns of the IDs, of the items that are excluded.
+    pub exclude: Option<String>,
+
+    /// The path to the file containing the delivery stop of each destination.
+    pub stops: Option<String>,
+
+    /// The path to the bill of materials that contains the components of the kits.
+    pub bill_of_materials: Option<String>,
+
+    /// Indicates if all the components of a kit should be placed in the same pack.
+    pub keep_kits_together: Option<bool>,
+
+    /// Indicates if the items that fill whole packs on their own should be placed in packs with a single item
+    /// ID, before the remaining items are mixed.
+    pub prefer_homogeneous: Option<bool>,
+
+    /// The path to the file that contains the members of the substitution groups.
+    pub substitutes: Option<String>,
+
+    /// The path to the file that maps the aliases of item IDs to the canonical IDs.
+    pub aliases: Option<String>,
+
+    /// Indicates if the reason for each placement should be written.
+    pub explain: Option<bool>,
+
+    /// The format in which the plan is written.
+    pub format: Option<OutputFormat>,
+
+    /// Indicates if the plan should be checked against the theoretical minimum number of packs.
+    pub quick_check: Option<bool>,
+
+    /// The path to the file that contains the last used pack number, if the packs should continue the
+    /// numbering of earlier plans.
+    pub sequence_file: Option<String>,
+
+    /// The path to the file to which the statistics of the plan are appended.
+    pub stats_file: Option<String>,
+
+    /// The directory to which each pack is also written as a separate file.
+    pub split_output: Option<String>,
+
+    /// Defines what happens with items that don't fit in an empty pack.
+    pub on_infeasible: Option<InfeasibleItemPolicy>,
+
+    /// Defines what happens with batches of items that share an ID.
+    pub duplicate_ids: Option<DuplicateIdPolicy>,
+
+    /// Defines the order of the items with the same length when the items are sorted by length.
+    pub tie_break: Option<TieBreak>,
+
+    /// The smallest number of packs over which the items are spread.
+    pub min_packs: Option<usize>,
+
+    /// Indicates if the existing output files, including the files of the split output, should be kept, in
+    /// which case the job fails when its output file already exists.
+    pub no_clobber: Option<bool>,
+}
+
+impl JobOptions {
+    /// Returns the options where every option that is not set is taken from the defaults.
+    pub fn or(&self, defaults: &JobOptions) -> JobOptions {
+        JobOptions {
+            rules: self.rules.clone().or_else(|| defaults.rules.clone()),
+            weight_bands: self
+                .weight_bands
+                .clone()
+                .or_else(|| defaults.weight_bands.clone()),
+            cartons: self.cartons.clone().or_else(|| defaults.cartons.clone()),
+            check_fit: self
+                .check_fit
+                .clone()
+                .or_else(|| defaults.check_fit.clone()),
+            nesting: self.nesting.clone().or_else(|| defaults.nesting.clone()),
+            class_limits: self
+                .class_limits
+                .clone()
+                .or_else(|| defaults.class_limits.clone()),
+            exclude: self.exclude.clone().or_else(|| defaults.exclude.clone()),
+            stops: self.stops.clone().or_else(|| defaults.stops.clone()),
+            bill_of_materials: self
+                .bill_of_materials
+                .clone()
+                .or_else(|| defaults.bill_of_materials.clone()),
+            keep_kits_together: self.keep_kits_together.or(defaults.keep_kits_together),
+            prefer_homogeneous: self.prefer_homogeneous.or(defaults.prefer_homogeneous),
+            substitutes: self
+                .substitutes
+                .clone()
+                .or_else(|| defaults.substitutes.clone()),
+            aliases: self.aliases.clone().or_else(|| defaults.aliases.clone()),
+            explain: self.explain.or(defaults.explain),
+            format: self.format.or(defaults.format),
+            quick_check: self.quick_check.or(defaults.quick_check),
+            sequence_file: self
+                .sequence_file
+                .clone()
+                .or_else(|| defaults.sequence_file.clone()),
+            stats_file: self
+                .stats_file
+                .clone()
+                .or_else(|| defaults.stats_file.clone()),
+            split_output: self
+                .split_output
+                .clone()
+                .or_else(|| defaults.split_output.clone()),
+            on_infeasible: self.on_infeasible.or(defaults.on_infeasible),
+            duplicate_ids: self.duplicate_ids.or(defaults.duplicate_ids),
+            tie_break: self.tie_break.or(defaults.tie_break),
+            min_packs: self.min_packs.or(defaults.min_packs),
+            no_clobber: self.no_clobber.or(defaults.no_clobber),
+        }
+    }
+}
+
+/// Describes a single plan in a batch.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct BatchJob {
+    /// The path to the file containing the pack information and the items.
+    pub input: String,
+
+    /// The path to the file to which the plan is written.
+    pub output: String,
+
+    /// The settings that override the defaults of the manifest.
+    #[serde(flatten)]
+    pub options: JobOptions,
+}
+
+/// Describes a set of plans that should be created in one go.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct BatchManifest {
+    /// Indicates if the jobs may run in parallel.
+    #[serde(default)]
+    pub parallel: bool,
+
+    /// The settings that apply to every job, unless the job overrides them.
+    #[serde(default)]
+    pub defaults: JobOptions,
+
+    /// The plans to create.
+    pub jobs: Vec<BatchJob>,
+
+    /// The URL to which a summary of the batch is posted when all the jobs have completed, if any.
+    #[serde(default)]
+    pub notify_url: Option<String>,
+}
+
+/// Describes the outcome of a single job in a batch.
+#[derive(Debug, PartialEq)]
+pub struct JobResult {
+    /// The path to the input file of the job, as provided in the manifest.
+    pub input: String,
+
+    /// The path to the output file of the job, as provided in the manifest.
+    pub output: String,
+
+    /// The error that stopped the job, if the job failed.
+    pub error: Option<Error>,
+
+    /// The warnings that were raised while planning, if the job succeeded.
+    pub warnings: Vec<PlanWarning>,
+}
+
+impl JobResult {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Returns the notification of the completed batch.
+pub fn batch_notification(results: &[JobResult]) -> Notification {
+    let jobs = results
+        .iter()
+        .map(|r| JobSummary {
+            input: Some(r.input.clone()),
+            output: Some(r.output.clone()),
+            succeeded: r.succeeded(),
+            error: r.error.as_ref().map(|e| e.to_string()),
+            warnings: r.warnings.iter().map(|w| w.to_string()).collect(),
+        })
+        .collect();
+    Notification::new(NotificationSource::Batch, jobs)
+}
+
+/// Reads a batch manifest in YAML format.
+pub fn parse_batch_manifest<R: Read>(reader: R) -> Result<BatchManifest, Error> {
+    serde_yaml::from_reader(reader).map_err(|e| Error::InvalidBatchManifest {
+        reason: e.to_string(),
+    })
+}
+
+fn load_configuration(options: &JobOptions, base_directory: &Path) -> Result<Configuration, Error> {
+    let rules = match &options.rules {
+        Some(path) => parse_pack_rules(&mut open_input(&base_directory.join(path))?)?,
+        None => Vec::new(),
+    };
+
+    let weight_bands = match &options.weight_bands {
+        Some(path) => parse_weight_bands(&mut open_input(&base_directory.join(path))?)?,
+        None => Vec::new(),
+    };
+
+    let cartons = match &options.cartons {
+        Some(path) => parse_cartons(&mut open_input(&base_directory.join(path))?)?,
+        None => Vec::new(),
+    };
+
+    let additional_items = match &options.check_fit {
+        Some(path) => Some(parse_items(&mut open_input(&base_directory.join(path))?)?),
+        None => None,
+    };
+
+    let nesting_rules = match &options.nesting {
+        Some(path) => parse_nesting_rules(&mut open_input(&base_directory.join(path))?)?,
+        None => Vec::new(),
+    };
+
+    let class_limits = match &options.class_limits {
+        Some(path) => parse_class_limits(&mut open_input(&base_directory.join(path))?)?,
+        None => Vec::new(),
+    };
+
+    let excluded_items = match &options.exclude {
+        Some(path) => parse_id_patterns(&mut open_input(&base_directory.join(path))?)?,
+        None => Vec::new(),
+    };
+
+    let delivery_stops = match &options.stops {
+        Some(path) => Some(parse_delivery_stops(&mut open_input(
+            &base_directory.join(path),
+        )?)?),
+        None => None,
+    };
+
+    let bill_of_materials = match &options.bill_of_materials {
+        Some(path) => parse_bill_of_materials(&mut open_input(&base_directory.join(path))?)?,
+        None => BillOfMaterials::default(),
+    };
+
+    let substitution_groups = match &options.substitutes {
+        Some(path) => parse_substitution_groups(&mut open_input(&base_directory.join(path))?)?,
+        None => SubstitutionGroups::default(),
+    };
+
+    let item_aliases = match &options.aliases {
+        Some(path) => parse_item_aliases(&mut open_input(&base_directory.join(path))?)?,
+        None => ItemAliases::default(),
+    };
+
+    Ok(Configuration {
+        rules,
+        additional_items,
+        bill_of_materials,
+        keep_kits_together: options.keep_kits_together.unwrap_or(false),
+        prefer_homogeneous: options.prefer_homogeneous.unwrap_or(false),
+        substitution_groups,
+        nesting_rules,
+        class_limits,
+        excluded_items,
+        item_aliases,
+        delivery_stops,
+        render_options: RenderOptions {
+            format: options.format.unwrap_or_default(),
+            weight_bands,
+            cartons,
+            explain: options.explain.unwrap_or(false),
+            ..RenderOptions::default()
+        },
+        quick_check: options.quick_check.unwrap_or(false),
+        pack_sequence: options
+            .sequence_file
+            .as_ref()
+            .map(|path| PackSequence::new(base_directory.join(path))),
+        statistics_file: options
+            .stats_file
+            .as_ref()
+            .map(|path| StatisticsFile::new(base_directory.join(path))),
+        split_output: options.split_output.as_ref().map(|path| SplitOutput {
+            existing_output: existing_output(options),
+            ..SplitOutput::new(base_directory.join(path))
+        }),
+        on_infeasible: options.on_infeasible.unwrap_or_default(),
+        duplicate_ids: options.duplicate_ids.unwrap_or_default(),
+        tie_break: options.tie_break.unwrap_or_default(),
+        minimum_packs: options.min_packs,
+        ..Configuration::default()
+    })
+}
+
+/// Returns what happens with the existing output files of a job.
+fn existing_output(options: &JobOptions) -> ExistingOutput {
+    if options.no_clobber.unwrap_or(false) {
+        ExistingOutput::Keep
+    } else {
+        ExistingOutput::Overwrite
+    }
+}
+
+fn run_job(
+    job: &BatchJob,
+    defaults: &JobOptions,
+    base_directory: &Path,
+) -> Result<Vec<PlanWarning>, Error> {
+    let options = job.options.or(defaults);
+    let configuration = load_configuration(&options, base_directory)?;
+
+    let mut reader = open_input(&base_directory.join(&job.input))?;
+
+    let output_path: PathBuf = base_directory.join(&job.output);
+    let to_write_error = |e: io::Error| Error::UnableToWriteOutput {
+        reason: format!("{}: {}", output_path.display(), e),
+    };
+    let mut writer = OutputFile::create_with(&output_path, existing_output(&options))?;
+
+    let warnings = plan_input(&mut reader, &mut writer, &configuration)?;
+    writer.finish().map_err(to_write_error)?;
+
+    Ok(warnings)
+}
+
+/// Runs all the jobs in the manifest and returns the outcome of each job, in the order of the manifest.
+/// A failing job doesn't stop the other jobs. When the manifest allows it the jobs are spread over as many
+/// threads as there are available processors.
+pub fn run_batch(manifest: &BatchManifest, base_directory: &Path) -> Vec<JobResult> {
+    let to_result = |job: &BatchJob| {
+        let (error, warnings) = match run_job(job, &manifest.defaults, base_directory) {
+            Ok(warnings) => (None, warnings),
+            Err(e) => (Some(e), Vec::new()),
+        };
+
+        JobResult {
+            input: job.input.clone(),
+            output: job.output.clone(),
+            error,
+            warnings,
+        }
+    };
+
+    if !manifest.parallel {
+        return manifest.jobs.iter().map(to_result).collect();
+    }
+
+    let number_of_threads = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(manifest.jobs.len());
+    let next_job = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<JobResult>>> =
+        Mutex::new((0..manifest.jobs.len()).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..number_of_threads {
+            scope.spawn(|| loop {
+                let index = next_job.fetch_add(1, Ordering::Relaxed);
+                let job = match manifest.jobs.get(index) {
+                    Some(j) => j,
+                    None => break,
+                };
+
+                let result = to_result(job);
+                results.lock().expect("A batch thread panicked.")[index] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .expect("A batch thread panicked.")
+        .into_iter()
+        .map(|r| r.expect("Every job should have a result."))
+        .collect()
+}
+
+/// Writes the outcome, and the warnings, of each job followed by the number of jobs that succeeded and failed.
+pub fn write_batch_summary<W: Write>(writer: &mut W, results: &[JobResult]) -> io::Result<()> {
+    for result in results {
+        match &result.error {
+            None => writeln!(writer, "OK: {} -> {}", result.input, result.output)?,
+            Some(e) => writeln!(writer, "FAILED: {}: {}", result.input, e)?,
+        }
+
+        for warning in &result.warnings {
+            writeln!(writer, "  Warning: {}", warning)?;
+        }
+    }
+
+    let succeeded = results.iter().filter(|r| r.succeeded()).count();
+    writeln!(
+        writer,
+        "Succeeded: {}, Failed: {}",
+        succeeded,
+        results.len() - succeeded
+    )
+}
@@ -0,0 +1,259 @@
+use std::fs;
+
+use super::*;
+
+use crate::test_support::create_test_directory;
+
+// parse_batch_manifest()
+
+#[test]
+fn when_parsing_a_valid_manifest_it_should_return_the_jobs() {
+    let input = "
+parallel: true
+notify_url: http://ops.example.com/hooks/planning
+defaults:
+  rules: rules.csv
+  explain: true
+jobs:
+  - input: a.txt
+    output: a.plan
+  - input: b.txt
+    output: b.plan
+    explain: false
+";
+    let manifest = parse_batch_manifest(input.as_bytes()).expect("Failed to parse the manifest.");
+    assert!(manifest.parallel);
+    assert_eq!(
+        manifest.notify_url,
+        Some("http://ops.example.com/hooks/planning".to_string())
+    );
+    assert_eq!(manifest.defaults.rules, Some("rules.csv".to_string()));
+    assert_eq!(manifest.jobs.len(), 2);
+    assert_eq!(manifest.jobs[0].input, "a.txt");
+    assert_eq!(manifest.jobs[1].output, "b.plan");
+    assert_eq!(manifest.jobs[1].options.explain, Some(false));
+}
+
+#[test]
+fn when_parsing_a_manifest_without_jobs_it_should_return_an_error() {
+    let result = parse_batch_manifest("parallel: true\n".as_bytes());
+    assert!(matches!(result, Err(Error::InvalidBatchManifest { .. })));
+}
+
+// JobOptions::or()
+
+#[test]
+fn when_combining_job_options_it_should_prefer_the_job_settings() {
+    let defaults = JobOptions {
+        rules: Some("rules.csv".to_string()),
+        weight_bands: Some("bands.csv".to_string()),
+        cartons: Some("cartons.csv".to_string()),
+        check_fit: None,
+        nesting: None,
+        class_limits: Some("classes.csv".to_string()),
+        exclude: Some("exclude.txt".to_string()),
+        stops: None,
+        bill_of_materials: None,
+        keep_kits_together: None,
+        prefer_homogeneous: Some(true),
+        substitutes: Some("groups.csv".to_string()),
+        aliases: Some("aliases.csv".to_string()),
+        explain: Some(true),
+        format: Some(OutputFormat::Json),
+        quick_check: Some(true),
+        sequence_file: None,
+        stats_file: Some("stats.jsonl".to_string()),
+        split_output: Some("labels".to_string()),
+        on_infeasible: Some(InfeasibleItemPolicy::Skip),
+        duplicate_ids: Some(DuplicateIdPolicy::Error),
+        tie_break: Some(TieBreak::Id),
+        min_packs: Some(4),
+        no_clobber: Some(true),
+    };
+    let options = JobOptions {
+        rules: Some("other-rules.csv".to_string()),
+        explain: Some(false),
+        ..JobOptions::default()
+    };
+
+    let combined = options.or(&defaults);
+    assert_eq!(combined.rules, Some("other-rules.csv".to_string()));
+    assert_eq!(combined.weight_bands, Some("bands.csv".to_string()));
+    assert_eq!(combined.check_fit, None);
+    assert_eq!(combined.explain, Some(false));
+    assert_eq!(combined.format, Some(OutputFormat::Json));
+    assert_eq!(combined.quick_check, Some(true));
+    assert_eq!(combined.on_infeasible, Some(InfeasibleItemPolicy::Skip));
+    assert_eq!(combined.substitutes, Some("groups.csv".to_string()));
+    assert_eq!(combined.aliases, Some("aliases.csv".to_string()));
+    assert_eq!(combined.cartons, Some("cartons.csv".to_string()));
+    assert_eq!(combined.duplicate_ids, Some(DuplicateIdPolicy::Error));
+    assert_eq!(combined.tie_break, Some(TieBreak::Id));
+    assert_eq!(combined.min_packs, Some(4));
+    assert_eq!(combined.prefer_homogeneous, Some(true));
+    assert_eq!(combined.class_limits, Some("classes.csv".to_string()));
+    assert_eq!(combined.exclude, Some("exclude.txt".to_string()));
+    assert_eq!(combined.stats_file, Some("stats.jsonl".to_string()));
+    assert_eq!(combined.split_output, Some("labels".to_string()));
+    assert_eq!(combined.no_clobber, Some(true));
+}
+
+// run_batch()
+
+#[test]
+fn when_running_a_batch_it_should_write_each_plan_and_report_the_failures() {
+    let directory = create_test_directory("run-batch");
+    fs::write(
+        directory.join("a.txt"),
+        "NATURAL,10,100.0\n1001,6200,4,9.5\n",
+    )
+    .unwrap();
+    fs::write(
+        directory.join("b.txt"),
+        "NATURAL,10,100.0\n1002,7200,2,11.0\n",
+    )
+    .unwrap();
+
+    for parallel in [false, true] {
+        let manifest = BatchManifest {
+            parallel,
+            defaults: JobOptions::default(),
+            jobs: vec![
+                BatchJob {
+                    input: "a.txt".to_string(),
+                    output: "a.plan".to_string(),
+                    options: JobOptions::default(),
+                },
+                BatchJob {
+                    input: "missing.txt".to_string(),
+                    output: "missing.plan".to_string(),
+                    options: JobOptions::default(),
+                },
+                BatchJob {
+                    input: "b.txt".to_string(),
+                    output: "b.plan".to_string(),
+                    options: JobOptions::default(),
+                },
+            ],
+            notify_url: None,
+        };
+
+        let results = run_batch(&manifest, &directory);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].succeeded());
+        assert!(matches!(
+            results[1].error,
+            Some(Error::UnableToOpenFile { .. })
+        ));
+        assert!(results[2].succeeded());
+
+        let plan = fs::read_to_string(directory.join("b.plan")).unwrap();
+        assert!(plan.starts_with("Pack Number: 1\n1002,7200.0,2,11.0\n"));
+    }
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_running_a_batch_that_keeps_the_existing_output_it_should_fail_the_jobs_with_an_output() {
+    let directory = create_test_directory("run-batch-no-clobber");
+    fs::write(
+        directory.join("a.txt"),
+        "NATURAL,10,100.0\n1001,6200,4,9.5\n",
+    )
+    .unwrap();
+    fs::write(directory.join("a.plan"), "Existing plan\n").unwrap();
+    let job = |output: &str| BatchJob {
+        input: "a.txt".to_string(),
+        output: output.to_string(),
+        options: JobOptions::default(),
+    };
+    let manifest = BatchManifest {
+        parallel: false,
+        defaults: JobOptions {
+            no_clobber: Some(true),
+            ..JobOptions::default()
+        },
+        jobs: vec![job("a.plan"), job("b.plan")],
+        notify_url: None,
+    };
+
+    let results = run_batch(&manifest, &directory);
+    assert!(matches!(
+        results[0].error,
+        Some(Error::OutputFileExists { .. })
+    ));
+    assert!(results[1].succeeded());
+    assert_eq!(
+        fs::read_to_string(directory.join("a.plan")).unwrap(),
+        "Existing plan\n"
+    );
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+// write_batch_summary()
+
+#[test]
+fn when_writing_the_batch_summary_it_should_count_the_failures() {
+    let results = vec![
+        JobResult {
+            input: "a.txt".to_string(),
+            output: "a.plan".to_string(),
+            error: None,
+            warnings: vec![PlanWarning::EmptyPack { pack_number: 2 }],
+        },
+        JobResult {
+            input: "b.txt".to_string(),
+            output: "b.plan".to_string(),
+            error: Some(Error::MissingPackInformation),
+            warnings: Vec::new(),
+        },
+    ];
+
+    let mut output = Vec::new();
+    write_batch_summary(&mut output, &results).expect("Failed to write the summary.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(
+        "OK: a.txt -> a.plan\n  Warning: Pack 2 doesn't contain any items.\nFAILED: b.txt: "
+    ));
+    assert!(output.ends_with("Succeeded: 1, Failed: 1\n"));
+}
+
+// batch_notification()
+
+#[test]
+fn when_creating_the_batch_notification_it_should_summarize_each_job() {
+    let results = vec![
+        JobResult {
+            input: "a.txt".to_string(),
+            output: "a.plan".to_string(),
+            error: None,
+            warnings: vec![PlanWarning::EmptyPack { pack_number: 2 }],
+        },
+        JobResult {
+            input: "b.txt".to_string(),
+            output: "b.plan".to_string(),
+            error: Some(Error::MissingPackInformation),
+            warnings: Vec::new(),
+        },
+    ];
+
+    let notification = batch_notification(&results);
+    assert_eq!(notification.source, NotificationSource::Batch);
+    assert_eq!((notification.succeeded, notification.failed), (1, 1));
+    assert_eq!(
+        notification.jobs[0],
+        JobSummary {
+            input: Some("a.txt".to_string()),
+            output: Some("a.plan".to_string()),
+            succeeded: true,
+            error: None,
+            warnings: vec!["Pack 2 doesn't contain any items.".to_string()],
+        }
+    );
+    assert_eq!(
+        notification.jobs[1].error,
+        Some(Error::MissingPackInformation.to_string())
+    );
+}
@@ -0,0 +1,338 @@
+//! Checkpoints of a plan that is being made, kept in a file so that a long running plan that is interrupted
+//! can resume from the last checkpoint instead of starting over.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::Error;
+use crate::model::{
+    ItemTemplate, Pack, PackRule, PackTemplate, PackedItem, PlacementLimit, PlanOutcome,
+    PlanWarning,
+};
+use crate::planner::{offset_pack_numbers, plan_packs_with_observer, PlanObserver};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "checkpoint_tests.rs"]
+mod tests;
+
+/// Describes where, and how often, the checkpoints of a plan are written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanCheckpoint {
+    /// The path of the checkpoint file.
+    pub path: PathBuf,
+
+    /// The minimum time between two checkpoints. A checkpoint is only written when a pack is closed.
+    pub interval: Duration,
+
+    /// Indicates if the plan resumes from the checkpoint file, if the file exists. Otherwise the plan starts
+    /// from the beginning and replaces the file.
+    pub resume: bool,
+}
+
+/// Describes the number of items of a single batch that were placed in a closed pack.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CheckpointPlacement {
+    /// The index of the batch in the items that are planned.
+    pub batch: usize,
+
+    /// The number of items that were placed.
+    pub count: i32,
+
+    /// The limit that determined the number of items that were placed.
+    pub limit: PlacementLimit,
+}
+
+/// Contains the packs that were closed when the checkpoint was written.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Checkpoint {
+    /// The hash of the pack template, the rules and the items, so that a plan only resumes from a checkpoint
+    /// of the same plan.
+    pub plan_hash: String,
+
+    /// The placements of each closed pack, in the order of the packs.
+    pub packs: Vec<Vec<CheckpointPlacement>>,
+}
+
+impl PlanCheckpoint {
+    /// Creates checkpoints in the file at the given path, at most every 10 seconds.
+    pub fn new(path: impl Into<PathBuf>, resume: bool) -> PlanCheckpoint {
+        PlanCheckpoint {
+            path: path.into(),
+            interval: Duration::from_secs(10),
+            resume,
+        }
+    }
+
+    /// Reads the checkpoint, or returns `None` if the checkpoint file doesn't exist.
+    pub fn read(&self) -> Result<Option<Checkpoint>, Error> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(Error::UnableToOpenFile {
+                    path: display_path(&self.path),
+                    reason: e.to_string(),
+                })
+            }
+        };
+
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| Error::InvalidCheckpoint {
+                path: display_path(&self.path),
+                reason: e.to_string(),
+            })
+    }
+
+    /// Writes the checkpoint. The checkpoint is written to a temporary file first, which then replaces the
+    /// checkpoint file, so an interruption never leaves a partial checkpoint behind.
+    pub fn write(&self, checkpoint: &Checkpoint) -> Result<(), Error> {
+        let to_write_error = |e: std::io::Error| Error::UnableToWriteOutput {
+            reason: e.to_string(),
+        };
+
+        let mut temporary_path = self.path.clone().into_os_string();
+        temporary_path.push(".tmp");
+        let content =
+            serde_json::to_string(checkpoint).map_err(|e| Error::UnableToWriteOutput {
+                reason: e.to_string(),
+            })?;
+        // The temporary file is flushed to the disk before the rename, so that a crash can't leave a checkpoint
+        // that was renamed into place but whose content was never written.
+        let mut file = fs::File::create(&temporary_path).map_err(to_write_error)?;
+        file.write_all(content.as_bytes())
+            .and_then(|_| file.sync_all())
+            .map_err(to_write_error)?;
+        drop(file);
+        fs::rename(&temporary_path, &self.path).map_err(to_write_error)
+    }
+
+    /// Removes the checkpoint file, if it exists.
+    pub fn remove(&self) -> Result<(), Error> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(Error::UnableToWriteOutput {
+                reason: e.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the hash of everything that determines the plan, as lowercase hexadecimal string.
+fn plan_hash(items: &[ItemTemplate], pack_template: &PackTemplate, rules: &[PackRule]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{pack_template:?}\n{rules:?}\n"));
+    for item in items {
+        hasher.update(format!("{item:?}\n"));
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Records the placements of the planner, by batch, and writes a checkpoint when a pack is closed and the
+/// interval since the last checkpoint has passed. Other observers are notified as well.
+struct CheckpointObserver<'a> {
+    checkpoint: Checkpoint,
+    plan_checkpoint: &'a PlanCheckpoint,
+    items: &'a [ItemTemplate],
+    next_batch: usize,
+    placed_from_batch: i32,
+    open_pack: Vec<CheckpointPlacement>,
+    last_written: Instant,
+    error: Option<Error>,
+    observer: &'a mut dyn PlanObserver,
+}
+
+impl PlanObserver for CheckpointObserver<'_> {
+    fn on_item_placed(&mut self, pack: &Pack, placement: &PackedItem) {
+        // The items are placed in the order of the batches, so each placement belongs to the first batch that
+        // still has items left.
+        while self.items[self.next_batch].count <= 0 {
+            self.next_batch += 1;
+        }
+
+        self.open_pack.push(CheckpointPlacement {
+            batch: self.next_batch,
+            count: placement.item.count,
+            limit: placement.limit,
+        });
+        self.placed_from_batch += placement.item.count;
+        if self.placed_from_batch >= self.items[self.next_batch].count {
+            self.next_batch += 1;
+            self.placed_from_batch = 0;
+        }
+
+        self.observer.on_item_placed(pack, placement);
+    }
+
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        self.checkpoint
+            .packs
+            .push(std::mem::take(&mut self.open_pack));
+        if self.error.is_none() && self.last_written.elapsed() >= self.plan_checkpoint.interval {
+            self.error = self.plan_checkpoint.write(&self.checkpoint).err();
+            self.last_written = Instant::now();
+        }
+
+        self.observer.on_pack_closed(pack);
+    }
+}
+
+/// Rebuilds the closed packs of the checkpoint. Returns the packs together with the items that were not placed
+/// in these packs yet, or `None` if the placements don't match the items.
+fn restore_packs(
+    checkpoint: &Checkpoint,
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+) -> Option<(Vec<Pack>, Vec<ItemTemplate>)> {
+    let mut items_left: Vec<i32> = items.iter().map(|t| t.count.max(0)).collect();
+    let mut packs = Vec::with_capacity(checkpoint.packs.len());
+    for placements in &checkpoint.packs {
+        let mut pack = Pack::new(packs.len() + 1);
+        for placement in placements {
+            let items_left_in_batch = items_left
+                .get_mut(placement.batch)
+                .filter(|left| placement.count > 0 && **left >= placement.count)?;
+            pack.add(
+                pack_template,
+                &items[placement.batch],
+                placement.count,
+                *items_left_in_batch,
+                placement.limit,
+            );
+            *items_left_in_batch -= placement.count;
+        }
+
+        packs.push(pack);
+    }
+
+    // The closed packs hold the first items, so the plan continues with the first batch that has items left.
+    let first_batch_left = items_left
+        .iter()
+        .position(|left| *left > 0)
+        .unwrap_or(items.len());
+    let remaining_items = items[first_batch_left..]
+        .iter()
+        .zip(&items_left[first_batch_left..])
+        .map(|(t, left)| ItemTemplate {
+            count: if t.count <= 0 { t.count } else { *left },
+            ..t.clone()
+        })
+        .collect();
+
+    Some((packs, remaining_items))
+}
+
+/// Places the items in packs in the same way as [`plan_packs_with_observer`], while writing checkpoints of
+/// the closed packs. When the plan resumes from a checkpoint, the closed packs of the checkpoint are rebuilt
+/// and only the remaining items are planned. The checkpoint file is removed once the plan is complete.
+///
+/// Returns an error if the checkpoint belongs to a different plan, or if an item is pinned to a pack or is a
+/// bulk item, which can't be checkpointed.
+pub fn plan_packs_with_checkpoints(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    plan_checkpoint: &PlanCheckpoint,
+    observer: &mut dyn PlanObserver,
+) -> Result<PlanOutcome, Error> {
+    if let Some(item) = items
+        .iter()
+        .find(|t| t.attributes.bulk || t.attributes.pinned_pack.is_some())
+    {
+        return Err(Error::UnsupportedCheckpointItem {
+            item_id: item.id.to_string(),
+        });
+    }
+
+    let plan_hash = plan_hash(items, pack_template, rules);
+    let checkpoint = match plan_checkpoint.read()? {
+        Some(checkpoint) if plan_checkpoint.resume => checkpoint,
+        _ => Checkpoint {
+            plan_hash: plan_hash.clone(),
+            packs: Vec::new(),
+        },
+    };
+    if checkpoint.plan_hash != plan_hash {
+        return Err(Error::CheckpointDoesNotMatchPlan {
+            path: display_path(&plan_checkpoint.path),
+        });
+    }
+
+    let (restored_packs, remaining_items) = restore_packs(&checkpoint, items, pack_template)
+        .ok_or_else(|| Error::CheckpointDoesNotMatchPlan {
+            path: display_path(&plan_checkpoint.path),
+        })?;
+
+    // The placements of the remaining items are recorded by the index of the batch in all the items.
+    let skipped_items = items.len() - remaining_items.len();
+    let placed_from_batch = remaining_items
+        .first()
+        .map_or(0, |t| items[skipped_items].count - t.count);
+    let mut checkpoint_observer = CheckpointObserver {
+        checkpoint,
+        plan_checkpoint,
+        items,
+        next_batch: skipped_items,
+        placed_from_batch,
+        open_pack: Vec::new(),
+        last_written: Instant::now(),
+        error: None,
+        observer,
+    };
+    let outcome = plan_packs_with_observer(
+        &remaining_items,
+        pack_template,
+        rules,
+        &mut checkpoint_observer,
+    )?;
+    if let Some(error) = checkpoint_observer.error {
+        return Err(error);
+    }
+
+    let outcome = offset_pack_numbers(outcome, restored_packs.len());
+    let mut warnings: Vec<PlanWarning> = items[..skipped_items]
+        .iter()
+        .filter(|t| t.count <= 0)
+        .map(|t| PlanWarning::ItemSkipped {
+            item_id: t.id.to_string(),
+            count: t.count,
+        })
+        .collect();
+    if let Some(maximum_value) = pack_template.maximum_value {
+        warnings.extend(
+            restored_packs
+                .iter()
+                .filter(|p| p.exceeds_maximum_value)
+                .map(|p| PlanWarning::MaximumValueExceeded {
+                    pack_number: p.number,
+                    value: p.value,
+                    maximum_value,
+                }),
+        );
+    }
+    warnings.extend(outcome.warnings);
+
+    plan_checkpoint.remove()?;
+
+    let mut packs = restored_packs;
+    packs.extend(outcome.packs);
+    Ok(PlanOutcome {
+        packs,
+        warnings,
+        optimality: None,
+    })
+}
+
+fn display_path(path: &Path) -> String {
+    path.display().to_string()
+}
@@ -0,0 +1,201 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::*;
+use crate::model::PackSortOrder;
+use crate::planner::plan_packs;
+
+/// Returns the path of a checkpoint file, unique to the test, that doesn't exist yet.
+fn create_checkpoint_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-checkpoint-{}-{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn create_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 5,
+        maximum_weight: 20.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+fn create_items() -> Vec<ItemTemplate> {
+    vec![
+        ItemTemplate::from_str("1,100,7,2.0").unwrap(),
+        ItemTemplate::from_str("2,50,0,1.0").unwrap(),
+        ItemTemplate::from_str("3,80,9,3.0").unwrap(),
+        ItemTemplate::from_str("4,60,4,1.5").unwrap(),
+    ]
+}
+
+/// Returns the number and the placed items of each pack.
+fn summarize(outcome: &PlanOutcome) -> Vec<(usize, Vec<(String, i32)>)> {
+    outcome
+        .packs
+        .iter()
+        .map(|p| {
+            (
+                p.number,
+                p.items
+                    .iter()
+                    .map(|i| (i.item.id.to_string(), i.item.count))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+/// Copies the checkpoint file when the given number of packs is closed, as if the plan was interrupted.
+struct Interruption<'a> {
+    path: &'a Path,
+    closed_packs: usize,
+    interrupt_after: usize,
+    checkpoint: Option<String>,
+}
+
+impl PlanObserver for Interruption<'_> {
+    fn on_pack_closed(&mut self, _pack: &Pack) {
+        self.closed_packs += 1;
+        if self.closed_packs == self.interrupt_after {
+            self.checkpoint = fs::read_to_string(self.path).ok();
+        }
+    }
+}
+
+// plan_packs_with_checkpoints()
+
+#[test]
+fn when_planning_with_checkpoints_it_should_plan_like_the_greedy_algorithm() {
+    let path = create_checkpoint_path("plan");
+    let items = create_items();
+    let pack_template = create_pack_template();
+
+    let expected = plan_packs(&items, &pack_template, &[]).unwrap();
+    let outcome = plan_packs_with_checkpoints(
+        &items,
+        &pack_template,
+        &[],
+        &PlanCheckpoint::new(&path, false),
+        &mut crate::planner::NoObserver,
+    )
+    .unwrap();
+
+    assert_eq!(summarize(&outcome), summarize(&expected));
+    assert_eq!(outcome.warnings, expected.warnings);
+    assert!(!path.exists());
+}
+
+#[test]
+fn when_resuming_from_a_checkpoint_it_should_complete_the_same_plan() {
+    let path = create_checkpoint_path("resume");
+    let items = create_items();
+    let pack_template = create_pack_template();
+    let expected = plan_packs(&items, &pack_template, &[]).unwrap();
+
+    for interrupt_after in 1..expected.packs.len() {
+        let plan_checkpoint = PlanCheckpoint {
+            interval: Duration::ZERO,
+            ..PlanCheckpoint::new(&path, false)
+        };
+        let mut interruption = Interruption {
+            path: &path,
+            closed_packs: 0,
+            interrupt_after,
+            checkpoint: None,
+        };
+        plan_packs_with_checkpoints(
+            &items,
+            &pack_template,
+            &[],
+            &plan_checkpoint,
+            &mut interruption,
+        )
+        .unwrap();
+        fs::write(&path, interruption.checkpoint.unwrap()).unwrap();
+
+        let outcome = plan_packs_with_checkpoints(
+            &items,
+            &pack_template,
+            &[],
+            &PlanCheckpoint::new(&path, true),
+            &mut crate::planner::NoObserver,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summarize(&outcome),
+            summarize(&expected),
+            "{interrupt_after}"
+        );
+        assert_eq!(outcome.warnings, expected.warnings, "{interrupt_after}");
+        assert!(!path.exists());
+    }
+}
+
+#[test]
+fn when_resuming_from_a_checkpoint_of_other_items_it_should_return_an_error() {
+    let path = create_checkpoint_path("mismatch");
+    let plan_checkpoint = PlanCheckpoint::new(&path, true);
+    plan_checkpoint
+        .write(&Checkpoint {
+            plan_hash: "0123".to_string(),
+            packs: Vec::new(),
+        })
+        .unwrap();
+
+    let result = plan_packs_with_checkpoints(
+        &create_items(),
+        &create_pack_template(),
+        &[],
+        &plan_checkpoint,
+        &mut crate::planner::NoObserver,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::CheckpointDoesNotMatchPlan { .. })
+    ));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn when_resuming_from_an_invalid_checkpoint_it_should_return_an_error() {
+    let path = create_checkpoint_path("invalid");
+    fs::write(&path, "not a checkpoint").unwrap();
+
+    let result = plan_packs_with_checkpoints(
+        &create_items(),
+        &create_pack_template(),
+        &[],
+        &PlanCheckpoint::new(&path, true),
+        &mut crate::planner::NoObserver,
+    );
+    assert!(matches!(result, Err(Error::InvalidCheckpoint { .. })));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn when_planning_a_pinned_item_with_checkpoints_it_should_return_an_error() {
+    let path = create_checkpoint_path("pinned");
+    let mut items = create_items();
+    Arc::make_mut(&mut items[2].attributes).pinned_pack = Some(1);
+
+    let result = plan_packs_with_checkpoints(
+        &items,
+        &create_pack_template(),
+        &[],
+        &PlanCheckpoint::new(&path, false),
+        &mut crate::planner::NoObserver,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedCheckpointItem { item_id }) if item_id == "3"
+    ));
+}
@@ -0,0 +1,423 @@
+//! A packing algorithm for orders in which only the weight and the number of pieces limit the packs, which
+//! searches for the smallest number of packs with bin completion (Korf, 2002) instead of filling one pack at a
+//! time.
+//!
+//! The search builds the packs one at a time. Each pack starts with the heaviest item that is left, and is
+//! completed with each set of the remaining items that fills the pack so far that no other remaining item
+//! fits. A branch is abandoned as soon as the packs so far, together with a lower bound on the packs for the
+//! remaining items, can't beat the best plan found so far. The first-fit decreasing plan is the starting
+//! point, so the search only has to improve on it.
+
+use crate::config::StrategyConfig;
+use crate::errors::Error;
+use crate::model::{
+    ItemTemplate, Pack, PackRule, PackTemplate, PlacementLimit, PlanOutcome, PlanWarning,
+};
+use crate::planner::{plan_packs, PackingAlgorithm};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "completion_tests.rs"]
+mod tests;
+
+// The number of nodes that the search visits when the algorithm doesn't set a limit
+const DEFAULT_NODE_LIMIT: u64 = 100_000;
+
+// The number of completions that are tried for a single pack, the fullest first
+const MAXIMUM_COMPLETIONS_PER_PACK: usize = 256;
+
+/// The algorithm that searches for the smallest number of packs with bin completion. See
+/// [`plan_with_bin_completion`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BinCompletionAlgorithm {
+    /// The number of nodes after which the search stops and the best plan found so far is used. Defaults to
+    /// 100,000 nodes when not set.
+    pub node_limit: Option<u64>,
+}
+
+impl PackingAlgorithm for BinCompletionAlgorithm {
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error> {
+        plan_with_bin_completion(
+            items,
+            pack_template,
+            rules,
+            self.node_limit.unwrap_or(DEFAULT_NODE_LIMIT),
+        )
+    }
+
+    fn strategy(&self) -> StrategyConfig {
+        StrategyConfig {
+            bin_completion: true,
+            ..StrategyConfig::default()
+        }
+    }
+}
+
+/// Indicates if only the maximum weight and the maximum number of pieces limit the packs of the items, i.e.
+/// there are no rules, no other limits on the pack, and no items that need a specific pack or that can't
+/// share a pack with the other items.
+pub fn is_single_capacity(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> bool {
+    let has_pack_limits = pack_template.maximum_length.is_some()
+        || pack_template.hard_maximum_weight.is_some()
+        || pack_template.maximum_value.is_some()
+        || pack_template.sheet.is_some()
+        || !pack_template.class_limits.is_empty();
+    if !rules.is_empty() || has_pack_limits || pack_template.maximum_number_of_pieces < 1 {
+        return false;
+    }
+
+    let first = match items.first() {
+        Some(first) => &first.attributes,
+        None => return true,
+    };
+    items.iter().all(|t| {
+        let attributes = &t.attributes;
+        !attributes.bulk
+            && attributes.pinned_pack.is_none()
+            && !attributes.close_pack_before
+            && attributes.kit_instance.is_none()
+            && attributes.multiple.is_none()
+            && attributes.storage_class == first.storage_class
+            && attributes.destination == first.destination
+            && t.weight >= 0.0
+            && t.weight <= pack_template.maximum_weight
+    })
+}
+
+/// The items of a single weight, which the search doesn't tell apart.
+#[derive(Clone, Copy, Debug)]
+struct WeightGroup {
+    weight: f64,
+    count: i32,
+}
+
+/// A pack of the search, as the number of items of each weight group.
+type Bin = Vec<i32>;
+
+/// Groups the items by weight, from the heaviest to the lightest weight.
+fn group_by_weight(items: &[ItemTemplate]) -> Vec<WeightGroup> {
+    let mut groups: Vec<WeightGroup> = Vec::new();
+    for template in items.iter().filter(|t| t.count > 0) {
+        match groups.iter_mut().find(|g| g.weight == template.weight) {
+            Some(group) => group.count += template.count,
+            None => groups.push(WeightGroup {
+                weight: template.weight,
+                count: template.count,
+            }),
+        }
+    }
+
+    groups.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    groups
+}
+
+/// Returns the number of items of the given weight that fit in the remaining capacity, in the same way as
+/// the greedy algorithm counts them.
+fn items_that_fit(weight: f64, remaining_weight: f64, remaining_pieces: i32) -> i32 {
+    if weight <= 0.0 {
+        return remaining_pieces;
+    }
+
+    ((remaining_weight / weight).floor() as i32).min(remaining_pieces)
+}
+
+struct Search<'a> {
+    weights: Vec<f64>,
+    pack_template: &'a PackTemplate,
+    node_limit: u64,
+    nodes: u64,
+    lower_bound: usize,
+    best: Vec<Bin>,
+    current: Vec<Bin>,
+}
+
+impl Search<'_> {
+    /// Returns a lower bound on the number of packs for the remaining items: the larger of the bound by the
+    /// number of pieces and the bound L2 of Martello and Toth by the weight.
+    fn lower_bound(&self, remaining: &[i32]) -> usize {
+        let capacity = self.pack_template.maximum_weight;
+        let pieces: i32 = remaining.iter().sum();
+        let by_pieces =
+            (pieces as usize).div_ceil(self.pack_template.maximum_number_of_pieces as usize);
+        if capacity <= 0.0 {
+            return by_pieces;
+        }
+
+        let half = capacity / 2.0;
+        let thresholds = std::iter::once(0.0).chain(
+            self.weights
+                .iter()
+                .zip(remaining)
+                .filter(|&(&w, &n)| n > 0 && w <= half)
+                .map(|(&w, _)| w),
+        );
+        let mut by_weight = 0;
+        for threshold in thresholds {
+            let mut large = 0;
+            let mut medium = 0;
+            let mut medium_weight = 0.0;
+            let mut small_weight = 0.0;
+            for (&weight, &count) in self.weights.iter().zip(remaining) {
+                if weight > capacity - threshold {
+                    large += count as usize;
+                } else if weight > half {
+                    medium += count as usize;
+                    medium_weight += weight * count as f64;
+                } else if weight >= threshold {
+                    small_weight += weight * count as f64;
+                }
+            }
+
+            let free_weight_in_medium = medium as f64 * capacity - medium_weight;
+            let extra = ((small_weight - free_weight_in_medium) / capacity - 1e-9)
+                .ceil()
+                .max(0.0) as usize;
+            by_weight = by_weight.max(large + medium + extra);
+        }
+
+        by_pieces.max(by_weight)
+    }
+
+    /// Returns the completions of a pack that holds a single item of the group `first`, i.e. the sets of
+    /// remaining items that fit with it and after which no other remaining item fits, the fullest first.
+    fn completions(&self, remaining: &[i32], first: usize) -> Vec<Bin> {
+        let mut bin = vec![0; self.weights.len()];
+        bin[first] = 1;
+        let remaining_weight = self.pack_template.maximum_weight - self.weights[first];
+        let remaining_pieces = self.pack_template.maximum_number_of_pieces - 1;
+
+        let mut completions = Vec::new();
+        self.extend(
+            remaining,
+            first,
+            remaining_weight,
+            remaining_pieces,
+            &mut bin,
+            &mut completions,
+        );
+
+        let weight_of = |bin: &Bin| -> f64 {
+            bin.iter()
+                .zip(&self.weights)
+                .map(|(&n, &w)| n as f64 * w)
+                .sum()
+        };
+        completions.sort_by(|a, b| weight_of(b).total_cmp(&weight_of(a)));
+        completions
+    }
+
+    fn extend(
+        &self,
+        remaining: &[i32],
+        group: usize,
+        remaining_weight: f64,
+        remaining_pieces: i32,
+        bin: &mut Bin,
+        completions: &mut Vec<Bin>,
+    ) {
+        if completions.len() >= MAXIMUM_COMPLETIONS_PER_PACK {
+            return;
+        }
+
+        if group == self.weights.len() {
+            // Only the completions to which no other item can be added are tried, because moving such an
+            // item from a later pack never needs more packs.
+            let is_maximal = (0..self.weights.len()).all(|g| {
+                remaining[g] == bin[g]
+                    || items_that_fit(self.weights[g], remaining_weight, remaining_pieces) == 0
+            });
+            if is_maximal {
+                completions.push(bin.clone());
+            }
+            return;
+        }
+
+        let available = remaining[group] - bin[group];
+        let maximum = items_that_fit(self.weights[group], remaining_weight, remaining_pieces)
+            .min(available)
+            .max(0);
+        for count in (0..=maximum).rev() {
+            bin[group] += count;
+            self.extend(
+                remaining,
+                group + 1,
+                remaining_weight - count as f64 * self.weights[group],
+                remaining_pieces - count,
+                bin,
+                completions,
+            );
+            bin[group] -= count;
+        }
+    }
+
+    /// Searches for a better plan for the remaining items, given the packs of the current plan.
+    fn search(&mut self, remaining: &mut [i32]) {
+        if self.nodes >= self.node_limit || self.best.len() <= self.lower_bound {
+            return;
+        }
+        self.nodes += 1;
+
+        let first = match remaining.iter().position(|&n| n > 0) {
+            Some(first) => first,
+            None => {
+                if self.current.len() < self.best.len() {
+                    self.best = self.current.clone();
+                }
+                return;
+            }
+        };
+        if self.current.len() + self.lower_bound(remaining) >= self.best.len() {
+            return;
+        }
+
+        for bin in self.completions(remaining, first) {
+            for (count, placed) in remaining.iter_mut().zip(&bin) {
+                *count -= placed;
+            }
+            self.current.push(bin);
+            self.search(remaining);
+            let bin = self.current.pop().unwrap_or_default();
+            for (count, placed) in remaining.iter_mut().zip(&bin) {
+                *count += placed;
+            }
+
+            if self.nodes >= self.node_limit || self.best.len() <= self.lower_bound {
+                return;
+            }
+        }
+    }
+}
+
+/// Places the items of each weight group in the first pack in which they fit, from the heaviest to the
+/// lightest group.
+fn first_fit_decreasing(groups: &[WeightGroup], pack_template: &PackTemplate) -> Vec<Bin> {
+    let mut bins: Vec<Bin> = Vec::new();
+    let mut loads: Vec<(f64, i32)> = Vec::new();
+    for (index, group) in groups.iter().enumerate() {
+        let mut count = group.count;
+        for (bin, (weight, pieces)) in bins.iter_mut().zip(loads.iter_mut()) {
+            let remaining_weight = pack_template.maximum_weight - *weight;
+            let remaining_pieces = pack_template.maximum_number_of_pieces - *pieces;
+            let placed =
+                items_that_fit(group.weight, remaining_weight, remaining_pieces).min(count);
+            if placed > 0 {
+                bin[index] += placed;
+                *weight += placed as f64 * group.weight;
+                *pieces += placed;
+                count -= placed;
+            }
+        }
+
+        while count > 0 {
+            let placed = items_that_fit(
+                group.weight,
+                pack_template.maximum_weight,
+                pack_template.maximum_number_of_pieces,
+            )
+            .min(count)
+            .max(1);
+            let mut bin = vec![0; groups.len()];
+            bin[index] = placed;
+            bins.push(bin);
+            loads.push((placed as f64 * group.weight, placed));
+            count -= placed;
+        }
+    }
+
+    bins
+}
+
+/// Creates the packs of the plan from the packs of the search, by taking the items of each weight group from
+/// the batches of that weight in the order in which the batches are provided.
+fn create_packs(
+    items: &[ItemTemplate],
+    groups: &[WeightGroup],
+    bins: &[Bin],
+    pack_template: &PackTemplate,
+) -> Vec<Pack> {
+    let mut items_left: Vec<i32> = items.iter().map(|t| t.count.max(0)).collect();
+    let mut packs = Vec::with_capacity(bins.len());
+    for (index, bin) in bins.iter().enumerate() {
+        let mut pack = Pack::new(index + 1);
+        for (group, &count) in groups.iter().zip(bin) {
+            let mut count = count;
+            for (template, left) in items.iter().zip(items_left.iter_mut()) {
+                if count == 0 {
+                    break;
+                }
+                if *left == 0 || template.weight != group.weight {
+                    continue;
+                }
+
+                let placed = count.min(*left);
+                let limit = if placed == *left {
+                    PlacementLimit::None
+                } else if pack.item_count + placed == pack_template.maximum_number_of_pieces {
+                    PlacementLimit::PieceCount
+                } else {
+                    PlacementLimit::Weight
+                };
+                pack.add(pack_template, template, placed, *left, limit);
+                *left -= placed;
+                count -= placed;
+            }
+        }
+
+        packs.push(pack);
+    }
+
+    packs
+}
+
+/// Places the items in the smallest number of packs that the search finds, when only the maximum weight and
+/// the maximum number of pieces limit the packs. Otherwise the items are planned with the greedy algorithm,
+/// see [`plan_packs`].
+///
+/// The search stops after visiting the given number of nodes, or as soon as a plan reaches the lower bound,
+/// and uses the best plan found so far, which is never worse than the first-fit decreasing plan. The packs
+/// hold the heaviest items first, so the sort order of the pack template isn't kept.
+pub fn plan_with_bin_completion(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    node_limit: u64,
+) -> Result<PlanOutcome, Error> {
+    if !is_single_capacity(items, pack_template, rules) {
+        return plan_packs(items, pack_template, rules);
+    }
+
+    let groups = group_by_weight(items);
+    let mut search = Search {
+        weights: groups.iter().map(|g| g.weight).collect(),
+        pack_template,
+        node_limit,
+        nodes: 0,
+        lower_bound: 0,
+        best: first_fit_decreasing(&groups, pack_template),
+        current: Vec::new(),
+    };
+    let mut remaining: Vec<i32> = groups.iter().map(|g| g.count).collect();
+    search.lower_bound = search.lower_bound(&remaining);
+    search.search(&mut remaining);
+
+    Ok(PlanOutcome {
+        packs: create_packs(items, &groups, &search.best, pack_template),
+        warnings: items
+            .iter()
+            .filter(|t| t.count <= 0)
+            .map(|t| PlanWarning::ItemSkipped {
+                item_id: t.id.to_string(),
+                count: t.count,
+            })
+            .collect(),
+        optimality: None,
+    })
+}
@@ -0,0 +1,144 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::*;
+use crate::model::{PackSortOrder, StorageClass};
+
+fn pack_template(maximum_number_of_pieces: i32, maximum_weight: f64) -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces,
+        maximum_weight,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+// is_single_capacity()
+
+#[test]
+fn when_only_the_weight_and_the_pieces_limit_the_packs_it_should_be_single_capacity() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,3,5.0").unwrap(),
+        ItemTemplate::from_str("2,200,3,4.0").unwrap(),
+    ];
+
+    assert!(is_single_capacity(&items, &pack_template(10, 10.0), &[]));
+}
+
+#[test]
+fn when_other_constraints_limit_the_packs_it_should_not_be_single_capacity() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,3,5.0").unwrap(),
+        ItemTemplate::from_str("2,200,3,4.0").unwrap(),
+    ];
+    let mut chilled_items = items.clone();
+    Arc::make_mut(&mut chilled_items[1].attributes).storage_class = StorageClass::Chilled;
+    let limited_length = PackTemplate {
+        maximum_length: Some(1000.0),
+        ..pack_template(10, 10.0)
+    };
+    let rules = vec![PackRule::MaximumWeightWithTag {
+        tag: "GLASS".to_string(),
+        maximum_weight: 5.0,
+    }];
+
+    assert!(!is_single_capacity(
+        &chilled_items,
+        &pack_template(10, 10.0),
+        &[]
+    ));
+    assert!(!is_single_capacity(&items, &limited_length, &[]));
+    assert!(!is_single_capacity(
+        &items,
+        &pack_template(10, 10.0),
+        &rules
+    ));
+    assert!(!is_single_capacity(&items, &pack_template(10, 4.5), &[]));
+}
+
+// plan_with_bin_completion()
+
+#[test]
+fn when_first_fit_decreasing_is_not_optimal_it_should_find_the_smallest_number_of_packs() {
+    // First-fit decreasing needs 3 packs: 5 + 4, 4 + 3 + 2 and 2.
+    let items = vec![
+        ItemTemplate::from_str("1,100,1,5.0").unwrap(),
+        ItemTemplate::from_str("2,100,2,4.0").unwrap(),
+        ItemTemplate::from_str("3,100,1,3.0").unwrap(),
+        ItemTemplate::from_str("4,100,2,2.0").unwrap(),
+    ];
+
+    let outcome = plan_with_bin_completion(&items, &pack_template(10, 10.0), &[], 1_000).unwrap();
+
+    assert_eq!(outcome.packs.len(), 2);
+    assert!(outcome.packs.iter().all(|p| p.weight == 10.0));
+    assert_eq!(
+        outcome
+            .packs
+            .iter()
+            .map(|p| p.number)
+            .collect::<Vec<usize>>(),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn when_planning_with_bin_completion_it_should_place_every_item_once_within_the_limits() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,7,4.5").unwrap(),
+        ItemTemplate::from_str("2,100,11,3.0").unwrap(),
+        ItemTemplate::from_str("3,100,5,4.5").unwrap(),
+        ItemTemplate::from_str("4,100,13,1.5").unwrap(),
+        ItemTemplate::from_str("5,100,0,1.0").unwrap(),
+    ];
+    let pack_template = pack_template(6, 12.0);
+
+    let outcome = plan_with_bin_completion(&items, &pack_template, &[], 10_000).unwrap();
+
+    for template in &items {
+        let placed: i32 = outcome
+            .packs
+            .iter()
+            .flat_map(|p| p.items())
+            .filter(|p| p.id == &*template.id)
+            .map(|p| p.count)
+            .sum();
+        assert_eq!(placed, template.count, "{}", template.id);
+    }
+    assert!(outcome
+        .packs
+        .iter()
+        .all(|p| p.weight <= 12.0 && p.item_count <= 6));
+    // The items weigh 106.5 kg, so at least 9 packs are needed.
+    assert_eq!(outcome.packs.len(), 9);
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::ItemSkipped {
+            item_id: "5".to_string(),
+            count: 0
+        }]
+    );
+}
+
+#[test]
+fn when_the_packs_are_not_single_capacity_it_should_plan_with_the_greedy_algorithm() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,1,5.0,tags=GLASS").unwrap(),
+        ItemTemplate::from_str("2,100,2,4.0").unwrap(),
+        ItemTemplate::from_str("3,100,1,3.0").unwrap(),
+        ItemTemplate::from_str("4,100,2,2.0").unwrap(),
+    ];
+    let rules = vec![PackRule::MaximumWeightWithTag {
+        tag: "GLASS".to_string(),
+        maximum_weight: 5.0,
+    }];
+    let pack_template = pack_template(10, 10.0);
+
+    let outcome = plan_with_bin_completion(&items, &pack_template, &rules, 1_000).unwrap();
+    let greedy = plan_packs(&items, &pack_template, &rules).unwrap();
+
+    assert_eq!(outcome.packs.len(), greedy.packs.len());
+    for (pack, greedy_pack) in outcome.packs.iter().zip(&greedy.packs) {
+        assert_eq!(pack.content_hash(), greedy_pack.content_hash());
+    }
+}
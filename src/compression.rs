@@ -0,0 +1,325 @@
+//! Transparent decompression of the input files and compression of the output files.
+//!
+//! Compressed inputs are detected by their magic bytes, compressed outputs by the extension of the file. The
+//! supported compression formats depend on the `gzip` and `zstd` features.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::errors::Error;
+use crate::input::{read_error, InputReader};
+
+#[cfg(test)]
+#[path = "compression_tests.rs"]
+mod tests;
+
+// The magic bytes at the start of the compressed streams
+const GZIP_MAGIC_BYTES: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC_BYTES: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+// The number of the next temporary file, which keeps the temporary files of the outputs of a process apart
+static NEXT_TEMPORARY_FILE_NUMBER: AtomicUsize = AtomicUsize::new(0);
+
+/// Defines the compression formats of the input and output files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        })
+    }
+}
+
+impl Compression {
+    /// Returns the compression format that starts with the given bytes.
+    pub fn from_magic_bytes(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(&GZIP_MAGIC_BYTES) {
+            Compression::Gzip
+        } else if bytes.starts_with(&ZSTD_MAGIC_BYTES) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Returns the compression format that belongs to the extension of the path, e.g. `.gz` or `.zst`.
+    pub fn from_extension(path: &Path) -> Compression {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+fn to_unsupported_error(path: &str, compression: Compression) -> Error {
+    Error::UnsupportedCompression {
+        path: path.to_string(),
+        compression: compression.to_string(),
+    }
+}
+
+/// Wraps the reader in a decoder if the data starts with the magic bytes of a supported compression format.
+/// The 'source' describes the origin of the data in the errors, e.g. the path of the file.
+pub fn decompress<'a, R: BufRead + 'a>(
+    reader: R,
+    source: &str,
+) -> Result<Box<dyn BufRead + 'a>, Error> {
+    // The read errors refer to the position in the data as it is stored, i.e. before it is decompressed.
+    let mut reader = InputReader::new(reader, source);
+    let start = reader.fill_buf().map_err(read_error)?;
+
+    match Compression::from_magic_bytes(start) {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        ))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let offset = reader.offset();
+            let decoder = zstd::Decoder::with_buffer(reader)
+                .map_err(|e| crate::input::read_error_at(e, source, offset))?;
+            Ok(Box::new(BufReader::new(decoder)))
+        }
+        #[allow(unreachable_patterns)]
+        compression => Err(to_unsupported_error(source, compression)),
+    }
+}
+
+/// Opens the file at the given path for reading, decompressing it if it is compressed.
+pub fn open_input(path: &Path) -> Result<Box<dyn BufRead>, Error> {
+    let file = File::open(path).map_err(|e| Error::UnableToOpenFile {
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    })?;
+
+    decompress(BufReader::new(file), &path.display().to_string())
+}
+
+/// Defines what happens when the output file already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExistingOutput {
+    /// The existing file is replaced.
+    #[default]
+    Overwrite,
+
+    /// The existing file is kept and the output isn't written.
+    Keep,
+}
+
+/// Writes the data, compressed or not, to the temporary file of an output file.
+enum Encoder {
+    Plain(BufWriter<File>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Encoder {
+    /// Writes the end of the compressed stream and returns the file.
+    fn finish(self) -> io::Result<BufWriter<File>> {
+        match self {
+            Encoder::Plain(f) => Ok(f),
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(e) => e.finish(),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(e) => e.finish(),
+        }
+    }
+
+    fn as_writer(&mut self) -> &mut dyn Write {
+        match self {
+            Encoder::Plain(f) => f,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(e) => e,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(e) => e,
+        }
+    }
+}
+
+/// Writes to an output file, compressing the data if the extension of the file asks for it. The output must
+/// be finished with [`OutputFile::finish`] to make sure that all the data is written.
+///
+/// The data is written to a temporary file next to the output file, which replaces the output file when the
+/// output is finished. A plan that is interrupted halfway therefore never leaves a truncated output file,
+/// only the previous output file, if any. The temporary file is removed when the output isn't finished.
+pub struct OutputFile {
+    // Only taken when the output is finished
+    encoder: Option<Encoder>,
+    path: PathBuf,
+    temporary_path: PathBuf,
+    existing_output: ExistingOutput,
+}
+
+impl OutputFile {
+    /// Creates the file at the given path, compressing the output if the path ends in `.gz` or `.zst`. An
+    /// existing file is replaced when the output is finished.
+    pub fn create(path: &Path) -> Result<OutputFile, Error> {
+        OutputFile::create_with(path, ExistingOutput::Overwrite)
+    }
+
+    /// Creates the file at the given path, compressing the output if the path ends in `.gz` or `.zst`. When
+    /// existing files are kept an error is returned if the file exists.
+    pub fn create_with(path: &Path, existing_output: ExistingOutput) -> Result<OutputFile, Error> {
+        let to_write_error = |e: io::Error| Error::UnableToWriteOutput {
+            reason: format!("{}: {}", path.display(), e),
+        };
+
+        let compression = Compression::from_extension(path);
+        if !is_supported(compression) {
+            return Err(to_unsupported_error(
+                &path.display().to_string(),
+                compression,
+            ));
+        }
+
+        if existing_output == ExistingOutput::Keep && path.exists() {
+            return Err(Error::OutputFileExists {
+                path: path.display().to_string(),
+            });
+        }
+
+        let temporary_path = temporary_path_for(path);
+        let file = BufWriter::new(File::create(&temporary_path).map_err(to_write_error)?);
+        let encoder = match compression {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(file, 0).map_err(|e| {
+                let _ = fs::remove_file(&temporary_path);
+                to_write_error(e)
+            })?),
+            _ => Encoder::Plain(file),
+        };
+
+        Ok(OutputFile {
+            encoder: Some(encoder),
+            path: path.to_path_buf(),
+            temporary_path,
+            existing_output,
+        })
+    }
+
+    /// Writes the remaining data, including the end of the compressed stream, to the file, and moves the file
+    /// into place. When existing files are kept, an error is returned if the file was created in the
+    /// meantime.
+    pub fn finish(mut self) -> io::Result<()> {
+        let encoder = self
+            .encoder
+            .take()
+            .expect("The output is only finished once.");
+        let result = self.move_into_place(encoder);
+        if result.is_err() {
+            let _ = fs::remove_file(&self.temporary_path);
+        }
+
+        result
+    }
+
+    fn move_into_place(&self, encoder: Encoder) -> io::Result<()> {
+        let file = encoder.finish()?.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+
+        match self.existing_output {
+            ExistingOutput::Overwrite => fs::rename(&self.temporary_path, &self.path),
+            ExistingOutput::Keep => {
+                // A link fails if the file exists, so a file that was created in the meantime is kept too.
+                fs::hard_link(&self.temporary_path, &self.path)?;
+                fs::remove_file(&self.temporary_path)
+            }
+        }
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        self.encoder
+            .as_mut()
+            .expect("The output is not written after it is finished.")
+            .as_writer()
+    }
+}
+
+impl Drop for OutputFile {
+    fn drop(&mut self) {
+        // An output that is finished has already moved, or removed, its temporary file.
+        if self.encoder.take().is_some() {
+            let _ = fs::remove_file(&self.temporary_path);
+        }
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+/// Returns the path of the temporary file of an output file, which is a hidden file in the same directory, so
+/// that it can be renamed to the output file.
+fn temporary_path_for(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let number = NEXT_TEMPORARY_FILE_NUMBER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        number
+    ))
+}
+
+/// Compresses the data in the format that belongs to the extension of the destination, e.g. `.gz`, for an
+/// output that isn't written to a local file.
+pub fn compress(data: &[u8], destination: &str) -> Result<Vec<u8>, Error> {
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    let to_write_error = |e: io::Error| Error::UnableToWriteOutput {
+        reason: format!("{}: {}", destination, e),
+    };
+
+    match Compression::from_extension(Path::new(destination)) {
+        Compression::None => Ok(data.to_vec()),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data).map_err(to_write_error)?;
+            encoder.finish().map_err(to_write_error)
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => zstd::encode_all(data, 0).map_err(to_write_error),
+        #[allow(unreachable_patterns)]
+        compression => Err(to_unsupported_error(destination, compression)),
+    }
+}
+
+/// Returns whether the compression format is enabled in this build.
+pub fn is_supported(compression: Compression) -> bool {
+    match compression {
+        Compression::None => true,
+        Compression::Gzip => cfg!(feature = "gzip"),
+        Compression::Zstd => cfg!(feature = "zstd"),
+    }
+}
@@ -0,0 +1,196 @@
+use std::io::Read;
+
+use super::*;
+
+use crate::test_support::create_test_directory;
+
+// Compression
+
+#[test]
+fn when_detecting_the_compression_from_magic_bytes_it_should_return_the_format() {
+    assert_eq!(
+        Compression::from_magic_bytes(&[0x1f, 0x8b, 0x08]),
+        Compression::Gzip
+    );
+    assert_eq!(
+        Compression::from_magic_bytes(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]),
+        Compression::Zstd
+    );
+    assert_eq!(Compression::from_magic_bytes(b"NATURAL"), Compression::None);
+    assert_eq!(Compression::from_magic_bytes(&[]), Compression::None);
+}
+
+#[test]
+fn when_detecting_the_compression_from_the_extension_it_should_return_the_format() {
+    assert_eq!(
+        Compression::from_extension(Path::new("a.csv.gz")),
+        Compression::Gzip
+    );
+    assert_eq!(
+        Compression::from_extension(Path::new("a.csv.zst")),
+        Compression::Zstd
+    );
+    assert_eq!(
+        Compression::from_extension(Path::new("a.csv")),
+        Compression::None
+    );
+}
+
+// decompress()
+
+#[test]
+fn when_decompressing_uncompressed_data_it_should_return_the_data() {
+    let mut reader = decompress(&b"NATURAL,10,20.0\n"[..], "test").unwrap();
+    let mut text = String::new();
+    reader.read_to_string(&mut text).unwrap();
+    assert_eq!(text, "NATURAL,10,20.0\n");
+}
+
+// OutputFile and open_input()
+
+#[cfg(feature = "gzip")]
+#[test]
+fn when_writing_and_reading_a_gzip_file_it_should_return_the_original_data() {
+    let directory = create_test_directory("gzip");
+    let path = directory.join("plan.txt.gz");
+
+    let mut output = OutputFile::create(&path).unwrap();
+    output.write_all(b"Pack Number: 1\n").unwrap();
+    output.finish().unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(Compression::from_magic_bytes(&bytes), Compression::Gzip);
+
+    let mut text = String::new();
+    open_input(&path)
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    assert_eq!(text, "Pack Number: 1\n");
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn when_writing_and_reading_a_zstd_file_it_should_return_the_original_data() {
+    let directory = create_test_directory("zstd");
+    let path = directory.join("plan.txt.zst");
+
+    let mut output = OutputFile::create(&path).unwrap();
+    output.write_all(b"Pack Number: 1\n").unwrap();
+    output.finish().unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(Compression::from_magic_bytes(&bytes), Compression::Zstd);
+
+    let mut text = String::new();
+    open_input(&path)
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    assert_eq!(text, "Pack Number: 1\n");
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn when_reading_gzip_data_without_the_gzip_feature_it_should_return_an_error() {
+    let result = decompress(&[0x1f, 0x8b, 0x08][..], "test");
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnsupportedCompression {
+            path: "test".to_string(),
+            compression: "gzip".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_writing_a_file_it_should_replace_the_file_without_leaving_a_temporary_file() {
+    let directory = create_test_directory("atomic");
+    let path = directory.join("plan.txt");
+    std::fs::write(&path, "Pack Number: 1\n").unwrap();
+
+    let mut output = OutputFile::create(&path).unwrap();
+    output.write_all(b"Pack Number: 2\n").unwrap();
+    output.finish().unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Pack Number: 2\n");
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_a_file_is_not_finished_it_should_keep_the_existing_file() {
+    let directory = create_test_directory("unfinished");
+    let path = directory.join("plan.txt");
+    std::fs::write(&path, "Pack Number: 1\n").unwrap();
+
+    let mut output = OutputFile::create(&path).unwrap();
+    output.write_all(b"Pack Num").unwrap();
+    drop(output);
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Pack Number: 1\n");
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_keeping_an_existing_file_it_should_return_an_error() {
+    let directory = create_test_directory("no-clobber");
+    let path = directory.join("plan.txt");
+    std::fs::write(&path, "Pack Number: 1\n").unwrap();
+
+    let result = OutputFile::create_with(&path, ExistingOutput::Keep);
+
+    assert!(matches!(result, Err(Error::OutputFileExists { .. })));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Pack Number: 1\n");
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_keeping_a_file_that_does_not_exist_it_should_write_the_file() {
+    let directory = create_test_directory("no-clobber-new");
+    let path = directory.join("plan.txt");
+
+    let mut output = OutputFile::create_with(&path, ExistingOutput::Keep).unwrap();
+    output.write_all(b"Pack Number: 1\n").unwrap();
+    output.finish().unwrap();
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Pack Number: 1\n");
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+// compress()
+
+#[test]
+fn when_compressing_for_a_destination_without_a_compressed_extension_it_should_return_the_data() {
+    assert_eq!(
+        compress(b"Pack Number: 1\n", "s3://plans/plan.txt").unwrap(),
+        b"Pack Number: 1\n"
+    );
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn when_compressing_for_a_gzip_destination_it_should_return_data_that_decompresses_to_the_original()
+{
+    let compressed = compress(b"Pack Number: 1\n", "s3://plans/plan.txt.gz").unwrap();
+    assert_eq!(
+        Compression::from_magic_bytes(&compressed),
+        Compression::Gzip
+    );
+
+    let mut text = String::new();
+    decompress(&compressed[..], "test")
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    assert_eq!(text, "Pack Number: 1\n");
+}
@@ -0,0 +1,169 @@
+//! The settings that determine a plan, in a form that can be written to and read from TOML, so that the exact
+//! configuration used for a plan can be archived with the plan and used again later to reproduce the plan.
+
+use serde::{Deserialize, Serialize};
+
+use crate::adaptive::AdaptiveAlgorithm;
+use crate::completion::BinCompletionAlgorithm;
+use crate::errors::Error;
+use crate::model::{
+    DuplicateIdPolicy, InfeasibleItemPolicy, PackRule, PackSortOrder, PackTemplate, TieBreak,
+};
+use crate::partition::PartitionedAlgorithm;
+use crate::planner::GreedyAlgorithm;
+use crate::restarts::{RestartAlgorithm, RestartObjective};
+use crate::{effective_pack_template, Configuration};
+
+#[cfg(test)]
+#[path = "config_tests.rs"]
+mod tests;
+
+/// Contains the settings of the strategy that places the items in the packs.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategyConfig {
+    /// The sort order that replaces the sort order of the pack template, if any.
+    pub sort_order: Option<PackSortOrder>,
+
+    /// The number of restarts with shuffled items. When set the restart strategy is used instead of a single
+    /// greedy plan.
+    pub restarts: Option<u32>,
+
+    /// The seed of the random number generator that shuffles the items between restarts.
+    pub seed: u64,
+
+    /// The objective by which the plans of the restarts are compared.
+    pub objective: RestartObjective,
+
+    /// Indicates if the plan should be checked against the theoretical minimum number of packs.
+    pub quick_check: bool,
+
+    /// Indicates if all the components of a kit should be placed in the same pack.
+    pub keep_kits_together: bool,
+
+    /// Indicates if the items that fill whole packs on their own should be placed in packs with a single item
+    /// ID, before the remaining items are mixed.
+    pub prefer_homogeneous: bool,
+
+    /// Indicates if the smallest number of packs is searched for with bin completion. Ignored when restarts
+    /// are set.
+    pub bin_completion: bool,
+
+    /// Indicates if the algorithm and the sort order are chosen from the statistics of the items. Overrides
+    /// `bin_completion`, and is ignored when restarts are set.
+    pub auto: bool,
+
+    /// Indicates if the items are partitioned into groups of compatible items, each of which is planned on
+    /// its own.
+    pub partition: bool,
+
+    /// Defines what happens with items that don't fit in an empty pack.
+    pub on_infeasible: InfeasibleItemPolicy,
+
+    /// Defines what happens with batches of items that share an ID.
+    pub duplicate_ids: DuplicateIdPolicy,
+
+    /// Defines the order of the items with the same length when the items are sorted by length.
+    pub tie_break: TieBreak,
+
+    /// The smallest number of packs over which the items are spread, if any.
+    pub minimum_packs: Option<usize>,
+}
+
+/// Contains the pack template, the strategy and the rules with which a plan is made.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct PlannerConfig {
+    /// The limits of the packs and the order in which the items are placed.
+    pub pack: PackTemplate,
+
+    /// The strategy that places the items in the packs.
+    pub strategy: StrategyConfig,
+
+    /// The rules that restrict which items can be combined in a single pack.
+    pub rules: Vec<PackRule>,
+}
+
+impl PlannerConfig {
+    /// Writes the configuration as a TOML document.
+    pub fn to_toml(&self) -> Result<String, Error> {
+        toml::to_string(self).map_err(|e| Error::InvalidPlannerConfig {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Reads the configuration from a TOML document. Settings that are not in the document keep their
+    /// default value.
+    pub fn from_toml(document: &str) -> Result<PlannerConfig, Error> {
+        toml::from_str(document).map_err(|e| Error::InvalidPlannerConfig {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Returns the configuration with which [`crate::plan_items`] plans the items of the pack template with the
+    /// given configuration, i.e. the pack template with the overrides of the configuration, the strategy of the
+    /// algorithm and the rules.
+    pub fn from_configuration(
+        pack_template: &PackTemplate,
+        configuration: &Configuration,
+    ) -> PlannerConfig {
+        PlannerConfig {
+            pack: effective_pack_template(pack_template, configuration),
+            strategy: StrategyConfig {
+                quick_check: configuration.quick_check,
+                keep_kits_together: configuration.keep_kits_together,
+                prefer_homogeneous: configuration.prefer_homogeneous,
+                on_infeasible: configuration.on_infeasible,
+                duplicate_ids: configuration.duplicate_ids,
+                tie_break: configuration.tie_break,
+                minimum_packs: configuration.minimum_packs,
+                ..configuration.algorithm.strategy()
+            },
+            rules: configuration.rules.clone(),
+        }
+    }
+
+    /// Returns the configuration for [`crate::plan_items`] with the strategy and the rules of this
+    /// configuration. The pack template is not part of that configuration, and is passed to
+    /// [`crate::plan_items`] separately.
+    pub fn to_configuration(&self) -> Configuration {
+        let mut configuration = Configuration {
+            rules: self.rules.clone(),
+            quick_check: self.strategy.quick_check,
+            sort_order: self.strategy.sort_order,
+            keep_kits_together: self.strategy.keep_kits_together,
+            prefer_homogeneous: self.strategy.prefer_homogeneous,
+            on_infeasible: self.strategy.on_infeasible,
+            duplicate_ids: self.strategy.duplicate_ids,
+            tie_break: self.strategy.tie_break,
+            minimum_packs: self.strategy.minimum_packs,
+            ..Configuration::default()
+        };
+        if self.strategy.bin_completion {
+            configuration.algorithm = Box::new(BinCompletionAlgorithm::default());
+        }
+        if self.strategy.auto {
+            configuration.algorithm = Box::new(AdaptiveAlgorithm {
+                keep_sort_order: self.strategy.sort_order.is_some(),
+                decision_hook: None,
+            });
+        }
+        if let Some(restarts) = self.strategy.restarts {
+            configuration.algorithm = Box::new(RestartAlgorithm {
+                restarts,
+                seed: self.strategy.seed,
+                objective: self.strategy.objective,
+                time_limit: None,
+            });
+        }
+        if self.strategy.partition {
+            let algorithm =
+                std::mem::replace(&mut configuration.algorithm, Box::new(GreedyAlgorithm));
+            configuration.algorithm = Box::new(PartitionedAlgorithm { algorithm });
+        }
+
+        configuration
+    }
+}
@@ -0,0 +1,156 @@
+use super::*;
+use crate::model::{LengthMode, NestingRule};
+
+fn create_planner_config() -> PlannerConfig {
+    PlannerConfig {
+        pack: PackTemplate {
+            maximum_number_of_pieces: 40,
+            maximum_weight: 500.0,
+            sort_order: PackSortOrder::LongToShort,
+            length_mode: LengthMode::SumWithGap(50.0),
+            maximum_length: Some(20000.0),
+            hard_maximum_weight: Some(550.0),
+            nesting_rules: vec![NestingRule {
+                inner_item_id: "ROD-12".to_string(),
+                outer_item_id: "TUBE-40".to_string(),
+            }],
+            ..PackTemplate::new()
+        },
+        strategy: StrategyConfig {
+            restarts: Some(20),
+            seed: 7,
+            objective: RestartObjective::BalancedWeight,
+            quick_check: true,
+            ..StrategyConfig::default()
+        },
+        rules: vec![
+            PackRule::MaximumItemsWithTag {
+                tag: "BATTERY".to_string(),
+                maximum: 1,
+            },
+            PackRule::ExclusiveTag {
+                tag: "COLD".to_string(),
+            },
+        ],
+    }
+}
+
+// PlannerConfig::to_toml() and PlannerConfig::from_toml()
+
+#[test]
+fn when_writing_a_configuration_to_toml_it_should_read_back_the_same_configuration() {
+    let config = create_planner_config();
+    let document = config
+        .to_toml()
+        .expect("Failed to write the configuration.");
+    let restored = PlannerConfig::from_toml(&document).expect("Failed to read the configuration.");
+
+    assert_eq!(restored.pack.maximum_number_of_pieces, 40);
+    assert_eq!(restored.pack.maximum_weight, 500.0);
+    assert_eq!(restored.pack.sort_order, PackSortOrder::LongToShort);
+    assert_eq!(restored.pack.length_mode, LengthMode::SumWithGap(50.0));
+    assert_eq!(restored.pack.maximum_length, Some(20000.0));
+    assert_eq!(restored.pack.hard_maximum_weight, Some(550.0));
+    assert_eq!(restored.pack.maximum_value, None);
+    assert_eq!(restored.pack.number_format, config.pack.number_format);
+    assert_eq!(restored.pack.nesting_rules, config.pack.nesting_rules);
+    assert_eq!(restored.strategy, config.strategy);
+    assert_eq!(restored.rules, config.rules);
+}
+
+#[test]
+fn when_reading_a_partial_configuration_it_should_use_the_defaults() {
+    let document = "
+[pack]
+maximum_number_of_pieces = 10
+maximum_weight = 50.0
+sort_order = \"NATURAL\"
+
+[[rules]]
+type = \"MAX_WEIGHT_WITH_TAG\"
+tag = \"GLASS\"
+maximum_weight = 10.0
+";
+    let config = PlannerConfig::from_toml(document).expect("Failed to read the configuration.");
+    assert_eq!(config.pack.sort_order, PackSortOrder::Natural);
+    assert_eq!(config.pack.length_mode, LengthMode::Maximum);
+    assert_eq!(config.strategy, StrategyConfig::default());
+    assert_eq!(
+        config.rules,
+        vec![PackRule::MaximumWeightWithTag {
+            tag: "GLASS".to_string(),
+            maximum_weight: 10.0
+        }]
+    );
+}
+
+#[test]
+fn when_reading_a_configuration_with_an_unknown_setting_it_should_return_an_error() {
+    let result = PlannerConfig::from_toml("[strategy]\ncolour = \"red\"\n");
+    assert!(matches!(result, Err(Error::InvalidPlannerConfig { .. })));
+}
+
+// PlannerConfig::to_configuration()
+
+#[test]
+fn when_creating_the_configuration_it_should_use_the_strategy_and_the_rules() {
+    let configuration = create_planner_config().to_configuration();
+    assert_eq!(configuration.rules.len(), 2);
+    assert!(configuration.quick_check);
+    assert_eq!(configuration.sort_order, None);
+}
+
+// PlannerConfig::from_configuration()
+
+#[test]
+fn when_describing_a_configuration_it_should_round_trip_the_strategy_and_apply_the_overrides() {
+    let config = create_planner_config();
+    let mut configuration = config.to_configuration();
+    configuration.maximum_weight = Some(400.0);
+    configuration.on_infeasible = InfeasibleItemPolicy::Skip;
+    configuration.tie_break = TieBreak::Id;
+    configuration.minimum_packs = Some(4);
+
+    let described = PlannerConfig::from_configuration(&config.pack, &configuration);
+    assert_eq!(described.pack.maximum_weight, 400.0);
+    assert_eq!(described.pack.maximum_number_of_pieces, 40);
+    assert_eq!(
+        described.strategy,
+        StrategyConfig {
+            on_infeasible: InfeasibleItemPolicy::Skip,
+            tie_break: TieBreak::Id,
+            minimum_packs: Some(4),
+            ..config.strategy
+        }
+    );
+    assert_eq!(described.rules, config.rules);
+}
+
+#[test]
+fn when_describing_a_configuration_with_bin_completion_it_should_enable_bin_completion() {
+    let config = PlannerConfig {
+        strategy: StrategyConfig {
+            bin_completion: true,
+            ..StrategyConfig::default()
+        },
+        ..PlannerConfig::default()
+    };
+
+    let described = PlannerConfig::from_configuration(&config.pack, &config.to_configuration());
+    assert!(described.strategy.bin_completion);
+}
+
+#[test]
+fn when_describing_a_configuration_with_the_auto_strategy_it_should_enable_the_auto_strategy() {
+    let config = PlannerConfig {
+        strategy: StrategyConfig {
+            auto: true,
+            ..StrategyConfig::default()
+        },
+        ..PlannerConfig::default()
+    };
+
+    let described = PlannerConfig::from_configuration(&config.pack, &config.to_configuration());
+    assert!(described.strategy.auto);
+    assert!(!described.strategy.bin_completion);
+}
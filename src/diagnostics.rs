@@ -0,0 +1,184 @@
+//! The positions of the parse errors in the input, and the rendering of the errors, so that the offending
+//! field can be found quickly in a large input file.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::errors::Error;
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "diagnostics_tests.rs"]
+mod tests;
+
+/// Defines how errors are shown to the user.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DiagnosticsFormat {
+    /// The error is shown as a single line of text.
+    #[default]
+    Plain,
+
+    /// The offending line of the input is shown, with the field that is not valid underlined.
+    Pretty,
+}
+
+impl FromStr for DiagnosticsFormat {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "plain" => Ok(DiagnosticsFormat::Plain),
+            "pretty" => Ok(DiagnosticsFormat::Pretty),
+            _ => Err(Error::UnknownDiagnosticsFormat {
+                input: input.to_string(),
+            }),
+        }
+    }
+}
+
+/// The characters of a line that caused a parse error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    /// The 1-based column, in characters, of the first character.
+    pub column: usize,
+
+    /// The number of characters.
+    pub length: usize,
+}
+
+impl Span {
+    /// Finds the field of the line that caused the error. If the error doesn't name a field, or the field
+    /// can't be found, the span covers the line without the surrounding white space.
+    pub fn locate(error: &Error, line: &str) -> Span {
+        let column_of = |start: usize| line[..start].chars().count() + 1;
+
+        let value = offending_value(error).map_or("", str::trim);
+        let start = if value.is_empty() {
+            None
+        } else {
+            find_field(line, value).or_else(|| line.find(value))
+        };
+
+        match start {
+            Some(start) => Span {
+                column: column_of(start),
+                length: value.chars().count(),
+            },
+            None => Span {
+                column: column_of(line.len() - line.trim_start().len()),
+                length: line.trim().chars().count().max(1),
+            },
+        }
+    }
+}
+
+/// Adds the number of the line, and the position of the field that caused the error, to a parse error.
+pub fn at_line(error: Error, line_number: usize, line: &str) -> Error {
+    let span = Span::locate(&error, line);
+    Error::InvalidInputLine {
+        line_number,
+        column: span.column,
+        length: span.length,
+        line: line.to_string(),
+        source: Box::new(error),
+    }
+}
+
+/// Renders the error in the given format. In the pretty format an error in a line of the input shows the
+/// line with the field that caused the error underlined, e.g.
+///
+/// ```text
+/// error: The provided input string "A,10,1O.5,2" contains an invalid value for the weight of an item: ...
+///  --> line 3, column 6
+///   |
+/// 3 | A,10,1O.5,2
+///   |      ^^^^
+/// ```
+pub fn render(error: &Error, format: DiagnosticsFormat) -> String {
+    match (format, error) {
+        (
+            DiagnosticsFormat::Pretty,
+            Error::InvalidInputLine {
+                line_number,
+                column,
+                length,
+                line,
+                source,
+            },
+        ) => {
+            let number = line_number.to_string();
+            let gutter = " ".repeat(number.len());
+
+            // Tabs are shown as a single space, so that the underline stays below the field.
+            let line = line.replace('\t', " ");
+
+            let mut text = String::new();
+            let _ = writeln!(text, "error: {source}");
+            let _ = writeln!(text, "{gutter}--> line {line_number}, column {column}");
+            let _ = writeln!(text, "{gutter} |");
+            let _ = writeln!(text, "{number} | {line}");
+            let _ = write!(
+                text,
+                "{gutter} | {}{}",
+                " ".repeat(column - 1),
+                "^".repeat((*length).max(1))
+            );
+            text
+        }
+        (DiagnosticsFormat::Pretty, _) => format!("error: {error}"),
+        (DiagnosticsFormat::Plain, _) => format!("Error: {error}"),
+    }
+}
+
+/// Returns the byte offset of the first comma separated field of the line that is equal to the value.
+fn find_field(line: &str, value: &str) -> Option<usize> {
+    let mut start = 0;
+    for field in line.split(',') {
+        let trimmed = field.trim_start();
+        let offset = start + field.len() - trimmed.len();
+        if trimmed.trim_end().trim_matches('"') == value {
+            return Some(offset + usize::from(trimmed.starts_with('"')));
+        }
+
+        start += field.len() + 1;
+    }
+
+    None
+}
+
+/// Returns the part of the input that caused the error, if the error names it.
+fn offending_value(error: &Error) -> Option<&str> {
+    match error {
+        Error::InvalidLengthMode { property_value, .. }
+        | Error::InvalidPackMaximumLength { property_value, .. }
+        | Error::InvalidPackHardMaximumWeight { property_value, .. }
+        | Error::InvalidPackMaximumValue { property_value, .. }
+        | Error::InvalidPackTareWeight { property_value, .. }
+        | Error::InvalidPackSortOrder { property_value, .. }
+        | Error::InvalidPackItemCount { property_value, .. }
+        | Error::InvalidPackWeight { property_value, .. }
+        | Error::InvalidItemLength { property_value, .. }
+        | Error::InvalidItemWeight { property_value, .. }
+        | Error::InvalidItemCount { property_value, .. }
+        | Error::InvalidItemValue { property_value, .. }
+        | Error::InvalidStorageClass { property_value, .. }
+        | Error::InvalidPackRuleItemCount { property_value, .. }
+        | Error::InvalidPackRuleWeight { property_value, .. }
+        | Error::InvalidWeightBandWeight { property_value, .. }
+        | Error::InvalidWeightBandCost { property_value, .. }
+        | Error::InvalidCartonLimit { property_value, .. }
+        | Error::InvalidNumberFormat { property_value, .. }
+        | Error::NumberOutOfRange { property_value, .. }
+        | Error::NonFiniteNumber { property_value, .. }
+        | Error::InvalidNumberFormatAttribute { property_value, .. }
+        | Error::InvalidItemUnit { property_value, .. }
+        | Error::InvalidBulkItem { property_value, .. }
+        | Error::InvalidItemWidth { property_value, .. }
+        | Error::InvalidSheetSize { property_value, .. }
+        | Error::InvalidOrderedQuantity { property_value, .. }
+        | Error::InvalidItemMultiple { property_value, .. } => Some(property_value),
+        Error::UnknownPackAttribute { attribute, .. } => Some(attribute),
+        Error::UnknownPackRule { rule, .. } => Some(rule),
+        Error::UnknownLocale { locale, .. } => Some(locale),
+        _ => None,
+    }
+}
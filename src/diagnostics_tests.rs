@@ -0,0 +1,118 @@
+use std::io::Cursor;
+
+use super::*;
+use crate::parser::parse_input;
+
+// DiagnosticsFormat
+
+#[test]
+fn when_parsing_a_diagnostics_format_it_should_accept_the_known_formats() {
+    assert_eq!(
+        DiagnosticsFormat::from_str("plain").unwrap(),
+        DiagnosticsFormat::Plain
+    );
+    assert_eq!(
+        DiagnosticsFormat::from_str("pretty").unwrap(),
+        DiagnosticsFormat::Pretty
+    );
+    assert!(matches!(
+        DiagnosticsFormat::from_str("fancy"),
+        Err(Error::UnknownDiagnosticsFormat { .. })
+    ));
+}
+
+// Span::locate()
+
+#[test]
+fn when_locating_an_error_with_a_value_it_should_return_the_span_of_the_field() {
+    let error = Error::InvalidOrderedQuantity {
+        input: "A1x, 1, 1, 1x".to_string(),
+        property_value: "1x".to_string(),
+    };
+
+    // The value is found as a whole field, not inside the ID.
+    let span = Span::locate(&error, "  A1x, 1, 1, 1x");
+
+    assert_eq!(
+        span,
+        Span {
+            column: 14,
+            length: 2
+        }
+    );
+}
+
+#[test]
+fn when_locating_an_error_without_a_value_it_should_return_the_span_of_the_line() {
+    let error = Error::InputStringShouldStartWithNumberOrKeyWord {
+        input: "  Éclair  ".to_string(),
+    };
+
+    let span = Span::locate(&error, "  Éclair  ");
+
+    assert_eq!(
+        span,
+        Span {
+            column: 3,
+            length: 6
+        }
+    );
+}
+
+// at_line()
+
+#[test]
+fn when_an_input_line_is_not_valid_it_should_return_the_line_number_and_the_column() {
+    let mut cursor = Cursor::new("NATURAL,40,500.0\n1001,6200,3O,10\n");
+
+    let result = parse_input(&mut cursor);
+
+    match result {
+        Err(Error::InvalidInputLine {
+            line_number,
+            column,
+            length,
+            line,
+            source,
+        }) => {
+            assert_eq!(line_number, 2);
+            assert_eq!(column, 11);
+            assert_eq!(length, 2);
+            assert_eq!(line, "1001,6200,3O,10");
+            assert!(matches!(*source, Error::InvalidItemCount { .. }));
+        }
+        r => panic!("Expected an invalid input line, got {r:?}"),
+    }
+}
+
+// render()
+
+#[test]
+fn when_rendering_an_input_line_error_in_the_pretty_format_it_should_underline_the_field() {
+    let mut cursor = Cursor::new("NATURAL,40,500.0\n1001,6200,3O,10\n");
+    let error = parse_input(&mut cursor).unwrap_err();
+
+    let text = render(&error, DiagnosticsFormat::Pretty);
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].starts_with("error: The provided input string"));
+    assert_eq!(lines[1], " --> line 2, column 11");
+    assert_eq!(lines[2], "  |");
+    assert_eq!(lines[3], "2 | 1001,6200,3O,10");
+    assert_eq!(lines[4], "  |           ^^");
+}
+
+#[test]
+fn when_rendering_an_error_in_the_plain_format_it_should_return_a_single_line() {
+    let mut cursor = Cursor::new("NATURAL,40,500.0\n1001,6200,3O,10\n");
+    let error = parse_input(&mut cursor).unwrap_err();
+
+    let text = render(&error, DiagnosticsFormat::Plain);
+
+    assert!(text.starts_with("Error: Line 2, column 11: The provided input string"));
+    assert_eq!(text.lines().count(), 1);
+    assert_eq!(
+        render(&Error::MissingPackInformation, DiagnosticsFormat::Pretty),
+        format!("error: {}", Error::MissingPackInformation)
+    );
+}
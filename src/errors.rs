@@ -0,0 +1,1056 @@
+//! The errors that can occur while parsing the input and planning the packs.
+
+use std::num::{ParseFloatError, ParseIntError};
+
+use thiserror::Error;
+
+/// Defines the different errors for the swerve model crate.
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    /// Indicates that one of the input strings didn't start in a valid way.
+    ///
+    /// * 'input' - The input string that was invalid.
+    #[error("The provided input string {input:?} was not valid. Expected the string to start with a number or one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT, PIN, ---CLOSE---, KIT:, GROUP:].")]
+    InputStringShouldStartWithNumberOrKeyWord {
+        /// The input string that is incorrect
+        input: String,
+    },
+
+    /// Indicates that the current input string contains a pack information header, but the current line is not the first line. So duplicate information is
+    /// provided.
+    ///
+    /// * 'current_line' - The contents of the current line, which contains the duplicate header
+    /// * 'current_line_index' - The index of the current line.
+    #[error("The provided input string {current_line:?} contains pack information, but this line is not the first line of the input stream. It is the {current_line_index:?} line. This means there is duplicate header information.")]
+    InputContainsDuplicatePackInformation {
+        current_line: String,
+        current_line_index: usize,
+    },
+
+    /// Indicates that the input contains more than one section, each with its own pack information, which
+    /// can't be used with the given argument.
+    ///
+    /// * 'argument' - The argument that requires an input with a single section
+    #[error("The input contains more than one section, which isn't supported with {argument}.")]
+    UnsupportedInputSections { argument: String },
+
+    /// Indicates that a string containing pack information has an attribute that is not known.
+    ///
+    /// * 'input' - The input string
+    /// * 'attribute' - The name of the unknown attribute
+    #[error("The provided input string {input:?} contains an unknown pack attribute: {attribute:?}. Expected one of [length, max_length, hard_max_weight, max_value, tare, sheet, dimensions, thousands_separator, scientific_notation].")]
+    UnknownPackAttribute { input: String, attribute: String },
+
+    /// Indicates that a string containing pack information has an invalid value for the length mode.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the length mode
+    #[error("The provided input string {input:?} contains an invalid value for the length mode of a pack: {property_value:?}. Expected one of [max, sum, sum-with-gap [gap]].")]
+    InvalidLengthMode {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a string containing pack information has an invalid value for the maximum length of the pack.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the maximum length
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the maximum length of a pack: {property_value:?}. Expected a positive floating point number.")]
+    InvalidPackMaximumLength {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing pack information has an invalid value for the hard maximum weight of
+    /// the pack.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the hard maximum weight
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the hard maximum weight of a pack: {property_value:?}. Expected a positive floating point number.")]
+    InvalidPackHardMaximumWeight {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing pack information has an invalid value for the maximum value of the
+    /// items in the pack.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the maximum value
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the maximum value of a pack: {property_value:?}. Expected a positive floating point number.")]
+    InvalidPackMaximumValue {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing pack information has an invalid value for the tare weight of the
+    /// pack, or a tare weight that leaves no weight for the items.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the tare weight
+    #[error("The provided input string {input:?} contains an invalid tare weight for a pack: {property_value:?}. Expected a positive floating point number that is less than the maximum weight of the pack.")]
+    InvalidPackTareWeight {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that the hard maximum weight of a pack is less than the maximum weight of the pack.
+    ///
+    /// * 'input' - The input string
+    /// * 'hard_maximum_weight' - The hard maximum weight of the pack
+    /// * 'maximum_weight' - The maximum weight of the pack
+    #[error("The provided input string {input:?} contains a hard maximum weight of {hard_maximum_weight} which is less than the maximum weight of the pack, {maximum_weight}.")]
+    HardMaximumWeightBelowMaximumWeight {
+        input: String,
+        hard_maximum_weight: f64,
+        maximum_weight: f64,
+    },
+
+    /// Indicates that a string containing item information has too few or too many property values.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_count' - The number of property values that were found in the input string
+    #[error("The provided input string {input:?} contains too few or too many property values. Expecting 3 values, optionally followed by [key]=[value] attributes, but got {property_count:?}")]
+    InvalidNumberOfPropertiesForPacks {
+        input: String,
+        property_count: usize,
+    },
+
+    // Indicates that a string containing pack information has an invalid value for the sort order.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the sort order
+    #[error("The provided input string {input:?} contains an invalid value for the sort order of a pack: {property_value:?}. Expected one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT].")]
+    InvalidPackSortOrder {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a string containing pack information has an invalid value for the number of items in a pack.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the number of items
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the number of the items in a pack: {property_value:?}. Expected a positive integer number.")]
+    InvalidPackItemCount {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseIntError,
+    },
+
+    // Indicates that a string containing pack information has an invalid value for the weight of the pack.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the weight property
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the weight of a pack: {property_value:?}. Expected a positive floating point number.")]
+    InvalidPackWeight {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing item information has too few or too many property values.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_count' - The number of property values that were found in the input string
+    #[error("The provided input string {input:?} contains too few or too many property values. Expecting 4 values, optionally followed by [key]=[value] attributes, but got {property_count:?}")]
+    InvalidNumberOfPropertiesForItem {
+        input: String,
+        property_count: usize,
+    },
+
+    /// Indicates that a string containing item information has an invalid value for the length of the item.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the length property
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the length of the item: {property_value:?}. Expected a positive floating point number.")]
+    InvalidItemLength {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing item information has an invalid value for the weight of the item.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the weight property
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the weight of the item: {property_value:?}. Expected a positive floating point number.")]
+    InvalidItemWeight {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing item information has an invalid value for the number of items.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the number of items
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the number of the items: {property_value:?}. Expected a positive integer number.")]
+    InvalidItemCount {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseIntError,
+    },
+
+    /// Indicates that a string containing item information has an attribute that is not known.
+    ///
+    /// * 'input' - The input string
+    /// * 'attribute' - The name of the unknown attribute
+    #[error("The provided input string {input:?} contains an unknown item attribute: {attribute:?}. Expected one of [tags, value, storage, destination, width, ordered].")]
+    UnknownItemAttribute { input: String, attribute: String },
+
+    /// Indicates that a string containing item information has an invalid value for the value of the item.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the value of the item
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the value of an item: {property_value:?}. Expected a positive floating point number.")]
+    InvalidItemValue {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing item information has a storage class that is not known.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The unknown storage class
+    #[error("The provided input string {input:?} contains an unknown storage class: {property_value:?}. Expected one of [ambient, chilled, frozen].")]
+    InvalidStorageClass {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that the destination of an item is empty.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} contains an empty destination. Expected destination=[destination].")]
+    InvalidItemDestination { input: String },
+
+    /// Indicates that a line that pins an item to a pack is not formatted correctly.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid pin directive. Expected PIN,[item id],pack=[pack number] with a pack number of at least 1.")]
+    InvalidPinDirective { input: String },
+
+    /// Indicates that an item was pinned to a pack, but the input doesn't contain an item with that ID.
+    ///
+    /// * 'item_id' - The ID of the pinned item
+    #[error("The item {item_id:?} is pinned to a pack, but the input doesn't contain an item with that ID.")]
+    PinnedItemNotFound { item_id: String },
+
+    /// Indicates that the items pinned to a pack don't fit in that pack.
+    ///
+    /// * 'item_id' - The ID of the pinned item that doesn't fit
+    /// * 'pack_number' - The number of the pack to which the item is pinned
+    #[error("The item {item_id:?} is pinned to pack {pack_number}, but not all of the items fit in that pack.")]
+    PinnedItemsDoNotFit { item_id: String, pack_number: usize },
+
+    /// Indicates that a string containing a pack rule starts with an unknown rule type.
+    ///
+    /// * 'input' - The input string
+    /// * 'rule' - The unknown rule type
+    #[error("The provided input string {input:?} contains an unknown pack rule: {rule:?}. Expected one of [MAX_ITEMS_WITH_TAG, MAX_WEIGHT_WITH_TAG, EXCLUSIVE_TAG, MAX_HEAVY_ITEMS].")]
+    UnknownPackRule { input: String, rule: String },
+
+    /// Indicates that a string containing a pack rule has too few or too many property values.
+    ///
+    /// * 'input' - The input string
+    /// * 'expected_count' - The number of property values that the rule requires
+    /// * 'property_count' - The number of property values that were found in the input string
+    #[error("The provided input string {input:?} contains too few or too many property values. Expecting {expected_count:?} values, but got {property_count:?}")]
+    InvalidNumberOfPropertiesForPackRule {
+        input: String,
+        expected_count: usize,
+        property_count: usize,
+    },
+
+    /// Indicates that a string containing a pack rule has an invalid value for the number of items.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the number of items
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the number of items of a pack rule: {property_value:?}. Expected a positive integer number.")]
+    InvalidPackRuleItemCount {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseIntError,
+    },
+
+    /// Indicates that a string containing a pack rule has an invalid value for the weight.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the weight
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the weight of a pack rule: {property_value:?}. Expected a positive floating point number.")]
+    InvalidPackRuleWeight {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing weight band information has too few or too many property values.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_count' - The number of property values that were found in the input string
+    #[error("The provided input string {input:?} contains too few or too many property values. Expecting 4 values, but got {property_count:?}")]
+    InvalidNumberOfPropertiesForWeightBand {
+        input: String,
+        property_count: usize,
+    },
+
+    /// Indicates that a string containing weight band information has an invalid value for one of the weight limits.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the weight limit
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the weight limit of a weight band: {property_value:?}. Expected a positive floating point number.")]
+    InvalidWeightBandWeight {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing weight band information has an invalid value for the cost.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the cost
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for the cost of a weight band: {property_value:?}. Expected a positive floating point number.")]
+    InvalidWeightBandCost {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that a string containing carton information has too few or too many property values.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_count' - The number of property values that were found in the input string
+    #[error("The provided input string {input:?} contains too few or too many property values. Expecting 3 values, but got {property_count:?}")]
+    InvalidNumberOfPropertiesForCarton {
+        input: String,
+        property_count: usize,
+    },
+
+    /// Indicates that a string containing carton information has an invalid value for the maximum length or
+    /// the maximum weight.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the limit
+    /// * 'source' - The source error
+    #[error("The provided input string {input:?} contains an invalid value for a limit of a carton: {property_value:?}. Expected a positive floating point number.")]
+    InvalidCartonLimit {
+        input: String,
+        property_value: String,
+        #[source]
+        source: ParseFloatError,
+    },
+
+    /// Indicates that the input could not be read.
+    ///
+    /// * 'reason' - The description of the underlying I/O error
+    #[error("Failed to read the input: {reason}")]
+    UnableToReadInput { reason: String },
+
+    /// Indicates that the input could not be read after part of it was read.
+    ///
+    /// * 'origin' - The description of the input, e.g. the path of the file
+    /// * 'offset' - The number of bytes that were read before the error
+    /// * 'reason' - The description of the underlying I/O error
+    #[error("Failed to read {origin} at byte {offset}: {reason}")]
+    Io {
+        origin: String,
+        offset: u64,
+        reason: String,
+    },
+
+    /// Indicates that the input did not contain a line with the pack information.
+    #[error("The input did not contain the pack information. Expected the first line to start with one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT], or the sort order, the maximum number of pieces and the maximum weight to be provided with --sort, --max-pieces and --max-weight.")]
+    MissingPackInformation,
+
+    /// Indicates that the output could not be written.
+    ///
+    /// * 'reason' - The description of the underlying I/O error
+    #[error("Failed to write the output: {reason}")]
+    UnableToWriteOutput { reason: String },
+
+    /// Indicates that a daemon request was larger than the maximum allowed size.
+    ///
+    /// * 'length' - The length of the request in bytes
+    /// * 'maximum_length' - The maximum allowed length in bytes
+    #[error("The daemon request has a length of {length} bytes, which is larger than the maximum of {maximum_length} bytes.")]
+    DaemonRequestTooLarge {
+        length: usize,
+        maximum_length: usize,
+    },
+
+    /// Indicates that the daemon could not start listening on its socket.
+    ///
+    /// * 'socket_path' - The path of the socket
+    /// * 'reason' - The description of the underlying error
+    #[error("Failed to start the daemon on the socket {socket_path:?}: {reason}")]
+    UnableToStartDaemon { socket_path: String, reason: String },
+
+    /// Indicates that a command line argument was not recognised.
+    ///
+    /// * 'argument' - The unknown argument
+    #[error("The command line argument {argument:?} is not known.")]
+    UnknownArgument { argument: String },
+
+    /// Indicates that a command line argument which requires a value was not followed by a value.
+    ///
+    /// * 'argument' - The argument that is missing its value
+    #[error("The command line argument {argument:?} requires a value, but none was provided.")]
+    MissingArgumentValue { argument: String },
+
+    /// Indicates that the value of a command line argument is not valid.
+    ///
+    /// * 'argument' - The argument
+    /// * 'value' - The invalid value
+    #[error("The value {value:?} of the command line argument {argument:?} is not valid.")]
+    InvalidArgumentValue { argument: String, value: String },
+
+    /// Indicates that the output format provided on the command line is not known.
+    ///
+    /// * 'format' - The unknown format
+    #[error("The output format {format:?} is not known. Expected one of [text, json, markdown, worksheet, cbor, arrow, parquet].")]
+    UnknownOutputFormat { format: String },
+
+    /// Indicates that a file could not be opened.
+    ///
+    /// * 'path' - The path of the file
+    /// * 'reason' - The description of the underlying error
+    #[error("Failed to open the file {path:?}: {reason}")]
+    UnableToOpenFile { path: String, reason: String },
+
+    /// Indicates that the batch manifest is not valid.
+    ///
+    /// * 'reason' - The description of the problem with the manifest
+    #[error("The batch manifest is not valid: {reason}")]
+    InvalidBatchManifest { reason: String },
+
+    /// Indicates that the configuration file is not valid.
+    ///
+    /// * 'reason' - The description of the problem with the configuration file
+    #[error("The configuration file is not valid: {reason}")]
+    InvalidConfigurationFile { reason: String },
+
+    /// Indicates that the requested profile is not defined in the configuration file.
+    ///
+    /// * 'profile' - The name of the profile
+    #[error("The profile {profile:?} is not defined in the configuration file.")]
+    UnknownProfile { profile: String },
+
+    /// Indicates that a planner configuration could not be written to, or read from, TOML.
+    ///
+    /// * 'reason' - The reason why the configuration is not valid
+    #[error("The planner configuration is not valid: {reason}")]
+    InvalidPlannerConfig { reason: String },
+
+    /// Indicates that a number is not written in the number format of the input, e.g. it uses scientific
+    /// notation while that isn't allowed, or the thousands are grouped incorrectly.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the number
+    #[error("The provided input string {input:?} contains a number that doesn't match the number format of the input: {property_value:?}.")]
+    InvalidNumberFormat {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a number is too large, or too small, to be stored.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the number
+    #[error("The provided input string {input:?} contains a number that is out of range: {property_value:?}.")]
+    NumberOutOfRange {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a number is not a finite number, e.g. `NaN` or `inf`.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the number
+    #[error("The provided input string {input:?} contains a number that is not finite: {property_value:?}. NaN and infinite values are not allowed.")]
+    NonFiniteNumber {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a string containing pack information has an invalid value for one of the number format
+    /// attributes.
+    ///
+    /// * 'input' - The input string
+    /// * 'attribute' - The name of the attribute
+    /// * 'property_value' - The string containing the 'value' of the attribute
+    #[error("The provided input string {input:?} contains an invalid value for the number format attribute {attribute:?}: {property_value:?}.")]
+    InvalidNumberFormatAttribute {
+        input: String,
+        attribute: String,
+        property_value: String,
+    },
+
+    /// Indicates that the quantity of an item uses a unit that isn't formatted correctly.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the quantity
+    #[error("The provided input string {input:?} contains an invalid unit for the quantity of an item: {property_value:?}. Expected [quantity] [unit]([eaches per unit]) with at least 1 each per unit.")]
+    InvalidItemUnit {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a bulk item, which has '-' as its length, doesn't have a valid total weight or weight
+    /// per kilogram.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the invalid quantity or weight
+    #[error("The provided input string {input:?} contains an invalid bulk quantity or weight: {property_value:?}. Expected TOTAL_KG=[total weight] as the quantity and [weight]/kg as the weight, both greater than 0.")]
+    InvalidBulkItem {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that an item that is cut from a total length doesn't have a valid total length, weight per
+    /// unit of length or cut increment.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the invalid length, quantity or weight
+    #[error("The provided input string {input:?} contains an invalid cut item length, quantity or weight: {property_value:?}. Expected '-' or the cut increment as the length, TOTAL_LENGTH=[total length] as the quantity and [weight]/length as the weight, all greater than 0.")]
+    InvalidCutItem {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that an item that is cut from a total length would be cut into more pieces than a batch can
+    /// hold.
+    ///
+    /// * 'item_id' - The ID of the item
+    /// * 'pieces' - The number of pieces into which the total length would be cut
+    #[error("The item {item_id:?} would be cut into {pieces} pieces, while a batch holds at most 2147483647 pieces. Expected a shorter total length, or a pack that takes longer or heavier pieces.")]
+    TooManyCutPieces { item_id: String, pieces: f64 },
+
+    /// Indicates that the output file exists while existing files should be kept.
+    ///
+    /// * 'path' - The path of the output file
+    #[error("The output file {path:?} already exists. Remove the file, or use --overwrite to replace it.")]
+    OutputFileExists { path: String },
+
+    /// Indicates that a file, or stream, is compressed in a format that isn't supported by this build.
+    ///
+    /// * 'path' - The path of the file, or a description of the stream
+    /// * 'compression' - The compression format
+    #[error("The data in {path:?} is compressed with {compression}, which isn't supported by this build. Enable the '{compression}' feature to support it.")]
+    UnsupportedCompression { path: String, compression: String },
+
+    /// Indicates that a table of items, i.e. an Arrow IPC or a Parquet file, couldn't be read or doesn't
+    /// describe the pack and the items.
+    ///
+    /// * 'origin' - The origin of the table, e.g. the path of the file
+    /// * 'reason' - The reason why the table is invalid
+    #[error("The table in {origin:?} is invalid: {reason}")]
+    InvalidTable { origin: String, reason: String },
+
+    /// Indicates that the input is a table in a format that isn't supported by this build.
+    ///
+    /// * 'origin' - The origin of the table, e.g. the path of the file
+    /// * 'format' - The format of the table
+    #[error("Unable to read {origin:?} because the {format} format isn't supported by this build. Enable the '{format}' feature to support it.")]
+    UnsupportedTableFormat { origin: String, format: String },
+
+    /// Indicates that a request to an HTTP endpoint failed.
+    ///
+    /// * 'url' - The URL of the endpoint
+    /// * 'reason' - The description of the underlying error
+    #[error("The request to {url:?} failed: {reason}")]
+    RemoteRequestFailed { url: String, reason: String },
+
+    /// Indicates that the URL of an object in an object store is not formatted correctly.
+    ///
+    /// * 'url' - The URL of the object
+    #[error("The provided URL {url:?} is not a valid object URL. Expected s3://[bucket]/[key] or gs://[bucket]/[key].")]
+    InvalidObjectUrl { url: String },
+
+    /// Indicates that the credentials of an object store are not set in the environment.
+    ///
+    /// * 'variable' - The name of the environment variable that is missing
+    #[error(
+        "Unable to access the object store because the environment variable {variable} isn't set."
+    )]
+    MissingObjectStoreCredentials { variable: String },
+
+    /// Indicates that an object store URL was provided while this build doesn't support object stores.
+    ///
+    /// * 'url' - The URL of the object
+    #[error("Unable to use the object {url:?} because object stores aren't supported by this build. Enable the 'object-store' feature to support them.")]
+    UnsupportedObjectStore { url: String },
+
+    /// Indicates that the items returned by an HTTP endpoint are not valid.
+    ///
+    /// * 'url' - The URL of the endpoint
+    /// * 'reason' - The description of the problem with the items
+    #[error("The items returned by {url:?} are not valid: {reason}")]
+    InvalidRemoteOrder { url: String, reason: String },
+
+    /// Indicates that an HTTP endpoint was provided while this build doesn't support HTTP.
+    ///
+    /// * 'url' - The URL of the endpoint
+    #[error("Unable to use the endpoint {url:?} because HTTP isn't supported by this build. Enable the 'http' feature to support it.")]
+    UnsupportedHttp { url: String },
+
+    /// Indicates that a CBOR document was provided, or requested, while this build doesn't support CBOR.
+    #[error("Unable to use CBOR because it isn't supported by this build. Enable the 'cbor' feature to support it.")]
+    UnsupportedCbor,
+
+    /// Indicates that the schema of an output was requested while this build doesn't support schemas.
+    #[error("Unable to describe the output because schemas aren't supported by this build. Enable the 'schema' feature to support them.")]
+    UnsupportedSchema,
+
+    /// Indicates that a signing key was provided while this build doesn't support signatures.
+    ///
+    /// * 'path' - The path of the signing key
+    #[error("Unable to sign the plan with the key {path:?} because signatures aren't supported by this build. Enable the 'sign' feature to support it.")]
+    UnsupportedSigning { path: String },
+
+    /// Indicates that the signing key is not an ed25519 private key in a PKCS#8 PEM file.
+    ///
+    /// * 'path' - The path of the signing key
+    /// * 'reason' - The description of the underlying error
+    #[error("The signing key {path:?} is not valid: {reason}")]
+    InvalidSigningKey { path: String, reason: String },
+
+    /// Indicates that an item that is pinned to a pack was provided to the incremental planner, which
+    /// cannot place items in packs that may already be closed.
+    ///
+    /// * 'item_id' - The ID of the pinned item
+    #[error("The item {item_id:?} is pinned to a pack, which isn't supported when the items are planned as they arrive.")]
+    PinnedItemInIncrementalPlan { item_id: String },
+
+    /// Indicates that an item doesn't fit in an empty pack, e.g. because it is heavier or longer than a pack
+    /// allows.
+    ///
+    /// * 'item_id' - The ID of the item
+    #[error("The item {item_id:?} doesn't fit in an empty pack.")]
+    ItemDoesNotFitInEmptyPack { item_id: String },
+
+    /// Indicates that a pack can't hold any weight, because its maximum weight is not more than its tare
+    /// weight, e.g. because the maximum weight is 0.
+    ///
+    /// * 'maximum_weight' - The maximum weight of the pack
+    /// * 'tare_weight' - The tare weight of the pack
+    #[error("The maximum weight of a pack is {maximum_weight} with a tare weight of {tare_weight}, which leaves no weight for the items. Expected a maximum weight greater than 0 and greater than the tare weight, on the pack line or with --max-weight.")]
+    PackHasNoWeightCapacity {
+        maximum_weight: f64,
+        tare_weight: f64,
+    },
+
+    /// Indicates that a pack can't hold any pieces, because its maximum number of pieces is 0 or less.
+    ///
+    /// * 'maximum_number_of_pieces' - The maximum number of pieces of the pack
+    #[error("The maximum number of pieces of a pack is {maximum_number_of_pieces}, which leaves no room for the items. Expected a maximum number of pieces greater than 0, on the pack line or with --max-pieces.")]
+    PackHasNoPieceCapacity { maximum_number_of_pieces: i32 },
+
+    /// Indicates that none of the items fit in an empty pack, which usually means that the limits of the pack
+    /// are wrong.
+    ///
+    /// * 'item_id' - The ID of the first item that doesn't fit
+    /// * 'limit' - The limit of the pack that the item exceeds
+    /// * 'reason' - The description of how the item exceeds the limit
+    #[error("None of the items fit in an empty pack. The first item, {item_id:?}, exceeds the {limit} limit: {reason}.")]
+    NoItemFitsInEmptyPack {
+        item_id: String,
+        limit: String,
+        reason: String,
+    },
+
+    /// Indicates that the input is written in a version of the input format that isn't supported.
+    ///
+    /// * 'version' - The version provided in the version directive
+    #[error("The input is written in version {version} of the input format, which isn't supported. Expected one of [1, 2].")]
+    UnsupportedInputVersion { version: u32 },
+
+    /// Indicates that a version directive isn't formatted correctly, or isn't the first line of the input.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid version directive. Expected #VERSION [version] on the first line of the input.")]
+    InvalidVersionDirective { input: String },
+
+    /// Indicates that a line of an input in version 1 of the input format contains attributes, which were
+    /// introduced in version 2.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} contains attributes, which require version 2 of the input format. Start the input with #VERSION 2, or without a version directive, to use attributes.")]
+    AttributesRequireVersion2 { input: String },
+
+    /// Indicates that a locale directive isn't formatted correctly, or doesn't come before the pack
+    /// information.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid locale directive. Expected #LOCALE [locale] before the pack information, in version 2 or later of the input format.")]
+    InvalidLocaleDirective { input: String },
+
+    /// Indicates that the locale of a locale directive is not known.
+    ///
+    /// * 'input' - The input string
+    /// * 'locale' - The locale that is not known
+    #[error("The locale {locale:?} in the provided input string {input:?} is not known. Expected one of [en-US, en-GB, en-AU, en-CA, ja-JP, zh-CN, de-DE, de-AT, nl-NL, nl-BE, es-ES, it-IT, pt-BR, da-DK, de-CH].")]
+    UnknownLocale { input: String, locale: String },
+
+    /// Indicates that a line that refers to a kit is not formatted correctly.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid kit line. Expected KIT:[kit id],[number of kits].")]
+    InvalidKitLine { input: String },
+
+    /// Indicates that a line of the bill of materials is not formatted correctly.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid bill of materials line. Expected [kit id],[item id],[length],[quantity per kit],[weight], optionally followed by [key]=[value] attributes. Bulk items can't be part of a kit.")]
+    InvalidBillOfMaterialsLine { input: String },
+
+    /// Indicates that the input refers to a kit that isn't in the bill of materials.
+    ///
+    /// * 'kit_id' - The ID of the kit
+    #[error("The kit {kit_id:?} is not in the bill of materials.")]
+    UnknownKit { kit_id: String },
+
+    /// Indicates that a line that refers to a substitution group is not formatted correctly.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid substitution group line. Expected GROUP:[group id],[number of items].")]
+    InvalidSubstitutionGroupLine { input: String },
+
+    /// Indicates that a line of the substitution groups is not formatted correctly.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid substitution line. Expected [group id],[item id],[length],[available quantity],[weight], optionally followed by [key]=[value] attributes. Bulk items can't be part of a substitution group.")]
+    InvalidSubstitutionLine { input: String },
+
+    /// Indicates that a line of the item aliases is not formatted correctly, or repeats an alias.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid alias line. Expected [alias],[canonical id], with each alias listed once and without aliases of aliases.")]
+    InvalidAliasLine { input: String },
+
+    /// Indicates that the input refers to a substitution group that isn't in the substitution groups.
+    ///
+    /// * 'group_id' - The ID of the group
+    #[error("The substitution group {group_id:?} is not in the substitution groups.")]
+    UnknownSubstitutionGroup { group_id: String },
+
+    /// Indicates that the members of a substitution group don't have enough items for the requested number.
+    ///
+    /// * 'group_id' - The ID of the group
+    /// * 'count' - The number of requested items that none of the members can supply
+    #[error("The members of the substitution group {group_id:?} are {count} items short of the requested number.")]
+    InsufficientSubstitutes { group_id: String, count: i32 },
+
+    /// Indicates that the components of a kit, which should be kept together, don't fit in an empty pack.
+    ///
+    /// * 'kit_id' - The ID of the kit
+    #[error("The components of the kit {kit_id:?} don't fit together in an empty pack.")]
+    KitDoesNotFitInEmptyPack { kit_id: String },
+
+    /// Indicates that a nesting rule is not formatted correctly.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid nesting rule. Expected [inner item id],[outer item id] with two different item IDs.")]
+    InvalidNestingRule { input: String },
+
+    /// Indicates that a filter expression is not formatted correctly.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid filter. Expected [property] [comparison] [number], e.g. 'weight < 30', with a property of length, weight, count, value or width and a comparison of <, <=, >, >=, == or !=.")]
+    InvalidItemFilter { input: String },
+
+    /// Indicates that a map expression is not formatted correctly, or divides by zero.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid map. Expected [property] [operator] [number], e.g. 'count *= 2', with a property of length, weight, count, value or width and an operator of =, +=, -=, *= or /=.")]
+    InvalidItemMap { input: String },
+
+    /// Indicates that a map expression gives a property of an item a negative value, or a value that is not a
+    /// finite number, e.g. a weight below zero after `weight -= 5`.
+    ///
+    /// * 'expression' - The map expression
+    /// * 'item_id' - The ID of the item
+    /// * 'value' - The value that the expression gives the property
+    #[error("The map '{expression}' gives the item {item_id} a value of {value}. The properties of an item must be finite numbers that are not negative.")]
+    InvalidMappedValue {
+        expression: String,
+        item_id: String,
+        value: f64,
+    },
+
+    /// Indicates that two batches of items with the same ID have a different length or weight.
+    ///
+    /// * 'item_id' - The ID of the items
+    /// * 'length' - The length of the items in the first batch
+    /// * 'weight' - The weight of the items in the first batch
+    /// * 'other_length' - The length of the items in the conflicting batch
+    /// * 'other_weight' - The weight of the items in the conflicting batch
+    #[error("The item {item_id:?} is provided with a length of {length} and a weight of {weight}, and with a length of {other_length} and a weight of {other_weight}. Items with the same ID should have the same length and weight.")]
+    ConflictingDuplicateItem {
+        item_id: String,
+        length: f64,
+        weight: f64,
+        other_length: f64,
+        other_weight: f64,
+    },
+
+    /// Indicates that a line of the class limits is not formatted correctly, or repeats a class.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid class limit. Expected [class],[maximum number of pieces],[maximum weight] with positive limits, or '-' to keep the limit of the pack, and each class only once.")]
+    InvalidClassLimits { input: String },
+
+    /// Indicates that a line of the delivery stops is not formatted correctly, or repeats a destination.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid delivery stop. Expected [destination],[stop number] with a stop number of at least 1, and each destination only once.")]
+    InvalidDeliveryStop { input: String },
+
+    /// Indicates that a line of the destination limits is not formatted correctly, or repeats a destination.
+    ///
+    /// * 'input' - The input string
+    #[error("The provided input string {input:?} is not a valid destination limit. Expected [destination],[maximum number of pieces],[maximum weight] with positive limits, or '-' for no limit, and each destination only once.")]
+    InvalidDestinationLimits { input: String },
+
+    /// Indicates that the packs are sequenced by their delivery stop, but a destination has no stop.
+    ///
+    /// * 'destination' - The destination without a stop
+    #[error("The destination {destination:?} doesn't have a delivery stop.")]
+    UnknownDestination { destination: String },
+
+    /// Indicates that the sequence file doesn't contain a valid pack number.
+    ///
+    /// * 'path' - The path of the sequence file
+    /// * 'content' - The content of the sequence file
+    #[error(
+        "The sequence file {path:?} contains {content:?} instead of the last used pack number."
+    )]
+    InvalidSequenceFile { path: String, content: String },
+
+    /// Indicates that a string containing item information has an invalid value for the width of the item.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the width
+    #[error("The provided input string {input:?} contains an invalid value for the width of an item: {property_value:?}. Expected a positive floating point number.")]
+    InvalidItemWidth {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a string containing pack information has an invalid sheet size.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the sheet size
+    #[error("The provided input string {input:?} contains an invalid sheet size: {property_value:?}. Expected [length]x[width] with two positive floating point numbers.")]
+    InvalidSheetSize {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a string containing pack information has invalid pack dimensions.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The string containing the 'value' for the dimensions
+    #[error("The provided input string {input:?} contains invalid pack dimensions: {property_value:?}. Expected [length]x[width]x[height] with three positive floating point numbers.")]
+    InvalidPackDimensions {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that the items are laid out on sheets, but an item doesn't have a width.
+    ///
+    /// * 'item_id' - The ID of the item
+    #[error("The item {item_id:?} doesn't have a width, which is required when the items are laid out on sheets.")]
+    MissingItemWidth { item_id: String },
+
+    /// Indicates that the items are laid out on sheets, but an item is a bulk item or is pinned to a pack.
+    ///
+    /// * 'item_id' - The ID of the item
+    #[error("The item {item_id:?} is a bulk item or is pinned to a pack, which isn't supported when the items are laid out on sheets.")]
+    UnsupportedSheetItem { item_id: String },
+
+    /// Indicates that the endpoint that serves the metrics could not be started.
+    ///
+    /// * 'address' - The address on which the endpoint should listen
+    /// * 'reason' - The description of the underlying error
+    #[error("Failed to start the metrics endpoint on {address:?}: {reason}")]
+    UnableToStartMetricsEndpoint { address: String, reason: String },
+
+    /// Indicates that the checkpoint file could not be read as a checkpoint.
+    ///
+    /// * 'path' - The path of the checkpoint file
+    /// * 'reason' - The description of the underlying error
+    #[error("The checkpoint {path:?} is not valid: {reason}")]
+    InvalidCheckpoint { path: String, reason: String },
+
+    /// Indicates that the plan resumes from a checkpoint that was written for different items, pack
+    /// information or rules.
+    ///
+    /// * 'path' - The path of the checkpoint file
+    #[error("The checkpoint {path:?} was written for a different plan.")]
+    CheckpointDoesNotMatchPlan { path: String },
+
+    /// Indicates that the plan is checkpointed, but an item is a bulk item or is pinned to a pack.
+    ///
+    /// * 'item_id' - The ID of the item
+    #[error("The item {item_id:?} is a bulk item or is pinned to a pack, which isn't supported when the plan is checkpointed.")]
+    UnsupportedCheckpointItem { item_id: String },
+
+    /// Indicates that two command line arguments were provided that can't be used together.
+    ///
+    /// * 'argument' - The argument
+    /// * 'other_argument' - The argument that can't be used together with the first argument
+    #[error(
+        "The command line argument {argument:?} can't be used together with {other_argument:?}."
+    )]
+    IncompatibleArguments {
+        argument: String,
+        other_argument: String,
+    },
+
+    /// Indicates that a command line argument was provided without another argument that it requires.
+    ///
+    /// * 'argument' - The argument
+    /// * 'required_argument' - The argument that is required by the first argument
+    #[error("The command line argument {argument:?} requires {required_argument:?}.")]
+    MissingRequiredArgument {
+        argument: String,
+        required_argument: String,
+    },
+
+    /// Indicates that the ordered quantity of an item is not a whole number, is less than the available
+    /// quantity, or belongs to a bulk item.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The ordered quantity
+    #[error("The provided input string {input:?} contains an invalid ordered quantity: {property_value:?}. Expected a whole number that is at least the available quantity of the item. Bulk items can't have an ordered quantity.")]
+    InvalidOrderedQuantity {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that the pack multiple of an item is not a positive whole number, or belongs to a bulk item.
+    ///
+    /// * 'input' - The input string
+    /// * 'property_value' - The pack multiple
+    #[error("The provided input string {input:?} contains an invalid pack multiple: {property_value:?}. Expected a whole number larger than zero. Bulk items can't have a pack multiple.")]
+    InvalidItemMultiple {
+        input: String,
+        property_value: String,
+    },
+
+    /// Indicates that a plan that should be merged is not a plan in the text format.
+    ///
+    /// * 'path' - The path of the plan
+    /// * 'line' - The line of the plan that is not valid
+    /// * 'reason' - The description of the problem
+    #[error("The plan {path:?} can't be merged because the line {line:?} is not valid: {reason}")]
+    InvalidPlan {
+        path: String,
+        line: String,
+        reason: String,
+    },
+
+    /// Indicates that a line of the input could not be parsed.
+    ///
+    /// * 'line_number' - The 1-based number of the line
+    /// * 'column' - The 1-based column, in characters, of the first character of the field that is not valid
+    /// * 'length' - The number of characters of the field that is not valid
+    /// * 'line' - The line that is not valid
+    /// * 'source' - The error that describes why the line is not valid
+    #[error("Line {line_number}, column {column}: {source}")]
+    InvalidInputLine {
+        line_number: usize,
+        column: usize,
+        length: usize,
+        line: String,
+        #[source]
+        source: Box<Error>,
+    },
+
+    /// Indicates that the items in the packs, together with the items that were left out, don't add up to the
+    /// items that were planned, so that the plan would silently lose items. This is a bug in the planner.
+    ///
+    /// * 'input_items' - The number of items that were planned
+    /// * 'input_weight' - The weight of the items that were planned
+    /// * 'output_items' - The number of items that were packed or left out
+    /// * 'output_weight' - The weight of the items that were packed or left out
+    #[error("The plan doesn't account for all the items. {input_items} items with a weight of {input_weight} were planned, but {output_items} items with a weight of {output_weight} were packed or left out. This is a bug in the planner.")]
+    TotalsNotConserved {
+        input_items: i64,
+        input_weight: f64,
+        output_items: i64,
+        output_weight: f64,
+    },
+
+    /// Indicates that a grade of the packs is not formatted as `[name]=[minimum utilization]`, with a
+    /// percentage between 0 and 100.
+    ///
+    /// * 'input' - The grade that is not valid
+    #[error("The grade {input:?} is not valid. Expected [name]=[minimum utilization], e.g. A=90, with a percentage between 0 and 100.")]
+    InvalidGrade { input: String },
+
+    /// Indicates that the carrier file is not valid.
+    ///
+    /// * 'reason' - The description of the problem with the carrier file
+    #[error("The carrier file is not valid: {reason}")]
+    InvalidCarrierConfiguration { reason: String },
+
+    /// Indicates that the API key of the carrier is not set in the environment.
+    ///
+    /// * 'variable' - The name of the environment variable that should contain the API key
+    #[error("The API key of the carrier is not set. Set the environment variable {variable:?}.")]
+    MissingCarrierApiKey { variable: String },
+
+    /// Indicates that a plan that should be in the JSON format could not be read.
+    ///
+    /// * 'path' - The path of the plan
+    /// * 'reason' - The description of the problem with the plan
+    #[error("The plan {path:?} is not a valid JSON plan: {reason}")]
+    InvalidJsonPlan { path: String, reason: String },
+
+    /// Indicates that the carrier file doesn't contain the address to which a pack should be shipped.
+    ///
+    /// * 'pack_number' - The number of the pack
+    /// * 'destination' - The destination of the pack, if it has one
+    #[error("There is no address to which pack {pack_number} (destination {destination:?}) can be shipped. Add an 'address_to' or an address for the destination to the carrier file.")]
+    MissingShipmentAddress {
+        pack_number: usize,
+        destination: Option<String>,
+    },
+
+    /// Indicates that the format of the diagnostics is not known.
+    ///
+    /// * 'input' - The format that is not known
+    #[error("The diagnostics format {input:?} is not known. Expected one of [plain, pretty].")]
+    UnknownDiagnosticsFormat { input: String },
+}
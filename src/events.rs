@@ -0,0 +1,275 @@
+//! A log of the decisions of the planner, written as one JSON document per line, so that a plan can be
+//! replayed step-by-step, e.g. by a user interface that scrubs through the placements.
+//!
+//! Each event is numbered with a sequence number that increases by one for every event in the log, also
+//! across the plans that share the log, so that the events can be put back in order after they are read.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compression::{ExistingOutput, OutputFile};
+use crate::errors::Error;
+use crate::model::{Pack, PackedItem, PlacementLimit};
+use crate::planner::PlanObserver;
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "events_tests.rs"]
+mod tests;
+
+/// A decision of the planner.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PlanEvent {
+    /// A number of items of a batch were placed in a pack.
+    ItemPlaced {
+        /// The number of the pack in which the items were placed.
+        pack_number: usize,
+
+        /// The ID of the items.
+        item_id: String,
+
+        /// The number of items that were placed.
+        count: i32,
+
+        /// The total weight of the items that were placed.
+        weight: f64,
+
+        /// The weight of the pack after the items were placed.
+        pack_weight: f64,
+
+        /// The number of items in the pack after the items were placed.
+        pack_item_count: i32,
+    },
+
+    /// A limit of the pack, or one of the pack rules, stopped the planner from placing more items of a batch
+    /// in a pack. Follows the placement that the limit applied to.
+    LimitApplied {
+        /// The number of the pack.
+        pack_number: usize,
+
+        /// The ID of the items.
+        item_id: String,
+
+        /// The limit that applied.
+        limit: PlacementLimit,
+
+        /// The number of items of the batch that were left for the next packs.
+        items_left: i32,
+    },
+
+    /// A pack was closed and will not change anymore, unless it is reopened.
+    PackClosed {
+        /// The number of the pack.
+        pack_number: usize,
+
+        /// The weight of the items in the pack.
+        weight: f64,
+
+        /// The number of items in the pack.
+        item_count: i32,
+
+        /// The fraction of the capacity of the pack that is used.
+        utilization: f64,
+    },
+
+    /// A closed pack was opened again to take more items.
+    PackReopened {
+        /// The number of the pack.
+        pack_number: usize,
+    },
+}
+
+/// An event of the log together with its sequence number, which is a single line of the log.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EventRecord {
+    /// The number of the event in the log, starting at 1.
+    pub sequence: u64,
+
+    /// The event.
+    #[serde(flatten)]
+    pub event: PlanEvent,
+}
+
+/// The log to which the events of the planner are written, through the [`PlanObserver`] implementation. The
+/// observer can't return errors, so the first error that occurs while writing is kept, and is returned by
+/// [`EventLog::flush`], after which no more events are written.
+pub struct EventLog {
+    destination: Mutex<Destination>,
+    sequence: AtomicU64,
+    error: Mutex<Option<String>>,
+}
+
+/// The destination to which the events are written.
+enum Destination {
+    Writer(Box<dyn Write + Send>),
+
+    /// A file that is moved into place when the log is finished.
+    File(OutputFile),
+
+    /// The file of the log was moved into place, so no more events can be written.
+    Finished,
+}
+
+impl Destination {
+    fn writer(&mut self) -> io::Result<&mut dyn Write> {
+        match self {
+            Destination::Writer(writer) => Ok(writer),
+            Destination::File(file) => Ok(file),
+            Destination::Finished => Err(io::Error::other("the event log is finished")),
+        }
+    }
+}
+
+impl EventLog {
+    /// Creates a log that writes the events to the writer.
+    pub fn new(writer: impl Write + Send + 'static) -> EventLog {
+        EventLog::with_destination(Destination::Writer(Box::new(writer)))
+    }
+
+    /// Creates a log that writes the events to the file at the given path, compressed if the path ends in
+    /// `.gz` or `.zst`. The file is only moved into place by [`EventLog::finish`]. When existing files are
+    /// kept an error is returned if the file exists.
+    pub fn create(path: &Path, existing_output: ExistingOutput) -> Result<EventLog, Error> {
+        let file = OutputFile::create_with(path, existing_output)?;
+        Ok(EventLog::with_destination(Destination::File(file)))
+    }
+
+    fn with_destination(destination: Destination) -> EventLog {
+        EventLog {
+            destination: Mutex::new(destination),
+            sequence: AtomicU64::new(0),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// Writes the event to the log, numbered with the next sequence number.
+    pub fn record(&self, event: PlanEvent) {
+        let mut error = self.error.lock().expect("The event log is poisoned.");
+        if error.is_some() {
+            return;
+        }
+
+        // The number is taken while the error is locked, so that the events are written in sequence.
+        let record = EventRecord {
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed) + 1,
+            event,
+        };
+        let result = serde_json::to_vec(&record)
+            .map_err(io::Error::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                self.destination
+                    .lock()
+                    .expect("The event log is poisoned.")
+                    .writer()?
+                    .write_all(&line)
+            });
+        if let Err(e) = result {
+            *error = Some(e.to_string());
+        }
+    }
+
+    /// Writes the events that are still buffered, and returns the first error that occurred while the events
+    /// were written, if any.
+    pub fn flush(&self) -> Result<(), Error> {
+        let error = self.error.lock().expect("The event log is poisoned.");
+        if let Some(reason) = &*error {
+            return Err(Error::UnableToWriteOutput {
+                reason: format!("event log: {reason}"),
+            });
+        }
+
+        self.destination
+            .lock()
+            .expect("The event log is poisoned.")
+            .writer()
+            .and_then(|writer| writer.flush())
+            .map_err(|e| Error::UnableToWriteOutput {
+                reason: format!("event log: {e}"),
+            })
+    }
+
+    /// Writes the events that are still buffered and, for a log that was created with [`EventLog::create`],
+    /// moves the file into place, after which no more events can be written. Returns the first error that
+    /// occurred while the events were written, if any.
+    pub fn finish(&self) -> Result<(), Error> {
+        self.flush()?;
+
+        let mut destination = self.destination.lock().expect("The event log is poisoned.");
+        match std::mem::replace(&mut *destination, Destination::Finished) {
+            Destination::File(file) => file.finish().map_err(|e| Error::UnableToWriteOutput {
+                reason: format!("event log: {e}"),
+            }),
+            Destination::Writer(writer) => {
+                *destination = Destination::Writer(writer);
+                Ok(())
+            }
+            Destination::Finished => Ok(()),
+        }
+    }
+}
+
+impl PlanObserver for &EventLog {
+    fn on_item_placed(&mut self, pack: &Pack, placement: &PackedItem) {
+        let item_id = placement.item.id.to_string();
+        self.record(PlanEvent::ItemPlaced {
+            pack_number: pack.number,
+            item_id: item_id.clone(),
+            count: placement.item.count,
+            weight: placement.item.weight * f64::from(placement.item.count),
+            pack_weight: pack.weight,
+            pack_item_count: pack.item_count,
+        });
+
+        if placement.limit != PlacementLimit::None {
+            self.record(PlanEvent::LimitApplied {
+                pack_number: pack.number,
+                item_id,
+                limit: placement.limit,
+                items_left: placement.items_remaining_in_batch - placement.item.count,
+            });
+        }
+    }
+
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        self.record(PlanEvent::PackClosed {
+            pack_number: pack.number,
+            weight: pack.weight,
+            item_count: pack.item_count,
+            utilization: pack.utilization,
+        });
+    }
+
+    fn on_pack_reopened(&mut self, pack: &Pack) {
+        self.record(PlanEvent::PackReopened {
+            pack_number: pack.number,
+        });
+    }
+}
+
+/// Forwards the intermediate steps to another observer, and writes them to the event log.
+pub(crate) struct EventLogObserver<'a> {
+    pub observer: &'a mut dyn PlanObserver,
+    pub log: &'a EventLog,
+}
+
+impl PlanObserver for EventLogObserver<'_> {
+    fn on_item_placed(&mut self, pack: &Pack, placement: &PackedItem) {
+        self.observer.on_item_placed(pack, placement);
+        (&mut self.log).on_item_placed(pack, placement);
+    }
+
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        self.observer.on_pack_closed(pack);
+        (&mut self.log).on_pack_closed(pack);
+    }
+
+    fn on_pack_reopened(&mut self, pack: &Pack) {
+        self.observer.on_pack_reopened(pack);
+        (&mut self.log).on_pack_reopened(pack);
+    }
+}
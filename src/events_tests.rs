@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::*;
+use crate::model::{ItemTemplate, PackSortOrder, PackTemplate};
+use crate::planner::plan_packs_with_observer;
+
+/// Returns the path of an event log, unique to the test, that doesn't exist yet.
+fn create_event_log_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-events-{}-{}.jsonl",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn plan_with_event_log(log: &EventLog) {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,8,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,4,1.5").unwrap(),
+    ];
+
+    plan_packs_with_observer(&items, &pack_template, &[], &mut &*log)
+        .expect("Failed to plan the packs.");
+}
+
+/// A writer that fails every write.
+struct FailingWriter;
+
+impl Write for FailingWriter {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("the disk is full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// EventLog
+
+#[test]
+fn when_planning_with_an_event_log_it_should_write_each_decision_in_sequence() {
+    let path = create_event_log_path("sequence");
+    let log = EventLog::create(&path, ExistingOutput::Overwrite)
+        .expect("Failed to create the event log.");
+    plan_with_event_log(&log);
+    log.finish().expect("Failed to finish the event log.");
+
+    let records: Vec<EventRecord> = fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Failed to read an event."))
+        .collect();
+    let sequence: Vec<u64> = records.iter().map(|r| r.sequence).collect();
+    assert_eq!(sequence, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(
+        records[1].event,
+        PlanEvent::ItemPlaced {
+            pack_number: 1,
+            item_id: "2".to_string(),
+            count: 2,
+            weight: 3.0,
+            pack_weight: 11.0,
+            pack_item_count: 10,
+        }
+    );
+    assert_eq!(
+        records[2].event,
+        PlanEvent::LimitApplied {
+            pack_number: 1,
+            item_id: "2".to_string(),
+            limit: PlacementLimit::PieceCount,
+            items_left: 2,
+        }
+    );
+    assert!(matches!(
+        records[3].event,
+        PlanEvent::PackClosed {
+            pack_number: 1,
+            item_count: 10,
+            ..
+        }
+    ));
+    assert!(matches!(
+        records[5].event,
+        PlanEvent::PackClosed { pack_number: 2, .. }
+    ));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn when_writing_an_event_it_should_write_a_single_line_of_json() {
+    let path = create_event_log_path("json");
+    let log = EventLog::create(&path, ExistingOutput::Overwrite)
+        .expect("Failed to create the event log.");
+    log.record(PlanEvent::PackReopened { pack_number: 3 });
+    log.record(PlanEvent::PackReopened { pack_number: 4 });
+    log.finish().expect("Failed to finish the event log.");
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\"sequence\":1,\"event\":\"pack_reopened\",\"pack_number\":3}\n\
+         {\"sequence\":2,\"event\":\"pack_reopened\",\"pack_number\":4}\n"
+    );
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn when_creating_an_event_log_it_should_only_move_the_file_into_place_when_finished() {
+    let path = create_event_log_path("finish");
+    let log = EventLog::create(&path, ExistingOutput::Overwrite)
+        .expect("Failed to create the event log.");
+    plan_with_event_log(&log);
+    log.flush().expect("Failed to flush the event log.");
+    assert!(!path.exists());
+
+    log.finish().expect("Failed to finish the event log.");
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 6);
+
+    log.record(PlanEvent::PackReopened { pack_number: 1 });
+    assert_eq!(
+        log.flush(),
+        Err(Error::UnableToWriteOutput {
+            reason: "event log: the event log is finished".to_string()
+        })
+    );
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn when_keeping_an_existing_event_log_it_should_return_an_error() {
+    let path = create_event_log_path("existing");
+    fs::write(&path, "earlier events\n").unwrap();
+
+    let result = EventLog::create(&path, ExistingOutput::Keep);
+    assert_eq!(
+        result.err(),
+        Some(Error::OutputFileExists {
+            path: path.display().to_string()
+        })
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "earlier events\n");
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn when_writing_an_event_fails_it_should_return_the_error_when_flushing() {
+    let log = EventLog::new(FailingWriter);
+    plan_with_event_log(&log);
+
+    assert_eq!(
+        log.flush(),
+        Err(Error::UnableToWriteOutput {
+            reason: "event log: the disk is full".to_string()
+        })
+    );
+}
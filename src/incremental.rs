@@ -0,0 +1,343 @@
+//! Planning of items that arrive one batch at a time, e.g. from a conveyor scanner, instead of all at once.
+
+use std::time::{Duration, Instant};
+
+use crate::errors::Error;
+use crate::model::{ItemTemplate, Pack, PackRule, PackTemplate, PlacementLimit, PlanWarning};
+use crate::planner::{bulk_weight_to_place, cut_items, number_of_items_to_place, PlanObserver};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "incremental_tests.rs"]
+mod tests;
+
+/// A function that is called with each pack the moment the incremental planner closes it.
+pub type IncrementalPackClosedHook = Box<dyn FnMut(&Pack) + Send>;
+
+/// Defines when a recently closed pack is reopened for a small batch, so that the last few pieces of an order
+/// don't get a pack of their own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReopenPolicy {
+    /// The time after closing a pack during which the pack may be reopened.
+    pub window: Duration,
+
+    /// The largest number of pieces of a batch for which a pack is reopened.
+    pub maximum_pieces: i32,
+}
+
+/// Places items in packs as they arrive. Each batch that is pushed is placed in the current pack, and a new
+/// pack is started once the current pack cannot take any more items, in the same way as the greedy
+/// algorithm. The placements and the closed packs are reported to the observer that is passed with each
+/// batch, so that closed packs can be sealed while the next items are still arriving.
+///
+/// Functions that should be called the moment a pack is complete, e.g. to print its label, can be registered
+/// with [`IncrementalPlanner::on_pack_closed`] instead of being passed with each batch.
+///
+/// Because the items that are still to come are not known the items are placed in the order in which they
+/// are pushed, regardless of the sort order of the pack template, and packs are never loaded up to the hard
+/// maximum weight. With a [`ReopenPolicy`] a small batch that doesn't fit in the current pack is placed in a
+/// recently closed pack instead, if it fits there as a whole.
+pub struct IncrementalPlanner {
+    pack_template: PackTemplate,
+    rules: Vec<PackRule>,
+    current_pack: Pack,
+    warnings: Vec<PlanWarning>,
+    pack_closed_hooks: Vec<IncrementalPackClosedHook>,
+    reopen_policy: Option<ReopenPolicy>,
+
+    // The packs that may still be reopened, with the time at which they were closed, the oldest first
+    recently_closed: Vec<(Pack, Instant)>,
+}
+
+impl IncrementalPlanner {
+    pub fn new(pack_template: PackTemplate, rules: Vec<PackRule>) -> IncrementalPlanner {
+        IncrementalPlanner {
+            pack_template,
+            rules,
+            current_pack: Pack::new(1),
+            warnings: Vec::new(),
+            pack_closed_hooks: Vec::new(),
+            reopen_policy: None,
+            recently_closed: Vec::new(),
+        }
+    }
+
+    /// Sets the policy by which recently closed packs are reopened for small batches. Without a policy closed
+    /// packs are never reopened.
+    pub fn set_reopen_policy(&mut self, policy: ReopenPolicy) {
+        self.reopen_policy = Some(policy);
+    }
+
+    /// Registers a function that is called with each pack as soon as it is closed, after the observer of the
+    /// batch is notified. The functions are called in the order in which they are registered.
+    pub fn on_pack_closed<F>(&mut self, hook: F)
+    where
+        F: FnMut(&Pack) + Send + 'static,
+    {
+        self.pack_closed_hooks.push(Box::new(hook));
+    }
+
+    /// Returns the pack that is currently being filled.
+    pub fn current_pack(&self) -> &Pack {
+        &self.current_pack
+    }
+
+    /// Returns the warnings that were raised so far.
+    pub fn warnings(&self) -> &[PlanWarning] {
+        &self.warnings
+    }
+
+    /// Places the items of the batch, closing the current pack, and starting a new one, whenever the
+    /// current pack cannot take any more items. A small batch may be placed in a recently closed pack
+    /// instead, see [`IncrementalPlanner::set_reopen_policy`], in which case the observer and the hooks are
+    /// told that the pack is reopened, that the items are placed, and that the pack is closed again. Batches
+    /// without any items are skipped, which is reported as a warning. Returns an error, without placing any
+    /// items, if the items can never be placed.
+    pub fn push(
+        &mut self,
+        template: &ItemTemplate,
+        observer: &mut dyn PlanObserver,
+    ) -> Result<(), Error> {
+        self.prune_recently_closed();
+        if template.count <= 0 {
+            self.warnings.push(PlanWarning::ItemSkipped {
+                item_id: template.id.to_string(),
+                count: template.count,
+            });
+            return Ok(());
+        }
+
+        if template.attributes.pinned_pack.is_some() {
+            return Err(Error::PinnedItemInIncrementalPlan {
+                item_id: template.id.to_string(),
+            });
+        }
+
+        if template.attributes.cut.is_some() {
+            for piece in cut_items(vec![template.clone()], &self.pack_template)? {
+                self.push(&piece, observer)?;
+            }
+            return Ok(());
+        }
+
+        if template.attributes.bulk {
+            return self.push_bulk(template, observer);
+        }
+
+        let (items_in_empty_pack, _) = number_of_items_to_place(
+            &self.pack_template,
+            &Pack::new(0),
+            template,
+            &self.rules,
+            template.attributes.pack_multiple(),
+        );
+        if items_in_empty_pack == 0 {
+            return Err(Error::ItemDoesNotFitInEmptyPack {
+                item_id: template.id.to_string(),
+            });
+        }
+
+        if template.attributes.close_pack_before {
+            if !self.current_pack.is_empty() {
+                self.close_current_pack(observer);
+            }
+
+            // The packs before the directive are complete, so they aren't reopened.
+            self.recently_closed.clear();
+        } else if self.place_in_closed_pack(template, observer) {
+            return Ok(());
+        }
+
+        let mut items_left_from_current_batch = template.count;
+        while items_left_from_current_batch > 0 {
+            let (items_to_pack, limit) = number_of_items_to_place(
+                &self.pack_template,
+                &self.current_pack,
+                template,
+                &self.rules,
+                items_left_from_current_batch,
+            );
+            if items_to_pack > 0 {
+                self.current_pack.add(
+                    &self.pack_template,
+                    template,
+                    items_to_pack,
+                    items_left_from_current_batch,
+                    limit,
+                );
+                observer.on_item_placed(
+                    &self.current_pack,
+                    self.current_pack
+                        .items
+                        .last()
+                        .expect("The pack has just been filled."),
+                );
+                items_left_from_current_batch -= items_to_pack;
+            }
+
+            if items_left_from_current_batch > 0 {
+                self.close_current_pack(observer);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Closes the current pack, if it contains any items, and returns the warnings that were raised while
+    /// planning.
+    pub fn finish(mut self, observer: &mut dyn PlanObserver) -> Vec<PlanWarning> {
+        if !self.current_pack.is_empty() {
+            notify_pack_closed(&self.current_pack, observer, &mut self.pack_closed_hooks);
+        }
+
+        self.warnings
+    }
+
+    /// Places the bulk item, splitting it over the current pack and as many new packs as needed.
+    fn push_bulk(
+        &mut self,
+        template: &ItemTemplate,
+        observer: &mut dyn PlanObserver,
+    ) -> Result<(), Error> {
+        let (weight_in_empty_pack, _) = bulk_weight_to_place(
+            &self.pack_template,
+            &Pack::new(0),
+            template,
+            &self.rules,
+            template.weight,
+        );
+        if weight_in_empty_pack <= 0.0 {
+            return Err(Error::ItemDoesNotFitInEmptyPack {
+                item_id: template.id.to_string(),
+            });
+        }
+
+        if template.attributes.close_pack_before && !self.current_pack.is_empty() {
+            self.close_current_pack(observer);
+        }
+
+        let mut bulk_left = template.weight;
+        loop {
+            let (weight, limit) = bulk_weight_to_place(
+                &self.pack_template,
+                &self.current_pack,
+                template,
+                &self.rules,
+                bulk_left,
+            );
+            if weight > 0.0 {
+                self.current_pack
+                    .add_bulk(&self.pack_template, template, weight, bulk_left, limit);
+                observer.on_item_placed(
+                    &self.current_pack,
+                    self.current_pack
+                        .items
+                        .last()
+                        .expect("The pack has just been filled."),
+                );
+                bulk_left -= weight;
+            }
+
+            if limit == PlacementLimit::None {
+                return Ok(());
+            }
+
+            self.close_current_pack(observer);
+        }
+    }
+
+    /// Places the whole batch in the most recently closed pack that can take it, if the policy allows the
+    /// batch to reopen a pack and the batch doesn't fit in the current pack. Returns `true` if the batch was
+    /// placed.
+    fn place_in_closed_pack(
+        &mut self,
+        template: &ItemTemplate,
+        observer: &mut dyn PlanObserver,
+    ) -> bool {
+        match self.reopen_policy {
+            Some(policy) if template.count <= policy.maximum_pieces => {}
+            _ => return false,
+        }
+
+        let fits_as_a_whole = |pack: &Pack| {
+            let (items_to_pack, limit) = number_of_items_to_place(
+                &self.pack_template,
+                pack,
+                template,
+                &self.rules,
+                template.count,
+            );
+            (items_to_pack == template.count).then_some(limit)
+        };
+        if fits_as_a_whole(&self.current_pack).is_some() {
+            return false;
+        }
+
+        let reopened = self
+            .recently_closed
+            .iter()
+            .rev()
+            .find_map(|(pack, _)| fits_as_a_whole(pack).map(|limit| (pack.number, limit)));
+        let (pack_number, limit) = match reopened {
+            Some(reopened) => reopened,
+            None => return false,
+        };
+
+        let (pack, _) = self
+            .recently_closed
+            .iter_mut()
+            .find(|(pack, _)| pack.number == pack_number)
+            .expect("The pack was found above.");
+        observer.on_pack_reopened(pack);
+        pack.add(
+            &self.pack_template,
+            template,
+            template.count,
+            template.count,
+            limit,
+        );
+        observer.on_item_placed(
+            pack,
+            pack.items.last().expect("The pack has just been filled."),
+        );
+        notify_pack_closed(pack, observer, &mut self.pack_closed_hooks);
+        true
+    }
+
+    fn close_current_pack(&mut self, observer: &mut dyn PlanObserver) {
+        notify_pack_closed(&self.current_pack, observer, &mut self.pack_closed_hooks);
+
+        let next_pack = Pack::new(self.current_pack.number + 1);
+        let closed_pack = std::mem::replace(&mut self.current_pack, next_pack);
+        if self.reopen_policy.is_some() {
+            self.recently_closed.push((closed_pack, Instant::now()));
+        }
+        self.prune_recently_closed();
+    }
+
+    /// Drops the recently closed packs of which the window of the reopen policy has passed, so that the packs
+    /// are only kept while they may be reopened.
+    fn prune_recently_closed(&mut self) {
+        let window = match self.reopen_policy {
+            Some(policy) => policy.window,
+            None => return,
+        };
+
+        // The packs are ordered by the time at which they were closed, so the expired packs come first.
+        let now = Instant::now();
+        let expired = self
+            .recently_closed
+            .partition_point(|(_, closed_at)| now.duration_since(*closed_at) >= window);
+        self.recently_closed.drain(..expired);
+    }
+}
+
+fn notify_pack_closed(
+    pack: &Pack,
+    observer: &mut dyn PlanObserver,
+    hooks: &mut [IncrementalPackClosedHook],
+) {
+    observer.on_pack_closed(pack);
+    for hook in hooks {
+        hook(pack);
+    }
+}
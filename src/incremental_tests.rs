@@ -0,0 +1,345 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::*;
+use crate::model::{PackSortOrder, PackedItem};
+
+/// Records the placements and the closed packs.
+#[derive(Default)]
+struct RecordingObserver {
+    steps: Vec<String>,
+    closed_packs: Vec<Pack>,
+}
+
+impl PlanObserver for RecordingObserver {
+    fn on_item_placed(&mut self, pack: &Pack, placement: &PackedItem) {
+        self.steps.push(format!(
+            "placed {} x {} in {}",
+            placement.item.count, placement.item.id, pack.number
+        ));
+    }
+
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        self.steps.push(format!("closed {}", pack.number));
+        self.closed_packs.push(pack.clone());
+    }
+
+    fn on_pack_reopened(&mut self, pack: &Pack) {
+        self.steps.push(format!("reopened {}", pack.number));
+    }
+}
+
+fn create_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+// IncrementalPlanner::push()
+
+#[test]
+fn when_pushing_items_it_should_close_each_pack_once_it_is_full() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut observer = RecordingObserver::default();
+
+    planner
+        .push(
+            &ItemTemplate::from_str("1,10,8,1.0").unwrap(),
+            &mut observer,
+        )
+        .expect("Failed to push the items.");
+    assert_eq!(observer.steps, vec!["placed 8 x 1 in 1"]);
+    assert!(observer.closed_packs.is_empty());
+
+    planner
+        .push(
+            &ItemTemplate::from_str("2,10,4,1.0").unwrap(),
+            &mut observer,
+        )
+        .expect("Failed to push the items.");
+    assert_eq!(
+        observer.steps,
+        vec![
+            "placed 8 x 1 in 1",
+            "placed 2 x 2 in 1",
+            "closed 1",
+            "placed 2 x 2 in 2"
+        ]
+    );
+    assert_eq!(observer.closed_packs.len(), 1);
+    assert_eq!(observer.closed_packs[0].item_count, 10);
+    assert_eq!(planner.current_pack().number, 2);
+    assert_eq!(planner.current_pack().item_count, 2);
+}
+
+#[test]
+fn when_pushing_an_item_that_closes_the_pack_it_should_start_a_new_pack() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut observer = RecordingObserver::default();
+
+    let mut item = ItemTemplate::from_str("2,10,2,1.0").unwrap();
+    Arc::make_mut(&mut item.attributes).close_pack_before = true;
+
+    planner
+        .push(
+            &ItemTemplate::from_str("1,10,2,1.0").unwrap(),
+            &mut observer,
+        )
+        .unwrap();
+    planner.push(&item, &mut observer).unwrap();
+    assert_eq!(
+        observer.steps,
+        vec!["placed 2 x 1 in 1", "closed 1", "placed 2 x 2 in 2"]
+    );
+}
+
+#[test]
+fn when_pushing_a_batch_without_items_it_should_skip_the_batch_with_a_warning() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut observer = RecordingObserver::default();
+
+    planner
+        .push(
+            &ItemTemplate::from_str("1,10,0,1.0").unwrap(),
+            &mut observer,
+        )
+        .unwrap();
+    assert!(observer.steps.is_empty());
+    assert_eq!(
+        planner.warnings(),
+        &[PlanWarning::ItemSkipped {
+            item_id: "1".to_string(),
+            count: 0
+        }]
+    );
+}
+
+#[test]
+fn when_pushing_an_item_that_is_too_heavy_it_should_return_an_error_without_placing_items() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut observer = RecordingObserver::default();
+
+    let result = planner.push(
+        &ItemTemplate::from_str("1,10,2,60.0").unwrap(),
+        &mut observer,
+    );
+    assert_eq!(
+        result,
+        Err(Error::ItemDoesNotFitInEmptyPack {
+            item_id: "1".to_string()
+        })
+    );
+    assert!(observer.steps.is_empty());
+    assert!(planner.current_pack().is_empty());
+}
+
+#[test]
+fn when_pushing_a_pinned_item_it_should_return_an_error() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut item = ItemTemplate::from_str("1,10,2,1.0").unwrap();
+    Arc::make_mut(&mut item.attributes).pinned_pack = Some(1);
+
+    let result = planner.push(&item, &mut RecordingObserver::default());
+    assert!(matches!(
+        result,
+        Err(Error::PinnedItemInIncrementalPlan { .. })
+    ));
+}
+
+#[test]
+fn when_pushing_a_bulk_item_it_should_split_it_over_the_packs() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut observer = RecordingObserver::default();
+
+    planner
+        .push(
+            &ItemTemplate::from_str("1,10,1,20.0").unwrap(),
+            &mut observer,
+        )
+        .unwrap();
+    planner
+        .push(
+            &ItemTemplate::from_str("3001,-,TOTAL_KG=100,1.0/kg").unwrap(),
+            &mut observer,
+        )
+        .expect("Failed to push the bulk item.");
+
+    let closed_weights: Vec<f64> = observer.closed_packs.iter().map(|p| p.weight).collect();
+    assert_eq!(closed_weights, vec![50.0, 50.0]);
+    assert_eq!(planner.current_pack().weight, 20.0);
+    assert_eq!(planner.current_pack().number, 3);
+}
+
+/// Pushes the items one by one, and returns the steps that were observed.
+fn push_all(planner: &mut IncrementalPlanner, items: &[&str]) -> Vec<String> {
+    let mut observer = RecordingObserver::default();
+    for item in items {
+        planner
+            .push(&ItemTemplate::from_str(item).unwrap(), &mut observer)
+            .expect("Failed to push the items.");
+    }
+
+    observer.steps
+}
+
+fn reopen_policy(maximum_pieces: i32) -> ReopenPolicy {
+    ReopenPolicy {
+        window: Duration::from_secs(60),
+        maximum_pieces,
+    }
+}
+
+#[test]
+fn when_a_small_batch_fits_in_a_recently_closed_pack_it_should_reopen_the_pack() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    planner.set_reopen_policy(reopen_policy(1));
+
+    let steps = push_all(
+        &mut planner,
+        &["1,10,1,45.0", "2,10,2,10.0", "3,10,1,40.0", "4,10,1,15.0"],
+    );
+    assert_eq!(
+        steps,
+        vec![
+            "placed 1 x 1 in 1",
+            "closed 1",
+            "placed 2 x 2 in 2",
+            "closed 2",
+            "placed 1 x 3 in 3",
+            "reopened 2",
+            "placed 1 x 4 in 2",
+            "closed 2"
+        ]
+    );
+    assert_eq!(planner.current_pack().number, 3);
+    assert_eq!(planner.current_pack().weight, 40.0);
+}
+
+#[test]
+fn when_a_batch_is_larger_than_the_policy_allows_it_should_not_reopen_a_pack() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    planner.set_reopen_policy(reopen_policy(1));
+
+    let steps = push_all(&mut planner, &["1,10,1,45.0", "2,10,2,10.0", "3,10,2,20.0"]);
+    assert!(!steps.iter().any(|s| s.starts_with("reopened")));
+    assert_eq!(planner.current_pack().number, 3);
+}
+
+#[test]
+fn when_the_window_has_passed_it_should_not_reopen_a_pack() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    planner.set_reopen_policy(ReopenPolicy {
+        window: Duration::ZERO,
+        maximum_pieces: 1,
+    });
+
+    let steps = push_all(
+        &mut planner,
+        &["1,10,1,45.0", "2,10,2,10.0", "3,10,1,40.0", "4,10,1,15.0"],
+    );
+    assert!(!steps.iter().any(|s| s.starts_with("reopened")));
+    assert_eq!(planner.current_pack().number, 4);
+}
+
+#[test]
+fn when_the_window_has_passed_it_should_drop_the_closed_packs() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    planner.set_reopen_policy(ReopenPolicy {
+        window: Duration::ZERO,
+        maximum_pieces: 1,
+    });
+
+    push_all(
+        &mut planner,
+        &["1,10,1,45.0", "2,10,2,10.0", "3001,-,TOTAL_KG=100,1.0/kg"],
+    );
+    assert_eq!(planner.current_pack().number, 4);
+    assert!(planner.recently_closed.is_empty());
+}
+
+#[test]
+fn when_a_batch_closes_the_pack_it_should_not_reopen_the_packs_before_it() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    planner.set_reopen_policy(reopen_policy(1));
+
+    let mut observer = RecordingObserver::default();
+    planner
+        .push(
+            &ItemTemplate::from_str("1,10,1,30.0").unwrap(),
+            &mut observer,
+        )
+        .unwrap();
+    let mut closing_item = ItemTemplate::from_str("2,10,1,45.0").unwrap();
+    Arc::make_mut(&mut closing_item.attributes).close_pack_before = true;
+    planner.push(&closing_item, &mut observer).unwrap();
+    planner
+        .push(
+            &ItemTemplate::from_str("3,10,1,10.0").unwrap(),
+            &mut observer,
+        )
+        .unwrap();
+
+    assert!(!observer.steps.iter().any(|s| s.starts_with("reopened")));
+    assert_eq!(planner.current_pack().number, 3);
+}
+
+// IncrementalPlanner::on_pack_closed()
+
+#[test]
+fn when_a_pack_closed_hook_is_registered_it_should_be_called_the_moment_each_pack_is_closed() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let labels = Arc::new(Mutex::new(Vec::new()));
+    let printed_labels = Arc::clone(&labels);
+    planner.on_pack_closed(move |pack| {
+        printed_labels.lock().unwrap().push(format!(
+            "pack {} with {} items",
+            pack.number, pack.item_count
+        ))
+    });
+    let mut observer = RecordingObserver::default();
+
+    planner
+        .push(
+            &ItemTemplate::from_str("1,10,14,1.0").unwrap(),
+            &mut observer,
+        )
+        .unwrap();
+    assert_eq!(*labels.lock().unwrap(), vec!["pack 1 with 10 items"]);
+
+    planner.finish(&mut observer);
+    assert_eq!(
+        *labels.lock().unwrap(),
+        vec!["pack 1 with 10 items", "pack 2 with 4 items"]
+    );
+}
+
+// IncrementalPlanner::finish()
+
+#[test]
+fn when_finishing_it_should_close_the_current_pack() {
+    let mut planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut observer = RecordingObserver::default();
+    planner
+        .push(
+            &ItemTemplate::from_str("1,10,2,1.0").unwrap(),
+            &mut observer,
+        )
+        .unwrap();
+
+    let warnings = planner.finish(&mut observer);
+    assert!(warnings.is_empty());
+    assert_eq!(observer.steps, vec!["placed 2 x 1 in 1", "closed 1"]);
+}
+
+#[test]
+fn when_finishing_without_items_it_should_not_close_an_empty_pack() {
+    let planner = IncrementalPlanner::new(create_pack_template(), Vec::new());
+    let mut observer = RecordingObserver::default();
+
+    planner.finish(&mut observer);
+    assert!(observer.closed_packs.is_empty());
+}
@@ -0,0 +1,204 @@
+//! Reading the input while keeping track of the position in the input, so that a read error says where the
+//! input broke off, and retrying the transient read errors of sockets and other network-backed inputs.
+
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+use std::thread;
+use std::time::Duration;
+
+use crate::errors::Error;
+
+#[cfg(test)]
+#[path = "input_tests.rs"]
+mod tests;
+
+// The time waited before the first retry of a transient error. Each following retry waits twice as long.
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// The read error of an [`InputReader`], which is carried inside the [`io::Error`] that the reader returns, so
+/// that the context survives the readers that are wrapped around it, e.g. the decompression.
+#[derive(Debug)]
+struct ReadFailure {
+    origin: String,
+    offset: u64,
+    reason: String,
+}
+
+impl fmt::Display for ReadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to read {} at byte {}: {}",
+            self.origin, self.offset, self.reason
+        )
+    }
+}
+
+impl error::Error for ReadFailure {}
+
+/// Indicates if the error may go away when the read is tried again, e.g. because a socket timed out.
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+/// Returns the context of the [`InputReader`] that the error was returned by, if any.
+fn read_failure(error: &io::Error) -> Option<&ReadFailure> {
+    error
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<ReadFailure>())
+}
+
+/// Returns the error for an I/O error that occurred while reading the input. Errors of an [`InputReader`] keep
+/// the origin and the position of the read, other errors only keep their description.
+pub fn read_error(error: io::Error) -> Error {
+    match read_failure(&error) {
+        Some(failure) => Error::Io {
+            origin: failure.origin.clone(),
+            offset: failure.offset,
+            reason: failure.reason.clone(),
+        },
+        None => Error::UnableToReadInput {
+            reason: error.to_string(),
+        },
+    }
+}
+
+/// Returns the error for an I/O error that occurred while reading the input at the given origin and offset.
+/// Errors of an [`InputReader`] keep their own origin and position, e.g. the position in the data as it is
+/// stored when the data is decompressed.
+pub fn read_error_at(error: io::Error, origin: &str, offset: u64) -> Error {
+    match read_failure(&error) {
+        Some(_) => read_error(error),
+        None => Error::Io {
+            origin: origin.to_string(),
+            offset,
+            reason: error.to_string(),
+        },
+    }
+}
+
+/// A reader that counts the bytes that were read, and adds the origin of the input and the number of bytes
+/// read so far to its errors, see [`read_error`]. Transient errors are retried when retries are set.
+pub struct InputReader<R> {
+    inner: R,
+    origin: String,
+    offset: u64,
+    retries: u32,
+}
+
+impl<R> InputReader<R> {
+    /// Creates a reader that reads from the inner reader. The 'origin' describes the input in the errors, e.g.
+    /// the path of the file.
+    pub fn new(inner: R, origin: &str) -> InputReader<R> {
+        InputReader {
+            inner,
+            origin: origin.to_string(),
+            offset: 0,
+            retries: 0,
+        }
+    }
+
+    /// Retries a read that failed with a transient error up to the given number of times, with a delay that
+    /// doubles with every retry, before the error is returned.
+    pub fn with_retries(mut self, retries: u32) -> InputReader<R> {
+        self.retries = retries;
+        self
+    }
+
+    /// Returns the number of bytes that were read so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the error for an I/O error that occurred while reading from this reader, with the origin and the
+    /// number of bytes read so far, also for errors that don't come from the inner reader, e.g. because the
+    /// input is not valid UTF-8.
+    pub fn error(&self, error: io::Error) -> Error {
+        read_error_at(error, &self.origin, self.offset)
+    }
+
+    /// Waits before the next attempt when the error is transient and retries are left, otherwise returns the
+    /// error with the context of the input.
+    fn retry_or_fail(&self, error: io::Error, attempt: u32) -> io::Result<()> {
+        if is_transient(&error) && attempt < self.retries {
+            thread::sleep(FIRST_RETRY_DELAY * 2u32.pow(attempt.min(16)));
+            return Ok(());
+        }
+
+        // The error of an inner reader already describes where its input broke off.
+        if read_failure(&error).is_some() {
+            return Err(error);
+        }
+
+        Err(io::Error::new(
+            error.kind(),
+            ReadFailure {
+                origin: self.origin.clone(),
+                offset: self.offset,
+                reason: error.to_string(),
+            },
+        ))
+    }
+}
+
+impl<R: Read> Read for InputReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.read(buf) {
+                Ok(length) => {
+                    self.offset += length as u64;
+                    return Ok(length);
+                }
+                Err(e) => self.retry_or_fail(e, attempt)?,
+            }
+            attempt += 1;
+        }
+    }
+}
+
+impl<R: BufRead> InputReader<R> {
+    /// Reads the next line, without the line ending, or returns `None` at the end of the input. A line that is
+    /// not valid UTF-8 returns an error with the offset of the start of the line.
+    pub fn next_line(&mut self) -> Result<Option<String>, Error> {
+        let start = self.offset;
+        let mut line = String::new();
+        let length = self
+            .read_line(&mut line)
+            .map_err(|e| read_error_at(e, &self.origin, start))?;
+        if length == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+
+        Ok(Some(line))
+    }
+}
+
+impl<R: BufRead> BufRead for InputReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let mut attempt = 0;
+        while let Err(e) = self.inner.fill_buf() {
+            self.retry_or_fail(e, attempt)?;
+            attempt += 1;
+        }
+
+        // Returns the bytes that were buffered above, or reads the end of the input again.
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.offset += amount as u64;
+        self.inner.consume(amount);
+    }
+}
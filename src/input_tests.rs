@@ -0,0 +1,156 @@
+use std::io::BufReader;
+
+use super::*;
+
+/// Returns the data up to the given offset, and then fails with the given error a number of times before the
+/// rest of the data is returned.
+struct FailingReader {
+    data: &'static [u8],
+    position: usize,
+    fail_at: usize,
+    kind: io::ErrorKind,
+    failures: u32,
+}
+
+impl FailingReader {
+    fn new(fail_at: usize, kind: io::ErrorKind, failures: u32) -> FailingReader {
+        FailingReader {
+            data: b"1,100,2,1.0\n2,200,3,2.0\n",
+            position: 0,
+            fail_at,
+            kind,
+            failures,
+        }
+    }
+}
+
+impl Read for FailingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.fail_at && self.failures > 0 {
+            self.failures -= 1;
+            return Err(io::Error::new(self.kind, "connection reset"));
+        }
+
+        let end = if self.position < self.fail_at {
+            self.fail_at
+        } else {
+            self.data.len()
+        };
+        let length = buf.len().min(end - self.position);
+        buf[..length].copy_from_slice(&self.data[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+// InputReader
+
+#[test]
+fn when_a_read_fails_it_should_return_the_origin_and_the_offset() {
+    let mut reader = InputReader::new(
+        BufReader::new(FailingReader::new(12, io::ErrorKind::ConnectionReset, 1)),
+        "orders.csv",
+    );
+
+    let mut content = String::new();
+    let error = reader.read_to_string(&mut content).unwrap_err();
+    assert_eq!(
+        read_error(error),
+        Error::Io {
+            origin: "orders.csv".to_string(),
+            offset: 12,
+            reason: "connection reset".to_string(),
+        }
+    );
+}
+
+#[test]
+fn when_a_read_fails_with_a_transient_error_it_should_retry_the_read() {
+    let mut reader = InputReader::new(
+        BufReader::new(FailingReader::new(12, io::ErrorKind::TimedOut, 2)),
+        "the daemon connection",
+    )
+    .with_retries(2);
+
+    let lines: Vec<String> = reader.by_ref().lines().map(|l| l.unwrap()).collect();
+    assert_eq!(lines, vec!["1,100,2,1.0", "2,200,3,2.0"]);
+}
+
+#[test]
+fn when_a_transient_error_persists_it_should_return_the_error() {
+    let mut reader = InputReader::new(
+        FailingReader::new(0, io::ErrorKind::WouldBlock, 3),
+        "the daemon connection",
+    )
+    .with_retries(2);
+
+    let mut length_bytes = [0u8; 4];
+    let error = reader.read_exact(&mut length_bytes).unwrap_err();
+    assert!(matches!(read_error(error), Error::Io { offset: 0, .. }));
+}
+
+#[test]
+fn when_an_inner_reader_fails_it_should_keep_the_origin_and_the_offset_of_the_inner_reader() {
+    let inner = InputReader::new(
+        BufReader::new(FailingReader::new(12, io::ErrorKind::ConnectionReset, 1)),
+        "orders.csv.gz",
+    );
+    let mut reader = InputReader::new(inner, "orders.csv");
+
+    let mut content = String::new();
+    let error = reader.read_to_string(&mut content).unwrap_err();
+    assert!(matches!(
+        read_error(error),
+        Error::Io { origin, offset: 12, .. } if origin == "orders.csv.gz"
+    ));
+}
+
+#[test]
+fn when_reading_lines_it_should_return_the_lines_without_the_line_endings() {
+    let mut reader = InputReader::new(&b"1,100,2,1.0\r\n2,200,3,2.0"[..], "orders.csv");
+
+    assert_eq!(reader.next_line(), Ok(Some("1,100,2,1.0".to_string())));
+    assert_eq!(reader.next_line(), Ok(Some("2,200,3,2.0".to_string())));
+    assert_eq!(reader.next_line(), Ok(None));
+}
+
+#[test]
+fn when_a_line_is_not_valid_utf8_it_should_return_the_origin_and_the_offset() {
+    let mut reader = InputReader::new(&b"1,100,2,1.0\n2,\xff\n"[..], "orders.csv");
+
+    assert!(reader.next_line().unwrap().is_some());
+    assert!(matches!(
+        reader.next_line(),
+        Err(Error::Io { origin, offset: 12, .. }) if origin == "orders.csv"
+    ));
+}
+
+// read_error()
+
+#[test]
+fn when_an_error_has_no_context_it_should_return_the_description() {
+    let error = io::Error::other("disk on fire");
+
+    assert_eq!(
+        read_error(error),
+        Error::UnableToReadInput {
+            reason: "disk on fire".to_string(),
+        }
+    );
+}
+
+// read_error_at()
+
+#[test]
+fn when_an_error_has_no_context_it_should_add_the_given_origin_and_offset() {
+    let error = io::Error::other("out of memory");
+
+    assert_eq!(
+        read_error_at(error, "orders.csv.zst", 0),
+        Error::Io {
+            origin: "orders.csv.zst".to_string(),
+            offset: 0,
+            reason: "out of memory".to_string(),
+        }
+    );
+}
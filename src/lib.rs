@@ -0,0 +1,610 @@
+//! A small library for sorting items into different packs based on their weight and quantity.
+//!
+//! The stable API of the library is in the [`v1`] module.
+
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub mod adaptive;
+pub mod attestation;
+#[cfg(feature = "text-format")]
+pub mod batch;
+pub mod checkpoint;
+pub mod completion;
+pub mod compression;
+pub mod config;
+pub mod diagnostics;
+pub mod errors;
+pub mod events;
+pub mod incremental;
+pub mod input;
+pub mod merge;
+pub mod metrics;
+pub mod model;
+#[cfg(feature = "text-format")]
+pub mod parser;
+pub mod partition;
+pub mod planner;
+pub mod profiles;
+pub mod progress;
+pub mod relaxation;
+pub mod remote;
+pub mod render;
+pub mod restarts;
+pub mod schema;
+pub mod scoring;
+pub mod sequence;
+pub mod shipping;
+pub mod signing;
+pub mod simulation;
+pub mod split;
+pub mod stats;
+pub mod storage;
+pub mod strip;
+pub mod table;
+#[cfg(test)]
+mod test_support;
+pub mod v1;
+
+use crate::attestation::Attestation;
+use crate::checkpoint::{plan_packs_with_checkpoints, PlanCheckpoint};
+use crate::config::PlannerConfig;
+use crate::errors::Error;
+use crate::events::{EventLog, EventLogObserver};
+use crate::metrics::Metrics;
+use crate::model::{
+    find_backorders, find_unpacked_items, BillOfMaterials, ClassLimits, DeliveryStop,
+    DestinationLimits, DuplicateIdPolicy, IdPattern, InfeasibleItemPolicy, InputSection,
+    ItemAliases, ItemFilter, ItemMap, ItemTemplate, NestingRule, PackRule, PackSortOrder,
+    PackTemplate, PlanTotals, PlanWarning, SubstitutionGroups, TieBreak, Totals,
+};
+use crate::planner::{
+    analyze_pack_count, apply_aliases, apply_destination_limits, can_fit, cut_items, exclude_items,
+    expand_kits, filter_and_map_items, offset_pack_numbers, place_infeasible_items,
+    prefer_homogeneous_packs, resolve_duplicate_ids, resolve_substitutions, round_to_multiples,
+    separate_infeasible_items, sequence_packs, sort_items, spread_packs, verify_pack_capacity,
+    verify_some_item_fits, GreedyAlgorithm, NoObserver, PackClosedHook, PackClosedObserver,
+    PackingAlgorithm, PlanObserver,
+};
+use crate::progress::Progress;
+use crate::render::{write_plan_with_totals, write_sections, RenderOptions};
+use crate::sequence::PackSequence;
+use crate::split::SplitOutput;
+use crate::stats::{PlanStatistics, StatisticsFile};
+use crate::strip::StripAlgorithm;
+#[cfg(feature = "text-format")]
+use crate::table::read_sections;
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "lib_tests.rs"]
+mod tests;
+
+/// Contains the settings, read from the command line and the files it refers to, that apply to every plan.
+pub struct Configuration {
+    /// The algorithm that places the items in the packs.
+    pub algorithm: Box<dyn PackingAlgorithm + Send + Sync>,
+
+    /// The rules that restrict which items can be combined in a single pack.
+    pub rules: Vec<PackRule>,
+
+    /// The additional items that should be checked against the remaining capacity of the plan, if any.
+    pub additional_items: Option<Vec<ItemTemplate>>,
+
+    /// The settings that determine how the packs are written.
+    pub render_options: RenderOptions,
+
+    /// Indicates if the plan should be checked against the theoretical minimum number of packs. When the items
+    /// fit exactly in a number of packs, and the plan uses more packs, a warning is raised.
+    pub quick_check: bool,
+
+    /// The sort order that replaces the sort order of the pack information in the input, if any.
+    pub sort_order: Option<PackSortOrder>,
+
+    /// The maximum number of pieces that replaces the one of the pack information in the input, if any.
+    pub maximum_number_of_pieces: Option<i32>,
+
+    /// The maximum weight that replaces the one of the pack information in the input, if any.
+    pub maximum_weight: Option<f64>,
+
+    /// The components of the kits to which the input may refer.
+    pub bill_of_materials: BillOfMaterials,
+
+    /// Indicates if all the components of a kit should be placed in the same pack.
+    pub keep_kits_together: bool,
+
+    /// Indicates if the items that fill whole packs on their own should be placed in packs with a single item
+    /// ID, before the remaining items are mixed.
+    pub prefer_homogeneous: bool,
+
+    /// The members of the substitution groups to which the input may refer.
+    pub substitution_groups: SubstitutionGroups,
+
+    /// The pairs of items that nest inside each other, which are added to the pack information of the input.
+    pub nesting_rules: Vec<NestingRule>,
+
+    /// The limits of the packs that contain items of a class, which are added to the pack information of the
+    /// input.
+    pub class_limits: Vec<ClassLimits>,
+
+    /// The delivery stops of the destinations, if the packs should be numbered in the reverse of the order in
+    /// which they are loaded on the truck.
+    pub delivery_stops: Option<Vec<DeliveryStop>>,
+
+    /// The limits of the total weight and the total number of pieces for each destination in a single run, e.g.
+    /// the daily pickup caps of the carrier. The items that exceed the limits are left for the next run.
+    pub destination_limits: Vec<DestinationLimits>,
+
+    /// The sequence from which the pack numbers are taken, if the packs should continue the numbering of
+    /// earlier plans instead of starting at 1.
+    pub pack_sequence: Option<PackSequence>,
+
+    /// Defines what happens with items that don't fit in an empty pack.
+    pub on_infeasible: InfeasibleItemPolicy,
+
+    /// Defines what happens with batches of items that share an ID.
+    pub duplicate_ids: DuplicateIdPolicy,
+
+    /// Defines the order of the items with the same length when the items are sorted by length.
+    pub tie_break: TieBreak,
+
+    /// The smallest number of packs over which the items are spread, if any, e.g. the number of packers that
+    /// work in parallel.
+    pub minimum_packs: Option<usize>,
+
+    /// The metrics in which the planned items and packs are counted, if the metrics are collected.
+    pub metrics: Option<Arc<Metrics>>,
+
+    /// The progress of the plan that is being made, if the progress is followed.
+    pub progress: Option<Arc<Progress>>,
+
+    /// The function that is called with each pack as soon as the algorithm closes it, if any. The packs are
+    /// numbered in the order in which they are planned, before they are numbered for the delivery stops or
+    /// the pack sequence. Algorithms that don't report the closed packs never call the function.
+    pub pack_closed_hook: Option<PackClosedHook>,
+
+    /// The log to which each placement and each closed pack is written as it happens, if the decisions of the
+    /// planner are recorded, e.g. to replay the plan. Algorithms that don't report their steps write no events.
+    pub event_log: Option<Arc<EventLog>>,
+
+    /// The checkpoints of the plan, if an interrupted plan should be able to resume. A checkpointed plan
+    /// always uses the greedy algorithm, and plans on sheets aren't checkpointed.
+    pub checkpoint: Option<PlanCheckpoint>,
+
+    /// The expressions that select the items that are planned. An item is only planned when it matches all
+    /// the filters.
+    pub item_filters: Vec<ItemFilter>,
+
+    /// The expressions that change the properties of the items before they are planned, in order.
+    pub item_maps: Vec<ItemMap>,
+
+    /// The patterns of the IDs of the items that are left out of the plan, e.g. because they ship separately.
+    pub excluded_items: Vec<IdPattern>,
+
+    /// The canonical IDs of the item IDs that are aliases, which replace the aliases before anything else
+    /// refers to the IDs of the items.
+    pub item_aliases: ItemAliases,
+
+    /// The file to which the statistics of each plan are appended, if the quality of the plans is tracked.
+    pub statistics_file: Option<StatisticsFile>,
+
+    /// The directory to which each pack is also written as a separate file, if any.
+    pub split_output: Option<SplitOutput>,
+
+    /// The SHA-256 hash of the input, if the plan should start with an attestation of how it was made. See
+    /// [`Attestation`].
+    pub input_sha256: Option<String>,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Configuration {
+            algorithm: Box::new(GreedyAlgorithm),
+            rules: Vec::new(),
+            additional_items: None,
+            render_options: RenderOptions::default(),
+            quick_check: false,
+            sort_order: None,
+            maximum_number_of_pieces: None,
+            maximum_weight: None,
+            bill_of_materials: BillOfMaterials::default(),
+            keep_kits_together: false,
+            prefer_homogeneous: false,
+            substitution_groups: SubstitutionGroups::default(),
+            nesting_rules: Vec::new(),
+            class_limits: Vec::new(),
+            delivery_stops: None,
+            destination_limits: Vec::new(),
+            pack_sequence: None,
+            on_infeasible: InfeasibleItemPolicy::default(),
+            duplicate_ids: DuplicateIdPolicy::default(),
+            tie_break: TieBreak::default(),
+            minimum_packs: None,
+            metrics: None,
+            progress: None,
+            pack_closed_hook: None,
+            event_log: None,
+            checkpoint: None,
+            item_filters: Vec::new(),
+            item_maps: Vec::new(),
+            excluded_items: Vec::new(),
+            item_aliases: ItemAliases::default(),
+            statistics_file: None,
+            split_output: None,
+            input_sha256: None,
+        }
+    }
+}
+
+/// Reads the pack and item information from the reader, plans the packs and writes them to the writer. The
+/// input is either the text input or a table of items, see [`read_sections`]. Each section of the input is
+/// planned independently, see [`plan_sections`]. Returns the warnings that were raised while planning.
+#[cfg(feature = "text-format")]
+pub fn plan_input<R: io::BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    configuration: &Configuration,
+) -> Result<Vec<PlanWarning>, Error> {
+    let sections = read_sections(reader, "the input")?;
+    plan_sections(sections, writer, configuration)
+}
+
+/// Plans the packs of each section independently and writes the plans to the writer, in the order of the
+/// sections. When there is more than one section each plan is preceded by the number of its section, and in
+/// the JSON format the plans are written as an array. Returns the warnings of all the sections.
+pub fn plan_sections<W: Write>(
+    mut sections: Vec<InputSection>,
+    writer: &mut W,
+    configuration: &Configuration,
+) -> Result<Vec<PlanWarning>, Error> {
+    if sections.len() == 1 {
+        let section = sections.remove(0);
+        return plan_items(&section.pack_template, section.items, writer, configuration);
+    }
+
+    let format = configuration.render_options.format;
+    let mut plans = Vec::with_capacity(sections.len());
+    let mut warnings = Vec::new();
+    for section in sections {
+        let mut plan = Vec::new();
+        warnings.extend(plan_items(
+            &section.pack_template,
+            section.items,
+            &mut plan,
+            configuration,
+        )?);
+        plans.push(plan);
+    }
+
+    write_sections(writer, &plans, format).map_err(|e: io::Error| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    })?;
+    Ok(warnings)
+}
+
+/// Returns the pack template with the sort order and the limits that the configuration replaces, and with the
+/// nesting rules and the class limits that the configuration adds.
+pub(crate) fn effective_pack_template(
+    pack_template: &PackTemplate,
+    configuration: &Configuration,
+) -> PackTemplate {
+    let mut nesting_rules = pack_template.nesting_rules.clone();
+    nesting_rules.extend(configuration.nesting_rules.iter().cloned());
+    let mut class_limits = pack_template.class_limits.clone();
+    class_limits.extend(configuration.class_limits.iter().cloned());
+    PackTemplate {
+        sort_order: configuration.sort_order.unwrap_or(pack_template.sort_order),
+        maximum_number_of_pieces: configuration
+            .maximum_number_of_pieces
+            .unwrap_or(pack_template.maximum_number_of_pieces),
+        maximum_weight: configuration
+            .maximum_weight
+            .unwrap_or(pack_template.maximum_weight),
+        nesting_rules,
+        class_limits,
+        ..pack_template.clone()
+    }
+}
+
+/// Keeps track of the totals of the items while they are prepared for planning. The steps that replace items
+/// by other items, e.g. a kit by its components, change the input, and the steps that remove items add those
+/// items to the items that are left out.
+struct PreparationTotals {
+    totals: PlanTotals,
+    current: Totals,
+}
+
+impl PreparationTotals {
+    fn new(item_templates: &[ItemTemplate]) -> Self {
+        let backorders = Totals::of_backorders(item_templates);
+        let current = Totals::of_items(item_templates);
+        PreparationTotals {
+            totals: PlanTotals {
+                input: current + backorders,
+                packed: Totals::default(),
+                left_out: backorders,
+            },
+            current,
+        }
+    }
+
+    fn replaced(&mut self, item_templates: &[ItemTemplate]) {
+        let remaining = Totals::of_items(item_templates);
+        self.totals.input = self.totals.input - self.current + remaining;
+        self.current = remaining;
+    }
+
+    fn removed(&mut self, item_templates: &[ItemTemplate]) {
+        let remaining = Totals::of_items(item_templates);
+        self.totals.left_out = self.totals.left_out + (self.current - remaining);
+        self.current = remaining;
+    }
+}
+
+/// Applies the configuration to the pack template and the items, and returns the pack template and the items,
+/// in order, that are planned, together with the warnings that were raised while excluding the items and
+/// selecting the substitutes, and the totals of the input and of the items that were left out.
+pub(crate) fn prepare_items(
+    pack_template: &PackTemplate,
+    item_templates: Vec<ItemTemplate>,
+    configuration: &Configuration,
+) -> Result<
+    (
+        PackTemplate,
+        Vec<ItemTemplate>,
+        Vec<PlanWarning>,
+        PlanTotals,
+    ),
+    Error,
+> {
+    // Without pack information in the input, all of it has to be provided by the configuration.
+    if pack_template.sort_order == PackSortOrder::NotSet
+        && (configuration.sort_order.is_none()
+            || configuration.maximum_number_of_pieces.is_none()
+            || configuration.maximum_weight.is_none())
+    {
+        return Err(Error::MissingPackInformation);
+    }
+
+    let pack_template = effective_pack_template(pack_template, configuration);
+    verify_pack_capacity(&pack_template)?;
+    let pack_template = pack_template.net_limits();
+
+    let mut totals = PreparationTotals::new(&item_templates);
+    let item_templates = apply_aliases(item_templates, &configuration.item_aliases);
+    let (item_templates, mut warnings) =
+        exclude_items(item_templates, &configuration.excluded_items);
+    totals.removed(&item_templates);
+    let item_templates = resolve_duplicate_ids(item_templates, configuration.duplicate_ids)?;
+    let item_templates = cut_items(item_templates, &pack_template)?;
+    totals.replaced(&item_templates);
+    let item_templates = expand_kits(
+        item_templates,
+        &configuration.bill_of_materials,
+        configuration.keep_kits_together,
+    )?;
+    totals.replaced(&item_templates);
+    let (item_templates, substitution_warnings) = resolve_substitutions(
+        item_templates,
+        &configuration.substitution_groups,
+        &pack_template,
+        &configuration.rules,
+        configuration.tie_break,
+    )?;
+    totals.replaced(&item_templates);
+    let item_templates = filter_and_map_items(item_templates, &configuration.item_filters, &[])?;
+    totals.removed(&item_templates);
+    let item_templates = filter_and_map_items(item_templates, &[], &configuration.item_maps)?;
+    totals.replaced(&item_templates);
+    let (item_templates, multiple_warnings) = round_to_multiples(item_templates);
+    totals.removed(&item_templates);
+    let items = sort_items(
+        item_templates,
+        pack_template.sort_order,
+        configuration.tie_break,
+    )?;
+    let (items, destination_warnings) =
+        apply_destination_limits(items, &configuration.destination_limits);
+    totals.removed(&items);
+    warnings.extend(substitution_warnings);
+    warnings.extend(multiple_warnings);
+    warnings.extend(destination_warnings);
+
+    Ok((pack_template, items, warnings, totals.totals))
+}
+
+/// Plans the packs for the given items and writes them to the writer. Returns the warnings that were raised
+/// while planning.
+pub fn plan_items<W: Write>(
+    pack_template: &PackTemplate,
+    item_templates: Vec<ItemTemplate>,
+    writer: &mut W,
+    configuration: &Configuration,
+) -> Result<Vec<PlanWarning>, Error> {
+    let start = Instant::now();
+
+    let planner_configuration = configuration
+        .input_sha256
+        .as_ref()
+        .map(|_| PlannerConfig::from_configuration(pack_template, configuration));
+    // The backorders are those of the order, before the kits and the substitution groups are replaced.
+    let backorders = find_backorders(&item_templates);
+    let (pack_template, items, preparation_warnings, mut totals) =
+        prepare_items(pack_template, item_templates, configuration)?;
+    let pack_template = &pack_template;
+    verify_some_item_fits(&items, pack_template, &configuration.rules)?;
+    let number_of_batches = items.len();
+
+    // Without a policy for the items that don't fit in an empty pack, the algorithm reports the error.
+    let (items, infeasible_items) = match configuration.on_infeasible {
+        InfeasibleItemPolicy::Error => (items, Vec::new()),
+        _ => separate_infeasible_items(items, pack_template, &configuration.rules),
+    };
+    let items = if configuration.prefer_homogeneous {
+        prefer_homogeneous_packs(items, pack_template, &configuration.rules)
+    } else {
+        items
+    };
+    // Flat items are always laid out on the sheets, so the algorithm of the configuration isn't used.
+    let algorithm: &dyn PackingAlgorithm = match pack_template.sheet {
+        Some(_) => &StripAlgorithm,
+        None => configuration.algorithm.as_ref(),
+    };
+    let mut progress = configuration.progress.as_deref();
+    let mut no_observer = NoObserver;
+    let observer: &mut dyn PlanObserver = match &mut progress {
+        Some(progress) => {
+            progress.start(items.iter().map(|t| t.count.max(0) as u64).sum());
+            progress
+        }
+        None => &mut no_observer,
+    };
+    let mut pack_closed_observer;
+    let observer: &mut dyn PlanObserver = match &configuration.pack_closed_hook {
+        Some(hook) => {
+            pack_closed_observer = PackClosedObserver { observer, hook };
+            &mut pack_closed_observer
+        }
+        None => observer,
+    };
+    let mut event_log_observer;
+    let observer: &mut dyn PlanObserver = match &configuration.event_log {
+        Some(log) => {
+            event_log_observer = EventLogObserver { observer, log };
+            &mut event_log_observer
+        }
+        None => observer,
+    };
+    let mut outcome = match &configuration.checkpoint {
+        Some(checkpoint) if pack_template.sheet.is_none() => plan_packs_with_checkpoints(
+            &items,
+            pack_template,
+            &configuration.rules,
+            checkpoint,
+            observer,
+        )?,
+        _ => algorithm.plan_with_observer(&items, pack_template, &configuration.rules, observer)?,
+    };
+    if let Some(event_log) = &configuration.event_log {
+        event_log.flush()?;
+    }
+    outcome.warnings.splice(0..0, preparation_warnings);
+
+    // The oversize packs are not part of the theoretical minimum, so the plan is checked before these
+    // packs are added.
+    if configuration.quick_check {
+        let analysis = analyze_pack_count(&items, pack_template, &outcome.packs);
+        if analysis.is_exact_fit && !analysis.reaches_minimum() {
+            outcome.warnings.push(PlanWarning::ExactFitNotReached {
+                minimum_number_of_packs: analysis.minimum_number_of_packs,
+                number_of_packs: analysis.number_of_packs,
+            });
+        }
+    }
+
+    outcome = place_infeasible_items(
+        outcome,
+        &infeasible_items,
+        pack_template,
+        configuration.on_infeasible,
+    )?;
+
+    if let Some(minimum_packs) = configuration.minimum_packs {
+        outcome = spread_packs(outcome, minimum_packs, pack_template);
+    }
+
+    // Every item that was planned should either be in a pack or be reported as left out, so a plan that
+    // loses items is never written.
+    totals.packed = Totals::of_packs(&outcome.packs);
+    if configuration.on_infeasible == InfeasibleItemPolicy::Skip {
+        totals.left_out = totals.left_out + Totals::of_items(&infeasible_items);
+    }
+    if !totals.is_conserved() {
+        return Err(Error::TotalsNotConserved {
+            input_items: totals.input.items,
+            input_weight: totals.input.weight,
+            output_items: totals.packed.items + totals.left_out.items,
+            output_weight: totals.packed.weight + totals.left_out.weight,
+        });
+    }
+
+    if let Some(delivery_stops) = &configuration.delivery_stops {
+        outcome = sequence_packs(outcome, delivery_stops)?;
+    }
+
+    if let Some(pack_sequence) = &configuration.pack_sequence {
+        let first_pack_number = pack_sequence.reserve(outcome.packs.len())?;
+        outcome = offset_pack_numbers(outcome, first_pack_number - 1);
+    }
+
+    let report = configuration
+        .additional_items
+        .as_ref()
+        .map(|additional_items| {
+            let additional_items = cut_items(
+                apply_aliases(additional_items.clone(), &configuration.item_aliases),
+                pack_template,
+            )?;
+            Ok::<_, Error>(can_fit(
+                &outcome.packs,
+                &additional_items,
+                pack_template,
+                &configuration.rules,
+            ))
+        })
+        .transpose()?;
+
+    // A time limit can stop the restarts early, so the plan is made again with the restarts that were made.
+    let attestation = configuration
+        .input_sha256
+        .as_deref()
+        .zip(planner_configuration)
+        .map(|(input_sha256, mut planner_configuration)| {
+            if let (Some(_), Some(optimality)) =
+                (planner_configuration.strategy.restarts, outcome.optimality)
+            {
+                planner_configuration.strategy.restarts = Some(optimality.attempts - 1);
+            }
+            Attestation::new(input_sha256, planner_configuration)
+        });
+
+    let unpacked_items = find_unpacked_items(&outcome.warnings, &backorders);
+    write_plan_with_totals(
+        writer,
+        &outcome.packs,
+        report.as_ref(),
+        &backorders,
+        &unpacked_items,
+        attestation.as_ref(),
+        Some(&totals),
+        &configuration.render_options,
+    )
+    .map_err(|e: io::Error| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    })?;
+
+    if let Some(split_output) = &configuration.split_output {
+        split_output.write(&outcome.packs, &configuration.render_options)?;
+    }
+
+    if let Some(metrics) = &configuration.metrics {
+        let number_of_items = outcome
+            .packs
+            .iter()
+            .map(|p| p.item_count.max(0) as u64)
+            .sum();
+        metrics.record_plan(number_of_items, outcome.packs.len() as u64);
+    }
+
+    if let Some(statistics_file) = &configuration.statistics_file {
+        statistics_file.append(&PlanStatistics::new(
+            pack_template,
+            configuration,
+            number_of_batches,
+            &outcome,
+            start.elapsed(),
+        ))?;
+    }
+
+    Ok(outcome.warnings)
+}
@@ -0,0 +1,771 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::*;
+use crate::model::{Pack, PackSortOrder, PlacementLimit, PlanOutcome};
+use crate::render::OutputFormat;
+
+/// Places every batch in its own pack, regardless of the capacity of the packs.
+struct PackPerBatchAlgorithm;
+
+impl PackingAlgorithm for PackPerBatchAlgorithm {
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        _rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error> {
+        let packs = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let mut pack = Pack::new(index + 1);
+                pack.add(
+                    pack_template,
+                    item,
+                    item.count,
+                    item.count,
+                    PlacementLimit::None,
+                );
+                pack
+            })
+            .collect();
+
+        Ok(PlanOutcome {
+            packs,
+            warnings: Vec::new(),
+            optimality: None,
+        })
+    }
+}
+
+// plan_input()
+
+#[test]
+fn when_planning_input_it_should_use_the_greedy_algorithm_by_default() {
+    let input = "NATURAL,40,500.0\n1001,6200,30,9.653\n2001,7200,50,11.21\n";
+    let mut output = Vec::new();
+    plan_input(
+        &mut input.as_bytes(),
+        &mut output,
+        &Configuration::default(),
+    )
+    .expect("Failed to plan the input.");
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Pack Number: 1\n1001,6200.0,30,9.7\n2001,7200.0,10,11.2\n"));
+}
+
+#[test]
+fn when_planning_input_with_a_custom_algorithm_it_should_use_that_algorithm() {
+    let input = "NATURAL,40,500.0\n1001,6200,30,9.653\n2001,7200,50,11.21\n";
+    let configuration = Configuration {
+        algorithm: Box::new(PackPerBatchAlgorithm),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "Pack Number: 1\n1001,6200.0,30,9.7\nPack Length: 6200.0, Pack Weight: 289.6\n\n\
+         Pack Number: 2\n2001,7200.0,50,11.2\nPack Length: 7200.0, Pack Weight: 560.5\n\n\
+         Totals: Input Items: 80, Input Weight: 850.1, Packed Items: 80, Packed Weight: 850.1\n\n"
+    );
+}
+
+#[test]
+fn when_planning_input_with_several_sections_it_should_plan_each_section_on_its_own() {
+    let input = "NATURAL,40,500.0\n1001,6200,30,9.653\nNATURAL,10,500.0\n2001,7200,15,11.21\n";
+    let mut output = Vec::new();
+    plan_input(
+        &mut input.as_bytes(),
+        &mut output,
+        &Configuration::default(),
+    )
+    .expect("Failed to plan the input.");
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Section: 1\n\n\
+         Pack Number: 1\n1001,6200.0,30,9.7\nPack Length: 6200.0, Pack Weight: 289.6\n\n\
+         Totals: Input Items: 30, Input Weight: 289.6, Packed Items: 30, Packed Weight: 289.6\n\n\
+         Section: 2\n\n\
+         Pack Number: 1\n2001,7200.0,10,11.2\nPack Length: 7200.0, Pack Weight: 112.1\n\n\
+         Pack Number: 2\n2001,7200.0,5,11.2\nPack Length: 7200.0, Pack Weight: 56.1\n\n\
+         Totals: Input Items: 15, Input Weight: 168.2, Packed Items: 15, Packed Weight: 168.2\n\n"
+    );
+}
+
+#[test]
+fn when_planning_input_with_several_sections_as_json_it_should_write_an_array_of_plans() {
+    let input = "NATURAL,40,500.0\n1001,6200,30,9.653\nNATURAL,10,500.0\n2001,7200,15,11.21\n";
+    let configuration = Configuration {
+        render_options: RenderOptions {
+            format: OutputFormat::Json,
+            ..RenderOptions::default()
+        },
+        ..Configuration::default()
+    };
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+
+    let plans: serde_json::Value = serde_json::from_slice(&output).expect("Invalid JSON.");
+    let plans = plans.as_array().expect("Expected an array of plans.");
+    assert_eq!(plans.len(), 2);
+    assert_eq!(plans[0]["packs"].as_array().unwrap().len(), 1);
+    assert_eq!(plans[1]["packs"].as_array().unwrap().len(), 2);
+}
+
+// plan_items()
+
+#[test]
+fn when_planning_items_it_should_write_the_plan() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 500.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1001,6200,30,9.653").unwrap()];
+
+    let mut output = Vec::new();
+    let warnings = plan_items(
+        &pack_template,
+        items,
+        &mut output,
+        &Configuration::default(),
+    )
+    .expect("Failed to plan the items.");
+
+    assert!(warnings.is_empty());
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Pack Number: 1\n1001,6200.0,30,9.7\nPack Length: 6200.0, Pack Weight: 289.6\n\n\
+         Totals: Input Items: 30, Input Weight: 289.6, Packed Items: 30, Packed Weight: 289.6\n\n"
+    );
+}
+
+#[test]
+fn when_planning_items_with_a_pack_closed_hook_it_should_call_the_hook_with_each_closed_pack() {
+    let input = "NATURAL,40,500.0\n1001,6200,30,9.653\n2001,7200,50,11.21\n";
+    let closed_packs = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded_packs = Arc::clone(&closed_packs);
+    let configuration = Configuration {
+        pack_closed_hook: Some(Arc::new(move |pack: &Pack| {
+            recorded_packs
+                .lock()
+                .unwrap()
+                .push((pack.number, pack.item_count))
+        })),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+
+    assert_eq!(*closed_packs.lock().unwrap(), vec![(1, 40), (2, 40)]);
+}
+
+#[test]
+fn when_planning_items_that_are_partially_available_it_should_plan_the_available_items() {
+    let input = "NATURAL,40,500.0\n1001,6200,3,9.653,ordered=5\n1002,100,2,1.0\n";
+
+    let mut output = Vec::new();
+    let warnings = plan_input(
+        &mut input.as_bytes(),
+        &mut output,
+        &Configuration::default(),
+    )
+    .expect("Failed to plan the items.");
+
+    assert!(warnings.is_empty());
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Pack Number: 1\n1001,6200.0,3,9.7\n1002,100.0,2,1.0\nPack Length: 6200.0, Pack Weight: 31.0\n\n\
+         Backorder: 1001, Ordered: 5, Available: 3, Short: 2\n\n\
+         Unpacked: 1001, Count: 2, Reason: backordered\n\n\
+         Totals: Input Items: 7, Input Weight: 50.3, Packed Items: 5, Packed Weight: 31.0, \
+         Left Out Items: 2, Left Out Weight: 19.3\n\n"
+    );
+}
+
+#[test]
+fn when_planning_items_with_an_input_hash_it_should_start_the_plan_with_the_attestation() {
+    let input = "NATURAL,10,500.0\n1001,100,4,1.0\n";
+    let configuration = Configuration {
+        input_sha256: Some("0123".to_string()),
+        maximum_weight: Some(100.0),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the items.");
+    let output = String::from_utf8(output).unwrap();
+    let header = format!(
+        "Attestation: Tool Version: {}, Input SHA-256: 0123, Timestamp: ",
+        env!("CARGO_PKG_VERSION")
+    );
+    assert!(output.starts_with(&header));
+    assert!(output.contains("\n  maximum_weight = 100.0\n"));
+    assert!(output.contains("\n\nPack Number: 1\n"));
+
+    let configuration = Configuration {
+        render_options: RenderOptions {
+            format: OutputFormat::Json,
+            ..RenderOptions::default()
+        },
+        ..configuration
+    };
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the items.");
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["attestation"]["input_sha256"], "0123");
+    assert_eq!(
+        document["attestation"]["configuration"]["pack"]["maximum_weight"],
+        100.0
+    );
+}
+
+#[test]
+fn when_planning_items_with_a_time_limit_it_should_attest_the_restarts_that_were_made() {
+    let input = "NATURAL,10,500.0\n1001,100,4,1.0\n";
+    let configuration = Configuration {
+        input_sha256: Some("0123".to_string()),
+        algorithm: Box::new(restarts::RestartAlgorithm {
+            restarts: restarts::UNLIMITED_RESTARTS,
+            time_limit: Some(std::time::Duration::from_secs(600)),
+            ..restarts::RestartAlgorithm::default()
+        }),
+        render_options: RenderOptions {
+            format: OutputFormat::Json,
+            ..RenderOptions::default()
+        },
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the items.");
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        document["attestation"]["configuration"]["strategy"]["restarts"],
+        0
+    );
+}
+
+#[test]
+fn when_planning_items_with_a_pack_multiple_it_should_place_full_multiples_and_report_the_leftover()
+{
+    let input = "NATURAL,10,500.0\n1001,100,14,1.0,multiple=4\n";
+
+    let mut output = Vec::new();
+    let warnings = plan_input(
+        &mut input.as_bytes(),
+        &mut output,
+        &Configuration::default(),
+    )
+    .expect("Failed to plan the items.");
+
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::IncompleteMultiple {
+            item_id: "1001".to_string(),
+            multiple: 4,
+            count: 2,
+        }]
+    );
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Pack Number: 1\n1001,100.0,8,1.0\nPack Length: 100.0, Pack Weight: 8.0\n\n\
+         Pack Number: 2\n1001,100.0,4,1.0\nPack Length: 100.0, Pack Weight: 4.0\n\n\
+         Unpacked: 1001, Count: 2, Reason: incomplete-multiple\n\n\
+         Totals: Input Items: 14, Input Weight: 14.0, Packed Items: 12, Packed Weight: 12.0, \
+         Left Out Items: 2, Left Out Weight: 2.0\n\n"
+    );
+}
+
+#[test]
+fn when_planning_items_with_a_tare_weight_it_should_count_the_tare_and_report_the_gross_weight() {
+    let input = "NATURAL,10,10.0,tare=1.5\n1001,100,10,1.0\n";
+
+    let mut output = Vec::new();
+    plan_input(
+        &mut input.as_bytes(),
+        &mut output,
+        &Configuration::default(),
+    )
+    .expect("Failed to plan the items.");
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Pack Number: 1\n1001,100.0,8,1.0\nPack Length: 100.0, Pack Weight: 8.0, Gross Weight: 9.5\n\n\
+         Pack Number: 2\n1001,100.0,2,1.0\nPack Length: 100.0, Pack Weight: 2.0, Gross Weight: 3.5\n\n\
+         Totals: Input Items: 10, Input Weight: 10.0, Packed Items: 10, Packed Weight: 10.0\n\n"
+    );
+}
+
+#[test]
+fn when_planning_items_with_a_sort_order_in_the_configuration_it_should_use_that_sort_order() {
+    let input = "NATURAL,1,50.0\n1,10,1,1.0\n2,30,1,1.0\n3,20,1,1.0\n";
+    let configuration = Configuration {
+        sort_order: Some(PackSortOrder::LongToShort),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Pack Number: 1\n2,30.0,1,1.0\n"));
+    assert!(output.contains("Pack Number: 3\n1,10.0,1,1.0\n"));
+}
+
+#[test]
+fn when_planning_input_without_pack_information_it_should_use_the_pack_of_the_configuration() {
+    let input = "1,10,3,10.0\n2,30,1,10.0\n";
+    let configuration = Configuration {
+        sort_order: Some(PackSortOrder::LongToShort),
+        maximum_number_of_pieces: Some(2),
+        maximum_weight: Some(100.0),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Pack Number: 1\n2,30.0,1,10.0\n1,10.0,1,10.0\n"));
+    assert!(output.contains("Pack Number: 2\n1,10.0,2,10.0\n"));
+}
+
+#[test]
+fn when_planning_input_with_pack_information_it_should_prefer_the_configuration() {
+    let input = "NATURAL,10,500.0\n1,10,3,10.0\n";
+    let configuration = Configuration {
+        maximum_weight: Some(20.0),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Pack Number: 1\n1,10.0,2,10.0\n"));
+    assert!(output.contains("Pack Number: 2\n1,10.0,1,10.0\n"));
+}
+
+#[test]
+fn when_planning_input_without_all_the_pack_information_it_should_return_an_error() {
+    let input = "1,10,3,10.0\n";
+    let configuration = Configuration {
+        sort_order: Some(PackSortOrder::Natural),
+        maximum_weight: Some(20.0),
+        ..Configuration::default()
+    };
+
+    let result = plan_input(&mut input.as_bytes(), &mut Vec::new(), &configuration);
+    assert_eq!(result.err().unwrap(), Error::MissingPackInformation);
+}
+
+#[test]
+fn when_planning_input_with_a_maximum_weight_of_zero_it_should_return_an_error() {
+    let input = "NATURAL,10,500.0\n1,10,3,10.0\n";
+    let configuration = Configuration {
+        maximum_weight: Some(0.0),
+        ..Configuration::default()
+    };
+
+    let result = plan_input(&mut input.as_bytes(), &mut Vec::new(), &configuration);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::PackHasNoWeightCapacity {
+            maximum_weight: 0.0,
+            tare_weight: 0.0
+        }
+    );
+}
+
+#[test]
+fn when_planning_input_of_which_no_item_fits_it_should_return_an_error_before_planning() {
+    let input = "NATURAL,10,5.0\n1,10,3,10.0\n2,10,1,20.0\n";
+    let configuration = Configuration {
+        on_infeasible: InfeasibleItemPolicy::Skip,
+        ..Configuration::default()
+    };
+
+    let result = plan_input(&mut input.as_bytes(), &mut Vec::new(), &configuration);
+    assert!(matches!(
+        result.err().unwrap(),
+        Error::NoItemFitsInEmptyPack { item_id, .. } if item_id == "1"
+    ));
+}
+
+#[test]
+fn when_planning_items_with_filters_and_maps_it_should_plan_the_changed_items() {
+    let input = "NATURAL,10,500.0\n1,10,2,10.0\n2,20,1,50.0\n";
+    let configuration = Configuration {
+        item_filters: vec![ItemFilter::from_str("weight < 30").unwrap()],
+        item_maps: vec![ItemMap::from_str("count *= 3").unwrap()],
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Pack Number: 1\n1,10.0,6,10.0\nPack Length: 10.0, Pack Weight: 60.0\n\n\
+         Totals: Input Items: 7, Input Weight: 110.0, Packed Items: 6, Packed Weight: 60.0, \
+         Left Out Items: 1, Left Out Weight: 50.0\n\n"
+    );
+}
+
+#[test]
+fn when_planning_a_substitution_group_it_should_plan_the_selected_members() {
+    let input = "NATURAL,10,50.0\n1001,100,4,10.0\nGROUP:mug-any-color,5\n";
+    let mut substitution_groups = SubstitutionGroups::default();
+    substitution_groups.groups.insert(
+        "mug-any-color".to_string(),
+        vec![
+            ItemTemplate::from_str("2001,100,4,10.0").unwrap(),
+            ItemTemplate::from_str("2002,100,10,2.0").unwrap(),
+        ],
+    );
+    let configuration = Configuration {
+        substitution_groups,
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    let warnings = plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Pack Number: 1\n1001,100.0,4,10.0\n2002,100.0,5,2.0\nPack Length: 100.0, Pack Weight: 50.0\n\n\
+         Totals: Input Items: 9, Input Weight: 50.0, Packed Items: 9, Packed Weight: 50.0\n\n"
+    );
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::ItemSubstituted {
+            group_id: "mug-any-color".to_string(),
+            item_id: "2002".to_string(),
+            count: 5
+        }]
+    );
+}
+
+#[test]
+fn when_planning_with_a_quick_check_that_misses_an_exact_fit_it_should_return_a_warning() {
+    let input = "NATURAL,10,50.0\n1,10,1,30.0\n2,10,1,15.0\n3,10,1,35.0\n4,10,1,20.0\n";
+    let configuration = Configuration {
+        quick_check: true,
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    let warnings = plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::ExactFitNotReached {
+            minimum_number_of_packs: 2,
+            number_of_packs: 3
+        }]
+    );
+}
+
+#[test]
+fn when_planning_with_homogeneous_packs_it_should_fill_whole_packs_with_a_single_item() {
+    let input = "NATURAL,4,50.0\n1,100,3,1.0\n2,100,5,1.0\n";
+    let configuration = Configuration {
+        prefer_homogeneous: true,
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Pack Number: 1\n1,100.0,3,1.0\nPack Length"));
+    assert!(output.contains("Pack Number: 2\n2,100.0,4,1.0\nPack Length"));
+    assert!(output.contains("Pack Number: 3\n2,100.0,1,1.0\nPack Length"));
+}
+
+#[test]
+fn when_planning_with_an_event_log_it_should_record_the_placements_and_the_closed_packs() {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-lib-events-{}.jsonl",
+        std::process::id()
+    ));
+    let input = "NATURAL,2,50.0\n1,10,3,1.0\n";
+    let configuration = Configuration {
+        event_log: Some(Arc::new(
+            EventLog::create(&path, crate::compression::ExistingOutput::Overwrite).unwrap(),
+        )),
+        ..Configuration::default()
+    };
+
+    plan_input(&mut input.as_bytes(), &mut Vec::new(), &configuration)
+        .expect("Failed to plan the input.");
+    configuration.event_log.unwrap().finish().unwrap();
+    let events: Vec<String> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            record["event"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            "item_placed",
+            "limit_applied",
+            "pack_closed",
+            "item_placed",
+            "pack_closed"
+        ]
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn when_planning_with_a_pack_sequence_it_should_continue_the_pack_numbers() {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-lib-sequence-{}.txt",
+        std::process::id()
+    ));
+    std::fs::write(&path, "37\n").unwrap();
+    let input = "NATURAL,1,50.0\n1,10,2,1.0\n";
+    let configuration = Configuration {
+        pack_sequence: Some(PackSequence::new(&path)),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Pack Number: 38\n"));
+    assert!(output.contains("Pack Number: 39\n"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "39\n");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn when_planning_with_a_split_output_it_should_write_each_pack_to_a_file() {
+    let directory =
+        std::env::temp_dir().join(format!("pack-planner-lib-split-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    let input = "NATURAL,4,50.0\n1,10,6,5.0\n";
+    let configuration = Configuration {
+        split_output: Some(SplitOutput::new(&directory)),
+        ..Configuration::default()
+    };
+
+    let mut plan = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut plan, &configuration)
+        .expect("Failed to plan the input.");
+
+    let first_pack = std::fs::read_to_string(directory.join("pack-1.txt")).unwrap();
+    let second_pack = std::fs::read_to_string(directory.join("pack-2.txt")).unwrap();
+    assert!(String::from_utf8(plan)
+        .unwrap()
+        .starts_with(&format!("{first_pack}{second_pack}Totals: ")));
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_planning_with_a_statistics_file_it_should_append_a_line_per_plan() {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-lib-stats-{}.jsonl",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let input = "NATURAL,4,50.0\n1,10,6,5.0\n2,10,1,10.0\n";
+    let configuration = Configuration {
+        statistics_file: Some(StatisticsFile::new(&path)),
+        ..Configuration::default()
+    };
+
+    for _ in 0..2 {
+        plan_input(&mut input.as_bytes(), &mut Vec::new(), &configuration)
+            .expect("Failed to plan the input.");
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let statistics: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(statistics["number_of_batches"], 2);
+    assert_eq!(statistics["number_of_packs"], 2);
+    assert_eq!(statistics["number_of_items"], 7);
+    assert_eq!(statistics["total_weight"], 40.0);
+    assert_eq!(statistics["weight_fill_rate"], 0.4);
+    assert_eq!(statistics["parameters"]["maximum_number_of_pieces"], 4);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn when_planning_items_that_do_not_fit_with_the_skip_policy_it_should_plan_the_other_items() {
+    let input = "NATURAL,10,50.0\n1,10,2,10.0\n2,10,1,80.0\n";
+    let configuration = Configuration {
+        on_infeasible: InfeasibleItemPolicy::Skip,
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    let warnings = plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "Pack Number: 1\n1,10.0,2,10.0\nPack Length: 10.0, Pack Weight: 20.0\n\n\
+         Unpacked: 2, Count: 1, Reason: infeasible\n\n\
+         Totals: Input Items: 3, Input Weight: 100.0, Packed Items: 2, Packed Weight: 20.0, \
+         Left Out Items: 1, Left Out Weight: 80.0\n\n"
+    );
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::InfeasibleItemSkipped {
+            item_id: "2".to_string(),
+            count: 1
+        }]
+    );
+}
+
+#[test]
+fn when_planning_with_item_aliases_it_should_write_the_original_id_of_the_aliased_items() {
+    let input = "NATURAL,10,50.0\n9001,10,2,10.0\n1001,10,1,10.0\n";
+    let configuration = Configuration {
+        item_aliases: ItemAliases {
+            aliases: HashMap::from([("9001".to_string(), "1001".to_string())]),
+        },
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(
+        "Pack Number: 1\n1001,10.0,2,10.0,ALIAS_OF=9001\n1001,10.0,1,10.0\nPack Length: 10.0, Pack Weight: 30.0\n"
+    ));
+}
+
+#[test]
+fn when_planning_a_cut_item_it_should_place_the_pieces_in_packs() {
+    let input = "NATURAL,10,50.0\n4001,-,TOTAL_LENGTH=60000,0.002/length\n";
+
+    let mut output = Vec::new();
+    plan_input(
+        &mut input.as_bytes(),
+        &mut output,
+        &Configuration::default(),
+    )
+    .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(concat!(
+        "Pack Number: 1\n4001,25000.0,1,50.0\nPack Length: 25000.0, Pack Weight: 50.0\n\n",
+        "Pack Number: 2\n4001,25000.0,1,50.0\nPack Length: 25000.0, Pack Weight: 50.0\n\n",
+        "Pack Number: 3\n4001,10000.0,1,20.0\nPack Length: 10000.0, Pack Weight: 20.0\n"
+    )));
+}
+
+#[test]
+fn when_planning_with_a_minimum_number_of_packs_it_should_spread_the_items() {
+    let input = "NATURAL,10,50.0\n1,10,4,5.0\n";
+    let configuration = Configuration {
+        minimum_packs: Some(2),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(
+        "Pack Number: 1\n1,10.0,2,5.0\nPack Length: 10.0, Pack Weight: 10.0\n\n\
+         Pack Number: 2\n1,10.0,2,5.0\nPack Length: 10.0, Pack Weight: 10.0\n\n"
+    ));
+}
+
+#[test]
+fn when_planning_with_excluded_items_it_should_leave_the_items_out_and_warn() {
+    let input = "NATURAL,10,50.0\n1,10,2,10.0\n20-GLASS,10,1,20.0\n";
+    let configuration = Configuration {
+        excluded_items: vec![IdPattern::new("*-GLASS")],
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    let warnings = plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "Pack Number: 1\n1,10.0,2,10.0\nPack Length: 10.0, Pack Weight: 20.0\n\n\
+         Unpacked: 20-GLASS, Count: 1, Reason: excluded\n\n\
+         Totals: Input Items: 3, Input Weight: 40.0, Packed Items: 2, Packed Weight: 20.0, \
+         Left Out Items: 1, Left Out Weight: 20.0\n\n"
+    );
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::ItemExcluded {
+            item_id: "20-GLASS".to_string(),
+            count: 1
+        }]
+    );
+}
+
+#[test]
+fn when_planning_items_that_do_not_fit_with_the_own_pack_policy_it_should_mark_the_pack() {
+    let input = "NATURAL,10,50.0\n1,10,2,10.0\n2,10,1,80.0\n";
+    let configuration = Configuration {
+        on_infeasible: InfeasibleItemPolicy::OwnPack,
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.ends_with(
+        "Pack Number: 2\n2,10.0,1,80.0\nPack Length: 10.0, Pack Weight: 80.0, Oversize: REVIEW\n\n\
+         Totals: Input Items: 3, Input Weight: 100.0, Packed Items: 3, Packed Weight: 100.0\n\n"
+    ));
+}
+
+#[test]
+fn when_planning_items_on_sheets_it_should_write_the_positions_on_the_sheet() {
+    let input = "NATURAL,10,50.0,sheet=3000x1500\n1,1000,2,1.0,width=1500\n";
+    let configuration = Configuration {
+        algorithm: Box::new(PackPerBatchAlgorithm),
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    plan_input(&mut input.as_bytes(), &mut output, &configuration)
+        .expect("Failed to plan the input.");
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "Pack Number: 1\n1,1000.0,2,1.0\n\
+         Sheet Position: 1 at 0.0,0.0, 1500.0x1000.0, rotated\n\
+         Sheet Position: 1 at 1500.0,0.0, 1500.0x1000.0, rotated\n\
+         Pack Length: 1000.0, Pack Weight: 2.0\n\n\
+         Totals: Input Items: 2, Input Weight: 2.0, Packed Items: 2, Packed Weight: 2.0\n\n"
+    );
+}
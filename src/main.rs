@@ -1,477 +1,1796 @@
-use std::io::{self, BufRead};
-use std::num::{ParseFloatError, ParseIntError};
+use std::env;
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process;
 use std::str::FromStr;
-use std::string::ToString;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use strum::{Display, EnumString, ParseError};
-use thiserror::Error;
+use pack_planner::adaptive::AdaptiveAlgorithm;
+use pack_planner::attestation::input_hash;
+use pack_planner::batch::{
+    batch_notification, parse_batch_manifest, run_batch, write_batch_summary,
+};
+use pack_planner::checkpoint::PlanCheckpoint;
+use pack_planner::completion::BinCompletionAlgorithm;
+use pack_planner::compression::{compress, decompress, open_input, ExistingOutput, OutputFile};
+use pack_planner::diagnostics::{render, DiagnosticsFormat};
+use pack_planner::errors::Error;
+use pack_planner::events::EventLog;
+use pack_planner::input::{read_error, InputReader};
+use pack_planner::merge::{merge_plans, write_merge_summary};
+use pack_planner::metrics::{serve_metrics_request, Health, Metrics, RequestInFlight};
+use pack_planner::model::{
+    BillOfMaterials, DuplicateIdPolicy, Grade, InfeasibleItemPolicy, InputSection, ItemAliases,
+    ItemFilter, ItemMap, PackSortOrder, PlanWarning, SubstitutionGroups, TieBreak,
+};
+use pack_planner::parser::{
+    parse_bill_of_materials, parse_cartons, parse_class_limits, parse_delivery_stops,
+    parse_destination_limits, parse_grades, parse_id_patterns, parse_item_aliases, parse_items,
+    parse_nesting_rules, parse_pack_rules, parse_substitution_groups, parse_weight_bands,
+};
+use pack_planner::partition::PartitionedAlgorithm;
+use pack_planner::planner::GreedyAlgorithm;
+use pack_planner::profiles::{parse_configuration_file, Profile};
+use pack_planner::progress::{write_progress_line, Progress};
+use pack_planner::relaxation::suggest_relaxations;
+use pack_planner::remote::{
+    fetch_order, parse_cbor_order, post_closed_pack, post_notification, post_plan, Notification,
+};
+use pack_planner::render::{ColorChoice, OutputFormat, RenderOptions, Style};
+use pack_planner::restarts::{RestartAlgorithm, RestartObjective, UNLIMITED_RESTARTS};
+use pack_planner::schema::{output_schema, OutputSchema};
+use pack_planner::sequence::PackSequence;
+use pack_planner::shipping::{
+    carrier_api_key, create_shipment, parse_carrier_configuration, parse_shipments,
+};
+use pack_planner::signing::SigningKey;
+use pack_planner::simulation::{simulate, SampleMode, Simulation};
+use pack_planner::split::SplitOutput;
+use pack_planner::stats::StatisticsFile;
+use pack_planner::storage::{is_object_url, read_object, write_object};
+use pack_planner::table::read_sections;
+use pack_planner::{plan_input, plan_items, plan_sections, Configuration};
 
 #[cfg(test)]
 #[path = "tests.rs"]
 mod tests;
 
-/// Defines the different errors for the swerve model crate.
-#[derive(Debug, Error, PartialEq)]
-pub enum Error {
-    /// Indicates that one of the input strings didn't start in a valid way.
-    ///
-    /// * 'input' - The input string that was invalid.
-    #[error("The provided input string {input:?} was not valid. Expected the string to start with a number or one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT].")]
-    InputStringShouldStartWithNumberOrKeyWord {
-        /// The input string that is incorrect
-        input: String,
-    },
-
-    /// Indicates that the current input string contains a pack information header, but the current line is not the first line. So duplicate information is
-    /// provided.
-    ///
-    /// * 'current_line' - The contents of the current line, which contains the duplicate header
-    /// * 'current_line_index' - The index of the current line.
-    #[error("The provided input string {current_line:?} contains pack information, but this line is not the first line of the input stream. It is the {current_line_index:?} line. This means there is duplicate header information.")]
-    InputContainsDuplicatePackInformation {
-        current_line: String,
-        current_line_index: usize,
-    },
-
-    /// Indicates that a string containing item information has too few or too many property values.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_count' - The number of property values that were found in the input string
-    #[error("The provided input string {input:?} contains too few or too many property values. Expecting 3 values, but got {property_count:?}")]
-    InvalidNumberOfPropertiesForPacks {
-        input: String,
-        property_count: usize,
-    },
-
-    // Indicates that a string containing pack information has an invalid value for the sort order.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_value' - The string containing the 'value' for the sort order
-    /// * 'source' - The source error
-    #[error("The provided input string {input:?} contains an invalid value for the sort order of a pack: {property_value:?}. Expected one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT].")]
-    InvalidPackSortOrder {
-        input: String,
-        property_value: String,
-        #[source]
-        source: ParseError,
-    },
-
-    /// Indicates that a string containing pack information has an invalid value for the number of items in a pack.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_value' - The string containing the 'value' for the number of items
-    /// * 'source' - The source error
-    #[error("The provided input string {input:?} contains an invalid value for the number of the items in a pack: {property_value:?}. Expected a positive integer number.")]
-    InvalidPackItemCount {
-        input: String,
-        property_value: String,
-        #[source]
-        source: ParseIntError,
-    },
-
-    // Indicates that a string containing pack information has an invalid value for the weight of the pack.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_value' - The string containing the 'value' for the weight property
-    /// * 'source' - The source error
-    #[error("The provided input string {input:?} contains an invalid value for the weight of a pack: {property_value:?}. Expected a positive floating point number.")]
-    InvalidPackWeight {
-        input: String,
-        property_value: String,
-        #[source]
-        source: ParseFloatError,
-    },
-
-    /// Indicates that a string containing item information has too few or too many property values.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_count' - The number of property values that were found in the input string
-    #[error("The provided input string {input:?} contains too few or too many property values. Expecting 4 values, but got {property_count:?}")]
-    InvalidNumberOfPropertiesForItem {
-        input: String,
-        property_count: usize,
-    },
-
-    /// Indicates that a string containing item information has an invalid value for the length of the item.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_value' - The string containing the 'value' for the length property
-    /// * 'source' - The source error
-    #[error("The provided input string {input:?} contains an invalid value for the length of the item: {property_value:?}. Expected a positive floating point number.")]
-    InvalidItemLength {
-        input: String,
-        property_value: String,
-        #[source]
-        source: ParseFloatError,
-    },
-
-    /// Indicates that a string containing item information has an invalid value for the weight of the item.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_value' - The string containing the 'value' for the weight property
-    /// * 'source' - The source error
-    #[error("The provided input string {input:?} contains an invalid value for the weight of the item: {property_value:?}. Expected a positive floating point number.")]
-    InvalidItemWeight {
-        input: String,
-        property_value: String,
-        #[source]
-        source: ParseFloatError,
-    },
-
-    /// Indicates that a string containing item information has an invalid value for the number of items.
-    ///
-    /// * 'input' - The input string
-    /// * 'property_value' - The string containing the 'value' for the number of items
-    /// * 'source' - The source error
-    #[error("The provided input string {input:?} contains an invalid value for the number of the items: {property_value:?}. Expected a positive integer number.")]
-    InvalidItemCount {
-        input: String,
-        property_value: String,
-        #[source]
-        source: ParseIntError,
-    },
-}
-
-// Indices used when parsing the pack information from the input
-const PACK_SORT_ORDER_INDEX: usize = 0;
-const PACK_MAXIMUM_ITEM_COUNT_INDEX: usize = 1;
-const PACK_MAXIMUM_WEIGHT_INDEX: usize = 2;
-
-// Indices used when parsing the items from the input
-const ITEM_ID_INDEX: usize = 0;
-const ITEM_LENGTH_INDEX: usize = 1;
-const ITEM_QUANTITY_INDEX: usize = 2;
-const ITEM_WEIGHT_INDEX: usize = 3;
-
-/// Contains properties for an item and the number of items with these properties as provided in the input.
-#[derive(Clone)]
-struct ItemTemplate {
-    id: String,
-    length: f64,
-    weight: f64,
-    count: i32,
-}
-
-impl FromStr for ItemTemplate {
-    type Err = Error;
-
-    fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = line.split(',').collect();
-        if parts.len() != 4 {
-            return Err(Error::InvalidNumberOfPropertiesForItem {
-                input: line.to_string(),
-                property_count: parts.len(),
-            });
+/// Parses a time limit, e.g. `500ms`, `5s` or `2m`. A number without a unit is a number of seconds.
+fn parse_time_limit(value: &str) -> Option<Duration> {
+    let (number, seconds_per_unit) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1.0)
+    } else if let Some(number) = value.strip_suffix('m') {
+        (number, 60.0)
+    } else {
+        (value, 1.0)
+    };
+
+    let number: f64 = number.trim().parse().ok()?;
+    Duration::try_from_secs_f64(number * seconds_per_unit).ok()
+}
+
+/// Contains the options provided on the command line.
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    /// The path to the file containing the carrier weight bands.
+    weight_bands_file: Option<String>,
+
+    /// The path to the file containing the cartons from which a carton is suggested for each pack.
+    cartons_file: Option<String>,
+
+    /// The path to the file containing the pack rules.
+    rules_file: Option<String>,
+
+    /// The path to the file containing the additional items that should be checked against the plan.
+    fit_check_file: Option<String>,
+
+    /// The path to the file containing the pairs of items that nest inside each other.
+    nesting_rules_file: Option<String>,
+
+    /// The path to the file containing the limits of the packs that contain items of a class.
+    class_limits_file: Option<String>,
+
+    /// The path to the file containing the IDs, or the patterns of the IDs, of the items that are left out of
+    /// the plan.
+    exclude_file: Option<String>,
+
+    /// The path to the file containing the limits of the total weight and the total number of pieces of each
+    /// destination.
+    destination_limits_file: Option<String>,
+
+    /// The path to the file containing the delivery stop of each destination, if the packs should be numbered
+    /// in the reverse of the order in which they are loaded.
+    delivery_stops_file: Option<String>,
+
+    /// The path to the bill of materials that contains the components of the kits.
+    bill_of_materials_file: Option<String>,
+
+    /// The path to the file that contains the members of the substitution groups.
+    substitution_groups_file: Option<String>,
+
+    /// The path to the file that maps the aliases of item IDs to the canonical IDs.
+    aliases_file: Option<String>,
+
+    /// The path to the file that contains the last used pack number, if the packs should continue the
+    /// numbering of earlier plans.
+    sequence_file: Option<String>,
+
+    /// The path to the file to which the statistics of the plan are appended, if the quality of the plans is
+    /// tracked.
+    stats_file: Option<String>,
+
+    /// The path to the file to which the decisions of the planner are written, if the plan should be
+    /// replayed.
+    event_log_file: Option<String>,
+
+    /// The directory to which each pack is also written as a separate file, if any.
+    split_output: Option<String>,
+
+    /// The path to the private key with which the plan is signed, if the plan should be signed.
+    signing_key_file: Option<String>,
+
+    /// The checkpoints of the plan, if an interrupted plan should be able to resume.
+    checkpoint: Option<PlanCheckpoint>,
+
+    /// Indicates if all the components of a kit should be placed in the same pack.
+    keep_kits_together: bool,
+
+    /// Indicates if the items that fill whole packs on their own should be placed in packs with a single item
+    /// ID, before the remaining items are mixed.
+    prefer_homogeneous: bool,
+
+    /// Defines what happens with items that don't fit in an empty pack.
+    on_infeasible: InfeasibleItemPolicy,
+
+    /// Defines what happens with batches of items that share an ID.
+    duplicate_ids: DuplicateIdPolicy,
+
+    /// Defines the order of the items with the same length when the items are sorted by length.
+    tie_break: TieBreak,
+
+    /// The smallest number of packs over which the items are spread, if any.
+    minimum_packs: Option<usize>,
+
+    /// Defines how errors are shown.
+    diagnostics: DiagnosticsFormat,
+
+    /// The expressions that select the items that are planned.
+    item_filters: Vec<ItemFilter>,
+
+    /// The expressions that change the properties of the items before they are planned.
+    item_maps: Vec<ItemMap>,
+
+    /// Indicates if the reason for each placement should be written.
+    explain: bool,
+
+    /// Indicates if the items of each pack are listed from the heaviest to the lightest item.
+    heaviest_first: bool,
+
+    /// Indicates if the plan should be checked against the theoretical minimum number of packs.
+    quick_check: bool,
+
+    /// Indicates if the limits of the pack that could be raised should be suggested when items don't fit in
+    /// an empty pack.
+    suggest_relaxations: bool,
+
+    /// Indicates if warnings should be treated as failures, in which case the plan is not written.
+    strict: bool,
+
+    /// Indicates if the plan should start with an attestation of how it was made.
+    attest: bool,
+
+    /// Defines what happens when the output file already exists.
+    existing_output: ExistingOutput,
+
+    /// Indicates if the smallest number of packs should be searched for with bin completion, instead of
+    /// filling one pack at a time.
+    bin_completion: bool,
+
+    /// Indicates if the algorithm and the sort order should be chosen from the statistics of the items.
+    auto_strategy: bool,
+
+    /// Indicates if the items should be partitioned into groups of compatible items, each of which is planned
+    /// on its own.
+    partition: bool,
+
+    /// The number of additional attempts with shuffled items, if the items should be planned more than once.
+    restarts: Option<u32>,
+
+    /// The seed of the random number generator that shuffles the items between attempts.
+    seed: Option<u64>,
+
+    /// The objective by which the plans of the attempts are compared.
+    objective: Option<RestartObjective>,
+
+    /// The time after which the best plan found so far is used, instead of making more attempts.
+    time_limit: Option<Duration>,
+
+    /// The sort order that replaces the sort order of the pack information in the input.
+    sort_order: Option<PackSortOrder>,
+
+    /// The maximum number of pieces that replaces the one of the pack information in the input.
+    maximum_number_of_pieces: Option<i32>,
+
+    /// The maximum weight that replaces the one of the pack information in the input.
+    maximum_weight: Option<f64>,
+
+    /// The time it takes to handle a single item, in seconds, from which the packing time is estimated.
+    handling_time: Option<f64>,
+
+    /// The grades by which the packs are graded on their utilization.
+    grades: Vec<Grade>,
+
+    /// The format in which the plan is written.
+    format: Option<OutputFormat>,
+
+    /// Defines when the plan and the warnings are colored.
+    color: ColorChoice,
+
+    /// The path to the configuration file that contains the profiles.
+    configuration_file: Option<String>,
+
+    /// The name of the profile, from the configuration file, that provides the defaults for the other options.
+    profile: Option<String>,
+
+    /// The path of the file from which the input is read, instead of the standard input. The path may be the
+    /// URL of an object in an object store.
+    input_file: Option<String>,
+
+    /// The path of the file to which the plan is written, instead of the standard output. The path may be the
+    /// URL of an object in an object store.
+    output_file: Option<String>,
+
+    /// The path of the Unix domain socket on which the daemon listens, or '-' to use stdin and stdout.
+    daemon_socket: Option<String>,
+
+    /// The address, e.g. `127.0.0.1:9100`, on which the metrics of the daemon are served over HTTP.
+    metrics_address: Option<String>,
+
+    /// The path to the manifest that describes a batch of plans.
+    batch_manifest: Option<String>,
+
+    /// The paths of the plans that are merged into a single plan, if plans should be merged instead of made.
+    plans_to_merge: Vec<String>,
+
+    /// The path of the JSON plan of which the packs are shipped, if the packs should be shipped instead of
+    /// planned.
+    plan_to_ship: Option<String>,
+
+    /// The path to the carrier file that describes the carrier API to which the packs are shipped.
+    carrier_file: Option<String>,
+
+    /// The output of which the JSON Schema is written, if the schema should be written instead of a plan.
+    schema: Option<OutputSchema>,
+
+    /// The number of plans of random samples of the items, if the number of packs should be simulated
+    /// instead of planned.
+    simulation_runs: Option<usize>,
+
+    /// The way the items of each simulated plan are selected.
+    sample_mode: SampleMode,
+
+    /// The URL of the endpoint from which the items are fetched, instead of reading them from the standard input.
+    fetch_url: Option<String>,
+
+    /// The URL of the endpoint to which the plan is posted.
+    callback_url: Option<String>,
+
+    /// The URL of the endpoint to which a summary is posted when a batch, or a daemon request, completes.
+    notify_url: Option<String>,
+
+    /// The URL of the endpoint to which each pack is posted the moment it is closed.
+    pack_closed_url: Option<String>,
+}
+
+fn parse_arguments<I: Iterator<Item = String>>(args: I) -> Result<Options, Error> {
+    let mut args = args.peekable();
+    let mut options = Options::default();
+    while let Some(argument) = args.next() {
+        match argument.as_str() {
+            "--weight-bands" => {
+                options.weight_bands_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--cartons" => {
+                options.cartons_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--rules" => {
+                options.rules_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--check-fit" => {
+                options.fit_check_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--nesting" => {
+                options.nesting_rules_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--exclude" => {
+                options.exclude_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--class-limits" => {
+                options.class_limits_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--destination-limits" => {
+                options.destination_limits_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--stops" => {
+                options.delivery_stops_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--bom" => {
+                options.bill_of_materials_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--substitutes" => {
+                options.substitution_groups_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--aliases" => {
+                options.aliases_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--sequence-file" => {
+                options.sequence_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--stats-file" => {
+                options.stats_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--event-log" => {
+                options.event_log_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--split-output" => {
+                options.split_output = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--sign" => {
+                options.signing_key_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--checkpoint" | "--resume" => {
+                let path = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.checkpoint = Some(PlanCheckpoint::new(path, argument == "--resume"));
+            }
+            "--keep-kits-together" => options.keep_kits_together = true,
+            "--prefer-homogeneous" => options.prefer_homogeneous = true,
+            "--partition" => options.partition = true,
+            "--explain" => options.explain = true,
+            "--heaviest-first" => options.heaviest_first = true,
+            "--quick-check" => options.quick_check = true,
+            "--suggest-relaxations" => options.suggest_relaxations = true,
+            "--strict" => options.strict = true,
+            "--attest" => options.attest = true,
+            "--overwrite" => options.existing_output = ExistingOutput::Overwrite,
+            "--no-clobber" => options.existing_output = ExistingOutput::Keep,
+            "--input" => {
+                options.input_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--output" => {
+                options.output_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--algorithm" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                (options.bin_completion, options.auto_strategy) = match value.as_str() {
+                    "greedy" => (false, false),
+                    "bin-completion" => (true, false),
+                    "auto" => (false, true),
+                    _ => return Err(Error::InvalidArgumentValue { argument, value }),
+                };
+            }
+            "--restarts" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.restarts =
+                    Some(value.parse().map_err(|_| Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    })?);
+            }
+            "--seed" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.seed = Some(value.parse().map_err(|_| Error::InvalidArgumentValue {
+                    argument: argument.clone(),
+                    value: value.clone(),
+                })?);
+            }
+            "--sample" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.sample_mode =
+                    SampleMode::from_str(&value).map_err(|_| Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    })?;
+            }
+            "--objective" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.objective = Some(RestartObjective::from_str(&value).map_err(|_| {
+                    Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    }
+                })?);
+            }
+            "--time-limit" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.time_limit =
+                    Some(
+                        parse_time_limit(&value).ok_or_else(|| Error::InvalidArgumentValue {
+                            argument: argument.clone(),
+                            value: value.clone(),
+                        })?,
+                    );
+            }
+            "--sort" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.sort_order = match PackSortOrder::from_str(&value) {
+                    Ok(PackSortOrder::NotSet) | Err(_) => {
+                        return Err(Error::InvalidArgumentValue {
+                            argument: argument.clone(),
+                            value: value.clone(),
+                        })
+                    }
+                    Ok(sort_order) => Some(sort_order),
+                };
+            }
+            "--max-pieces" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.maximum_number_of_pieces =
+                    Some(value.parse().ok().filter(|p: &i32| *p > 0).ok_or_else(|| {
+                        Error::InvalidArgumentValue {
+                            argument: argument.clone(),
+                            value: value.clone(),
+                        }
+                    })?);
+            }
+            "--max-weight" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.maximum_weight = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|w: &f64| w.is_finite() && *w > 0.0)
+                        .ok_or_else(|| Error::InvalidArgumentValue {
+                            argument: argument.clone(),
+                            value: value.clone(),
+                        })?,
+                );
+            }
+            "--grades" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.grades = parse_grades(&value)?;
+            }
+            "--handling-time" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.handling_time = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|t: &f64| t.is_finite() && *t >= 0.0)
+                        .ok_or_else(|| Error::InvalidArgumentValue {
+                            argument: argument.clone(),
+                            value: value.clone(),
+                        })?,
+                );
+            }
+            "--on-infeasible" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.on_infeasible = InfeasibleItemPolicy::from_str(&value).map_err(|_| {
+                    Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    }
+                })?;
+            }
+            "--duplicate-ids" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.duplicate_ids = DuplicateIdPolicy::from_str(&value).map_err(|_| {
+                    Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    }
+                })?;
+            }
+            "--tie-break" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.tie_break =
+                    TieBreak::from_str(&value).map_err(|_| Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    })?;
+            }
+            "--min-packs" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.minimum_packs = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|m: &usize| *m > 0)
+                        .ok_or_else(|| Error::InvalidArgumentValue {
+                            argument: argument.clone(),
+                            value: value.clone(),
+                        })?,
+                );
+            }
+            "--diagnostics" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.diagnostics = DiagnosticsFormat::from_str(&value).map_err(|_| {
+                    Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    }
+                })?;
+            }
+            "--filter" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.item_filters.push(ItemFilter::from_str(&value)?);
+            }
+            "--map" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.item_maps.push(ItemMap::from_str(&value)?);
+            }
+            "--format" => {
+                let format = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.format = Some(
+                    OutputFormat::from_str(&format)
+                        .map_err(|_| Error::UnknownOutputFormat { format })?,
+                );
+            }
+            "--color" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                options.color =
+                    ColorChoice::from_str(&value).map_err(|_| Error::InvalidArgumentValue {
+                        argument: argument.clone(),
+                        value: value.clone(),
+                    })?;
+            }
+            "--config" => {
+                options.configuration_file =
+                    Some(args.next().ok_or(Error::MissingArgumentValue {
+                        argument: argument.clone(),
+                    })?);
+            }
+            "--profile" => {
+                options.profile = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--daemon" => {
+                options.daemon_socket = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--metrics" => {
+                options.metrics_address = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--fetch-url" => {
+                options.fetch_url = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--callback-url" => {
+                options.callback_url = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--notify-url" => {
+                options.notify_url = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--pack-closed-url" => {
+                options.pack_closed_url = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "batch" => {
+                options.batch_manifest = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "merge-plans" => {
+                // The plans are all the following values, up to the next option.
+                while let Some(path) = args.next_if(|a| !a.starts_with("--")) {
+                    options.plans_to_merge.push(path);
+                }
+                if options.plans_to_merge.is_empty() {
+                    return Err(Error::MissingArgumentValue { argument });
+                }
+            }
+            "ship" => {
+                options.plan_to_ship = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "--carrier" => {
+                options.carrier_file = Some(args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?);
+            }
+            "schema" => {
+                // The output is optional, and defaults to the plan.
+                options.schema = Some(match args.next_if(|a| !a.starts_with("--")) {
+                    Some(value) => OutputSchema::from_str(&value)
+                        .map_err(|_| Error::InvalidArgumentValue { argument, value })?,
+                    None => OutputSchema::default(),
+                });
+            }
+            "simulate" => {
+                let value = args.next().ok_or(Error::MissingArgumentValue {
+                    argument: argument.clone(),
+                })?;
+                match value.parse() {
+                    Ok(runs) if runs > 0 => options.simulation_runs = Some(runs),
+                    _ => return Err(Error::InvalidArgumentValue { argument, value }),
+                }
+            }
+            _ => return Err(Error::UnknownArgument { argument }),
         }
+    }
 
-        let id = parts[ITEM_ID_INDEX].to_string();
+    Ok(options)
+}
 
-        let length;
-        match parts[ITEM_LENGTH_INDEX].parse() {
-            Ok(v) => length = v,
+// The status codes used in the daemon responses
+const DAEMON_RESPONSE_SUCCESS: u8 = 0;
+
+const DAEMON_RESPONSE_FAILURE: u8 = 1;
+
+// The maximum size of a single daemon request, in bytes
+const DAEMON_MAXIMUM_REQUEST_LENGTH: usize = 64 * 1024 * 1024;
+
+// The time between two checks for new connections, or for the end of the requests in flight while the
+// daemon is stopping
+const DAEMON_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// The number of times a read of a daemon connection is retried when it fails with a transient error, e.g. a
+// timeout of a network-backed socket
+const DAEMON_READ_RETRIES: u32 = 5;
+
+// The time a client of the metrics endpoint may take to send its request, or to receive the response, before
+// its connection is closed, so that a client that doesn't send anything can't block the endpoint
+const METRICS_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Reads a single request from the daemon connection. A request consists of the length of the payload, as a
+/// 4 byte big-endian unsigned integer, followed by the payload itself. Returns `None` if the connection was
+/// closed before the start of a new request.
+fn read_daemon_request<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>, Error> {
+    let mut length_bytes = [0u8; 4];
+    match reader.read_exact(&mut length_bytes) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(read_error(e)),
+    }
+
+    let length = u32::from_be_bytes(length_bytes) as usize;
+    if length > DAEMON_MAXIMUM_REQUEST_LENGTH {
+        return Err(Error::DaemonRequestTooLarge {
+            length,
+            maximum_length: DAEMON_MAXIMUM_REQUEST_LENGTH,
+        });
+    }
+
+    let mut payload = vec![0u8; length];
+    reader.read_exact(&mut payload).map_err(read_error)?;
+
+    Ok(Some(payload))
+}
+
+/// Writes a single response to the daemon connection. A response consists of a status byte, 0 for success
+/// and 1 for failure, the length of the payload, as a 4 byte big-endian unsigned integer, and the payload.
+fn write_daemon_response<W: Write>(writer: &mut W, status: u8, payload: &[u8]) -> io::Result<()> {
+    writer.write_all(&[status])?;
+    writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Plans a single daemon request. When the plans are written in the CBOR format the request is a CBOR order,
+/// with the same contents as the orders of the order service, otherwise it is the same text that would be
+/// provided on stdin.
+fn plan_daemon_request<W: Write>(
+    request: &[u8],
+    writer: &mut W,
+    configuration: &Configuration,
+) -> Result<Vec<PlanWarning>, Error> {
+    if configuration.render_options.format != OutputFormat::Cbor {
+        return plan_input(&mut &request[..], writer, configuration);
+    }
+
+    let (pack_template, items) = parse_cbor_order(request, "the daemon request")?;
+    plan_items(&pack_template, items, writer, configuration)
+}
+
+/// Handles all the requests on a single daemon connection until the connection is closed. The first request
+/// is in flight from the moment the connection was accepted, see 'accepted', so that a daemon that stops
+/// can't drop a request that arrived before its connection was served.
+fn serve_daemon_connection<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    configuration: &Configuration,
+    health: &Arc<Health>,
+    accepted: RequestInFlight,
+    notify_url: Option<&str>,
+) -> Result<(), Error> {
+    let to_write_error = |e: io::Error| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    };
+
+    let mut accepted = Some(accepted);
+    loop {
+        let request = match read_daemon_request(reader) {
+            Ok(Some(request)) => request,
+            Ok(None) => return Ok(()),
             Err(e) => {
-                return Err(Error::InvalidItemLength {
-                    input: line.to_string(),
-                    property_value: parts[ITEM_LENGTH_INDEX].to_string(),
-                    source: e,
-                })
+                // The connection is not in a known state anymore so report the error and stop.
+                write_daemon_response(writer, DAEMON_RESPONSE_FAILURE, e.to_string().as_bytes())
+                    .map_err(to_write_error)?;
+                return Err(e);
             }
         };
 
-        let weight;
-        match parts[ITEM_WEIGHT_INDEX].parse() {
-            Ok(v) => weight = v,
+        let request_in_flight = accepted.take().unwrap_or_else(|| health.start_request());
+        let start = Instant::now();
+        let mut output = Vec::new();
+        let result = plan_daemon_request(&request, &mut output, configuration);
+        if let Some(metrics) = &configuration.metrics {
+            metrics.record_request(start.elapsed(), result.is_ok());
+        }
+
+        match &result {
+            // The protocol has no room for warnings, so only the plan is returned.
+            Ok(_) => write_daemon_response(writer, DAEMON_RESPONSE_SUCCESS, &output),
             Err(e) => {
-                return Err(Error::InvalidItemWeight {
-                    input: line.to_string(),
-                    property_value: parts[ITEM_WEIGHT_INDEX].to_string(),
-                    source: e,
-                })
+                write_daemon_response(writer, DAEMON_RESPONSE_FAILURE, e.to_string().as_bytes())
             }
-        };
+        }
+        .map_err(to_write_error)?;
+
+        if let Some(url) = notify_url {
+            notify(url, &Notification::for_request(&result));
+        }
+        drop(request_in_flight);
+    }
+}
+
+/// Set by the handler of the `SIGTERM` signal to ask the daemon to stop.
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Makes the `SIGTERM` signal, e.g. from Kubernetes, ask the daemon to stop instead of ending the process.
+#[cfg(unix)]
+fn handle_termination_signal() {
+    extern "C" fn request_shutdown(_: libc::c_int) {
+        // Only async-signal-safe operations, such as an atomic store, may be used in a signal handler.
+        SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+    }
 
-        let count;
-        match parts[ITEM_QUANTITY_INDEX].parse() {
-            Ok(v) => count = v,
+    // SAFETY: The handler only stores to an atomic, which is async-signal-safe.
+    let handler: extern "C" fn(libc::c_int) = request_shutdown;
+    unsafe {
+        libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+fn handle_termination_signal() {}
+
+/// Listens on the Unix domain socket at the given path and handles each connection on its own thread, until
+/// 'shutdown' is set. The daemon then stops accepting connections, waits until the requests in flight are
+/// answered, and removes the socket file.
+#[cfg(unix)]
+fn run_daemon(
+    socket_path: &str,
+    configuration: Configuration,
+    health: Arc<Health>,
+    notify_url: Option<&str>,
+    shutdown: &AtomicBool,
+) -> Result<(), Error> {
+    use std::os::unix::net::UnixListener;
+
+    let to_start_error = |e: io::Error| Error::UnableToStartDaemon {
+        socket_path: socket_path.to_string(),
+        reason: e.to_string(),
+    };
+    let listener = UnixListener::bind(socket_path).map_err(to_start_error)?;
+
+    // The listener doesn't block, so that a request to stop is noticed while no connections arrive.
+    listener.set_nonblocking(true).map_err(to_start_error)?;
+
+    let configuration = Arc::new(configuration);
+    health.set_ready(true);
+    while !shutdown.load(Ordering::SeqCst) {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(DAEMON_POLL_INTERVAL);
+                continue;
+            }
             Err(e) => {
-                return Err(Error::InvalidItemCount {
-                    input: line.to_string(),
-                    property_value: parts[ITEM_QUANTITY_INDEX].to_string(),
-                    source: e,
-                })
+                eprintln!("Failed to accept a daemon connection: {e}");
+                continue;
             }
         };
+        let accepted = health.start_request();
+        if let Err(e) = stream.set_nonblocking(false) {
+            eprintln!("Failed to accept a daemon connection: {e}");
+            continue;
+        }
 
-        Ok(Self {
-            id,
-            length,
-            weight,
-            count,
-        })
+        let configuration = Arc::clone(&configuration);
+        let health = Arc::clone(&health);
+        let notify_url = notify_url.map(str::to_string);
+        thread::spawn(move || {
+            let mut reader = InputReader::new(&stream, "the daemon connection")
+                .with_retries(DAEMON_READ_RETRIES);
+            let mut writer = &stream;
+            if let Err(e) = serve_daemon_connection(
+                &mut reader,
+                &mut writer,
+                &configuration,
+                &health,
+                accepted,
+                notify_url.as_deref(),
+            ) {
+                eprintln!("Daemon connection failed: {e}");
+            }
+        });
+    }
+
+    health.set_ready(false);
+    drop(listener);
+    while health.requests_in_flight() > 0 {
+        thread::sleep(DAEMON_POLL_INTERVAL);
     }
-}
 
-/// Defines the different ways in which packs can be ordered.
-#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq)]
-enum PackSortOrder {
-    NotSet,
-    #[strum(to_string = "NATURAL")]
-    Natural,
-    #[strum(to_string = "SHORT_TO_LONG")]
-    ShortToLong,
-    #[strum(to_string = "LONG_TO_SHORT")]
-    LongToShort,
+    let _ = std::fs::remove_file(socket_path);
+    Ok(())
 }
 
-struct PackTemplate {
-    maximum_number_of_pieces: i32,
-    maximum_weight: f64,
-    sort_order: PackSortOrder,
+#[cfg(not(unix))]
+fn run_daemon(
+    socket_path: &str,
+    _configuration: Configuration,
+    _health: Arc<Health>,
+    _notify_url: Option<&str>,
+    _shutdown: &AtomicBool,
+) -> Result<(), Error> {
+    Err(Error::UnableToStartDaemon {
+        socket_path: socket_path.to_string(),
+        reason: "Unix domain sockets are not supported on this platform.".to_string(),
+    })
 }
 
-impl PackTemplate {
-    fn new() -> PackTemplate {
-        PackTemplate {
-            maximum_number_of_pieces: 0,
-            maximum_weight: 0.0,
-            sort_order: PackSortOrder::NotSet,
-        }
+/// Posts the notification to the endpoint. The jobs have already completed, so a notification that can't be
+/// delivered is reported on the standard error stream instead of failing the jobs.
+fn notify(url: &str, notification: &Notification) {
+    if let Err(e) = post_notification(url, notification) {
+        eprintln!("Failed to send the notification: {e}");
     }
+}
 
-    fn from_line(&mut self, s: &str) -> Result<(), Error> {
-        let parts: Vec<&str> = s.split(',').collect();
-        if parts.len() != 3 {
-            return Err(Error::InvalidNumberOfPropertiesForPacks {
-                input: s.to_string(),
-                property_count: parts.len(),
+/// Listens on the given address and serves the metrics over HTTP, on a thread of its own, until the
+/// application stops. The connections are served one at a time, each with a timeout so that a slow client
+/// can't stop the others from being served.
+fn start_metrics_endpoint(
+    address: &str,
+    metrics: Arc<Metrics>,
+    health: Arc<Health>,
+) -> Result<(), Error> {
+    let listener = TcpListener::bind(address).map_err(|e| Error::UnableToStartMetricsEndpoint {
+        address: address.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| {
+                stream.set_read_timeout(Some(METRICS_CONNECTION_TIMEOUT))?;
+                stream.set_write_timeout(Some(METRICS_CONNECTION_TIMEOUT))?;
+                let mut reader = BufReader::new(&stream);
+                let mut writer = &stream;
+                serve_metrics_request(&mut reader, &mut writer, &metrics, &health)
             });
+            if let Err(e) = result {
+                eprintln!("Failed to serve the metrics: {e}");
+            }
         }
+    });
 
-        let pack_sort_order;
-        match PackSortOrder::from_str(parts[PACK_SORT_ORDER_INDEX]) {
-            Ok(s) => pack_sort_order = s,
-            Err(e) => {
-                return Err(Error::InvalidPackSortOrder {
-                    input: s.to_string(),
-                    property_value: parts[PACK_SORT_ORDER_INDEX].to_string(),
-                    source: e,
-                })
-            }
-        };
+    Ok(())
+}
 
-        let maximum_number_of_items;
-        match parts[PACK_MAXIMUM_ITEM_COUNT_INDEX].parse() {
-            Ok(v) => maximum_number_of_items = v,
-            Err(e) => {
-                return Err(Error::InvalidPackItemCount {
-                    input: s.to_string(),
-                    property_value: parts[PACK_MAXIMUM_ITEM_COUNT_INDEX].to_string(),
-                    source: e,
-                })
-            }
-        };
+/// Draws the progress of a plan on the standard error stream, on a thread of its own, until it is dropped.
+struct ProgressBar {
+    done: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
 
-        let maximum_weight;
-        match parts[PACK_MAXIMUM_WEIGHT_INDEX].parse() {
-            Ok(v) => maximum_weight = v,
-            Err(e) => {
-                return Err(Error::InvalidPackWeight {
-                    input: s.to_string(),
-                    property_value: parts[PACK_MAXIMUM_WEIGHT_INDEX].to_string(),
-                    source: e,
-                })
+impl ProgressBar {
+    fn start(progress: Arc<Progress>) -> ProgressBar {
+        let done = Arc::new(AtomicBool::new(false));
+        let thread_done = Arc::clone(&done);
+        let thread = thread::spawn(move || {
+            let started = Instant::now();
+            while !thread_done.load(Ordering::Relaxed) {
+                // Nothing is drawn while the input is still being read.
+                let snapshot = progress.snapshot();
+                if snapshot.total_items > 0 {
+                    let mut line = b"\r".to_vec();
+                    let _ = write_progress_line(&mut line, &snapshot, started.elapsed());
+                    line.extend_from_slice(b"\x1b[K");
+                    let _ = io::stderr().write_all(&line);
+                }
+
+                thread::park_timeout(Duration::from_millis(100));
             }
-        };
 
-        self.maximum_number_of_pieces = maximum_number_of_items;
-        self.maximum_weight = maximum_weight;
-        self.sort_order = pack_sort_order;
+            // Clear the line, so that the warnings and the errors start at the beginning of the line.
+            let _ = io::stderr().write_all(b"\r\x1b[K");
+        });
 
-        Ok(())
+        ProgressBar {
+            done,
+            thread: Some(thread),
+        }
     }
 }
 
-fn parse_input<R: BufRead>(reader: &mut R) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
-    let mut pack_template = PackTemplate::new();
-    let mut item_templates: Vec<ItemTemplate> = Vec::new();
-
-    let mut is_first_line = true;
-    let mut line_index: usize = 0;
-    for line in reader.lines() {
-        let line = line.unwrap();
-        if line.is_empty() {
-            break;
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
         }
+    }
+}
 
-        let trimmed_line = line.trim();
+/// Defines the exit codes of the application.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ExitStatus {
+    /// The plan was created without any warnings.
+    Success = 0,
 
-        // The line should start either with an integer number or one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT]
-        let is_number = if let Some(c) = trimmed_line.chars().next() {
-            c.is_digit(10)
-        } else {
-            false // Empty string
-        };
+    /// The plan was created, but warnings were raised. With `--strict` the plan is not written.
+    Warnings = 1,
 
-        let is_keyword = trimmed_line.starts_with("NATURAL")
-            || trimmed_line.starts_with("SHORT_TO_LONG")
-            || trimmed_line.starts_with("LONG_TO_SHORT");
+    /// The input, one of the files or the command line arguments could not be parsed.
+    InvalidInput = 2,
 
-        if !is_number && !is_keyword {
-            return Err(Error::InputStringShouldStartWithNumberOrKeyWord {
-                input: line.clone(),
-            });
+    /// Some of the items cannot be placed in any pack. When these items are skipped, or placed in oversize
+    /// packs, the plan is written.
+    Infeasible = 3,
+
+    /// Reading or writing one of the files, streams or endpoints failed.
+    Io = 4,
+
+    /// The planner created a plan that doesn't account for all the items, which is a bug in the planner.
+    Internal = 5,
+}
+
+/// Returns the exit status that belongs to the error.
+fn exit_status_for(error: &Error) -> ExitStatus {
+    match error {
+        Error::PinnedItemsDoNotFit { .. }
+        | Error::PinnedItemInIncrementalPlan { .. }
+        | Error::ItemDoesNotFitInEmptyPack { .. }
+        | Error::KitDoesNotFitInEmptyPack { .. }
+        | Error::NoItemFitsInEmptyPack { .. } => ExitStatus::Infeasible,
+        Error::UnableToReadInput { .. }
+        | Error::Io { .. }
+        | Error::UnableToWriteOutput { .. }
+        | Error::UnableToOpenFile { .. }
+        | Error::UnableToStartDaemon { .. }
+        | Error::UnableToStartMetricsEndpoint { .. }
+        | Error::DaemonRequestTooLarge { .. }
+        | Error::UnsupportedCompression { .. }
+        | Error::UnsupportedTableFormat { .. }
+        | Error::OutputFileExists { .. }
+        | Error::RemoteRequestFailed { .. }
+        | Error::UnsupportedHttp { .. }
+        | Error::UnsupportedObjectStore { .. }
+        | Error::MissingObjectStoreCredentials { .. }
+        | Error::MissingCarrierApiKey { .. }
+        | Error::UnsupportedSigning { .. } => ExitStatus::Io,
+        Error::TotalsNotConserved { .. } => ExitStatus::Internal,
+        _ => ExitStatus::InvalidInput,
+    }
+}
+
+/// Indicates if the warnings report items that don't fit in an empty pack, which were left out of the plan or
+/// placed in oversize packs.
+fn has_infeasible_warnings(warnings: &[PlanWarning]) -> bool {
+    warnings.iter().any(|w| {
+        matches!(
+            w,
+            PlanWarning::InfeasibleItemSkipped { .. } | PlanWarning::OversizePack { .. }
+        )
+    })
+}
+
+/// Returns the exit status of a plan that was created with the warnings. A plan with items that don't fit in
+/// an empty pack exits with [`ExitStatus::Infeasible`], even though the other items were planned.
+fn exit_status_for_warnings(warnings: &[PlanWarning]) -> ExitStatus {
+    if has_infeasible_warnings(warnings) {
+        ExitStatus::Infeasible
+    } else if warnings.is_empty() {
+        ExitStatus::Success
+    } else {
+        ExitStatus::Warnings
+    }
+}
+
+/// Indicates if the plan failed, or is incomplete, because items don't fit in an empty pack.
+fn has_infeasible_items(result: &Result<Vec<PlanWarning>, Error>) -> bool {
+    match result {
+        Ok(warnings) => has_infeasible_warnings(warnings),
+        Err(error) => matches!(
+            error,
+            Error::ItemDoesNotFitInEmptyPack { .. }
+                | Error::NoItemFitsInEmptyPack { .. }
+                | Error::KitDoesNotFitInEmptyPack { .. }
+                | Error::PinnedItemsDoNotFit { .. }
+        ),
+    }
+}
+
+/// Writes the detached signature of the plan next to the output file, in a file with the `.sig` extension
+/// added to the name of the output file.
+fn write_signature(
+    output_file: &str,
+    existing_output: ExistingOutput,
+    signature: &[u8],
+) -> Result<(), Error> {
+    write_output(
+        Some(&format!("{output_file}.sig")),
+        existing_output,
+        signature,
+    )
+}
+
+/// Parses the sections of the input, which is either the text input or a table of items. The 'origin'
+/// describes the input in the errors. When the plan is attested the input is read completely first, so that
+/// the hash of the input is added to the configuration.
+fn parse_attested_input<R: BufRead>(
+    input: &mut R,
+    origin: &str,
+    configuration: &mut Configuration,
+    attest: bool,
+) -> Result<Vec<InputSection>, Error> {
+    if !attest {
+        return read_sections(input, origin);
+    }
+
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes).map_err(read_error)?;
+    configuration.input_sha256 = Some(input_hash(&bytes));
+    read_sections(&mut bytes.as_slice(), origin)
+}
+
+/// Opens the input file, or the object in an object store, for reading, decompressing it if it is compressed.
+fn read_input(path: &str) -> Result<Box<dyn BufRead>, Error> {
+    if is_object_url(path) {
+        decompress(io::Cursor::new(read_object(path)?), path)
+    } else {
+        open_input(Path::new(path))
+    }
+}
+
+/// Writes the plan to the output file, or to the standard output if no output file was provided.
+fn write_output(
+    output_file: Option<&str>,
+    existing_output: ExistingOutput,
+    plan: &[u8],
+) -> Result<(), Error> {
+    let to_write_error = |e: io::Error| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    };
+
+    match output_file {
+        Some(url) if is_object_url(url) => write_object(url, &compress(plan, url)?),
+        Some(path) => {
+            let mut output = OutputFile::create_with(Path::new(path), existing_output)?;
+            output.write_all(plan).map_err(to_write_error)?;
+            output.finish().map_err(to_write_error)
         }
+        None => io::stdout().lock().write_all(plan).map_err(to_write_error),
+    }
+}
 
-        // The first line contains information about the packs that we're allowed to create
-        if is_keyword {
-            if !is_first_line {
-                return Err(Error::InputContainsDuplicatePackInformation {
-                    current_line: line,
-                    current_line_index: line_index,
-                });
-            }
+/// Runs the jobs in the batch manifest, writes a summary and returns the exit status. When jobs failed the
+/// status belongs to the error of the first failed job.
+fn run_batch_manifest(manifest_path: &str, notify_url: Option<&str>) -> Result<ExitStatus, Error> {
+    let file = open_input(Path::new(manifest_path))?;
+    let manifest = parse_batch_manifest(file)?;
 
-            pack_template.from_line(trimmed_line)?;
+    // The paths in the manifest are relative to the manifest itself.
+    let base_directory = Path::new(manifest_path)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+    let results = run_batch(&manifest, base_directory);
 
-            // Any line after this cannot be the first line anymore.
-            is_first_line = false;
-        } else {
-            let item = ItemTemplate::from_str(trimmed_line)?;
-            item_templates.push(item);
+    let stdout = io::stdout();
+    write_batch_summary(&mut stdout.lock(), &results).map_err(|e| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    })?;
+    if let Some(url) = notify_url.or(manifest.notify_url.as_deref()) {
+        notify(url, &batch_notification(&results));
+    }
+
+    let status = match results.iter().find_map(|r| r.error.as_ref()) {
+        Some(e) => exit_status_for(e),
+        None if results.iter().any(|r| has_infeasible_warnings(&r.warnings)) => {
+            ExitStatus::Infeasible
         }
+        None if results.iter().any(|r| !r.warnings.is_empty()) => ExitStatus::Warnings,
+        None => ExitStatus::Success,
+    };
+    Ok(status)
+}
+
+/// Merges the plans into a single plan, which is written to the output file, or to the standard output,
+/// followed by the summary of the merged plans.
+fn run_merge_plans(
+    plan_paths: &[String],
+    output_file: Option<&str>,
+    existing_output: ExistingOutput,
+) -> Result<ExitStatus, Error> {
+    let plans = plan_paths
+        .iter()
+        .map(|path| Ok((path.clone(), read_input(path)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut plan = Vec::new();
+    let merged_plans = merge_plans(plans, &mut plan)?;
+    write_merge_summary(&mut plan, &merged_plans).map_err(|e| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    })?;
+
+    write_output(output_file, existing_output, &plan)?;
+    Ok(ExitStatus::Success)
+}
+
+/// Creates a shipment with the carrier API for each pack in the plan and writes the ID of each shipment as
+/// soon as it is created, so that the shipments that were created before a failure are known.
+fn run_ship(plan_path: &str, carrier_path: Option<&str>) -> Result<ExitStatus, Error> {
+    let carrier_path = carrier_path.ok_or_else(|| Error::MissingRequiredArgument {
+        argument: "ship".to_string(),
+        required_argument: "--carrier".to_string(),
+    })?;
+    let carrier = parse_carrier_configuration(open_input(Path::new(carrier_path))?)?;
+    let api_key = carrier_api_key(&carrier)?;
+    let shipments = parse_shipments(read_input(plan_path)?, plan_path)?;
 
-        line_index += 1;
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let backend = carrier.backend();
+    for shipment in &shipments {
+        let id = create_shipment(backend.as_ref(), &carrier, &api_key, shipment)?;
+        writeln!(stdout, "Pack {}: Shipment {}", shipment.pack_number, id).map_err(|e| {
+            Error::UnableToWriteOutput {
+                reason: e.to_string(),
+            }
+        })?;
     }
 
-    Ok((pack_template, item_templates))
+    Ok(ExitStatus::Success)
 }
 
-fn maximum_number_of_items_to_add(
-    pack_template: &PackTemplate,
-    current_pack_weight: f64,
-    current_pack_item_count: i32,
-    template: &ItemTemplate,
-) -> i32 {
-    let weight_space_in_pack = pack_template.maximum_weight - current_pack_weight;
-    let item_space_in_pack = pack_template.maximum_number_of_pieces - current_pack_item_count;
+/// Writes the JSON Schema of the output to the standard output.
+fn run_schema(schema: OutputSchema) -> Result<ExitStatus, Error> {
+    let document = output_schema(schema)?;
 
-    let max_items_by_weight = (weight_space_in_pack / template.weight).floor() as i32;
-    let items_to_add = if max_items_by_weight < item_space_in_pack {
-        max_items_by_weight
-    } else {
-        item_space_in_pack
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    serde_json::to_writer_pretty(&mut stdout, &document)
+        .map_err(io::Error::from)
+        .and_then(|_| writeln!(stdout))
+        .map_err(|e| Error::UnableToWriteOutput {
+            reason: e.to_string(),
+        })?;
+
+    Ok(ExitStatus::Success)
+}
+
+// The configuration file that is used when a profile is requested without a configuration file
+const DEFAULT_CONFIGURATION_FILE: &str = "pack-planner.yaml";
+
+/// Loads the profile that was requested on the command line, if any, from the configuration file.
+fn load_profile(options: &Options) -> Result<Profile, Error> {
+    let name = match &options.profile {
+        Some(n) => n,
+        None => return Ok(Profile::default()),
     };
-    items_to_add
+
+    let path = options
+        .configuration_file
+        .as_deref()
+        .unwrap_or(DEFAULT_CONFIGURATION_FILE);
+    let configuration_file = parse_configuration_file(open_input(Path::new(path))?)?;
+    configuration_file.profile(name).cloned()
 }
 
-fn print_item_line(item: &ItemTemplate, count: i32) {
-    println!(
-        "{},{:.1},{},{:.1}",
-        item.id, item.length, count, item.weight
-    );
+/// Returns `true` if the stream is a terminal and colors weren't disabled with the `NO_COLOR` environment
+/// variable.
+fn is_color_terminal(stream: &impl IsTerminal) -> bool {
+    stream.is_terminal() && env::var_os("NO_COLOR").is_none()
 }
 
-fn print_footer(current_weight: f64, pack_length: f64) {
-    println!("Pack Length: {pack_length:.1}, Pack Weight: {current_weight:.1}");
+/// Returns the argument that stops the packs of the plan from being reported one by one as they are closed,
+/// if any. Only the greedy algorithm closes the packs of the plan one by one. The restarts close the packs of
+/// attempts that may not be kept, the bin completion, which the auto strategy may choose, doesn't report its
+/// packs, the packs that are spread over the minimum number of packs are split after they are closed, and the
+/// packs are numbered for the delivery stops or the pack sequence after they are closed. The 'restarts' are
+/// those of the profile.
+fn argument_without_reported_packs(
+    options: &Options,
+    restarts: Option<u32>,
+) -> Option<&'static str> {
+    if options.bin_completion || options.auto_strategy {
+        Some("--algorithm")
+    } else if options.restarts.is_some() || restarts.is_some() {
+        Some("--restarts")
+    } else if options.time_limit.is_some() {
+        Some("--time-limit")
+    } else if options.minimum_packs.is_some() {
+        Some("--min-packs")
+    } else if options.delivery_stops_file.is_some() {
+        Some("--stops")
+    } else if options.sequence_file.is_some() {
+        Some("--sequence-file")
+    } else {
+        None
+    }
 }
 
-fn print_packs(items: Vec<ItemTemplate>, pack_template: PackTemplate) {
-    let mut pack_index = 0;
-    let mut current_pack_weight = pack_template.maximum_weight;
-    let mut current_pack_item_count = pack_template.maximum_number_of_pieces;
-    let mut longest_item_in_pack: f64 = 0.0;
+/// Loads the files referred to by the options into the configuration. The options provided on the command
+/// line take precedence over the settings of the profile.
+fn load_configuration(options: &Options) -> Result<Configuration, Error> {
+    let profile = load_profile(options)?;
+
+    let weight_bands = match &options.weight_bands_file {
+        Some(path) => parse_weight_bands(&mut open_input(Path::new(path))?)?,
+        None => Vec::new(),
+    };
+
+    let cartons = match &options.cartons_file {
+        Some(path) => parse_cartons(&mut open_input(Path::new(path))?)?,
+        None => Vec::new(),
+    };
+
+    let rules = match &options.rules_file {
+        Some(path) => parse_pack_rules(&mut open_input(Path::new(path))?)?,
+        None => Vec::new(),
+    };
+
+    let additional_items = match &options.fit_check_file {
+        Some(path) => Some(parse_items(&mut open_input(Path::new(path))?)?),
+        None => None,
+    };
+
+    let bill_of_materials = match &options.bill_of_materials_file {
+        Some(path) => parse_bill_of_materials(&mut open_input(Path::new(path))?)?,
+        None => BillOfMaterials::default(),
+    };
+
+    let substitution_groups = match &options.substitution_groups_file {
+        Some(path) => parse_substitution_groups(&mut open_input(Path::new(path))?)?,
+        None => SubstitutionGroups::default(),
+    };
+
+    let item_aliases = match &options.aliases_file {
+        Some(path) => parse_item_aliases(&mut open_input(Path::new(path))?)?,
+        None => ItemAliases::default(),
+    };
+
+    let nesting_rules = match &options.nesting_rules_file {
+        Some(path) => parse_nesting_rules(&mut open_input(Path::new(path))?)?,
+        None => Vec::new(),
+    };
+
+    let class_limits = match &options.class_limits_file {
+        Some(path) => parse_class_limits(&mut open_input(Path::new(path))?)?,
+        None => Vec::new(),
+    };
+
+    let excluded_items = match &options.exclude_file {
+        Some(path) => parse_id_patterns(&mut open_input(Path::new(path))?)?,
+        None => Vec::new(),
+    };
 
-    for (index, template) in items.iter().enumerate() {
-        if template.weight > pack_template.maximum_weight {
-            // Uh oh
-            panic!("A single item weighs more than the maximum weight of the pack. We will never be able to add it.");
+    let destination_limits = match &options.destination_limits_file {
+        Some(path) => parse_destination_limits(&mut open_input(Path::new(path))?)?,
+        None => Vec::new(),
+    };
+
+    let delivery_stops = match &options.delivery_stops_file {
+        Some(path) => Some(parse_delivery_stops(&mut open_input(Path::new(path))?)?),
+        None => None,
+    };
+
+    let mut configuration = Configuration {
+        rules,
+        additional_items,
+        bill_of_materials,
+        keep_kits_together: options.keep_kits_together,
+        prefer_homogeneous: options.prefer_homogeneous,
+        substitution_groups,
+        nesting_rules,
+        class_limits,
+        excluded_items,
+        item_aliases,
+        delivery_stops,
+        destination_limits,
+        pack_sequence: options.sequence_file.as_deref().map(PackSequence::new),
+        statistics_file: options.stats_file.as_deref().map(StatisticsFile::new),
+        split_output: options
+            .split_output
+            .as_deref()
+            .map(|directory| SplitOutput {
+                directory: directory.into(),
+                existing_output: options.existing_output,
+            }),
+        checkpoint: options.checkpoint.clone(),
+        on_infeasible: options.on_infeasible,
+        duplicate_ids: options.duplicate_ids,
+        tie_break: options.tie_break,
+        minimum_packs: options.minimum_packs,
+        item_filters: options.item_filters.clone(),
+        item_maps: options.item_maps.clone(),
+        render_options: RenderOptions {
+            format: options.format.or(profile.format).unwrap_or_default(),
+            weight_bands,
+            cartons,
+            explain: options.explain || profile.explain.unwrap_or(false),
+            heaviest_first: options.heaviest_first || profile.heaviest_first.unwrap_or(false),
+            precision: profile
+                .precision
+                .unwrap_or(RenderOptions::default().precision),
+            weight_unit: profile.weight_unit,
+            grades: options.grades.clone(),
+            handling_time: options.handling_time.or(profile.handling_time),
+            // The plan is only written to the terminal when it isn't written to a file or sent elsewhere.
+            color: options.color.is_enabled(
+                options.output_file.is_none()
+                    && options.callback_url.is_none()
+                    && options.daemon_socket.is_none()
+                    && is_color_terminal(&io::stdout()),
+            ),
+        },
+        quick_check: options.quick_check || profile.quick_check.unwrap_or(false),
+        sort_order: options.sort_order.or(profile.sort_order),
+        maximum_number_of_pieces: options.maximum_number_of_pieces,
+        maximum_weight: options.maximum_weight,
+        ..Configuration::default()
+    };
+    // Without a number of restarts a time limit keeps restarting until the time is up, or until the plan
+    // can't be improved.
+    let restarts = options
+        .restarts
+        .or(profile.restarts)
+        .or(options.time_limit.map(|_| UNLIMITED_RESTARTS));
+    if options.bin_completion || options.auto_strategy {
+        if restarts.is_some() {
+            return Err(Error::IncompatibleArguments {
+                argument: "--algorithm".to_string(),
+                other_argument: if options.time_limit.is_some() && options.restarts.is_none() {
+                    "--time-limit".to_string()
+                } else {
+                    "--restarts".to_string()
+                },
+            });
         }
 
-        let mut items_left_from_current_batch = template.count;
-        while items_left_from_current_batch > 0 {
-            let mut items_to_add = maximum_number_of_items_to_add(
-                &pack_template,
-                current_pack_weight,
-                current_pack_item_count,
-                &template,
-            );
-            if items_to_add > 0 {
-                let items_to_pack: i32;
-                (items_to_pack, items_left_from_current_batch) =
-                    if items_to_add < items_left_from_current_batch {
-                        (items_to_add, items_left_from_current_batch - items_to_add)
-                    } else {
-                        (items_left_from_current_batch, 0)
-                    };
-
-                print_item_line(&template, items_to_pack);
-                current_pack_weight += (items_to_pack as f64) * template.weight;
-                current_pack_item_count += items_to_pack;
-
-                longest_item_in_pack = if template.length > longest_item_in_pack {
-                    template.length
+        configuration.algorithm = if options.auto_strategy {
+            // An explicitly chosen sort order is kept, and the decision is logged with the warnings.
+            Box::new(AdaptiveAlgorithm {
+                keep_sort_order: configuration.sort_order.is_some(),
+                decision_hook: Some(Arc::new(|decision| eprintln!("Strategy: {decision}"))),
+            })
+        } else {
+            Box::new(BinCompletionAlgorithm::default())
+        };
+    }
+    if let Some(restarts) = restarts {
+        // A checkpointed plan places the items only once, so it can't be restarted.
+        if let Some(checkpoint) = &options.checkpoint {
+            return Err(Error::IncompatibleArguments {
+                argument: if checkpoint.resume {
+                    "--resume".to_string()
                 } else {
-                    longest_item_in_pack
-                };
+                    "--checkpoint".to_string()
+                },
+                other_argument: if options.time_limit.is_some() && options.restarts.is_none() {
+                    "--time-limit".to_string()
+                } else {
+                    "--restarts".to_string()
+                },
+            });
+        }
+
+        configuration.algorithm = Box::new(RestartAlgorithm {
+            restarts,
+            seed: options.seed.or(profile.seed).unwrap_or_default(),
+            objective: options.objective.or(profile.objective).unwrap_or_default(),
+            time_limit: options.time_limit,
+        });
+    }
+    if options.partition {
+        let algorithm = std::mem::replace(&mut configuration.algorithm, Box::new(GreedyAlgorithm));
+        configuration.algorithm = Box::new(PartitionedAlgorithm { algorithm });
+    }
+    if let Some(url) = &options.pack_closed_url {
+        if let Some(other_argument) = argument_without_reported_packs(options, profile.restarts) {
+            return Err(Error::IncompatibleArguments {
+                argument: "--pack-closed-url".to_string(),
+                other_argument: other_argument.to_string(),
+            });
+        }
 
-                items_to_add -= items_to_pack;
+        let url = url.clone();
+        let render_options = configuration.render_options.clone();
+        configuration.pack_closed_hook = Some(Arc::new(move |pack| {
+            // The plan is still being made, so a pack that can't be delivered doesn't stop it.
+            if let Err(e) = post_closed_pack(&url, pack, &render_options) {
+                eprintln!("Failed to send the closed pack: {e}");
             }
+        }));
+    }
+    if let Some(path) = &options.event_log_file {
+        // The event log records the same steps as the closed packs that are sent.
+        if let Some(other_argument) = argument_without_reported_packs(options, profile.restarts) {
+            return Err(Error::IncompatibleArguments {
+                argument: "--event-log".to_string(),
+                other_argument: other_argument.to_string(),
+            });
+        }
 
-            if items_to_add <= 0 {
-                // Print a summary if we have printed at least one pack
-                if pack_index > 0 {
-                    print_footer(current_pack_weight, longest_item_in_pack);
-                    println!();
-                }
+        configuration.event_log = Some(Arc::new(EventLog::create(
+            Path::new(path),
+            options.existing_output,
+        )?));
+    }
 
-                pack_index += 1;
-                longest_item_in_pack = 0.0;
-                current_pack_weight = 0.0;
-                current_pack_item_count = 0;
+    Ok(configuration)
+}
 
-                // print the header
-                if index <= items.len() - 1 && items_left_from_current_batch > 0 {
-                    println!("Pack Number: {pack_index}");
-                }
-            }
+/// Creates a single plan, or runs the daemon, as described by the options and returns the exit status.
+fn run(options: &Options) -> Result<ExitStatus, Error> {
+    if let Some(manifest_path) = &options.batch_manifest {
+        return run_batch_manifest(manifest_path, options.notify_url.as_deref());
+    }
+
+    if !options.plans_to_merge.is_empty() {
+        return run_merge_plans(
+            &options.plans_to_merge,
+            options.output_file.as_deref(),
+            options.existing_output,
+        );
+    }
+
+    if let Some(schema) = options.schema {
+        return run_schema(schema);
+    }
+
+    if let Some(plan_path) = &options.plan_to_ship {
+        return run_ship(plan_path, options.carrier_file.as_deref());
+    }
+
+    // The input of an attested plan is hashed while it is read, which isn't possible for the requests of the
+    // daemon and the fetched orders.
+    if options.attest {
+        let other_argument = if options.daemon_socket.is_some() {
+            Some("--daemon")
+        } else if options.fetch_url.is_some() {
+            Some("--fetch-url")
+        } else {
+            None
+        };
+        if let Some(other_argument) = other_argument {
+            return Err(Error::IncompatibleArguments {
+                argument: "--attest".to_string(),
+                other_argument: other_argument.to_string(),
+            });
         }
     }
 
-    //print_footer(current_pack_weight, longest_item_in_pack);
+    let configuration = load_configuration(options)?;
+    let event_log = configuration.event_log.clone();
+    let status = run_with_configuration(options, configuration);
+
+    // The event log is moved into place once no more events are written, also when the plan failed, so that
+    // the decisions that led to the failure can be read.
+    let finished = event_log.map_or(Ok(()), |log| log.finish());
+    let status = status?;
+    finished?;
+    Ok(status)
 }
 
-fn main() {
-    let stdin = io::stdin();
-    let (pack_template, item_templates) = parse_input(&mut stdin.lock()).expect("Parsing failure.");
+/// Creates a single plan, or runs the daemon, with the configuration that was loaded for the options, and
+/// returns the exit status.
+fn run_with_configuration(
+    options: &Options,
+    mut configuration: Configuration,
+) -> Result<ExitStatus, Error> {
+    let health = Arc::new(Health::new());
+    if let Some(address) = &options.metrics_address {
+        if options.daemon_socket.is_none() {
+            return Err(Error::UnableToStartMetricsEndpoint {
+                address: address.clone(),
+                reason: "The metrics are only served in daemon mode.".to_string(),
+            });
+        }
 
-    let items = match pack_template.sort_order {
-        PackSortOrder::Natural => {
-            // Do nothing. Just pass it through as it was
-            item_templates
+        let metrics = Arc::new(Metrics::new());
+        start_metrics_endpoint(address, Arc::clone(&metrics), Arc::clone(&health))?;
+        configuration.metrics = Some(metrics);
+    }
+
+    match options.daemon_socket.as_deref() {
+        Some("-") => {
+            let stdin = io::stdin();
+            let stdout = io::stdout();
+            health.set_ready(true);
+            serve_daemon_connection(
+                &mut InputReader::new(stdin.lock(), "the standard input")
+                    .with_retries(DAEMON_READ_RETRIES),
+                &mut stdout.lock(),
+                &configuration,
+                &health,
+                health.start_request(),
+                options.notify_url.as_deref(),
+            )?;
+            return Ok(ExitStatus::Success);
         }
-        PackSortOrder::ShortToLong => {
-            let mut sorted_order = item_templates.clone();
-            sorted_order.sort_by(|a, b| {
-                a.length
-                    .partial_cmp(&b.length)
-                    .expect("There shouldn't be any NaN's")
-            });
-            sorted_order
+        Some(socket_path) => {
+            handle_termination_signal();
+            run_daemon(
+                socket_path,
+                configuration,
+                health,
+                options.notify_url.as_deref(),
+                &SHUTDOWN_REQUESTED,
+            )?;
+            return Ok(ExitStatus::Success);
+        }
+        None => {}
+    }
+
+    // An existing output file is found before the plan is made, so that it doesn't waste a long running plan.
+    if let (ExistingOutput::Keep, Some(path)) = (options.existing_output, &options.output_file) {
+        if !is_object_url(path) && Path::new(path).exists() {
+            return Err(Error::OutputFileExists { path: path.clone() });
+        }
+    }
+
+    // The key is read before the plan is made, so that an invalid key doesn't waste a long running plan.
+    let signing_key = match &options.signing_key_file {
+        Some(path) => {
+            if options.output_file.is_none() {
+                return Err(Error::MissingRequiredArgument {
+                    argument: "--sign".to_string(),
+                    required_argument: "--output".to_string(),
+                });
+            }
+
+            let pem = std::fs::read(path).map_err(|e| Error::UnableToOpenFile {
+                path: path.clone(),
+                reason: e.to_string(),
+            })?;
+            Some(SigningKey::from_pem(&pem, path)?)
+        }
+        None => None,
+    };
+
+    // A plan of many items can take minutes, so the progress is shown while the plan isn't written to the
+    // terminal.
+    let progress_bar = if (options.output_file.is_some() || !io::stdout().is_terminal())
+        && io::stderr().is_terminal()
+    {
+        let progress = Arc::new(Progress::new());
+        configuration.progress = Some(Arc::clone(&progress));
+        Some(ProgressBar::start(progress))
+    } else {
+        None
+    };
+
+    let sections = match (&options.fetch_url, &options.input_file) {
+        (Some(url), _) => {
+            let (pack_template, items) = fetch_order(url)?;
+            vec![InputSection {
+                pack_template,
+                items,
+            }]
+        }
+        (None, Some(path)) => parse_attested_input(
+            &mut read_input(path)?,
+            path,
+            &mut configuration,
+            options.attest,
+        )?,
+        (None, None) => {
+            let stdin = io::stdin();
+            let mut input = decompress(stdin.lock(), "the standard input")?;
+            parse_attested_input(
+                &mut input,
+                "the standard input",
+                &mut configuration,
+                options.attest,
+            )?
         }
-        PackSortOrder::LongToShort => {
-            let mut sorted_order = item_templates.clone();
-            sorted_order.sort_by(|a, b| {
-                b.length
-                    .partial_cmp(&a.length)
-                    .expect("There shouldn't be any NaN's")
+    };
+    if let Some(runs) = options.simulation_runs {
+        let [section] = sections.as_slice() else {
+            return Err(Error::UnsupportedInputSections {
+                argument: "simulate".to_string(),
             });
-            sorted_order
+        };
+        let simulation = Simulation {
+            runs,
+            mode: options.sample_mode,
+            seed: options.seed.unwrap_or_default(),
+        };
+        let report = simulate(
+            &section.pack_template,
+            &section.items,
+            &configuration,
+            &simulation,
+        )?;
+        drop(progress_bar);
+        write_output(
+            options.output_file.as_deref(),
+            options.existing_output,
+            report.to_string().as_bytes(),
+        )?;
+        return Ok(ExitStatus::Success);
+    }
+
+    // The relaxations are suggested for the pack information of a single section.
+    let section_to_probe = match sections.as_slice() {
+        [section] if options.suggest_relaxations => Some(section.clone()),
+        _ => None,
+    };
+
+    let mut plan = Vec::new();
+    let result = plan_sections(sections, &mut plan, &configuration);
+    drop(progress_bar);
+
+    let color = options.color.is_enabled(is_color_terminal(&io::stderr()));
+    if let Some(section) = section_to_probe.filter(|_| has_infeasible_items(&result)) {
+        for relaxation in
+            suggest_relaxations(&section.pack_template, section.items, &configuration)?
+        {
+            eprintln!(
+                "{}",
+                Style::Warning.apply(format!("Suggestion: {relaxation}"), color)
+            );
         }
-        _ => {
-            // Error
-            panic!("Undefined sort order detected.")
+    }
+    let warnings = result?;
+    for warning in &warnings {
+        eprintln!(
+            "{}",
+            Style::Warning.apply(format!("Warning: {warning}"), color)
+        );
+    }
+
+    // In strict mode a plan with warnings is a failed plan, so it isn't written.
+    if options.strict && !warnings.is_empty() {
+        return Ok(exit_status_for_warnings(&warnings));
+    }
+
+    write_output(
+        options.output_file.as_deref(),
+        options.existing_output,
+        &plan,
+    )?;
+    if let (Some(key), Some(output_file)) = (&signing_key, &options.output_file) {
+        write_signature(output_file, options.existing_output, &key.sign(&plan))?;
+    }
+    if let Some(url) = &options.callback_url {
+        post_plan(url, configuration.render_options.format, &plan)?;
+    }
+
+    Ok(exit_status_for_warnings(&warnings))
+}
+
+fn main() -> process::ExitCode {
+    let mut diagnostics = DiagnosticsFormat::Plain;
+    let status = match parse_arguments(env::args().skip(1)).and_then(|options| {
+        diagnostics = options.diagnostics;
+        run(&options)
+    }) {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("{}", render(&e, diagnostics));
+            exit_status_for(&e)
         }
     };
 
-    print_packs(items, pack_template);
+    process::ExitCode::from(status as u8)
 }
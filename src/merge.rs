@@ -0,0 +1,290 @@
+//! Merging of plans that were written in the text format, e.g. the plans of the different shifts of a day,
+//! into a single plan.
+//!
+//! The packs of the plans are numbered one after the other, in the order in which the plans are merged, so
+//! that every pack of the merged plan has a unique number. All other lines of the packs are copied as they
+//! are. The backorders and the unpacked items of all the plans are written after the packs of the merged plan.
+//! The attestations, the totals, the number of packs by grade and the total packing times of the plans are
+//! left out, because the merged plan wasn't made in a single run.
+
+use std::io::{self, BufRead, Write};
+
+use crate::errors::Error;
+use crate::input::InputReader;
+
+#[cfg(test)]
+#[path = "merge_tests.rs"]
+mod tests;
+
+// The start of the first line of a pack
+const PACK_HEADER: &str = "Pack Number: ";
+
+// The start of the last line of a pack
+const PACK_FOOTER: &str = "Pack Length: ";
+
+// The start of the line that separates the packs of different storage classes
+const STORAGE_CLASS_HEADER: &str = "Storage Class: ";
+
+// The start of the line that describes a backorder, which is written after the packs
+const BACKORDER_HEADER: &str = "Backorder: ";
+
+// The start of the line that describes items that are not packed, which is written after the backorders
+const UNPACKED_HEADER: &str = "Unpacked: ";
+
+// The start of the attestation of the plan, which is followed by the lines of the configuration, each
+// indented by two spaces
+const ATTESTATION_HEADER: &str = "Attestation: ";
+
+// The start of the line that precedes the plan of each section of the input, when the input has more than
+// one section
+const SECTION_HEADER: &str = "Section: ";
+
+// The start of the line with the total packing time of the plan, which is written after the packs
+const PACKING_TIME_HEADER: &str = "Total Packing Time: ";
+
+// The start of the line with the number of packs with each grade, which is written after the packs
+const GRADES_HEADER: &str = "Grades: ";
+
+// The start of the line with the totals of the items of the plan, which is written after the packs
+const TOTALS_HEADER: &str = "Totals: ";
+
+// The start of the report of the additional items, which refers to the pack numbers of the original plan
+const FIT_REPORT_HEADER: &str = "Additional Items: ";
+
+// The number of decimals with which the weights of the summary are written
+const SUMMARY_PRECISION: usize = 1;
+
+/// Describes the packs that one of the merged plans contributed to the merged plan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergedPlan {
+    /// The name of the plan, e.g. the path of the file it was read from.
+    pub source: String,
+
+    /// The number of the first pack of the plan in the merged plan, or `None` if the plan has no packs.
+    pub first_pack_number: Option<usize>,
+
+    /// The number of packs of the plan.
+    pub number_of_packs: usize,
+
+    /// The number of pieces in the packs of the plan. A placement of bulk counts as a single piece.
+    pub number_of_items: i64,
+
+    /// The total weight of the packs of the plan.
+    pub weight: f64,
+}
+
+impl MergedPlan {
+    fn new(source: &str) -> MergedPlan {
+        MergedPlan {
+            source: source.to_string(),
+            first_pack_number: None,
+            number_of_packs: 0,
+            number_of_items: 0,
+            weight: 0.0,
+        }
+    }
+}
+
+/// Returns the number of pieces of an item line, i.e. `[id],[length],[count],[weight]` or
+/// `[id],-,TOTAL_KG=[weight]` for bulk, optionally followed by `ALIAS_OF=[id]`.
+fn number_of_pieces(line: &str) -> Option<i64> {
+    let values: Vec<&str> = line.split(',').collect();
+    match values.as_slice() {
+        [_, "-", ..] => Some(1),
+        [_, _, count, _] | [_, _, count, _, _] => count.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Returns the weight of the pack from the footer of the pack, i.e.
+/// `Pack Length: [length], Pack Weight: [weight]`, optionally followed by the unit and other properties.
+fn pack_weight(footer: &str) -> Option<f64> {
+    let (_, weight) = footer.split_once("Pack Weight: ")?;
+    weight.split([',', ' ']).next().and_then(|w| w.parse().ok())
+}
+
+/// Copies the packs of a single plan to the writer, numbering the packs from the given pack number onwards.
+/// The backorder lines are added to the backorders, and the unpacked item lines to the unpacked items, instead.
+fn merge_plan<R: BufRead, W: Write>(
+    source: &str,
+    reader: R,
+    writer: &mut W,
+    next_pack_number: usize,
+    backorders: &mut Vec<String>,
+    unpacked_items: &mut Vec<String>,
+) -> Result<MergedPlan, Error> {
+    let to_write_error = |e: io::Error| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    };
+    let to_plan_error = |line: &str, reason: &str| Error::InvalidPlan {
+        path: source.to_string(),
+        line: line.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut merged_plan = MergedPlan::new(source);
+    let mut is_in_pack = false;
+    let mut is_in_attestation = false;
+    let mut is_after_skipped_line = false;
+    let mut reader = InputReader::new(reader, source);
+    while let Some(line) = reader.next_line()? {
+        if line.starts_with(ATTESTATION_HEADER) && !is_in_pack {
+            is_in_attestation = true;
+            continue;
+        }
+
+        if is_in_attestation {
+            is_in_attestation = line.starts_with("  ");
+            continue;
+        }
+
+        // The packs of all the sections are numbered in a single sequence, so the sections, and the empty line
+        // that follows their header, aren't kept. Neither are the summary lines of each plan.
+        if [
+            SECTION_HEADER,
+            GRADES_HEADER,
+            TOTALS_HEADER,
+            PACKING_TIME_HEADER,
+        ]
+        .iter()
+        .any(|header| line.starts_with(header))
+            && !is_in_pack
+        {
+            is_after_skipped_line = true;
+            continue;
+        }
+
+        if is_after_skipped_line {
+            is_after_skipped_line = false;
+            if line.is_empty() {
+                continue;
+            }
+        }
+
+        if let Some(header) = line.strip_prefix(PACK_HEADER) {
+            if is_in_pack {
+                return Err(to_plan_error(
+                    &line,
+                    "The previous pack doesn't have a footer.",
+                ));
+            }
+
+            // The header is either the pack number, or the pack number followed by the destination.
+            let (_, details) = header.split_at(header.find(',').unwrap_or(header.len()));
+            let pack_number = next_pack_number + merged_plan.number_of_packs;
+            writeln!(writer, "{PACK_HEADER}{pack_number}{details}").map_err(to_write_error)?;
+
+            merged_plan.first_pack_number.get_or_insert(pack_number);
+            merged_plan.number_of_packs += 1;
+            is_in_pack = true;
+            continue;
+        }
+
+        if line.starts_with(FIT_REPORT_HEADER) {
+            return Err(to_plan_error(
+                &line,
+                "Plans with additional items can't be merged, because the additional items refer to the original pack numbers.",
+            ));
+        }
+
+        if is_in_pack {
+            if line.starts_with(PACK_FOOTER) {
+                merged_plan.weight += pack_weight(&line)
+                    .ok_or_else(|| to_plan_error(&line, "The pack weight is not a number."))?;
+                is_in_pack = false;
+            } else if !line.starts_with(' ') && !line.starts_with("Sheet Position: ") {
+                merged_plan.number_of_items += number_of_pieces(&line)
+                    .ok_or_else(|| to_plan_error(&line, "The line is not a placement."))?;
+            }
+        } else if line.starts_with(BACKORDER_HEADER) {
+            backorders.push(line);
+            continue;
+        } else if line.starts_with(UNPACKED_HEADER) {
+            unpacked_items.push(line);
+            continue;
+        } else if !line.is_empty() && !line.starts_with(STORAGE_CLASS_HEADER) {
+            return Err(to_plan_error(
+                &line,
+                "Expected a line that starts with 'Pack Number:' or 'Storage Class:'.",
+            ));
+        }
+
+        writeln!(writer, "{line}").map_err(to_write_error)?;
+    }
+
+    if is_in_pack {
+        return Err(to_plan_error("", "The last pack doesn't have a footer."));
+    }
+
+    Ok(merged_plan)
+}
+
+/// Merges the plans, each of which is provided with its name and a reader of the plan in the text format,
+/// into a single plan, in which the packs are numbered from 1 in the order of the plans, followed by the
+/// backorders and the unpacked items of all the plans. Returns the packs that each plan contributed. Plans that contain a report of additional items can't be merged.
+pub fn merge_plans<R: BufRead, W: Write>(
+    plans: Vec<(String, R)>,
+    writer: &mut W,
+) -> Result<Vec<MergedPlan>, Error> {
+    let mut merged_plans = Vec::with_capacity(plans.len());
+    let mut backorders = Vec::new();
+    let mut unpacked_items = Vec::new();
+    let mut next_pack_number = 1;
+    for (source, reader) in plans {
+        let merged_plan = merge_plan(
+            &source,
+            reader,
+            writer,
+            next_pack_number,
+            &mut backorders,
+            &mut unpacked_items,
+        )?;
+        next_pack_number += merged_plan.number_of_packs;
+        merged_plans.push(merged_plan);
+    }
+
+    let to_write_error = |e: io::Error| Error::UnableToWriteOutput {
+        reason: e.to_string(),
+    };
+    for lines in [&backorders, &unpacked_items] {
+        for line in lines {
+            writeln!(writer, "{line}").map_err(to_write_error)?;
+        }
+        if !lines.is_empty() {
+            writeln!(writer).map_err(to_write_error)?;
+        }
+    }
+
+    Ok(merged_plans)
+}
+
+/// Writes the summary of the merged plan, with a line for each of the plans that were merged, followed by
+/// the totals of the merged plan.
+pub fn write_merge_summary<W: Write>(
+    writer: &mut W,
+    merged_plans: &[MergedPlan],
+) -> io::Result<()> {
+    for plan in merged_plans {
+        let packs = match plan.first_pack_number {
+            Some(first) => format!("{}-{}", first, first + plan.number_of_packs - 1),
+            None => "NONE".to_string(),
+        };
+        writeln!(
+            writer,
+            "Merged Plan: {}, Packs: {}, Items: {}, Weight: {:.*}",
+            plan.source, packs, plan.number_of_items, SUMMARY_PRECISION, plan.weight
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "Total Packs: {}, Total Items: {}, Total Weight: {:.*}",
+        merged_plans
+            .iter()
+            .map(|p| p.number_of_packs)
+            .sum::<usize>(),
+        merged_plans.iter().map(|p| p.number_of_items).sum::<i64>(),
+        SUMMARY_PRECISION,
+        merged_plans.iter().map(|p| p.weight).sum::<f64>()
+    )
+}
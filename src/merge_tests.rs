@@ -0,0 +1,226 @@
+use super::*;
+
+const MORNING_PLAN: &str = "Pack Number: 1
+1001,100.0,5,2.0
+Pack Length: 100.0, Pack Weight: 10.0
+
+Pack Number: 2, Destination: north
+1001,100.0,2,2.0
+1002,-,TOTAL_KG=3.5,REMAINING_KG=0.0
+Pack Length: 100.0, Pack Weight: 7.5 kg, Overweight: REVIEW
+
+";
+
+const EVENING_PLAN: &str = "Storage Class: frozen
+
+Pack Number: 1
+2001,50.0,3,4.0
+  Placed 3 of 3 remaining items, limited by: end of batch. Remaining weight: 20.0 -> 8.0, remaining pieces: 5 -> 2
+Pack Length: 50.0, Pack Weight: 12.0
+
+Backorder: 2001, Ordered: 5, Available: 3, Short: 2
+
+";
+
+/// Merges the plans and returns the merged plan.
+fn merge(plans: &[(&str, &str)]) -> Result<(String, Vec<MergedPlan>), Error> {
+    let plans = plans
+        .iter()
+        .map(|(source, plan)| (source.to_string(), plan.as_bytes()))
+        .collect();
+    let mut output = Vec::new();
+    let merged_plans = merge_plans(plans, &mut output)?;
+    Ok((String::from_utf8(output).unwrap(), merged_plans))
+}
+
+// merge_plans()
+
+#[test]
+fn when_merging_plans_it_should_number_the_packs_one_after_the_other() {
+    let (output, merged_plans) =
+        merge(&[("morning.txt", MORNING_PLAN), ("evening.txt", EVENING_PLAN)]).unwrap();
+
+    let headers: Vec<&str> = output
+        .lines()
+        .filter(|l| l.starts_with("Pack Number"))
+        .collect();
+    assert_eq!(
+        headers,
+        vec![
+            "Pack Number: 1",
+            "Pack Number: 2, Destination: north",
+            "Pack Number: 3"
+        ]
+    );
+    assert!(output.contains("Storage Class: frozen\n\nPack Number: 3\n2001,50.0,3,4.0\n  Placed"));
+    assert!(output.contains("Pack Length: 100.0, Pack Weight: 7.5 kg, Overweight: REVIEW\n"));
+
+    assert_eq!(
+        merged_plans,
+        vec![
+            MergedPlan {
+                source: "morning.txt".to_string(),
+                first_pack_number: Some(1),
+                number_of_packs: 2,
+                number_of_items: 8,
+                weight: 17.5,
+            },
+            MergedPlan {
+                source: "evening.txt".to_string(),
+                first_pack_number: Some(3),
+                number_of_packs: 1,
+                number_of_items: 3,
+                weight: 12.0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn when_merging_plans_with_backorders_it_should_write_the_backorders_after_the_packs() {
+    let (output, _) =
+        merge(&[("evening.txt", EVENING_PLAN), ("morning.txt", MORNING_PLAN)]).unwrap();
+
+    assert!(output.ends_with(
+        "Pack Number: 3, Destination: north
+1001,100.0,2,2.0
+1002,-,TOTAL_KG=3.5,REMAINING_KG=0.0
+Pack Length: 100.0, Pack Weight: 7.5 kg, Overweight: REVIEW
+
+Backorder: 2001, Ordered: 5, Available: 3, Short: 2
+
+"
+    ));
+}
+
+#[test]
+fn when_merging_plans_with_unpacked_items_it_should_write_them_after_the_backorders() {
+    let plan = "Pack Number: 1
+3001,10.0,1,1.0
+Pack Length: 10.0, Pack Weight: 1.0
+
+Unpacked: 3002, Count: 2, Reason: excluded
+
+";
+    let (output, _) = merge(&[("late.txt", plan), ("evening.txt", EVENING_PLAN)]).unwrap();
+
+    assert!(output.ends_with(
+        "Backorder: 2001, Ordered: 5, Available: 3, Short: 2
+
+Unpacked: 3002, Count: 2, Reason: excluded
+
+"
+    ));
+}
+
+#[test]
+fn when_merging_attested_plans_it_should_leave_out_the_attestations() {
+    let plan = format!(
+        "Attestation: Tool Version: 0.1.0, Input SHA-256: 0123, Timestamp: 1700000000
+  [pack]
+  maximum_number_of_pieces = 10
+
+{MORNING_PLAN}"
+    );
+    let (output, _) = merge(&[("attested.txt", &plan)]).unwrap();
+
+    assert!(output.starts_with("Pack Number: 1\n"));
+    assert!(!output.contains("Attestation"));
+}
+
+#[test]
+fn when_merging_a_plan_with_sections_it_should_number_the_packs_of_all_sections_in_sequence() {
+    let plan = format!("Section: 1\n\n{MORNING_PLAN}Section: 2\n\n{MORNING_PLAN}");
+    let (output, merged_plans) = merge(&[("sections.txt", &plan)]).unwrap();
+
+    assert!(output.starts_with("Pack Number: 1\n"));
+    assert!(output.contains("Pack Number: 4, Destination: north\n"));
+    assert!(!output.contains("Section"));
+    assert_eq!(merged_plans[0].number_of_packs, 4);
+}
+
+#[test]
+fn when_merging_plans_with_summary_lines_it_should_leave_out_the_summary_lines() {
+    let plan = format!(
+        "{MORNING_PLAN}Grades: A: 1, B: 1\n\n\
+         Total Packing Time: 3.5 min\n\n\
+         Totals: Input Items: 7, Input Weight: 17.5, Packed Items: 7, Packed Weight: 17.5\n\n"
+    );
+    let (output, merged_plans) = merge(&[("morning.txt", &plan), ("evening.txt", &plan)]).unwrap();
+
+    assert!(!output.contains("Grades"));
+    assert!(!output.contains("Totals"));
+    assert!(!output.contains("Total Packing Time"));
+    assert_eq!(merged_plans[1].first_pack_number, Some(3));
+}
+
+#[test]
+fn when_merging_a_plan_without_packs_it_should_not_use_pack_numbers() {
+    let (output, merged_plans) =
+        merge(&[("empty.txt", ""), ("evening.txt", EVENING_PLAN)]).unwrap();
+
+    assert!(output.contains("Pack Number: 1\n"));
+    assert_eq!(merged_plans[0].first_pack_number, None);
+    assert_eq!(merged_plans[1].first_pack_number, Some(1));
+}
+
+#[test]
+fn when_merging_a_plan_with_additional_items_it_should_return_an_error() {
+    let plan = format!("{MORNING_PLAN}Additional Items: FIT\nPack Number: 1, 3001,10.0,1,1.0\n");
+    let result = merge(&[("morning.txt", MORNING_PLAN), ("fit.txt", &plan)]);
+    assert!(matches!(
+        result,
+        Err(Error::InvalidPlan { path, line, .. }) if path == "fit.txt" && line == "Additional Items: FIT"
+    ));
+}
+
+#[test]
+fn when_merging_a_file_that_is_not_a_text_plan_it_should_return_an_error() {
+    for plan in [
+        "{\n  \"packs\": []\n}\n",
+        "Pack Number: 1\n1001,100.0,5,2.0\n",
+        "Pack Number: 1\nnot an item\nPack Length: 1.0, Pack Weight: 1.0\n",
+    ] {
+        assert!(
+            matches!(
+                merge(&[("plan.json", plan)]),
+                Err(Error::InvalidPlan { path, .. }) if path == "plan.json"
+            ),
+            "{plan}"
+        );
+    }
+}
+
+#[test]
+fn when_a_plan_is_not_valid_utf8_it_should_return_the_plan_and_the_offset() {
+    let plans = vec![("plan.txt".to_string(), &b"Pack Number: 1\n\xff\n"[..])];
+    let mut output = Vec::new();
+
+    assert!(matches!(
+        merge_plans(plans, &mut output),
+        Err(Error::Io { origin, offset: 15, .. }) if origin == "plan.txt"
+    ));
+}
+
+// write_merge_summary()
+
+#[test]
+fn when_writing_the_merge_summary_it_should_list_each_plan_and_the_totals() {
+    let (_, merged_plans) = merge(&[
+        ("morning.txt", MORNING_PLAN),
+        ("empty.txt", ""),
+        ("evening.txt", EVENING_PLAN),
+    ])
+    .unwrap();
+
+    let mut output = Vec::new();
+    write_merge_summary(&mut output, &merged_plans).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Merged Plan: morning.txt, Packs: 1-2, Items: 8, Weight: 17.5
+Merged Plan: empty.txt, Packs: NONE, Items: 0, Weight: 0.0
+Merged Plan: evening.txt, Packs: 3-3, Items: 3, Weight: 12.0
+Total Packs: 3, Total Items: 11, Total Weight: 29.5
+"
+    );
+}
@@ -0,0 +1,236 @@
+//! Counters of the plans created by a long running planner, e.g. the daemon, which are exposed in the
+//! Prometheus text format so that the planner can be monitored like any other service. The health and the
+//! readiness of the planner are exposed next to the metrics, e.g. for the probes of Kubernetes.
+
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(test)]
+#[path = "metrics_tests.rs"]
+mod tests;
+
+// The upper bounds, in seconds, of the buckets of the planning duration histogram
+const DURATION_BUCKETS: [f64; 8] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+// The path on which the metrics are served
+const METRICS_PATH: &str = "/metrics";
+
+// The path that reports if the planner is running
+const HEALTH_PATH: &str = "/healthz";
+
+// The path that reports if the planner accepts requests
+const READINESS_PATH: &str = "/readyz";
+
+/// Contains the counters of the requests that were handled, and the items and packs that were planned. The
+/// counters can be updated from several threads at the same time.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    requests: AtomicU64,
+    failed_requests: AtomicU64,
+    items_planned: AtomicU64,
+    packs_produced: AtomicU64,
+    duration_buckets: [AtomicU64; DURATION_BUCKETS.len()],
+    duration_count: AtomicU64,
+    duration_sum_nanoseconds: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics::default()
+    }
+
+    /// Records a handled request, how long it took and if it succeeded.
+    pub fn record_request(&self, duration: Duration, succeeded: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if !succeeded {
+            self.failed_requests.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let seconds = duration.as_secs_f64();
+        for (bucket, upper_bound) in self.duration_buckets.iter().zip(DURATION_BUCKETS) {
+            if seconds <= upper_bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        self.duration_count.fetch_add(1, Ordering::Relaxed);
+        self.duration_sum_nanoseconds.fetch_add(
+            u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Records a plan with the given number of items and packs.
+    pub fn record_plan(&self, number_of_items: u64, number_of_packs: u64) {
+        self.items_planned
+            .fetch_add(number_of_items, Ordering::Relaxed);
+        self.packs_produced
+            .fetch_add(number_of_packs, Ordering::Relaxed);
+    }
+
+    /// Writes the metrics in the Prometheus text format.
+    pub fn write_prometheus<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let counters = [
+            (
+                "pack_planner_requests_total",
+                "The number of plan requests that were handled.",
+                &self.requests,
+            ),
+            (
+                "pack_planner_failed_requests_total",
+                "The number of plan requests that failed.",
+                &self.failed_requests,
+            ),
+            (
+                "pack_planner_items_planned_total",
+                "The number of items that were placed in packs.",
+                &self.items_planned,
+            ),
+            (
+                "pack_planner_packs_produced_total",
+                "The number of packs that were planned.",
+                &self.packs_produced,
+            ),
+        ];
+        for (name, help, counter) in counters {
+            writeln!(writer, "# HELP {name} {help}")?;
+            writeln!(writer, "# TYPE {name} counter")?;
+            writeln!(writer, "{name} {}", counter.load(Ordering::Relaxed))?;
+        }
+
+        let name = "pack_planner_planning_duration_seconds";
+        writeln!(
+            writer,
+            "# HELP {name} The time it took to handle a plan request."
+        )?;
+        writeln!(writer, "# TYPE {name} histogram")?;
+        for (bucket, upper_bound) in self.duration_buckets.iter().zip(DURATION_BUCKETS) {
+            writeln!(
+                writer,
+                "{name}_bucket{{le=\"{upper_bound}\"}} {}",
+                bucket.load(Ordering::Relaxed)
+            )?;
+        }
+
+        let count = self.duration_count.load(Ordering::Relaxed);
+        writeln!(writer, "{name}_bucket{{le=\"+Inf\"}} {count}")?;
+        writeln!(
+            writer,
+            "{name}_sum {}",
+            self.duration_sum_nanoseconds.load(Ordering::Relaxed) as f64 / 1e9
+        )?;
+        writeln!(writer, "{name}_count {count}")
+    }
+}
+
+/// Tracks if a long running planner accepts requests, and the requests that it is handling, so that the
+/// planner can finish the requests in flight before it stops.
+#[derive(Debug, Default)]
+pub struct Health {
+    is_ready: AtomicBool,
+    requests_in_flight: AtomicU64,
+}
+
+impl Health {
+    /// Creates the health of a planner that doesn't accept requests yet.
+    pub fn new() -> Health {
+        Health::default()
+    }
+
+    /// Indicates if the planner accepts requests.
+    pub fn is_ready(&self) -> bool {
+        self.is_ready.load(Ordering::SeqCst)
+    }
+
+    pub fn set_ready(&self, is_ready: bool) {
+        self.is_ready.store(is_ready, Ordering::SeqCst);
+    }
+
+    /// Returns the number of requests that are being handled.
+    pub fn requests_in_flight(&self) -> u64 {
+        self.requests_in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Records the start of a request. The request is in flight until the returned value is dropped, which
+    /// may happen on another thread, e.g. the thread that handles the connection of the request.
+    pub fn start_request(self: &Arc<Self>) -> RequestInFlight {
+        self.requests_in_flight.fetch_add(1, Ordering::SeqCst);
+        RequestInFlight {
+            health: Arc::clone(self),
+        }
+    }
+}
+
+/// A request that is being handled, see [`Health::start_request`].
+#[derive(Debug)]
+pub struct RequestInFlight {
+    health: Arc<Health>,
+}
+
+impl Drop for RequestInFlight {
+    fn drop(&mut self) {
+        self.health
+            .requests_in_flight
+            .fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Handles a single HTTP request for the metrics or the health of the planner. A `GET /metrics` request is
+/// answered with the metrics. A `GET /healthz` request is answered with `200 OK` while the planner runs, and
+/// a `GET /readyz` request with `200 OK` while the planner accepts requests and `503 Service Unavailable`
+/// otherwise, e.g. while it starts or stops. Any other path is answered with `404 Not Found` and any other
+/// method with `405 Method Not Allowed`. The connection is closed after the response.
+pub fn serve_metrics_request<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    metrics: &Metrics,
+    health: &Health,
+) -> io::Result<()> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // The headers aren't used, but are read so that the client doesn't see the connection reset.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let (status, content_type, body) = if method != "GET" {
+        (
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Method Not Allowed\n".to_vec(),
+        )
+    } else if path == HEALTH_PATH {
+        ("200 OK", "text/plain", b"ok\n".to_vec())
+    } else if path == READINESS_PATH {
+        if health.is_ready() {
+            ("200 OK", "text/plain", b"ready\n".to_vec())
+        } else {
+            (
+                "503 Service Unavailable",
+                "text/plain",
+                b"not ready\n".to_vec(),
+            )
+        }
+    } else if path == METRICS_PATH {
+        let mut body = Vec::new();
+        metrics.write_prometheus(&mut body)?;
+        ("200 OK", "text/plain; version=0.0.4", body)
+    } else {
+        ("404 Not Found", "text/plain", b"Not Found\n".to_vec())
+    };
+
+    write!(
+        writer,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
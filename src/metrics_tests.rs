@@ -0,0 +1,124 @@
+use std::io::Cursor;
+
+use super::*;
+
+fn render(metrics: &Metrics) -> String {
+    let mut output = Vec::new();
+    metrics
+        .write_prometheus(&mut output)
+        .expect("Failed to write the metrics.");
+    String::from_utf8(output).unwrap()
+}
+
+// Metrics::write_prometheus()
+
+#[test]
+fn when_writing_the_metrics_it_should_write_the_counters() {
+    let metrics = Metrics::new();
+    metrics.record_plan(30, 2);
+    metrics.record_plan(5, 1);
+    metrics.record_request(Duration::from_millis(2), true);
+    metrics.record_request(Duration::from_millis(20), false);
+
+    let text = render(&metrics);
+    assert!(text
+        .contains("# TYPE pack_planner_requests_total counter\npack_planner_requests_total 2\n"));
+    assert!(text.contains("pack_planner_failed_requests_total 1\n"));
+    assert!(text.contains("pack_planner_items_planned_total 35\n"));
+    assert!(text.contains("pack_planner_packs_produced_total 3\n"));
+}
+
+#[test]
+fn when_writing_the_metrics_it_should_write_a_cumulative_duration_histogram() {
+    let metrics = Metrics::new();
+    metrics.record_request(Duration::from_millis(2), true);
+    metrics.record_request(Duration::from_millis(20), true);
+    metrics.record_request(Duration::from_secs(10), true);
+
+    let text = render(&metrics);
+    assert!(text.contains("# TYPE pack_planner_planning_duration_seconds histogram\n"));
+    assert!(text.contains("pack_planner_planning_duration_seconds_bucket{le=\"0.001\"} 0\n"));
+    assert!(text.contains("pack_planner_planning_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+    assert!(text.contains("pack_planner_planning_duration_seconds_bucket{le=\"0.05\"} 2\n"));
+    assert!(text.contains("pack_planner_planning_duration_seconds_bucket{le=\"5\"} 2\n"));
+    assert!(text.contains("pack_planner_planning_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+    assert!(text.contains("pack_planner_planning_duration_seconds_sum 10.022\n"));
+    assert!(text.contains("pack_planner_planning_duration_seconds_count 3\n"));
+}
+
+// serve_metrics_request()
+
+fn serve(request: &str, metrics: &Metrics) -> String {
+    serve_with_health(request, metrics, &Health::new())
+}
+
+fn serve_with_health(request: &str, metrics: &Metrics, health: &Health) -> String {
+    let mut reader = Cursor::new(request.as_bytes().to_vec());
+    let mut output = Vec::new();
+    serve_metrics_request(&mut reader, &mut output, metrics, health)
+        .expect("Failed to serve the request.");
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn when_serving_a_metrics_request_it_should_return_the_metrics() {
+    let metrics = Metrics::new();
+    metrics.record_plan(4, 1);
+
+    let response = serve(
+        "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n",
+        &metrics,
+    );
+    let body = render(&metrics);
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains(&format!("Content-Length: {}\r\n", body.len())));
+    assert!(response.ends_with(&format!("\r\n\r\n{body}")));
+}
+
+#[test]
+fn when_serving_a_request_for_another_path_it_should_return_not_found() {
+    let response = serve("GET /health HTTP/1.1\r\n\r\n", &Metrics::new());
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+    let response = serve("GET /metricsz HTTP/1.1\r\n\r\n", &Metrics::new());
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+    let response = serve("POST /metrics HTTP/1.1\r\n\r\n", &Metrics::new());
+    assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+}
+
+#[test]
+fn when_serving_a_health_request_it_should_return_ok() {
+    let response = serve("GET /healthz HTTP/1.1\r\n\r\n", &Metrics::new());
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with("\r\n\r\nok\n"));
+}
+
+#[test]
+fn when_serving_a_readiness_request_it_should_return_if_the_planner_is_ready() {
+    let health = Health::new();
+    let response = serve_with_health("GET /readyz HTTP/1.1\r\n\r\n", &Metrics::new(), &health);
+    assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+
+    health.set_ready(true);
+    let response = serve_with_health("GET /readyz HTTP/1.1\r\n\r\n", &Metrics::new(), &health);
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with("\r\n\r\nready\n"));
+}
+
+// Health
+
+#[test]
+fn when_a_request_is_handled_it_should_be_in_flight_until_it_is_done() {
+    let health = Arc::new(Health::new());
+
+    let first_request = health.start_request();
+    let second_request = health.start_request();
+    assert_eq!(health.requests_in_flight(), 2);
+
+    drop(first_request);
+    assert_eq!(health.requests_in_flight(), 1);
+
+    drop(second_request);
+    assert_eq!(health.requests_in_flight(), 0);
+}
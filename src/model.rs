@@ -0,0 +1,2003 @@
+//! The data types that describe the items, the packs and the constraints on the packs.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::{Add, Bound, Sub};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "text-format")]
+use strum::{Display, EnumString};
+
+use crate::errors::Error;
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "model_tests.rs"]
+mod tests;
+
+/// Counts the checks that the planner makes while it places the items, e.g. a placement of a batch in a pack,
+/// a pack rule or a nesting rule, so that the tests can verify how the work grows with the number of items.
+/// The counts are kept per thread.
+#[cfg(test)]
+pub(crate) mod checks {
+    use std::cell::Cell;
+
+    thread_local! {
+        static CHECKS: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Adds the given number of checks to the count of the current thread.
+    pub(crate) fn add(count: u64) {
+        CHECKS.with(|checks| checks.set(checks.get() + count));
+    }
+
+    /// Returns the number of checks that the current thread made since the last call.
+    #[cfg_attr(not(feature = "text-format"), allow(dead_code))]
+    pub(crate) fn take() -> u64 {
+        CHECKS.with(|checks| checks.replace(0))
+    }
+}
+
+/// Contains the optional attributes of an item. In the input these follow the required item properties
+/// as a set of `[key]=[value]` pairs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ItemAttributes {
+    /// The free-form tags of the item, used by the pack rules.
+    pub tags: Vec<String>,
+
+    /// The 1-based number of the pack in which all the items must be placed, if the item is pinned to a pack.
+    pub pinned_pack: Option<usize>,
+
+    /// Indicates if the current pack should be closed before the item is placed, so that the item starts a
+    /// new pack.
+    pub close_pack_before: bool,
+
+    /// The value of a single item, e.g. for insurance, if the value is known. For a bulk item this is the
+    /// value per kilogram, and for a cut item the value per unit of length.
+    pub value: Option<f64>,
+
+    /// Indicates if the item is a bulk item, e.g. sand, which is measured by its weight instead of being
+    /// counted. A bulk item has a count of 1 and its weight is the total weight of the bulk, which the planner
+    /// may split over several packs in arbitrary amounts.
+    pub bulk: bool,
+
+    /// The storage class of the item. Items of different storage classes are never placed in the same pack.
+    pub storage_class: StorageClass,
+
+    /// Indicates if the item refers to a kit, in which case the ID is the ID of the kit and the count is the
+    /// number of kits. Kits are replaced by their components, from the bill of materials, before the packs are
+    /// planned.
+    pub kit: bool,
+
+    /// The instance of the kit to which the item belongs, if the components of each kit should be kept in the
+    /// same pack.
+    pub kit_instance: Option<KitInstance>,
+
+    /// Indicates if the item refers to a substitution group, in which case the ID is the ID of the group and
+    /// the count is the number of items that is requested. The group is replaced by a selection of its
+    /// members, from the substitution groups, before the packs are planned.
+    pub substitution_group: bool,
+
+    /// The destination to which the item is delivered, if known. Items for different destinations are never
+    /// placed in the same pack.
+    pub destination: Option<String>,
+
+    /// The width of a single item, if the item is flat, e.g. a panel. Only used when the items are laid out
+    /// on sheets.
+    pub width: Option<f64>,
+
+    /// The number of items that was ordered, if known. The count of the item is the number of items that is
+    /// available, and only the available items are planned. The difference is reported as a backorder.
+    pub ordered: Option<i32>,
+
+    /// The multiple in which the items must be packed, if the items are sold in packs of several items, e.g.
+    /// 6 for a six-pack. Only multiples of this number are placed in a pack.
+    pub multiple: Option<i32>,
+
+    /// The ID with which the item was provided, if the ID was an alias that was replaced by the canonical ID
+    /// of the item.
+    pub alias_of: Option<String>,
+
+    /// How the item is cut into pieces, if the item is provided as a total length, e.g. cable. Such an item has
+    /// a count of 1, its length is the total length and its weight the total weight, until it is cut into
+    /// pieces before the packs are planned.
+    pub cut: Option<CutItem>,
+}
+
+/// Describes an item that is provided as a total length with a weight per unit of length, e.g. cable or pipe,
+/// and that is cut into pieces that fit in the packs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CutItem {
+    /// The weight of a single unit of length, in the unit of the lengths of the input.
+    pub weight_per_length: f64,
+
+    /// The length of which the length of each piece is a multiple, if any, e.g. 1000 to cut whole meters
+    /// from lengths in millimeters. The last piece holds what remains of the total length.
+    pub increment: Option<f64>,
+}
+
+/// Limits the total weight and the total number of pieces that are shipped to a destination in a single run,
+/// e.g. the daily pickup cap of the carrier, across all the packs for the destination.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DestinationLimits {
+    /// The destination of the items.
+    pub destination: String,
+
+    /// The maximum number of pieces for the destination, if the number of pieces is limited.
+    pub maximum_number_of_pieces: Option<i32>,
+
+    /// The maximum total weight for the destination, if the weight is limited.
+    pub maximum_weight: Option<f64>,
+}
+
+/// Defines the stop of the delivery route at which the packs for a destination are unloaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryStop {
+    /// The destination of the packs.
+    pub destination: String,
+
+    /// The 1-based number of the stop on the route.
+    pub stop: u32,
+}
+
+/// Identifies a single kit, e.g. the second of three gift sets, of which all the components must be placed in
+/// the same pack.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KitInstance {
+    /// The ID of the kit.
+    pub kit_id: Arc<str>,
+
+    /// The number of the kit, which is unique within the input.
+    pub number: usize,
+}
+
+/// Contains the components of each kit, by kit ID.
+#[derive(Clone, Debug, Default)]
+pub struct BillOfMaterials {
+    /// The components of the kits. The count of each component is the number of that component in a single
+    /// kit.
+    pub kits: HashMap<String, Vec<ItemTemplate>>,
+}
+
+/// Contains the members of each substitution group, by group ID. Any member of a group can take the place of
+/// a requested item of the group, e.g. the red and the blue variant of a product.
+#[derive(Clone, Debug, Default)]
+pub struct SubstitutionGroups {
+    /// The members of the groups, in the order of preference. The count of each member is the number of
+    /// items of that member that is available.
+    pub groups: HashMap<String, Vec<ItemTemplate>>,
+}
+
+/// Maps the aliases of item IDs, e.g. the legacy SKUs of another system, to the canonical IDs of the items.
+#[derive(Clone, Debug, Default)]
+pub struct ItemAliases {
+    /// The canonical ID of each alias.
+    pub aliases: HashMap<String, String>,
+}
+
+/// Defines the temperature zones in which items are stored and shipped.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StorageClass {
+    #[default]
+    Ambient,
+    Chilled,
+    Frozen,
+}
+
+impl fmt::Display for StorageClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StorageClass::Ambient => "ambient",
+            StorageClass::Chilled => "chilled",
+            StorageClass::Frozen => "frozen",
+        })
+    }
+}
+
+impl FromStr for StorageClass {
+    type Err = Error;
+
+    /// Parses the name of a storage class, e.g. `chilled`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "ambient" => Ok(StorageClass::Ambient),
+            "chilled" => Ok(StorageClass::Chilled),
+            "frozen" => Ok(StorageClass::Frozen),
+            _ => Err(Error::InvalidStorageClass {
+                input: value.to_string(),
+                property_value: value.to_string(),
+            }),
+        }
+    }
+}
+
+impl ItemAttributes {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns the smallest number of items that can be placed in a pack, which is the pack multiple of the
+    /// item, or 1 if the item doesn't have a pack multiple.
+    pub fn pack_multiple(&self) -> i32 {
+        self.multiple.unwrap_or(1)
+    }
+}
+
+/// Contains properties for an item and the number of items with these properties as provided in the input.
+///
+/// The ID and the attributes are shared between all copies of a template, so that copying a template for
+/// each placement in a pack doesn't allocate.
+#[derive(Clone, Debug)]
+pub struct ItemTemplate {
+    pub id: Arc<str>,
+    pub length: f64,
+    pub weight: f64,
+    pub count: i32,
+    pub attributes: Arc<ItemAttributes>,
+}
+
+impl ItemTemplate {
+    /// Returns the value of a single item, or of the whole amount of a bulk item or of the whole length of an
+    /// item that is still to be cut, if the value is known.
+    pub fn item_value(&self) -> Option<f64> {
+        if self.attributes.bulk {
+            self.attributes.value.map(|v| v * self.weight)
+        } else if self.attributes.cut.is_some() {
+            self.attributes.value.map(|v| v * self.length)
+        } else {
+            self.attributes.value
+        }
+    }
+}
+
+/// Defines the item properties to which the filter and map expressions can refer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+pub enum ItemField {
+    #[cfg_attr(feature = "text-format", strum(serialize = "length"))]
+    Length,
+    #[cfg_attr(feature = "text-format", strum(serialize = "weight"))]
+    Weight,
+    #[cfg_attr(feature = "text-format", strum(serialize = "count"))]
+    Count,
+    #[cfg_attr(feature = "text-format", strum(serialize = "value"))]
+    Value,
+    #[cfg_attr(feature = "text-format", strum(serialize = "width"))]
+    Width,
+}
+
+impl ItemField {
+    /// Returns the value of the property for the given item, or `None` if the item doesn't have the property.
+    pub fn get(&self, item: &ItemTemplate) -> Option<f64> {
+        match self {
+            ItemField::Length => Some(item.length),
+            ItemField::Weight => Some(item.weight),
+            ItemField::Count => Some(item.count as f64),
+            ItemField::Value => item.attributes.value,
+            ItemField::Width => item.attributes.width,
+        }
+    }
+
+    /// Sets the property of the given item. A count is rounded towards zero.
+    pub fn set(&self, item: &mut ItemTemplate, value: f64) {
+        match self {
+            ItemField::Length => item.length = value,
+            ItemField::Weight => item.weight = value,
+            ItemField::Count => item.count = value.trunc() as i32,
+            ItemField::Value => Arc::make_mut(&mut item.attributes).value = Some(value),
+            ItemField::Width => Arc::make_mut(&mut item.attributes).width = Some(value),
+        }
+    }
+}
+
+/// Defines the comparisons that a filter expression can make.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+pub enum Comparison {
+    #[cfg_attr(feature = "text-format", strum(serialize = "<"))]
+    Less,
+    #[cfg_attr(feature = "text-format", strum(serialize = "<="))]
+    LessOrEqual,
+    #[cfg_attr(feature = "text-format", strum(serialize = ">"))]
+    Greater,
+    #[cfg_attr(feature = "text-format", strum(serialize = ">="))]
+    GreaterOrEqual,
+    #[cfg_attr(feature = "text-format", strum(serialize = "=="))]
+    Equal,
+    #[cfg_attr(feature = "text-format", strum(serialize = "!="))]
+    NotEqual,
+}
+
+/// Defines an expression, e.g. `weight < 30`, that selects the items that are planned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemFilter {
+    pub field: ItemField,
+    pub comparison: Comparison,
+    pub value: f64,
+}
+
+impl ItemFilter {
+    /// Returns true if the item matches the expression. An item without the property never matches.
+    pub fn matches(&self, item: &ItemTemplate) -> bool {
+        let actual = match self.field.get(item) {
+            Some(v) => v,
+            None => return false,
+        };
+
+        match self.comparison {
+            Comparison::Less => actual < self.value,
+            Comparison::LessOrEqual => actual <= self.value,
+            Comparison::Greater => actual > self.value,
+            Comparison::GreaterOrEqual => actual >= self.value,
+            Comparison::Equal => actual == self.value,
+            Comparison::NotEqual => actual != self.value,
+        }
+    }
+}
+
+/// A pattern that selects items by their ID, e.g. `BOLT-*`. A `*` matches any number of characters and a `?`
+/// matches a single character. A pattern without these wildcards matches a single ID.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdPattern {
+    pub pattern: String,
+}
+
+impl IdPattern {
+    pub fn new(pattern: impl Into<String>) -> IdPattern {
+        IdPattern {
+            pattern: pattern.into(),
+        }
+    }
+
+    /// Returns true if the whole ID matches the pattern.
+    pub fn matches(&self, id: &str) -> bool {
+        let pattern: Vec<char> = self.pattern.chars().collect();
+        let id: Vec<char> = id.chars().collect();
+
+        // The position after the last '*', and the position in the ID from which that '*' is retried, so that
+        // the '*' can take one more character when the rest of the pattern doesn't match.
+        let mut star: Option<(usize, usize)> = None;
+        let (mut p, mut i) = (0, 0);
+        while i < id.len() {
+            if p < pattern.len() && pattern[p] == '*' {
+                p += 1;
+                star = Some((p, i));
+            } else if p < pattern.len() && (pattern[p] == '?' || pattern[p] == id[i]) {
+                p += 1;
+                i += 1;
+            } else if let Some((star_p, star_i)) = star {
+                p = star_p;
+                i = star_i + 1;
+                star = Some((star_p, star_i + 1));
+            } else {
+                return false;
+            }
+        }
+
+        pattern[p..].iter().all(|c| *c == '*')
+    }
+}
+
+/// Defines the operations that a map expression can apply.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+pub enum MapOperation {
+    #[cfg_attr(feature = "text-format", strum(serialize = "="))]
+    Assign,
+    #[cfg_attr(feature = "text-format", strum(serialize = "+="))]
+    Add,
+    #[cfg_attr(feature = "text-format", strum(serialize = "-="))]
+    Subtract,
+    #[cfg_attr(feature = "text-format", strum(serialize = "*="))]
+    Multiply,
+    #[cfg_attr(feature = "text-format", strum(serialize = "/="))]
+    Divide,
+}
+
+/// Defines an expression, e.g. `count *= 2`, that changes a property of the items before they are planned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemMap {
+    pub field: ItemField,
+    pub operation: MapOperation,
+    pub value: f64,
+}
+
+impl ItemMap {
+    /// Applies the expression to the item. Only an assignment changes a property that the item doesn't have.
+    /// Returns an error, and leaves the item unchanged, if the expression gives the property a negative value,
+    /// or a value that is not a finite number.
+    pub fn apply(&self, item: &mut ItemTemplate) -> Result<(), Error> {
+        let current = self.field.get(item);
+        let new_value = match (self.operation, current) {
+            (MapOperation::Assign, _) => self.value,
+            (_, None) => return Ok(()),
+            (MapOperation::Add, Some(v)) => v + self.value,
+            (MapOperation::Subtract, Some(v)) => v - self.value,
+            (MapOperation::Multiply, Some(v)) => v * self.value,
+            (MapOperation::Divide, Some(v)) => v / self.value,
+        };
+        if !new_value.is_finite() || new_value < 0.0 {
+            return Err(Error::InvalidMappedValue {
+                expression: self.to_string(),
+                item_id: item.id.to_string(),
+                value: new_value,
+            });
+        }
+
+        self.field.set(item, new_value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ItemMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = match self.field {
+            ItemField::Length => "length",
+            ItemField::Weight => "weight",
+            ItemField::Count => "count",
+            ItemField::Value => "value",
+            ItemField::Width => "width",
+        };
+        let operation = match self.operation {
+            MapOperation::Assign => "=",
+            MapOperation::Add => "+=",
+            MapOperation::Subtract => "-=",
+            MapOperation::Multiply => "*=",
+            MapOperation::Divide => "/=",
+        };
+        write!(f, "{field} {operation} {}", self.value)
+    }
+}
+
+/// Defines the different ways in which packs can be ordered.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PackSortOrder {
+    NotSet,
+    Natural,
+    ShortToLong,
+    LongToShort,
+}
+
+impl fmt::Display for PackSortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PackSortOrder::NotSet => "NotSet",
+            PackSortOrder::Natural => "NATURAL",
+            PackSortOrder::ShortToLong => "SHORT_TO_LONG",
+            PackSortOrder::LongToShort => "LONG_TO_SHORT",
+        })
+    }
+}
+
+impl FromStr for PackSortOrder {
+    type Err = Error;
+
+    /// Parses the name of a sort order, e.g. `NATURAL`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "NotSet" => Ok(PackSortOrder::NotSet),
+            "NATURAL" => Ok(PackSortOrder::Natural),
+            "SHORT_TO_LONG" => Ok(PackSortOrder::ShortToLong),
+            "LONG_TO_SHORT" => Ok(PackSortOrder::LongToShort),
+            _ => Err(Error::InvalidPackSortOrder {
+                input: value.to_string(),
+                property_value: value.to_string(),
+            }),
+        }
+    }
+}
+
+/// Defines how the lengths of the items in a pack contribute to the length of the pack.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum LengthMode {
+    /// The length of the pack is the length of the longest item, i.e. the items are placed side by side.
+    #[default]
+    Maximum,
+
+    /// The length of the pack is the sum of the lengths of the items, i.e. the items are placed end-to-end.
+    Sum,
+
+    /// The length of the pack is the sum of the lengths of the items plus the given gap between each pair
+    /// of consecutive items.
+    SumWithGap(f64),
+}
+
+/// Describes how the numbers in the input are written.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct NumberFormat {
+    /// The character used to group the thousands, e.g. the ',' in `1,200`, if the numbers are grouped. When
+    /// the separator is a period the decimal separator is a comma, e.g. `1.200,5`.
+    pub thousands_separator: Option<char>,
+
+    /// Indicates if numbers may be written in scientific notation, e.g. `1.2e3`.
+    pub allow_scientific_notation: bool,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            thousands_separator: None,
+            allow_scientific_notation: true,
+        }
+    }
+}
+
+/// A part of the input that starts with its own pack information. The items of a section are planned
+/// independently of the items of the other sections.
+#[derive(Clone, Debug)]
+pub struct InputSection {
+    pub pack_template: PackTemplate,
+    pub items: Vec<ItemTemplate>,
+}
+
+/// Contains the limits of a pack and the order in which the items are placed in the packs.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct PackTemplate {
+    pub maximum_number_of_pieces: i32,
+    pub maximum_weight: f64,
+    pub sort_order: PackSortOrder,
+
+    /// The way the item lengths determine the length of a pack.
+    pub length_mode: LengthMode,
+
+    /// The maximum length of a pack, if the length of the pack is limited.
+    pub maximum_length: Option<f64>,
+
+    /// The weight up to which a pack may be overloaded, if overloading is allowed. The planner only exceeds
+    /// the maximum weight when this avoids starting an extra pack for the last few items.
+    pub hard_maximum_weight: Option<f64>,
+
+    /// The maximum total value of the items in a pack, e.g. the insurance limit of the carrier, if the value
+    /// is limited.
+    pub maximum_value: Option<f64>,
+
+    /// The way the numbers in the input are written.
+    pub number_format: NumberFormat,
+
+    /// The pairs of items that nest inside each other, which reduces the length of a pack when the length
+    /// mode sums the lengths of the items.
+    pub nesting_rules: Vec<NestingRule>,
+
+    /// The size of the sheets, if the items are flat and each pack is a sheet on which the items are laid
+    /// out.
+    pub sheet: Option<SheetSize>,
+
+    /// The lower limits of the packs that contain items of a class, e.g. glass that is packed at most 10
+    /// pieces to a pack.
+    pub class_limits: Vec<ClassLimits>,
+
+    /// The weight of the empty packaging, e.g. the carton, which counts towards the maximum weight of the
+    /// pack. The weight of a pack is the net weight of its items, and the gross weight includes the tare.
+    pub tare_weight: f64,
+
+    /// The internal size of a pack, if items that are longer than the internal diagonal of the pack should
+    /// be rejected.
+    pub dimensions: Option<PackDimensions>,
+}
+
+impl PackTemplate {
+    pub fn new() -> PackTemplate {
+        PackTemplate {
+            maximum_number_of_pieces: 0,
+            maximum_weight: 0.0,
+            sort_order: PackSortOrder::NotSet,
+            length_mode: LengthMode::Maximum,
+            maximum_length: None,
+            hard_maximum_weight: None,
+            maximum_value: None,
+            number_format: NumberFormat::default(),
+            nesting_rules: Vec::new(),
+            sheet: None,
+            class_limits: Vec::new(),
+            tare_weight: 0.0,
+            dimensions: None,
+        }
+    }
+
+    /// Returns the pack template of which the maximum weights are those of the items, i.e. the maximum
+    /// weights without the tare, so that the planner only has to consider the net weight of the items.
+    pub fn net_limits(&self) -> PackTemplate {
+        let tare_weight = self.tare_weight;
+        if tare_weight == 0.0 {
+            return self.clone();
+        }
+
+        PackTemplate {
+            maximum_weight: self.maximum_weight - tare_weight,
+            hard_maximum_weight: self.hard_maximum_weight.map(|w| w - tare_weight),
+            class_limits: self
+                .class_limits
+                .iter()
+                .map(|l| ClassLimits {
+                    maximum_weight: l.maximum_weight.map(|w| w - tare_weight),
+                    ..l.clone()
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the limits that apply when the item is added to the pack. These are the limits of the pack
+    /// template, lowered by the class limits of the item and of the items that are already in the pack.
+    pub fn limits_for(&self, pack: &Pack, template: &ItemTemplate) -> Cow<'_, PackTemplate> {
+        let mut limits = Cow::Borrowed(self);
+        for class_limits in &self.class_limits {
+            let applies = template.attributes.has_tag(&class_limits.class)
+                || pack.number_of_items_with_tag(&class_limits.class) > 0;
+            if !applies {
+                continue;
+            }
+
+            if let Some(maximum) = class_limits.maximum_number_of_pieces {
+                if maximum < limits.maximum_number_of_pieces {
+                    limits.to_mut().maximum_number_of_pieces = maximum;
+                }
+            }
+
+            if let Some(maximum) = class_limits.maximum_weight {
+                if maximum < limits.maximum_weight {
+                    limits.to_mut().maximum_weight = maximum;
+                }
+            }
+        }
+
+        limits
+    }
+}
+
+impl Default for PackTemplate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lowers the limits of a pack that contains items of a class, e.g. a pack that holds glass, regardless of the
+/// other items in the pack. The class of an item is one of its tags. The limits that aren't set are those of
+/// the pack template, and a class can't raise the limits of the pack template.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ClassLimits {
+    /// The tag of the items of the class.
+    pub class: String,
+
+    /// The maximum number of pieces in a pack with items of the class, if the number of pieces is lowered.
+    pub maximum_number_of_pieces: Option<i32>,
+
+    /// The maximum weight of a pack with items of the class, if the weight is lowered.
+    pub maximum_weight: Option<f64>,
+}
+
+/// Describes the size of a sheet, e.g. of sheet metal or board, on which flat items are laid out. The length
+/// runs along the x axis and the width along the y axis.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct SheetSize {
+    pub length: f64,
+    pub width: f64,
+}
+
+/// Describes the internal size of a pack, e.g. of a crate.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct PackDimensions {
+    pub length: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl PackDimensions {
+    /// Returns the length of the internal diagonal of the pack, which is the longest item that physically
+    /// fits in the pack.
+    pub fn diagonal(&self) -> f64 {
+        (self.length * self.length + self.width * self.width + self.height * self.height).sqrt()
+    }
+}
+
+/// Describes where a single flat item is laid out on a sheet. The position is the corner of the item that is
+/// closest to the origin of the sheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SheetPlacement {
+    /// The ID of the item.
+    pub item_id: Arc<str>,
+
+    /// The position of the item along the length of the sheet.
+    pub x: f64,
+
+    /// The position of the item along the width of the sheet.
+    pub y: f64,
+
+    /// The size of the item along the length of the sheet.
+    pub length: f64,
+
+    /// The size of the item along the width of the sheet.
+    pub width: f64,
+
+    /// Indicates if the item is turned a quarter, i.e. the length of the item runs along the width of the
+    /// sheet.
+    pub rotated: bool,
+}
+
+/// Defines a rule that restricts which items can be combined in a single pack.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type")]
+pub enum PackRule {
+    /// At most 'maximum' items with the given tag can be placed in a single pack.
+    #[serde(rename = "MAX_ITEMS_WITH_TAG")]
+    MaximumItemsWithTag { tag: String, maximum: i32 },
+
+    /// The items with the given tag may weigh at most 'maximum_weight' in total in a single pack.
+    #[serde(rename = "MAX_WEIGHT_WITH_TAG")]
+    MaximumWeightWithTag { tag: String, maximum_weight: f64 },
+
+    /// Items with the given tag can only be placed in a pack with other items that have the same tag.
+    #[serde(rename = "EXCLUSIVE_TAG")]
+    ExclusiveTag { tag: String },
+
+    /// At most 'maximum' items that each weigh more than 'threshold' can be placed in a single pack, so that
+    /// a pack doesn't hold more heavy items than a person can handle.
+    #[serde(rename = "MAX_HEAVY_ITEMS")]
+    MaximumHeavyItems { threshold: f64, maximum: i32 },
+}
+
+/// Defines an item that nests inside another item, e.g. a short rod that fits inside a tube. When both items
+/// are in the same pack each outer item can hold a single inner item, and the pair only takes up the length of
+/// the outer item. An inner item that is longer than the outer item never nests.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct NestingRule {
+    /// The ID of the item that fits inside the other item.
+    pub inner_item_id: String,
+
+    /// The ID of the item that holds the other item.
+    pub outer_item_id: String,
+}
+
+/// Returns the length that is saved by nesting the items inside each other, according to the nesting rules of
+/// the pack template. The rules are applied in order, and each item is only nested once. Nesting doesn't
+/// change the length of a pack that takes the length of the longest item.
+///
+/// The items are the items in the pack, by ID, together with the 'added' items. Only the items named by the
+/// rules are looked up, so the time this takes depends on the number of rules, not on the number of items.
+fn nested_length(
+    pack_template: &PackTemplate,
+    items_by_id: &HashMap<Arc<str>, (i32, f64)>,
+    added: Option<(&ItemTemplate, i32)>,
+) -> f64 {
+    let gap = match pack_template.length_mode {
+        LengthMode::Maximum => return 0.0,
+        LengthMode::Sum => 0.0,
+        LengthMode::SumWithGap(gap) => gap,
+    };
+
+    // The number of items with an ID, and their length, before any of them are nested
+    let totals_of = |id: &str| {
+        let totals = items_by_id.get(id).copied();
+        match added {
+            Some((template, count)) if *template.id == *id => {
+                let (existing_count, length) = totals.unwrap_or((0, template.length));
+                Some((existing_count + count, length))
+            }
+            _ => totals,
+        }
+    };
+
+    // The number of items that are still available for nesting, and their length, by item ID, for the items
+    // of the rules that were applied so far
+    let mut available: HashMap<&str, (i32, f64)> = HashMap::new();
+    let mut nested_length = 0.0;
+    for rule in &pack_template.nesting_rules {
+        #[cfg(test)]
+        checks::add(1);
+
+        let inner_item_id = rule.inner_item_id.as_str();
+        let outer_item_id = rule.outer_item_id.as_str();
+        let (inner_count, inner_length) = match available
+            .get(inner_item_id)
+            .copied()
+            .or_else(|| totals_of(inner_item_id))
+        {
+            Some(a) => a,
+            None => continue,
+        };
+        let (outer_count, outer_length) = match available
+            .get(outer_item_id)
+            .copied()
+            .or_else(|| totals_of(outer_item_id))
+        {
+            Some(a) => a,
+            None => continue,
+        };
+        if inner_length > outer_length {
+            continue;
+        }
+
+        // A nested item takes up neither its own length nor the gap next to it.
+        let pairs = inner_count.min(outer_count);
+        nested_length += (pairs as f64) * (inner_length + gap);
+        available.insert(inner_item_id, (inner_count - pairs, inner_length));
+        available.insert(outer_item_id, (outer_count - pairs, outer_length));
+    }
+
+    nested_length
+}
+
+/// Defines the limit that determined how many items of a batch could be placed in a pack.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PlacementLimit {
+    /// All the remaining items of the batch fit in the pack.
+    None,
+    /// The remaining weight capacity of the pack limited the number of items.
+    Weight,
+    /// The remaining number of pieces of the pack limited the number of items.
+    PieceCount,
+    /// The remaining length of the pack limited the number of items.
+    Length,
+    /// The remaining value capacity of the pack limited the number of items.
+    Value,
+    /// The pack contains items of a different storage class.
+    StorageClass,
+    /// The pack contains items for a different destination.
+    Destination,
+    /// One of the pack rules limited the number of items.
+    Rule,
+    /// The remaining space on the sheet limited the number of items.
+    Sheet,
+    /// The items were spread over more packs to reach the minimum number of packs.
+    MinimumPacks,
+}
+
+impl fmt::Display for PlacementLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PlacementLimit::None => "none, all remaining items fit",
+            PlacementLimit::Weight => "weight",
+            PlacementLimit::PieceCount => "piece count",
+            PlacementLimit::Length => "length",
+            PlacementLimit::Value => "value",
+            PlacementLimit::StorageClass => "storage class",
+            PlacementLimit::Destination => "destination",
+            PlacementLimit::Rule => "pack rule",
+            PlacementLimit::Sheet => "sheet space",
+            PlacementLimit::MinimumPacks => "minimum number of packs",
+        })
+    }
+}
+
+/// Describes a number of items from a single batch that were placed in a pack, together with the
+/// information on why that number of items was placed.
+#[derive(Clone, Debug)]
+pub struct PackedItem {
+    /// The item that was placed. The 'count' is the number of items placed in the pack.
+    pub item: ItemTemplate,
+
+    /// The number of items of the batch that still needed to be placed before this placement.
+    pub items_remaining_in_batch: i32,
+
+    /// The limit that determined the number of items that were placed.
+    pub limit: PlacementLimit,
+
+    /// The weight capacity left in the pack before the items were placed.
+    pub remaining_weight_before: f64,
+
+    /// The number of pieces left in the pack before the items were placed.
+    pub remaining_pieces_before: i32,
+
+    /// The weight of the bulk that still needed to be placed before this placement, if the item is a bulk
+    /// item.
+    pub bulk_remaining_before: Option<f64>,
+}
+
+impl PackedItem {
+    pub fn remaining_weight_after(&self) -> f64 {
+        self.remaining_weight_before - (self.item.count as f64) * self.item.weight
+    }
+
+    pub fn remaining_pieces_after(&self) -> i32 {
+        self.remaining_pieces_before - self.item.count
+    }
+
+    /// Returns the weight of the bulk that still needs to be placed after this placement, if the item is a
+    /// bulk item.
+    pub fn bulk_remaining_after(&self) -> Option<f64> {
+        self.bulk_remaining_before.map(|w| w - self.item.weight)
+    }
+}
+
+/// A borrowed view of a number of items from a single batch that were placed in a pack. The ID and the
+/// attributes are shared by all the placements of the batch, so a view doesn't copy them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PackedItemRef<'a> {
+    /// The ID of the item.
+    pub id: &'a str,
+
+    /// The length of a single item.
+    pub length: f64,
+
+    /// The weight of a single item, or the weight of the bulk that was placed if the item is a bulk item.
+    pub weight: f64,
+
+    /// The number of items that were placed.
+    pub count: i32,
+
+    /// The attributes of the item.
+    pub attributes: &'a ItemAttributes,
+
+    /// The limit that determined the number of items that were placed.
+    pub limit: PlacementLimit,
+}
+
+/// Contains the items that were placed in a single pack.
+#[derive(Clone, Debug)]
+pub struct Pack {
+    /// The 1-based number of the pack.
+    pub number: usize,
+
+    /// The items in the pack.
+    pub items: Vec<PackedItem>,
+
+    /// The total weight of the items in the pack, i.e. the net weight of the pack.
+    pub weight: f64,
+
+    /// The weight of the empty packaging, which is the tare weight of the pack template.
+    pub tare_weight: f64,
+
+    /// The length of the pack, as determined by the length mode of the pack template.
+    pub length: f64,
+
+    /// The total number of items in the pack.
+    pub item_count: i32,
+
+    /// Indicates if the pack was loaded beyond the maximum weight, up to the hard maximum weight, and should
+    /// be reviewed before it is shipped.
+    pub overweight: bool,
+
+    /// The total value of the items in the pack. Items without a value don't contribute to the value.
+    pub value: f64,
+
+    /// Indicates if the total value of the items exceeds the maximum value of the pack. This only happens
+    /// when a single item is worth more than the maximum value.
+    pub exceeds_maximum_value: bool,
+
+    /// The storage class of the items in the pack, which is the class of the first item that is placed.
+    pub storage_class: StorageClass,
+
+    /// The destination of the items in the pack, which is the destination of the first item that is placed.
+    pub destination: Option<String>,
+
+    /// Indicates if the pack holds a single item that doesn't fit in an empty pack, and should be reviewed
+    /// before it is shipped.
+    pub oversize: bool,
+
+    /// The positions of the items on the sheet, if the items are laid out on sheets.
+    pub sheet_placements: Vec<SheetPlacement>,
+
+    /// The fraction of the capacity of the pack that is used, i.e. the largest of the fractions of the maximum
+    /// weight, the maximum number of pieces and, when the items are placed end-to-end, the maximum length
+    /// that are used. Kept up to date by [`Pack::add`].
+    pub utilization: f64,
+
+    /// The totals of the items by tag and by weight, which the rules use instead of visiting all the items in
+    /// the pack. Kept up to date by [`Pack::add`].
+    pub item_totals: ItemTotals,
+}
+
+/// The number and the total weight of the items with a tag.
+#[derive(Clone, Copy, Debug, Default)]
+struct TagTotals {
+    count: i32,
+    weight: f64,
+}
+
+/// The totals of the items in a pack, by tag and by the weight of a single item.
+#[derive(Clone, Debug, Default)]
+pub struct ItemTotals {
+    /// The number and the weight of the items with each tag.
+    by_tag: HashMap<String, TagTotals>,
+
+    /// The number of items, that aren't bulk, by the bits of the weight of a single item. The bits of positive
+    /// weights are ordered in the same way as the weights.
+    counts_by_weight: BTreeMap<u64, i32>,
+
+    /// The number of items with each ID, and the length of the first of these items, which the nesting rules
+    /// use.
+    by_id: HashMap<Arc<str>, (i32, f64)>,
+}
+
+impl ItemTotals {
+    fn add(&mut self, template: &ItemTemplate, count: i32) {
+        let tags = &template.attributes.tags;
+        for (index, tag) in tags.iter().enumerate() {
+            if tags[..index].contains(tag) {
+                continue;
+            }
+
+            let totals = self.by_tag.entry(tag.clone()).or_default();
+            totals.count += count;
+            totals.weight += (count as f64) * template.weight;
+        }
+
+        if !template.attributes.bulk && template.weight > 0.0 {
+            *self
+                .counts_by_weight
+                .entry(template.weight.to_bits())
+                .or_default() += count;
+        }
+        self.by_id
+            .entry(template.id.clone())
+            .or_insert((0, template.length))
+            .0 += count;
+    }
+}
+
+impl Pack {
+    pub fn new(number: usize) -> Pack {
+        Pack {
+            number,
+            items: Vec::new(),
+            weight: 0.0,
+            tare_weight: 0.0,
+            length: 0.0,
+            item_count: 0,
+            overweight: false,
+            value: 0.0,
+            exceeds_maximum_value: false,
+            storage_class: StorageClass::default(),
+            destination: None,
+            oversize: false,
+            sheet_placements: Vec::new(),
+            utilization: 0.0,
+            item_totals: ItemTotals::default(),
+        }
+    }
+
+    /// Adds 'count' items of the template to the pack. The 'items_remaining_in_batch' and the 'limit' describe
+    /// why that number of items was placed.
+    pub fn add(
+        &mut self,
+        pack_template: &PackTemplate,
+        template: &ItemTemplate,
+        count: i32,
+        items_remaining_in_batch: i32,
+        limit: PlacementLimit,
+    ) {
+        if self.is_empty() {
+            self.storage_class = template.attributes.storage_class;
+            self.destination = template.attributes.destination.clone();
+            self.tare_weight = pack_template.tare_weight;
+        }
+
+        // The length depends on the items that are already in the pack, so determine it before the items are
+        // added.
+        let length = self.length_after_adding(pack_template, template, count);
+
+        let placement = self.placement_of(
+            pack_template,
+            template,
+            count,
+            items_remaining_in_batch,
+            limit,
+        );
+        self.items.push(placement);
+        self.add_totals(pack_template, template, count, length);
+    }
+
+    /// Adds 'count' more items of the batch of the last placement to that placement, instead of placing them
+    /// on a line of their own. Returns the placement of the added items, e.g. to report it to an observer.
+    pub fn extend_last_placement(
+        &mut self,
+        pack_template: &PackTemplate,
+        count: i32,
+        limit: PlacementLimit,
+    ) -> PackedItem {
+        let last = self
+            .items
+            .last()
+            .expect("The pack has a placement to extend.");
+        let template = last.item.clone();
+        let items_remaining_in_batch = last.items_remaining_in_batch - last.item.count;
+        let length = self.length_after_adding(pack_template, &template, count);
+        let placement = self.placement_of(
+            pack_template,
+            &template,
+            count,
+            items_remaining_in_batch,
+            limit,
+        );
+
+        let last = self
+            .items
+            .last_mut()
+            .expect("The pack has a placement to extend.");
+        last.item.count += count;
+        last.limit = limit;
+        self.add_totals(pack_template, &template, count, length);
+
+        placement
+    }
+
+    /// Returns the placement of 'count' items of the template in the pack, before the items are added.
+    fn placement_of(
+        &self,
+        pack_template: &PackTemplate,
+        template: &ItemTemplate,
+        count: i32,
+        items_remaining_in_batch: i32,
+        limit: PlacementLimit,
+    ) -> PackedItem {
+        let mut item = template.clone();
+        item.count = count;
+        PackedItem {
+            item,
+            items_remaining_in_batch,
+            limit,
+            remaining_weight_before: pack_template.maximum_weight - self.weight,
+            remaining_pieces_before: pack_template.maximum_number_of_pieces - self.item_count,
+            bulk_remaining_before: None,
+        }
+    }
+
+    /// Adds 'count' items of the template to the totals of the pack, where the 'length' is the length of the
+    /// pack after the items are added.
+    fn add_totals(
+        &mut self,
+        pack_template: &PackTemplate,
+        template: &ItemTemplate,
+        count: i32,
+        length: f64,
+    ) {
+        self.item_totals.add(template, count);
+        self.weight += (count as f64) * template.weight;
+        self.item_count += count;
+        self.value += (count as f64) * template.item_value().unwrap_or(0.0);
+        if let Some(maximum_value) = pack_template.maximum_value {
+            self.exceeds_maximum_value = self.value > maximum_value;
+        }
+        self.length = length;
+        self.utilization = self.utilization_of(pack_template);
+    }
+
+    /// Returns the fraction of the capacity of the pack template that is used by the pack.
+    fn utilization_of(&self, pack_template: &PackTemplate) -> f64 {
+        let fraction = |used: f64, maximum: f64| {
+            if maximum > 0.0 {
+                used / maximum
+            } else {
+                0.0
+            }
+        };
+        let length = match (pack_template.length_mode, pack_template.maximum_length) {
+            (LengthMode::Maximum, _) | (_, None) => 0.0,
+            (_, Some(maximum_length)) => fraction(self.length, maximum_length),
+        };
+
+        fraction(self.weight, pack_template.maximum_weight)
+            .max(fraction(
+                self.item_count as f64,
+                pack_template.maximum_number_of_pieces as f64,
+            ))
+            .max(length)
+    }
+
+    /// Returns the length the pack would have after adding the given number of items of the template, taking
+    /// the items that nest inside each other into account.
+    pub fn length_after_adding(
+        &self,
+        pack_template: &PackTemplate,
+        template: &ItemTemplate,
+        count: i32,
+    ) -> f64 {
+        let mut length = match pack_template.length_mode {
+            LengthMode::Maximum => self.length.max(template.length),
+            LengthMode::Sum => self.length + (count as f64) * template.length,
+            LengthMode::SumWithGap(gap) => {
+                let number_of_gaps = if self.is_empty() { count - 1 } else { count };
+                self.length + (count as f64) * template.length + (number_of_gaps as f64) * gap
+            }
+        };
+
+        if !pack_template.nesting_rules.is_empty() {
+            let items_by_id = &self.item_totals.by_id;
+            let nested_before = nested_length(pack_template, items_by_id, None);
+            let nested_after = nested_length(pack_template, items_by_id, Some((template, count)));
+            length -= nested_after - nested_before;
+        }
+
+        length
+    }
+
+    /// Adds the given weight of the bulk item to the pack, as a single piece. The 'bulk_remaining_before' is
+    /// the weight of the bulk that still needed to be placed, and the 'limit' describes why that weight was
+    /// placed.
+    pub fn add_bulk(
+        &mut self,
+        pack_template: &PackTemplate,
+        template: &ItemTemplate,
+        weight: f64,
+        bulk_remaining_before: f64,
+        limit: PlacementLimit,
+    ) {
+        let portion = ItemTemplate {
+            weight,
+            count: 1,
+            ..template.clone()
+        };
+        self.add(pack_template, &portion, 1, 1, limit);
+        if let Some(placement) = self.items.last_mut() {
+            placement.bulk_remaining_before = Some(bulk_remaining_before);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the weight of the pack including the packaging, which is the weight that the carrier
+    /// enforces.
+    pub fn gross_weight(&self) -> f64 {
+        self.weight + self.tare_weight
+    }
+
+    /// Returns the estimated time it takes to pack the pack, in seconds, given the time it takes to handle a
+    /// single item.
+    pub fn packing_time(&self, handling_time: f64) -> f64 {
+        (self.item_count as f64) * handling_time
+    }
+
+    /// Returns the items in the pack, in the order in which they were placed, as borrowed views.
+    pub fn items(&self) -> impl ExactSizeIterator<Item = PackedItemRef<'_>> {
+        self.items.iter().map(|p| PackedItemRef {
+            id: &p.item.id,
+            length: p.item.length,
+            weight: p.item.weight,
+            count: p.item.count,
+            attributes: &p.item.attributes,
+            limit: p.limit,
+        })
+    }
+
+    /// Returns the SHA-256 hash, as lowercase hexadecimal string, of the contents of the pack. The hash covers
+    /// the ID, the length, the quantity and the weight of each placement, in the order in which the items
+    /// were placed, so any change to the contents of the pack changes the hash.
+    pub fn content_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for packed_item in &self.items {
+            let item = &packed_item.item;
+            hasher.update(format!(
+                "{}\t{}\t{}\t{}\n",
+                item.id, item.length, item.count, item.weight
+            ));
+        }
+
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub fn number_of_items_with_tag(&self, tag: &str) -> i32 {
+        self.item_totals.by_tag.get(tag).map_or(0, |t| t.count)
+    }
+
+    /// Returns the number of items in the pack that each weigh more than the given weight. Bulk items are not
+    /// counted.
+    pub fn number_of_items_heavier_than(&self, weight: f64) -> i32 {
+        let counts_by_weight = &self.item_totals.counts_by_weight;
+        let heavier_weights = if weight >= 0.0 {
+            counts_by_weight.range((Bound::Excluded(weight.to_bits()), Bound::Unbounded))
+        } else {
+            counts_by_weight.range(..)
+        };
+        heavier_weights
+            .map(|(_, count)| {
+                #[cfg(test)]
+                checks::add(1);
+
+                count
+            })
+            .sum()
+    }
+
+    pub fn weight_of_items_with_tag(&self, tag: &str) -> f64 {
+        self.item_totals.by_tag.get(tag).map_or(0.0, |t| t.weight)
+    }
+}
+
+/// Defines what happens with items that don't fit in an empty pack, e.g. because a single item is heavier
+/// than the maximum weight of a pack.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+#[serde(rename_all = "kebab-case")]
+pub enum InfeasibleItemPolicy {
+    /// The plan fails with an error.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "error"))]
+    Error,
+
+    /// The items are left out of the plan, which is reported as a warning.
+    #[cfg_attr(feature = "text-format", strum(serialize = "skip"))]
+    Skip,
+
+    /// Each item is placed on its own in a pack that is marked as oversize, which is reported as a warning.
+    #[cfg_attr(feature = "text-format", strum(serialize = "own-pack"))]
+    OwnPack,
+}
+
+/// Defines what happens with batches of items that share an ID.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+#[serde(rename_all = "kebab-case")]
+pub enum DuplicateIdPolicy {
+    /// The batches are planned as provided, even if they have a different length or weight.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "allow"))]
+    Allow,
+
+    /// The plan fails with an error if the batches have a different length or weight.
+    #[cfg_attr(feature = "text-format", strum(serialize = "error"))]
+    Error,
+
+    /// The batches with the same length, weight and attributes are combined into the first batch. The plan
+    /// fails with an error if the batches have a different length or weight.
+    #[cfg_attr(feature = "text-format", strum(serialize = "merge"))]
+    Merge,
+}
+
+/// Defines the order of the items with the same length when the items are sorted from short to long, or from
+/// long to short. The items that are still equal keep the order in which they were given, because the sort
+/// is stable.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+#[serde(rename_all = "kebab-case")]
+pub enum TieBreak {
+    /// The items keep the order in which they were given.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "input"))]
+    Input,
+
+    /// The items are ordered by their ID, so that the plan doesn't depend on the order of the input.
+    #[cfg_attr(feature = "text-format", strum(serialize = "id"))]
+    Id,
+
+    /// The items are ordered from the heaviest to the lightest item.
+    #[cfg_attr(feature = "text-format", strum(serialize = "weight"))]
+    Weight,
+}
+
+/// Describes a problem with a plan that doesn't stop the plan from being created, but that the user may
+/// want to know about.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlanWarning {
+    /// A batch was skipped because it doesn't contain any items.
+    ItemSkipped { item_id: String, count: i32 },
+
+    /// A pack doesn't contain any items. This happens when items are pinned to a pack beyond the packs
+    /// that are needed for the other items.
+    EmptyPack { pack_number: usize },
+
+    /// A pack was loaded beyond the maximum weight, up to the hard maximum weight, to avoid starting an extra
+    /// pack for the last few items.
+    OverweightPack {
+        pack_number: usize,
+        weight: f64,
+        maximum_weight: f64,
+    },
+
+    /// The items fit exactly in a number of packs, but the plan uses more packs than that.
+    ExactFitNotReached {
+        minimum_number_of_packs: usize,
+        number_of_packs: usize,
+    },
+
+    /// A single item is worth more than the maximum value of a pack, so it was placed on its own in a pack
+    /// that exceeds the maximum value.
+    MaximumValueExceeded {
+        pack_number: usize,
+        value: f64,
+        maximum_value: f64,
+    },
+
+    /// The items of a batch don't fit in an empty pack, so they were left out of the plan.
+    InfeasibleItemSkipped { item_id: String, count: i32 },
+
+    /// An item doesn't fit in an empty pack, so it was placed on its own in an oversize pack.
+    OversizePack { pack_number: usize, item_id: String },
+
+    /// A number of the items that were requested from a substitution group are supplied by a member of the
+    /// group.
+    ItemSubstituted {
+        group_id: String,
+        item_id: String,
+        count: i32,
+    },
+
+    /// The items of a batch match the exclusion list, so they were left out of the plan.
+    ItemExcluded { item_id: String, count: i32 },
+
+    /// The count of a batch is not a multiple of the pack multiple of the item, so the items that don't make
+    /// up a full multiple were left out of the plan.
+    IncompleteMultiple {
+        item_id: String,
+        multiple: i32,
+        count: i32,
+    },
+
+    /// The items of a batch exceed the limits of their destination, so they were left for the next run.
+    DestinationLimitReached {
+        item_id: String,
+        destination: String,
+        count: i32,
+    },
+
+    /// The time limit was reached before all the attempts of the planner were made, and the best plan found
+    /// so far uses more packs than the theoretical minimum, so the plan may not be optimal.
+    TimeLimitReached {
+        attempts: u32,
+        number_of_packs: usize,
+        minimum_number_of_packs: usize,
+    },
+}
+
+impl fmt::Display for PlanWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanWarning::ItemSkipped { item_id, count } => write!(
+                f,
+                "The item {item_id:?} was skipped because its quantity is {count}."
+            ),
+            PlanWarning::EmptyPack { pack_number } => {
+                write!(f, "Pack {pack_number} doesn't contain any items.")
+            }
+            PlanWarning::OverweightPack {
+                pack_number,
+                weight,
+                maximum_weight,
+            } => write!(
+                f,
+                "Pack {pack_number} weighs {weight:.1}, which is more than the maximum weight of {maximum_weight:.1}. Review the pack before it is shipped."
+            ),
+            PlanWarning::ExactFitNotReached {
+                minimum_number_of_packs,
+                number_of_packs,
+            } => write!(
+                f,
+                "The items fit exactly in {minimum_number_of_packs} packs, but the plan uses {number_of_packs} packs."
+            ),
+            PlanWarning::MaximumValueExceeded {
+                pack_number,
+                value,
+                maximum_value,
+            } => write!(
+                f,
+                "Pack {pack_number} is worth {value:.2}, which is more than the maximum value of {maximum_value:.2}, because a single item is worth more than the maximum value."
+            ),
+            PlanWarning::InfeasibleItemSkipped { item_id, count } => write!(
+                f,
+                "The item {item_id:?} was skipped because it doesn't fit in an empty pack. {count} items were not planned."
+            ),
+            PlanWarning::OversizePack {
+                pack_number,
+                item_id,
+            } => write!(
+                f,
+                "Pack {pack_number} holds the item {item_id:?}, which doesn't fit in an empty pack. Review the pack before it is shipped."
+            ),
+            PlanWarning::ItemSubstituted {
+                group_id,
+                item_id,
+                count,
+            } => write!(
+                f,
+                "{count} items of the substitution group {group_id:?} are supplied by the item {item_id:?}."
+            ),
+            PlanWarning::ItemExcluded { item_id, count } => write!(
+                f,
+                "The item {item_id:?} was excluded from the plan. {count} items were not planned."
+            ),
+            PlanWarning::IncompleteMultiple {
+                item_id,
+                multiple,
+                count,
+            } => write!(
+                f,
+                "{count} items of the item {item_id:?} don't make up a full multiple of {multiple}, so they were not planned."
+            ),
+            PlanWarning::DestinationLimitReached {
+                item_id,
+                destination,
+                count,
+            } => write!(
+                f,
+                "{count} items of the item {item_id:?} exceed the limits of the destination {destination:?}, so they are left for the next run."
+            ),
+            PlanWarning::TimeLimitReached {
+                attempts,
+                number_of_packs,
+                minimum_number_of_packs,
+            } => write!(
+                f,
+                "The time limit was reached after {attempts} attempts. The best plan found so far uses {number_of_packs} packs, while the theoretical minimum is {minimum_number_of_packs} packs, so the plan may not be optimal."
+            ),
+        }
+    }
+}
+
+/// Compares the number of packs of a plan with the theoretical minimum number of packs, which follows from the
+/// total weight and the total number of items.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PackCountAnalysis {
+    /// The minimum number of packs needed to hold the total weight and the total number of items.
+    pub minimum_number_of_packs: usize,
+
+    /// Indicates if the total weight, or the total number of items, divides perfectly into the minimum
+    /// number of packs, i.e. every pack has to be filled completely to reach the minimum.
+    pub is_exact_fit: bool,
+
+    /// The number of packs in the plan.
+    pub number_of_packs: usize,
+}
+
+impl PackCountAnalysis {
+    pub fn reaches_minimum(&self) -> bool {
+        self.number_of_packs <= self.minimum_number_of_packs
+    }
+}
+
+/// Describes the range in which the number of packs for a set of items is expected to fall, estimated
+/// without planning the packs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EstimateRange {
+    /// The theoretical minimum number of packs, which follows from the total weight and the total number of
+    /// items.
+    pub minimum: usize,
+
+    /// The number of packs the greedy planner needs when only the weight and the number of pieces of the packs
+    /// are taken into account.
+    pub maximum: usize,
+}
+
+/// Contains the packs of a plan and the warnings that were raised while creating the plan.
+#[derive(Clone, Debug, Default)]
+pub struct PlanOutcome {
+    /// The planned packs, ordered by their number.
+    pub packs: Vec<Pack>,
+
+    /// The soft issues found while planning, in the order in which they were found.
+    pub warnings: Vec<PlanWarning>,
+
+    /// How far the algorithm searched for the plan, for the algorithms that make several attempts, e.g. the
+    /// restarts.
+    pub optimality: Option<Optimality>,
+}
+
+/// Describes how far an algorithm that makes several attempts searched for the best plan.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Optimality {
+    /// The number of attempts that were made, including the first.
+    pub attempts: u32,
+
+    /// Indicates if the plan uses the theoretical minimum number of packs, in which case no plan uses fewer
+    /// packs.
+    pub reaches_minimum: bool,
+}
+
+impl PlanOutcome {
+    /// Returns the weight and the number of pieces that each pack can still take, and the totals over all the
+    /// packs. The pack template is the one with which the packs were planned. The tare weight of a pack counts
+    /// towards its maximum weight. Packs that are loaded beyond the maximum weight, e.g. oversize packs, have
+    /// no capacity left.
+    pub fn remaining_capacity(&self, pack_template: &PackTemplate) -> RemainingCapacity {
+        let packs: Vec<PackCapacity> = self
+            .packs
+            .iter()
+            .map(|pack| PackCapacity {
+                pack_number: pack.number,
+                weight: (pack_template.maximum_weight - pack.gross_weight()).max(0.0),
+                pieces: (pack_template.maximum_number_of_pieces - pack.item_count).max(0),
+            })
+            .collect();
+
+        RemainingCapacity {
+            weight: packs.iter().map(|p| p.weight).sum(),
+            pieces: packs.iter().map(|p| p.pieces).sum(),
+            packs,
+        }
+    }
+}
+
+/// Describes the weight and the number of pieces that a pack of a plan can still take.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PackCapacity {
+    /// The number of the pack.
+    pub pack_number: usize,
+
+    /// The weight that can still be added to the pack.
+    pub weight: f64,
+
+    /// The number of pieces that can still be added to the pack.
+    pub pieces: i32,
+}
+
+/// Describes the capacity that is left in the packs of a plan, see [`PlanOutcome::remaining_capacity`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RemainingCapacity {
+    /// The capacity of each pack, in the order of the packs of the plan.
+    pub packs: Vec<PackCapacity>,
+
+    /// The total weight that can still be added to the packs.
+    pub weight: f64,
+
+    /// The total number of pieces that can still be added to the packs.
+    pub pieces: i32,
+}
+
+impl RemainingCapacity {
+    /// Returns the number of items of the template that fit in the remaining capacity of the packs, in full
+    /// multiples of the item. Only the weight and the number of pieces are considered, so use
+    /// [`crate::planner::can_fit`] to also check the length, the value and the rules of the packs.
+    pub fn number_of_items(&self, template: &ItemTemplate) -> i32 {
+        let multiple = template.attributes.pack_multiple();
+        self.packs
+            .iter()
+            .map(|pack| {
+                let by_weight = if template.weight > 0.0 {
+                    (pack.weight / template.weight)
+                        .floor()
+                        .min(f64::from(i32::MAX)) as i32
+                } else {
+                    i32::MAX
+                };
+                let items = by_weight.min(pack.pieces);
+                items - items % multiple
+            })
+            .sum()
+    }
+}
+
+/// Describes a number of additional items that can be placed in an existing pack.
+#[derive(Clone, Debug)]
+pub struct FitPlacement {
+    /// The number of the existing pack in which the items can be placed.
+    pub pack_number: usize,
+
+    /// The item that can be placed. The 'count' is the number of items that fit in the pack.
+    pub item: ItemTemplate,
+}
+
+/// Describes whether a set of additional items fits in the remaining capacity of a set of existing packs.
+#[derive(Clone, Debug, Default)]
+pub struct FitReport {
+    /// The locations in the existing packs where the additional items can be placed.
+    pub placements: Vec<FitPlacement>,
+
+    /// The additional items that do not fit in any of the existing packs. The 'count' is the number of
+    /// items that could not be placed.
+    pub unplaced_items: Vec<ItemTemplate>,
+}
+
+impl FitReport {
+    pub fn fits(&self) -> bool {
+        self.unplaced_items.is_empty()
+    }
+}
+
+/// Describes an item of which fewer items are available than were ordered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backorder {
+    /// The ID of the item.
+    pub item_id: String,
+
+    /// The number of items that was ordered.
+    pub ordered: i32,
+
+    /// The number of items that is available, and that is planned.
+    pub available: i32,
+}
+
+impl Backorder {
+    /// Returns the number of items that can't be delivered.
+    pub fn shortfall(&self) -> i32 {
+        self.ordered - self.available
+    }
+}
+
+/// Returns the backorders of the items that have an ordered quantity which is larger than the available
+/// quantity, in the order in which the items first appear. Batches of items that share an ID are combined.
+pub fn find_backorders(items: &[ItemTemplate]) -> Vec<Backorder> {
+    let mut backorders: Vec<Backorder> = Vec::new();
+    let mut backorder_indices: HashMap<&str, usize> = HashMap::new();
+    for template in items {
+        let index = *backorder_indices.entry(&template.id).or_insert_with(|| {
+            backorders.push(Backorder {
+                item_id: template.id.to_string(),
+                ordered: 0,
+                available: 0,
+            });
+            backorders.len() - 1
+        });
+        let backorder = &mut backorders[index];
+        backorder.ordered += template.attributes.ordered.unwrap_or(template.count);
+        backorder.available += template.count;
+    }
+
+    backorders.retain(|b| b.shortfall() > 0);
+    backorders
+}
+
+/// Describes why items of the order are not in any of the packs of a plan.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum UnpackedReason {
+    /// The items don't fit in an empty pack, and the infeasible items are skipped.
+    Infeasible,
+
+    /// The items match the exclusion list.
+    Excluded,
+
+    /// The items don't make up a full multiple of the pack multiple of the item.
+    IncompleteMultiple,
+
+    /// The items were ordered, but aren't available.
+    Backordered,
+
+    /// The items exceed the limits of their destination, and are left for the next run.
+    Spillover,
+}
+
+impl fmt::Display for UnpackedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UnpackedReason::Infeasible => "infeasible",
+            UnpackedReason::Excluded => "excluded",
+            UnpackedReason::IncompleteMultiple => "incomplete-multiple",
+            UnpackedReason::Backordered => "backordered",
+            UnpackedReason::Spillover => "spillover",
+        })
+    }
+}
+
+/// Describes a number of items of the order that are not in any of the packs of a plan.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnpackedItem {
+    /// The ID of the item.
+    pub item_id: String,
+
+    /// The number of items that are not packed.
+    pub count: i32,
+
+    /// The reason why the items are not packed.
+    pub reason: UnpackedReason,
+}
+
+/// Returns the items that are not packed, first those of the warnings, in the order in which the warnings
+/// were raised, followed by the shortfall of the backorders.
+pub fn find_unpacked_items(
+    warnings: &[PlanWarning],
+    backorders: &[Backorder],
+) -> Vec<UnpackedItem> {
+    let unpacked_item = |item_id: &str, count: i32, reason: UnpackedReason| UnpackedItem {
+        item_id: item_id.to_string(),
+        count,
+        reason,
+    };
+
+    warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            PlanWarning::InfeasibleItemSkipped { item_id, count } => {
+                Some(unpacked_item(item_id, *count, UnpackedReason::Infeasible))
+            }
+            PlanWarning::ItemExcluded { item_id, count } => {
+                Some(unpacked_item(item_id, *count, UnpackedReason::Excluded))
+            }
+            PlanWarning::IncompleteMultiple { item_id, count, .. } => Some(unpacked_item(
+                item_id,
+                *count,
+                UnpackedReason::IncompleteMultiple,
+            )),
+            PlanWarning::DestinationLimitReached { item_id, count, .. } => {
+                Some(unpacked_item(item_id, *count, UnpackedReason::Spillover))
+            }
+            _ => None,
+        })
+        .chain(
+            backorders
+                .iter()
+                .map(|b| unpacked_item(&b.item_id, b.shortfall(), UnpackedReason::Backordered)),
+        )
+        .collect()
+}
+
+// The relative difference between two total weights below which the weights are considered equal, which
+// allows for the rounding of the weights of the bulk that is split over several packs
+const TOTAL_WEIGHT_TOLERANCE: f64 = 1e-9;
+
+/// The number of items and the weight of a group of items. Bulk items are only part of the weight, because
+/// they are measured by their weight instead of being counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Totals {
+    /// The number of items that aren't bulk.
+    pub items: i64,
+
+    /// The weight of all the items.
+    pub weight: f64,
+}
+
+impl Totals {
+    /// Returns the totals of the given items.
+    pub fn of_items<'a>(items: impl IntoIterator<Item = &'a ItemTemplate>) -> Self {
+        items
+            .into_iter()
+            .fold(Totals::default(), |totals, template| {
+                let count = template.count.max(0);
+                if template.attributes.bulk {
+                    Totals {
+                        items: totals.items,
+                        weight: totals.weight + template.weight,
+                    }
+                } else {
+                    Totals {
+                        items: totals.items + count as i64,
+                        weight: totals.weight + (count as f64) * template.weight,
+                    }
+                }
+            })
+    }
+
+    /// Returns the totals of the items in the given packs.
+    pub fn of_packs(packs: &[Pack]) -> Self {
+        Totals::of_items(packs.iter().flat_map(|p| p.items.iter().map(|i| &i.item)))
+    }
+
+    /// Returns the totals of the items that were ordered, but aren't available, see [`find_backorders`].
+    pub fn of_backorders<'a>(items: impl IntoIterator<Item = &'a ItemTemplate>) -> Self {
+        items
+            .into_iter()
+            .fold(Totals::default(), |totals, template| {
+                let ordered = template.attributes.ordered.unwrap_or(template.count);
+                let shortfall = (ordered - template.count.max(0)).max(0);
+                Totals {
+                    items: totals.items + shortfall as i64,
+                    weight: totals.weight + (shortfall as f64) * template.weight,
+                }
+            })
+    }
+
+    fn matches(&self, other: &Totals) -> bool {
+        let tolerance = TOTAL_WEIGHT_TOLERANCE * self.weight.abs().max(1.0);
+        self.items == other.items && (self.weight - other.weight).abs() <= tolerance
+    }
+}
+
+impl Add for Totals {
+    type Output = Totals;
+
+    fn add(self, other: Totals) -> Totals {
+        Totals {
+            items: self.items + other.items,
+            weight: self.weight + other.weight,
+        }
+    }
+}
+
+impl Sub for Totals {
+    type Output = Totals;
+
+    fn sub(self, other: Totals) -> Totals {
+        Totals {
+            items: self.items - other.items,
+            weight: self.weight - other.weight,
+        }
+    }
+}
+
+/// Compares the items that were given to the planner with the items in the packs of the plan, and the items
+/// that the planner left out, so that no items are lost without being reported.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlanTotals {
+    /// The items of the input, with the ordered quantity of the items of which fewer are available. The cut
+    /// items, the kits, the substitution groups and the items that are mapped are counted as the items that
+    /// replace them.
+    pub input: Totals,
+
+    /// The items in the packs.
+    pub packed: Totals,
+
+    /// The items that were left out of the plan, i.e. the items that are excluded, filtered out, not part of
+    /// a full pack multiple, left for the next run by the destination limits or backordered, and the items
+    /// that don't fit in an empty pack and are skipped.
+    pub left_out: Totals,
+}
+
+impl PlanTotals {
+    /// Returns `true` if all the input items are either packed or left out.
+    pub fn is_conserved(&self) -> bool {
+        let accounted_for = Totals {
+            items: self.packed.items + self.left_out.items,
+            weight: self.packed.weight + self.left_out.weight,
+        };
+        self.input.matches(&accounted_for)
+    }
+}
+
+/// Defines a weight band for a carrier, e.g. all packs between 0 and 5 kg, and the cost of shipping a pack
+/// in that band.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightBand {
+    pub name: String,
+    pub minimum_weight: f64,
+    pub maximum_weight: f64,
+    pub cost: f64,
+}
+
+/// Returns the first band which contains the gross weight of the pack, which includes its tare weight, if
+/// there is one.
+pub fn find_weight_band<'a>(bands: &'a [WeightBand], pack: &Pack) -> Option<&'a WeightBand> {
+    let gross_weight = pack.gross_weight();
+    bands
+        .iter()
+        .find(|b| gross_weight >= b.minimum_weight && gross_weight <= b.maximum_weight)
+}
+
+/// Defines a grade of the packs that use at least a fraction of their capacity, e.g. `A` for the packs that
+/// use at least 90% of their capacity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grade {
+    pub name: String,
+    pub minimum_utilization: f64,
+}
+
+/// Returns the grade of a pack with the given utilization, which is the grade with the highest minimum
+/// utilization that the pack reaches, if there is one.
+pub fn find_grade(grades: &[Grade], utilization: f64) -> Option<&Grade> {
+    grades
+        .iter()
+        .filter(|g| utilization >= g.minimum_utilization)
+        .max_by(|a, b| a.minimum_utilization.total_cmp(&b.minimum_utilization))
+}
+
+/// Defines a carton in which a pack can be shipped, e.g. a small box that holds up to 600 mm and 5 kg.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Carton {
+    pub name: String,
+    pub maximum_length: f64,
+    pub maximum_weight: f64,
+}
+
+/// Returns the smallest carton that holds both the length and the gross weight of the pack, if there is one.
+/// The smallest carton is the one with the shortest maximum length, and of these the one with the lowest
+/// maximum weight, whatever the order of the cartons. Of cartons with the same capacity the first one is
+/// returned.
+pub fn find_carton<'a>(cartons: &'a [Carton], pack: &Pack) -> Option<&'a Carton> {
+    let gross_weight = pack.gross_weight();
+    cartons
+        .iter()
+        .filter(|c| pack.length <= c.maximum_length && gross_weight <= c.maximum_weight)
+        .min_by(|a, b| {
+            a.maximum_length
+                .total_cmp(&b.maximum_length)
+                .then_with(|| a.maximum_weight.total_cmp(&b.maximum_weight))
+        })
+}
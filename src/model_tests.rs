@@ -0,0 +1,684 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use super::*;
+
+// PackSortOrder
+
+#[test]
+fn when_parsing_sort_order_it_should_return_the_correct_type() {
+    let natural_sort_order =
+        PackSortOrder::from_str("NATURAL").expect("Failed to parse the natural sort order.");
+    assert_eq!(PackSortOrder::Natural, natural_sort_order);
+
+    let short_to_long_sort_order = PackSortOrder::from_str("SHORT_TO_LONG")
+        .expect("Failed to parse the short-to-long sort order.");
+    assert_eq!(PackSortOrder::ShortToLong, short_to_long_sort_order);
+
+    let long_to_short_sort_order = PackSortOrder::from_str("LONG_TO_SHORT")
+        .expect("Failed to parse the long-to-short sort order");
+    assert_eq!(PackSortOrder::LongToShort, long_to_short_sort_order);
+}
+
+#[test]
+fn when_creating_a_string_representation_of_the_sort_order_it_should_return_the_correct_value() {
+    assert_eq!("NATURAL", PackSortOrder::Natural.to_string());
+    assert_eq!("SHORT_TO_LONG", PackSortOrder::ShortToLong.to_string());
+    assert_eq!("LONG_TO_SHORT", PackSortOrder::LongToShort.to_string());
+}
+
+// PackTemplate
+
+#[test]
+fn when_creating_a_new_pack_template_it_should_initialize_properly() {
+    let pack = PackTemplate::new();
+    assert_eq!(pack.maximum_number_of_pieces, 0);
+    assert_eq!(pack.maximum_weight, 0.0);
+    assert_eq!(pack.sort_order, PackSortOrder::NotSet);
+}
+
+// PackTemplate::limits_for()
+
+#[test]
+fn when_finding_the_limits_for_an_item_it_should_apply_the_classes_of_the_item_and_the_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 100.0,
+        class_limits: vec![
+            ClassLimits {
+                class: "GLASS".to_string(),
+                maximum_number_of_pieces: Some(10),
+                maximum_weight: None,
+            },
+            ClassLimits {
+                class: "CERAMIC".to_string(),
+                maximum_number_of_pieces: Some(20),
+                maximum_weight: Some(60.0),
+            },
+        ],
+        ..PackTemplate::new()
+    };
+    let glass = ItemTemplate::from_str("1,100,3,2.0,tags=GLASS").unwrap();
+    let ceramic = ItemTemplate::from_str("2,100,3,2.0,tags=CERAMIC").unwrap();
+    let other = ItemTemplate::from_str("3,100,3,2.0").unwrap();
+
+    let empty_pack = Pack::new(1);
+    let limits = pack_template.limits_for(&empty_pack, &other);
+    assert!(matches!(limits, Cow::Borrowed(_)));
+    let limits = pack_template.limits_for(&empty_pack, &glass);
+    assert_eq!(limits.maximum_number_of_pieces, 10);
+    assert_eq!(limits.maximum_weight, 100.0);
+
+    let mut pack = Pack::new(1);
+    pack.add(&pack_template, &ceramic, 3, 3, PlacementLimit::None);
+    let limits = pack_template.limits_for(&pack, &other);
+    assert_eq!(limits.maximum_number_of_pieces, 20);
+    assert_eq!(limits.maximum_weight, 60.0);
+    let limits = pack_template.limits_for(&pack, &glass);
+    assert_eq!(limits.maximum_number_of_pieces, 10);
+    assert_eq!(limits.maximum_weight, 60.0);
+}
+
+// PackTemplate::net_limits()
+
+#[test]
+fn when_finding_the_net_limits_it_should_subtract_the_tare_from_the_maximum_weights() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 100.0,
+        hard_maximum_weight: Some(110.0),
+        class_limits: vec![ClassLimits {
+            class: "GLASS".to_string(),
+            maximum_number_of_pieces: Some(10),
+            maximum_weight: Some(60.0),
+        }],
+        tare_weight: 2.5,
+        ..PackTemplate::new()
+    };
+
+    let limits = pack_template.net_limits();
+    assert_eq!(limits.maximum_number_of_pieces, 40);
+    assert_eq!(limits.maximum_weight, 97.5);
+    assert_eq!(limits.hard_maximum_weight, Some(107.5));
+    assert_eq!(limits.class_limits[0].maximum_weight, Some(57.5));
+    assert_eq!(limits.tare_weight, 2.5);
+}
+
+// Pack::content_hash()
+
+#[test]
+fn when_hashing_the_contents_of_a_pack_it_should_return_a_stable_hash() {
+    let pack_template = PackTemplate::new();
+    let item = ItemTemplate::from_str("item1,10,5,4").unwrap();
+    let mut pack = Pack::new(1);
+    pack.add(&pack_template, &item, 5, 5, PlacementLimit::None);
+
+    assert_eq!(
+        pack.content_hash(),
+        "a0e0888a6c4f571cff8cbff6501c2d789ced91d74584c438075d4f12626ecfc4"
+    );
+}
+
+#[test]
+fn when_hashing_packs_with_different_contents_it_should_return_different_hashes() {
+    let pack_template = PackTemplate::new();
+    let item = ItemTemplate::from_str("item1,10,5,4").unwrap();
+    let mut first_pack = Pack::new(1);
+    first_pack.add(&pack_template, &item, 5, 5, PlacementLimit::None);
+    let mut second_pack = Pack::new(2);
+    second_pack.add(&pack_template, &item, 4, 5, PlacementLimit::None);
+
+    assert_ne!(first_pack.content_hash(), second_pack.content_hash());
+}
+
+// Pack::utilization
+
+#[test]
+fn when_adding_items_to_a_pack_it_should_use_the_largest_fraction_of_the_capacity() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        ..PackTemplate::new()
+    };
+    let mut pack = Pack::new(1);
+    pack.add(
+        &pack_template,
+        &ItemTemplate::from_str("1,100,4,5.0").unwrap(),
+        4,
+        4,
+        PlacementLimit::None,
+    );
+    assert_eq!(pack.utilization, 0.4);
+
+    pack.add(
+        &pack_template,
+        &ItemTemplate::from_str("2,100,1,50.0").unwrap(),
+        1,
+        1,
+        PlacementLimit::None,
+    );
+    assert_eq!(pack.utilization, 0.7);
+
+    let pack_template = PackTemplate {
+        length_mode: LengthMode::Sum,
+        maximum_length: Some(500.0),
+        ..pack_template
+    };
+    let mut pack = Pack::new(1);
+    pack.add(
+        &pack_template,
+        &ItemTemplate::from_str("1,200,2,1.0").unwrap(),
+        2,
+        2,
+        PlacementLimit::None,
+    );
+    assert_eq!(pack.utilization, 0.8);
+}
+
+// Pack::items()
+
+#[test]
+fn when_iterating_the_items_of_a_pack_it_should_borrow_the_shared_item_data() {
+    let pack_template = PackTemplate::new();
+    let first = ItemTemplate::from_str("item1,10,5,4,tags=FRAGILE").unwrap();
+    let second = ItemTemplate::from_str("item2,20,1,2").unwrap();
+    let mut pack = Pack::new(1);
+    pack.add(&pack_template, &first, 3, 5, PlacementLimit::Weight);
+    pack.add(&pack_template, &second, 1, 1, PlacementLimit::None);
+
+    let items: Vec<PackedItemRef> = pack.items().collect();
+    assert_eq!(items.len(), 2);
+    assert_eq!(
+        (items[0].id, items[0].count, items[0].limit),
+        ("item1", 3, PlacementLimit::Weight)
+    );
+    assert_eq!(
+        (items[1].id, items[1].length, items[1].weight),
+        ("item2", 20.0, 2.0)
+    );
+    assert_eq!(items[0].attributes.tags, vec!["FRAGILE".to_string()]);
+
+    // The placement refers to the ID of the batch instead of a copy of it
+    assert!(std::ptr::eq(items[0].id, &*first.id));
+}
+
+// Pack::number_of_items_with_tag() / weight_of_items_with_tag() / number_of_items_heavier_than()
+
+#[test]
+fn when_adding_items_it_should_keep_the_totals_by_tag_and_weight() {
+    let pack_template = PackTemplate::new();
+    let mut pack = Pack::new(1);
+    for (line, count) in [
+        ("1,100,3,2.0,tags=A|A|B", 3),
+        ("2,100,2,6.0,tags=A", 2),
+        ("3,-,TOTAL_KG=8,1.0/kg,tags=B", 1),
+        ("4,100,1,4.0", 1),
+    ] {
+        let template = ItemTemplate::from_str(line).unwrap();
+        pack.add(
+            &pack_template,
+            &template,
+            count,
+            count,
+            PlacementLimit::None,
+        );
+    }
+
+    assert_eq!(pack.number_of_items_with_tag("A"), 5);
+    assert_eq!(pack.number_of_items_with_tag("B"), 4);
+    assert_eq!(pack.number_of_items_with_tag("C"), 0);
+    assert_eq!(pack.weight_of_items_with_tag("A"), 18.0);
+    assert_eq!(pack.weight_of_items_with_tag("B"), 14.0);
+    assert_eq!(pack.number_of_items_heavier_than(2.0), 3);
+    assert_eq!(pack.number_of_items_heavier_than(4.0), 2);
+    assert_eq!(pack.number_of_items_heavier_than(0.0), 6);
+}
+
+// Pack::length_after_adding()
+
+fn create_nesting_pack_template(length_mode: LengthMode) -> PackTemplate {
+    PackTemplate {
+        length_mode,
+        nesting_rules: vec![NestingRule {
+            inner_item_id: "rod".to_string(),
+            outer_item_id: "tube".to_string(),
+        }],
+        ..PackTemplate::new()
+    }
+}
+
+#[test]
+fn when_adding_items_that_nest_it_should_only_count_the_length_of_the_outer_items() {
+    let pack_template = create_nesting_pack_template(LengthMode::Sum);
+    let tube = ItemTemplate::from_str("tube,100,2,1").unwrap();
+    let rod = ItemTemplate::from_str("rod,80,3,1").unwrap();
+
+    let mut pack = Pack::new(1);
+    pack.add(&pack_template, &tube, 2, 2, PlacementLimit::None);
+    assert_eq!(pack.length_after_adding(&pack_template, &rod, 2), 200.0);
+
+    // Each tube holds a single rod, so the third rod adds its own length.
+    pack.add(&pack_template, &rod, 3, 3, PlacementLimit::None);
+    assert_eq!(pack.length, 280.0);
+}
+
+#[test]
+fn when_adding_items_that_nest_with_a_gap_it_should_not_count_the_gap_of_the_inner_items() {
+    let pack_template = create_nesting_pack_template(LengthMode::SumWithGap(10.0));
+    let rod = ItemTemplate::from_str("rod,80,1,1").unwrap();
+    let tube = ItemTemplate::from_str("tube,100,2,1").unwrap();
+
+    let mut pack = Pack::new(1);
+    pack.add(&pack_template, &rod, 1, 1, PlacementLimit::None);
+    pack.add(&pack_template, &tube, 2, 2, PlacementLimit::None);
+    assert_eq!(pack.length, 210.0);
+}
+
+#[test]
+fn when_adding_an_inner_item_that_is_longer_than_the_outer_item_it_should_not_nest() {
+    let pack_template = create_nesting_pack_template(LengthMode::Sum);
+    let tube = ItemTemplate::from_str("tube,100,1,1").unwrap();
+    let rod = ItemTemplate::from_str("rod,120,1,1").unwrap();
+
+    let mut pack = Pack::new(1);
+    pack.add(&pack_template, &tube, 1, 1, PlacementLimit::None);
+    assert_eq!(pack.length_after_adding(&pack_template, &rod, 1), 220.0);
+}
+
+#[test]
+fn when_adding_items_that_nest_in_several_placements_it_should_nest_the_items_of_all_the_placements(
+) {
+    let pack_template = create_nesting_pack_template(LengthMode::Sum);
+    let tube = ItemTemplate::from_str("tube,100,1,1").unwrap();
+    let rod = ItemTemplate::from_str("rod,80,1,1").unwrap();
+
+    let mut pack = Pack::new(1);
+    pack.add(&pack_template, &tube, 1, 1, PlacementLimit::None);
+    pack.add(&pack_template, &rod, 1, 1, PlacementLimit::None);
+    pack.add(&pack_template, &tube, 1, 1, PlacementLimit::None);
+    assert_eq!(pack.length, 200.0);
+
+    // The second tube is still empty, while the first rod already nests in the first tube.
+    assert_eq!(pack.length_after_adding(&pack_template, &rod, 2), 280.0);
+}
+
+// IdPattern::matches()
+
+#[test]
+fn when_matching_an_id_without_wildcards_it_should_only_match_the_same_id() {
+    let pattern = IdPattern::new("BOLT-10");
+    assert!(pattern.matches("BOLT-10"));
+    assert!(!pattern.matches("BOLT-100"));
+    assert!(!pattern.matches("bolt-10"));
+}
+
+#[test]
+fn when_matching_an_id_with_wildcards_it_should_match_the_whole_id() {
+    let pattern = IdPattern::new("BOLT-*");
+    assert!(pattern.matches("BOLT-"));
+    assert!(pattern.matches("BOLT-10"));
+    assert!(!pattern.matches("NUT-10"));
+
+    let pattern = IdPattern::new("*-?0-*X");
+    assert!(pattern.matches("A-10-X"));
+    assert!(pattern.matches("A-B-10-XX-X"));
+    assert!(!pattern.matches("A-100-Y"));
+    assert!(!pattern.matches("A-0-X"));
+
+    assert!(IdPattern::new("*").matches(""));
+    assert!(IdPattern::new("**").matches("anything"));
+}
+
+// find_backorders()
+
+#[test]
+fn when_finding_backorders_it_should_combine_the_batches_of_an_item() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,5,2.0,ordered=8").unwrap(),
+        ItemTemplate::from_str("1002,100,3,2.0").unwrap(),
+        ItemTemplate::from_str("1003,100,0,2.0,ordered=4").unwrap(),
+        ItemTemplate::from_str("1001,100,2,2.0").unwrap(),
+        ItemTemplate::from_str("1002,100,1,2.0,ordered=1").unwrap(),
+    ];
+
+    assert_eq!(
+        find_backorders(&items),
+        vec![
+            Backorder {
+                item_id: "1001".to_string(),
+                ordered: 10,
+                available: 7,
+            },
+            Backorder {
+                item_id: "1003".to_string(),
+                ordered: 4,
+                available: 0,
+            },
+        ]
+    );
+}
+
+// find_unpacked_items()
+
+#[test]
+fn when_finding_unpacked_items_it_should_list_the_warnings_and_then_the_backorders() {
+    let warnings = vec![
+        PlanWarning::ItemExcluded {
+            item_id: "1001".to_string(),
+            count: 3,
+        },
+        PlanWarning::EmptyPack { pack_number: 2 },
+        PlanWarning::InfeasibleItemSkipped {
+            item_id: "1002".to_string(),
+            count: 1,
+        },
+        PlanWarning::IncompleteMultiple {
+            item_id: "1003".to_string(),
+            multiple: 6,
+            count: 4,
+        },
+    ];
+    let backorders = vec![Backorder {
+        item_id: "1004".to_string(),
+        ordered: 10,
+        available: 7,
+    }];
+
+    let unpacked_item = |item_id: &str, count: i32, reason: UnpackedReason| UnpackedItem {
+        item_id: item_id.to_string(),
+        count,
+        reason,
+    };
+    assert_eq!(
+        find_unpacked_items(&warnings, &backorders),
+        vec![
+            unpacked_item("1001", 3, UnpackedReason::Excluded),
+            unpacked_item("1002", 1, UnpackedReason::Infeasible),
+            unpacked_item("1003", 4, UnpackedReason::IncompleteMultiple),
+            unpacked_item("1004", 3, UnpackedReason::Backordered),
+        ]
+    );
+}
+
+// Totals / PlanTotals
+
+#[test]
+fn when_totaling_items_it_should_only_count_the_items_that_are_not_bulk() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,3,2.0").unwrap(),
+        ItemTemplate::from_str("1002,-,TOTAL_KG=7.5,1.0/kg").unwrap(),
+    ];
+    assert_eq!(
+        Totals::of_items(&items),
+        Totals {
+            items: 3,
+            weight: 13.5
+        }
+    );
+}
+
+#[test]
+fn when_totaling_backorders_it_should_count_the_items_that_are_not_available() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,3,2.0,ordered=5").unwrap(),
+        ItemTemplate::from_str("1002,100,4,1.0").unwrap(),
+    ];
+    assert_eq!(
+        Totals::of_backorders(&items),
+        Totals {
+            items: 2,
+            weight: 4.0
+        }
+    );
+}
+
+#[test]
+fn when_totaling_packs_it_should_match_the_totals_of_the_packed_items() {
+    let pack_template = PackTemplate::new();
+    let item = ItemTemplate::from_str("1001,100,5,2.0").unwrap();
+    let bulk = ItemTemplate::from_str("1002,-,TOTAL_KG=7.5,1.0/kg").unwrap();
+    let mut first_pack = Pack::new(1);
+    first_pack.add(&pack_template, &item, 3, 5, PlacementLimit::None);
+    first_pack.add_bulk(&pack_template, &bulk, 2.5, 7.5, PlacementLimit::Weight);
+    let mut second_pack = Pack::new(2);
+    second_pack.add(&pack_template, &item, 2, 2, PlacementLimit::None);
+    second_pack.add_bulk(&pack_template, &bulk, 5.0, 5.0, PlacementLimit::None);
+
+    assert_eq!(
+        Totals::of_packs(&[first_pack, second_pack]),
+        Totals::of_items(&[item, bulk])
+    );
+}
+
+#[test]
+fn when_items_are_packed_or_left_out_it_should_conserve_the_totals() {
+    let totals = |items, weight| Totals { items, weight };
+    let plan_totals = PlanTotals {
+        input: totals(10, 30.0),
+        packed: totals(8, 20.0),
+        left_out: totals(2, 10.0),
+    };
+    assert!(plan_totals.is_conserved());
+
+    let plan_totals = PlanTotals {
+        left_out: Totals::default(),
+        ..plan_totals
+    };
+    assert!(!plan_totals.is_conserved());
+
+    let plan_totals = PlanTotals {
+        input: totals(8, 20.5),
+        ..plan_totals
+    };
+    assert!(!plan_totals.is_conserved());
+}
+
+// PlanOutcome::remaining_capacity()
+
+fn create_partially_filled_plan(pack_template: &PackTemplate) -> PlanOutcome {
+    let mut first_pack = Pack::new(1);
+    first_pack.add(
+        pack_template,
+        &ItemTemplate::from_str("1,100,8,10.0").unwrap(),
+        8,
+        8,
+        PlacementLimit::Weight,
+    );
+    let mut second_pack = Pack::new(2);
+    second_pack.add(
+        pack_template,
+        &ItemTemplate::from_str("2,100,2,15.0").unwrap(),
+        2,
+        2,
+        PlacementLimit::None,
+    );
+
+    PlanOutcome {
+        packs: vec![first_pack, second_pack],
+        warnings: Vec::new(),
+        optimality: None,
+    }
+}
+
+#[test]
+fn when_finding_the_remaining_capacity_it_should_return_the_capacity_of_each_pack_and_the_total() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        ..PackTemplate::new()
+    };
+
+    let capacity = create_partially_filled_plan(&pack_template).remaining_capacity(&pack_template);
+    assert_eq!(
+        capacity.packs,
+        vec![
+            PackCapacity {
+                pack_number: 1,
+                weight: 20.0,
+                pieces: 2
+            },
+            PackCapacity {
+                pack_number: 2,
+                weight: 70.0,
+                pieces: 8
+            }
+        ]
+    );
+    assert_eq!(capacity.weight, 90.0);
+    assert_eq!(capacity.pieces, 10);
+}
+
+#[test]
+fn when_finding_the_remaining_capacity_of_packs_with_a_tare_weight_it_should_count_the_tare() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        tare_weight: 5.0,
+        ..PackTemplate::new()
+    };
+
+    let capacity = create_partially_filled_plan(&pack_template).remaining_capacity(&pack_template);
+    let weights: Vec<f64> = capacity.packs.iter().map(|p| p.weight).collect();
+    assert_eq!(weights, vec![15.0, 65.0]);
+    assert_eq!(capacity.weight, 80.0);
+}
+
+#[test]
+fn when_finding_the_remaining_capacity_of_an_overweight_pack_it_should_not_be_negative() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 5,
+        maximum_weight: 50.0,
+        ..PackTemplate::new()
+    };
+
+    let capacity = create_partially_filled_plan(&pack_template).remaining_capacity(&pack_template);
+    assert_eq!(capacity.packs[0].weight, 0.0);
+    assert_eq!(capacity.packs[0].pieces, 0);
+    assert_eq!(capacity.weight, 20.0);
+    assert_eq!(capacity.pieces, 3);
+}
+
+#[test]
+fn when_counting_the_items_that_fit_in_the_remaining_capacity_it_should_use_the_smallest_limit_of_each_pack(
+) {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        ..PackTemplate::new()
+    };
+    let capacity = create_partially_filled_plan(&pack_template).remaining_capacity(&pack_template);
+
+    // The first pack takes 2 items by weight and by count, the second pack 7 items by weight.
+    let item = ItemTemplate::from_str("3,100,1,10.0").unwrap();
+    assert_eq!(capacity.number_of_items(&item), 9);
+
+    let item = ItemTemplate::from_str("3,100,1,10.0,multiple=2").unwrap();
+    assert_eq!(capacity.number_of_items(&item), 8);
+
+    let item = ItemTemplate::from_str("3,100,1,10.0,multiple=3").unwrap();
+    assert_eq!(capacity.number_of_items(&item), 6);
+}
+
+// find_grade()
+
+#[test]
+fn when_finding_a_grade_it_should_return_the_highest_grade_that_is_reached() {
+    let grade = |name: &str, minimum_utilization| Grade {
+        name: name.to_string(),
+        minimum_utilization,
+    };
+    let grades = vec![grade("B", 0.7), grade("A", 0.9), grade("C", 0.2)];
+    assert_eq!(find_grade(&grades, 0.95).unwrap().name, "A");
+    assert_eq!(find_grade(&grades, 0.9).unwrap().name, "A");
+    assert_eq!(find_grade(&grades, 0.75).unwrap().name, "B");
+    assert_eq!(find_grade(&grades, 0.3).unwrap().name, "C");
+    assert!(find_grade(&grades, 0.1).is_none());
+}
+
+// find_weight_band()
+
+#[test]
+fn when_finding_a_weight_band_it_should_return_the_first_matching_band() {
+    let bands = vec![
+        WeightBand::from_str("SMALL,0,5,4.50").unwrap(),
+        WeightBand::from_str("MEDIUM,5,20,9.75").unwrap(),
+    ];
+    let pack_of = |weight: f64| Pack {
+        weight,
+        ..Pack::new(1)
+    };
+    assert_eq!(
+        find_weight_band(&bands, &pack_of(3.0)).unwrap().name,
+        "SMALL"
+    );
+    assert_eq!(
+        find_weight_band(&bands, &pack_of(5.0)).unwrap().name,
+        "SMALL"
+    );
+    assert_eq!(
+        find_weight_band(&bands, &pack_of(5.1)).unwrap().name,
+        "MEDIUM"
+    );
+    assert!(find_weight_band(&bands, &pack_of(25.0)).is_none());
+}
+
+#[test]
+fn when_finding_a_weight_band_it_should_include_the_tare_weight_of_the_pack() {
+    let bands = vec![
+        WeightBand::from_str("SMALL,0,5,4.50").unwrap(),
+        WeightBand::from_str("MEDIUM,5,20,9.75").unwrap(),
+    ];
+    let pack = Pack {
+        weight: 4.5,
+        tare_weight: 1.0,
+        ..Pack::new(1)
+    };
+    assert_eq!(find_weight_band(&bands, &pack).unwrap().name, "MEDIUM");
+}
+
+// find_carton()
+
+fn create_carton(name: &str, maximum_length: f64, maximum_weight: f64) -> Carton {
+    Carton {
+        name: name.to_string(),
+        maximum_length,
+        maximum_weight,
+    }
+}
+
+#[test]
+fn when_finding_a_carton_it_should_return_the_smallest_carton_that_fits_whatever_the_order() {
+    let cartons = vec![
+        create_carton("LARGE", 1200.0, 20.0),
+        create_carton("MEDIUM-HEAVY", 600.0, 15.0),
+        create_carton("MEDIUM", 600.0, 10.0),
+        create_carton("SMALL", 300.0, 5.0),
+    ];
+    let pack = Pack {
+        length: 500.0,
+        weight: 4.0,
+        ..Pack::new(1)
+    };
+    assert_eq!(find_carton(&cartons, &pack).unwrap().name, "MEDIUM");
+
+    let too_long = Pack {
+        length: 1500.0,
+        ..Pack::new(2)
+    };
+    assert!(find_carton(&cartons, &too_long).is_none());
+}
+
+#[test]
+fn when_finding_a_carton_it_should_include_the_tare_weight_of_the_pack() {
+    let cartons = vec![
+        create_carton("SMALL", 600.0, 5.0),
+        create_carton("LARGE", 1200.0, 20.0),
+    ];
+    let pack = Pack {
+        length: 500.0,
+        weight: 4.5,
+        tare_weight: 1.0,
+        ..Pack::new(1)
+    };
+    assert_eq!(find_carton(&cartons, &pack).unwrap().name, "LARGE");
+}
@@ -0,0 +1,2025 @@
+//! Parsing of the line based input formats for the items, the pack information, the pack rules and the
+//! weight bands.
+
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::diagnostics;
+use crate::errors::Error;
+use crate::input::read_error;
+use crate::model::{
+    BillOfMaterials, Carton, ClassLimits, Comparison, CutItem, DeliveryStop, DestinationLimits,
+    Grade, IdPattern, InputSection, ItemAliases, ItemAttributes, ItemField, ItemFilter, ItemMap,
+    ItemTemplate, LengthMode, MapOperation, NestingRule, NumberFormat, PackDimensions, PackRule,
+    PackSortOrder, PackTemplate, SheetSize, StorageClass, SubstitutionGroups, WeightBand,
+};
+
+#[cfg(test)]
+#[path = "parser_tests.rs"]
+mod tests;
+
+// Indices used when parsing the limits of an item class
+const CLASS_LIMITS_CLASS_INDEX: usize = 0;
+const CLASS_LIMITS_MAXIMUM_ITEM_COUNT_INDEX: usize = 1;
+const CLASS_LIMITS_MAXIMUM_WEIGHT_INDEX: usize = 2;
+
+// The value of a class limit that keeps the limit of the pack template
+const CLASS_LIMITS_UNCHANGED: &str = "-";
+
+// Indices used when parsing a delivery stop
+const DELIVERY_STOP_DESTINATION_INDEX: usize = 0;
+const DELIVERY_STOP_NUMBER_INDEX: usize = 1;
+
+// Indices used when parsing the limits of a destination
+const DESTINATION_LIMITS_DESTINATION_INDEX: usize = 0;
+const DESTINATION_LIMITS_MAXIMUM_ITEM_COUNT_INDEX: usize = 1;
+const DESTINATION_LIMITS_MAXIMUM_WEIGHT_INDEX: usize = 2;
+
+// The value of a destination limit that doesn't limit the destination
+const DESTINATION_LIMITS_UNLIMITED: &str = "-";
+
+// Indices used when parsing a nesting rule
+const NESTING_RULE_INNER_ITEM_ID_INDEX: usize = 0;
+const NESTING_RULE_OUTER_ITEM_ID_INDEX: usize = 1;
+
+// Indices used when parsing the pack information from the input
+const PACK_SORT_ORDER_INDEX: usize = 0;
+const PACK_MAXIMUM_ITEM_COUNT_INDEX: usize = 1;
+const PACK_MAXIMUM_WEIGHT_INDEX: usize = 2;
+const PACK_ATTRIBUTES_START_INDEX: usize = 3;
+
+// Indices used when parsing the items from the input
+const ITEM_ID_INDEX: usize = 0;
+const ITEM_LENGTH_INDEX: usize = 1;
+const ITEM_QUANTITY_INDEX: usize = 2;
+const ITEM_WEIGHT_INDEX: usize = 3;
+const ITEM_ATTRIBUTES_START_INDEX: usize = 4;
+
+// The length, the quantity prefix and the weight suffix that mark a bulk item, e.g. `3001,-,TOTAL_KG=250,1.0/kg`
+const BULK_ITEM_LENGTH: &str = "-";
+const BULK_ITEM_QUANTITY_PREFIX: &str = "TOTAL_KG=";
+const BULK_ITEM_WEIGHT_SUFFIX: &str = "/kg";
+
+// The length, the quantity prefix and the weight suffix that mark an item that is cut from a total length, e.g.
+// `4001,1000,TOTAL_LENGTH=25500,0.0021/length`. The length is the cut increment, or '-' for any length.
+const CUT_ITEM_ANY_LENGTH: &str = "-";
+const CUT_ITEM_QUANTITY_PREFIX: &str = "TOTAL_LENGTH=";
+const CUT_ITEM_WEIGHT_SUFFIX: &str = "/length";
+
+// The separator used between the values of an item attribute that can have multiple values, e.g. the tags
+const ITEM_ATTRIBUTE_VALUE_SEPARATOR: char = '|';
+
+// The directive, and the indices, used when parsing a line that pins an item to a pack
+const PIN_DIRECTIVE: &str = "PIN";
+const PIN_DIRECTIVE_INDEX: usize = 0;
+const PIN_ITEM_ID_INDEX: usize = 1;
+const PIN_PACK_NUMBER_INDEX: usize = 2;
+
+// The directive that closes the current pack
+const CLOSE_PACK_DIRECTIVE: &str = "---CLOSE---";
+
+// The prefixes used when parsing a line that refers to a kit, e.g. `KIT:gift-set-1,3`, or to a substitution
+// group, e.g. `GROUP:mug-any-color,12`
+const KIT_PREFIX: &str = "KIT:";
+const GROUP_PREFIX: &str = "GROUP:";
+
+// Indices used when parsing a line that refers to a kit or to a substitution group
+const REFERENCE_ID_INDEX: usize = 0;
+const REFERENCE_COUNT_INDEX: usize = 1;
+
+// The directive that selects the version of the input format, e.g. `#VERSION 2`
+const VERSION_DIRECTIVE: &str = "#VERSION";
+
+// The directive that selects the locale in which the numbers of the input are written, e.g. `#LOCALE de-DE`
+const LOCALE_DIRECTIVE: &str = "#LOCALE";
+
+// The thousands separators of the supported locales. The decimal separator of the locales that group the
+// thousands with a period is a comma.
+const LOCALE_THOUSANDS_SEPARATORS: &[(&str, char)] = &[
+    ("en-US", ','),
+    ("en-GB", ','),
+    ("en-AU", ','),
+    ("en-CA", ','),
+    ("ja-JP", ','),
+    ("zh-CN", ','),
+    ("de-DE", '.'),
+    ("de-AT", '.'),
+    ("nl-NL", '.'),
+    ("nl-BE", '.'),
+    ("es-ES", '.'),
+    ("it-IT", '.'),
+    ("pt-BR", '.'),
+    ("da-DK", '.'),
+    ("de-CH", '\''),
+];
+
+/// The version of the input format that is used when the input doesn't start with a version directive.
+/// Version 1 is the original format, with only the pack information and the item properties. Version 2 adds
+/// the attributes, the directives and the units.
+pub const LATEST_INPUT_VERSION: u32 = 2;
+
+// Indices used when parsing the pack rules
+const PACK_RULE_TYPE_INDEX: usize = 0;
+const PACK_RULE_TAG_INDEX: usize = 1;
+const PACK_RULE_MAXIMUM_INDEX: usize = 2;
+
+// Indices used when parsing the weight bands
+const WEIGHT_BAND_NAME_INDEX: usize = 0;
+const WEIGHT_BAND_MINIMUM_WEIGHT_INDEX: usize = 1;
+const WEIGHT_BAND_MAXIMUM_WEIGHT_INDEX: usize = 2;
+const WEIGHT_BAND_COST_INDEX: usize = 3;
+
+// Indices used when parsing the cartons
+const CARTON_NAME_INDEX: usize = 0;
+const CARTON_MAXIMUM_LENGTH_INDEX: usize = 1;
+const CARTON_MAXIMUM_WEIGHT_INDEX: usize = 2;
+
+impl ItemAttributes {
+    fn from_parts(
+        line: &str,
+        parts: &[&str],
+        format: &NumberFormat,
+    ) -> Result<ItemAttributes, Error> {
+        let mut attributes = ItemAttributes::default();
+        for part in parts {
+            // The caller has already verified that each of the parts contains a key and a value.
+            let (key, value) = part.split_once('=').unwrap_or((part, ""));
+            match key.trim() {
+                "tags" => {
+                    attributes.tags = value
+                        .split(ITEM_ATTRIBUTE_VALUE_SEPARATOR)
+                        .map(|t| t.trim())
+                        .filter(|t| !t.is_empty())
+                        .map(|t| t.to_string())
+                        .collect();
+                }
+                "value" => {
+                    let value = value.trim();
+                    attributes.value = Some(parse_float(value, format).map_err(|e| {
+                        e.into_error(line, value, |input, property_value, source| {
+                            Error::InvalidItemValue {
+                                input,
+                                property_value,
+                                source,
+                            }
+                        })
+                    })?);
+                }
+                "storage" => {
+                    let value = value.trim();
+                    attributes.storage_class =
+                        StorageClass::from_str(value).map_err(|_| Error::InvalidStorageClass {
+                            input: line.to_string(),
+                            property_value: value.to_string(),
+                        })?;
+                }
+                "destination" => {
+                    let value = value.trim();
+                    if value.is_empty() {
+                        return Err(Error::InvalidItemDestination {
+                            input: line.to_string(),
+                        });
+                    }
+
+                    attributes.destination = Some(value.to_string());
+                }
+                "width" => {
+                    let value = value.trim();
+                    let to_width_error = || Error::InvalidItemWidth {
+                        input: line.to_string(),
+                        property_value: value.to_string(),
+                    };
+                    let width = parse_float(value, format)
+                        .map_err(|e| e.into_error(line, value, |_, _, _| to_width_error()))?;
+                    if width <= 0.0 {
+                        return Err(to_width_error());
+                    }
+
+                    attributes.width = Some(width);
+                }
+                "ordered" => {
+                    let value = value.trim();
+                    match parse_integer(value, format) {
+                        Ok(ordered) if ordered >= 0 => attributes.ordered = Some(ordered),
+                        _ => {
+                            return Err(Error::InvalidOrderedQuantity {
+                                input: line.to_string(),
+                                property_value: value.to_string(),
+                            })
+                        }
+                    }
+                }
+                "multiple" => {
+                    let value = value.trim();
+                    match parse_integer(value, format) {
+                        Ok(multiple) if multiple > 0 => attributes.multiple = Some(multiple),
+                        _ => {
+                            return Err(Error::InvalidItemMultiple {
+                                input: line.to_string(),
+                                property_value: value.to_string(),
+                            })
+                        }
+                    }
+                }
+                _ => {
+                    return Err(Error::UnknownItemAttribute {
+                        input: line.to_string(),
+                        attribute: key.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(attributes)
+    }
+}
+
+/// Describes why a number in the input could not be read.
+enum NumberError<E> {
+    /// The number contains invalid characters.
+    Syntax(E),
+
+    /// The number doesn't match the number format of the input.
+    Format,
+
+    /// The number is too large, or too small, to be stored.
+    Range,
+
+    /// The number is not a number, or infinite.
+    NotFinite,
+}
+
+impl<E> NumberError<E> {
+    /// Converts the number error into an error for the given line. Syntax errors are converted with the
+    /// given function, which receives the line, the value and the source error.
+    fn into_error<F: FnOnce(String, String, E) -> Error>(
+        self,
+        line: &str,
+        value: &str,
+        to_syntax_error: F,
+    ) -> Error {
+        match self {
+            NumberError::Syntax(e) => to_syntax_error(line.to_string(), value.to_string(), e),
+            NumberError::Format => Error::InvalidNumberFormat {
+                input: line.to_string(),
+                property_value: value.to_string(),
+            },
+            NumberError::Range => Error::NumberOutOfRange {
+                input: line.to_string(),
+                property_value: value.to_string(),
+            },
+            NumberError::NotFinite => Error::NonFiniteNumber {
+                input: line.to_string(),
+                property_value: value.to_string(),
+            },
+        }
+    }
+}
+
+/// Splits a line in its comma separated fields. Commas inside double quotes don't separate fields, so that
+/// numbers with a comma as thousands separator can be provided, e.g. `"1,200"`. The quotes around a field
+/// are removed.
+fn split_fields(line: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut field_start = 0;
+    let mut is_quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => is_quoted = !is_quoted,
+            ',' if !is_quoted => {
+                fields.push(&line[field_start..index]);
+                field_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&line[field_start..]);
+
+    fields
+        .into_iter()
+        .map(|f| {
+            let trimmed = f.trim();
+            if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') {
+                &trimmed[1..trimmed.len() - 1]
+            } else {
+                f
+            }
+        })
+        .collect()
+}
+
+/// Rewrites a number in the given format to the format understood by the standard library, i.e. without
+/// thousands separators and with a period as the decimal separator. Returns `None` if the number doesn't
+/// match the format.
+fn normalize_number(value: &str, format: &NumberFormat) -> Option<String> {
+    let value = value.trim();
+    if !format.allow_scientific_notation && value.contains(['e', 'E']) {
+        return None;
+    }
+
+    let separator = match format.thousands_separator {
+        Some(s) => s,
+        None => return Some(value.to_string()),
+    };
+
+    let decimal_separator = if separator == '.' { ',' } else { '.' };
+    let (integer_part, fraction) = match value.split_once(decimal_separator) {
+        Some((i, f)) => (i, Some(f)),
+        None => (value, None),
+    };
+
+    let mut normalized = String::with_capacity(value.len());
+    let digits = match integer_part.strip_prefix(['-', '+']) {
+        Some(d) => {
+            normalized.push_str(&integer_part[..1]);
+            d
+        }
+        None => integer_part,
+    };
+
+    // All groups, except the first, should have exactly three digits.
+    for (index, group) in digits.split(separator).enumerate() {
+        let is_valid_group = if index == 0 {
+            (!group.is_empty() && group.len() <= 3) || !digits.contains(separator)
+        } else {
+            group.len() == 3
+        };
+        if !is_valid_group {
+            return None;
+        }
+
+        normalized.push_str(group);
+    }
+
+    if let Some(f) = fraction {
+        normalized.push('.');
+        normalized.push_str(f);
+    }
+
+    Some(normalized)
+}
+
+/// Parses a floating point number written in the given format. Only finite numbers are accepted, because
+/// `NaN` and infinite values can't be compared with other numbers in a meaningful way.
+fn parse_float(value: &str, format: &NumberFormat) -> Result<f64, NumberError<ParseFloatError>> {
+    let normalized = normalize_number(value, format).ok_or(NumberError::Format)?;
+    let number: f64 = normalized.parse().map_err(NumberError::Syntax)?;
+
+    if !number.is_finite() {
+        // Very large numbers, e.g. 1e999, are parsed as infinity.
+        let lowercase = normalized.to_ascii_lowercase();
+        if lowercase.contains("inf") || lowercase.contains("nan") {
+            return Err(NumberError::NotFinite);
+        }
+
+        return Err(NumberError::Range);
+    }
+
+    Ok(number)
+}
+
+/// Parses a whole number written in the given format. If scientific notation is allowed the number may be
+/// written as, for instance, `1.2e3`, as long as the value is a whole number.
+fn parse_integer(value: &str, format: &NumberFormat) -> Result<i32, NumberError<ParseIntError>> {
+    let normalized = normalize_number(value, format).ok_or(NumberError::Format)?;
+    let error = match normalized.parse::<i32>() {
+        Ok(v) => return Ok(v),
+        Err(e) => e,
+    };
+
+    match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => Err(NumberError::Range),
+        _ if format.allow_scientific_notation && normalized.contains(['e', 'E']) => {
+            match normalized.parse::<f64>() {
+                Ok(v) if v.fract() != 0.0 => Err(NumberError::Syntax(error)),
+                Ok(v) if v < i32::MIN as f64 || v > i32::MAX as f64 => Err(NumberError::Range),
+                Ok(v) => Ok(v as i32),
+                Err(_) => Err(NumberError::Syntax(error)),
+            }
+        }
+        _ => Err(NumberError::Syntax(error)),
+    }
+}
+
+/// Parses the value of the `thousands_separator` pack attribute.
+fn parse_thousands_separator(value: &str) -> Option<Option<char>> {
+    match value {
+        "none" => Some(None),
+        "comma" => Some(Some(',')),
+        "period" => Some(Some('.')),
+        "space" => Some(Some(' ')),
+        "apostrophe" => Some(Some('\'')),
+        "underscore" => Some(Some('_')),
+        _ => None,
+    }
+}
+
+/// Parses the quantity of an item, which is either a number of eaches, e.g. `36`, or a number of higher units
+/// followed by the unit and the number of eaches per unit, e.g. `3 BOX(12)`. Returns the quantity and the
+/// number of eaches per unit, which is 1 for a quantity in eaches.
+fn parse_quantity(line: &str, value: &str, format: &NumberFormat) -> Result<(i32, i32), Error> {
+    let to_unit_error = || Error::InvalidItemUnit {
+        input: line.to_string(),
+        property_value: value.to_string(),
+    };
+
+    let (quantity, unit) = match value.trim().strip_suffix(')') {
+        Some(v) => {
+            // The unit name starts after the last digit of the quantity, e.g. `3 BOX(12)` or `3BOX(12)`.
+            let unit_start = v
+                .find(|c: char| c.is_alphabetic())
+                .ok_or_else(to_unit_error)?;
+            (&v[..unit_start], Some(&v[unit_start..]))
+        }
+        None => (value, None),
+    };
+
+    let quantity = parse_integer(quantity, format).map_err(|e| {
+        e.into_error(line, value, |input, property_value, source| {
+            Error::InvalidItemCount {
+                input,
+                property_value,
+                source,
+            }
+        })
+    })?;
+
+    let eaches_per_unit = match unit {
+        Some(u) => {
+            let (_, eaches) = u.split_once('(').ok_or_else(to_unit_error)?;
+            match parse_integer(eaches, format) {
+                Ok(n) if n > 0 => n,
+                _ => return Err(to_unit_error()),
+            }
+        }
+        None => 1,
+    };
+
+    Ok((quantity, eaches_per_unit))
+}
+
+/// Parses the number in the value of a part of an item line, which must be greater than 0. The value is the
+/// part without its prefix or suffix, or `None` if the part didn't have the prefix or suffix. Invalid numbers
+/// are reported with the error of 'to_error', which receives the line and the part.
+fn parse_positive_part(
+    line: &str,
+    part: &str,
+    value: Option<&str>,
+    format: &NumberFormat,
+    to_error: fn(String, String) -> Error,
+) -> Result<f64, Error> {
+    let value = value.ok_or_else(|| to_error(line.to_string(), part.to_string()))?;
+    match parse_float(value.trim(), format) {
+        Ok(v) if v > 0.0 => Ok(v),
+        Ok(_) | Err(NumberError::Syntax(_)) => Err(to_error(line.to_string(), part.to_string())),
+        Err(e) => Err(e.into_error(line, part, |input, property_value, _| {
+            to_error(input, property_value)
+        })),
+    }
+}
+
+/// Parses the quantity and the weight of a bulk item, formatted as `TOTAL_KG=[total weight]` and
+/// `[weight]/kg`, and returns the total weight of the bulk.
+fn parse_bulk_weight(line: &str, parts: &[&str], format: &NumberFormat) -> Result<f64, Error> {
+    let to_bulk_error = |input, property_value| Error::InvalidBulkItem {
+        input,
+        property_value,
+    };
+
+    let quantity = parts[ITEM_QUANTITY_INDEX];
+    let total_weight = parse_positive_part(
+        line,
+        quantity,
+        quantity.trim().strip_prefix(BULK_ITEM_QUANTITY_PREFIX),
+        format,
+        to_bulk_error,
+    )?;
+    let weight = parts[ITEM_WEIGHT_INDEX];
+    let weight_per_kg = parse_positive_part(
+        line,
+        weight,
+        weight.trim().strip_suffix(BULK_ITEM_WEIGHT_SUFFIX),
+        format,
+        to_bulk_error,
+    )?;
+
+    Ok(total_weight * weight_per_kg)
+}
+
+/// Parses the length, the quantity and the weight of an item that is cut from a total length, formatted as
+/// `-` or `[cut increment]`, `TOTAL_LENGTH=[total length]` and `[weight]/length`. Returns the total length and
+/// the way in which the item is cut.
+fn parse_cut_item(
+    line: &str,
+    parts: &[&str],
+    format: &NumberFormat,
+) -> Result<(f64, CutItem), Error> {
+    let to_cut_error = |input, property_value| Error::InvalidCutItem {
+        input,
+        property_value,
+    };
+
+    let quantity = parts[ITEM_QUANTITY_INDEX];
+    let total_length = parse_positive_part(
+        line,
+        quantity,
+        quantity.trim().strip_prefix(CUT_ITEM_QUANTITY_PREFIX),
+        format,
+        to_cut_error,
+    )?;
+    let weight = parts[ITEM_WEIGHT_INDEX];
+    let weight_per_length = parse_positive_part(
+        line,
+        weight,
+        weight.trim().strip_suffix(CUT_ITEM_WEIGHT_SUFFIX),
+        format,
+        to_cut_error,
+    )?;
+    let length = parts[ITEM_LENGTH_INDEX];
+    let increment = match length.trim() {
+        CUT_ITEM_ANY_LENGTH => None,
+        increment => Some(parse_positive_part(
+            line,
+            length,
+            Some(increment),
+            format,
+            to_cut_error,
+        )?),
+    };
+
+    Ok((
+        total_length,
+        CutItem {
+            weight_per_length,
+            increment,
+        },
+    ))
+}
+
+impl ItemTemplate {
+    /// Parses an item line in which the numbers are written in the given format.
+    fn from_line(line: &str, format: &NumberFormat) -> Result<ItemTemplate, Error> {
+        let parts: Vec<&str> = split_fields(line);
+        let has_only_attributes_after_properties = parts
+            .iter()
+            .skip(ITEM_ATTRIBUTES_START_INDEX)
+            .all(|p| p.contains('='));
+        if parts.len() < ITEM_ATTRIBUTES_START_INDEX || !has_only_attributes_after_properties {
+            return Err(Error::InvalidNumberOfPropertiesForItem {
+                input: line.to_string(),
+                property_count: parts.len(),
+            });
+        }
+
+        let id: Arc<str> = parts[ITEM_ID_INDEX].into();
+
+        if parts[ITEM_QUANTITY_INDEX]
+            .trim()
+            .starts_with(CUT_ITEM_QUANTITY_PREFIX)
+        {
+            let (total_length, cut) = parse_cut_item(line, &parts, format)?;
+            let mut attributes =
+                ItemAttributes::from_parts(line, &parts[ITEM_ATTRIBUTES_START_INDEX..], format)?;
+            if let Some(ordered) = attributes.ordered {
+                return Err(Error::InvalidOrderedQuantity {
+                    input: line.to_string(),
+                    property_value: ordered.to_string(),
+                });
+            }
+            if let Some(multiple) = attributes.multiple {
+                return Err(Error::InvalidItemMultiple {
+                    input: line.to_string(),
+                    property_value: multiple.to_string(),
+                });
+            }
+            attributes.cut = Some(cut);
+
+            return Ok(Self {
+                id,
+                length: total_length,
+                weight: total_length * cut.weight_per_length,
+                count: 1,
+                attributes: Arc::new(attributes),
+            });
+        }
+
+        if parts[ITEM_LENGTH_INDEX].trim() == BULK_ITEM_LENGTH {
+            let weight = parse_bulk_weight(line, &parts, format)?;
+            let mut attributes =
+                ItemAttributes::from_parts(line, &parts[ITEM_ATTRIBUTES_START_INDEX..], format)?;
+            if let Some(ordered) = attributes.ordered {
+                return Err(Error::InvalidOrderedQuantity {
+                    input: line.to_string(),
+                    property_value: ordered.to_string(),
+                });
+            }
+            if let Some(multiple) = attributes.multiple {
+                return Err(Error::InvalidItemMultiple {
+                    input: line.to_string(),
+                    property_value: multiple.to_string(),
+                });
+            }
+            attributes.bulk = true;
+
+            return Ok(Self {
+                id,
+                length: 0.0,
+                weight,
+                count: 1,
+                attributes: Arc::new(attributes),
+            });
+        }
+
+        let length = parse_float(parts[ITEM_LENGTH_INDEX], format).map_err(|e| {
+            e.into_error(
+                line,
+                parts[ITEM_LENGTH_INDEX],
+                |input, property_value, source| Error::InvalidItemLength {
+                    input,
+                    property_value,
+                    source,
+                },
+            )
+        })?;
+
+        let weight = parse_float(parts[ITEM_WEIGHT_INDEX], format).map_err(|e| {
+            e.into_error(
+                line,
+                parts[ITEM_WEIGHT_INDEX],
+                |input, property_value, source| Error::InvalidItemWeight {
+                    input,
+                    property_value,
+                    source,
+                },
+            )
+        })?;
+
+        let (quantity, eaches_per_unit) = parse_quantity(line, parts[ITEM_QUANTITY_INDEX], format)?;
+        let count =
+            quantity
+                .checked_mul(eaches_per_unit)
+                .ok_or_else(|| Error::NumberOutOfRange {
+                    input: line.to_string(),
+                    property_value: parts[ITEM_QUANTITY_INDEX].to_string(),
+                })?;
+
+        // The weight and the value of an item in a higher unit are those of the whole unit. All the eaches in
+        // a unit share the length of the unit.
+        let weight = weight / (eaches_per_unit as f64);
+
+        let mut attributes =
+            ItemAttributes::from_parts(line, &parts[ITEM_ATTRIBUTES_START_INDEX..], format)?;
+        attributes.value = attributes.value.map(|v| v / (eaches_per_unit as f64));
+        // The ordered quantity is in the same unit as the available quantity.
+        if let Some(ordered) = attributes.ordered {
+            attributes.ordered = match ordered.checked_mul(eaches_per_unit) {
+                Some(ordered) if ordered >= count => Some(ordered),
+                _ => {
+                    return Err(Error::InvalidOrderedQuantity {
+                        input: line.to_string(),
+                        property_value: ordered.to_string(),
+                    })
+                }
+            };
+        }
+        // The pack multiple is in the same unit as the quantity.
+        if let Some(multiple) = attributes.multiple {
+            attributes.multiple = Some(multiple.checked_mul(eaches_per_unit).ok_or_else(|| {
+                Error::NumberOutOfRange {
+                    input: line.to_string(),
+                    property_value: multiple.to_string(),
+                }
+            })?);
+        }
+
+        Ok(Self {
+            id,
+            length,
+            weight,
+            count,
+            attributes: Arc::new(attributes),
+        })
+    }
+}
+
+impl FromStr for ItemTemplate {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        ItemTemplate::from_line(line, &NumberFormat::default())
+    }
+}
+
+impl PackTemplate {
+    #[allow(clippy::wrong_self_convention)]
+    fn from_line(&mut self, s: &str) -> Result<(), Error> {
+        let parts: Vec<&str> = split_fields(s);
+        let has_only_attributes_after_properties = parts
+            .iter()
+            .skip(PACK_ATTRIBUTES_START_INDEX)
+            .all(|p| p.contains('='));
+        if parts.len() < PACK_ATTRIBUTES_START_INDEX || !has_only_attributes_after_properties {
+            return Err(Error::InvalidNumberOfPropertiesForPacks {
+                input: s.to_string(),
+                property_count: parts.len(),
+            });
+        }
+
+        let pack_sort_order;
+        match PackSortOrder::from_str(parts[PACK_SORT_ORDER_INDEX]) {
+            Ok(s) => pack_sort_order = s,
+            Err(_) => {
+                return Err(Error::InvalidPackSortOrder {
+                    input: s.to_string(),
+                    property_value: parts[PACK_SORT_ORDER_INDEX].to_string(),
+                })
+            }
+        };
+
+        // The attributes may change the number format, so they are read before any of the numbers.
+        let mut maximum_length = None;
+        let mut hard_maximum_weight = None;
+        let mut maximum_value = None;
+        let mut tare_weight = None;
+        let mut sheet = None;
+        let mut dimensions = None;
+        for part in &parts[PACK_ATTRIBUTES_START_INDEX..] {
+            // The properties have already been verified to contain a key and a value.
+            let (key, value) = part.split_once('=').unwrap_or((part, ""));
+            let key = key.trim();
+            let value = value.trim();
+            let to_number_format_error = || Error::InvalidNumberFormatAttribute {
+                input: s.to_string(),
+                attribute: key.to_string(),
+                property_value: value.to_string(),
+            };
+            match key {
+                "length" => {
+                    self.length_mode =
+                        parse_length_mode(value).ok_or(Error::InvalidLengthMode {
+                            input: s.to_string(),
+                            property_value: value.to_string(),
+                        })?;
+                }
+                "max_length" => maximum_length = Some(value),
+                "hard_max_weight" => hard_maximum_weight = Some(value),
+                "max_value" => maximum_value = Some(value),
+                "tare" => tare_weight = Some(value),
+                "sheet" => sheet = Some(value),
+                "dimensions" => dimensions = Some(value),
+                "thousands_separator" => {
+                    self.number_format.thousands_separator =
+                        parse_thousands_separator(value).ok_or_else(to_number_format_error)?;
+                }
+                "scientific_notation" => {
+                    self.number_format.allow_scientific_notation =
+                        value.parse().map_err(|_| to_number_format_error())?;
+                }
+                _ => {
+                    return Err(Error::UnknownPackAttribute {
+                        input: s.to_string(),
+                        attribute: key.to_string(),
+                    })
+                }
+            }
+        }
+
+        let format = self.number_format;
+        if let Some(value) = maximum_length {
+            let length = parse_float(value, &format).map_err(|e| {
+                e.into_error(s, value, |input, property_value, source| {
+                    Error::InvalidPackMaximumLength {
+                        input,
+                        property_value,
+                        source,
+                    }
+                })
+            })?;
+            self.maximum_length = Some(length);
+        }
+
+        if let Some(value) = maximum_value {
+            let maximum = parse_float(value, &format).map_err(|e| {
+                e.into_error(s, value, |input, property_value, source| {
+                    Error::InvalidPackMaximumValue {
+                        input,
+                        property_value,
+                        source,
+                    }
+                })
+            })?;
+            self.maximum_value = Some(maximum);
+        }
+
+        if let Some(value) = sheet {
+            self.sheet =
+                Some(
+                    parse_sheet_size(value, &format).ok_or_else(|| Error::InvalidSheetSize {
+                        input: s.to_string(),
+                        property_value: value.to_string(),
+                    })?,
+                );
+        }
+
+        if let Some(value) = dimensions {
+            self.dimensions = Some(parse_pack_dimensions(value, &format).ok_or_else(|| {
+                Error::InvalidPackDimensions {
+                    input: s.to_string(),
+                    property_value: value.to_string(),
+                }
+            })?);
+        }
+
+        let maximum_number_of_items = parse_integer(parts[PACK_MAXIMUM_ITEM_COUNT_INDEX], &format)
+            .map_err(|e| {
+                e.into_error(
+                    s,
+                    parts[PACK_MAXIMUM_ITEM_COUNT_INDEX],
+                    |input, property_value, source| Error::InvalidPackItemCount {
+                        input,
+                        property_value,
+                        source,
+                    },
+                )
+            })?;
+
+        let maximum_weight =
+            parse_float(parts[PACK_MAXIMUM_WEIGHT_INDEX], &format).map_err(|e| {
+                e.into_error(
+                    s,
+                    parts[PACK_MAXIMUM_WEIGHT_INDEX],
+                    |input, property_value, source| Error::InvalidPackWeight {
+                        input,
+                        property_value,
+                        source,
+                    },
+                )
+            })?;
+
+        if let Some(value) = hard_maximum_weight {
+            let weight = parse_float(value, &format).map_err(|e| {
+                e.into_error(s, value, |input, property_value, source| {
+                    Error::InvalidPackHardMaximumWeight {
+                        input,
+                        property_value,
+                        source,
+                    }
+                })
+            })?;
+            if weight < maximum_weight {
+                return Err(Error::HardMaximumWeightBelowMaximumWeight {
+                    input: s.to_string(),
+                    hard_maximum_weight: weight,
+                    maximum_weight,
+                });
+            }
+
+            self.hard_maximum_weight = Some(weight);
+        }
+
+        if let Some(value) = tare_weight {
+            let to_tare_error = || Error::InvalidPackTareWeight {
+                input: s.to_string(),
+                property_value: value.to_string(),
+            };
+            let weight = parse_float(value, &format)
+                .map_err(|e| e.into_error(s, value, |_, _, _| to_tare_error()))?;
+            if weight < 0.0 || weight >= maximum_weight {
+                return Err(to_tare_error());
+            }
+
+            self.tare_weight = weight;
+        }
+
+        self.maximum_number_of_pieces = maximum_number_of_items;
+        self.maximum_weight = maximum_weight;
+        self.sort_order = pack_sort_order;
+
+        Ok(())
+    }
+}
+
+/// Parses the length mode of a pack, which is one of `max`, `sum` or `sum-with-gap [gap]`.
+fn parse_length_mode(value: &str) -> Option<LengthMode> {
+    match value {
+        "max" => Some(LengthMode::Maximum),
+        "sum" => Some(LengthMode::Sum),
+        _ => {
+            let gap = value.strip_prefix("sum-with-gap")?.trim().parse().ok()?;
+            Some(LengthMode::SumWithGap(gap))
+        }
+    }
+}
+
+/// Parses the size of a sheet, formatted as `[length]x[width]`, e.g. `3000x1500`.
+fn parse_sheet_size(value: &str, format: &NumberFormat) -> Option<SheetSize> {
+    let (length, width) = value.split_once(['x', 'X'])?;
+    let length = parse_float(length.trim(), format).ok()?;
+    let width = parse_float(width.trim(), format).ok()?;
+    (length > 0.0 && width > 0.0).then_some(SheetSize { length, width })
+}
+
+/// Parses the internal size of a pack, formatted as `[length]x[width]x[height]`, e.g. `1200x800x600`.
+fn parse_pack_dimensions(value: &str, format: &NumberFormat) -> Option<PackDimensions> {
+    let sizes = value
+        .split(['x', 'X'])
+        .map(|v| parse_float(v.trim(), format).ok().filter(|s| *s > 0.0))
+        .collect::<Option<Vec<f64>>>()?;
+    match sizes[..] {
+        [length, width, height] => Some(PackDimensions {
+            length,
+            width,
+            height,
+        }),
+        _ => None,
+    }
+}
+
+/// Parses a directive that selects the version of the input format, formatted as `#VERSION [version]`.
+fn parse_version_directive(line: &str) -> Result<u32, Error> {
+    let version = line
+        .strip_prefix(VERSION_DIRECTIVE)
+        .filter(|v| v.starts_with(char::is_whitespace))
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .ok_or_else(|| Error::InvalidVersionDirective {
+            input: line.to_string(),
+        })?;
+
+    if version == 0 || version > LATEST_INPUT_VERSION {
+        return Err(Error::UnsupportedInputVersion { version });
+    }
+
+    Ok(version)
+}
+
+/// Parses a locale directive, formatted as `#LOCALE [locale]`, e.g. `#LOCALE de-DE`, and returns the
+/// thousands separator of the locale. The locale is not case sensitive, and may use an underscore instead of
+/// a hyphen.
+fn parse_locale_directive(line: &str) -> Result<char, Error> {
+    let locale = line
+        .strip_prefix(LOCALE_DIRECTIVE)
+        .filter(|l| l.starts_with(char::is_whitespace))
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .ok_or_else(|| Error::InvalidLocaleDirective {
+            input: line.to_string(),
+        })?;
+
+    LOCALE_THOUSANDS_SEPARATORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(&locale.replace('_', "-")))
+        .map(|(_, separator)| *separator)
+        .ok_or_else(|| Error::UnknownLocale {
+            input: line.to_string(),
+            locale: locale.to_string(),
+        })
+}
+
+/// Verifies that a pack or item line only uses the features of version 1 of the input format, i.e. it has no
+/// attributes, no units, no bulk quantities and no cut items.
+fn verify_version_1_line(line: &str, is_pack_line: bool) -> Result<(), Error> {
+    let parts = split_fields(line);
+    let property_count = if is_pack_line {
+        PACK_ATTRIBUTES_START_INDEX
+    } else {
+        ITEM_ATTRIBUTES_START_INDEX
+    };
+    if parts.len() > property_count {
+        return Err(Error::AttributesRequireVersion2 {
+            input: line.to_string(),
+        });
+    }
+
+    if is_pack_line {
+        if parts.len() != PACK_ATTRIBUTES_START_INDEX {
+            return Err(Error::InvalidNumberOfPropertiesForPacks {
+                input: line.to_string(),
+                property_count: parts.len(),
+            });
+        }
+    } else {
+        if parts.len() != ITEM_ATTRIBUTES_START_INDEX {
+            return Err(Error::InvalidNumberOfPropertiesForItem {
+                input: line.to_string(),
+                property_count: parts.len(),
+            });
+        }
+
+        if parts[ITEM_QUANTITY_INDEX].contains('(') {
+            return Err(Error::InvalidItemUnit {
+                input: line.to_string(),
+                property_value: parts[ITEM_QUANTITY_INDEX].to_string(),
+            });
+        }
+
+        if parts[ITEM_QUANTITY_INDEX]
+            .trim()
+            .starts_with(CUT_ITEM_QUANTITY_PREFIX)
+        {
+            return Err(Error::InvalidCutItem {
+                input: line.to_string(),
+                property_value: parts[ITEM_QUANTITY_INDEX].to_string(),
+            });
+        }
+
+        if parts[ITEM_LENGTH_INDEX].trim() == BULK_ITEM_LENGTH {
+            return Err(Error::InvalidBulkItem {
+                input: line.to_string(),
+                property_value: parts[ITEM_QUANTITY_INDEX].to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the pack information and the items from the input. The input may start with a version directive,
+/// e.g. `#VERSION 1`, which selects the version of the input format. Without a directive the latest version
+/// is used. Returns an error if the input contains more than one section, see [`parse_input_sections`].
+pub fn parse_input<R: BufRead>(reader: &mut R) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
+    let section = parse_sections(reader, false)?
+        .pop()
+        .expect("The input always has a section.");
+    Ok((section.pack_template, section.items))
+}
+
+/// Reads the sections of the input, each of which starts with its own pack information and is planned
+/// independently of the other sections. The attributes of the first pack information are the defaults of the
+/// other sections, which may override them. An input with a single pack information has a single section.
+pub fn parse_input_sections<R: BufRead>(reader: &mut R) -> Result<Vec<InputSection>, Error> {
+    parse_sections(reader, true)
+}
+
+fn parse_sections<R: BufRead>(
+    reader: &mut R,
+    allow_sections: bool,
+) -> Result<Vec<InputSection>, Error> {
+    let mut sections: Vec<InputSection> = Vec::new();
+    let mut default_pack_template = PackTemplate::new();
+    let mut pack_template = PackTemplate::new();
+    let mut item_templates: Vec<ItemTemplate> = Vec::new();
+    let mut pins: Vec<(String, usize)> = Vec::new();
+    let mut close_pack_before_next_item = false;
+    let mut version = LATEST_INPUT_VERSION;
+
+    let mut is_first_line = true;
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line.map_err(read_error)?;
+        if line.is_empty() {
+            break;
+        }
+
+        let at_line = |e| diagnostics::at_line(e, line_index + 1, &line);
+        let trimmed_line = line.trim();
+
+        if trimmed_line.starts_with(VERSION_DIRECTIVE) {
+            if line_index > 0 {
+                return Err(at_line(Error::InvalidVersionDirective {
+                    input: line.clone(),
+                }));
+            }
+
+            version = parse_version_directive(trimmed_line).map_err(at_line)?;
+            continue;
+        }
+
+        // The locale is the default number format of the whole input, so it comes before the pack
+        // information. The attributes of the pack information still override it.
+        if trimmed_line.starts_with(LOCALE_DIRECTIVE) {
+            if !is_first_line || version < 2 {
+                return Err(at_line(Error::InvalidLocaleDirective {
+                    input: line.clone(),
+                }));
+            }
+
+            let separator = parse_locale_directive(trimmed_line).map_err(at_line)?;
+            pack_template.number_format.thousands_separator = Some(separator);
+            continue;
+        }
+
+        // The line should start either with an integer number or one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT]
+        let is_number = if let Some(c) = trimmed_line.chars().next() {
+            c.is_ascii_digit()
+        } else {
+            false // Empty string
+        };
+
+        let is_keyword = trimmed_line.starts_with("NATURAL")
+            || trimmed_line.starts_with("SHORT_TO_LONG")
+            || trimmed_line.starts_with("LONG_TO_SHORT");
+
+        // The directives were introduced in version 2.
+        let is_pin_directive = version >= 2 && trimmed_line.starts_with(PIN_DIRECTIVE);
+        let is_close_pack_directive = version >= 2 && trimmed_line == CLOSE_PACK_DIRECTIVE;
+        let is_kit = version >= 2 && trimmed_line.starts_with(KIT_PREFIX);
+        let is_group = version >= 2 && trimmed_line.starts_with(GROUP_PREFIX);
+
+        if !is_number
+            && !is_keyword
+            && !is_pin_directive
+            && !is_close_pack_directive
+            && !is_kit
+            && !is_group
+        {
+            return Err(at_line(Error::InputStringShouldStartWithNumberOrKeyWord {
+                input: line.clone(),
+            }));
+        }
+
+        // The first line contains information about the packs that we're allowed to create
+        if version == 1 && (is_keyword || is_number) {
+            verify_version_1_line(trimmed_line, is_keyword).map_err(at_line)?;
+        }
+
+        if is_keyword {
+            if is_first_line {
+                pack_template.from_line(trimmed_line).map_err(at_line)?;
+                default_pack_template = pack_template.clone();
+
+                // Any line after this cannot be the first line anymore.
+                is_first_line = false;
+            } else if allow_sections {
+                sections.push(finish_section(
+                    pack_template,
+                    std::mem::take(&mut item_templates),
+                    std::mem::take(&mut pins),
+                )?);
+
+                pack_template = default_pack_template.clone();
+                pack_template.from_line(trimmed_line).map_err(at_line)?;
+                verify_inherited_limits(&pack_template, trimmed_line).map_err(at_line)?;
+            } else {
+                return Err(at_line(Error::InputContainsDuplicatePackInformation {
+                    current_line: line.clone(),
+                    current_line_index: line_index,
+                }));
+            }
+        } else if is_pin_directive {
+            pins.push(parse_pin_directive(trimmed_line).map_err(at_line)?);
+        } else if is_close_pack_directive {
+            // The pack is closed by the item that follows the directive, so that the directive stays with
+            // that item if the items are sorted.
+            close_pack_before_next_item = true;
+        } else {
+            let mut item = if is_kit {
+                parse_kit_line(trimmed_line, &pack_template.number_format)
+            } else if is_group {
+                parse_group_line(trimmed_line, &pack_template.number_format)
+            } else {
+                ItemTemplate::from_line(trimmed_line, &pack_template.number_format)
+            }
+            .map_err(at_line)?;
+            if close_pack_before_next_item {
+                Arc::make_mut(&mut item.attributes).close_pack_before = true;
+                close_pack_before_next_item = false;
+            }
+
+            item_templates.push(item);
+        }
+    }
+
+    sections.push(finish_section(pack_template, item_templates, pins)?);
+    Ok(sections)
+}
+
+/// Creates a section of the input once all its items are known. The pins refer to the packs of the section,
+/// and may be provided before the items they refer to.
+fn finish_section(
+    pack_template: PackTemplate,
+    mut item_templates: Vec<ItemTemplate>,
+    pins: Vec<(String, usize)>,
+) -> Result<InputSection, Error> {
+    for (item_id, pack_number) in pins {
+        let mut is_found = false;
+        for item in item_templates.iter_mut().filter(|i| *i.id == *item_id) {
+            Arc::make_mut(&mut item.attributes).pinned_pack = Some(pack_number);
+            is_found = true;
+        }
+
+        if !is_found {
+            return Err(Error::PinnedItemNotFound { item_id });
+        }
+    }
+
+    Ok(InputSection {
+        pack_template,
+        items: item_templates,
+    })
+}
+
+/// Verifies that the weights that a section inherits from the first pack information still fit the maximum
+/// weight of the section.
+fn verify_inherited_limits(pack_template: &PackTemplate, line: &str) -> Result<(), Error> {
+    if let Some(hard_maximum_weight) = pack_template.hard_maximum_weight {
+        if hard_maximum_weight < pack_template.maximum_weight {
+            return Err(Error::HardMaximumWeightBelowMaximumWeight {
+                input: line.to_string(),
+                hard_maximum_weight,
+                maximum_weight: pack_template.maximum_weight,
+            });
+        }
+    }
+
+    if pack_template.tare_weight >= pack_template.maximum_weight {
+        return Err(Error::InvalidPackTareWeight {
+            input: line.to_string(),
+            property_value: pack_template.tare_weight.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Parses a directive that pins an item to a pack, formatted as `PIN,[item id],pack=[pack number]`. Returns
+/// the item ID and the 1-based pack number.
+fn parse_pin_directive(line: &str) -> Result<(String, usize), Error> {
+    let to_error = || Error::InvalidPinDirective {
+        input: line.to_string(),
+    };
+
+    let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+    if parts.len() != 3 || parts[PIN_DIRECTIVE_INDEX] != PIN_DIRECTIVE {
+        return Err(to_error());
+    }
+
+    let pack_number: usize = parts[PIN_PACK_NUMBER_INDEX]
+        .strip_prefix("pack=")
+        .and_then(|n| n.trim().parse().ok())
+        .ok_or_else(to_error)?;
+    if pack_number == 0 {
+        return Err(to_error());
+    }
+
+    Ok((parts[PIN_ITEM_ID_INDEX].to_string(), pack_number))
+}
+
+/// Parses a line that refers to a kit or to a substitution group, formatted as `[prefix][id],[count]`.
+/// Returns the ID and the count.
+fn parse_reference_line(
+    line: &str,
+    prefix: &str,
+    format: &NumberFormat,
+    to_error: impl Fn() -> Error,
+) -> Result<(Arc<str>, i32), Error> {
+    let parts: Vec<&str> = split_fields(line.strip_prefix(prefix).ok_or_else(&to_error)?);
+    if parts.len() != 2 || parts[REFERENCE_ID_INDEX].trim().is_empty() {
+        return Err(to_error());
+    }
+
+    let count = parse_integer(parts[REFERENCE_COUNT_INDEX], format).map_err(|e| {
+        e.into_error(
+            line,
+            parts[REFERENCE_COUNT_INDEX],
+            |input, property_value, source| Error::InvalidItemCount {
+                input,
+                property_value,
+                source,
+            },
+        )
+    })?;
+
+    Ok((parts[REFERENCE_ID_INDEX].trim().into(), count))
+}
+
+/// Parses a line that refers to a kit, formatted as `KIT:[kit id],[number of kits]`. Returns an item that
+/// stands in for the kit until the kit is replaced by its components.
+fn parse_kit_line(line: &str, format: &NumberFormat) -> Result<ItemTemplate, Error> {
+    let (id, count) = parse_reference_line(line, KIT_PREFIX, format, || Error::InvalidKitLine {
+        input: line.to_string(),
+    })?;
+
+    Ok(ItemTemplate {
+        id,
+        length: 0.0,
+        weight: 0.0,
+        count,
+        attributes: Arc::new(ItemAttributes {
+            kit: true,
+            ..ItemAttributes::default()
+        }),
+    })
+}
+
+/// Parses a line that refers to a substitution group, formatted as `GROUP:[group id],[number of items]`.
+/// Returns an item that stands in for the group until members of the group are selected.
+fn parse_group_line(line: &str, format: &NumberFormat) -> Result<ItemTemplate, Error> {
+    let (id, count) = parse_reference_line(line, GROUP_PREFIX, format, || {
+        Error::InvalidSubstitutionGroupLine {
+            input: line.to_string(),
+        }
+    })?;
+
+    Ok(ItemTemplate {
+        id,
+        length: 0.0,
+        weight: 0.0,
+        count,
+        attributes: Arc::new(ItemAttributes {
+            substitution_group: true,
+            ..ItemAttributes::default()
+        }),
+    })
+}
+
+/// Reads a list of items, one item per line. Empty lines and lines starting with a '#' are ignored.
+pub fn parse_items<R: BufRead>(reader: &mut R) -> Result<Vec<ItemTemplate>, Error> {
+    let mut items = Vec::new();
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        items.push(
+            ItemTemplate::from_str(trimmed_line)
+                .map_err(|e| diagnostics::at_line(e, line_index + 1, &line))?,
+        );
+    }
+
+    Ok(items)
+}
+
+impl FromStr for PackRule {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        let expected_count = match parts[PACK_RULE_TYPE_INDEX] {
+            "MAX_ITEMS_WITH_TAG" => 3,
+            "MAX_WEIGHT_WITH_TAG" => 3,
+            "EXCLUSIVE_TAG" => 2,
+            "MAX_HEAVY_ITEMS" => 3,
+            rule => {
+                return Err(Error::UnknownPackRule {
+                    input: line.to_string(),
+                    rule: rule.to_string(),
+                })
+            }
+        };
+
+        if parts.len() != expected_count {
+            return Err(Error::InvalidNumberOfPropertiesForPackRule {
+                input: line.to_string(),
+                expected_count,
+                property_count: parts.len(),
+            });
+        }
+
+        let parse_maximum = || {
+            parts[PACK_RULE_MAXIMUM_INDEX]
+                .parse()
+                .map_err(|e| Error::InvalidPackRuleItemCount {
+                    input: line.to_string(),
+                    property_value: parts[PACK_RULE_MAXIMUM_INDEX].to_string(),
+                    source: e,
+                })
+        };
+
+        // The heavy items are selected by their weight instead of a tag.
+        if parts[PACK_RULE_TYPE_INDEX] == "MAX_HEAVY_ITEMS" {
+            let threshold =
+                parts[PACK_RULE_TAG_INDEX]
+                    .parse()
+                    .map_err(|e| Error::InvalidPackRuleWeight {
+                        input: line.to_string(),
+                        property_value: parts[PACK_RULE_TAG_INDEX].to_string(),
+                        source: e,
+                    })?;
+
+            return Ok(PackRule::MaximumHeavyItems {
+                threshold,
+                maximum: parse_maximum()?,
+            });
+        }
+
+        let tag = parts[PACK_RULE_TAG_INDEX].to_string();
+        if parts[PACK_RULE_TYPE_INDEX] == "EXCLUSIVE_TAG" {
+            return Ok(PackRule::ExclusiveTag { tag });
+        }
+
+        if parts[PACK_RULE_TYPE_INDEX] == "MAX_WEIGHT_WITH_TAG" {
+            let maximum_weight = parts[PACK_RULE_MAXIMUM_INDEX].parse().map_err(|e| {
+                Error::InvalidPackRuleWeight {
+                    input: line.to_string(),
+                    property_value: parts[PACK_RULE_MAXIMUM_INDEX].to_string(),
+                    source: e,
+                }
+            })?;
+
+            return Ok(PackRule::MaximumWeightWithTag {
+                tag,
+                maximum_weight,
+            });
+        }
+
+        Ok(PackRule::MaximumItemsWithTag {
+            tag,
+            maximum: parse_maximum()?,
+        })
+    }
+}
+
+/// Reads the pack rules, one rule per line. Empty lines and lines starting with a '#' are ignored.
+pub fn parse_pack_rules<R: BufRead>(reader: &mut R) -> Result<Vec<PackRule>, Error> {
+    let mut rules = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        rules.push(PackRule::from_str(trimmed_line)?);
+    }
+
+    Ok(rules)
+}
+
+impl FromStr for WeightBand {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() != 4 {
+            return Err(Error::InvalidNumberOfPropertiesForWeightBand {
+                input: line.to_string(),
+                property_count: parts.len(),
+            });
+        }
+
+        let name = parts[WEIGHT_BAND_NAME_INDEX].to_string();
+
+        let minimum_weight = parts[WEIGHT_BAND_MINIMUM_WEIGHT_INDEX]
+            .parse()
+            .map_err(|e| Error::InvalidWeightBandWeight {
+                input: line.to_string(),
+                property_value: parts[WEIGHT_BAND_MINIMUM_WEIGHT_INDEX].to_string(),
+                source: e,
+            })?;
+
+        let maximum_weight = parts[WEIGHT_BAND_MAXIMUM_WEIGHT_INDEX]
+            .parse()
+            .map_err(|e| Error::InvalidWeightBandWeight {
+                input: line.to_string(),
+                property_value: parts[WEIGHT_BAND_MAXIMUM_WEIGHT_INDEX].to_string(),
+                source: e,
+            })?;
+
+        let cost =
+            parts[WEIGHT_BAND_COST_INDEX]
+                .parse()
+                .map_err(|e| Error::InvalidWeightBandCost {
+                    input: line.to_string(),
+                    property_value: parts[WEIGHT_BAND_COST_INDEX].to_string(),
+                    source: e,
+                })?;
+
+        Ok(Self {
+            name,
+            minimum_weight,
+            maximum_weight,
+            cost,
+        })
+    }
+}
+
+/// Reads the weight bands, one band per line formatted as `[name],[minimum weight],[maximum weight],[cost]`.
+/// Empty lines and lines starting with a '#' are ignored.
+pub fn parse_weight_bands<R: BufRead>(reader: &mut R) -> Result<Vec<WeightBand>, Error> {
+    let mut bands = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        bands.push(WeightBand::from_str(trimmed_line)?);
+    }
+
+    Ok(bands)
+}
+
+impl FromStr for Grade {
+    type Err = Error;
+
+    /// Reads a grade formatted as `[name]=[minimum utilization]`, where the minimum utilization is a
+    /// percentage of the capacity of the pack, e.g. `A=90`.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid_grade = || Error::InvalidGrade {
+            input: input.to_string(),
+        };
+        let (name, minimum_utilization) = input.split_once('=').ok_or_else(invalid_grade)?;
+        let name = name.trim();
+        let minimum_utilization: f64 = minimum_utilization
+            .trim()
+            .parse()
+            .map_err(|_| invalid_grade())?;
+        if name.is_empty() || !(0.0..=100.0).contains(&minimum_utilization) {
+            return Err(invalid_grade());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            minimum_utilization: minimum_utilization / 100.0,
+        })
+    }
+}
+
+/// Reads the grades of the packs, formatted as a comma separated list of `[name]=[minimum utilization]`,
+/// e.g. `A=90,B=70,C=0`.
+pub fn parse_grades(input: &str) -> Result<Vec<Grade>, Error> {
+    input.split(',').map(Grade::from_str).collect()
+}
+
+impl FromStr for Carton {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() != 3 {
+            return Err(Error::InvalidNumberOfPropertiesForCarton {
+                input: line.to_string(),
+                property_count: parts.len(),
+            });
+        }
+
+        let parse_limit = |index: usize| {
+            parts[index]
+                .parse::<f64>()
+                .map_err(|e| Error::InvalidCartonLimit {
+                    input: line.to_string(),
+                    property_value: parts[index].to_string(),
+                    source: e,
+                })
+        };
+
+        Ok(Self {
+            name: parts[CARTON_NAME_INDEX].to_string(),
+            maximum_length: parse_limit(CARTON_MAXIMUM_LENGTH_INDEX)?,
+            maximum_weight: parse_limit(CARTON_MAXIMUM_WEIGHT_INDEX)?,
+        })
+    }
+}
+
+/// Reads the cartons, one carton per line formatted as `[name],[maximum length],[maximum weight]`, in any
+/// order. Empty lines and lines starting with a '#' are ignored.
+pub fn parse_cartons<R: BufRead>(reader: &mut R) -> Result<Vec<Carton>, Error> {
+    let mut cartons = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        cartons.push(Carton::from_str(trimmed_line)?);
+    }
+
+    Ok(cartons)
+}
+
+/// Reads the bill of materials, one component per line, formatted as `[kit id],[item line]`, where the
+/// quantity of the item is the number of that item in a single kit. Empty lines and lines starting with a
+/// '#' are ignored.
+pub fn parse_bill_of_materials<R: BufRead>(reader: &mut R) -> Result<BillOfMaterials, Error> {
+    let mut bill_of_materials = BillOfMaterials::default();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let to_error = || Error::InvalidBillOfMaterialsLine {
+            input: trimmed_line.to_string(),
+        };
+        let (kit_id, item_line) = trimmed_line.split_once(',').ok_or_else(to_error)?;
+        let kit_id = kit_id.trim();
+        if kit_id.is_empty() {
+            return Err(to_error());
+        }
+
+        let component = ItemTemplate::from_str(item_line.trim())?;
+        if component.attributes.bulk || component.attributes.cut.is_some() {
+            return Err(to_error());
+        }
+
+        bill_of_materials
+            .kits
+            .entry(kit_id.to_string())
+            .or_default()
+            .push(component);
+    }
+
+    Ok(bill_of_materials)
+}
+
+impl FromStr for NestingRule {
+    type Err = Error;
+
+    /// Parses a nesting rule, formatted as `[inner item id],[outer item id]`.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let to_error = || Error::InvalidNestingRule {
+            input: line.to_string(),
+        };
+
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() != 2 || parts.iter().any(|p| p.is_empty()) {
+            return Err(to_error());
+        }
+
+        let inner_item_id = parts[NESTING_RULE_INNER_ITEM_ID_INDEX];
+        let outer_item_id = parts[NESTING_RULE_OUTER_ITEM_ID_INDEX];
+        if inner_item_id == outer_item_id {
+            return Err(to_error());
+        }
+
+        Ok(NestingRule {
+            inner_item_id: inner_item_id.to_string(),
+            outer_item_id: outer_item_id.to_string(),
+        })
+    }
+}
+
+/// Splits an expression, formatted as `[property] [operator] [number]`, into its parts. The operator is the
+/// first of the given operators with which the remainder of the expression starts, so longer operators have
+/// to be provided before the operators they start with.
+fn split_expression<'a>(
+    expression: &'a str,
+    operators: &[&'a str],
+) -> Option<(&'a str, &'a str, f64)> {
+    let expression = expression.trim();
+    let field_end = expression
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(expression.len());
+    let (field, remainder) = expression.split_at(field_end);
+    let remainder = remainder.trim_start();
+    let operator = operators.iter().find(|o| remainder.starts_with(**o))?;
+    let value: f64 = remainder[operator.len()..].trim().parse().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+
+    Some((field, operator, value))
+}
+
+impl FromStr for ItemFilter {
+    type Err = Error;
+
+    /// Parses a filter expression, formatted as `[property] [comparison] [number]`, e.g. `weight < 30`.
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let to_error = || Error::InvalidItemFilter {
+            input: expression.to_string(),
+        };
+
+        let (field, comparison, value) =
+            split_expression(expression, &["<=", ">=", "==", "!=", "<", ">"])
+                .ok_or_else(to_error)?;
+        Ok(ItemFilter {
+            field: ItemField::from_str(field).map_err(|_| to_error())?,
+            comparison: Comparison::from_str(comparison).map_err(|_| to_error())?,
+            value,
+        })
+    }
+}
+
+impl FromStr for ItemMap {
+    type Err = Error;
+
+    /// Parses a map expression, formatted as `[property] [operator] [number]`, e.g. `count *= 2`.
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let to_error = || Error::InvalidItemMap {
+            input: expression.to_string(),
+        };
+
+        let (field, operation, value) =
+            split_expression(expression, &["+=", "-=", "*=", "/=", "="]).ok_or_else(to_error)?;
+        let operation = MapOperation::from_str(operation).map_err(|_| to_error())?;
+        if operation == MapOperation::Divide && value == 0.0 {
+            return Err(to_error());
+        }
+
+        Ok(ItemMap {
+            field: ItemField::from_str(field).map_err(|_| to_error())?,
+            operation,
+            value,
+        })
+    }
+}
+
+/// Reads the substitution groups, one member per line, formatted as `[group id],[item line]`, where the
+/// quantity of the item is the number of items of that member that is available. The members of a group are
+/// kept in the order of the lines. Empty lines and lines starting with a '#' are ignored.
+pub fn parse_substitution_groups<R: BufRead>(reader: &mut R) -> Result<SubstitutionGroups, Error> {
+    let mut substitution_groups = SubstitutionGroups::default();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let to_error = || Error::InvalidSubstitutionLine {
+            input: trimmed_line.to_string(),
+        };
+        let (group_id, item_line) = trimmed_line.split_once(',').ok_or_else(to_error)?;
+        let group_id = group_id.trim();
+        if group_id.is_empty() {
+            return Err(to_error());
+        }
+
+        let member = ItemTemplate::from_str(item_line.trim())?;
+        if member.attributes.bulk || member.attributes.cut.is_some() {
+            return Err(to_error());
+        }
+
+        substitution_groups
+            .groups
+            .entry(group_id.to_string())
+            .or_default()
+            .push(member);
+    }
+
+    Ok(substitution_groups)
+}
+
+/// Reads the aliases of the item IDs, one alias per line, formatted as `[alias],[canonical id]`. Empty lines
+/// and lines starting with a '#' are ignored. Returns an error if an alias is listed more than once, or if an
+/// ID is both an alias and a canonical ID, because aliases aren't followed from one to the next.
+pub fn parse_item_aliases<R: BufRead>(reader: &mut R) -> Result<ItemAliases, Error> {
+    let mut item_aliases = ItemAliases::default();
+    let mut canonical_ids = HashSet::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let to_error = || Error::InvalidAliasLine {
+            input: trimmed_line.to_string(),
+        };
+        let (alias, canonical_id) = trimmed_line.split_once(',').ok_or_else(to_error)?;
+        let (alias, canonical_id) = (alias.trim(), canonical_id.trim());
+        if alias.is_empty()
+            || canonical_id.is_empty()
+            || canonical_id.contains(',')
+            || alias == canonical_id
+            || item_aliases.aliases.contains_key(alias)
+            || item_aliases.aliases.contains_key(canonical_id)
+            || canonical_ids.contains(alias)
+        {
+            return Err(to_error());
+        }
+
+        canonical_ids.insert(canonical_id.to_string());
+        item_aliases
+            .aliases
+            .insert(alias.to_string(), canonical_id.to_string());
+    }
+
+    Ok(item_aliases)
+}
+
+/// Reads the nesting rules, one rule per line. Empty lines and lines starting with a '#' are ignored.
+pub fn parse_nesting_rules<R: BufRead>(reader: &mut R) -> Result<Vec<NestingRule>, Error> {
+    let mut rules = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        rules.push(NestingRule::from_str(trimmed_line)?);
+    }
+
+    Ok(rules)
+}
+
+impl FromStr for ClassLimits {
+    type Err = Error;
+
+    /// Parses the limits of an item class, formatted as `[class],[maximum number of pieces],[maximum weight]`,
+    /// where a `-` keeps the limit of the pack template.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let to_error = || Error::InvalidClassLimits {
+            input: line.to_string(),
+        };
+
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() != 3 || parts[CLASS_LIMITS_CLASS_INDEX].is_empty() {
+            return Err(to_error());
+        }
+
+        let maximum_number_of_pieces = match parts[CLASS_LIMITS_MAXIMUM_ITEM_COUNT_INDEX] {
+            CLASS_LIMITS_UNCHANGED => None,
+            value => Some(
+                value
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|m| *m > 0)
+                    .ok_or_else(to_error)?,
+            ),
+        };
+        let maximum_weight = match parts[CLASS_LIMITS_MAXIMUM_WEIGHT_INDEX] {
+            CLASS_LIMITS_UNCHANGED => None,
+            value => Some(
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|m| *m > 0.0)
+                    .ok_or_else(to_error)?,
+            ),
+        };
+
+        Ok(ClassLimits {
+            class: parts[CLASS_LIMITS_CLASS_INDEX].to_string(),
+            maximum_number_of_pieces,
+            maximum_weight,
+        })
+    }
+}
+
+/// Reads the limits of the item classes, one class per line. Empty lines and lines starting with a '#' are
+/// ignored. Returns an error if a class is listed more than once.
+pub fn parse_class_limits<R: BufRead>(reader: &mut R) -> Result<Vec<ClassLimits>, Error> {
+    let mut class_limits: Vec<ClassLimits> = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let limits = ClassLimits::from_str(trimmed_line)?;
+        if class_limits.iter().any(|l| l.class == limits.class) {
+            return Err(Error::InvalidClassLimits {
+                input: trimmed_line.to_string(),
+            });
+        }
+
+        class_limits.push(limits);
+    }
+
+    Ok(class_limits)
+}
+
+/// Reads the patterns of the IDs of the items that are excluded, one ID or pattern per line. Empty lines and
+/// lines starting with a '#' are ignored.
+pub fn parse_id_patterns<R: BufRead>(reader: &mut R) -> Result<Vec<IdPattern>, Error> {
+    let mut patterns = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        patterns.push(IdPattern::new(trimmed_line));
+    }
+
+    Ok(patterns)
+}
+
+impl FromStr for DeliveryStop {
+    type Err = Error;
+
+    /// Parses a delivery stop, formatted as `[destination],[stop number]`.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let to_error = || Error::InvalidDeliveryStop {
+            input: line.to_string(),
+        };
+
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() != 2 || parts[DELIVERY_STOP_DESTINATION_INDEX].is_empty() {
+            return Err(to_error());
+        }
+
+        let stop: u32 = parts[DELIVERY_STOP_NUMBER_INDEX]
+            .parse()
+            .map_err(|_| to_error())?;
+        if stop == 0 {
+            return Err(to_error());
+        }
+
+        Ok(DeliveryStop {
+            destination: parts[DELIVERY_STOP_DESTINATION_INDEX].to_string(),
+            stop,
+        })
+    }
+}
+
+/// Reads the delivery stops, one destination per line. Empty lines and lines starting with a '#' are ignored.
+/// Returns an error if a destination is listed more than once.
+pub fn parse_delivery_stops<R: BufRead>(reader: &mut R) -> Result<Vec<DeliveryStop>, Error> {
+    let mut stops: Vec<DeliveryStop> = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let stop = DeliveryStop::from_str(trimmed_line)?;
+        if stops.iter().any(|s| s.destination == stop.destination) {
+            return Err(Error::InvalidDeliveryStop {
+                input: trimmed_line.to_string(),
+            });
+        }
+
+        stops.push(stop);
+    }
+
+    Ok(stops)
+}
+
+impl FromStr for DestinationLimits {
+    type Err = Error;
+
+    /// Parses the limits of a destination, formatted as
+    /// `[destination],[maximum number of pieces],[maximum weight]`, where a `-` doesn't limit the destination.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let to_error = || Error::InvalidDestinationLimits {
+            input: line.to_string(),
+        };
+
+        let parts: Vec<&str> = line.split(',').map(|p| p.trim()).collect();
+        if parts.len() != 3 || parts[DESTINATION_LIMITS_DESTINATION_INDEX].is_empty() {
+            return Err(to_error());
+        }
+
+        let maximum_number_of_pieces = match parts[DESTINATION_LIMITS_MAXIMUM_ITEM_COUNT_INDEX] {
+            DESTINATION_LIMITS_UNLIMITED => None,
+            value => Some(
+                value
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|m| *m > 0)
+                    .ok_or_else(to_error)?,
+            ),
+        };
+        let maximum_weight = match parts[DESTINATION_LIMITS_MAXIMUM_WEIGHT_INDEX] {
+            DESTINATION_LIMITS_UNLIMITED => None,
+            value => Some(
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|m| m.is_finite() && *m > 0.0)
+                    .ok_or_else(to_error)?,
+            ),
+        };
+
+        Ok(DestinationLimits {
+            destination: parts[DESTINATION_LIMITS_DESTINATION_INDEX].to_string(),
+            maximum_number_of_pieces,
+            maximum_weight,
+        })
+    }
+}
+
+/// Reads the limits of the destinations, one destination per line. Empty lines and lines starting with a '#'
+/// are ignored. Returns an error if a destination is listed more than once.
+pub fn parse_destination_limits<R: BufRead>(
+    reader: &mut R,
+) -> Result<Vec<DestinationLimits>, Error> {
+    let mut destination_limits: Vec<DestinationLimits> = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(read_error)?;
+
+        let trimmed_line = line.trim();
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let limits = DestinationLimits::from_str(trimmed_line)?;
+        if destination_limits
+            .iter()
+            .any(|l| l.destination == limits.destination)
+        {
+            return Err(Error::InvalidDestinationLimits {
+                input: trimmed_line.to_string(),
+            });
+        }
+
+        destination_limits.push(limits);
+    }
+
+    Ok(destination_limits)
+}
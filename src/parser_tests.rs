@@ -0,0 +1,1606 @@
+use std::io::Cursor;
+
+use super::*;
+use crate::errors::Error;
+use crate::model::LengthMode;
+
+// ItemTemplate
+
+#[test]
+fn when_parsing_a_valid_item_input_string_it_should_return_an_item_template() {
+    let input = "item1,10.5,20,3.0";
+    let result = ItemTemplate::from_str(input);
+    assert!(result.is_ok());
+
+    let item = result.unwrap();
+    assert_eq!(&*item.id, "item1");
+    assert_eq!(item.length, 10.5);
+    assert_eq!(item.count, 20);
+    assert_eq!(item.weight, 3.0);
+}
+
+#[test]
+fn when_parsing_an_item_input_with_too_few_properties_it_should_return_an_error() {
+    let input = "item1,10.5,20";
+    let result = ItemTemplate::from_str(input);
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberOfPropertiesForItem {
+            input: input.to_string(),
+            property_count: 3
+        }
+    );
+}
+
+#[test]
+fn when_parsing_an_item_input_with_too_many_properties_it_should_return_an_error() {
+    let input = "item1,10.5,20,3.0,10.0";
+    let result = ItemTemplate::from_str(input);
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberOfPropertiesForItem {
+            input: input.to_string(),
+            property_count: 5
+        }
+    );
+}
+
+#[test]
+fn when_parsing_an_item_input_with_an_invalid_item_length_it_should_return_an_error() {
+    let input = "item1,abc,20,3.0";
+    let result = ItemTemplate::from_str(input);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_an_item_input_with_an_invalid_item_weight_it_should_return_an_error() {
+    let input = "item1,10.5,20,xyz";
+    let result = ItemTemplate::from_str(input);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_an_item_input_with_an_invalid_item_amount_it_should_return_an_error() {
+    let input = "item1,10.5,abc,3.0";
+    let result = ItemTemplate::from_str(input);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_an_item_input_with_tags_it_should_store_the_tags() {
+    let input = "item1,10.5,20,3.0,tags=BATTERY|COLD";
+    let item = ItemTemplate::from_str(input).expect("Failed to parse the item.");
+    assert_eq!(item.attributes.tags, vec!["BATTERY", "COLD"]);
+    assert!(item.attributes.has_tag("COLD"));
+    assert!(!item.attributes.has_tag("GLASS"));
+}
+
+#[test]
+fn when_parsing_an_item_input_with_a_value_it_should_store_the_value() {
+    let item = ItemTemplate::from_str("item1,10.5,20,3.0,value=125.50")
+        .expect("Failed to parse the item.");
+    assert_eq!(item.attributes.value, Some(125.5));
+
+    let item = ItemTemplate::from_str("item1,10.5,20,3.0").expect("Failed to parse the item.");
+    assert_eq!(item.attributes.value, None);
+}
+
+#[test]
+fn when_parsing_an_item_input_with_an_invalid_value_it_should_return_an_error() {
+    let result = ItemTemplate::from_str("item1,10.5,20,3.0,value=lots");
+    assert!(matches!(result, Err(Error::InvalidItemValue { .. })));
+}
+
+#[test]
+fn when_parsing_an_item_input_with_a_storage_class_it_should_store_the_class() {
+    let item = ItemTemplate::from_str("item1,10.5,20,3.0,storage=frozen")
+        .expect("Failed to parse the item.");
+    assert_eq!(item.attributes.storage_class, StorageClass::Frozen);
+
+    let item = ItemTemplate::from_str("item1,10.5,20,3.0").expect("Failed to parse the item.");
+    assert_eq!(item.attributes.storage_class, StorageClass::Ambient);
+}
+
+#[test]
+fn when_parsing_an_item_input_with_an_unknown_storage_class_it_should_return_an_error() {
+    let result = ItemTemplate::from_str("item1,10.5,20,3.0,storage=warm");
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidStorageClass {
+            input: "item1,10.5,20,3.0,storage=warm".to_string(),
+            property_value: "warm".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_an_item_input_with_a_destination_it_should_store_the_destination() {
+    let item = ItemTemplate::from_str("item1,10.5,20,3.0,destination=north")
+        .expect("Failed to parse the item.");
+    assert_eq!(item.attributes.destination, Some("north".to_string()));
+
+    let result = ItemTemplate::from_str("item1,10.5,20,3.0,destination=");
+    assert!(matches!(result, Err(Error::InvalidItemDestination { .. })));
+}
+
+#[test]
+fn when_parsing_an_item_input_with_a_width_it_should_store_the_width() {
+    let item =
+        ItemTemplate::from_str("item1,1200,20,3.0,width=600").expect("Failed to parse the item.");
+    assert_eq!(item.attributes.width, Some(600.0));
+
+    let result = ItemTemplate::from_str("item1,1200,20,3.0,width=0");
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidItemWidth {
+            input: "item1,1200,20,3.0,width=0".to_string(),
+            property_value: "0".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_an_item_input_with_an_unknown_attribute_it_should_return_an_error() {
+    let input = "item1,10.5,20,3.0,colour=red";
+    let result = ItemTemplate::from_str(input);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnknownItemAttribute {
+            input: input.to_string(),
+            attribute: "colour".to_string()
+        }
+    );
+}
+
+// PackTemplate
+
+#[test]
+fn when_parsing_a_valid_pack_input_string_it_should_return_a_pack_template() {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,10,20.0";
+    let result = pack.from_line(input);
+    assert!(result.is_ok());
+    assert_eq!(pack.maximum_number_of_pieces, 10);
+    assert_eq!(pack.maximum_weight, 20.0);
+    assert_eq!(pack.sort_order, PackSortOrder::Natural);
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_too_few_properties_it_should_return_an_error() {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,10";
+    let result = pack.from_line(input);
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberOfPropertiesForPacks {
+            input: input.to_string(),
+            property_count: 2
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_too_many_properties_it_should_return_an_error() {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,10,20.0,Extra";
+    let result = pack.from_line(input);
+    assert!(result.is_err());
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberOfPropertiesForPacks {
+            input: input.to_string(),
+            property_count: 4
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_an_invalid_sort_order_it_should_return_an_error() {
+    let mut pack = PackTemplate::new();
+    let input = "InvalidSortOrder,10,20.0";
+    let result = pack.from_line(input);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_an_invalid_quantity_it_should_return_an_error() {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,abc,20.0";
+    let result = pack.from_line(input);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_an_invalid_weight_it_should_return_an_error() {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,10,abc";
+    let result = pack.from_line(input);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_a_length_mode_it_should_store_the_length_mode() {
+    let mut pack = PackTemplate::new();
+    let result = pack.from_line("NATURAL,10,20.0,length=sum-with-gap 5,max_length=1000");
+    assert!(result.is_ok());
+    assert_eq!(pack.length_mode, LengthMode::SumWithGap(5.0));
+    assert_eq!(pack.maximum_length, Some(1000.0));
+
+    let mut pack = PackTemplate::new();
+    pack.from_line("NATURAL,10,20.0,length=sum")
+        .expect("Failed to parse the pack information.");
+    assert_eq!(pack.length_mode, LengthMode::Sum);
+    assert_eq!(pack.maximum_length, None);
+}
+
+#[test]
+fn when_parsing_a_pack_input_without_a_length_mode_it_should_use_the_maximum_length() {
+    let mut pack = PackTemplate::new();
+    pack.from_line("NATURAL,10,20.0")
+        .expect("Failed to parse the pack information.");
+    assert_eq!(pack.length_mode, LengthMode::Maximum);
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_an_invalid_length_mode_it_should_return_an_error() {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,10,20.0,length=sum-with-gap";
+    let result = pack.from_line(input);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidLengthMode {
+            input: input.to_string(),
+            property_value: "sum-with-gap".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_a_hard_maximum_weight_it_should_store_the_weight() {
+    let mut pack = PackTemplate::new();
+    pack.from_line("NATURAL,10,20.0,hard_max_weight=22.5")
+        .expect("Failed to parse the pack information.");
+    assert_eq!(pack.maximum_weight, 20.0);
+    assert_eq!(pack.hard_maximum_weight, Some(22.5));
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_a_hard_maximum_weight_below_the_maximum_weight_it_should_return_an_error(
+) {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,10,20.0,hard_max_weight=15";
+    let result = pack.from_line(input);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::HardMaximumWeightBelowMaximumWeight {
+            input: input.to_string(),
+            hard_maximum_weight: 15.0,
+            maximum_weight: 20.0
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_a_maximum_value_it_should_store_the_value() {
+    let mut pack = PackTemplate::new();
+    pack.from_line("NATURAL,10,20.0,max_value=2500")
+        .expect("Failed to parse the pack information.");
+    assert_eq!(pack.maximum_value, Some(2500.0));
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_a_tare_weight_it_should_store_the_weight() {
+    let mut pack = PackTemplate::new();
+    pack.from_line("NATURAL,10,20.0,tare=0.8")
+        .expect("Failed to parse the pack information.");
+    assert_eq!(pack.maximum_weight, 20.0);
+    assert_eq!(pack.tare_weight, 0.8);
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_an_invalid_tare_weight_it_should_return_an_error() {
+    for input in [
+        "NATURAL,10,20.0,tare=heavy",
+        "NATURAL,10,20.0,tare=-1",
+        "NATURAL,10,20.0,tare=20",
+    ] {
+        let mut pack = PackTemplate::new();
+        let result = pack.from_line(input);
+        assert!(
+            matches!(result, Err(Error::InvalidPackTareWeight { .. })),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_a_sheet_size_it_should_store_the_size() {
+    let mut pack = PackTemplate::new();
+    pack.from_line("NATURAL,10,20.0,sheet=3000x1500")
+        .expect("Failed to parse the pack information.");
+    assert_eq!(
+        pack.sheet,
+        Some(SheetSize {
+            length: 3000.0,
+            width: 1500.0
+        })
+    );
+
+    let input = "NATURAL,10,20.0,sheet=3000";
+    let result = PackTemplate::new().from_line(input);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidSheetSize {
+            input: input.to_string(),
+            property_value: "3000".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_dimensions_it_should_store_the_dimensions() {
+    let mut pack = PackTemplate::new();
+    pack.from_line("NATURAL,10,20.0,dimensions=1200x800x600")
+        .expect("Failed to parse the pack information.");
+    assert_eq!(
+        pack.dimensions,
+        Some(PackDimensions {
+            length: 1200.0,
+            width: 800.0,
+            height: 600.0
+        })
+    );
+
+    for value in ["1200x800", "1200x800x0", "1200xwidex600"] {
+        let input = format!("NATURAL,10,20.0,dimensions={value}");
+        let result = PackTemplate::new().from_line(&input);
+        assert_eq!(
+            result.err().unwrap(),
+            Error::InvalidPackDimensions {
+                input: input.clone(),
+                property_value: value.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn when_parsing_a_pack_input_with_an_unknown_attribute_it_should_return_an_error() {
+    let mut pack = PackTemplate::new();
+    let input = "NATURAL,10,20.0,colour=red";
+    let result = pack.from_line(input);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnknownPackAttribute {
+            input: input.to_string(),
+            attribute: "colour".to_string()
+        }
+    );
+}
+
+// parse_input()
+
+/// Returns the error without the position in the input that is added to the errors of the input lines.
+fn without_position<T>(result: Result<T, Error>) -> Result<T, Error> {
+    result.map_err(|e| match e {
+        Error::InvalidInputLine { source, .. } => *source,
+        e => e,
+    })
+}
+
+#[test]
+fn when_parsing_a_valid_input_it_should_return_the_templates() {
+    let input = "NATURAL,10,20.0\n100,10.5,20,3.0\n110,8.0,15,5.0";
+    let mut cursor = Cursor::new(input);
+    let result = parse_input(&mut cursor);
+    assert!(result.is_ok());
+
+    let (pack_template, item_templates) = result.unwrap();
+    assert_eq!(pack_template.maximum_number_of_pieces, 10);
+    assert_eq!(pack_template.maximum_weight, 20.0);
+    assert_eq!(pack_template.sort_order, PackSortOrder::Natural);
+    assert_eq!(item_templates.len(), 2);
+    assert_eq!(&*item_templates[0].id, "100");
+    assert_eq!(&*item_templates[1].id, "110");
+}
+
+#[test]
+fn when_parsing_input_with_invalid_pack_information_it_should_return_an_error() {
+    let input = "INVALID_KEYWORD,10,20.0\n100,10.5,20,3.0";
+    let mut cursor = Cursor::new(input);
+    let result = parse_input(&mut cursor);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_input_with_duplicate_pack_information_it_should_return_an_error() {
+    let input = "NATURAL,10,20.0\nNATURAL,8,15.0\n100,10.5,20,3.0";
+    let mut cursor = Cursor::new(input);
+    let result = parse_input(&mut cursor);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_input_with_invalid_item_information_it_should_return_an_error() {
+    let input = "NATURAL,10,20.0\ninvalid_item_format\n100,10.5,20,3.0";
+    let mut cursor = Cursor::new(input);
+    let result = parse_input(&mut cursor);
+    assert!(result.is_err());
+}
+
+#[test]
+fn when_parsing_input_with_a_pin_directive_it_should_pin_the_item() {
+    let input = "NATURAL,10,20.0\nPIN,110,pack=2\n100,10.5,20,3.0\n110,8.0,15,5.0";
+    let mut cursor = Cursor::new(input);
+    let (_, item_templates) = parse_input(&mut cursor).expect("Failed to parse the input.");
+    assert_eq!(item_templates.len(), 2);
+    assert_eq!(item_templates[0].attributes.pinned_pack, None);
+    assert_eq!(item_templates[1].attributes.pinned_pack, Some(2));
+}
+
+#[test]
+fn when_parsing_input_with_a_pin_directive_for_an_unknown_item_it_should_return_an_error() {
+    let input = "NATURAL,10,20.0\n100,10.5,20,3.0\nPIN,999,pack=1";
+    let mut cursor = Cursor::new(input);
+    let result = parse_input(&mut cursor);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::PinnedItemNotFound {
+            item_id: "999".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_input_with_an_invalid_pin_directive_it_should_return_an_error() {
+    for directive in [
+        "PIN,100",
+        "PIN,100,pack=0",
+        "PIN,100,pack=first",
+        "PIN,100,2",
+    ] {
+        let input = format!("NATURAL,10,20.0\n100,10.5,20,3.0\n{}", directive);
+        let mut cursor = Cursor::new(input);
+        let result = without_position(parse_input(&mut cursor));
+        assert_eq!(
+            result.err().unwrap(),
+            Error::InvalidPinDirective {
+                input: directive.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn when_parsing_input_with_a_close_directive_it_should_mark_the_next_item() {
+    let input =
+        "NATURAL,10,20.0\n100,10.5,2,3.0\n---CLOSE---\n110,8.0,1,5.0\n120,8.0,1,5.0\n---CLOSE---";
+    let mut cursor = Cursor::new(input);
+    let (_, item_templates) = parse_input(&mut cursor).expect("Failed to parse the input.");
+    assert_eq!(item_templates.len(), 3);
+    assert!(!item_templates[0].attributes.close_pack_before);
+    assert!(item_templates[1].attributes.close_pack_before);
+    assert!(!item_templates[2].attributes.close_pack_before);
+}
+
+// parse_input_sections()
+
+#[test]
+fn when_parsing_input_with_several_sections_it_should_use_the_first_pack_information_as_the_defaults(
+) {
+    let input = "NATURAL,10,20.0,max_length=1000\n100,10.5,20,3.0\nPIN,100,pack=2\n\
+                 LONG_TO_SHORT,8,15.0,length=sum\n200,8.0,15,5.0\n210,9.0,5,5.0";
+    let sections =
+        parse_input_sections(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(sections.len(), 2);
+
+    assert_eq!(sections[0].pack_template.sort_order, PackSortOrder::Natural);
+    assert_eq!(sections[0].pack_template.length_mode, LengthMode::Maximum);
+    assert_eq!(sections[0].items.len(), 1);
+    assert_eq!(sections[0].items[0].attributes.pinned_pack, Some(2));
+
+    assert_eq!(
+        sections[1].pack_template.sort_order,
+        PackSortOrder::LongToShort
+    );
+    assert_eq!(sections[1].pack_template.maximum_number_of_pieces, 8);
+    assert_eq!(sections[1].pack_template.maximum_weight, 15.0);
+    assert_eq!(sections[1].pack_template.length_mode, LengthMode::Sum);
+    assert_eq!(sections[1].pack_template.maximum_length, Some(1000.0));
+    assert_eq!(sections[1].items.len(), 2);
+}
+
+#[test]
+fn when_parsing_a_section_that_inherits_a_hard_maximum_weight_below_its_maximum_weight_it_should_return_an_error(
+) {
+    let input =
+        "NATURAL,10,20.0,hard_max_weight=25.0\n100,10.5,20,3.0\nNATURAL,10,30.0\n200,8.0,15,5.0";
+    let result = without_position(parse_input_sections(&mut Cursor::new(input)));
+    assert_eq!(
+        result.err().unwrap(),
+        Error::HardMaximumWeightBelowMaximumWeight {
+            input: "NATURAL,10,30.0".to_string(),
+            hard_maximum_weight: 25.0,
+            maximum_weight: 30.0,
+        }
+    );
+}
+
+// parse_input() with a version directive
+
+#[test]
+fn when_parsing_an_input_with_a_version_directive_it_should_use_that_version() {
+    let input = "#VERSION 2\nNATURAL,10,20.0,max_length=1000\n100,10.5,20,3.0,tags=COLD";
+    let (pack_template, item_templates) =
+        parse_input(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(pack_template.maximum_length, Some(1000.0));
+    assert!(item_templates[0].attributes.has_tag("COLD"));
+}
+
+#[test]
+fn when_parsing_a_version_1_input_it_should_return_the_templates() {
+    let input = "#VERSION 1\nNATURAL,10,20.0\n100,10.5,20,3.0";
+    let (pack_template, item_templates) =
+        parse_input(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(pack_template.maximum_number_of_pieces, 10);
+    assert_eq!(item_templates.len(), 1);
+}
+
+#[test]
+fn when_parsing_a_version_1_input_with_newer_features_it_should_return_an_error() {
+    let input = "#VERSION 1\nNATURAL,10,20.0\n100,10.5,20,3.0,tags=COLD";
+    assert_eq!(
+        without_position(parse_input(&mut Cursor::new(input))).err(),
+        Some(Error::AttributesRequireVersion2 {
+            input: "100,10.5,20,3.0,tags=COLD".to_string()
+        })
+    );
+
+    let input = "#VERSION 1\nNATURAL,10,20.0,max_length=1000\n100,10.5,20,3.0";
+    assert_eq!(
+        without_position(parse_input(&mut Cursor::new(input))).err(),
+        Some(Error::AttributesRequireVersion2 {
+            input: "NATURAL,10,20.0,max_length=1000".to_string()
+        })
+    );
+
+    let input = "#VERSION 1\nNATURAL,10,20.0\n100,10.5,20";
+    assert!(matches!(
+        without_position(parse_input(&mut Cursor::new(input))),
+        Err(Error::InvalidNumberOfPropertiesForItem { .. })
+    ));
+
+    let input = "#VERSION 1\nNATURAL,10,20.0\n100,10.5,2 BOX(10),3.0";
+    assert!(matches!(
+        without_position(parse_input(&mut Cursor::new(input))),
+        Err(Error::InvalidItemUnit { .. })
+    ));
+
+    let input = "#VERSION 1\nNATURAL,10,20.0\n---CLOSE---\n100,10.5,20,3.0";
+    assert!(matches!(
+        without_position(parse_input(&mut Cursor::new(input))),
+        Err(Error::InputStringShouldStartWithNumberOrKeyWord { .. })
+    ));
+}
+
+#[test]
+fn when_parsing_an_input_with_an_unsupported_version_it_should_return_an_error() {
+    let input = "#VERSION 3\nNATURAL,10,20.0\n100,10.5,20,3.0";
+    assert_eq!(
+        without_position(parse_input(&mut Cursor::new(input)))
+            .err()
+            .unwrap(),
+        Error::UnsupportedInputVersion { version: 3 }
+    );
+}
+
+#[test]
+fn when_parsing_an_input_with_an_invalid_version_directive_it_should_return_an_error() {
+    for input in [
+        "#VERSION two\nNATURAL,10,20.0",
+        "#VERSION2\nNATURAL,10,20.0",
+        "NATURAL,10,20.0\n#VERSION 2",
+    ] {
+        assert!(matches!(
+            without_position(parse_input(&mut Cursor::new(input))),
+            Err(Error::InvalidVersionDirective { .. })
+        ));
+    }
+}
+
+// parse_input() with a locale directive
+
+#[test]
+fn when_parsing_an_input_with_a_decimal_comma_locale_it_should_read_the_numbers_in_that_locale() {
+    let input = "#LOCALE de-DE\nNATURAL,40,\"500,5\"\n100,6.200,1.200,\"9,5\"";
+    let (pack_template, item_templates) =
+        parse_input(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(pack_template.maximum_weight, 500.5);
+    assert_eq!(item_templates[0].length, 6200.0);
+    assert_eq!(item_templates[0].count, 1200);
+    assert_eq!(item_templates[0].weight, 9.5);
+}
+
+#[test]
+fn when_parsing_an_input_with_a_locale_after_the_version_it_should_apply_to_every_section() {
+    let input = "#VERSION 2\n#LOCALE en_us\nNATURAL,40,500\n100,\"6,200\",10,1.5\nLONG_TO_SHORT,20,250\n200,\"7,200\",5,2.5";
+    let sections =
+        parse_input_sections(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(sections[0].items[0].length, 6200.0);
+    assert_eq!(sections[1].items[0].length, 7200.0);
+}
+
+#[test]
+fn when_parsing_an_input_with_a_locale_and_a_thousands_separator_it_should_use_the_separator() {
+    let input = "#LOCALE de-DE\nNATURAL,40,500,thousands_separator=none\n100,6200,10,1.5";
+    let (_, item_templates) =
+        parse_input(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(item_templates[0].weight, 1.5);
+}
+
+#[test]
+fn when_parsing_an_input_with_an_unknown_locale_it_should_return_an_error() {
+    let input = "#LOCALE xx-XX\nNATURAL,40,500\n100,6200,10,1.5";
+    assert_eq!(
+        without_position(parse_input(&mut Cursor::new(input)))
+            .err()
+            .unwrap(),
+        Error::UnknownLocale {
+            input: "#LOCALE xx-XX".to_string(),
+            locale: "xx-XX".to_string(),
+        }
+    );
+}
+
+#[test]
+fn when_parsing_an_input_with_an_invalid_locale_directive_it_should_return_an_error() {
+    for input in [
+        "#LOCALE\nNATURAL,40,500",
+        "#LOCALEde-DE\nNATURAL,40,500",
+        "NATURAL,40,500\n#LOCALE de-DE",
+        "#VERSION 1\n#LOCALE de-DE\nNATURAL,40,500",
+    ] {
+        assert!(
+            matches!(
+                without_position(parse_input(&mut Cursor::new(input))),
+                Err(Error::InvalidLocaleDirective { .. })
+            ),
+            "{input}"
+        );
+    }
+}
+
+// Units of measure
+
+#[test]
+fn when_parsing_an_item_with_a_quantity_in_a_higher_unit_it_should_expand_to_eaches() {
+    let item =
+        ItemTemplate::from_str("item1,100,3 BOX(12),6.0").expect("Failed to parse the item.");
+    assert_eq!(item.count, 36);
+    assert_eq!(item.weight, 0.5);
+    assert_eq!(item.length, 100.0);
+}
+
+#[test]
+fn when_parsing_an_item_with_a_value_in_a_higher_unit_it_should_store_the_value_of_an_each() {
+    let item = ItemTemplate::from_str("item1,100,3 BOX(12),6.0,value=120")
+        .expect("Failed to parse the item.");
+    assert_eq!(item.attributes.value, Some(10.0));
+}
+
+#[test]
+fn when_parsing_an_item_with_a_unit_without_a_space_it_should_expand_to_eaches() {
+    let item =
+        ItemTemplate::from_str("item1,100,2PALLET(40),80").expect("Failed to parse the item.");
+    assert_eq!(item.count, 80);
+    assert_eq!(item.weight, 2.0);
+}
+
+#[test]
+fn when_parsing_an_item_with_an_invalid_unit_it_should_return_an_error() {
+    for quantity in ["3 BOX()", "3 BOX(0)", "3 BOX(twelve)", "(12)"] {
+        let input = format!("item1,100,{},6.0", quantity);
+        let result = ItemTemplate::from_str(&input);
+        assert_eq!(
+            result.err().unwrap(),
+            Error::InvalidItemUnit {
+                input: input.clone(),
+                property_value: quantity.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn when_parsing_an_item_with_too_many_eaches_it_should_return_a_range_error() {
+    let result = ItemTemplate::from_str("item1,100,2000000000 BOX(12),6.0");
+    assert!(matches!(result, Err(Error::NumberOutOfRange { .. })));
+}
+
+#[test]
+fn when_parsing_an_item_with_an_ordered_quantity_it_should_store_the_ordered_eaches() {
+    let item =
+        ItemTemplate::from_str("item1,100,7,6.0,ordered=10").expect("Failed to parse the item.");
+    assert_eq!(item.count, 7);
+    assert_eq!(item.attributes.ordered, Some(10));
+
+    let item = ItemTemplate::from_str("item1,100,2 BOX(12),6.0,ordered=3")
+        .expect("Failed to parse the item.");
+    assert_eq!(item.count, 24);
+    assert_eq!(item.attributes.ordered, Some(36));
+}
+
+#[test]
+fn when_parsing_an_item_with_an_invalid_ordered_quantity_it_should_return_an_error() {
+    for input in [
+        "item1,100,7,6.0,ordered=ten",
+        "item1,100,7,6.0,ordered=-1",
+        "item1,100,7,6.0,ordered=5",
+        "item1,-,TOTAL_KG=10,2.0/kg,ordered=12",
+    ] {
+        let result = ItemTemplate::from_str(input);
+        assert!(
+            matches!(result, Err(Error::InvalidOrderedQuantity { .. })),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_an_item_with_a_pack_multiple_it_should_store_the_multiple_in_eaches() {
+    let item =
+        ItemTemplate::from_str("item1,100,12,6.0,multiple=6").expect("Failed to parse the item.");
+    assert_eq!(item.attributes.multiple, Some(6));
+
+    let item = ItemTemplate::from_str("item1,100,4 BOX(12),6.0,multiple=2")
+        .expect("Failed to parse the item.");
+    assert_eq!(item.attributes.multiple, Some(24));
+}
+
+#[test]
+fn when_parsing_an_item_with_an_invalid_pack_multiple_it_should_return_an_error() {
+    for input in [
+        "item1,100,12,6.0,multiple=six",
+        "item1,100,12,6.0,multiple=0",
+        "item1,100,12,6.0,multiple=-6",
+        "item1,-,TOTAL_KG=10,2.0/kg,multiple=6",
+    ] {
+        let result = ItemTemplate::from_str(input);
+        assert!(
+            matches!(result, Err(Error::InvalidItemMultiple { .. })),
+            "{input}"
+        );
+    }
+}
+
+// Bulk items
+
+#[test]
+fn when_parsing_a_bulk_item_it_should_store_the_total_weight() {
+    let item = ItemTemplate::from_str("3001,-,TOTAL_KG=250,1.0/kg,value=2.5")
+        .expect("Failed to parse the item.");
+    assert!(item.attributes.bulk);
+    assert_eq!(item.count, 1);
+    assert_eq!(item.length, 0.0);
+    assert_eq!(item.weight, 250.0);
+    assert_eq!(item.item_value(), Some(625.0));
+}
+
+#[test]
+fn when_parsing_a_bulk_item_with_an_invalid_quantity_it_should_return_an_error() {
+    for line in [
+        "3001,-,250,1.0/kg",
+        "3001,-,TOTAL_KG=0,1.0/kg",
+        "3001,-,TOTAL_KG=250,1.0",
+        "3001,-,TOTAL_KG=abc,1.0/kg",
+    ] {
+        assert!(
+            matches!(
+                ItemTemplate::from_str(line),
+                Err(Error::InvalidBulkItem { .. })
+            ),
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_a_cut_item_it_should_store_the_total_length_and_the_weight_per_length() {
+    let item = ItemTemplate::from_str("4001,1000,TOTAL_LENGTH=25500,0.002/length,value=0.01")
+        .expect("Failed to parse the item.");
+    assert_eq!(
+        item.attributes.cut,
+        Some(CutItem {
+            weight_per_length: 0.002,
+            increment: Some(1000.0),
+        })
+    );
+    assert_eq!(item.count, 1);
+    assert_eq!(item.length, 25500.0);
+    assert_eq!(item.weight, 51.0);
+    assert_eq!(item.item_value(), Some(255.0));
+
+    let item = ItemTemplate::from_str("4001,-,TOTAL_LENGTH=25500,0.002/length").unwrap();
+    assert_eq!(item.attributes.cut.unwrap().increment, None);
+}
+
+#[test]
+fn when_parsing_a_cut_item_with_an_invalid_length_quantity_or_weight_it_should_return_an_error() {
+    for line in [
+        "4001,0,TOTAL_LENGTH=25500,0.002/length",
+        "4001,-,TOTAL_LENGTH=0,0.002/length",
+        "4001,-,TOTAL_LENGTH=25500,0.002",
+        "4001,-,TOTAL_LENGTH=abc,0.002/length",
+        "4001,-,TOTAL_LENGTH=25500,0.002/length,multiple=2",
+    ] {
+        assert!(
+            matches!(
+                ItemTemplate::from_str(line),
+                Err(Error::InvalidCutItem { .. } | Error::InvalidItemMultiple { .. })
+            ),
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_a_version_1_input_with_a_bulk_item_it_should_return_an_error() {
+    let input = "#VERSION 1\nNATURAL,10,20.0\n3001,-,TOTAL_KG=250,1.0/kg";
+    assert!(matches!(
+        without_position(parse_input(&mut Cursor::new(input))),
+        Err(Error::InvalidBulkItem { .. })
+    ));
+}
+
+// Kits
+
+#[test]
+fn when_parsing_a_kit_line_it_should_return_an_item_that_refers_to_the_kit() {
+    let input = "NATURAL,10,20.0\nKIT:gift-set-1,3";
+    let (_, items) = parse_input(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(items.len(), 1);
+    assert!(items[0].attributes.kit);
+    assert_eq!(&*items[0].id, "gift-set-1");
+    assert_eq!(items[0].count, 3);
+}
+
+#[test]
+fn when_parsing_an_invalid_kit_line_it_should_return_an_error() {
+    for line in ["KIT:gift-set-1", "KIT:,3", "KIT:gift-set-1,3,4"] {
+        let input = format!("NATURAL,10,20.0\n{line}");
+        assert!(
+            matches!(
+                without_position(parse_input(&mut Cursor::new(input))),
+                Err(Error::InvalidKitLine { .. })
+            ),
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_a_version_1_input_with_a_kit_line_it_should_return_an_error() {
+    let input = "#VERSION 1\nNATURAL,10,20.0\nKIT:gift-set-1,3";
+    assert!(matches!(
+        without_position(parse_input(&mut Cursor::new(input))),
+        Err(Error::InputStringShouldStartWithNumberOrKeyWord { .. })
+    ));
+}
+
+// parse_bill_of_materials()
+
+#[test]
+fn when_parsing_a_bill_of_materials_it_should_group_the_components_by_kit() {
+    let input = "# Gift sets\ngift-set-1,1001,200,2,0.5\ngift-set-1,1002,300,1,1.5\n\ngift-set-2,1001,200,4,0.5";
+    let bill_of_materials = parse_bill_of_materials(&mut Cursor::new(input))
+        .expect("Failed to parse the bill of materials.");
+    assert_eq!(bill_of_materials.kits.len(), 2);
+
+    let components = &bill_of_materials.kits["gift-set-1"];
+    assert_eq!(components.len(), 2);
+    assert_eq!(&*components[0].id, "1001");
+    assert_eq!(components[0].count, 2);
+    assert_eq!(&*components[1].id, "1002");
+}
+
+#[test]
+fn when_parsing_a_bill_of_materials_with_an_invalid_line_it_should_return_an_error() {
+    for line in [
+        "gift-set-1",
+        ",1001,200,2,0.5",
+        "gift-set-1,3001,-,TOTAL_KG=250,1.0/kg",
+    ] {
+        assert!(
+            matches!(
+                parse_bill_of_materials(&mut Cursor::new(line)),
+                Err(Error::InvalidBillOfMaterialsLine { .. })
+            ),
+            "{line}"
+        );
+    }
+}
+
+// Substitution groups
+
+#[test]
+fn when_parsing_a_group_line_it_should_return_an_item_that_refers_to_the_group() {
+    let input = "NATURAL,10,20.0\nGROUP:mug-any-color,12";
+    let (_, items) = parse_input(&mut Cursor::new(input)).expect("Failed to parse the input.");
+    assert_eq!(items.len(), 1);
+    assert!(items[0].attributes.substitution_group);
+    assert!(!items[0].attributes.kit);
+    assert_eq!(&*items[0].id, "mug-any-color");
+    assert_eq!(items[0].count, 12);
+}
+
+#[test]
+fn when_parsing_an_invalid_group_line_it_should_return_an_error() {
+    for line in ["GROUP:mug-any-color", "GROUP:,3", "GROUP:mug-any-color,3,4"] {
+        let input = format!("NATURAL,10,20.0\n{line}");
+        assert!(
+            matches!(
+                without_position(parse_input(&mut Cursor::new(input))),
+                Err(Error::InvalidSubstitutionGroupLine { .. })
+            ),
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_substitution_groups_it_should_keep_the_members_in_order() {
+    let input =
+        "# Prefer the red mugs\nmug-any-color,2001,120,8,0.4\n\nmug-any-color,2002,120,20,0.4";
+    let substitution_groups = parse_substitution_groups(&mut Cursor::new(input))
+        .expect("Failed to parse the substitution groups.");
+    assert_eq!(substitution_groups.groups.len(), 1);
+
+    let members = &substitution_groups.groups["mug-any-color"];
+    let members: Vec<(&str, i32)> = members.iter().map(|m| (&*m.id, m.count)).collect();
+    assert_eq!(members, vec![("2001", 8), ("2002", 20)]);
+}
+
+#[test]
+fn when_parsing_substitution_groups_with_an_invalid_line_it_should_return_an_error() {
+    for line in [
+        "mug-any-color",
+        ",2001,120,8,0.4",
+        "mug-any-color,3001,-,TOTAL_KG=250,1.0/kg",
+    ] {
+        assert!(
+            matches!(
+                parse_substitution_groups(&mut Cursor::new(line)),
+                Err(Error::InvalidSubstitutionLine { .. })
+            ),
+            "{line}"
+        );
+    }
+}
+
+// Number formats
+
+#[test]
+fn when_parsing_an_item_in_scientific_notation_it_should_return_the_values() {
+    let item =
+        ItemTemplate::from_str("item1,1.5e3,1.2e3,2.5E-1").expect("Failed to parse the item.");
+    assert_eq!(item.length, 1500.0);
+    assert_eq!(item.count, 1200);
+    assert_eq!(item.weight, 0.25);
+}
+
+#[test]
+fn when_parsing_an_item_with_a_fractional_count_in_scientific_notation_it_should_return_an_error() {
+    let result = ItemTemplate::from_str("item1,100,1.25e1,2.5");
+    assert!(matches!(result, Err(Error::InvalidItemCount { .. })));
+}
+
+#[test]
+fn when_parsing_input_with_a_comma_thousands_separator_it_should_accept_quoted_numbers() {
+    let input =
+        "NATURAL,\"1,000\",\"2,500.5\",thousands_separator=comma\n100,\"6,200\",\"1,200\",9.5";
+    let mut cursor = Cursor::new(input);
+    let (pack_template, item_templates) =
+        parse_input(&mut cursor).expect("Failed to parse the input.");
+    assert_eq!(pack_template.maximum_number_of_pieces, 1000);
+    assert_eq!(pack_template.maximum_weight, 2500.5);
+    assert_eq!(item_templates[0].length, 6200.0);
+    assert_eq!(item_templates[0].count, 1200);
+}
+
+#[test]
+fn when_parsing_input_with_a_period_thousands_separator_it_should_use_a_decimal_comma() {
+    let input = "NATURAL,40,500,thousands_separator=period\n100,6.200,1.200,\"9,5\"";
+    let mut cursor = Cursor::new(input);
+    let (_, item_templates) = parse_input(&mut cursor).expect("Failed to parse the input.");
+    assert_eq!(item_templates[0].length, 6200.0);
+    assert_eq!(item_templates[0].count, 1200);
+    assert_eq!(item_templates[0].weight, 9.5);
+}
+
+#[test]
+fn when_parsing_input_with_incorrectly_grouped_thousands_it_should_return_a_format_error() {
+    let input = "NATURAL,40,500,thousands_separator=apostrophe\n100,6200,1'20,9.5";
+    let mut cursor = Cursor::new(input);
+    let result = without_position(parse_input(&mut cursor));
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberFormat {
+            input: "100,6200,1'20,9.5".to_string(),
+            property_value: "1'20".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_input_with_scientific_notation_disabled_it_should_return_a_format_error() {
+    let input = "NATURAL,40,500,scientific_notation=false\n100,6200,10,1e1";
+    let mut cursor = Cursor::new(input);
+    let result = without_position(parse_input(&mut cursor));
+    assert!(matches!(result, Err(Error::InvalidNumberFormat { .. })));
+}
+
+#[test]
+fn when_parsing_an_item_with_a_count_that_is_too_large_it_should_return_a_range_error() {
+    for count in ["3000000000", "3e9"] {
+        let input = format!("item1,100,{},2.5", count);
+        let result = ItemTemplate::from_str(&input);
+        assert_eq!(
+            result.err().unwrap(),
+            Error::NumberOutOfRange {
+                input: input.clone(),
+                property_value: count.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn when_parsing_an_item_with_a_weight_that_is_too_large_it_should_return_a_range_error() {
+    let result = ItemTemplate::from_str("item1,100,10,1e999");
+    assert!(matches!(result, Err(Error::NumberOutOfRange { .. })));
+}
+
+#[test]
+fn when_parsing_an_item_with_a_number_that_is_not_finite_it_should_return_an_error() {
+    for line in [
+        "item1,NaN,10,1.5",
+        "item1,100,10,inf",
+        "item1,100,10,-Infinity",
+        "item1,100,10,1.5,value=nan",
+    ] {
+        assert!(
+            matches!(
+                ItemTemplate::from_str(line),
+                Err(Error::NonFiniteNumber { .. })
+            ),
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_pack_information_with_a_number_that_is_not_finite_it_should_return_an_error() {
+    let input = "NATURAL,40,inf\n100,6200,10,1.5";
+    let result = without_position(parse_input(&mut Cursor::new(input)));
+    assert_eq!(
+        result.err().unwrap(),
+        Error::NonFiniteNumber {
+            input: "NATURAL,40,inf".to_string(),
+            property_value: "inf".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_pack_information_with_an_unknown_thousands_separator_it_should_return_an_error() {
+    let input = "NATURAL,40,500,thousands_separator=dot\n100,6200,10,1.5";
+    let mut cursor = Cursor::new(input);
+    let result = without_position(parse_input(&mut cursor));
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberFormatAttribute {
+            input: "NATURAL,40,500,thousands_separator=dot".to_string(),
+            attribute: "thousands_separator".to_string(),
+            property_value: "dot".to_string()
+        }
+    );
+}
+
+// WeightBand
+
+#[test]
+fn when_parsing_a_valid_weight_band_it_should_return_the_band() {
+    let band = WeightBand::from_str("SMALL,0,5,4.50").expect("Failed to parse the weight band.");
+    assert_eq!(band.name, "SMALL");
+    assert_eq!(band.minimum_weight, 0.0);
+    assert_eq!(band.maximum_weight, 5.0);
+    assert_eq!(band.cost, 4.5);
+}
+
+#[test]
+fn when_parsing_a_weight_band_with_too_few_properties_it_should_return_an_error() {
+    let input = "SMALL,0,5";
+    let result = WeightBand::from_str(input);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberOfPropertiesForWeightBand {
+            input: input.to_string(),
+            property_count: 3
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_weight_band_with_an_invalid_cost_it_should_return_an_error() {
+    let result = WeightBand::from_str("SMALL,0,5,abc");
+    assert!(matches!(result, Err(Error::InvalidWeightBandCost { .. })));
+}
+
+#[test]
+fn when_parsing_weight_bands_it_should_skip_comments_and_empty_lines() {
+    let input = "# name,min,max,cost\nSMALL,0,5,4.50\n\nMEDIUM,5,20,9.75\n";
+    let mut cursor = Cursor::new(input);
+    let bands = parse_weight_bands(&mut cursor).expect("Failed to parse the weight bands.");
+    assert_eq!(bands.len(), 2);
+    assert_eq!(bands[1].name, "MEDIUM");
+}
+
+// Carton
+
+#[test]
+fn when_parsing_a_valid_carton_it_should_return_the_carton() {
+    let carton = Carton::from_str("SMALL,600,5").expect("Failed to parse the carton.");
+    assert_eq!(carton.name, "SMALL");
+    assert_eq!(carton.maximum_length, 600.0);
+    assert_eq!(carton.maximum_weight, 5.0);
+}
+
+#[test]
+fn when_parsing_a_carton_with_an_invalid_limit_it_should_return_an_error() {
+    let result = Carton::from_str("SMALL,long,5");
+    assert!(matches!(result, Err(Error::InvalidCartonLimit { .. })));
+
+    let result = Carton::from_str("SMALL,600");
+    assert!(matches!(
+        result,
+        Err(Error::InvalidNumberOfPropertiesForCarton {
+            property_count: 2,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn when_parsing_cartons_it_should_keep_the_order_of_the_file() {
+    let input = "# name,length,weight\nSMALL,600,5\n\nLARGE,1200,20\n";
+    let mut cursor = Cursor::new(input);
+    let cartons = parse_cartons(&mut cursor).expect("Failed to parse the cartons.");
+    assert_eq!(cartons.len(), 2);
+    assert_eq!(cartons[0].name, "SMALL");
+    assert_eq!(cartons[1].name, "LARGE");
+}
+
+// Grade
+
+#[test]
+fn when_parsing_grades_it_should_return_the_grades_with_a_fraction_of_the_capacity() {
+    let grades = parse_grades("A=90, B = 70,C=0").expect("Failed to parse the grades.");
+    assert_eq!(
+        grades,
+        vec![
+            Grade {
+                name: "A".to_string(),
+                minimum_utilization: 0.9
+            },
+            Grade {
+                name: "B".to_string(),
+                minimum_utilization: 0.7
+            },
+            Grade {
+                name: "C".to_string(),
+                minimum_utilization: 0.0
+            },
+        ]
+    );
+}
+
+#[test]
+fn when_parsing_an_invalid_grade_it_should_return_an_error() {
+    for input in ["A", "=90", "A=high", "A=120", "A=-5"] {
+        assert_eq!(
+            Grade::from_str(input).err().unwrap(),
+            Error::InvalidGrade {
+                input: input.to_string()
+            }
+        );
+    }
+}
+
+// PackRule
+
+#[test]
+fn when_parsing_a_maximum_items_with_tag_rule_it_should_return_the_rule() {
+    let rule =
+        PackRule::from_str("MAX_ITEMS_WITH_TAG,BATTERY,1").expect("Failed to parse the rule.");
+    assert_eq!(
+        rule,
+        PackRule::MaximumItemsWithTag {
+            tag: "BATTERY".to_string(),
+            maximum: 1
+        }
+    );
+}
+
+#[test]
+fn when_parsing_an_exclusive_tag_rule_it_should_return_the_rule() {
+    let rule = PackRule::from_str("EXCLUSIVE_TAG, COLD").expect("Failed to parse the rule.");
+    assert_eq!(
+        rule,
+        PackRule::ExclusiveTag {
+            tag: "COLD".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_maximum_weight_with_tag_rule_it_should_return_the_rule() {
+    let rule =
+        PackRule::from_str("MAX_WEIGHT_WITH_TAG, GLASS, 10.5").expect("Failed to parse the rule.");
+    assert_eq!(
+        rule,
+        PackRule::MaximumWeightWithTag {
+            tag: "GLASS".to_string(),
+            maximum_weight: 10.5
+        }
+    );
+}
+
+#[test]
+fn when_parsing_a_maximum_weight_with_tag_rule_with_an_invalid_weight_it_should_return_an_error() {
+    let result = PackRule::from_str("MAX_WEIGHT_WITH_TAG,GLASS,heavy");
+    assert!(matches!(result, Err(Error::InvalidPackRuleWeight { .. })));
+}
+
+#[test]
+fn when_parsing_a_maximum_heavy_items_rule_it_should_return_the_rule() {
+    let rule = PackRule::from_str("MAX_HEAVY_ITEMS, 25.5, 2").expect("Failed to parse the rule.");
+    assert_eq!(
+        rule,
+        PackRule::MaximumHeavyItems {
+            threshold: 25.5,
+            maximum: 2
+        }
+    );
+
+    let result = PackRule::from_str("MAX_HEAVY_ITEMS,heavy,2");
+    assert!(matches!(result, Err(Error::InvalidPackRuleWeight { .. })));
+}
+
+#[test]
+fn when_parsing_an_unknown_rule_it_should_return_an_error() {
+    let result = PackRule::from_str("MIN_ITEMS_WITH_TAG,COLD,1");
+    assert!(matches!(result, Err(Error::UnknownPackRule { .. })));
+}
+
+#[test]
+fn when_parsing_a_rule_with_too_few_properties_it_should_return_an_error() {
+    let input = "MAX_ITEMS_WITH_TAG,BATTERY";
+    let result = PackRule::from_str(input);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidNumberOfPropertiesForPackRule {
+            input: input.to_string(),
+            expected_count: 3,
+            property_count: 2
+        }
+    );
+}
+
+#[test]
+fn when_parsing_pack_rules_it_should_skip_comments_and_empty_lines() {
+    let input = "# Compliance rules\nMAX_ITEMS_WITH_TAG,BATTERY,1\n\nEXCLUSIVE_TAG,COLD\n";
+    let mut cursor = Cursor::new(input);
+    let rules = parse_pack_rules(&mut cursor).expect("Failed to parse the rules.");
+    assert_eq!(rules.len(), 2);
+}
+
+// NestingRule
+
+#[test]
+fn when_parsing_nesting_rules_it_should_return_the_rules() {
+    let input = "# Rods fit in tubes\nROD-12, TUBE-40\n\nROD-8,TUBE-20";
+    let rules = parse_nesting_rules(&mut Cursor::new(input)).expect("Failed to parse the rules.");
+    assert_eq!(
+        rules,
+        vec![
+            NestingRule {
+                inner_item_id: "ROD-12".to_string(),
+                outer_item_id: "TUBE-40".to_string(),
+            },
+            NestingRule {
+                inner_item_id: "ROD-8".to_string(),
+                outer_item_id: "TUBE-20".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn when_parsing_an_invalid_nesting_rule_it_should_return_an_error() {
+    for line in [
+        "ROD-12",
+        "ROD-12,",
+        "ROD-12,TUBE-40,TUBE-20",
+        "ROD-12,ROD-12",
+    ] {
+        assert_eq!(
+            NestingRule::from_str(line),
+            Err(Error::InvalidNestingRule {
+                input: line.to_string()
+            }),
+            "{line}"
+        );
+    }
+}
+
+// ItemFilter and ItemMap
+
+#[test]
+fn when_parsing_a_filter_it_should_return_the_filter() {
+    assert_eq!(
+        ItemFilter::from_str("weight < 30"),
+        Ok(ItemFilter {
+            field: ItemField::Weight,
+            comparison: Comparison::Less,
+            value: 30.0
+        })
+    );
+    assert_eq!(
+        ItemFilter::from_str("length>=1200.5"),
+        Ok(ItemFilter {
+            field: ItemField::Length,
+            comparison: Comparison::GreaterOrEqual,
+            value: 1200.5
+        })
+    );
+}
+
+#[test]
+fn when_parsing_an_invalid_filter_it_should_return_an_error() {
+    for expression in [
+        "weight",
+        "weight < ",
+        "height < 3",
+        "weight =< 3",
+        "weight < x",
+        "weight < inf",
+    ] {
+        assert_eq!(
+            ItemFilter::from_str(expression),
+            Err(Error::InvalidItemFilter {
+                input: expression.to_string()
+            }),
+            "{expression}"
+        );
+    }
+}
+
+#[test]
+fn when_parsing_a_map_it_should_return_the_map() {
+    assert_eq!(
+        ItemMap::from_str("count *= 2"),
+        Ok(ItemMap {
+            field: ItemField::Count,
+            operation: MapOperation::Multiply,
+            value: 2.0
+        })
+    );
+    assert_eq!(
+        ItemMap::from_str("weight=-1.5"),
+        Ok(ItemMap {
+            field: ItemField::Weight,
+            operation: MapOperation::Assign,
+            value: -1.5
+        })
+    );
+}
+
+#[test]
+fn when_parsing_an_invalid_map_it_should_return_an_error() {
+    for expression in ["count", "count *=", "count < 2", "height = 3", "count /= 0"] {
+        assert_eq!(
+            ItemMap::from_str(expression),
+            Err(Error::InvalidItemMap {
+                input: expression.to_string()
+            }),
+            "{expression}"
+        );
+    }
+}
+
+// DeliveryStop
+
+#[test]
+fn when_parsing_delivery_stops_it_should_return_the_stops() {
+    let input = "# The route of monday\nnorth,1\n\nsouth, 2";
+    let stops = parse_delivery_stops(&mut Cursor::new(input)).expect("Failed to parse the stops.");
+    assert_eq!(
+        stops,
+        vec![
+            DeliveryStop {
+                destination: "north".to_string(),
+                stop: 1,
+            },
+            DeliveryStop {
+                destination: "south".to_string(),
+                stop: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn when_parsing_an_invalid_delivery_stop_it_should_return_an_error() {
+    for input in ["north", "north,0", "north,first", ",1", "north,1\nnorth,2"] {
+        assert!(
+            matches!(
+                parse_delivery_stops(&mut Cursor::new(input)),
+                Err(Error::InvalidDeliveryStop { .. })
+            ),
+            "{input}"
+        );
+    }
+}
+
+// DestinationLimits
+
+#[test]
+fn when_parsing_destination_limits_it_should_return_the_limits() {
+    let input = "# The daily pickup caps\nnorth,100,-\n\nsouth, -, 2500.5";
+    let limits =
+        parse_destination_limits(&mut Cursor::new(input)).expect("Failed to parse the limits.");
+    assert_eq!(
+        limits,
+        vec![
+            DestinationLimits {
+                destination: "north".to_string(),
+                maximum_number_of_pieces: Some(100),
+                maximum_weight: None,
+            },
+            DestinationLimits {
+                destination: "south".to_string(),
+                maximum_number_of_pieces: None,
+                maximum_weight: Some(2500.5),
+            },
+        ]
+    );
+}
+
+#[test]
+fn when_parsing_invalid_destination_limits_it_should_return_an_error() {
+    for input in [
+        "north",
+        "north,10",
+        ",10,-",
+        "north,-1,-",
+        "north,10,heavy",
+        "north,10,-\nnorth,20,-",
+    ] {
+        assert!(
+            matches!(
+                parse_destination_limits(&mut Cursor::new(input)),
+                Err(Error::InvalidDestinationLimits { .. })
+            ),
+            "{input}"
+        );
+    }
+}
+
+// ClassLimits
+
+#[test]
+fn when_parsing_class_limits_it_should_return_the_limits() {
+    let input = "# Fragile goods\nGLASS,10,-\n\nCERAMIC, -, 25.5";
+    let class_limits =
+        parse_class_limits(&mut Cursor::new(input)).expect("Failed to parse the class limits.");
+    assert_eq!(
+        class_limits,
+        vec![
+            ClassLimits {
+                class: "GLASS".to_string(),
+                maximum_number_of_pieces: Some(10),
+                maximum_weight: None,
+            },
+            ClassLimits {
+                class: "CERAMIC".to_string(),
+                maximum_number_of_pieces: None,
+                maximum_weight: Some(25.5),
+            },
+        ]
+    );
+}
+
+#[test]
+fn when_parsing_invalid_class_limits_it_should_return_an_error() {
+    for input in [
+        "GLASS,10",
+        ",10,-",
+        "GLASS,0,-",
+        "GLASS,ten,-",
+        "GLASS,-,-1.0",
+        "GLASS,10,-\nGLASS,5,-",
+    ] {
+        assert!(
+            matches!(
+                parse_class_limits(&mut Cursor::new(input)),
+                Err(Error::InvalidClassLimits { .. })
+            ),
+            "{input}"
+        );
+    }
+}
+
+// parse_id_patterns()
+
+#[test]
+fn when_parsing_id_patterns_it_should_return_a_pattern_per_line() {
+    let input = "# Ships separately\nGLASS-*\n\n  1003  \n";
+    let patterns =
+        parse_id_patterns(&mut Cursor::new(input)).expect("Failed to parse the patterns.");
+    assert_eq!(
+        patterns,
+        vec![IdPattern::new("GLASS-*"), IdPattern::new("1003")]
+    );
+}
+
+// parse_item_aliases()
+
+#[test]
+fn when_parsing_item_aliases_it_should_map_each_alias_to_the_canonical_id() {
+    let input = "# Legacy SKUs
+OLD-1001, 1001
+
+OLD-1002,1002
+LEGACY-1001,1001
+";
+    let aliases =
+        parse_item_aliases(&mut Cursor::new(input)).expect("Failed to parse the aliases.");
+    assert_eq!(aliases.aliases.len(), 3);
+    assert_eq!(aliases.aliases["OLD-1001"], "1001");
+    assert_eq!(aliases.aliases["LEGACY-1001"], "1001");
+}
+
+#[test]
+fn when_parsing_item_aliases_with_an_alias_of_an_alias_it_should_return_an_error() {
+    let input = "OLD-1001,1001
+OLDER-1001,OLD-1001
+";
+    let result = parse_item_aliases(&mut Cursor::new(input));
+    assert!(matches!(
+        result,
+        Err(Error::InvalidAliasLine { input }) if input == "OLDER-1001,OLD-1001"
+    ));
+}
+
+#[test]
+fn when_parsing_item_aliases_with_a_repeated_alias_it_should_return_an_error() {
+    let input = "OLD-1001,1001
+OLD-1001,1002
+";
+    let result = parse_item_aliases(&mut Cursor::new(input));
+    assert!(matches!(result, Err(Error::InvalidAliasLine { .. })));
+}
+
+// parse_items()
+
+#[test]
+fn when_parsing_items_it_should_skip_comments_and_empty_lines() {
+    let input = "# Late additions\n3001,100,5,10\n\n3002,100,2,1";
+    let mut cursor = Cursor::new(input);
+    let items = parse_items(&mut cursor).expect("Failed to parse the items.");
+    assert_eq!(items.len(), 2);
+    assert_eq!(&*items[1].id, "3002");
+}
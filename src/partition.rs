@@ -0,0 +1,208 @@
+//! Partitioning the items into groups of compatible items before they are planned, so that inputs with many
+//! incompatibility rules don't check those rules for every placement.
+//!
+//! Two items are compatible when they may share a pack, i.e. they have the same storage class, the same
+//! destination and the same exclusive tags. The groups are the connected components of the graph in which the
+//! compatible items are connected, and no pack ever holds items of two groups. Each group is planned on its
+//! own, without the rules that only keep the groups apart, and the packs of the groups follow each other in
+//! the order in which the groups first appear in the items.
+
+use std::collections::HashMap;
+
+use crate::config::StrategyConfig;
+use crate::errors::Error;
+use crate::model::{
+    ItemTemplate, Optimality, Pack, PackRule, PackTemplate, PackedItem, PlanOutcome, StorageClass,
+};
+use crate::planner::{
+    analyze_pack_count, offset_pack_numbers, NoObserver, PackingAlgorithm, PlanObserver,
+};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "partition_tests.rs"]
+mod tests;
+
+/// The properties that decide with which other items an item may share a pack.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct CompatibilityKey {
+    storage_class: StorageClass,
+    destination: Option<String>,
+
+    /// Indicates, for each exclusive tag of the rules, if the item has the tag.
+    exclusive_tags: Vec<bool>,
+}
+
+impl CompatibilityKey {
+    fn of(item: &ItemTemplate, exclusive_tags: &[&str]) -> CompatibilityKey {
+        CompatibilityKey {
+            storage_class: item.attributes.storage_class,
+            destination: item.attributes.destination.clone(),
+            exclusive_tags: exclusive_tags
+                .iter()
+                .map(|tag| item.attributes.has_tag(tag))
+                .collect(),
+        }
+    }
+}
+
+/// Splits the items into groups of compatible items, each in the order of the items. The groups are ordered by
+/// their first item. Returns `None` if the items can't be planned in groups, because items are pinned to a
+/// pack, which refers to the pack numbers of the whole plan, or because the components of a kit belong to
+/// different groups.
+pub fn partition_items(
+    items: &[ItemTemplate],
+    rules: &[PackRule],
+) -> Option<Vec<Vec<ItemTemplate>>> {
+    if items.iter().any(|t| t.attributes.pinned_pack.is_some()) {
+        return None;
+    }
+
+    let exclusive_tags: Vec<&str> = rules
+        .iter()
+        .filter_map(|r| match r {
+            PackRule::ExclusiveTag { tag } => Some(tag.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut group_indices: HashMap<CompatibilityKey, usize> = HashMap::new();
+    let mut groups: Vec<Vec<ItemTemplate>> = Vec::new();
+    let mut kit_groups = HashMap::new();
+    for item in items {
+        let key = CompatibilityKey::of(item, &exclusive_tags);
+        let next_index = groups.len();
+        let index = *group_indices.entry(key).or_insert(next_index);
+        if index == next_index {
+            groups.push(Vec::new());
+        }
+
+        if let Some(kit_instance) = &item.attributes.kit_instance {
+            let kit = (kit_instance.kit_id.clone(), kit_instance.number);
+            if *kit_groups.entry(kit).or_insert(index) != index {
+                return None;
+            }
+        }
+
+        groups[index].push(item.clone());
+    }
+
+    Some(groups)
+}
+
+/// Forwards the intermediate steps of the plan of a group to another observer, with the packs numbered as
+/// they are in the plan of all the groups.
+struct OffsetObserver<'a> {
+    observer: &'a mut dyn PlanObserver,
+    offset: usize,
+}
+
+impl OffsetObserver<'_> {
+    fn renumber(&self, pack: &Pack) -> Pack {
+        let mut pack = pack.clone();
+        pack.number += self.offset;
+        pack
+    }
+}
+
+impl PlanObserver for OffsetObserver<'_> {
+    fn on_item_placed(&mut self, pack: &Pack, placement: &PackedItem) {
+        if self.offset == 0 {
+            self.observer.on_item_placed(pack, placement);
+        } else {
+            let pack = self.renumber(pack);
+            self.observer.on_item_placed(&pack, placement);
+        }
+    }
+
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        if self.offset == 0 {
+            self.observer.on_pack_closed(pack);
+        } else {
+            self.observer.on_pack_closed(&self.renumber(pack));
+        }
+    }
+
+    fn on_pack_reopened(&mut self, pack: &Pack) {
+        if self.offset == 0 {
+            self.observer.on_pack_reopened(pack);
+        } else {
+            self.observer.on_pack_reopened(&self.renumber(pack));
+        }
+    }
+}
+
+/// The algorithm that partitions the items into groups of compatible items, see [`partition_items`], and plans
+/// each group with another algorithm. The items are planned as a whole when they can't be partitioned.
+pub struct PartitionedAlgorithm {
+    /// The algorithm that plans each group.
+    pub algorithm: Box<dyn PackingAlgorithm + Send + Sync>,
+}
+
+impl PackingAlgorithm for PartitionedAlgorithm {
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error> {
+        self.plan_with_observer(items, pack_template, rules, &mut NoObserver)
+    }
+
+    fn plan_with_observer(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+        observer: &mut dyn PlanObserver,
+    ) -> Result<PlanOutcome, Error> {
+        let groups = match partition_items(items, rules) {
+            Some(groups) if groups.len() > 1 => groups,
+            _ => {
+                return self
+                    .algorithm
+                    .plan_with_observer(items, pack_template, rules, observer)
+            }
+        };
+
+        // The exclusive tags are the same for all the items of a group, so their rules always hold.
+        let group_rules: Vec<PackRule> = rules
+            .iter()
+            .filter(|r| !matches!(r, PackRule::ExclusiveTag { .. }))
+            .cloned()
+            .collect();
+
+        let mut outcome = PlanOutcome::default();
+        let mut attempts = None;
+        for group in groups {
+            let offset = outcome.packs.len();
+            let mut group_observer = OffsetObserver { observer, offset };
+            let group_outcome = self.algorithm.plan_with_observer(
+                &group,
+                pack_template,
+                &group_rules,
+                &mut group_observer,
+            )?;
+
+            let group_outcome = offset_pack_numbers(group_outcome, offset);
+            outcome.packs.extend(group_outcome.packs);
+            outcome.warnings.extend(group_outcome.warnings);
+            attempts = attempts.max(group_outcome.optimality.map(|o| o.attempts));
+        }
+
+        // The attempts are those of the group with the most attempts, while the theoretical minimum is that of
+        // all the items.
+        outcome.optimality = attempts.map(|attempts| Optimality {
+            attempts,
+            reaches_minimum: analyze_pack_count(items, pack_template, &outcome.packs)
+                .reaches_minimum(),
+        });
+        Ok(outcome)
+    }
+
+    fn strategy(&self) -> StrategyConfig {
+        StrategyConfig {
+            partition: true,
+            ..self.algorithm.strategy()
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+use super::*;
+use crate::model::{PackSortOrder, PlanWarning};
+use crate::planner::GreedyAlgorithm;
+
+fn pack_template(maximum_number_of_pieces: i32, maximum_weight: f64) -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces,
+        maximum_weight,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+fn exclusive_rules() -> Vec<PackRule> {
+    vec![
+        PackRule::ExclusiveTag {
+            tag: "HAZMAT".to_string(),
+        },
+        PackRule::ExclusiveTag {
+            tag: "FOOD".to_string(),
+        },
+    ]
+}
+
+fn ids(items: &[ItemTemplate]) -> Vec<&str> {
+    items.iter().map(|t| &*t.id).collect()
+}
+
+/// Records the numbers of the packs that were closed.
+#[derive(Default)]
+struct ClosedPacks {
+    numbers: Vec<usize>,
+}
+
+impl PlanObserver for ClosedPacks {
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        self.numbers.push(pack.number);
+    }
+}
+
+// partition_items()
+
+#[test]
+fn when_partitioning_items_it_should_group_the_items_with_the_same_exclusive_tags() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,2,1.0,tags=HAZMAT").unwrap(),
+        ItemTemplate::from_str("2,100,2,1.0").unwrap(),
+        ItemTemplate::from_str("3,100,2,1.0,tags=HAZMAT|FOOD").unwrap(),
+        ItemTemplate::from_str("4,100,2,1.0,tags=HAZMAT|FRAGILE").unwrap(),
+        ItemTemplate::from_str("5,100,2,1.0,destination=north").unwrap(),
+    ];
+
+    let groups = partition_items(&items, &exclusive_rules()).unwrap();
+    let groups: Vec<Vec<&str>> = groups.iter().map(|g| ids(g)).collect();
+    assert_eq!(
+        groups,
+        vec![vec!["1", "4"], vec!["2"], vec!["3"], vec!["5"]]
+    );
+}
+
+#[test]
+fn when_partitioning_items_with_a_pinned_item_it_should_not_partition() {
+    let input = "NATURAL,10,100.0\nPIN,2,pack=1\n1,100,2,1.0,tags=HAZMAT\n2,100,2,1.0";
+    let (_, items) = crate::parser::parse_input(&mut input.as_bytes()).unwrap();
+
+    assert!(partition_items(&items, &exclusive_rules()).is_none());
+}
+
+// PartitionedAlgorithm
+
+#[test]
+fn when_planning_partitioned_items_it_should_not_mix_the_groups() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,3,1.0,tags=HAZMAT").unwrap(),
+        ItemTemplate::from_str("2,100,3,1.0").unwrap(),
+        ItemTemplate::from_str("3,100,3,1.0,tags=HAZMAT").unwrap(),
+        ItemTemplate::from_str("4,100,3,1.0").unwrap(),
+    ];
+    let algorithm = PartitionedAlgorithm {
+        algorithm: Box::new(GreedyAlgorithm),
+    };
+
+    let mut observer = ClosedPacks::default();
+    let outcome = algorithm
+        .plan_with_observer(
+            &items,
+            &pack_template(6, 100.0),
+            &exclusive_rules(),
+            &mut observer,
+        )
+        .unwrap();
+
+    // Planned as a whole, the alternating items would take four packs.
+    let packs: Vec<(usize, Vec<&str>)> = outcome
+        .packs
+        .iter()
+        .map(|p| (p.number, p.items.iter().map(|i| &*i.item.id).collect()))
+        .collect();
+    assert_eq!(packs, vec![(1, vec!["1", "3"]), (2, vec!["2", "4"])]);
+    assert_eq!(observer.numbers, vec![1, 2]);
+}
+
+#[test]
+fn when_planning_partitioned_items_it_should_number_the_packs_after_those_of_the_earlier_groups() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,12,1.0,tags=HAZMAT").unwrap(),
+        ItemTemplate::from_str("2,100,0,1.0").unwrap(),
+        ItemTemplate::from_str("3,100,4,1.0").unwrap(),
+    ];
+    let algorithm = PartitionedAlgorithm {
+        algorithm: Box::new(GreedyAlgorithm),
+    };
+
+    let outcome = algorithm
+        .plan(&items, &pack_template(6, 100.0), &exclusive_rules())
+        .unwrap();
+    let numbers: Vec<usize> = outcome.packs.iter().map(|p| p.number).collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+    assert_eq!(&*outcome.packs[2].items[0].item.id, "3");
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::ItemSkipped {
+            item_id: "2".to_string(),
+            count: 0,
+        }]
+    );
+}
+
+#[test]
+fn when_describing_the_strategy_it_should_include_the_strategy_of_the_algorithm() {
+    let algorithm = PartitionedAlgorithm {
+        algorithm: Box::new(crate::completion::BinCompletionAlgorithm::default()),
+    };
+
+    let strategy = algorithm.strategy();
+    assert!(strategy.partition);
+    assert!(strategy.bin_completion);
+}
@@ -0,0 +1,2215 @@
+//! Placement of the items in packs.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::config::StrategyConfig;
+use crate::errors::Error;
+use crate::model::{
+    BillOfMaterials, DeliveryStop, DestinationLimits, DuplicateIdPolicy, EstimateRange,
+    FitPlacement, FitReport, IdPattern, InfeasibleItemPolicy, ItemAliases, ItemAttributes,
+    ItemFilter, ItemMap, ItemTemplate, KitInstance, LengthMode, Pack, PackCountAnalysis, PackRule,
+    PackSortOrder, PackTemplate, PackedItem, PlacementLimit, PlanOutcome, PlanWarning,
+    SubstitutionGroups, TieBreak,
+};
+use crate::strip::orient_on_sheet;
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "planner_tests.rs"]
+mod tests;
+
+/// Receives the intermediate steps of the planner, e.g. to show or log the planning step-by-step. All
+/// methods do nothing by default.
+pub trait PlanObserver {
+    /// Called after a number of items from a batch were placed in a pack. The pack already contains the
+    /// placement.
+    fn on_item_placed(&mut self, _pack: &Pack, _placement: &PackedItem) {}
+
+    /// Called when the planner stops placing items in a pack. The pack will not change anymore, unless it is
+    /// reopened.
+    fn on_pack_closed(&mut self, _pack: &Pack) {}
+
+    /// Called when a closed pack is opened again to take more items, which only the
+    /// [`crate::incremental::IncrementalPlanner`] does. The pack is reported as closed again once the items
+    /// are placed.
+    fn on_pack_reopened(&mut self, _pack: &Pack) {}
+}
+
+/// Defines an algorithm that places items in packs. This allows other algorithms to reuse the parsing and
+/// the rendering of the plans.
+pub trait PackingAlgorithm {
+    /// Places the items, in the given order, in packs that match the pack template and the rules.
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error>;
+
+    /// Places the items in the same way as [`PackingAlgorithm::plan`], while reporting the placements and the
+    /// closed packs to the observer. By default the observer isn't notified.
+    fn plan_with_observer(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+        _observer: &mut dyn PlanObserver,
+    ) -> Result<PlanOutcome, Error> {
+        self.plan(items, pack_template, rules)
+    }
+
+    /// Returns the settings of the algorithm in the strategy of a [`crate::config::PlannerConfig`], so that
+    /// the plan can be made again. By default the algorithm has no settings, which is the greedy algorithm.
+    fn strategy(&self) -> StrategyConfig {
+        StrategyConfig::default()
+    }
+}
+
+/// The built-in algorithm, which fills one pack at a time. See [`plan_packs`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GreedyAlgorithm;
+
+impl PackingAlgorithm for GreedyAlgorithm {
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error> {
+        plan_packs(items, pack_template, rules)
+    }
+
+    fn plan_with_observer(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+        observer: &mut dyn PlanObserver,
+    ) -> Result<PlanOutcome, Error> {
+        plan_packs_with_observer(items, pack_template, rules, observer)
+    }
+}
+
+/// The observer used when the caller isn't interested in the intermediate steps.
+pub(crate) struct NoObserver;
+
+impl PlanObserver for NoObserver {}
+
+/// A function that is called with each pack as soon as the pack is closed, e.g. to print the label of the pack
+/// while the rest of the plan is still being made.
+pub type PackClosedHook = Arc<dyn Fn(&Pack) + Send + Sync>;
+
+/// Forwards the intermediate steps to another observer, and calls the hook with each closed pack.
+pub(crate) struct PackClosedObserver<'a> {
+    pub observer: &'a mut dyn PlanObserver,
+    pub hook: &'a PackClosedHook,
+}
+
+impl PlanObserver for PackClosedObserver<'_> {
+    fn on_item_placed(&mut self, pack: &Pack, placement: &PackedItem) {
+        self.observer.on_item_placed(pack, placement);
+    }
+
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        self.observer.on_pack_closed(pack);
+        (self.hook)(pack);
+    }
+
+    fn on_pack_reopened(&mut self, pack: &Pack) {
+        self.observer.on_pack_reopened(pack);
+    }
+}
+
+fn maximum_number_of_items_to_add(
+    pack_template: &PackTemplate,
+    current_pack_weight: f64,
+    current_pack_item_count: i32,
+    template: &ItemTemplate,
+) -> i32 {
+    let weight_space_in_pack = pack_template.maximum_weight - current_pack_weight;
+    let item_space_in_pack = pack_template.maximum_number_of_pieces - current_pack_item_count;
+
+    let max_items_by_weight = (weight_space_in_pack / template.weight).floor() as i32;
+    if max_items_by_weight < item_space_in_pack {
+        max_items_by_weight
+    } else {
+        item_space_in_pack
+    }
+}
+
+/// Returns the maximum number of items of the given template that the rules allow to be added to the pack.
+/// Returns `i32::MAX` if the rules don't restrict the number of items.
+fn maximum_number_of_items_allowed_by_rules(
+    rules: &[PackRule],
+    pack: &Pack,
+    template: &ItemTemplate,
+) -> i32 {
+    let mut maximum = i32::MAX;
+    for rule in rules {
+        #[cfg(test)]
+        crate::model::checks::add(1);
+
+        match rule {
+            PackRule::MaximumItemsWithTag {
+                tag,
+                maximum: maximum_for_tag,
+            } => {
+                if template.attributes.has_tag(tag) {
+                    let items_with_tag = pack.number_of_items_with_tag(tag);
+                    maximum = maximum.min((maximum_for_tag - items_with_tag).max(0));
+                }
+            }
+            PackRule::MaximumWeightWithTag {
+                tag,
+                maximum_weight,
+            } => {
+                if template.attributes.has_tag(tag) && template.weight > 0.0 {
+                    let remaining_weight = maximum_weight - pack.weight_of_items_with_tag(tag);
+                    let items_by_weight = (remaining_weight / template.weight).floor().max(0.0);
+                    maximum = maximum.min(items_by_weight.min(i32::MAX as f64) as i32);
+                }
+            }
+            PackRule::MaximumHeavyItems {
+                threshold,
+                maximum: maximum_heavy_items,
+            } => {
+                if !template.attributes.bulk && template.weight > *threshold {
+                    let heavy_items = pack.number_of_items_heavier_than(*threshold);
+                    maximum = maximum.min((maximum_heavy_items - heavy_items).max(0));
+                }
+            }
+            PackRule::ExclusiveTag { tag } => {
+                // Every placement holds at least one item, so the counts tell whether all, or none, of the
+                // items in the pack have the tag.
+                let items_with_tag = pack.number_of_items_with_tag(tag);
+                let is_compatible = if template.attributes.has_tag(tag) {
+                    items_with_tag == pack.item_count
+                } else {
+                    items_with_tag == 0
+                };
+
+                if !is_compatible {
+                    maximum = 0;
+                }
+            }
+        }
+    }
+
+    maximum
+}
+
+/// Returns the maximum number of items of the given template that can be added to the pack without exceeding
+/// the maximum length of the pack, or none if the item is longer than the internal diagonal of the pack.
+/// Returns `i32::MAX` if the pack has no maximum length.
+fn maximum_number_of_items_by_length(
+    pack_template: &PackTemplate,
+    pack: &Pack,
+    template: &ItemTemplate,
+) -> i32 {
+    // An item that is longer than the internal diagonal of the pack doesn't physically fit, whatever its weight.
+    if let Some(dimensions) = &pack_template.dimensions {
+        if template.length > dimensions.diagonal() {
+            return 0;
+        }
+    }
+
+    let maximum_length = match pack_template.maximum_length {
+        Some(l) => l,
+        None => return i32::MAX,
+    };
+
+    let mut maximum =
+        maximum_number_of_items_without_nesting(pack_template, pack, template, maximum_length);
+    if pack_template.nesting_rules.is_empty() || pack_template.length_mode == LengthMode::Maximum {
+        return maximum;
+    }
+
+    // Nesting only shortens the pack, so at least the items that fit by their own length fit, and possibly a
+    // few more that nest inside the items in the pack, or that hold items of the pack. Each step only looks up
+    // the items of the nesting rules, so it doesn't depend on the number of items in the pack.
+    maximum = maximum.max(0);
+    while maximum < i32::MAX
+        && pack.length_after_adding(pack_template, template, maximum + 1) <= maximum_length
+    {
+        maximum += 1;
+    }
+
+    maximum
+}
+
+/// Returns the maximum number of items of the given template that can be added to the pack without exceeding
+/// the maximum length, when none of the items nest inside each other.
+fn maximum_number_of_items_without_nesting(
+    pack_template: &PackTemplate,
+    pack: &Pack,
+    template: &ItemTemplate,
+    maximum_length: f64,
+) -> i32 {
+    match pack_template.length_mode {
+        LengthMode::Maximum => {
+            if template.length <= maximum_length {
+                i32::MAX
+            } else {
+                0
+            }
+        }
+        LengthMode::Sum => ((maximum_length - pack.length) / template.length).floor() as i32,
+        LengthMode::SumWithGap(gap) => {
+            // Every item but the first one in the pack adds a gap, so an empty pack has space for one more gap.
+            let available_length = if pack.is_empty() {
+                maximum_length + gap
+            } else {
+                maximum_length - pack.length
+            };
+            (available_length / (template.length + gap)).floor() as i32
+        }
+    }
+}
+
+/// Returns the maximum number of items of the given template that can be added to the pack without exceeding
+/// the maximum value of the pack. An empty pack always takes at least one item, so that an item that is worth
+/// more than the maximum value is placed on its own. Returns `i32::MAX` if the value of the pack isn't limited.
+fn maximum_number_of_items_by_value(
+    pack_template: &PackTemplate,
+    pack: &Pack,
+    template: &ItemTemplate,
+) -> i32 {
+    let (maximum_value, item_value) = match (pack_template.maximum_value, template.item_value()) {
+        (Some(m), Some(v)) if v > 0.0 => (m, v),
+        _ => return i32::MAX,
+    };
+
+    let items_by_value = ((maximum_value - pack.value) / item_value).floor().max(0.0);
+    let items_by_value = items_by_value.min(i32::MAX as f64) as i32;
+    if pack.is_empty() {
+        items_by_value.max(1)
+    } else {
+        items_by_value
+    }
+}
+
+/// Returns the maximum number of items of the given template that can be added to the pack without mixing
+/// storage classes, which is either none or any number of items.
+fn maximum_number_of_items_by_storage_class(pack: &Pack, template: &ItemTemplate) -> i32 {
+    if pack.is_empty() || pack.storage_class == template.attributes.storage_class {
+        i32::MAX
+    } else {
+        0
+    }
+}
+
+/// Returns the maximum number of items of the given template that can be added to the pack without mixing
+/// destinations, which is either none or any number of items.
+fn maximum_number_of_items_by_destination(pack: &Pack, template: &ItemTemplate) -> i32 {
+    if pack.is_empty() || pack.destination == template.attributes.destination {
+        i32::MAX
+    } else {
+        0
+    }
+}
+
+/// Determines which of the pack limits restricts the number of items that can be added to the pack.
+fn limit_for_items_to_add(
+    pack_template: &PackTemplate,
+    current_pack_weight: f64,
+    current_pack_item_count: i32,
+    template: &ItemTemplate,
+    items_left_in_batch: i32,
+) -> PlacementLimit {
+    let weight_space_in_pack = pack_template.maximum_weight - current_pack_weight;
+    let item_space_in_pack = pack_template.maximum_number_of_pieces - current_pack_item_count;
+    let max_items_by_weight = (weight_space_in_pack / template.weight).floor() as i32;
+
+    if items_left_in_batch <= max_items_by_weight.min(item_space_in_pack) {
+        PlacementLimit::None
+    } else if max_items_by_weight < item_space_in_pack {
+        PlacementLimit::Weight
+    } else {
+        PlacementLimit::PieceCount
+    }
+}
+
+/// Determines how many items of the batch can be placed in the pack and which limit determined that number.
+pub(crate) fn number_of_items_to_place(
+    pack_template: &PackTemplate,
+    pack: &Pack,
+    template: &ItemTemplate,
+    rules: &[PackRule],
+    items_left_in_batch: i32,
+) -> (i32, PlacementLimit) {
+    #[cfg(test)]
+    crate::model::checks::add(1);
+
+    let pack_template = &*pack_template.limits_for(pack, template);
+    let items_that_fit =
+        maximum_number_of_items_to_add(pack_template, pack.weight, pack.item_count, template);
+    let items_allowed_by_length = maximum_number_of_items_by_length(pack_template, pack, template);
+    let items_allowed_by_value = maximum_number_of_items_by_value(pack_template, pack, template);
+    let items_allowed_by_storage_class = maximum_number_of_items_by_storage_class(pack, template);
+    let items_allowed_by_destination = maximum_number_of_items_by_destination(pack, template);
+    let items_allowed_by_rules = maximum_number_of_items_allowed_by_rules(rules, pack, template);
+
+    let items_to_place = items_that_fit
+        .min(items_allowed_by_length)
+        .min(items_allowed_by_value)
+        .min(items_allowed_by_storage_class)
+        .min(items_allowed_by_destination)
+        .min(items_allowed_by_rules)
+        .min(items_left_in_batch)
+        .max(0);
+
+    let limit = if items_to_place == items_left_in_batch {
+        PlacementLimit::None
+    } else if items_to_place == items_that_fit {
+        limit_for_items_to_add(
+            pack_template,
+            pack.weight,
+            pack.item_count,
+            template,
+            items_left_in_batch,
+        )
+    } else if items_to_place == items_allowed_by_length {
+        PlacementLimit::Length
+    } else if items_to_place == items_allowed_by_value {
+        PlacementLimit::Value
+    } else if items_to_place == items_allowed_by_storage_class {
+        PlacementLimit::StorageClass
+    } else if items_to_place == items_allowed_by_destination {
+        PlacementLimit::Destination
+    } else {
+        PlacementLimit::Rule
+    };
+
+    // Only full multiples are placed, unless the rest of the batch is placed.
+    let items_to_place = if items_to_place < items_left_in_batch {
+        items_to_place - items_to_place % template.attributes.pack_multiple()
+    } else {
+        items_to_place
+    };
+
+    (items_to_place, limit)
+}
+
+// The weight of bulk below which the bulk is considered to be placed completely
+const BULK_WEIGHT_TOLERANCE: f64 = 1e-9;
+
+/// Returns the maximum weight of items of the given bulk template that the weight rules allow to be added
+/// to the pack. Returns `f64::MAX` if the rules don't restrict the weight.
+fn maximum_bulk_weight_allowed_by_rules(
+    rules: &[PackRule],
+    pack: &Pack,
+    template: &ItemTemplate,
+) -> f64 {
+    let mut maximum = f64::MAX;
+    for rule in rules {
+        if let PackRule::MaximumWeightWithTag {
+            tag,
+            maximum_weight,
+        } = rule
+        {
+            if template.attributes.has_tag(tag) {
+                let remaining_weight = maximum_weight - pack.weight_of_items_with_tag(tag);
+                maximum = maximum.min(remaining_weight.max(0.0));
+            }
+        }
+    }
+
+    maximum
+}
+
+/// Determines how much of the remaining bulk can be placed in the pack, and which limit determined that
+/// weight. A placement of bulk takes up a single piece of the pack.
+pub(crate) fn bulk_weight_to_place(
+    pack_template: &PackTemplate,
+    pack: &Pack,
+    template: &ItemTemplate,
+    rules: &[PackRule],
+    bulk_left: f64,
+) -> (f64, PlacementLimit) {
+    let pack_template = &*pack_template.limits_for(pack, template);
+
+    // The rules that count items, and the other rules, apply to a placement as if it was a weightless piece.
+    let piece = ItemTemplate {
+        weight: 0.0,
+        count: 1,
+        ..template.clone()
+    };
+    if pack.item_count >= pack_template.maximum_number_of_pieces {
+        return (0.0, PlacementLimit::PieceCount);
+    }
+
+    if maximum_number_of_items_by_length(pack_template, pack, &piece) < 1 {
+        return (0.0, PlacementLimit::Length);
+    }
+
+    if maximum_number_of_items_by_storage_class(pack, &piece) < 1 {
+        return (0.0, PlacementLimit::StorageClass);
+    }
+
+    if maximum_number_of_items_by_destination(pack, &piece) < 1 {
+        return (0.0, PlacementLimit::Destination);
+    }
+
+    if maximum_number_of_items_allowed_by_rules(rules, pack, &piece) < 1 {
+        return (0.0, PlacementLimit::Rule);
+    }
+
+    let weight_space = (pack_template.maximum_weight - pack.weight).max(0.0);
+    let value_space = match (pack_template.maximum_value, template.attributes.value) {
+        (Some(m), Some(v)) if v > 0.0 => ((m - pack.value) / v).max(0.0),
+        _ => f64::MAX,
+    };
+    let rule_space = maximum_bulk_weight_allowed_by_rules(rules, pack, template);
+
+    let weight = bulk_left.min(weight_space).min(value_space).min(rule_space);
+    let limit = if weight == bulk_left {
+        PlacementLimit::None
+    } else if weight == weight_space {
+        PlacementLimit::Weight
+    } else if weight == value_space {
+        PlacementLimit::Value
+    } else {
+        PlacementLimit::Rule
+    };
+
+    if weight <= BULK_WEIGHT_TOLERANCE {
+        (0.0, limit)
+    } else {
+        (weight, limit)
+    }
+}
+
+/// Places the bulk item in the packs, starting with the pack at the given index, and starts new packs until
+/// all of the bulk is placed. Returns the index of the pack that is filled last.
+fn place_bulk_item(
+    packs: &mut Vec<Pack>,
+    mut current_pack_index: usize,
+    template: &ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    observer: &mut dyn PlanObserver,
+) -> Result<usize, Error> {
+    let mut bulk_left = template.weight;
+    while bulk_left > BULK_WEIGHT_TOLERANCE {
+        let current_pack = &mut packs[current_pack_index];
+        let (weight, limit) =
+            bulk_weight_to_place(pack_template, current_pack, template, rules, bulk_left);
+        if weight > 0.0 {
+            current_pack.add_bulk(pack_template, template, weight, bulk_left, limit);
+            observer.on_item_placed(
+                current_pack,
+                current_pack
+                    .items
+                    .last()
+                    .expect("The pack has just been filled."),
+            );
+            bulk_left -= weight;
+        } else if current_pack.is_empty() {
+            return Err(Error::ItemDoesNotFitInEmptyPack {
+                item_id: template.id.to_string(),
+            });
+        }
+
+        if bulk_left > BULK_WEIGHT_TOLERANCE {
+            current_pack_index = close_pack(packs, current_pack_index, observer);
+        }
+    }
+
+    Ok(current_pack_index)
+}
+
+/// Places the items that are pinned to a specific pack in those packs. Returns the packs up to and including
+/// the highest pack number that has pinned items. Packs without pinned items are empty.
+fn place_pinned_items(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    observer: &mut dyn PlanObserver,
+) -> Result<Vec<Pack>, Error> {
+    let mut packs: Vec<Pack> = Vec::new();
+    for template in items.iter().filter(|t| t.count > 0) {
+        let pack_number = match template.attributes.pinned_pack {
+            Some(n) => n,
+            None => continue,
+        };
+
+        while packs.len() < pack_number {
+            packs.push(Pack::new(packs.len() + 1));
+        }
+
+        let pack = &mut packs[pack_number - 1];
+        let (items_to_pack, limit) =
+            number_of_items_to_place(pack_template, pack, template, rules, template.count);
+        if items_to_pack < template.count {
+            return Err(Error::PinnedItemsDoNotFit {
+                item_id: template.id.to_string(),
+                pack_number,
+            });
+        }
+
+        if template.attributes.bulk {
+            pack.add_bulk(
+                pack_template,
+                template,
+                template.weight,
+                template.weight,
+                limit,
+            );
+        } else {
+            pack.add(
+                pack_template,
+                template,
+                items_to_pack,
+                template.count,
+                limit,
+            );
+        }
+        observer.on_item_placed(
+            pack,
+            pack.items.last().expect("The pack has just been filled."),
+        );
+    }
+
+    Ok(packs)
+}
+
+// The relative margin by which the weight of the remaining items may exceed the hard maximum weight before
+// the items are known not to fit, which covers the rounding of the sums of the weights
+const REMAINING_WEIGHT_MARGIN: f64 = 1e-9;
+
+/// The totals of the items that are placed after a batch, which rule out most attempts to place all the
+/// remaining items in the current pack without visiting the items.
+#[derive(Clone, Copy, Debug, Default)]
+struct RemainingItems {
+    /// The total weight of the items.
+    weight: f64,
+
+    /// The number of pieces, where a bulk item is a single piece.
+    pieces: i64,
+
+    /// Indicates if one of the items closes the pack before it is placed.
+    closes_pack: bool,
+}
+
+/// Returns, for each index of the items, the totals of the items from that index onwards. The last entry is
+/// the totals of no items at all.
+fn remaining_items_from(items: &[&ItemTemplate]) -> Vec<RemainingItems> {
+    let mut remaining_items = vec![RemainingItems::default(); items.len() + 1];
+    for (index, template) in items.iter().enumerate().rev() {
+        let next = remaining_items[index + 1];
+        remaining_items[index] = RemainingItems {
+            weight: next.weight + (template.count as f64) * template.weight,
+            pieces: next.pieces + template.count as i64,
+            closes_pack: next.closes_pack || template.attributes.close_pack_before,
+        };
+    }
+
+    remaining_items
+}
+
+/// Places all the remaining items in the pack if the items fit when the pack is loaded up to the hard maximum
+/// weight. This avoids starting an extra pack that would only contain the last few items. Returns `true` if
+/// the items were placed, in which case the pack is marked as overweight.
+///
+/// The 'remaining_items' are the totals of the 'later_items', which are only visited if the totals fit in
+/// the pack, so that the check takes constant time for all but the last few packs.
+#[allow(clippy::too_many_arguments)]
+fn place_remaining_items_overweight(
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    pack: &mut Pack,
+    template: &ItemTemplate,
+    items_left_in_batch: i32,
+    later_items: &[&ItemTemplate],
+    remaining_items: &RemainingItems,
+    observer: &mut dyn PlanObserver,
+) -> bool {
+    let hard_maximum_weight = match pack_template.hard_maximum_weight {
+        Some(w) => w,
+        None => return false,
+    };
+
+    // A later item that closes the pack starts a new pack anyway.
+    if remaining_items.closes_pack {
+        return false;
+    }
+
+    let total_weight =
+        pack.weight + (items_left_in_batch as f64) * template.weight + remaining_items.weight;
+    let total_pieces = pack.item_count as i64 + items_left_in_batch as i64 + remaining_items.pieces;
+    if total_weight > hard_maximum_weight * (1.0 + REMAINING_WEIGHT_MARGIN)
+        || total_pieces > pack_template.maximum_number_of_pieces as i64
+    {
+        return false;
+    }
+
+    let overweight_template = PackTemplate {
+        maximum_weight: hard_maximum_weight,
+        ..pack_template.clone()
+    };
+    let remaining_items = std::iter::once((template, items_left_in_batch))
+        .chain(later_items.iter().map(|t| (*t, t.count)));
+
+    // The rest of a batch of which the first items were placed in the pack joins the placement of these items,
+    // so that the batch is on a single line.
+    let extends_last_placement = pack.items.last().is_some_and(|p| {
+        p.item.id == template.id
+            && Arc::ptr_eq(&p.item.attributes, &template.attributes)
+            && p.items_remaining_in_batch - p.item.count == items_left_in_batch
+    });
+
+    let mut candidate = pack.clone();
+    let mut placements = Vec::new();
+    for (index, (item, count)) in remaining_items.enumerate() {
+        let (items_to_pack, limit) =
+            number_of_items_to_place(&overweight_template, &candidate, item, rules, count);
+        if items_to_pack < count {
+            return false;
+        }
+
+        if index == 0 && extends_last_placement {
+            placements.push(candidate.extend_last_placement(pack_template, items_to_pack, limit));
+        } else {
+            candidate.add(pack_template, item, items_to_pack, count, limit);
+            placements.push(
+                candidate
+                    .items
+                    .last()
+                    .expect("The item was just added.")
+                    .clone(),
+            );
+        }
+    }
+
+    candidate.overweight = candidate.weight > pack_template.maximum_weight;
+    *pack = candidate;
+    for placement in &placements {
+        observer.on_item_placed(pack, placement);
+    }
+
+    true
+}
+
+/// Returns `true` if all the given items, with their full count, fit in the pack together.
+fn items_fit_in_pack(
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    pack: &Pack,
+    items: &[&ItemTemplate],
+) -> bool {
+    let mut candidate = pack.clone();
+    for item in items {
+        let (items_to_pack, limit) =
+            number_of_items_to_place(pack_template, &candidate, item, rules, item.count);
+        if items_to_pack < item.count {
+            return false;
+        }
+
+        candidate.add(pack_template, item, items_to_pack, item.count, limit);
+    }
+
+    true
+}
+
+/// Closes the pack at the given index and returns the index of the next pack, which is created if it doesn't
+/// exist yet.
+fn close_pack(packs: &mut Vec<Pack>, pack_index: usize, observer: &mut dyn PlanObserver) -> usize {
+    observer.on_pack_closed(&packs[pack_index]);
+
+    let next_pack_index = pack_index + 1;
+    if next_pack_index == packs.len() {
+        packs.push(Pack::new(next_pack_index + 1));
+    }
+
+    next_pack_index
+}
+
+/// Places the given items in packs, in the order in which the items are provided. Items that are pinned to a
+/// pack are placed first, the other items are placed around them. A new pack is started once the current pack
+/// cannot take any more items, either because it is full or because the rules don't allow the next item in
+/// the pack, or when the next item should close the current pack. Batches without any items are skipped,
+/// which is reported as a warning. Returns an error if an item doesn't even fit in an empty pack.
+///
+/// When the pack template has a hard maximum weight, and the items that are left fit in the current pack
+/// without exceeding that weight, the items are placed in the current pack instead of a new pack. Such packs
+/// are marked as overweight and reported as a warning.
+///
+/// Bulk items are split over as many packs as needed, with each pack taking as much of the bulk as its
+/// remaining capacity allows. A bulk item that is pinned to a pack is placed in that pack as a whole.
+pub fn plan_packs(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Result<PlanOutcome, Error> {
+    plan_packs_with_observer(items, pack_template, rules, &mut NoObserver)
+}
+
+/// Places the given items in packs in the same way as [`plan_packs`], while reporting each placement and
+/// each closed pack to the observer.
+pub fn plan_packs_with_observer(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    observer: &mut dyn PlanObserver,
+) -> Result<PlanOutcome, Error> {
+    let mut warnings: Vec<PlanWarning> = items
+        .iter()
+        .filter(|t| t.count <= 0)
+        .map(|t| PlanWarning::ItemSkipped {
+            item_id: t.id.to_string(),
+            count: t.count,
+        })
+        .collect();
+
+    let mut packs = place_pinned_items(items, pack_template, rules, observer)?;
+    if packs.is_empty() {
+        packs.push(Pack::new(1));
+    }
+
+    let unpinned_items: Vec<&ItemTemplate> = items
+        .iter()
+        .filter(|t| t.count > 0 && t.attributes.pinned_pack.is_none())
+        .collect();
+
+    let remaining_items = match pack_template.hard_maximum_weight {
+        Some(_) => remaining_items_from(&unpinned_items),
+        None => Vec::new(),
+    };
+
+    let mut current_pack_index = 0;
+    'items: for (index, template) in unpinned_items.iter().enumerate() {
+        if !template.attributes.bulk && template.weight > pack_template.maximum_weight {
+            // A single item weighs more than the maximum weight of the pack, so it can never be placed.
+            return Err(Error::ItemDoesNotFitInEmptyPack {
+                item_id: template.id.to_string(),
+            });
+        }
+
+        if template.attributes.close_pack_before && !packs[current_pack_index].is_empty() {
+            current_pack_index = close_pack(&mut packs, current_pack_index, observer);
+        }
+
+        // Before the first component of a kit is placed, make sure that all the components of the kit fit in
+        // the pack, so that they are never split over two packs.
+        if let Some(kit_instance) = &template.attributes.kit_instance {
+            let is_first_component = index == 0
+                || unpinned_items[index - 1].attributes.kit_instance.as_ref() != Some(kit_instance);
+            if is_first_component {
+                let components: Vec<&ItemTemplate> = unpinned_items[index..]
+                    .iter()
+                    .take_while(|t| t.attributes.kit_instance.as_ref() == Some(kit_instance))
+                    .copied()
+                    .collect();
+                while !items_fit_in_pack(
+                    pack_template,
+                    rules,
+                    &packs[current_pack_index],
+                    &components,
+                ) {
+                    if packs[current_pack_index].is_empty() {
+                        return Err(Error::KitDoesNotFitInEmptyPack {
+                            kit_id: kit_instance.kit_id.to_string(),
+                        });
+                    }
+
+                    current_pack_index = close_pack(&mut packs, current_pack_index, observer);
+                }
+            }
+        }
+
+        if template.attributes.bulk {
+            current_pack_index = place_bulk_item(
+                &mut packs,
+                current_pack_index,
+                template,
+                pack_template,
+                rules,
+                observer,
+            )?;
+            continue;
+        }
+
+        let mut items_left_from_current_batch = template.count;
+        while items_left_from_current_batch > 0 {
+            let current_pack = &mut packs[current_pack_index];
+            let (items_to_pack, limit) = number_of_items_to_place(
+                pack_template,
+                current_pack,
+                template,
+                rules,
+                items_left_from_current_batch,
+            );
+            if items_to_pack > 0 {
+                current_pack.add(
+                    pack_template,
+                    template,
+                    items_to_pack,
+                    items_left_from_current_batch,
+                    limit,
+                );
+                observer.on_item_placed(
+                    current_pack,
+                    current_pack
+                        .items
+                        .last()
+                        .expect("The pack has just been filled."),
+                );
+                items_left_from_current_batch -= items_to_pack;
+            } else if current_pack.is_empty() {
+                return Err(Error::ItemDoesNotFitInEmptyPack {
+                    item_id: template.id.to_string(),
+                });
+            }
+
+            if items_left_from_current_batch > 0 {
+                if let Some(later_totals) = remaining_items.get(index + 1) {
+                    if place_remaining_items_overweight(
+                        pack_template,
+                        rules,
+                        &mut packs[current_pack_index],
+                        template,
+                        items_left_from_current_batch,
+                        &unpinned_items[index + 1..],
+                        later_totals,
+                        observer,
+                    ) {
+                        break 'items;
+                    }
+                }
+
+                current_pack_index = close_pack(&mut packs, current_pack_index, observer);
+            }
+        }
+    }
+
+    // Packs in between packs with pinned items keep their number, even if they are empty, but there is no
+    // need to keep empty packs at the end.
+    while packs.last().is_some_and(|p| p.is_empty()) {
+        packs.pop();
+    }
+
+    for pack in packs.iter().skip(current_pack_index) {
+        observer.on_pack_closed(pack);
+    }
+
+    warnings.extend(
+        packs
+            .iter()
+            .filter(|p| p.is_empty())
+            .map(|p| PlanWarning::EmptyPack {
+                pack_number: p.number,
+            }),
+    );
+    warnings.extend(
+        packs
+            .iter()
+            .filter(|p| p.overweight)
+            .map(|p| PlanWarning::OverweightPack {
+                pack_number: p.number,
+                weight: p.gross_weight(),
+                maximum_weight: pack_template.maximum_weight + pack_template.tare_weight,
+            }),
+    );
+    if let Some(maximum_value) = pack_template.maximum_value {
+        warnings.extend(packs.iter().filter(|p| p.exceeds_maximum_value).map(|p| {
+            PlanWarning::MaximumValueExceeded {
+                pack_number: p.number,
+                value: p.value,
+                maximum_value,
+            }
+        }));
+    }
+
+    Ok(PlanOutcome {
+        packs,
+        warnings,
+        optimality: None,
+    })
+}
+
+// Adds as many of the items as fit to the existing packs, in order. Returns the number of the pack and the
+// number of items that were added, for each pack to which items were added.
+fn add_to_existing_packs(
+    packs: &mut [Pack],
+    template: &ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Vec<(usize, i32)> {
+    let mut additions = Vec::new();
+    let mut items_left_from_current_batch = template.count;
+    for pack in packs.iter_mut() {
+        if items_left_from_current_batch <= 0 {
+            break;
+        }
+
+        let (items_to_add, limit) = number_of_items_to_place(
+            pack_template,
+            pack,
+            template,
+            rules,
+            items_left_from_current_batch,
+        );
+        if items_to_add <= 0 {
+            continue;
+        }
+
+        pack.add(
+            pack_template,
+            template,
+            items_to_add,
+            items_left_from_current_batch,
+            limit,
+        );
+        items_left_from_current_batch -= items_to_add;
+        additions.push((pack.number, items_to_add));
+    }
+
+    additions
+}
+
+/// Determines if the new items fit in the remaining capacity of the existing packs, without changing the
+/// placement of the items that are already in the packs. The items are placed in the first pack that has
+/// space for them.
+pub fn can_fit(
+    existing_packs: &[Pack],
+    new_items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> FitReport {
+    let mut packs = existing_packs.to_vec();
+    let mut report = FitReport::default();
+
+    for template in new_items {
+        let mut items_left_from_current_batch = template.count;
+        for (pack_number, items_added) in
+            add_to_existing_packs(&mut packs, template, pack_template, rules)
+        {
+            items_left_from_current_batch -= items_added;
+
+            let mut item = template.clone();
+            item.count = items_added;
+            report.placements.push(FitPlacement { pack_number, item });
+        }
+
+        if items_left_from_current_batch > 0 {
+            let mut item = template.clone();
+            item.count = items_left_from_current_batch;
+            report.unplaced_items.push(item);
+        }
+    }
+
+    report
+}
+
+// The relative difference below which the total weight is considered to be a whole number of packs
+const EXACT_FIT_TOLERANCE: f64 = 1e-9;
+
+/// Determines the theoretical minimum number of packs for the items, from the total weight and the total
+/// number of items. Returns the minimum and whether the items fit exactly in that number of packs. Batches
+/// without any items are ignored.
+fn minimum_number_of_packs(items: &[ItemTemplate], pack_template: &PackTemplate) -> (usize, bool) {
+    let items = items.iter().filter(|t| t.count > 0);
+    let total_count: i64 = items.clone().map(|t| t.count as i64).sum();
+    let total_weight: f64 = items.map(|t| (t.count as f64) * t.weight).sum();
+
+    let maximum_pieces = pack_template.maximum_number_of_pieces.max(1) as i64;
+    let packs_by_count = (total_count + maximum_pieces - 1) / maximum_pieces;
+    let count_fits_exactly = total_count > 0 && total_count % maximum_pieces == 0;
+
+    let packs_by_weight = total_weight / pack_template.maximum_weight;
+    let whole_packs_by_weight = packs_by_weight.round();
+    let weight_fits_exactly = whole_packs_by_weight > 0.0
+        && (packs_by_weight - whole_packs_by_weight).abs()
+            <= EXACT_FIT_TOLERANCE * whole_packs_by_weight;
+    let packs_by_weight = if weight_fits_exactly {
+        whole_packs_by_weight as i64
+    } else {
+        packs_by_weight.ceil() as i64
+    };
+
+    let minimum_number_of_packs = packs_by_count.max(packs_by_weight);
+    let is_exact_fit = (count_fits_exactly && packs_by_count == minimum_number_of_packs)
+        || (weight_fits_exactly && packs_by_weight == minimum_number_of_packs);
+
+    (minimum_number_of_packs as usize, is_exact_fit)
+}
+
+/// Determines the theoretical minimum number of packs for the items, from the total weight and the total
+/// number of items, and compares it with the number of packs in the plan. Batches without any items are
+/// ignored.
+pub fn analyze_pack_count(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    packs: &[Pack],
+) -> PackCountAnalysis {
+    let (minimum_number_of_packs, is_exact_fit) = minimum_number_of_packs(items, pack_template);
+    PackCountAnalysis {
+        minimum_number_of_packs,
+        is_exact_fit,
+        number_of_packs: packs.len(),
+    }
+}
+
+/// Counts the packs the greedy planner needs for the items, in the given order, when only the maximum weight
+/// and the maximum number of pieces of the packs are taken into account. Items are placed in bulk, so the
+/// count takes a single step per batch and per pack. An item that doesn't fit in an empty pack is counted
+/// as a pack of its own.
+fn count_greedy_packs(items: &[ItemTemplate], pack_template: &PackTemplate) -> usize {
+    let mut number_of_packs = 0;
+    let mut pack_weight = 0.0;
+    let mut pack_pieces = 0;
+    let mut is_pack_open = false;
+
+    for template in items.iter().filter(|t| t.count > 0) {
+        if template.attributes.close_pack_before && pack_pieces > 0 {
+            is_pack_open = false;
+        }
+
+        // The weight of a bulk item is split over the packs, all other items are counted one at a time.
+        let (mut left, weight_per_unit) = if template.attributes.bulk {
+            (template.weight, 1.0)
+        } else {
+            (template.count as f64, template.weight)
+        };
+        while left > BULK_WEIGHT_TOLERANCE {
+            if !is_pack_open {
+                number_of_packs += 1;
+                pack_weight = 0.0;
+                pack_pieces = 0;
+                is_pack_open = true;
+            }
+
+            let piece_space = pack_template.maximum_number_of_pieces - pack_pieces;
+            let units_by_weight = (pack_template.maximum_weight - pack_weight) / weight_per_unit;
+            let units = if template.attributes.bulk {
+                units_by_weight.min(left)
+            } else {
+                units_by_weight.floor().min(left)
+            };
+
+            if piece_space <= 0 || units <= BULK_WEIGHT_TOLERANCE {
+                if pack_pieces == 0 {
+                    // The item doesn't even fit in an empty pack.
+                    left -= if template.attributes.bulk { left } else { 1.0 };
+                    pack_pieces = 1;
+                }
+
+                is_pack_open = false;
+                continue;
+            }
+
+            let (units, pieces) = if template.attributes.bulk {
+                (units, 1)
+            } else {
+                let units = units.min(piece_space as f64);
+                (units, units as i32)
+            };
+            pack_weight += units * weight_per_unit;
+            pack_pieces += pieces;
+            left -= units;
+        }
+    }
+
+    number_of_packs
+}
+
+/// Estimates the number of packs for the items, in the order in which they will be placed, without planning
+/// the packs. The minimum is the theoretical minimum, from the total weight and the total number of items,
+/// and the maximum is a quick greedy count that only takes the maximum weight and the maximum number of
+/// pieces into account. This is fast enough to give feedback, e.g. in a user interface, before the plan is
+/// made. Pack rules and the other limits of the pack can result in more packs than the maximum.
+pub fn estimate_packs(items: &[ItemTemplate], pack_template: &PackTemplate) -> EstimateRange {
+    let (minimum, _) = minimum_number_of_packs(items, pack_template);
+    let maximum = count_greedy_packs(items, pack_template);
+    EstimateRange {
+        minimum,
+        maximum: maximum.max(minimum),
+    }
+}
+
+/// Orders the items according to the sort order of the pack. The items are grouped by their storage class
+/// and their destination first, so that the packs of each group follow each other in the plan. The components
+/// of a kit instance stay together, in their original order, and are sorted by their longest component.
+///
+/// Items with the same length are ordered by the tie break. The sort is stable, so items that are still equal
+/// keep the order in which they were given, and the same input always gives the same order.
+pub fn sort_items(
+    item_templates: Vec<ItemTemplate>,
+    sort_order: PackSortOrder,
+    tie_break: TieBreak,
+) -> Result<Vec<ItemTemplate>, Error> {
+    let mut units: Vec<Vec<ItemTemplate>> = Vec::new();
+    for template in item_templates {
+        match units.last_mut() {
+            Some(unit)
+                if template.attributes.kit_instance.is_some()
+                    && unit[0].attributes.kit_instance == template.attributes.kit_instance =>
+            {
+                unit.push(template)
+            }
+            _ => units.push(vec![template]),
+        }
+    }
+
+    let unit_length =
+        |unit: &[ItemTemplate]| unit.iter().map(|t| t.length).fold(f64::MIN, f64::max);
+    // Compares the groups by reference, as the sort compares each unit many times.
+    let compare_unit_groups = |a: &[ItemTemplate], b: &[ItemTemplate]| {
+        let (a, b) = (&a[0].attributes, &b[0].attributes);
+        a.storage_class
+            .cmp(&b.storage_class)
+            .then_with(|| a.destination.as_deref().cmp(&b.destination.as_deref()))
+    };
+    let unit_tie_break = |a: &[ItemTemplate], b: &[ItemTemplate]| match tie_break {
+        TieBreak::Input => Ordering::Equal,
+        TieBreak::Id => a[0].id.cmp(&b[0].id),
+        TieBreak::Weight => {
+            let unit_weight =
+                |unit: &[ItemTemplate]| unit.iter().map(|t| t.weight).fold(f64::MIN, f64::max);
+            unit_weight(b).total_cmp(&unit_weight(a))
+        }
+    };
+    match sort_order {
+        PackSortOrder::Natural => {
+            // Keep the order in which the items were given within each group
+            units.sort_by(|a, b| compare_unit_groups(a, b));
+        }
+        PackSortOrder::ShortToLong => {
+            units.sort_by(|a, b| {
+                compare_unit_groups(a, b)
+                    .then_with(|| unit_length(a).total_cmp(&unit_length(b)))
+                    .then_with(|| unit_tie_break(a, b))
+            });
+        }
+        PackSortOrder::LongToShort => {
+            units.sort_by(|a, b| {
+                compare_unit_groups(a, b)
+                    .then_with(|| unit_length(b).total_cmp(&unit_length(a)))
+                    .then_with(|| unit_tie_break(a, b))
+            });
+        }
+        PackSortOrder::NotSet => return Err(Error::MissingPackInformation),
+    }
+
+    Ok(units.into_iter().flatten().collect())
+}
+
+/// Orders the packs by the delivery stop of their destination and renumbers them, so that the packs are
+/// listed in the reverse of the order in which they are loaded on the truck. Pack 1 is the last pack that is
+/// loaded, and the first pack that is unloaded. Packs for the same stop keep their order, and packs without a
+/// destination are loaded first. The pack numbers in the warnings are updated to match. Returns an error if a
+/// pack has a destination without a stop.
+pub fn sequence_packs(outcome: PlanOutcome, stops: &[DeliveryStop]) -> Result<PlanOutcome, Error> {
+    let stop_of = |pack: &Pack| match &pack.destination {
+        Some(destination) => stops
+            .iter()
+            .find(|s| s.destination == *destination)
+            .map(|s| s.stop)
+            .ok_or_else(|| Error::UnknownDestination {
+                destination: destination.clone(),
+            }),
+        None => Ok(u32::MAX),
+    };
+
+    let mut keyed_packs = outcome
+        .packs
+        .into_iter()
+        .map(|p| stop_of(&p).map(|stop| (stop, p)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    keyed_packs.sort_by_key(|(stop, _)| *stop);
+
+    let mut pack_numbers = HashMap::new();
+    let mut packs = Vec::with_capacity(keyed_packs.len());
+    for (index, (_, mut pack)) in keyed_packs.into_iter().enumerate() {
+        pack_numbers.insert(pack.number, index + 1);
+        pack.number = index + 1;
+        packs.push(pack);
+    }
+
+    let warnings = renumber_warnings(outcome.warnings, |pack_number| {
+        pack_numbers
+            .get(&pack_number)
+            .copied()
+            .unwrap_or(pack_number)
+    });
+
+    Ok(PlanOutcome {
+        packs,
+        warnings,
+        optimality: None,
+    })
+}
+
+/// Divides the placements of a pack into the smallest groups of items that can be moved to another pack, in
+/// the order of the placements. Each group is a list of placement indices with the number of items of the
+/// placement. A placement of bulk and the components of a kit are never divided, and a batch with a pack
+/// multiple is divided in multiples.
+fn movable_units(pack: &Pack) -> Vec<Vec<(usize, i32)>> {
+    let mut units: Vec<Vec<(usize, i32)>> = Vec::new();
+    let mut kit_units = HashMap::new();
+    for (index, placement) in pack.items.iter().enumerate() {
+        let item = &placement.item;
+        if let Some(kit_instance) = &item.attributes.kit_instance {
+            let kit = (kit_instance.kit_id.clone(), kit_instance.number);
+            let next_unit = units.len();
+            let unit = *kit_units.entry(kit).or_insert(next_unit);
+            if unit == next_unit {
+                units.push(Vec::new());
+            }
+            units[unit].push((index, item.count));
+        } else if item.attributes.bulk {
+            units.push(vec![(index, item.count)]);
+        } else {
+            let multiple = item.attributes.pack_multiple();
+            let mut remaining = item.count;
+            while remaining > 0 {
+                let count = remaining.min(multiple);
+                units.push(vec![(index, count)]);
+                remaining -= count;
+            }
+        }
+    }
+
+    units
+}
+
+/// Returns the number of pieces in a unit of [`movable_units`].
+fn unit_pieces(unit: &[(usize, i32)]) -> i32 {
+    unit.iter().map(|(_, count)| count).sum()
+}
+
+/// Spreads the items of a pack over the given number of packs, with about the same number of pieces in each
+/// pack. The placements keep their order. Items of a placement that are spread over several packs are
+/// placed with the [`PlacementLimit::MinimumPacks`] limit.
+fn split_pack(pack: &Pack, number_of_parts: usize, pack_template: &PackTemplate) -> Vec<Pack> {
+    let units = movable_units(pack);
+    let total_pieces = units.iter().map(|u| unit_pieces(u) as f64).sum::<f64>();
+
+    // Each part ends when the pieces of the earlier parts reach its share of the total.
+    let mut parts: Vec<Vec<(usize, i32)>> = vec![Vec::new(); number_of_parts];
+    let mut part = 0;
+    let mut pieces = 0.0;
+    for unit in units {
+        while part + 1 < number_of_parts
+            && pieces >= (total_pieces * (part + 1) as f64 / number_of_parts as f64).round()
+        {
+            part += 1;
+        }
+        pieces += unit_pieces(&unit) as f64;
+        parts[part].extend(unit);
+    }
+
+    let mut placed = vec![0; pack.items.len()];
+    parts
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .map(|mut part| {
+            part.sort_by_key(|(index, _)| *index);
+            let mut counts: Vec<(usize, i32)> = Vec::new();
+            for (index, count) in part {
+                match counts.last_mut() {
+                    Some((last_index, last_count)) if *last_index == index => *last_count += count,
+                    _ => counts.push((index, count)),
+                }
+            }
+
+            let mut new_pack = Pack::new(pack.number);
+            for (index, count) in counts {
+                let placement = &pack.items[index];
+                if placement.item.attributes.bulk {
+                    new_pack.add_bulk(
+                        pack_template,
+                        &placement.item,
+                        placement.item.weight,
+                        placement
+                            .bulk_remaining_before
+                            .unwrap_or(placement.item.weight),
+                        placement.limit,
+                    );
+                } else if count == placement.item.count {
+                    new_pack.add(
+                        pack_template,
+                        &placement.item,
+                        count,
+                        placement.items_remaining_in_batch,
+                        placement.limit,
+                    );
+                } else {
+                    new_pack.add(
+                        pack_template,
+                        &placement.item,
+                        count,
+                        placement.items_remaining_in_batch - placed[index],
+                        PlacementLimit::MinimumPacks,
+                    );
+                }
+                placed[index] += count;
+            }
+
+            new_pack
+        })
+        .collect()
+}
+
+/// Splits the packs until the plan has at least the given number of packs, e.g. one for each packer that
+/// works in parallel, with about the same number of pieces in each pack. The additional packs go, one at a
+/// time, to the pack with the most pieces per pack, and the packs that are split follow the pack from which
+/// they were split. The packs are renumbered and the pack numbers in the warnings are updated to match.
+///
+/// Oversize and overweight packs and packs on sheets are never split, and neither are the placements of bulk
+/// and the components of a kit, so a plan of few such items may have fewer packs. A plan with pinned items is
+/// returned as is, because the pins refer to the pack numbers.
+pub fn spread_packs(
+    outcome: PlanOutcome,
+    minimum_number_of_packs: usize,
+    pack_template: &PackTemplate,
+) -> PlanOutcome {
+    let has_pinned_items = outcome.packs.iter().any(|p| {
+        p.items
+            .iter()
+            .any(|i| i.item.attributes.pinned_pack.is_some())
+    });
+    if outcome.packs.len() >= minimum_number_of_packs || has_pinned_items {
+        return outcome;
+    }
+
+    let units: Vec<usize> = outcome
+        .packs
+        .iter()
+        .map(|p| {
+            if p.oversize || p.overweight || !p.sheet_placements.is_empty() {
+                1
+            } else {
+                movable_units(p).len()
+            }
+        })
+        .collect();
+    let mut parts = vec![1; outcome.packs.len()];
+    for _ in outcome.packs.len()..minimum_number_of_packs {
+        let busiest = (0..parts.len())
+            .filter(|&i| parts[i] < units[i])
+            .max_by(|&a, &b| {
+                let per_part = |i: usize| outcome.packs[i].item_count as f64 / parts[i] as f64;
+                per_part(a).total_cmp(&per_part(b)).then(b.cmp(&a))
+            });
+        match busiest {
+            Some(index) => parts[index] += 1,
+            None => break,
+        }
+    }
+
+    let first_number = outcome.packs.first().map_or(1, |p| p.number);
+    let mut pack_numbers = HashMap::new();
+    let mut packs = Vec::with_capacity(minimum_number_of_packs);
+    for (pack, number_of_parts) in outcome.packs.into_iter().zip(parts) {
+        pack_numbers.insert(pack.number, first_number + packs.len());
+        if number_of_parts == 1 {
+            packs.push(pack);
+        } else {
+            packs.extend(split_pack(&pack, number_of_parts, pack_template));
+        }
+    }
+    for (index, pack) in packs.iter_mut().enumerate() {
+        pack.number = first_number + index;
+    }
+
+    let warnings = renumber_warnings(outcome.warnings, |pack_number| {
+        pack_numbers
+            .get(&pack_number)
+            .copied()
+            .unwrap_or(pack_number)
+    });
+
+    PlanOutcome {
+        packs,
+        warnings,
+        optimality: outcome.optimality,
+    }
+}
+
+/// Adds the offset to the number of each pack, so that the packs of the plan continue the numbering of
+/// earlier plans. The pack numbers in the warnings are updated to match.
+pub fn offset_pack_numbers(outcome: PlanOutcome, offset: usize) -> PlanOutcome {
+    let packs = outcome
+        .packs
+        .into_iter()
+        .map(|mut p| {
+            p.number += offset;
+            p
+        })
+        .collect();
+    let warnings = renumber_warnings(outcome.warnings, |pack_number| pack_number + offset);
+
+    PlanOutcome {
+        packs,
+        warnings,
+        optimality: outcome.optimality,
+    }
+}
+
+// Replaces the pack numbers in the warnings that refer to a pack
+fn renumber_warnings(
+    warnings: Vec<PlanWarning>,
+    renumber: impl Fn(usize) -> usize,
+) -> Vec<PlanWarning> {
+    warnings
+        .into_iter()
+        .map(|w| match w {
+            PlanWarning::EmptyPack { pack_number } => PlanWarning::EmptyPack {
+                pack_number: renumber(pack_number),
+            },
+            PlanWarning::OverweightPack {
+                pack_number,
+                weight,
+                maximum_weight,
+            } => PlanWarning::OverweightPack {
+                pack_number: renumber(pack_number),
+                weight,
+                maximum_weight,
+            },
+            PlanWarning::MaximumValueExceeded {
+                pack_number,
+                value,
+                maximum_value,
+            } => PlanWarning::MaximumValueExceeded {
+                pack_number: renumber(pack_number),
+                value,
+                maximum_value,
+            },
+            PlanWarning::OversizePack {
+                pack_number,
+                item_id,
+            } => PlanWarning::OversizePack {
+                pack_number: renumber(pack_number),
+                item_id,
+            },
+            w => w,
+        })
+        .collect()
+}
+
+/// Returns the limit that keeps a single item of the template, or all of the bulk, out of an empty pack, or
+/// `None` if it fits.
+pub(crate) fn infeasible_limit(
+    template: &ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Option<PlacementLimit> {
+    if let (Some(sheet), Some(width)) = (&pack_template.sheet, template.attributes.width) {
+        if orient_on_sheet(template.length, width, sheet).is_none() {
+            return Some(PlacementLimit::Sheet);
+        }
+    }
+
+    let empty_pack = Pack::new(0);
+    let (fits, limit) = if template.attributes.bulk {
+        let (weight, limit) =
+            bulk_weight_to_place(pack_template, &empty_pack, template, rules, template.weight);
+        (weight > 0.0, limit)
+    } else {
+        let (items_to_place, limit) = number_of_items_to_place(
+            pack_template,
+            &empty_pack,
+            template,
+            rules,
+            template.attributes.pack_multiple(),
+        );
+        (items_to_place > 0, limit)
+    };
+
+    if fits {
+        None
+    } else {
+        Some(limit)
+    }
+}
+
+/// Returns `true` if a single item of the template, or all of the bulk, doesn't fit in an empty pack.
+pub(crate) fn is_infeasible(
+    template: &ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> bool {
+    infeasible_limit(template, pack_template, rules).is_some()
+}
+
+/// Verifies that a pack can hold a weight and a number of pieces at all. The pack template is the one
+/// before the tare weight is subtracted, so that the error names the limits as they were provided.
+pub fn verify_pack_capacity(pack_template: &PackTemplate) -> Result<(), Error> {
+    if pack_template.maximum_weight <= pack_template.tare_weight {
+        return Err(Error::PackHasNoWeightCapacity {
+            maximum_weight: pack_template.maximum_weight,
+            tare_weight: pack_template.tare_weight,
+        });
+    }
+
+    if pack_template.maximum_number_of_pieces <= 0 {
+        return Err(Error::PackHasNoPieceCapacity {
+            maximum_number_of_pieces: pack_template.maximum_number_of_pieces,
+        });
+    }
+
+    Ok(())
+}
+
+/// Describes how a single item of the template exceeds the limit of an empty pack.
+fn describe_violation(
+    template: &ItemTemplate,
+    pack_template: &PackTemplate,
+    limit: PlacementLimit,
+) -> String {
+    let limits = pack_template.limits_for(&Pack::new(0), template);
+    match (limit, template.attributes.pack_multiple()) {
+        (PlacementLimit::Weight, _) => format!(
+            "a single piece weighs {} while a pack holds at most {}",
+            template.weight, limits.maximum_weight
+        ),
+        (PlacementLimit::PieceCount, 1) => format!(
+            "a pack holds at most {} pieces",
+            limits.maximum_number_of_pieces
+        ),
+        (PlacementLimit::PieceCount, multiple) => format!(
+            "the item is packed in multiples of {} while a pack holds at most {} pieces",
+            multiple, limits.maximum_number_of_pieces
+        ),
+        (PlacementLimit::Length, _) => match limits.maximum_length {
+            Some(maximum_length) => format!(
+                "a single piece has a length of {} while a pack has a maximum length of {}",
+                template.length, maximum_length
+            ),
+            None => "a single piece is longer than a pack allows".to_string(),
+        },
+        (PlacementLimit::Value, _) => match (template.attributes.value, limits.maximum_value) {
+            (Some(value), Some(maximum_value)) if !template.attributes.bulk => format!(
+                "a single piece has a value of {} while a pack has a maximum value of {}",
+                value, maximum_value
+            ),
+            _ => "the item is worth more than a pack may hold".to_string(),
+        },
+        (PlacementLimit::Sheet, _) => match (&limits.sheet, template.attributes.width) {
+            (Some(sheet), Some(width)) => format!(
+                "a piece of {} by {} doesn't fit on a sheet of {} by {}",
+                template.length, width, sheet.length, sheet.width
+            ),
+            _ => "a single piece doesn't fit on a sheet".to_string(),
+        },
+        (PlacementLimit::Rule, _) => {
+            "the pack rules don't allow a single piece of the item in a pack".to_string()
+        }
+        _ => "a single piece doesn't fit in an empty pack".to_string(),
+    }
+}
+
+/// Verifies that at least one of the items fits in an empty pack. When none of the items fit, the limits of
+/// the pack are most likely wrong, e.g. given in a different unit than the items, so the error names the
+/// limit that the first item exceeds and by how much, instead of the planner failing on the first item.
+pub fn verify_some_item_fits(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Result<(), Error> {
+    let mut first_violation = None;
+    for template in items.iter().filter(|t| t.count > 0) {
+        match infeasible_limit(template, pack_template, rules) {
+            Some(limit) => {
+                first_violation.get_or_insert((template, limit));
+            }
+            None => return Ok(()),
+        }
+    }
+
+    match first_violation {
+        Some((template, limit)) => Err(Error::NoItemFitsInEmptyPack {
+            item_id: template.id.to_string(),
+            limit: limit.to_string(),
+            reason: describe_violation(template, pack_template, limit),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Separates the items that don't fit in an empty pack from the other items, so that the other items can be
+/// planned. Pinned items, the components of kits that are kept together, and batches without any items are
+/// never separated. Returns the items that fit, and
+/// the items that don't fit, in their original order.
+pub fn separate_infeasible_items(
+    items: Vec<ItemTemplate>,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> (Vec<ItemTemplate>, Vec<ItemTemplate>) {
+    items.into_iter().partition(|t| {
+        t.count <= 0
+            || t.attributes.pinned_pack.is_some()
+            || t.attributes.kit_instance.is_some()
+            || !is_infeasible(t, pack_template, rules)
+    })
+}
+
+/// Handles the items that don't fit in an empty pack, as separated by [`separate_infeasible_items`],
+/// according to the policy. Skipped items are reported as a warning. With [`InfeasibleItemPolicy::OwnPack`]
+/// each item, or all of the bulk, is placed on its own in an oversize pack after the other packs, and each
+/// oversize pack is reported as a warning. Returns an error for the first item if the policy is
+/// [`InfeasibleItemPolicy::Error`].
+pub fn place_infeasible_items(
+    mut outcome: PlanOutcome,
+    infeasible_items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    policy: InfeasibleItemPolicy,
+) -> Result<PlanOutcome, Error> {
+    for template in infeasible_items {
+        match policy {
+            InfeasibleItemPolicy::Error => {
+                return Err(Error::ItemDoesNotFitInEmptyPack {
+                    item_id: template.id.to_string(),
+                })
+            }
+            InfeasibleItemPolicy::Skip => {
+                outcome.warnings.push(PlanWarning::InfeasibleItemSkipped {
+                    item_id: template.id.to_string(),
+                    count: template.count,
+                });
+            }
+            InfeasibleItemPolicy::OwnPack => {
+                let number_of_packs = if template.attributes.bulk {
+                    1
+                } else {
+                    template.count
+                };
+                for index in 0..number_of_packs {
+                    let number = outcome.packs.last().map_or(0, |p| p.number) + 1;
+                    let mut pack = Pack::new(number);
+                    if template.attributes.bulk {
+                        pack.add_bulk(
+                            pack_template,
+                            template,
+                            template.weight,
+                            template.weight,
+                            PlacementLimit::None,
+                        );
+                    } else {
+                        pack.add(
+                            pack_template,
+                            template,
+                            1,
+                            template.count - index,
+                            PlacementLimit::None,
+                        );
+                    }
+                    pack.oversize = true;
+
+                    outcome.warnings.push(PlanWarning::OversizePack {
+                        pack_number: number,
+                        item_id: template.id.to_string(),
+                    });
+                    outcome.packs.push(pack);
+                }
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Checks, and with the `Merge` policy combines, the batches of items that share an ID. Batches that are
+/// combined keep the position of the first batch.
+///
+/// Returns an error if the policy isn't `Allow` and two batches with the same ID have a different length or
+/// weight.
+pub fn resolve_duplicate_ids(
+    item_templates: Vec<ItemTemplate>,
+    policy: DuplicateIdPolicy,
+) -> Result<Vec<ItemTemplate>, Error> {
+    if policy == DuplicateIdPolicy::Allow {
+        return Ok(item_templates);
+    }
+
+    let mut first_batches: HashMap<Arc<str>, usize> = HashMap::new();
+    let mut resolved: Vec<ItemTemplate> = Vec::with_capacity(item_templates.len());
+    for template in item_templates {
+        let first = match first_batches.get(&template.id) {
+            Some(&index) => &mut resolved[index],
+            None => {
+                first_batches.insert(template.id.clone(), resolved.len());
+                resolved.push(template);
+                continue;
+            }
+        };
+
+        if first.length != template.length || first.weight != template.weight {
+            return Err(Error::ConflictingDuplicateItem {
+                item_id: template.id.to_string(),
+                length: first.length,
+                weight: first.weight,
+                other_length: template.length,
+                other_weight: template.weight,
+            });
+        }
+
+        if policy == DuplicateIdPolicy::Merge && first.attributes == template.attributes {
+            first.count += template.count;
+        } else {
+            resolved.push(template);
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Splits each batch that fills at least one empty pack on its own into the items that fill whole packs and
+/// the remaining items, so that the whole packs hold a single item ID. The packs are closed before and after
+/// the whole packs, and the remaining items are mixed with the next batches. The order of the items is kept.
+/// Bulk items, pinned items and the components of kits are not split.
+pub fn prefer_homogeneous_packs(
+    item_templates: Vec<ItemTemplate>,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Vec<ItemTemplate> {
+    let empty_pack = Pack::new(1);
+    let mut split_items = Vec::with_capacity(item_templates.len());
+    let mut close_pack_before_next_item = false;
+    for mut template in item_templates {
+        if close_pack_before_next_item {
+            Arc::make_mut(&mut template.attributes).close_pack_before = true;
+            close_pack_before_next_item = false;
+        }
+
+        let is_splittable = template.count > 0
+            && !template.attributes.bulk
+            && template.attributes.pinned_pack.is_none()
+            && template.attributes.kit_instance.is_none();
+        let items_per_pack = if is_splittable {
+            number_of_items_to_place(pack_template, &empty_pack, &template, rules, i32::MAX).0
+        } else {
+            0
+        };
+        if items_per_pack <= 0 || template.count < items_per_pack {
+            split_items.push(template);
+            continue;
+        }
+
+        let items_in_whole_packs = template.count - template.count % items_per_pack;
+        let mut whole_packs = template.clone();
+        whole_packs.count = items_in_whole_packs;
+        Arc::make_mut(&mut whole_packs.attributes).close_pack_before = true;
+        split_items.push(whole_packs);
+
+        if template.count > items_in_whole_packs {
+            template.count -= items_in_whole_packs;
+            Arc::make_mut(&mut template.attributes).close_pack_before = true;
+            split_items.push(template);
+        } else {
+            close_pack_before_next_item = true;
+        }
+    }
+
+    split_items
+}
+
+/// Removes the items that don't match all the filters, and then applies the maps, in order, to the remaining
+/// items. The filters are applied to the items as provided, so a map doesn't change which items are planned.
+/// Returns an error if a map gives an item a negative value, or a value that is not a finite number.
+pub fn filter_and_map_items(
+    item_templates: Vec<ItemTemplate>,
+    filters: &[ItemFilter],
+    maps: &[ItemMap],
+) -> Result<Vec<ItemTemplate>, Error> {
+    item_templates
+        .into_iter()
+        .filter(|t| filters.iter().all(|f| f.matches(t)))
+        .map(|mut t| {
+            for map in maps {
+                map.apply(&mut t)?;
+            }
+            Ok(t)
+        })
+        .collect()
+}
+
+/// Replaces the IDs of the items that are aliases by the canonical IDs. The original ID of each replaced item
+/// is kept in the `alias_of` attribute of the item.
+pub fn apply_aliases(
+    item_templates: Vec<ItemTemplate>,
+    aliases: &ItemAliases,
+) -> Vec<ItemTemplate> {
+    if aliases.aliases.is_empty() {
+        return item_templates;
+    }
+
+    item_templates
+        .into_iter()
+        .map(|t| match aliases.aliases.get(&*t.id) {
+            Some(canonical_id) => ItemTemplate {
+                id: canonical_id.as_str().into(),
+                attributes: Arc::new(ItemAttributes {
+                    alias_of: Some(t.id.to_string()),
+                    ..(*t.attributes).clone()
+                }),
+                ..t
+            },
+            None => t,
+        })
+        .collect()
+}
+
+/// Removes the items of which the ID matches one of the patterns. Each batch that is removed is reported as a
+/// warning.
+pub fn exclude_items(
+    item_templates: Vec<ItemTemplate>,
+    patterns: &[IdPattern],
+) -> (Vec<ItemTemplate>, Vec<PlanWarning>) {
+    if patterns.is_empty() {
+        return (item_templates, Vec::new());
+    }
+
+    let (excluded, included): (Vec<ItemTemplate>, Vec<ItemTemplate>) = item_templates
+        .into_iter()
+        .partition(|t| patterns.iter().any(|p| p.matches(&t.id)));
+    let warnings = excluded
+        .into_iter()
+        .map(|t| PlanWarning::ItemExcluded {
+            item_id: t.id.to_string(),
+            count: t.count,
+        })
+        .collect();
+
+    (included, warnings)
+}
+
+/// Removes the items of each batch that don't make up a full multiple of the pack multiple of the item, so
+/// that only full multiples are planned. Each batch that is reduced is reported as a warning, and a batch
+/// that holds less than a single multiple is dropped, so that it isn't reported as an empty batch as well.
+pub fn round_to_multiples(
+    mut item_templates: Vec<ItemTemplate>,
+) -> (Vec<ItemTemplate>, Vec<PlanWarning>) {
+    let mut warnings = Vec::new();
+    item_templates.retain_mut(|template| {
+        let multiple = template.attributes.pack_multiple();
+        let leftover = template.count.max(0) % multiple;
+        if leftover == 0 {
+            return true;
+        }
+
+        template.count -= leftover;
+        warnings.push(PlanWarning::IncompleteMultiple {
+            item_id: template.id.to_string(),
+            multiple,
+            count: leftover,
+        });
+        template.count > 0
+    });
+
+    (item_templates, warnings)
+}
+
+// The share of a cut increment, or of a total length, that is ignored when the total length is cut, so that
+// rounding errors don't leave a tiny last piece
+const CUT_LENGTH_TOLERANCE: f64 = 1e-9;
+
+/// Cuts the items that are provided as a total length into pieces, see [`crate::model::CutItem`]. The pieces
+/// are as long as possible, but no longer than the maximum length of the pack and no heavier than the maximum
+/// weight of the pack, and their length is a multiple of the cut increment of the item, if any. The pieces of
+/// the same length form a single batch, and the last piece holds what remains of the total length. A piece of
+/// a single increment is cut even if it doesn't fit in an empty pack, so that it is reported like any other
+/// item that doesn't fit. The order of the items is kept, and the value of a piece is the value per unit of
+/// length times its length. Returns an error if an item would be cut into more pieces than a batch can hold.
+pub fn cut_items(
+    item_templates: Vec<ItemTemplate>,
+    pack_template: &PackTemplate,
+) -> Result<Vec<ItemTemplate>, Error> {
+    let mut items = Vec::with_capacity(item_templates.len());
+    for template in item_templates {
+        let cut = match template.attributes.cut {
+            Some(cut) => cut,
+            None => {
+                items.push(template);
+                continue;
+            }
+        };
+
+        let total_length = template.length;
+        let mut piece_length = total_length;
+        if let Some(maximum_length) = pack_template.maximum_length {
+            piece_length = piece_length.min(maximum_length);
+        }
+        if pack_template.maximum_weight > 0.0 {
+            piece_length = piece_length.min(pack_template.maximum_weight / cut.weight_per_length);
+        }
+        if let Some(increment) = cut.increment {
+            let increments = (piece_length / increment + CUT_LENGTH_TOLERANCE).floor();
+            piece_length = (increments.max(1.0) * increment).min(total_length);
+        }
+
+        let pieces = (total_length / piece_length + CUT_LENGTH_TOLERANCE).floor();
+        if pieces > i32::MAX as f64 {
+            return Err(Error::TooManyCutPieces {
+                item_id: template.id.to_string(),
+                pieces,
+            });
+        }
+
+        let remainder = total_length - pieces * piece_length;
+        let piece = |length: f64, count: i32| ItemTemplate {
+            id: template.id.clone(),
+            length,
+            weight: length * cut.weight_per_length,
+            count,
+            attributes: Arc::new(ItemAttributes {
+                value: template.attributes.value.map(|v| v * length),
+                cut: None,
+                ..(*template.attributes).clone()
+            }),
+        };
+        items.push(piece(piece_length, pieces as i32));
+        if remainder > total_length * CUT_LENGTH_TOLERANCE {
+            items.push(piece(remainder, 1));
+        }
+    }
+
+    Ok(items)
+}
+
+/// Removes the items that exceed the limits of their destination, in the order in which the items are planned,
+/// so that the items planned last are left for the next run. Only the full multiples of the pack multiple of
+/// an item are kept, and a bulk item is either kept in full or left out in full. Each batch that is reduced is
+/// reported as a warning.
+pub fn apply_destination_limits(
+    mut item_templates: Vec<ItemTemplate>,
+    destination_limits: &[DestinationLimits],
+) -> (Vec<ItemTemplate>, Vec<PlanWarning>) {
+    if destination_limits.is_empty() {
+        return (item_templates, Vec::new());
+    }
+
+    // The number of pieces and the weight that are still available for each destination
+    let mut remaining: HashMap<&str, (i32, f64)> = destination_limits
+        .iter()
+        .map(|l| {
+            (
+                l.destination.as_str(),
+                (
+                    l.maximum_number_of_pieces.unwrap_or(i32::MAX),
+                    l.maximum_weight.unwrap_or(f64::INFINITY),
+                ),
+            )
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    item_templates.retain_mut(|template| {
+        let destination = match template.attributes.destination.as_deref() {
+            Some(d) => d,
+            None => return true,
+        };
+        let (remaining_pieces, remaining_weight) = match remaining.get_mut(destination) {
+            Some(r) => r,
+            None => return true,
+        };
+
+        let count = template.count.max(0);
+        let kept = if template.attributes.bulk {
+            if *remaining_pieces >= 1 && template.weight <= *remaining_weight {
+                count
+            } else {
+                0
+            }
+        } else {
+            let fitting_by_weight = if template.weight > 0.0 {
+                (*remaining_weight / template.weight)
+                    .floor()
+                    .min(count as f64) as i32
+            } else {
+                count
+            };
+            let fitting = count.min(*remaining_pieces).min(fitting_by_weight);
+            fitting - fitting % template.attributes.pack_multiple()
+        };
+
+        // The weight of a bulk item is the weight of the whole bulk, which has a count of 1.
+        *remaining_pieces -= kept;
+        *remaining_weight -= template.weight * kept as f64;
+        if kept == count {
+            return true;
+        }
+
+        warnings.push(PlanWarning::DestinationLimitReached {
+            item_id: template.id.to_string(),
+            destination: destination.to_string(),
+            count: count - kept,
+        });
+        template.count = kept;
+        kept > 0
+    });
+
+    (item_templates, warnings)
+}
+
+/// Replaces each item that refers to a substitution group by members of the group, from the substitution
+/// groups. The other items are planned first, and for each group the member of which the most items fit in
+/// the remaining space of those packs is selected, up to the number of items of the member that is available.
+/// The rest of the requested items are taken from the members in the order of preference, and take up new
+/// packs. Each selected member is reported as a warning.
+///
+/// Returns an error if an item refers to an unknown group, or if the members of a group don't have enough
+/// items for the requested number.
+pub fn resolve_substitutions(
+    item_templates: Vec<ItemTemplate>,
+    substitution_groups: &SubstitutionGroups,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    tie_break: TieBreak,
+) -> Result<(Vec<ItemTemplate>, Vec<PlanWarning>), Error> {
+    if !item_templates
+        .iter()
+        .any(|t| t.attributes.substitution_group)
+    {
+        return Ok((item_templates, Vec::new()));
+    }
+
+    // Any issue with the other items is reported when the packs are planned.
+    let other_items: Vec<ItemTemplate> = item_templates
+        .iter()
+        .filter(|t| !t.attributes.substitution_group)
+        .cloned()
+        .collect();
+    let mut packs = sort_items(other_items, pack_template.sort_order, tie_break)
+        .and_then(|items| plan_packs(&items, pack_template, rules))
+        .map(|outcome| outcome.packs)
+        .unwrap_or_default();
+
+    let mut available_by_group: HashMap<&str, Vec<i32>> = HashMap::new();
+    let mut items = Vec::with_capacity(item_templates.len());
+    let mut warnings = Vec::new();
+    for template in &item_templates {
+        if !template.attributes.substitution_group {
+            items.push(template.clone());
+            continue;
+        }
+
+        let (group_id, members) = substitution_groups
+            .groups
+            .get_key_value(&*template.id)
+            .ok_or_else(|| Error::UnknownSubstitutionGroup {
+                group_id: template.id.to_string(),
+            })?;
+        let available = available_by_group
+            .entry(group_id)
+            .or_insert_with(|| members.iter().map(|m| m.count.max(0)).collect());
+
+        let mut selected = vec![0; members.len()];
+        let mut items_left = template.count;
+        while items_left > 0 {
+            // The member of which the most items fit, where the first member wins a tie.
+            let mut best: Option<(usize, i32, Vec<Pack>)> = None;
+            for (index, member) in members.iter().enumerate() {
+                let count = items_left.min(available[index]);
+                if count <= 0 {
+                    continue;
+                }
+
+                let candidate = ItemTemplate {
+                    count,
+                    ..member.clone()
+                };
+                let mut candidate_packs = packs.clone();
+                let items_fitted: i32 =
+                    add_to_existing_packs(&mut candidate_packs, &candidate, pack_template, rules)
+                        .iter()
+                        .map(|(_, items_added)| items_added)
+                        .sum();
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, most_fitted, _)| items_fitted > *most_fitted)
+                {
+                    best = Some((index, items_fitted, candidate_packs));
+                }
+            }
+
+            let (index, items_fitted, candidate_packs) =
+                best.ok_or_else(|| Error::InsufficientSubstitutes {
+                    group_id: group_id.to_string(),
+                    count: items_left,
+                })?;
+            let count = if items_fitted > 0 {
+                packs = candidate_packs;
+                items_fitted
+            } else {
+                items_left.min(available[index])
+            };
+
+            selected[index] += count;
+            available[index] -= count;
+            items_left -= count;
+        }
+
+        for (member, count) in members.iter().zip(selected) {
+            if count <= 0 {
+                continue;
+            }
+
+            warnings.push(PlanWarning::ItemSubstituted {
+                group_id: group_id.to_string(),
+                item_id: member.id.to_string(),
+                count,
+            });
+            items.push(ItemTemplate {
+                count,
+                ..member.clone()
+            });
+        }
+    }
+
+    Ok((items, warnings))
+}
+
+/// Replaces each item that refers to a kit by the components of the kit, from the bill of materials. When
+/// the kits should be kept together, each kit is replaced by its own set of components, which are marked
+/// with the kit instance, so that the planner places them in the same pack. Otherwise the components of all
+/// the kits on a line are combined.
+pub fn expand_kits(
+    item_templates: Vec<ItemTemplate>,
+    bill_of_materials: &BillOfMaterials,
+    keep_together: bool,
+) -> Result<Vec<ItemTemplate>, Error> {
+    let mut items = Vec::with_capacity(item_templates.len());
+    let mut number_of_kits = 0;
+    for template in item_templates {
+        if !template.attributes.kit {
+            items.push(template);
+            continue;
+        }
+
+        let components =
+            bill_of_materials
+                .kits
+                .get(&*template.id)
+                .ok_or_else(|| Error::UnknownKit {
+                    kit_id: template.id.to_string(),
+                })?;
+
+        if !keep_together {
+            items.extend(components.iter().map(|c| ItemTemplate {
+                count: c.count * template.count,
+                ..c.clone()
+            }));
+            continue;
+        }
+
+        for _ in 0..template.count {
+            number_of_kits += 1;
+            let kit_instance = KitInstance {
+                kit_id: template.id.clone(),
+                number: number_of_kits,
+            };
+            items.extend(components.iter().map(|c| ItemTemplate {
+                attributes: Arc::new(ItemAttributes {
+                    kit_instance: Some(kit_instance.clone()),
+                    ..(*c.attributes).clone()
+                }),
+                ..c.clone()
+            }));
+        }
+    }
+
+    Ok(items)
+}
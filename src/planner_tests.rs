@@ -0,0 +1,2690 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::*;
+use crate::model::{ClassLimits, LengthMode, NestingRule, PackDimensions, StorageClass};
+
+// maximum_number_of_items_to_add
+
+#[test]
+fn when_finding_the_maximum_items_with_a_weight_limit_it_should_return_the_correct_count() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::NotSet,
+        ..PackTemplate::new()
+    };
+    let current_pack_weight = 30.0;
+    let current_pack_item_count = 5;
+    let template = ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 5.0,
+        count: 1,
+        attributes: Arc::default(),
+    };
+    assert_eq!(
+        maximum_number_of_items_to_add(
+            &pack_template,
+            current_pack_weight,
+            current_pack_item_count,
+            &template
+        ),
+        4
+    );
+}
+
+#[test]
+fn when_finding_the_maximum_items_with_an_item_limit_it_should_return_the_correct_count() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::NotSet,
+        ..PackTemplate::new()
+    };
+    let current_pack_weight = 20.0;
+    let current_pack_item_count = 9;
+    let template = ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 5.0,
+        count: 1,
+        attributes: Arc::default(),
+    };
+    assert_eq!(
+        maximum_number_of_items_to_add(
+            &pack_template,
+            current_pack_weight,
+            current_pack_item_count,
+            &template
+        ),
+        1
+    );
+}
+
+#[test]
+fn when_finding_the_maximum_items_with_no_limit_it_should_return_the_correct_count() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::NotSet,
+        ..PackTemplate::new()
+    };
+    let current_pack_weight = 45.0;
+    let current_pack_item_count = 9;
+    let template = ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 5.0,
+        count: 1,
+        attributes: Arc::default(),
+    };
+    assert_eq!(
+        maximum_number_of_items_to_add(
+            &pack_template,
+            current_pack_weight,
+            current_pack_item_count,
+            &template
+        ),
+        1
+    );
+}
+
+// plan_packs()
+
+#[test]
+fn when_planning_items_that_exceed_a_pack_it_should_start_a_new_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 3,
+        maximum_weight: 500.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate {
+            id: "1001".into(),
+            length: 6200.0,
+            weight: 9.653,
+            count: 3,
+            attributes: Arc::default(),
+        },
+        ItemTemplate {
+            id: "2001".into(),
+            length: 7200.0,
+            weight: 11.21,
+            count: 4,
+            attributes: Arc::default(),
+        },
+    ];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 3);
+
+    assert_eq!(packs[0].number, 1);
+    assert_eq!(packs[0].items.len(), 1);
+    assert_eq!(&*packs[0].items[0].item.id, "1001");
+    assert_eq!(packs[0].items[0].item.count, 3);
+    assert_eq!(packs[0].length, 6200.0);
+
+    assert_eq!(packs[1].number, 2);
+    assert_eq!(&*packs[1].items[0].item.id, "2001");
+    assert_eq!(packs[1].items[0].item.count, 3);
+
+    assert_eq!(packs[2].number, 3);
+    assert_eq!(packs[2].items[0].item.count, 1);
+    assert_eq!(packs[2].item_count, 1);
+    assert_eq!(packs[2].weight, 11.21);
+}
+
+#[test]
+fn when_planning_items_that_fit_in_one_pack_it_should_mix_the_items() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 500.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate {
+            id: "1001".into(),
+            length: 6200.0,
+            weight: 9.653,
+            count: 30,
+            attributes: Arc::default(),
+        },
+        ItemTemplate {
+            id: "2001".into(),
+            length: 7200.0,
+            weight: 11.21,
+            count: 50,
+            attributes: Arc::default(),
+        },
+    ];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+    assert_eq!(packs[0].items.len(), 2);
+    assert_eq!(packs[0].items[1].item.count, 10);
+    assert_eq!(packs[0].length, 7200.0);
+    assert_eq!(packs[1].items[0].item.count, 40);
+}
+
+#[test]
+fn when_finding_the_limit_for_a_batch_that_fits_it_should_return_none() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::NotSet,
+        ..PackTemplate::new()
+    };
+    let template = ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 5.0,
+        count: 2,
+        attributes: Arc::default(),
+    };
+    assert_eq!(
+        limit_for_items_to_add(&pack_template, 0.0, 0, &template, 2),
+        PlacementLimit::None
+    );
+}
+
+#[test]
+fn when_finding_the_limit_for_a_batch_limited_by_weight_it_should_return_weight() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::NotSet,
+        ..PackTemplate::new()
+    };
+    let template = ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 5.0,
+        count: 8,
+        attributes: Arc::default(),
+    };
+    assert_eq!(
+        limit_for_items_to_add(&pack_template, 30.0, 0, &template, 8),
+        PlacementLimit::Weight
+    );
+}
+
+#[test]
+fn when_finding_the_limit_for_a_batch_limited_by_pieces_it_should_return_piece_count() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::NotSet,
+        ..PackTemplate::new()
+    };
+    let template = ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 1.0,
+        count: 8,
+        attributes: Arc::default(),
+    };
+    assert_eq!(
+        limit_for_items_to_add(&pack_template, 0.0, 5, &template, 8),
+        PlacementLimit::PieceCount
+    );
+}
+
+#[test]
+fn when_planning_items_it_should_record_the_remaining_capacity_of_each_placement() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 4.0,
+        count: 15,
+        attributes: Arc::default(),
+    }];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    let placement = &packs[0].items[0];
+    assert_eq!(placement.items_remaining_in_batch, 15);
+    assert_eq!(placement.limit, PlacementLimit::PieceCount);
+    assert_eq!(placement.remaining_weight_before, 50.0);
+    assert_eq!(placement.remaining_weight_after(), 10.0);
+    assert_eq!(placement.remaining_pieces_before, 10);
+    assert_eq!(placement.remaining_pieces_after(), 0);
+}
+
+#[test]
+fn when_planning_with_the_sum_length_mode_it_should_add_the_item_lengths() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        length_mode: LengthMode::Sum,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,100,2,1.0").unwrap(),
+        ItemTemplate::from_str("2,50,1,1.0").unwrap(),
+    ];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs[0].length, 250.0);
+}
+
+#[test]
+fn when_planning_with_the_sum_with_gap_length_mode_it_should_add_a_gap_between_the_items() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        length_mode: LengthMode::SumWithGap(10.0),
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,100,2,1.0").unwrap(),
+        ItemTemplate::from_str("2,50,1,1.0").unwrap(),
+    ];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs[0].length, 270.0);
+}
+
+#[test]
+fn when_planning_with_a_maximum_length_it_should_start_a_new_pack_when_the_length_is_reached() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        length_mode: LengthMode::SumWithGap(10.0),
+        maximum_length: Some(320.0),
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,100,4,1.0").unwrap()];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+    assert_eq!(packs[0].items[0].item.count, 3);
+    assert_eq!(packs[0].items[0].limit, PlacementLimit::Length);
+    assert_eq!(packs[0].length, 320.0);
+    assert_eq!(packs[1].items[0].item.count, 1);
+    assert_eq!(packs[1].length, 100.0);
+}
+
+#[test]
+fn when_planning_with_a_maximum_length_and_the_maximum_length_mode_it_should_only_limit_single_items(
+) {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        length_mode: LengthMode::Maximum,
+        maximum_length: Some(150.0),
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,100,4,1.0").unwrap()];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 1);
+    assert_eq!(packs[0].length, 100.0);
+}
+
+#[test]
+fn when_planning_items_that_nest_it_should_place_more_items_within_the_maximum_length() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        length_mode: LengthMode::Sum,
+        maximum_length: Some(300.0),
+        nesting_rules: vec![NestingRule {
+            inner_item_id: "rod".to_string(),
+            outer_item_id: "tube".to_string(),
+        }],
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("tube,100,2,1.0").unwrap(),
+        ItemTemplate::from_str("rod,80,4,1.0").unwrap(),
+    ];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+    assert_eq!(packs[0].items[1].item.count, 3);
+    assert_eq!(packs[0].items[1].limit, PlacementLimit::Length);
+    assert_eq!(packs[0].length, 280.0);
+}
+
+// sort_items()
+
+#[test]
+fn when_sorting_items_short_to_long_it_should_order_by_increasing_length() {
+    let items = vec![
+        ItemTemplate::from_str("1,20,1,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,1.0").unwrap(),
+    ];
+    let sorted = sort_items(items, PackSortOrder::ShortToLong, TieBreak::Input)
+        .expect("Failed to sort the items.");
+    assert_eq!(&*sorted[0].id, "2");
+    assert_eq!(&*sorted[1].id, "1");
+}
+
+#[test]
+fn when_sorting_items_with_the_same_length_it_should_keep_the_input_order() {
+    let items = vec![
+        ItemTemplate::from_str("3,10,1,1.0").unwrap(),
+        ItemTemplate::from_str("1,20,1,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,1.0").unwrap(),
+        ItemTemplate::from_str("4,10,1,1.0").unwrap(),
+    ];
+
+    let sorted = sort_items(items.clone(), PackSortOrder::ShortToLong, TieBreak::Input)
+        .expect("Failed to sort the items.");
+    let ids: Vec<&str> = sorted.iter().map(|t| &*t.id).collect();
+    assert_eq!(ids, vec!["3", "2", "4", "1"]);
+
+    let sorted = sort_items(items, PackSortOrder::LongToShort, TieBreak::Input)
+        .expect("Failed to sort the items.");
+    let ids: Vec<&str> = sorted.iter().map(|t| &*t.id).collect();
+    assert_eq!(ids, vec!["1", "3", "2", "4"]);
+}
+
+#[test]
+fn when_sorting_items_with_the_same_length_by_id_it_should_not_depend_on_the_input_order() {
+    let items = vec![
+        ItemTemplate::from_str("3,10,1,1.0").unwrap(),
+        ItemTemplate::from_str("1,20,1,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,1.0").unwrap(),
+    ];
+    let mut reversed = items.clone();
+    reversed.reverse();
+
+    for items in [items, reversed] {
+        let sorted = sort_items(items, PackSortOrder::ShortToLong, TieBreak::Id)
+            .expect("Failed to sort the items.");
+        let ids: Vec<&str> = sorted.iter().map(|t| &*t.id).collect();
+        assert_eq!(ids, vec!["2", "3", "1"]);
+    }
+}
+
+#[test]
+fn when_sorting_items_with_the_same_length_by_weight_it_should_place_the_heaviest_first() {
+    let items = vec![
+        ItemTemplate::from_str("1,10,1,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,3.0").unwrap(),
+        ItemTemplate::from_str("3,10,1,2.0").unwrap(),
+        ItemTemplate::from_str("4,10,1,3.0").unwrap(),
+    ];
+
+    let sorted = sort_items(items, PackSortOrder::LongToShort, TieBreak::Weight)
+        .expect("Failed to sort the items.");
+    let ids: Vec<&str> = sorted.iter().map(|t| &*t.id).collect();
+    assert_eq!(ids, vec!["2", "4", "3", "1"]);
+}
+
+#[test]
+fn when_sorting_items_it_should_group_the_items_by_storage_class() {
+    let items = vec![
+        ItemTemplate::from_str("1,20,1,1.0,storage=frozen").unwrap(),
+        ItemTemplate::from_str("2,10,1,1.0").unwrap(),
+        ItemTemplate::from_str("3,30,1,1.0,storage=chilled").unwrap(),
+        ItemTemplate::from_str("4,40,1,1.0").unwrap(),
+    ];
+
+    let sorted = sort_items(items.clone(), PackSortOrder::LongToShort, TieBreak::Input)
+        .expect("Failed to sort the items.");
+    let ids: Vec<&str> = sorted.iter().map(|t| &*t.id).collect();
+    assert_eq!(ids, vec!["4", "2", "3", "1"]);
+
+    let sorted = sort_items(items, PackSortOrder::Natural, TieBreak::Input)
+        .expect("Failed to sort the items.");
+    let ids: Vec<&str> = sorted.iter().map(|t| &*t.id).collect();
+    assert_eq!(ids, vec!["2", "4", "3", "1"]);
+}
+
+#[test]
+fn when_sorting_items_with_a_length_that_is_not_a_number_it_should_not_panic() {
+    let mut items = vec![
+        ItemTemplate::from_str("1,20,1,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,1.0").unwrap(),
+        ItemTemplate::from_str("3,30,1,1.0").unwrap(),
+    ];
+    items[1].length = f64::NAN;
+
+    for sort_order in [PackSortOrder::ShortToLong, PackSortOrder::LongToShort] {
+        let sorted = sort_items(items.clone(), sort_order, TieBreak::Input)
+            .expect("Failed to sort the items.");
+        assert_eq!(sorted.len(), 3);
+    }
+}
+
+#[test]
+fn when_sorting_items_without_a_sort_order_it_should_return_an_error() {
+    let items = vec![ItemTemplate::from_str("1,20,1,1.0").unwrap()];
+    let result = sort_items(items, PackSortOrder::NotSet, TieBreak::Input);
+    assert_eq!(result.err().unwrap(), Error::MissingPackInformation);
+}
+
+// plan_packs() with rules
+
+#[test]
+fn when_planning_with_a_maximum_items_with_tag_rule_it_should_limit_the_tagged_items_per_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,3,1.0,tags=BATTERY").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0").unwrap(),
+    ];
+    let rules = vec![PackRule::from_str("MAX_ITEMS_WITH_TAG,BATTERY,1").unwrap()];
+
+    let packs = plan_packs(&items, &pack_template, &rules)
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 3);
+    assert_eq!(packs[0].items[0].item.count, 1);
+    assert_eq!(packs[0].items[0].limit, PlacementLimit::Rule);
+    assert_eq!(packs[1].items[0].item.count, 1);
+    assert_eq!(packs[2].items[0].item.count, 1);
+    assert_eq!(&*packs[2].items[1].item.id, "2");
+}
+
+#[test]
+fn when_planning_with_a_maximum_weight_with_tag_rule_it_should_limit_the_tagged_weight_per_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 20,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,3,4.0,tags=GLASS").unwrap(),
+        ItemTemplate::from_str("2,10,2,3.0,tags=GLASS").unwrap(),
+        ItemTemplate::from_str("3,10,2,5.0").unwrap(),
+    ];
+    let rules = vec![PackRule::from_str("MAX_WEIGHT_WITH_TAG,GLASS,10").unwrap()];
+
+    let packs = plan_packs(&items, &pack_template, &rules)
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+    assert_eq!(packs[0].items[0].item.count, 2);
+    assert_eq!(packs[0].items[0].limit, PlacementLimit::Rule);
+    assert_eq!(packs[0].weight_of_items_with_tag("GLASS"), 8.0);
+    assert_eq!(packs[1].items[0].item.count, 1);
+    assert_eq!(packs[1].items[1].item.count, 2);
+    assert_eq!(packs[1].weight_of_items_with_tag("GLASS"), 10.0);
+    assert_eq!(packs[1].items[2].item.count, 2);
+}
+
+#[test]
+fn when_planning_with_a_maximum_heavy_items_rule_it_should_spread_the_heavy_items_over_the_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,3,20.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,15.0").unwrap(),
+        ItemTemplate::from_str("3,10,4,12.0").unwrap(),
+    ];
+    let rules = vec![PackRule::from_str("MAX_HEAVY_ITEMS,12,2").unwrap()];
+
+    let packs = plan_packs(&items, &pack_template, &rules)
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 3);
+    assert_eq!(packs[0].items[0].item.count, 2);
+    assert_eq!(packs[0].items[0].limit, PlacementLimit::Rule);
+    assert!(packs
+        .iter()
+        .all(|p| p.number_of_items_heavier_than(12.0) <= 2));
+    assert_eq!(packs[2].items.last().unwrap().item.count, 4);
+}
+
+#[test]
+fn when_planning_with_an_exclusive_tag_rule_it_should_not_mix_tagged_and_untagged_items() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,1.0,tags=COLD").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0,tags=COLD|GLASS").unwrap(),
+        ItemTemplate::from_str("3,10,2,1.0").unwrap(),
+    ];
+    let rules = vec![PackRule::from_str("EXCLUSIVE_TAG,COLD").unwrap()];
+
+    let packs = plan_packs(&items, &pack_template, &rules)
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+    assert_eq!(packs[0].items.len(), 2);
+    assert_eq!(packs[1].items.len(), 1);
+    assert_eq!(&*packs[1].items[0].item.id, "3");
+}
+
+#[test]
+fn when_planning_with_class_limits_it_should_lower_the_limits_of_the_packs_with_the_class() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 500.0,
+        sort_order: PackSortOrder::Natural,
+        class_limits: vec![ClassLimits {
+            class: "GLASS".to_string(),
+            maximum_number_of_pieces: Some(10),
+            maximum_weight: None,
+        }],
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,45,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,12,1.0,tags=GLASS").unwrap(),
+        ItemTemplate::from_str("3,10,5,1.0").unwrap(),
+    ];
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    let counts: Vec<i32> = packs.iter().map(|p| p.item_count).collect();
+    assert_eq!(counts, vec![40, 10, 10, 2]);
+    assert_eq!(packs[1].items[1].item.count, 5);
+    assert_eq!(packs[1].items[1].limit, PlacementLimit::PieceCount);
+    assert_eq!(packs[2].items[0].item.count, 7);
+    assert_eq!(packs[2].items[1].item.count, 3);
+    assert_eq!(&*packs[3].items[0].item.id, "3");
+}
+
+#[test]
+fn when_planning_an_item_that_is_heavier_than_a_pack_it_should_return_an_error() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,10,2,60.0").unwrap()];
+
+    let result = plan_packs(&items, &pack_template, &[]);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::ItemDoesNotFitInEmptyPack {
+            item_id: "1".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_planning_items_of_different_storage_classes_it_should_never_mix_them_in_a_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0,storage=chilled").unwrap(),
+        ItemTemplate::from_str("3,10,2,1.0,storage=chilled").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert_eq!(outcome.packs[0].storage_class, StorageClass::Ambient);
+    assert_eq!(outcome.packs[0].item_count, 2);
+    assert_eq!(outcome.packs[0].items[0].limit, PlacementLimit::None);
+    assert_eq!(outcome.packs[1].storage_class, StorageClass::Chilled);
+    assert_eq!(outcome.packs[1].item_count, 4);
+}
+
+#[test]
+fn when_planning_items_for_different_destinations_it_should_never_mix_them_in_a_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,1.0,destination=north").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0,destination=south").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert_eq!(outcome.packs[0].destination, Some("north".to_string()));
+    assert_eq!(outcome.packs[1].destination, Some("south".to_string()));
+    assert_eq!(outcome.packs[1].items[0].limit, PlacementLimit::None);
+}
+
+#[test]
+fn when_adding_an_item_of_another_storage_class_it_should_report_the_storage_class_limit() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        ..PackTemplate::new()
+    };
+    let mut pack = Pack::new(1);
+    pack.add(
+        &pack_template,
+        &ItemTemplate::from_str("1,10,2,1.0,storage=frozen").unwrap(),
+        2,
+        2,
+        PlacementLimit::None,
+    );
+
+    let template = ItemTemplate::from_str("2,10,2,1.0").unwrap();
+    assert_eq!(
+        number_of_items_to_place(&pack_template, &pack, &template, &[], 2),
+        (0, PlacementLimit::StorageClass)
+    );
+}
+
+#[test]
+fn when_an_item_has_a_pack_multiple_it_should_only_place_full_multiples() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        ..PackTemplate::new()
+    };
+    let template = ItemTemplate::from_str("1,10,12,1.0,multiple=4").unwrap();
+
+    assert_eq!(
+        number_of_items_to_place(&pack_template, &Pack::new(1), &template, &[], 12),
+        (8, PlacementLimit::PieceCount)
+    );
+    assert_eq!(
+        number_of_items_to_place(&pack_template, &Pack::new(1), &template, &[], 4),
+        (4, PlacementLimit::None)
+    );
+}
+
+// plan_packs() with pinned items
+
+#[test]
+fn when_planning_with_a_pinned_item_it_should_place_the_item_in_the_pinned_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let mut items = vec![
+        ItemTemplate::from_str("1,10,15,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,4,1.0").unwrap(),
+    ];
+    Arc::make_mut(&mut items[1].attributes).pinned_pack = Some(1);
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+    assert_eq!(&*packs[0].items[0].item.id, "2");
+    assert_eq!(packs[0].items[0].item.count, 4);
+    assert_eq!(&*packs[0].items[1].item.id, "1");
+    assert_eq!(packs[0].items[1].item.count, 6);
+    assert_eq!(packs[1].items[0].item.count, 9);
+}
+
+#[test]
+fn when_planning_with_an_item_pinned_to_a_later_pack_it_should_fill_the_earlier_packs_first() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let mut items = vec![
+        ItemTemplate::from_str("1,10,3,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0").unwrap(),
+    ];
+    Arc::make_mut(&mut items[1].attributes).pinned_pack = Some(3);
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 3);
+    assert_eq!(&*packs[0].items[0].item.id, "1");
+    assert!(packs[1].is_empty());
+    assert_eq!(packs[2].number, 3);
+    assert_eq!(&*packs[2].items[0].item.id, "2");
+}
+
+#[test]
+fn when_planning_with_pinned_items_that_do_not_fit_it_should_return_an_error() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let mut items = vec![ItemTemplate::from_str("1,10,12,1.0").unwrap()];
+    Arc::make_mut(&mut items[0].attributes).pinned_pack = Some(1);
+
+    let result = plan_packs(&items, &pack_template, &[]);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::PinnedItemsDoNotFit {
+            item_id: "1".to_string(),
+            pack_number: 1
+        }
+    );
+}
+
+// plan_packs() with closed packs
+
+#[test]
+fn when_planning_an_item_that_closes_the_pack_it_should_start_a_new_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let mut items = vec![
+        ItemTemplate::from_str("1,10,2,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0").unwrap(),
+        ItemTemplate::from_str("3,10,2,1.0").unwrap(),
+    ];
+    Arc::make_mut(&mut items[1].attributes).close_pack_before = true;
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+    assert_eq!(packs[0].item_count, 2);
+    assert_eq!(&*packs[1].items[0].item.id, "2");
+    assert_eq!(packs[1].item_count, 4);
+}
+
+#[test]
+fn when_planning_the_first_item_that_closes_the_pack_it_should_not_leave_an_empty_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let mut items = vec![ItemTemplate::from_str("1,10,2,1.0").unwrap()];
+    Arc::make_mut(&mut items[0].attributes).close_pack_before = true;
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 1);
+    assert_eq!(outcome.packs[0].number, 1);
+    assert!(outcome.warnings.is_empty());
+}
+
+// plan_packs() warnings
+
+#[test]
+fn when_planning_a_batch_without_items_it_should_skip_the_batch_with_a_warning() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,0,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 1);
+    assert_eq!(outcome.packs[0].items.len(), 1);
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::ItemSkipped {
+            item_id: "1".to_string(),
+            count: 0
+        }]
+    );
+}
+
+#[test]
+fn when_planning_leaves_a_pack_empty_it_should_return_a_warning() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let mut items = vec![
+        ItemTemplate::from_str("1,10,3,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0").unwrap(),
+    ];
+    Arc::make_mut(&mut items[1].attributes).pinned_pack = Some(3);
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::EmptyPack { pack_number: 2 }]
+    );
+}
+
+#[test]
+fn when_planning_items_that_fit_it_should_not_return_warnings() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,10,15,1.0").unwrap()];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert!(outcome.warnings.is_empty());
+}
+
+// plan_packs() with a hard maximum weight
+
+#[test]
+fn when_planning_with_a_hard_maximum_weight_it_should_overload_the_last_pack_instead_of_starting_a_new_one(
+) {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        hard_maximum_weight: Some(55.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,4,10.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,6.0").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 1);
+    assert_eq!(outcome.packs[0].item_count, 6);
+    assert_eq!(outcome.packs[0].weight, 52.0);
+    assert!(outcome.packs[0].overweight);
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::OverweightPack {
+            pack_number: 1,
+            weight: 52.0,
+            maximum_weight: 50.0
+        }]
+    );
+}
+
+#[test]
+fn when_planning_with_a_hard_maximum_weight_it_should_keep_the_rest_of_a_batch_on_its_line() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        hard_maximum_weight: Some(55.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,4,10.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,6.0").unwrap(),
+    ];
+
+    let mut observer = RecordingObserver::default();
+    let outcome = plan_packs_with_observer(&items, &pack_template, &[], &mut observer)
+        .expect("Failed to plan the packs.");
+    let pack = &outcome.packs[0];
+    assert_eq!(pack.items.len(), 2);
+    assert_eq!(pack.items[1].item.count, 2);
+    assert_eq!(pack.items[1].items_remaining_in_batch, 2);
+    assert_eq!(pack.items[1].limit, PlacementLimit::None);
+    assert_eq!(pack.weight, 52.0);
+    assert_eq!(
+        observer.steps,
+        vec![
+            "placed 4 x 1 in 1",
+            "placed 1 x 2 in 1",
+            "placed 1 x 2 in 1",
+            "closed 1"
+        ]
+    );
+}
+
+#[test]
+fn when_planning_with_a_hard_maximum_weight_and_items_that_exceed_it_it_should_start_a_new_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        hard_maximum_weight: Some(55.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,4,10.0").unwrap(),
+        ItemTemplate::from_str("2,10,3,6.0").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert_eq!(outcome.packs[0].weight, 46.0);
+    assert!(!outcome.packs[0].overweight);
+    assert_eq!(outcome.packs[1].weight, 12.0);
+    assert!(outcome.warnings.is_empty());
+}
+
+#[test]
+fn when_planning_without_a_hard_maximum_weight_it_should_not_overload_the_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,4,10.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,6.0").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert!(outcome.packs.iter().all(|p| !p.overweight));
+}
+
+// plan_packs() with pack dimensions
+
+#[test]
+fn when_planning_an_item_longer_than_the_diagonal_of_the_pack_it_should_return_an_error() {
+    // The internal diagonal of the pack is a little over 1562.
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        dimensions: Some(PackDimensions {
+            length: 1200.0,
+            width: 800.0,
+            height: 600.0,
+        }),
+        ..PackTemplate::new()
+    };
+
+    let items = vec![ItemTemplate::from_str("1,1560,2,1.0").unwrap()];
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 1);
+
+    let items = vec![ItemTemplate::from_str("2,1600,2,1.0").unwrap()];
+    assert_eq!(
+        plan_packs(&items, &pack_template, &[]).err().unwrap(),
+        Error::ItemDoesNotFitInEmptyPack {
+            item_id: "2".to_string()
+        }
+    );
+}
+
+// plan_packs() with a maximum value
+
+#[test]
+fn when_planning_with_a_maximum_value_it_should_split_the_items_over_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        maximum_value: Some(1000.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,5,1.0,value=300").unwrap(),
+        ItemTemplate::from_str("2,10,5,1.0").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert_eq!(outcome.packs[0].item_count, 3);
+    assert_eq!(outcome.packs[0].value, 900.0);
+    assert_eq!(outcome.packs[0].items[0].limit, PlacementLimit::Value);
+    assert_eq!(outcome.packs[1].item_count, 7);
+    assert_eq!(outcome.packs[1].value, 600.0);
+    assert!(outcome.warnings.is_empty());
+}
+
+#[test]
+fn when_planning_an_item_worth_more_than_the_maximum_value_it_should_place_it_on_its_own_with_a_warning(
+) {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        maximum_value: Some(1000.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,1.0,value=1500").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert_eq!(outcome.packs[0].item_count, 1);
+    assert!(outcome.packs[0].exceeds_maximum_value);
+    assert_eq!(outcome.packs[1].item_count, 3);
+    assert_eq!(
+        outcome.warnings,
+        vec![
+            PlanWarning::MaximumValueExceeded {
+                pack_number: 1,
+                value: 1500.0,
+                maximum_value: 1000.0
+            },
+            PlanWarning::MaximumValueExceeded {
+                pack_number: 2,
+                value: 1500.0,
+                maximum_value: 1000.0
+            }
+        ]
+    );
+}
+
+// plan_packs() with bulk items
+
+#[test]
+fn when_planning_a_bulk_item_it_should_fill_the_packs_up_to_the_maximum_weight() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,15.0").unwrap(),
+        ItemTemplate::from_str("3001,-,TOTAL_KG=250,1.0/kg").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    let bulk_weights: Vec<f64> = outcome
+        .packs
+        .iter()
+        .flat_map(|p| p.items.iter())
+        .filter(|p| p.item.attributes.bulk)
+        .map(|p| p.item.weight)
+        .collect();
+    assert_eq!(bulk_weights, vec![70.0, 100.0, 80.0]);
+    assert_eq!(outcome.packs.len(), 3);
+    assert_eq!(outcome.packs[0].weight, 100.0);
+    assert_eq!(outcome.packs[0].items[1].limit, PlacementLimit::Weight);
+    assert_eq!(
+        outcome.packs[0].items[1].bulk_remaining_after(),
+        Some(180.0)
+    );
+    assert_eq!(outcome.packs[2].items[0].limit, PlacementLimit::None);
+    assert_eq!(outcome.packs[2].items[0].bulk_remaining_after(), Some(0.0));
+}
+
+#[test]
+fn when_planning_a_bulk_item_with_a_weight_rule_it_should_limit_the_bulk_per_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("3001,-,TOTAL_KG=50,1.0/kg,tags=SAND").unwrap()];
+    let rules = vec![PackRule::MaximumWeightWithTag {
+        tag: "SAND".to_string(),
+        maximum_weight: 20.0,
+    }];
+
+    let outcome = plan_packs(&items, &pack_template, &rules).expect("Failed to plan the packs.");
+    let weights: Vec<f64> = outcome.packs.iter().map(|p| p.weight).collect();
+    assert_eq!(weights, vec![20.0, 20.0, 10.0]);
+    assert_eq!(outcome.packs[0].items[0].limit, PlacementLimit::Rule);
+}
+
+#[test]
+fn when_planning_a_bulk_item_in_a_full_pack_it_should_start_a_new_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 2,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,1.0").unwrap(),
+        ItemTemplate::from_str("3001,-,TOTAL_KG=30,1.0/kg").unwrap(),
+    ];
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert_eq!(outcome.packs[1].weight, 30.0);
+    assert_eq!(outcome.packs[1].item_count, 1);
+}
+
+// plan_packs_with_observer()
+
+/// Records the steps of the planner as short descriptions.
+#[derive(Default)]
+struct RecordingObserver {
+    steps: Vec<String>,
+}
+
+impl PlanObserver for RecordingObserver {
+    fn on_item_placed(&mut self, pack: &Pack, placement: &PackedItem) {
+        self.steps.push(format!(
+            "placed {} x {} in {}",
+            placement.item.count, placement.item.id, pack.number
+        ));
+    }
+
+    fn on_pack_closed(&mut self, pack: &Pack) {
+        self.steps.push(format!("closed {}", pack.number));
+    }
+}
+
+#[test]
+fn when_planning_with_an_observer_it_should_report_each_placement_and_closed_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,8,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,4,1.0").unwrap(),
+    ];
+
+    let mut observer = RecordingObserver::default();
+    plan_packs_with_observer(&items, &pack_template, &[], &mut observer)
+        .expect("Failed to plan the packs.");
+    assert_eq!(
+        observer.steps,
+        vec![
+            "placed 8 x 1 in 1",
+            "placed 2 x 2 in 1",
+            "closed 1",
+            "placed 2 x 2 in 2",
+            "closed 2"
+        ]
+    );
+}
+
+#[test]
+fn when_planning_pinned_items_with_an_observer_it_should_close_every_pack_once() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let mut items = vec![
+        ItemTemplate::from_str("1,10,3,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,1.0").unwrap(),
+    ];
+    Arc::make_mut(&mut items[1].attributes).pinned_pack = Some(2);
+
+    let mut observer = RecordingObserver::default();
+    plan_packs_with_observer(&items, &pack_template, &[], &mut observer)
+        .expect("Failed to plan the packs.");
+    assert_eq!(
+        observer.steps,
+        vec![
+            "placed 2 x 2 in 2",
+            "placed 3 x 1 in 1",
+            "closed 1",
+            "closed 2"
+        ]
+    );
+}
+
+// plan_packs() complexity
+
+// The number of batches of the smaller of the two inputs that are compared, and the factor by which the larger
+// input is larger
+const COMPLEXITY_BATCHES: usize = 500;
+
+const COMPLEXITY_GROWTH: u64 = 8;
+
+/// Returns the number of checks, e.g. placements and rules, that planning the batches created by 'create_item'
+/// takes.
+fn checks_to_plan(
+    number_of_batches: usize,
+    create_item: impl Fn(usize) -> ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> u64 {
+    let items: Vec<ItemTemplate> = (0..number_of_batches).map(create_item).collect();
+    crate::model::checks::take();
+    plan_packs(&items, pack_template, rules).expect("Failed to plan the packs.");
+    crate::model::checks::take()
+}
+
+/// Asserts that the number of checks grows linearly with the number of batches. Linear growth is 8, while
+/// quadratic growth is 64.
+fn assert_linear_growth(
+    create_item: impl Fn(usize) -> ItemTemplate,
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) {
+    let small = checks_to_plan(COMPLEXITY_BATCHES, &create_item, pack_template, rules);
+    let large = checks_to_plan(
+        COMPLEXITY_BATCHES * COMPLEXITY_GROWTH as usize,
+        &create_item,
+        pack_template,
+        rules,
+    );
+
+    assert!(small > 0);
+    assert!(
+        large <= small * COMPLEXITY_GROWTH * 2,
+        "Planning {}x as many batches took {} checks instead of {}.",
+        COMPLEXITY_GROWTH,
+        large,
+        small
+    );
+}
+
+#[test]
+fn when_planning_many_batches_with_a_hard_maximum_weight_it_should_make_a_linear_number_of_checks()
+{
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 500.0,
+        hard_maximum_weight: Some(520.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    assert_linear_growth(
+        |i| ItemTemplate::from_str(&format!("{i},100,7,9.5")).unwrap(),
+        &pack_template,
+        &[],
+    );
+}
+
+#[test]
+fn when_planning_many_small_items_with_rules_it_should_make_a_linear_number_of_checks() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 1_000_000,
+        maximum_weight: 1_000_000.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let rules = vec![
+        PackRule::MaximumItemsWithTag {
+            tag: "FRAGILE".to_string(),
+            maximum: 1_000_000,
+        },
+        PackRule::MaximumWeightWithTag {
+            tag: "FRAGILE".to_string(),
+            maximum_weight: 1_000_000.0,
+        },
+        PackRule::MaximumHeavyItems {
+            threshold: 5.0,
+            maximum: 1_000_000,
+        },
+        PackRule::ExclusiveTag {
+            tag: "FRAGILE".to_string(),
+        },
+    ];
+    assert_linear_growth(
+        |i| ItemTemplate::from_str(&format!("{i},100,1,{}.0,tags=FRAGILE", i % 10 + 1)).unwrap(),
+        &pack_template,
+        &rules,
+    );
+}
+
+#[test]
+fn when_planning_many_items_with_nesting_rules_it_should_make_a_linear_number_of_checks() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 1_000_000,
+        maximum_weight: 1_000_000.0,
+        sort_order: PackSortOrder::Natural,
+        length_mode: LengthMode::Sum,
+        maximum_length: Some(1e12),
+        nesting_rules: vec![NestingRule {
+            inner_item_id: "rod".to_string(),
+            outer_item_id: "tube".to_string(),
+        }],
+        ..PackTemplate::new()
+    };
+    assert_linear_growth(
+        |i| {
+            let (id, length) = if i % 2 == 0 {
+                ("tube", 100)
+            } else {
+                ("rod", 80)
+            };
+            ItemTemplate::from_str(&format!("{id},{length},1,1.0")).unwrap()
+        },
+        &pack_template,
+        &[],
+    );
+}
+
+// sequence_packs()
+
+fn create_pack(number: usize, destination: Option<&str>) -> Pack {
+    Pack {
+        destination: destination.map(|d| d.to_string()),
+        ..Pack::new(number)
+    }
+}
+
+#[test]
+fn when_sequencing_packs_it_should_number_the_packs_by_delivery_stop() {
+    let outcome = PlanOutcome {
+        packs: vec![
+            create_pack(1, Some("south")),
+            create_pack(2, None),
+            create_pack(3, Some("north")),
+            create_pack(4, Some("south")),
+        ],
+        warnings: vec![PlanWarning::EmptyPack { pack_number: 3 }],
+        optimality: None,
+    };
+    let stops = vec![
+        DeliveryStop {
+            destination: "north".to_string(),
+            stop: 1,
+        },
+        DeliveryStop {
+            destination: "south".to_string(),
+            stop: 2,
+        },
+    ];
+
+    let outcome = sequence_packs(outcome, &stops).expect("Failed to sequence the packs.");
+    let destinations: Vec<(usize, Option<&str>)> = outcome
+        .packs
+        .iter()
+        .map(|p| (p.number, p.destination.as_deref()))
+        .collect();
+    assert_eq!(
+        destinations,
+        vec![
+            (1, Some("north")),
+            (2, Some("south")),
+            (3, Some("south")),
+            (4, None)
+        ]
+    );
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::EmptyPack { pack_number: 1 }]
+    );
+}
+
+#[test]
+fn when_sequencing_a_pack_for_a_destination_without_a_stop_it_should_return_an_error() {
+    let outcome = PlanOutcome {
+        packs: vec![create_pack(1, Some("east"))],
+        warnings: Vec::new(),
+        optimality: None,
+    };
+    assert_eq!(
+        sequence_packs(outcome, &[]).err().unwrap(),
+        Error::UnknownDestination {
+            destination: "east".to_string()
+        }
+    );
+}
+
+// offset_pack_numbers()
+
+#[test]
+fn when_offsetting_the_pack_numbers_it_should_renumber_the_packs_and_the_warnings() {
+    let outcome = PlanOutcome {
+        packs: vec![create_pack(1, None), create_pack(2, None)],
+        warnings: vec![
+            PlanWarning::EmptyPack { pack_number: 2 },
+            PlanWarning::ItemSkipped {
+                item_id: "1001".to_string(),
+                count: 0,
+            },
+        ],
+        optimality: None,
+    };
+
+    let outcome = offset_pack_numbers(outcome, 37);
+    let numbers: Vec<usize> = outcome.packs.iter().map(|p| p.number).collect();
+    assert_eq!(numbers, vec![38, 39]);
+    assert_eq!(
+        outcome.warnings,
+        vec![
+            PlanWarning::EmptyPack { pack_number: 39 },
+            PlanWarning::ItemSkipped {
+                item_id: "1001".to_string(),
+                count: 0,
+            },
+        ]
+    );
+}
+
+// spread_packs()
+
+fn spread_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 8,
+        maximum_weight: 1000.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+#[test]
+fn when_spreading_a_pack_it_should_balance_the_pieces_over_the_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 20,
+        ..spread_pack_template()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1001,100,7,10.0").unwrap(),
+        ItemTemplate::from_str("1002,100,3,10.0").unwrap(),
+    ];
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+
+    let outcome = spread_packs(outcome, 3, &pack_template);
+    let placements: Vec<(usize, &str, i32, PlacementLimit)> = outcome
+        .packs
+        .iter()
+        .flat_map(|p| {
+            p.items
+                .iter()
+                .map(|i| (p.number, &*i.item.id, i.item.count, i.limit))
+        })
+        .collect();
+    assert_eq!(
+        placements,
+        vec![
+            (1, "1001", 3, PlacementLimit::MinimumPacks),
+            (2, "1001", 4, PlacementLimit::MinimumPacks),
+            (3, "1002", 3, PlacementLimit::None),
+        ]
+    );
+    assert_eq!(outcome.packs[1].items[0].items_remaining_in_batch, 4);
+    assert_eq!(outcome.packs[1].weight, 40.0);
+}
+
+#[test]
+fn when_spreading_packs_it_should_split_the_fullest_pack_and_renumber_the_warnings() {
+    let pack_template = spread_pack_template();
+    let items = vec![ItemTemplate::from_str("1001,100,10,10.0").unwrap()];
+    let mut outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    outcome
+        .warnings
+        .push(PlanWarning::EmptyPack { pack_number: 2 });
+
+    let outcome = spread_packs(outcome, 3, &pack_template);
+    let counts: Vec<(usize, i32)> = outcome
+        .packs
+        .iter()
+        .map(|p| (p.number, p.item_count))
+        .collect();
+    assert_eq!(counts, vec![(1, 4), (2, 4), (3, 2)]);
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::EmptyPack { pack_number: 3 }]
+    );
+}
+
+#[test]
+fn when_spreading_the_components_of_a_kit_it_should_keep_the_kit_together() {
+    let pack_template = spread_pack_template();
+    let kit_instance = KitInstance {
+        kit_id: "KIT-1".into(),
+        number: 1,
+    };
+    let items: Vec<ItemTemplate> = ["1001,100,2,10.0", "1002,100,2,10.0"]
+        .iter()
+        .map(|line| {
+            let item = ItemTemplate::from_str(line).unwrap();
+            ItemTemplate {
+                attributes: Arc::new(ItemAttributes {
+                    kit_instance: Some(kit_instance.clone()),
+                    ..(*item.attributes).clone()
+                }),
+                ..item
+            }
+        })
+        .collect();
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+
+    let outcome = spread_packs(outcome, 2, &pack_template);
+    assert_eq!(outcome.packs.len(), 1);
+    assert_eq!(outcome.packs[0].item_count, 4);
+}
+
+// separate_infeasible_items() and place_infeasible_items()
+
+fn create_infeasible_items() -> Vec<ItemTemplate> {
+    let mut pinned_item = ItemTemplate::from_str("3,10,1,60.0").unwrap();
+    Arc::make_mut(&mut pinned_item.attributes).pinned_pack = Some(1);
+    vec![
+        ItemTemplate::from_str("1,10,2,10.0").unwrap(),
+        ItemTemplate::from_str("2,10,2,80.0").unwrap(),
+        pinned_item,
+    ]
+}
+
+fn create_infeasible_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        hard_maximum_weight: Some(100.0),
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+#[test]
+fn when_separating_infeasible_items_it_should_keep_the_pinned_items() {
+    let (items, infeasible_items) = separate_infeasible_items(
+        create_infeasible_items(),
+        &create_infeasible_pack_template(),
+        &[],
+    );
+    let ids: Vec<&str> = items.iter().map(|i| i.id.as_ref()).collect();
+    assert_eq!(ids, vec!["1", "3"]);
+    assert_eq!(infeasible_items.len(), 1);
+    assert_eq!(infeasible_items[0].id.as_ref(), "2");
+}
+
+#[test]
+fn when_skipping_infeasible_items_it_should_report_the_items() {
+    let pack_template = create_infeasible_pack_template();
+    let (_, infeasible_items) =
+        separate_infeasible_items(create_infeasible_items(), &pack_template, &[]);
+    let outcome = PlanOutcome {
+        packs: vec![Pack::new(1)],
+        warnings: Vec::new(),
+        optimality: None,
+    };
+
+    let outcome = place_infeasible_items(
+        outcome,
+        &infeasible_items,
+        &pack_template,
+        InfeasibleItemPolicy::Skip,
+    )
+    .expect("Failed to place the infeasible items.");
+    assert_eq!(outcome.packs.len(), 1);
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::InfeasibleItemSkipped {
+            item_id: "2".to_string(),
+            count: 2
+        }]
+    );
+}
+
+#[test]
+fn when_placing_infeasible_items_in_their_own_pack_it_should_add_an_oversize_pack_per_item() {
+    let pack_template = create_infeasible_pack_template();
+    let (_, infeasible_items) =
+        separate_infeasible_items(create_infeasible_items(), &pack_template, &[]);
+    let outcome = PlanOutcome {
+        packs: vec![Pack::new(1)],
+        warnings: Vec::new(),
+        optimality: None,
+    };
+
+    let outcome = place_infeasible_items(
+        outcome,
+        &infeasible_items,
+        &pack_template,
+        InfeasibleItemPolicy::OwnPack,
+    )
+    .expect("Failed to place the infeasible items.");
+    assert_eq!(outcome.packs.len(), 3);
+    for pack in &outcome.packs[1..] {
+        assert!(pack.oversize);
+        assert_eq!(pack.item_count, 1);
+        assert_eq!(pack.weight, 80.0);
+    }
+    assert_eq!(
+        outcome.warnings,
+        vec![
+            PlanWarning::OversizePack {
+                pack_number: 2,
+                item_id: "2".to_string()
+            },
+            PlanWarning::OversizePack {
+                pack_number: 3,
+                item_id: "2".to_string()
+            }
+        ]
+    );
+}
+
+#[test]
+fn when_placing_infeasible_items_with_the_error_policy_it_should_return_an_error() {
+    let pack_template = create_infeasible_pack_template();
+    let (_, infeasible_items) =
+        separate_infeasible_items(create_infeasible_items(), &pack_template, &[]);
+    let outcome = PlanOutcome {
+        packs: Vec::new(),
+        warnings: Vec::new(),
+        optimality: None,
+    };
+
+    assert_eq!(
+        place_infeasible_items(
+            outcome,
+            &infeasible_items,
+            &pack_template,
+            InfeasibleItemPolicy::Error
+        )
+        .err()
+        .unwrap(),
+        Error::ItemDoesNotFitInEmptyPack {
+            item_id: "2".to_string()
+        }
+    );
+}
+
+// verify_pack_capacity()
+
+#[test]
+fn when_verifying_a_pack_without_weight_capacity_it_should_return_an_error() {
+    let pack_template = PackTemplate {
+        maximum_weight: 5.0,
+        tare_weight: 5.0,
+        ..create_infeasible_pack_template()
+    };
+
+    assert_eq!(
+        verify_pack_capacity(&pack_template),
+        Err(Error::PackHasNoWeightCapacity {
+            maximum_weight: 5.0,
+            tare_weight: 5.0
+        })
+    );
+}
+
+#[test]
+fn when_verifying_a_pack_without_piece_capacity_it_should_return_an_error() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 0,
+        ..create_infeasible_pack_template()
+    };
+
+    assert_eq!(
+        verify_pack_capacity(&pack_template),
+        Err(Error::PackHasNoPieceCapacity {
+            maximum_number_of_pieces: 0
+        })
+    );
+    assert_eq!(
+        verify_pack_capacity(&create_infeasible_pack_template()),
+        Ok(())
+    );
+}
+
+// verify_some_item_fits()
+
+#[test]
+fn when_verifying_items_of_which_one_fits_it_should_succeed() {
+    assert_eq!(
+        verify_some_item_fits(
+            &create_infeasible_items(),
+            &create_infeasible_pack_template(),
+            &[]
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn when_verifying_items_of_which_none_fit_it_should_name_the_limit_of_the_first_item() {
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,80.0").unwrap(),
+        ItemTemplate::from_str("2,2000,1,1.0").unwrap(),
+    ];
+    let pack_template = PackTemplate {
+        maximum_length: Some(1000.0),
+        ..create_infeasible_pack_template()
+    };
+
+    assert_eq!(
+        verify_some_item_fits(&items, &pack_template, &[]),
+        Err(Error::NoItemFitsInEmptyPack {
+            item_id: "1".to_string(),
+            limit: "weight".to_string(),
+            reason: "a single piece weighs 80 while a pack holds at most 50".to_string()
+        })
+    );
+}
+
+#[test]
+fn when_verifying_items_in_multiples_larger_than_a_pack_it_should_name_the_multiple() {
+    let items = vec![ItemTemplate::from_str("1,10,12,1.0,multiple=12").unwrap()];
+
+    let error = verify_some_item_fits(&items, &create_infeasible_pack_template(), &[])
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "None of the items fit in an empty pack. The first item, \"1\", exceeds the piece count limit: the item is packed in multiples of 12 while a pack holds at most 10 pieces."
+    );
+}
+
+// expand_kits()
+
+fn create_bill_of_materials() -> BillOfMaterials {
+    let mut bill_of_materials = BillOfMaterials::default();
+    bill_of_materials.kits.insert(
+        "gift-set-1".to_string(),
+        vec![
+            ItemTemplate::from_str("1001,300,2,5.0").unwrap(),
+            ItemTemplate::from_str("1002,100,1,10.0").unwrap(),
+        ],
+    );
+    bill_of_materials
+}
+
+fn create_kit(count: i32) -> ItemTemplate {
+    ItemTemplate {
+        id: "gift-set-1".into(),
+        length: 0.0,
+        weight: 0.0,
+        count,
+        attributes: Arc::new(ItemAttributes {
+            kit: true,
+            ..ItemAttributes::default()
+        }),
+    }
+}
+
+#[test]
+fn when_expanding_kits_it_should_combine_the_components_of_all_the_kits() {
+    let items = vec![
+        ItemTemplate::from_str("2001,200,1,1.0").unwrap(),
+        create_kit(3),
+    ];
+    let expanded =
+        expand_kits(items, &create_bill_of_materials(), false).expect("Failed to expand the kits.");
+
+    let counts: Vec<(&str, i32)> = expanded.iter().map(|t| (&*t.id, t.count)).collect();
+    assert_eq!(counts, vec![("2001", 1), ("1001", 6), ("1002", 3)]);
+    assert!(expanded.iter().all(|t| t.attributes.kit_instance.is_none()));
+}
+
+#[test]
+fn when_expanding_kits_that_are_kept_together_it_should_mark_the_components_of_each_kit() {
+    let expanded = expand_kits(vec![create_kit(2)], &create_bill_of_materials(), true)
+        .expect("Failed to expand the kits.");
+
+    let numbers: Vec<usize> = expanded
+        .iter()
+        .map(|t| t.attributes.kit_instance.as_ref().unwrap().number)
+        .collect();
+    assert_eq!(numbers, vec![1, 1, 2, 2]);
+    assert_eq!(expanded[0].count, 2);
+}
+
+#[test]
+fn when_expanding_an_unknown_kit_it_should_return_an_error() {
+    let result = expand_kits(vec![create_kit(1)], &BillOfMaterials::default(), false);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnknownKit {
+            kit_id: "gift-set-1".to_string()
+        }
+    );
+}
+
+// resolve_duplicate_ids()
+
+fn create_duplicate_items() -> Vec<ItemTemplate> {
+    vec![
+        ItemTemplate::from_str("1001,100,3,10.0").unwrap(),
+        ItemTemplate::from_str("1002,200,1,20.0").unwrap(),
+        ItemTemplate::from_str("1001,100,2,10.0").unwrap(),
+        ItemTemplate::from_str("1001,100,4,10.0,tags=FRAGILE").unwrap(),
+    ]
+}
+
+#[test]
+fn when_merging_duplicate_ids_it_should_combine_the_identical_batches() {
+    let items = resolve_duplicate_ids(create_duplicate_items(), DuplicateIdPolicy::Merge)
+        .expect("Failed to resolve the duplicate IDs.");
+    let batches: Vec<(&str, i32)> = items.iter().map(|t| (&*t.id, t.count)).collect();
+    assert_eq!(batches, vec![("1001", 5), ("1002", 1), ("1001", 4)]);
+}
+
+#[test]
+fn when_checking_duplicate_ids_it_should_keep_the_batches() {
+    let items = resolve_duplicate_ids(create_duplicate_items(), DuplicateIdPolicy::Error)
+        .expect("Failed to resolve the duplicate IDs.");
+    assert_eq!(items.len(), 4);
+}
+
+#[test]
+fn when_duplicate_ids_conflict_it_should_return_an_error_unless_allowed() {
+    let mut items = create_duplicate_items();
+    items.push(ItemTemplate::from_str("1002,200,1,25.0").unwrap());
+
+    for policy in [DuplicateIdPolicy::Error, DuplicateIdPolicy::Merge] {
+        let result = resolve_duplicate_ids(items.clone(), policy);
+        assert_eq!(
+            result.err().unwrap(),
+            Error::ConflictingDuplicateItem {
+                item_id: "1002".to_string(),
+                length: 200.0,
+                weight: 20.0,
+                other_length: 200.0,
+                other_weight: 25.0,
+            }
+        );
+    }
+
+    let allowed = resolve_duplicate_ids(items, DuplicateIdPolicy::Allow)
+        .expect("Failed to resolve the duplicate IDs.");
+    assert_eq!(allowed.len(), 5);
+}
+
+// filter_and_map_items()
+
+#[test]
+fn when_filtering_and_mapping_items_it_should_map_only_the_matching_items() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,3,10.0").unwrap(),
+        ItemTemplate::from_str("1002,100,5,40.0").unwrap(),
+        ItemTemplate::from_str("1003,100,1,20.0").unwrap(),
+    ];
+    let filters = vec![ItemFilter::from_str("weight < 30").unwrap()];
+    let maps = vec![
+        ItemMap::from_str("count *= 2").unwrap(),
+        ItemMap::from_str("weight = 50").unwrap(),
+    ];
+
+    let items = filter_and_map_items(items, &filters, &maps).expect("Failed to map the items.");
+    let properties: Vec<(&str, i32, f64)> =
+        items.iter().map(|t| (&*t.id, t.count, t.weight)).collect();
+    assert_eq!(properties, vec![("1001", 6, 50.0), ("1003", 2, 50.0)]);
+}
+
+#[test]
+fn when_filtering_items_on_a_missing_property_it_should_remove_the_items() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,3,10.0,value=5").unwrap(),
+        ItemTemplate::from_str("1002,100,5,40.0").unwrap(),
+    ];
+    let filters = vec![ItemFilter::from_str("value != 1").unwrap()];
+
+    let items = filter_and_map_items(items, &filters, &[]).expect("Failed to map the items.");
+    assert_eq!(items.len(), 1);
+    assert_eq!(&*items[0].id, "1001");
+}
+
+#[test]
+fn when_mapping_a_count_it_should_round_towards_zero() {
+    let items = vec![ItemTemplate::from_str("1001,100,5,10.0").unwrap()];
+    let maps = vec![ItemMap::from_str("count /= 2").unwrap()];
+
+    let items = filter_and_map_items(items, &[], &maps).expect("Failed to map the items.");
+    assert_eq!(items[0].count, 2);
+}
+
+#[test]
+fn when_a_map_gives_an_item_a_negative_value_it_should_return_an_error() {
+    let items = vec![ItemTemplate::from_str("1001,100,5,10.0").unwrap()];
+    let maps = vec![ItemMap::from_str("weight -= 12.5").unwrap()];
+
+    assert_eq!(
+        filter_and_map_items(items, &[], &maps).err().unwrap(),
+        Error::InvalidMappedValue {
+            expression: "weight -= 12.5".to_string(),
+            item_id: "1001".to_string(),
+            value: -2.5
+        }
+    );
+}
+
+#[test]
+fn when_a_map_gives_an_item_a_value_that_is_not_finite_it_should_return_an_error() {
+    let items = vec![ItemTemplate::from_str("1001,100,5,10.0").unwrap()];
+    let maps = vec![ItemMap::from_str("length *= 1e308").unwrap()];
+
+    assert!(matches!(
+        filter_and_map_items(items, &[], &maps),
+        Err(Error::InvalidMappedValue { item_id, value, .. })
+            if item_id == "1001" && value == f64::INFINITY
+    ));
+}
+
+// apply_aliases()
+
+#[test]
+fn when_applying_aliases_it_should_replace_the_aliases_and_keep_the_original_ids() {
+    let items = vec![
+        ItemTemplate::from_str("OLD-1001,100,3,10.0,tags=FRAGILE").unwrap(),
+        ItemTemplate::from_str("1002,100,5,40.0").unwrap(),
+    ];
+    let aliases = ItemAliases {
+        aliases: HashMap::from([("OLD-1001".to_string(), "1001".to_string())]),
+    };
+
+    let items = apply_aliases(items, &aliases);
+    assert_eq!(&*items[0].id, "1001");
+    assert_eq!(items[0].attributes.alias_of.as_deref(), Some("OLD-1001"));
+    assert!(items[0].attributes.has_tag("FRAGILE"));
+    assert_eq!(&*items[1].id, "1002");
+    assert_eq!(items[1].attributes.alias_of, None);
+}
+
+// exclude_items()
+
+#[test]
+fn when_excluding_items_it_should_remove_the_matching_items_and_report_them() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,3,10.0").unwrap(),
+        ItemTemplate::from_str("GLASS-1,100,5,40.0").unwrap(),
+        ItemTemplate::from_str("1003,100,1,20.0").unwrap(),
+    ];
+    let patterns = vec![IdPattern::new("GLASS-*"), IdPattern::new("1003")];
+
+    let (items, warnings) = exclude_items(items, &patterns);
+    assert_eq!(items.len(), 1);
+    assert_eq!(&*items[0].id, "1001");
+    assert_eq!(
+        warnings,
+        vec![
+            PlanWarning::ItemExcluded {
+                item_id: "GLASS-1".to_string(),
+                count: 5,
+            },
+            PlanWarning::ItemExcluded {
+                item_id: "1003".to_string(),
+                count: 1,
+            },
+        ]
+    );
+}
+
+// round_to_multiples()
+
+#[test]
+fn when_rounding_to_multiples_it_should_remove_the_incomplete_multiples_and_report_them() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,14,10.0,multiple=6").unwrap(),
+        ItemTemplate::from_str("1002,100,12,10.0,multiple=6").unwrap(),
+        ItemTemplate::from_str("1003,100,5,10.0").unwrap(),
+    ];
+
+    let (items, warnings) = round_to_multiples(items);
+    assert_eq!(
+        items.iter().map(|t| t.count).collect::<Vec<i32>>(),
+        vec![12, 12, 5]
+    );
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::IncompleteMultiple {
+            item_id: "1001".to_string(),
+            multiple: 6,
+            count: 2,
+        }]
+    );
+}
+
+#[test]
+fn when_rounding_a_batch_smaller_than_a_multiple_it_should_drop_the_batch() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,4,10.0,multiple=6").unwrap(),
+        ItemTemplate::from_str("1002,100,0,10.0").unwrap(),
+    ];
+
+    let (items, warnings) = round_to_multiples(items);
+    assert_eq!(
+        items.iter().map(|t| (&*t.id, t.count)).collect::<Vec<_>>(),
+        vec![("1002", 0)]
+    );
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::IncompleteMultiple {
+            item_id: "1001".to_string(),
+            multiple: 6,
+            count: 4,
+        }]
+    );
+}
+
+// cut_items()
+
+/// Returns the length, the count and the weight of each batch.
+fn batches(items: &[ItemTemplate]) -> Vec<(f64, i32, f64)> {
+    items
+        .iter()
+        .map(|t| (t.length, t.count, t.weight))
+        .collect()
+}
+
+#[test]
+fn when_cutting_items_it_should_cut_pieces_that_fill_the_weight_of_a_pack() {
+    let items = vec![
+        ItemTemplate::from_str("4001,-,TOTAL_LENGTH=60000,0.002/length").unwrap(),
+        ItemTemplate::from_str("1001,100,5,1.0").unwrap(),
+    ];
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        ..PackTemplate::new()
+    };
+
+    let items = cut_items(items, &pack_template).expect("Failed to cut the items.");
+    assert_eq!(
+        batches(&items),
+        vec![(25000.0, 2, 50.0), (10000.0, 1, 20.0), (100.0, 5, 1.0)]
+    );
+    assert!(items.iter().all(|t| t.attributes.cut.is_none()));
+}
+
+#[test]
+fn when_cutting_items_with_an_increment_it_should_cut_multiples_of_the_increment_that_fit_the_pack_length(
+) {
+    let items =
+        vec![
+            ItemTemplate::from_str("4001,1000,TOTAL_LENGTH=25500,0.002/length,value=0.01").unwrap(),
+        ];
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 500.0,
+        maximum_length: Some(6500.0),
+        ..PackTemplate::new()
+    };
+
+    let items = cut_items(items, &pack_template).expect("Failed to cut the items.");
+    assert_eq!(batches(&items), vec![(6000.0, 4, 12.0), (1500.0, 1, 3.0)]);
+    assert_eq!(items[0].item_value(), Some(60.0));
+    assert_eq!(items[1].item_value(), Some(15.0));
+}
+
+#[test]
+fn when_an_increment_does_not_fit_in_a_pack_it_should_cut_pieces_of_a_single_increment() {
+    let items = vec![ItemTemplate::from_str("4001,1000,TOTAL_LENGTH=2500,0.002/length").unwrap()];
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 500.0,
+        maximum_length: Some(500.0),
+        ..PackTemplate::new()
+    };
+
+    let items = cut_items(items, &pack_template).expect("Failed to cut the items.");
+    assert_eq!(batches(&items), vec![(1000.0, 2, 2.0), (500.0, 1, 1.0)]);
+}
+
+#[test]
+fn when_cutting_more_pieces_than_a_batch_can_hold_it_should_return_an_error() {
+    let items = vec![ItemTemplate::from_str("4001,1,TOTAL_LENGTH=1e10,0.002/length").unwrap()];
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 500.0,
+        maximum_length: Some(1.0),
+        ..PackTemplate::new()
+    };
+
+    assert_eq!(
+        cut_items(items, &pack_template).err(),
+        Some(Error::TooManyCutPieces {
+            item_id: "4001".to_string(),
+            pieces: 1e10,
+        })
+    );
+}
+
+// apply_destination_limits()
+
+#[test]
+fn when_applying_destination_limits_it_should_leave_the_items_beyond_the_limits_for_the_next_run() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,10,10.0,destination=north").unwrap(),
+        ItemTemplate::from_str("1002,100,10,10.0,destination=north,multiple=4").unwrap(),
+        ItemTemplate::from_str("1003,100,10,10.0,destination=south").unwrap(),
+        ItemTemplate::from_str("1004,100,10,10.0").unwrap(),
+    ];
+    let limits = vec![DestinationLimits {
+        destination: "north".to_string(),
+        maximum_number_of_pieces: Some(16),
+        maximum_weight: Some(150.0),
+    }];
+
+    let (items, warnings) = apply_destination_limits(items, &limits);
+    assert_eq!(
+        items
+            .iter()
+            .map(|t| (t.id.to_string(), t.count))
+            .collect::<Vec<(String, i32)>>(),
+        vec![
+            ("1001".to_string(), 10),
+            ("1002".to_string(), 4),
+            ("1003".to_string(), 10),
+            ("1004".to_string(), 10),
+        ]
+    );
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::DestinationLimitReached {
+            item_id: "1002".to_string(),
+            destination: "north".to_string(),
+            count: 6,
+        }]
+    );
+}
+
+#[test]
+fn when_applying_destination_limits_it_should_remove_the_batches_that_do_not_fit_at_all() {
+    let items = vec![
+        ItemTemplate::from_str("1001,100,5,10.0,destination=north").unwrap(),
+        ItemTemplate::from_str("1002,100,5,10.0,destination=north").unwrap(),
+    ];
+    let limits = vec![DestinationLimits {
+        destination: "north".to_string(),
+        maximum_number_of_pieces: Some(5),
+        maximum_weight: None,
+    }];
+
+    let (items, warnings) = apply_destination_limits(items, &limits);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id.as_ref(), "1001");
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::DestinationLimitReached {
+            item_id: "1002".to_string(),
+            destination: "north".to_string(),
+            count: 5,
+        }]
+    );
+}
+
+// resolve_substitutions()
+
+fn create_substitution_groups() -> SubstitutionGroups {
+    let mut substitution_groups = SubstitutionGroups::default();
+    substitution_groups.groups.insert(
+        "mug-any-color".to_string(),
+        vec![
+            ItemTemplate::from_str("2001,100,4,10.0").unwrap(),
+            ItemTemplate::from_str("2002,100,10,2.0").unwrap(),
+        ],
+    );
+    substitution_groups
+}
+
+fn create_group_request(count: i32) -> ItemTemplate {
+    ItemTemplate {
+        id: "mug-any-color".into(),
+        length: 0.0,
+        weight: 0.0,
+        count,
+        attributes: Arc::new(ItemAttributes {
+            substitution_group: true,
+            ..ItemAttributes::default()
+        }),
+    }
+}
+
+#[test]
+fn when_resolving_substitutions_it_should_select_the_member_that_fills_the_remaining_space() {
+    let pack_template = PackTemplate {
+        sort_order: PackSortOrder::Natural,
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        ..PackTemplate::new()
+    };
+    // The first pack has 10 kg left, which holds a single item of the first member, or five of the second.
+    let items = vec![
+        ItemTemplate::from_str("1001,100,4,10.0").unwrap(),
+        create_group_request(5),
+    ];
+
+    let (items, warnings) = resolve_substitutions(
+        items,
+        &create_substitution_groups(),
+        &pack_template,
+        &[],
+        TieBreak::Input,
+    )
+    .expect("Failed to resolve the substitutions.");
+    let counts: Vec<(&str, i32)> = items.iter().map(|t| (&*t.id, t.count)).collect();
+    assert_eq!(counts, vec![("1001", 4), ("2002", 5)]);
+    assert_eq!(
+        warnings,
+        vec![PlanWarning::ItemSubstituted {
+            group_id: "mug-any-color".to_string(),
+            item_id: "2002".to_string(),
+            count: 5
+        }]
+    );
+}
+
+#[test]
+fn when_resolving_substitutions_without_remaining_space_it_should_select_the_members_in_order() {
+    let pack_template = PackTemplate {
+        sort_order: PackSortOrder::Natural,
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        ..PackTemplate::new()
+    };
+
+    let (items, _) = resolve_substitutions(
+        vec![create_group_request(6)],
+        &create_substitution_groups(),
+        &pack_template,
+        &[],
+        TieBreak::Input,
+    )
+    .expect("Failed to resolve the substitutions.");
+    let counts: Vec<(&str, i32)> = items.iter().map(|t| (&*t.id, t.count)).collect();
+    assert_eq!(counts, vec![("2001", 4), ("2002", 2)]);
+}
+
+#[test]
+fn when_resolving_substitutions_with_too_few_members_it_should_return_an_error() {
+    let result = resolve_substitutions(
+        vec![create_group_request(10), create_group_request(5)],
+        &create_substitution_groups(),
+        &PackTemplate::new(),
+        &[],
+        TieBreak::Input,
+    );
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InsufficientSubstitutes {
+            group_id: "mug-any-color".to_string(),
+            count: 1
+        }
+    );
+}
+
+#[test]
+fn when_resolving_an_unknown_substitution_group_it_should_return_an_error() {
+    let result = resolve_substitutions(
+        vec![create_group_request(1)],
+        &SubstitutionGroups::default(),
+        &PackTemplate::new(),
+        &[],
+        TieBreak::Input,
+    );
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnknownSubstitutionGroup {
+            group_id: "mug-any-color".to_string()
+        }
+    );
+}
+
+// plan_packs() with kits
+
+#[test]
+fn when_sorting_kits_that_are_kept_together_it_should_keep_the_components_together() {
+    let mut items = expand_kits(vec![create_kit(2)], &create_bill_of_materials(), true).unwrap();
+    items.insert(0, ItemTemplate::from_str("2001,200,1,1.0").unwrap());
+    items.push(ItemTemplate::from_str("2002,50,1,1.0").unwrap());
+
+    let sorted = sort_items(items, PackSortOrder::LongToShort, TieBreak::Input)
+        .expect("Failed to sort the items.");
+    let ids: Vec<&str> = sorted.iter().map(|t| &*t.id).collect();
+    assert_eq!(ids, vec!["1001", "1002", "1001", "1002", "2001", "2002"]);
+}
+
+#[test]
+fn when_planning_kits_that_are_kept_together_it_should_not_split_a_kit_over_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 5,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = expand_kits(vec![create_kit(3)], &create_bill_of_materials(), true).unwrap();
+
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 3);
+    assert!(outcome.packs.iter().all(|p| p.item_count == 3));
+
+    // Without the constraint the components fill the packs up to the maximum number of pieces.
+    let items = expand_kits(vec![create_kit(3)], &create_bill_of_materials(), false).unwrap();
+    let outcome = plan_packs(&items, &pack_template, &[]).expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+}
+
+#[test]
+fn when_planning_a_kit_that_does_not_fit_in_an_empty_pack_it_should_return_an_error() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 2,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = expand_kits(vec![create_kit(1)], &create_bill_of_materials(), true).unwrap();
+
+    let result = plan_packs(&items, &pack_template, &[]);
+    assert_eq!(
+        result.err().unwrap(),
+        Error::KitDoesNotFitInEmptyPack {
+            kit_id: "gift-set-1".to_string()
+        }
+    );
+}
+
+// analyze_pack_count()
+
+#[test]
+fn when_analyzing_items_that_fill_the_packs_exactly_by_count_it_should_report_an_exact_fit() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,12,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,8,1.0").unwrap(),
+    ];
+    let packs = plan_packs(&items, &pack_template, &[]).unwrap().packs;
+
+    let analysis = analyze_pack_count(&items, &pack_template, &packs);
+    assert_eq!(
+        analysis,
+        PackCountAnalysis {
+            minimum_number_of_packs: 2,
+            is_exact_fit: true,
+            number_of_packs: 2
+        }
+    );
+    assert!(analysis.reaches_minimum());
+}
+
+#[test]
+fn when_analyzing_items_that_fill_the_packs_exactly_by_weight_it_should_detect_a_missed_minimum() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    // The greedy planner places 30 + 15 in the first pack, so the 35 doesn't fit and a third pack is needed.
+    let items = vec![
+        ItemTemplate::from_str("1,10,1,30.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,15.0").unwrap(),
+        ItemTemplate::from_str("3,10,1,35.0").unwrap(),
+        ItemTemplate::from_str("4,10,1,20.0").unwrap(),
+    ];
+    let packs = plan_packs(&items, &pack_template, &[]).unwrap().packs;
+
+    let analysis = analyze_pack_count(&items, &pack_template, &packs);
+    assert_eq!(analysis.minimum_number_of_packs, 2);
+    assert!(analysis.is_exact_fit);
+    assert_eq!(analysis.number_of_packs, 3);
+    assert!(!analysis.reaches_minimum());
+}
+
+#[test]
+fn when_analyzing_items_that_do_not_divide_into_whole_packs_it_should_not_report_an_exact_fit() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,10,15,1.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[]).unwrap().packs;
+
+    let analysis = analyze_pack_count(&items, &pack_template, &packs);
+    assert_eq!(analysis.minimum_number_of_packs, 2);
+    assert!(!analysis.is_exact_fit);
+}
+
+// estimate_packs()
+
+#[test]
+fn when_estimating_the_packs_it_should_return_the_bounds() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,1,30.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,30.0").unwrap(),
+        ItemTemplate::from_str("3,10,1,20.0").unwrap(),
+        ItemTemplate::from_str("4,10,1,20.0").unwrap(),
+    ];
+
+    let estimate = estimate_packs(&items, &pack_template);
+    assert_eq!(
+        estimate,
+        EstimateRange {
+            minimum: 2,
+            maximum: 3
+        }
+    );
+
+    let plan = plan_packs(&items, &pack_template, &[]).unwrap();
+    assert_eq!(plan.packs.len(), estimate.maximum);
+}
+
+#[test]
+fn when_estimating_the_packs_it_should_match_the_greedy_plan_for_large_batches() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 40,
+        maximum_weight: 500.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1001,6200,30,9.653").unwrap(),
+        ItemTemplate::from_str("2001,7200,50,11.21").unwrap(),
+        ItemTemplate::from_str("3001,-,TOTAL_KG=700,1.0/kg").unwrap(),
+        ItemTemplate::from_str("4001,1000,200,0.5").unwrap(),
+    ];
+
+    let estimate = estimate_packs(&items, &pack_template);
+    let plan = plan_packs(&items, &pack_template, &[]).unwrap();
+    assert_eq!(estimate.maximum, plan.packs.len());
+    assert!(estimate.minimum <= estimate.maximum);
+}
+
+#[test]
+fn when_estimating_the_packs_for_an_item_that_does_not_fit_it_should_count_a_pack_for_the_item() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,10,2,60.0").unwrap()];
+
+    let estimate = estimate_packs(&items, &pack_template);
+    assert_eq!(estimate.maximum, 3);
+}
+
+// can_fit()
+
+#[test]
+fn when_checking_items_that_fit_in_the_remaining_capacity_it_should_place_them_in_the_existing_packs(
+) {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,10,14,2.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let new_items = vec![ItemTemplate::from_str("2,10,5,1.0").unwrap()];
+    let report = can_fit(&packs, &new_items, &pack_template, &[]);
+    assert!(report.fits());
+    assert_eq!(report.placements.len(), 1);
+    assert_eq!(report.placements[0].pack_number, 2);
+    assert_eq!(report.placements[0].item.count, 5);
+}
+
+#[test]
+fn when_checking_items_that_span_several_packs_it_should_split_the_items() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,5,9.0").unwrap(),
+        ItemTemplate::from_str("2,10,3,6.0").unwrap(),
+    ];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    assert_eq!(packs.len(), 2);
+
+    let new_items = vec![ItemTemplate::from_str("3,10,4,2.0").unwrap()];
+    let report = can_fit(&packs, &new_items, &pack_template, &[]);
+    assert!(report.fits());
+    assert_eq!(report.placements.len(), 2);
+    assert_eq!(report.placements[0].pack_number, 1);
+    assert_eq!(report.placements[0].item.count, 2);
+    assert_eq!(report.placements[1].pack_number, 2);
+    assert_eq!(report.placements[1].item.count, 2);
+}
+
+#[test]
+fn when_checking_items_that_do_not_fit_it_should_report_the_unplaced_items() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,10,9,2.0,tags=COLD").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    let rules = vec![PackRule::from_str("EXCLUSIVE_TAG,COLD").unwrap()];
+
+    let new_items = vec![ItemTemplate::from_str("2,10,1,1.0").unwrap()];
+    let report = can_fit(&packs, &new_items, &pack_template, &rules);
+    assert!(!report.fits());
+    assert!(report.placements.is_empty());
+    assert_eq!(report.unplaced_items.len(), 1);
+    assert_eq!(report.unplaced_items[0].count, 1);
+}
+
+#[test]
+fn when_checking_items_it_should_not_change_the_existing_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1,10,4,2.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let new_items = vec![ItemTemplate::from_str("2,10,5,1.0").unwrap()];
+    let _ = can_fit(&packs, &new_items, &pack_template, &[]);
+    assert_eq!(packs[0].items.len(), 1);
+    assert_eq!(packs[0].item_count, 4);
+}
+
+// prefer_homogeneous_packs()
+
+#[test]
+fn when_preferring_homogeneous_packs_it_should_split_the_batches_that_fill_whole_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 4,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,100,6,1.0").unwrap(),
+        ItemTemplate::from_str("2,100,3,1.0").unwrap(),
+        ItemTemplate::from_str("3,100,8,1.0").unwrap(),
+        ItemTemplate::from_str("4,100,1,1.0").unwrap(),
+    ];
+
+    let items = prefer_homogeneous_packs(items, &pack_template, &[]);
+    let batches: Vec<(&str, i32, bool)> = items
+        .iter()
+        .map(|t| (&*t.id, t.count, t.attributes.close_pack_before))
+        .collect();
+    assert_eq!(
+        batches,
+        vec![
+            ("1", 4, true),
+            ("1", 2, true),
+            ("2", 3, false),
+            ("3", 8, true),
+            ("4", 1, true)
+        ]
+    );
+
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    let packs: Vec<Vec<(&str, i32)>> = packs
+        .iter()
+        .map(|p| {
+            p.items
+                .iter()
+                .map(|i| (&*i.item.id, i.item.count))
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        packs,
+        vec![
+            vec![("1", 4)],
+            vec![("1", 2), ("2", 2)],
+            vec![("2", 1)],
+            vec![("3", 4)],
+            vec![("3", 4)],
+            vec![("4", 1)]
+        ]
+    );
+}
+
+#[test]
+fn when_preferring_homogeneous_packs_it_should_use_the_weight_limit_and_keep_bulk_items() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 10.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,100,5,4.0").unwrap(),
+        ItemTemplate::from_str("2,-,TOTAL_KG=30,1.0/kg").unwrap(),
+    ];
+
+    let items = prefer_homogeneous_packs(items, &pack_template, &[]);
+    let batches: Vec<(&str, i32, bool)> = items
+        .iter()
+        .map(|t| (&*t.id, t.count, t.attributes.close_pack_before))
+        .collect();
+    assert_eq!(
+        batches,
+        vec![("1", 4, true), ("1", 1, true), ("2", 1, false)]
+    );
+}
@@ -0,0 +1,98 @@
+//! Named presets of settings, read from a configuration file, so that different teams can run the planner in
+//! the same way without long command lines.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+use crate::errors::Error;
+use crate::model::PackSortOrder;
+use crate::render::OutputFormat;
+use crate::restarts::RestartObjective;
+
+#[cfg(test)]
+#[path = "profiles_tests.rs"]
+mod tests;
+
+/// Contains the settings of a named profile. Settings that are not set in the profile keep their default
+/// value, and settings that are provided on the command line take precedence over the profile.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// The sort order that replaces the sort order of the input.
+    #[serde(default, deserialize_with = "deserialize_sort_order")]
+    pub sort_order: Option<PackSortOrder>,
+
+    /// The number of restarts with shuffled items. When set the restart strategy is used instead of a single
+    /// greedy plan.
+    pub restarts: Option<u32>,
+
+    /// The seed of the random number generator that shuffles the items between restarts.
+    pub seed: Option<u64>,
+
+    /// The objective by which the plans of the restarts are compared.
+    pub objective: Option<RestartObjective>,
+
+    /// The unit of the weights, which is written after the weight of each pack.
+    pub weight_unit: Option<String>,
+
+    /// The time it takes to handle a single item, in seconds, from which the packing time is estimated.
+    pub handling_time: Option<f64>,
+
+    /// The number of decimals with which the lengths and the weights are written.
+    pub precision: Option<usize>,
+
+    /// The format in which the plan is written.
+    pub format: Option<OutputFormat>,
+
+    /// Indicates if the reason for each placement should be written.
+    pub explain: Option<bool>,
+
+    /// Indicates if the items of each pack are listed from the heaviest to the lightest item.
+    pub heaviest_first: Option<bool>,
+
+    /// Indicates if the plan should be checked against the theoretical minimum number of packs.
+    pub quick_check: Option<bool>,
+}
+
+/// Contains the settings read from the configuration file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigurationFile {
+    /// The profiles, by name.
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl ConfigurationFile {
+    /// Returns the profile with the given name.
+    pub fn profile(&self, name: &str) -> Result<&Profile, Error> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| Error::UnknownProfile {
+                profile: name.to_string(),
+            })
+    }
+}
+
+fn deserialize_sort_order<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<PackSortOrder>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    match PackSortOrder::from_str(&value) {
+        Ok(PackSortOrder::NotSet) | Err(_) => Err(D::Error::custom(format!(
+            "unknown sort order {value:?}, expected one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT]"
+        ))),
+        Ok(sort_order) => Ok(Some(sort_order)),
+    }
+}
+
+/// Reads a configuration file in YAML format.
+pub fn parse_configuration_file<R: Read>(reader: R) -> Result<ConfigurationFile, Error> {
+    serde_yaml::from_reader(reader).map_err(|e| Error::InvalidConfigurationFile {
+        reason: e.to_string(),
+    })
+}
@@ -0,0 +1,78 @@
+use super::*;
+
+// parse_configuration_file()
+
+#[test]
+fn when_parsing_a_configuration_file_it_should_return_the_profiles() {
+    let input = "
+profiles:
+  export-eu:
+    sort_order: LONG_TO_SHORT
+    restarts: 20
+    seed: 7
+    objective: balanced-weight
+    weight_unit: kg
+    precision: 2
+    format: json
+    explain: true
+  warehouse:
+    quick_check: true
+    heaviest_first: true
+    handling_time: 6.5
+";
+    let file = parse_configuration_file(input.as_bytes()).expect("Failed to parse the file.");
+    assert_eq!(file.profiles.len(), 2);
+
+    let profile = file
+        .profile("export-eu")
+        .expect("Failed to find the profile.");
+    assert_eq!(profile.sort_order, Some(PackSortOrder::LongToShort));
+    assert_eq!(profile.restarts, Some(20));
+    assert_eq!(profile.seed, Some(7));
+    assert_eq!(profile.objective, Some(RestartObjective::BalancedWeight));
+    assert_eq!(profile.weight_unit, Some("kg".to_string()));
+    assert_eq!(profile.precision, Some(2));
+    assert_eq!(profile.format, Some(OutputFormat::Json));
+    assert_eq!(profile.explain, Some(true));
+    assert_eq!(profile.quick_check, None);
+    assert_eq!(profile.heaviest_first, None);
+
+    let profile = file
+        .profile("warehouse")
+        .expect("Failed to find the profile.");
+    assert_eq!(profile.heaviest_first, Some(true));
+    assert_eq!(profile.handling_time, Some(6.5));
+}
+
+#[test]
+fn when_parsing_a_configuration_file_with_an_unknown_setting_it_should_return_an_error() {
+    let input = "profiles:\n  export-eu:\n    colour: red\n";
+    let result = parse_configuration_file(input.as_bytes());
+    assert!(matches!(
+        result,
+        Err(Error::InvalidConfigurationFile { .. })
+    ));
+}
+
+#[test]
+fn when_parsing_a_configuration_file_with_an_unknown_sort_order_it_should_return_an_error() {
+    let input = "profiles:\n  export-eu:\n    sort_order: HEAVY_FIRST\n";
+    let result = parse_configuration_file(input.as_bytes());
+    assert!(matches!(
+        result,
+        Err(Error::InvalidConfigurationFile { .. })
+    ));
+}
+
+// ConfigurationFile::profile()
+
+#[test]
+fn when_requesting_an_unknown_profile_it_should_return_an_error() {
+    let file = ConfigurationFile::default();
+    assert_eq!(
+        file.profile("export-eu").err().unwrap(),
+        Error::UnknownProfile {
+            profile: "export-eu".to_string()
+        }
+    );
+}
@@ -0,0 +1,122 @@
+//! The progress of a plan that is being made, so that a long running plan can be followed, e.g. with a
+//! progress bar, from another thread.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::model::{Pack, PackedItem};
+use crate::planner::PlanObserver;
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "progress_tests.rs"]
+mod tests;
+
+// The number of characters in the bar of the progress line
+const BAR_WIDTH: usize = 30;
+
+/// Contains the counters of the items that were placed and the packs that were closed by the plan that is
+/// being made. The counters are updated by the planner, through the [`PlanObserver`] implementation, and can
+/// be read from other threads at the same time.
+#[derive(Debug, Default)]
+pub struct Progress {
+    total_items: AtomicU64,
+    placed_items: AtomicU64,
+    closed_packs: AtomicU64,
+}
+
+/// Describes the progress of a plan at a single moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProgressSnapshot {
+    /// The number of items that are being planned.
+    pub total_items: u64,
+
+    /// The number of items that were placed in a pack.
+    pub placed_items: u64,
+
+    /// The number of packs that will not change anymore.
+    pub closed_packs: u64,
+}
+
+impl Progress {
+    pub fn new() -> Progress {
+        Progress::default()
+    }
+
+    /// Resets the counters for a new plan of the given number of items.
+    pub fn start(&self, total_items: u64) {
+        self.total_items.store(total_items, Ordering::Relaxed);
+        self.placed_items.store(0, Ordering::Relaxed);
+        self.closed_packs.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns the current values of the counters.
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        ProgressSnapshot {
+            total_items: self.total_items.load(Ordering::Relaxed),
+            placed_items: self.placed_items.load(Ordering::Relaxed),
+            closed_packs: self.closed_packs.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl PlanObserver for &Progress {
+    fn on_item_placed(&mut self, _pack: &Pack, placement: &PackedItem) {
+        self.placed_items
+            .fetch_add(placement.item.count.max(0) as u64, Ordering::Relaxed);
+    }
+
+    fn on_pack_closed(&mut self, _pack: &Pack) {
+        self.closed_packs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_pack_reopened(&mut self, _pack: &Pack) {
+        // The pack is counted again when it is closed again.
+        self.closed_packs.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl ProgressSnapshot {
+    /// Returns the time that is left, estimated from the time it took to place the items so far, or `None`
+    /// if no items were placed yet.
+    pub fn estimated_time_left(&self, elapsed: Duration) -> Option<Duration> {
+        if self.placed_items == 0 {
+            return None;
+        }
+
+        let items_left = self.total_items.saturating_sub(self.placed_items);
+        Some(elapsed.mul_f64(items_left as f64 / self.placed_items as f64))
+    }
+}
+
+/// Writes the progress as a single line with a bar, the number of items that were placed, the number of
+/// closed packs and the estimated time that is left, e.g.
+/// `[###############               ] 500/1000 items, 12 packs closed, ETA 0:42`.
+pub fn write_progress_line<W: Write>(
+    writer: &mut W,
+    snapshot: &ProgressSnapshot,
+    elapsed: Duration,
+) -> io::Result<()> {
+    let placed_items = snapshot.placed_items.min(snapshot.total_items);
+    let filled = if snapshot.total_items == 0 {
+        0
+    } else {
+        (placed_items as usize * BAR_WIDTH) / snapshot.total_items as usize
+    };
+
+    write!(
+        writer,
+        "[{}{}] {placed_items}/{} items, {} packs closed, ETA ",
+        "#".repeat(filled),
+        " ".repeat(BAR_WIDTH - filled),
+        snapshot.total_items,
+        snapshot.closed_packs
+    )?;
+    match snapshot.estimated_time_left(elapsed) {
+        Some(left) => {
+            let seconds = left.as_secs();
+            write!(writer, "{}:{:02}", seconds / 60, seconds % 60)
+        }
+        None => write!(writer, "-"),
+    }
+}
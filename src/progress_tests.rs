@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use super::*;
+use crate::model::{ItemTemplate, PackSortOrder, PackTemplate};
+use crate::planner::plan_packs_with_observer;
+
+// Progress
+
+#[test]
+fn when_planning_with_progress_it_should_count_the_placed_items_and_the_closed_packs() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,8,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,4,1.0").unwrap(),
+    ];
+
+    let progress = Progress::new();
+    progress.start(12);
+    plan_packs_with_observer(&items, &pack_template, &[], &mut &progress)
+        .expect("Failed to plan the packs.");
+    assert_eq!(
+        progress.snapshot(),
+        ProgressSnapshot {
+            total_items: 12,
+            placed_items: 12,
+            closed_packs: 2,
+        }
+    );
+}
+
+// write_progress_line()
+
+#[test]
+fn when_writing_the_progress_it_should_write_the_bar_the_counts_and_the_time_left() {
+    let snapshot = ProgressSnapshot {
+        total_items: 1000,
+        placed_items: 500,
+        closed_packs: 12,
+    };
+    let mut output = Vec::new();
+    write_progress_line(&mut output, &snapshot, Duration::from_secs(42))
+        .expect("Failed to write the progress.");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "[###############               ] 500/1000 items, 12 packs closed, ETA 0:42"
+    );
+}
+
+#[test]
+fn when_writing_the_progress_before_any_item_is_placed_it_should_not_estimate_the_time_left() {
+    let snapshot = ProgressSnapshot {
+        total_items: 1000,
+        ..ProgressSnapshot::default()
+    };
+    let mut output = Vec::new();
+    write_progress_line(&mut output, &snapshot, Duration::from_secs(3))
+        .expect("Failed to write the progress.");
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("0/1000 items, 0 packs closed, ETA -"));
+}
@@ -0,0 +1,225 @@
+//! Suggestions for the limits of the packs that could be raised when items don't fit in an empty pack, so
+//! that the user knows which limit stands in the way of a complete plan.
+//!
+//! Each limit is raised on its own, and the smallest value at which all the items can be planned is found by
+//! probing values between the current limit and a value at which all the items fit in a single pack.
+
+use std::fmt;
+
+use crate::errors::Error;
+use crate::model::{ItemTemplate, PackRule, PackTemplate};
+use crate::planner::is_infeasible;
+use crate::strip::plan_sheets;
+use crate::{prepare_items, Configuration};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "relaxation_tests.rs"]
+mod tests;
+
+// The number of probes used to find the smallest value of a limit that is a decimal number
+const NUMBER_OF_PROBES: usize = 40;
+
+/// Defines the limit of a pack that is raised.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RelaxedLimit {
+    MaximumWeight,
+    MaximumNumberOfPieces,
+    MaximumLength,
+    MaximumValue,
+}
+
+impl fmt::Display for RelaxedLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RelaxedLimit::MaximumWeight => "max weight",
+            RelaxedLimit::MaximumNumberOfPieces => "max pieces",
+            RelaxedLimit::MaximumLength => "max length",
+            RelaxedLimit::MaximumValue => "max value",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Describes a limit of the pack that, when raised to the given value, allows all the items to be planned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relaxation {
+    /// The limit that is raised.
+    pub limit: RelaxedLimit,
+
+    /// The smallest value of the limit at which all the items can be planned. Decimal limits are rounded up
+    /// to one decimal.
+    pub value: f64,
+
+    /// The number of packs of the plan with the raised limit.
+    pub number_of_packs: usize,
+}
+
+impl fmt::Display for Relaxation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "raising {} to {} would fit all items in {} packs",
+            self.limit, self.value, self.number_of_packs
+        )
+    }
+}
+
+/// Returns the number of packs if all the items can be planned with the pack template, without placing an
+/// item on its own in an oversize pack, or `None` otherwise.
+fn plan_all_items(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Option<usize> {
+    if items
+        .iter()
+        .any(|t| t.count > 0 && is_infeasible(t, pack_template, rules))
+    {
+        return None;
+    }
+
+    plan_sheets(items, pack_template, rules)
+        .ok()
+        .map(|outcome| outcome.packs.len())
+}
+
+/// Returns the pack template with the limit set to the given value.
+fn relax(pack_template: &PackTemplate, limit: RelaxedLimit, value: f64) -> PackTemplate {
+    let mut relaxed = pack_template.clone();
+    match limit {
+        RelaxedLimit::MaximumWeight => {
+            relaxed.maximum_weight = value;
+            relaxed.hard_maximum_weight = relaxed.hard_maximum_weight.map(|w| w.max(value));
+        }
+        RelaxedLimit::MaximumNumberOfPieces => relaxed.maximum_number_of_pieces = value as i32,
+        RelaxedLimit::MaximumLength => relaxed.maximum_length = Some(value),
+        RelaxedLimit::MaximumValue => relaxed.maximum_value = Some(value),
+    }
+    relaxed
+}
+
+/// Finds the smallest value of the limit, above the current value, at which all the items can be planned.
+/// The 'upper_bound' is a value at which all the items fit in a single pack.
+fn find_relaxation(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+    limit: RelaxedLimit,
+    current: f64,
+    upper_bound: f64,
+) -> Option<Relaxation> {
+    let plan = |value: f64| plan_all_items(items, &relax(pack_template, limit, value), rules);
+
+    let is_whole_number = limit == RelaxedLimit::MaximumNumberOfPieces;
+    let round_up = |value: f64| {
+        if is_whole_number {
+            value.ceil()
+        } else {
+            (value * 10.0).ceil() / 10.0
+        }
+    };
+
+    let mut lower = current;
+    let mut upper = round_up(upper_bound.max(current));
+    plan(upper)?;
+    for _ in 0..NUMBER_OF_PROBES {
+        let middle = round_up((lower + upper) / 2.0);
+        if middle <= lower || middle >= upper {
+            break;
+        }
+
+        if plan(middle).is_some() {
+            upper = middle;
+        } else {
+            lower = middle;
+        }
+    }
+
+    plan(upper).map(|number_of_packs| Relaxation {
+        limit,
+        value: upper,
+        number_of_packs,
+    })
+}
+
+/// Returns, for each limit of the pack, the smallest value to which the limit can be raised so that all the
+/// items can be planned, in the order weight, pieces, length and value. Limits that don't help are left out.
+/// Returns no relaxations if all the items can already be planned. The rules and the sheet size are not
+/// relaxed.
+pub fn probe_relaxations(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Vec<Relaxation> {
+    if plan_all_items(items, pack_template, rules).is_some() {
+        return Vec::new();
+    }
+
+    let items_to_place = || items.iter().filter(|t| t.count > 0);
+    let total_weight: f64 = items_to_place()
+        .map(|t| {
+            if t.attributes.bulk {
+                t.weight
+            } else {
+                t.weight * t.count as f64
+            }
+        })
+        .sum();
+    let total_pieces: i32 = items_to_place()
+        .map(|t| if t.attributes.bulk { 1 } else { t.count })
+        .sum();
+    let total_length: f64 = items_to_place()
+        .map(|t| t.length * t.count as f64)
+        .sum::<f64>()
+        * 2.0;
+    let total_value: f64 = items_to_place()
+        .map(|t| t.attributes.value.unwrap_or_default() * t.count as f64)
+        .sum();
+
+    let mut limits = vec![
+        (
+            RelaxedLimit::MaximumWeight,
+            pack_template.maximum_weight,
+            total_weight,
+        ),
+        (
+            RelaxedLimit::MaximumNumberOfPieces,
+            pack_template.maximum_number_of_pieces as f64,
+            total_pieces as f64,
+        ),
+    ];
+    if let Some(maximum_length) = pack_template.maximum_length {
+        limits.push((RelaxedLimit::MaximumLength, maximum_length, total_length));
+    }
+    if let Some(maximum_value) = pack_template.maximum_value {
+        limits.push((RelaxedLimit::MaximumValue, maximum_value, total_value));
+    }
+
+    limits
+        .into_iter()
+        .filter_map(|(limit, current, upper_bound)| {
+            find_relaxation(items, pack_template, rules, limit, current, upper_bound)
+        })
+        .collect()
+}
+
+/// Prepares the items in the same way as [`crate::plan_items`], and returns the limits of the pack that can
+/// be raised so that all the items can be planned. See [`probe_relaxations`].
+pub fn suggest_relaxations(
+    pack_template: &PackTemplate,
+    item_templates: Vec<ItemTemplate>,
+    configuration: &Configuration,
+) -> Result<Vec<Relaxation>, Error> {
+    let (pack_template, items, _, _) = prepare_items(pack_template, item_templates, configuration)?;
+    let mut relaxations = probe_relaxations(&items, &pack_template, &configuration.rules);
+
+    // The items are planned against the weight without the tare, while the maximum weight includes it.
+    for relaxation in relaxations
+        .iter_mut()
+        .filter(|r| r.limit == RelaxedLimit::MaximumWeight)
+    {
+        relaxation.value += pack_template.tare_weight;
+    }
+
+    Ok(relaxations)
+}
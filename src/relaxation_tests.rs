@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+use super::*;
+use crate::model::PackSortOrder;
+use crate::parser::parse_input;
+
+fn create_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 5,
+        maximum_weight: 20.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    }
+}
+
+// probe_relaxations()
+
+#[test]
+fn when_all_the_items_fit_it_should_not_suggest_a_relaxation() {
+    let items = vec![ItemTemplate::from_str("1,100,7,2.0").unwrap()];
+    assert!(probe_relaxations(&items, &create_pack_template(), &[]).is_empty());
+}
+
+#[test]
+fn when_an_item_is_too_heavy_it_should_suggest_the_smallest_maximum_weight() {
+    let items = vec![
+        ItemTemplate::from_str("1,100,7,2.0").unwrap(),
+        ItemTemplate::from_str("2,80,9,21.53").unwrap(),
+    ];
+
+    let relaxations = probe_relaxations(&items, &create_pack_template(), &[]);
+    assert_eq!(
+        relaxations,
+        vec![Relaxation {
+            limit: RelaxedLimit::MaximumWeight,
+            value: 21.6,
+            number_of_packs: 11,
+        }]
+    );
+    assert_eq!(
+        relaxations[0].to_string(),
+        "raising max weight to 21.6 would fit all items in 11 packs"
+    );
+}
+
+#[test]
+fn when_an_item_is_too_long_it_should_suggest_the_maximum_length() {
+    let pack_template = PackTemplate {
+        maximum_length: Some(150.0),
+        ..create_pack_template()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,100,4,2.0").unwrap(),
+        ItemTemplate::from_str("2,200,2,2.0").unwrap(),
+    ];
+
+    let relaxations = probe_relaxations(&items, &pack_template, &[]);
+    assert_eq!(
+        relaxations,
+        vec![Relaxation {
+            limit: RelaxedLimit::MaximumLength,
+            value: 200.0,
+            number_of_packs: 2,
+        }]
+    );
+}
+
+#[test]
+fn when_a_kit_has_too_many_components_it_should_suggest_the_maximum_pieces() {
+    let input = "NATURAL,2,50.0\nKIT:gift-set-1,1\n";
+    let (pack_template, items) = parse_input(&mut input.as_bytes()).unwrap();
+    let mut configuration = Configuration {
+        keep_kits_together: true,
+        ..Configuration::default()
+    };
+    configuration.bill_of_materials.kits.insert(
+        "gift-set-1".to_string(),
+        vec![
+            ItemTemplate::from_str("1001,300,2,5.0").unwrap(),
+            ItemTemplate::from_str("1002,100,1,10.0").unwrap(),
+        ],
+    );
+
+    let relaxations = suggest_relaxations(&pack_template, items, &configuration)
+        .expect("Failed to suggest the relaxations.");
+    assert_eq!(
+        relaxations,
+        vec![Relaxation {
+            limit: RelaxedLimit::MaximumNumberOfPieces,
+            value: 3.0,
+            number_of_packs: 1,
+        }]
+    );
+}
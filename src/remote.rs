@@ -0,0 +1,369 @@
+//! Fetching the items from, and posting the plans and the notifications to, an HTTP endpoint.
+//!
+//! The endpoint returns the pack information and the items as a JSON document. The support for HTTP depends
+//! on the `http` feature. The same document can also be read as CBOR, which depends on the `cbor` feature.
+
+use std::io::Read;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+use crate::model::{ItemAttributes, ItemTemplate, Pack, PackSortOrder, PackTemplate, PlanWarning};
+use crate::render::{OutputFormat, RenderOptions};
+
+#[cfg(test)]
+#[path = "remote_tests.rs"]
+mod tests;
+
+/// The pack information in the document returned by the endpoint.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackDocument {
+    sort_order: String,
+    maximum_number_of_pieces: i32,
+    maximum_weight: f64,
+    #[serde(default)]
+    maximum_length: Option<f64>,
+    #[serde(default)]
+    hard_maximum_weight: Option<f64>,
+    #[serde(default)]
+    maximum_value: Option<f64>,
+}
+
+/// A single item in the document returned by the endpoint.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ItemDocument {
+    id: String,
+    length: f64,
+    quantity: i32,
+    weight: f64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    value: Option<f64>,
+}
+
+/// The document returned by the endpoint.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OrderDocument {
+    pack: PackDocument,
+    items: Vec<ItemDocument>,
+}
+
+/// Reads the pack information and the items from a JSON document. The 'url' is the origin of the document,
+/// which is used in the errors.
+pub fn parse_order<R: Read>(
+    reader: R,
+    url: &str,
+) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
+    let to_order_error = |reason: String| Error::InvalidRemoteOrder {
+        url: url.to_string(),
+        reason,
+    };
+
+    let document: OrderDocument =
+        serde_json::from_reader(reader).map_err(|e| to_order_error(e.to_string()))?;
+    to_templates(document, url)
+}
+
+/// Reads the pack information and the items from a CBOR document, which has the same contents as the JSON
+/// document of [`parse_order`]. The 'source' is the origin of the document, which is used in the errors.
+#[cfg(feature = "cbor")]
+pub fn parse_cbor_order<R: Read>(
+    reader: R,
+    source: &str,
+) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
+    let to_order_error = |reason: String| Error::InvalidRemoteOrder {
+        url: source.to_string(),
+        reason,
+    };
+
+    // CBOR keeps integers and floating point numbers apart, so the document is read through a JSON value,
+    // which accepts an integer where a floating point number is expected, in the same way as a JSON order.
+    let value: serde_json::Value =
+        ciborium::from_reader(reader).map_err(|e| to_order_error(e.to_string()))?;
+    let document: OrderDocument =
+        serde_json::from_value(value).map_err(|e| to_order_error(e.to_string()))?;
+    to_templates(document, source)
+}
+
+#[cfg(not(feature = "cbor"))]
+pub fn parse_cbor_order<R: Read>(
+    _reader: R,
+    _source: &str,
+) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
+    Err(Error::UnsupportedCbor)
+}
+
+/// Verifies the document and converts it to the pack template and the items.
+fn to_templates(
+    document: OrderDocument,
+    url: &str,
+) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
+    let to_order_error = |reason: String| Error::InvalidRemoteOrder {
+        url: url.to_string(),
+        reason,
+    };
+
+    let sort_order = PackSortOrder::from_str(&document.pack.sort_order).map_err(|_| {
+        to_order_error(format!(
+            "The sort order {:?} is not known. Expected one of [NATURAL, SHORT_TO_LONG, LONG_TO_SHORT].",
+            document.pack.sort_order
+        ))
+    })?;
+    if document.pack.maximum_number_of_pieces <= 0 || document.pack.maximum_weight <= 0.0 {
+        return Err(to_order_error(
+            "The maximum number of pieces and the maximum weight of a pack must be positive."
+                .to_string(),
+        ));
+    }
+
+    if document
+        .pack
+        .hard_maximum_weight
+        .is_some_and(|w| w < document.pack.maximum_weight)
+    {
+        return Err(to_order_error(
+            "The hard maximum weight of a pack must be at least the maximum weight.".to_string(),
+        ));
+    }
+
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: document.pack.maximum_number_of_pieces,
+        maximum_weight: document.pack.maximum_weight,
+        sort_order,
+        maximum_length: document.pack.maximum_length,
+        hard_maximum_weight: document.pack.hard_maximum_weight,
+        maximum_value: document.pack.maximum_value,
+        ..PackTemplate::new()
+    };
+
+    let items = document
+        .items
+        .into_iter()
+        .map(|item| {
+            if item.quantity < 0 || item.length < 0.0 || item.weight < 0.0 {
+                return Err(to_order_error(format!(
+                    "The item {:?} has a negative length, quantity or weight.",
+                    item.id
+                )));
+            }
+
+            Ok(ItemTemplate {
+                id: item.id.into(),
+                length: item.length,
+                weight: item.weight,
+                count: item.quantity,
+                attributes: Arc::new(ItemAttributes {
+                    tags: item.tags,
+                    value: item.value,
+                    ..ItemAttributes::default()
+                }),
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok((pack_template, items))
+}
+
+/// Defines what completed the jobs of a notification.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationSource {
+    /// A batch of plans described by a manifest.
+    Batch,
+
+    /// A single plan request to the daemon.
+    Daemon,
+}
+
+/// Describes the outcome of a single job in a notification.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct JobSummary {
+    /// The path to the input file of the job, if the job read its input from a file.
+    pub input: Option<String>,
+
+    /// The path to the output file of the job, if the job wrote its plan to a file.
+    pub output: Option<String>,
+
+    /// Indicates if the job succeeded.
+    pub succeeded: bool,
+
+    /// The description of the error that stopped the job, if the job failed.
+    pub error: Option<String>,
+
+    /// The descriptions of the warnings that were raised while planning.
+    pub warnings: Vec<String>,
+}
+
+/// The summary that is posted, as a JSON document, to the notification endpoint when jobs have completed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Notification {
+    /// What completed the jobs.
+    pub source: NotificationSource,
+
+    /// The number of jobs that succeeded.
+    pub succeeded: usize,
+
+    /// The number of jobs that failed.
+    pub failed: usize,
+
+    /// The outcome of each job.
+    pub jobs: Vec<JobSummary>,
+}
+
+impl Notification {
+    /// Creates the notification of the given jobs.
+    pub fn new(source: NotificationSource, jobs: Vec<JobSummary>) -> Notification {
+        let succeeded = jobs.iter().filter(|j| j.succeeded).count();
+        Notification {
+            source,
+            succeeded,
+            failed: jobs.len() - succeeded,
+            jobs,
+        }
+    }
+
+    /// Creates the notification of a single plan request to the daemon.
+    pub fn for_request(result: &Result<Vec<PlanWarning>, Error>) -> Notification {
+        let job = JobSummary {
+            input: None,
+            output: None,
+            succeeded: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            warnings: result
+                .as_ref()
+                .map(|warnings| warnings.iter().map(|w| w.to_string()).collect())
+                .unwrap_or_default(),
+        };
+        Notification::new(NotificationSource::Daemon, vec![job])
+    }
+}
+
+/// Returns the media type of a plan written in the given format.
+pub fn content_type(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Text => "text/plain; charset=utf-8",
+        OutputFormat::Json => "application/json",
+        OutputFormat::Markdown => "text/markdown; charset=utf-8",
+        OutputFormat::Worksheet => "text/plain; charset=utf-8",
+        OutputFormat::Cbor => "application/cbor",
+        OutputFormat::Arrow => "application/vnd.apache.arrow.file",
+        OutputFormat::Parquet => "application/vnd.apache.parquet",
+    }
+}
+
+#[cfg(feature = "http")]
+pub(crate) fn to_request_error(url: &str, error: ureq::Error) -> Error {
+    let reason = match error {
+        ureq::Error::Status(code, response) => {
+            format!(
+                "The endpoint responded with status {} {}.",
+                code,
+                response.status_text()
+            )
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    };
+
+    Error::RemoteRequestFailed {
+        url: url.to_string(),
+        reason,
+    }
+}
+
+/// Fetches the pack information and the items from the endpoint at the given URL.
+#[cfg(feature = "http")]
+pub fn fetch_order(url: &str) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
+    let response = ureq::get(url)
+        .set("Accept", "application/json")
+        .call()
+        .map_err(|e| to_request_error(url, e))?;
+
+    parse_order(response.into_reader(), url)
+}
+
+#[cfg(not(feature = "http"))]
+pub fn fetch_order(url: &str) -> Result<(PackTemplate, Vec<ItemTemplate>), Error> {
+    Err(Error::UnsupportedHttp {
+        url: url.to_string(),
+    })
+}
+
+/// Posts the plan, written in the given format, to the endpoint at the given URL.
+#[cfg(feature = "http")]
+pub fn post_plan(url: &str, format: OutputFormat, plan: &[u8]) -> Result<(), Error> {
+    ureq::post(url)
+        .set("Content-Type", content_type(format))
+        .send_bytes(plan)
+        .map_err(|e| to_request_error(url, e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+pub fn post_plan(url: &str, _format: OutputFormat, _plan: &[u8]) -> Result<(), Error> {
+    Err(Error::UnsupportedHttp {
+        url: url.to_string(),
+    })
+}
+
+/// Posts the notification, as a JSON document, to the endpoint at the given URL.
+#[cfg(feature = "http")]
+pub fn post_notification(url: &str, notification: &Notification) -> Result<(), Error> {
+    let body = serde_json::to_vec(notification).map_err(|e| Error::RemoteRequestFailed {
+        url: url.to_string(),
+        reason: e.to_string(),
+    })?;
+    ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_bytes(&body)
+        .map_err(|e| to_request_error(url, e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+pub fn post_notification(url: &str, _notification: &Notification) -> Result<(), Error> {
+    Err(Error::UnsupportedHttp {
+        url: url.to_string(),
+    })
+}
+
+/// Posts the pack, as a JSON document in the same way as the packs of a plan in the JSON format, to the
+/// endpoint at the given URL.
+#[cfg(feature = "http")]
+pub fn post_closed_pack(
+    url: &str,
+    pack: &Pack,
+    render_options: &RenderOptions,
+) -> Result<(), Error> {
+    let mut body = Vec::new();
+    crate::render::write_json_pack(&mut body, pack, render_options).map_err(|e| {
+        Error::RemoteRequestFailed {
+            url: url.to_string(),
+            reason: e.to_string(),
+        }
+    })?;
+    ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_bytes(&body)
+        .map_err(|e| to_request_error(url, e))?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+pub fn post_closed_pack(
+    url: &str,
+    _pack: &Pack,
+    _render_options: &RenderOptions,
+) -> Result<(), Error> {
+    Err(Error::UnsupportedHttp {
+        url: url.to_string(),
+    })
+}
@@ -0,0 +1,156 @@
+use super::*;
+
+#[cfg(feature = "http")]
+use crate::test_support::serve_single_request;
+
+const ORDER: &str = r#"{
+    "pack": { "sort_order": "NATURAL", "maximum_number_of_pieces": 40, "maximum_weight": 500.0 },
+    "items": [
+        { "id": "1001", "length": 6200, "quantity": 30, "weight": 9.653, "tags": ["COLD"] },
+        { "id": "2001", "length": 7200, "quantity": 50, "weight": 11.21 }
+    ]
+}"#;
+
+// parse_order()
+
+#[test]
+fn when_parsing_an_order_it_should_return_the_pack_and_the_items() {
+    let (pack_template, items) = parse_order(ORDER.as_bytes(), "http://test").unwrap();
+
+    assert_eq!(pack_template.sort_order, PackSortOrder::Natural);
+    assert_eq!(pack_template.maximum_number_of_pieces, 40);
+    assert_eq!(pack_template.maximum_weight, 500.0);
+    assert_eq!(pack_template.maximum_length, None);
+
+    assert_eq!(items.len(), 2);
+    assert_eq!(&*items[0].id, "1001");
+    assert_eq!(items[0].length, 6200.0);
+    assert_eq!(items[0].count, 30);
+    assert_eq!(items[0].weight, 9.653);
+    assert!(items[0].attributes.has_tag("COLD"));
+    assert!(items[1].attributes.tags.is_empty());
+}
+
+#[test]
+fn when_parsing_an_order_with_an_unknown_sort_order_it_should_return_an_error() {
+    let order = ORDER.replace("NATURAL", "RANDOM");
+    let result = parse_order(order.as_bytes(), "http://test");
+    assert!(matches!(result, Err(Error::InvalidRemoteOrder { .. })));
+}
+
+#[test]
+fn when_parsing_an_order_with_a_negative_quantity_it_should_return_an_error() {
+    let order = ORDER.replace("\"quantity\": 30", "\"quantity\": -30");
+    let result = parse_order(order.as_bytes(), "http://test");
+    assert!(matches!(result, Err(Error::InvalidRemoteOrder { .. })));
+}
+
+#[test]
+fn when_parsing_an_invalid_document_it_should_return_an_error() {
+    let result = parse_order(&b"NATURAL,40,500.0"[..], "http://test");
+    assert!(matches!(result, Err(Error::InvalidRemoteOrder { .. })));
+}
+
+// parse_cbor_order()
+
+#[cfg(feature = "cbor")]
+#[test]
+fn when_parsing_a_cbor_order_it_should_return_the_same_pack_and_items_as_the_json_order() {
+    let document: serde_json::Value = serde_json::from_str(ORDER).unwrap();
+    let mut order = Vec::new();
+    ciborium::into_writer(&document, &mut order).unwrap();
+
+    let (pack_template, items) = parse_cbor_order(order.as_slice(), "test").unwrap();
+    assert_eq!(pack_template.maximum_number_of_pieces, 40);
+    assert_eq!(pack_template.maximum_weight, 500.0);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].length, 6200.0);
+    assert!(items[0].attributes.has_tag("COLD"));
+
+    let result = parse_cbor_order(ORDER.as_bytes(), "test");
+    assert!(matches!(result, Err(Error::InvalidRemoteOrder { .. })));
+}
+
+// fetch_order() and post_plan()
+
+#[cfg(feature = "http")]
+#[test]
+fn when_fetching_an_order_it_should_return_the_items_from_the_endpoint() {
+    let (address, server) = serve_single_request("200 OK", ORDER);
+    let url = format!("{address}/orders");
+
+    let (_, items) = fetch_order(&url).unwrap();
+    assert_eq!(items.len(), 2);
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("GET /orders HTTP/1.1"));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn when_the_endpoint_returns_an_error_status_it_should_return_an_error() {
+    let (address, server) = serve_single_request("503 Service Unavailable", "");
+    let url = format!("{address}/orders");
+
+    let result = fetch_order(&url);
+    assert!(matches!(result, Err(Error::RemoteRequestFailed { .. })));
+    server.join().unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn when_posting_a_plan_it_should_send_the_plan_to_the_endpoint() {
+    let (address, server) = serve_single_request("204 No Content", "");
+    let url = format!("{address}/orders");
+
+    post_plan(&url, OutputFormat::Json, b"{\"packs\":[]}").unwrap();
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /orders HTTP/1.1"));
+    assert!(request
+        .to_ascii_lowercase()
+        .contains("content-type: application/json"));
+    assert!(request.ends_with("{\"packs\":[]}"));
+}
+
+// post_notification()
+
+#[test]
+fn when_creating_the_notification_of_a_request_it_should_report_the_outcome() {
+    let notification = Notification::for_request(&Err(Error::MissingPackInformation));
+    assert_eq!(notification.source, NotificationSource::Daemon);
+    assert_eq!((notification.succeeded, notification.failed), (0, 1));
+    assert_eq!(
+        notification.jobs[0].error,
+        Some(Error::MissingPackInformation.to_string())
+    );
+
+    let notification = Notification::for_request(&Ok(Vec::new()));
+    assert_eq!((notification.succeeded, notification.failed), (1, 0));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn when_posting_a_notification_it_should_send_the_summary_as_json() {
+    let (address, server) = serve_single_request("204 No Content", "");
+    let url = format!("{address}/orders");
+
+    let notification = Notification::for_request(&Ok(Vec::new()));
+    post_notification(&url, &notification).unwrap();
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /orders HTTP/1.1"));
+    assert!(request
+        .to_ascii_lowercase()
+        .contains("content-type: application/json"));
+    assert!(request.ends_with(
+        r#"{"source":"daemon","succeeded":1,"failed":0,"jobs":[{"input":null,"output":null,"succeeded":true,"error":null,"warnings":[]}]}"#
+    ));
+}
+
+#[cfg(not(feature = "http"))]
+#[test]
+fn when_http_is_not_supported_it_should_return_an_error() {
+    let result = fetch_order("http://test");
+    assert!(matches!(result, Err(Error::UnsupportedHttp { .. })));
+}
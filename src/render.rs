@@ -0,0 +1,1609 @@
+//! Writing of the planned packs in a human readable form.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "text-format")]
+use strum::{Display, EnumString};
+
+use crate::attestation::Attestation;
+use crate::model::{
+    find_carton, find_grade, find_weight_band, Backorder, Carton, FitReport, Grade, ItemTemplate,
+    Pack, PackedItem, PlacementLimit, PlanTotals, SheetPlacement, StorageClass, Totals,
+    UnpackedItem, UnpackedReason, WeightBand,
+};
+use crate::table::{write_table_plan, write_table_sections, TableFormat};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "render_tests.rs"]
+mod tests;
+
+/// Defines the formats in which a plan can be written.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// A human readable format.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "text"))]
+    Text,
+
+    /// A machine-readable JSON document, which contains a hash of the contents of each pack.
+    #[cfg_attr(feature = "text-format", strum(serialize = "json"))]
+    Json,
+
+    /// A report with a table per pack and a summary, e.g. for a wiki page.
+    #[cfg_attr(feature = "text-format", strum(serialize = "markdown"))]
+    Markdown,
+
+    /// A pick-to-pack worksheet, which lists for each item the packs that contain the item, so that pickers
+    /// can gather the items one item at a time.
+    #[cfg_attr(feature = "text-format", strum(serialize = "worksheet"))]
+    Worksheet,
+
+    /// A compact binary CBOR document with the same contents as the JSON document, e.g. for callers that plan
+    /// many times a second. Requires the `cbor` feature.
+    #[cfg_attr(feature = "text-format", strum(serialize = "cbor"))]
+    Cbor,
+
+    /// An Arrow IPC file with a row per placement, e.g. for a data platform. Requires the `arrow` feature.
+    #[cfg_attr(feature = "text-format", strum(serialize = "arrow"))]
+    Arrow,
+
+    /// A Parquet file with the same rows as the Arrow IPC file. Requires the `parquet` feature.
+    #[cfg_attr(feature = "text-format", strum(serialize = "parquet"))]
+    Parquet,
+}
+
+// The number of decimals with which the lengths and the weights are written by default
+const DEFAULT_PRECISION: usize = 1;
+
+// The escape code that resets the style of the terminal
+const RESET_STYLE: &str = "\x1b[0m";
+
+/// Defines when the text output is colored.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+pub enum ColorChoice {
+    /// Colors are used when the output is written to a terminal.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "auto"))]
+    Auto,
+
+    /// Colors are always used, e.g. when the output is piped to a pager that supports colors.
+    #[cfg_attr(feature = "text-format", strum(serialize = "always"))]
+    Always,
+
+    /// Colors are never used.
+    #[cfg_attr(feature = "text-format", strum(serialize = "never"))]
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns `true` if colors should be used for output that is, or isn't, written to a terminal.
+    pub fn is_enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => is_terminal,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Defines the styles of the colored text output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    /// The headers of the packs and the storage classes.
+    Header,
+
+    /// The warnings, e.g. a plan that doesn't reach the minimum number of packs.
+    Warning,
+
+    /// The packs that exceed their capacity and the items that don't fit.
+    OverCapacity,
+}
+
+impl Style {
+    fn escape_code(self) -> &'static str {
+        match self {
+            Style::Header => "\x1b[1;36m",
+            Style::Warning => "\x1b[33m",
+            Style::OverCapacity => "\x1b[1;31m",
+        }
+    }
+
+    /// Returns the value with the style applied, if colors are enabled.
+    pub fn apply<T: fmt::Display>(self, value: T, is_enabled: bool) -> Styled<T> {
+        Styled {
+            value,
+            style: is_enabled.then_some(self),
+        }
+    }
+}
+
+/// A value that is written with a style. See [`Style::apply`].
+pub struct Styled<T> {
+    value: T,
+    style: Option<Style>,
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.style {
+            Some(style) => write!(f, "{}{}{}", style.escape_code(), self.value, RESET_STYLE),
+            None => self.value.fmt(f),
+        }
+    }
+}
+
+/// Contains the settings that determine how the packs are written.
+#[derive(Clone)]
+pub struct RenderOptions {
+    /// The format in which the plan is written.
+    pub format: OutputFormat,
+
+    /// The carrier weight bands used to classify the packs. No classification is written if there are no bands.
+    pub weight_bands: Vec<WeightBand>,
+
+    /// The cartons, from the smallest to the largest, from which the smallest carton that fits each pack is
+    /// suggested. No carton is written if there are no cartons.
+    pub cartons: Vec<Carton>,
+
+    /// Indicates if the reason for each placement should be written.
+    pub explain: bool,
+
+    /// The number of decimals with which the lengths and the weights are written in the text and Markdown
+    /// formats.
+    pub precision: usize,
+
+    /// The unit of the weights, e.g. `kg`, which is written after the weight of each pack, if the unit is
+    /// known. The weights are never converted.
+    pub weight_unit: Option<String>,
+
+    /// Indicates if the text format is colored with terminal escape codes.
+    pub color: bool,
+
+    /// Indicates if the items of each pack are listed from the heaviest to the lightest item, instead of in
+    /// the order in which they were placed, in the text and Markdown formats. The listing then doubles as
+    /// the stacking order for the packers.
+    pub heaviest_first: bool,
+
+    /// The grades by which the packs are graded on their utilization. No grade is written if there are no
+    /// grades.
+    pub grades: Vec<Grade>,
+
+    /// The time it takes to handle a single item, in seconds, from which the packing time of each pack and of
+    /// the plan is estimated. No packing time is written if the handling time is unknown.
+    pub handling_time: Option<f64>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            format: OutputFormat::default(),
+            weight_bands: Vec::new(),
+            cartons: Vec::new(),
+            explain: false,
+            precision: DEFAULT_PRECISION,
+            weight_unit: None,
+            color: false,
+            heaviest_first: false,
+            grades: Vec::new(),
+            handling_time: None,
+        }
+    }
+}
+
+/// Writes the original ID of an item of which the ID was an alias, as a trailing `ALIAS_OF=[id]` value.
+fn write_alias_of<W: Write>(writer: &mut W, item: &ItemTemplate) -> io::Result<()> {
+    match &item.attributes.alias_of {
+        Some(alias) => write!(writer, ",ALIAS_OF={}", alias),
+        None => Ok(()),
+    }
+}
+
+fn write_item_line<W: Write>(
+    writer: &mut W,
+    item: &ItemTemplate,
+    precision: usize,
+) -> io::Result<()> {
+    if item.attributes.bulk {
+        write!(
+            writer,
+            "{},-,TOTAL_KG={:.*}",
+            item.id, precision, item.weight
+        )?;
+    } else {
+        write!(
+            writer,
+            "{},{:.*},{},{:.*}",
+            item.id, precision, item.length, item.count, precision, item.weight
+        )?;
+    }
+
+    write_alias_of(writer, item)?;
+    writeln!(writer)
+}
+
+/// Writes the line for a placement. A placement of bulk also shows the weight of the bulk that still needs
+/// to be placed.
+fn write_placement_line<W: Write>(
+    writer: &mut W,
+    packed_item: &PackedItem,
+    precision: usize,
+) -> io::Result<()> {
+    let item = &packed_item.item;
+    match packed_item.bulk_remaining_after() {
+        Some(remaining) => {
+            write!(
+                writer,
+                "{},-,TOTAL_KG={:.*},REMAINING_KG={:.*}",
+                item.id, precision, item.weight, precision, remaining
+            )?;
+            write_alias_of(writer, item)?;
+            writeln!(writer)
+        }
+        None => write_item_line(writer, item, precision),
+    }
+}
+
+fn write_explanation<W: Write>(
+    writer: &mut W,
+    packed_item: &PackedItem,
+    precision: usize,
+) -> io::Result<()> {
+    if let Some(bulk_remaining) = packed_item.bulk_remaining_before {
+        return writeln!(
+            writer,
+            "  Placed {:.*} of {:.*} remaining bulk, limited by: {}. Remaining weight: {:.*} -> {:.*}, remaining pieces: {} -> {}",
+            precision,
+            packed_item.item.weight,
+            precision,
+            bulk_remaining,
+            packed_item.limit,
+            precision,
+            packed_item.remaining_weight_before,
+            precision,
+            packed_item.remaining_weight_after(),
+            packed_item.remaining_pieces_before,
+            packed_item.remaining_pieces_after()
+        );
+    }
+
+    writeln!(
+        writer,
+        "  Placed {} of {} remaining items, limited by: {}. Remaining weight: {:.*} -> {:.*}, remaining pieces: {} -> {}",
+        packed_item.item.count,
+        packed_item.items_remaining_in_batch,
+        packed_item.limit,
+        precision,
+        packed_item.remaining_weight_before,
+        precision,
+        packed_item.remaining_weight_after(),
+        packed_item.remaining_pieces_before,
+        packed_item.remaining_pieces_after()
+    )
+}
+
+fn write_footer<W: Write>(
+    writer: &mut W,
+    pack: &Pack,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    let precision = render_options.precision;
+    write!(
+        writer,
+        "Pack Length: {:.*}, Pack Weight: {:.*}",
+        precision, pack.length, precision, pack.weight
+    )?;
+    if let Some(unit) = &render_options.weight_unit {
+        write!(writer, " {}", unit)?;
+    }
+
+    if pack.tare_weight > 0.0 {
+        write!(
+            writer,
+            ", Gross Weight: {:.*}",
+            precision,
+            pack.gross_weight()
+        )?;
+        if let Some(unit) = &render_options.weight_unit {
+            write!(writer, " {}", unit)?;
+        }
+    }
+
+    let color = render_options.color;
+    if pack.overweight {
+        write!(
+            writer,
+            ", {}",
+            Style::OverCapacity.apply("Overweight: REVIEW", color)
+        )?;
+    }
+
+    if pack.value > 0.0 {
+        write!(writer, ", Pack Value: {:.2}", pack.value)?;
+    }
+
+    if pack.exceeds_maximum_value {
+        write!(
+            writer,
+            ", {}",
+            Style::OverCapacity.apply("Maximum Value: EXCEEDED", color)
+        )?;
+    }
+
+    if pack.oversize {
+        write!(
+            writer,
+            ", {}",
+            Style::OverCapacity.apply("Oversize: REVIEW", color)
+        )?;
+    }
+
+    let bands = &render_options.weight_bands;
+    if !bands.is_empty() {
+        match find_weight_band(bands, pack) {
+            Some(band) => write!(
+                writer,
+                ", Weight Class: {}, Estimated Cost: {:.2}",
+                band.name, band.cost
+            )?,
+            None => write!(writer, ", Weight Class: NONE, Estimated Cost: -")?,
+        }
+    }
+
+    let grades = &render_options.grades;
+    if !grades.is_empty() {
+        write!(
+            writer,
+            ", Utilization: {:.0}%, Grade: {}",
+            pack.utilization * 100.0,
+            find_grade(grades, pack.utilization).map_or(NO_GRADE, |g| g.name.as_str())
+        )?;
+    }
+
+    if let Some(handling_time) = render_options.handling_time {
+        write!(
+            writer,
+            ", Packing Time: {}",
+            format_packing_time(pack.packing_time(handling_time))
+        )?;
+    }
+
+    let cartons = &render_options.cartons;
+    if !cartons.is_empty() {
+        match find_carton(cartons, pack) {
+            Some(carton) => write!(writer, ", Carton: {}", carton.name)?,
+            None => write!(
+                writer,
+                ", {}",
+                Style::OverCapacity.apply("Carton: NONE", color)
+            )?,
+        }
+    }
+
+    writeln!(writer)
+}
+
+/// Formats a packing time, given in seconds, in minutes, which is the unit in which labor is scheduled.
+fn format_packing_time(seconds: f64) -> String {
+    format!("{:.1} min", seconds / 60.0)
+}
+
+// The name under which the packs that don't reach any of the grades are counted
+const NO_GRADE: &str = "NONE";
+
+/// Returns the number of packs with each grade, from the highest to the lowest grade, followed by the number
+/// of packs without a grade if there are any. Returns nothing if there are no grades.
+fn count_grades<'a>(packs: &[Pack], render_options: &'a RenderOptions) -> Vec<(&'a str, usize)> {
+    if render_options.grades.is_empty() {
+        return Vec::new();
+    }
+
+    let mut grades: Vec<&Grade> = render_options.grades.iter().collect();
+    grades.sort_by(|a, b| b.minimum_utilization.total_cmp(&a.minimum_utilization));
+    let mut counts: Vec<(&str, usize)> = grades.iter().map(|g| (g.name.as_str(), 0)).collect();
+    let mut ungraded = 0;
+    for pack in packs {
+        match find_grade(&render_options.grades, pack.utilization) {
+            Some(grade) => {
+                if let Some(count) = counts.iter_mut().find(|(name, _)| *name == grade.name) {
+                    count.1 += 1;
+                }
+            }
+            None => ungraded += 1,
+        }
+    }
+
+    if ungraded > 0 {
+        counts.push((NO_GRADE, ungraded));
+    }
+
+    counts
+}
+
+/// Returns the estimated time it takes to pack all the packs, in seconds, if the handling time is known.
+fn total_packing_time(packs: &[Pack], render_options: &RenderOptions) -> Option<f64> {
+    render_options
+        .handling_time
+        .map(|handling_time| packs.iter().map(|p| p.packing_time(handling_time)).sum())
+}
+
+/// Writes the position of a single item on the sheet.
+fn write_sheet_placement_line<W: Write>(
+    writer: &mut W,
+    placement: &SheetPlacement,
+    precision: usize,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "Sheet Position: {} at {:.*},{:.*}, {:.*}x{:.*}",
+        placement.item_id,
+        precision,
+        placement.x,
+        precision,
+        placement.y,
+        precision,
+        placement.length,
+        precision,
+        placement.width
+    )?;
+    if placement.rotated {
+        write!(writer, ", rotated")?;
+    }
+
+    writeln!(writer)
+}
+
+/// Returns the items of the pack in the order in which they are listed. Items of the same weight keep the
+/// order in which they were placed.
+fn listed_items<'a>(pack: &'a Pack, render_options: &RenderOptions) -> Vec<&'a PackedItem> {
+    let mut items: Vec<&PackedItem> = pack.items.iter().collect();
+    if render_options.heaviest_first {
+        items.sort_by(|a, b| b.item.weight.total_cmp(&a.item.weight));
+    }
+
+    items
+}
+
+/// Returns the storage class of the pack if it isn't the default class, i.e. if it should be written.
+fn special_storage_class(pack: &Pack) -> Option<StorageClass> {
+    (pack.storage_class != StorageClass::default()).then_some(pack.storage_class)
+}
+
+/// Writes the packs. When the plan contains packs of different storage classes a `Storage Class` line is
+/// written before each group of packs with the same class.
+pub fn write_packs<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    let color = render_options.color;
+    let has_storage_classes = packs.iter().any(|p| special_storage_class(p).is_some());
+    let mut current_storage_class = None;
+    for pack in packs {
+        if has_storage_classes && current_storage_class != Some(pack.storage_class) {
+            let header = format!("Storage Class: {}", pack.storage_class);
+            writeln!(writer, "{}", Style::Header.apply(header, color))?;
+            writeln!(writer)?;
+            current_storage_class = Some(pack.storage_class);
+        }
+
+        let header = match &pack.destination {
+            Some(destination) => {
+                format!("Pack Number: {}, Destination: {}", pack.number, destination)
+            }
+            None => format!("Pack Number: {}", pack.number),
+        };
+        writeln!(writer, "{}", Style::Header.apply(header, color))?;
+        for packed_item in listed_items(pack, render_options) {
+            write_placement_line(writer, packed_item, render_options.precision)?;
+            if render_options.explain {
+                write_explanation(writer, packed_item, render_options.precision)?;
+            }
+        }
+
+        for placement in &pack.sheet_placements {
+            write_sheet_placement_line(writer, placement, render_options.precision)?;
+        }
+
+        write_footer(writer, pack, render_options)?;
+        writeln!(writer)?;
+    }
+
+    let grade_counts = count_grades(packs, render_options);
+    if !grade_counts.is_empty() {
+        let counts: Vec<String> = grade_counts
+            .iter()
+            .map(|(name, count)| format!("{name}: {count}"))
+            .collect();
+        writeln!(writer, "Grades: {}", counts.join(", "))?;
+        writeln!(writer)?;
+    }
+
+    if let Some(packing_time) = total_packing_time(packs, render_options) {
+        writeln!(
+            writer,
+            "Total Packing Time: {}",
+            format_packing_time(packing_time)
+        )?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the totals of the items that were planned, packed and left out, so that a plan that loses items
+/// stands out.
+pub fn write_totals<W: Write>(
+    writer: &mut W,
+    totals: &PlanTotals,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    let precision = render_options.precision;
+    write!(
+        writer,
+        "Totals: Input Items: {}, Input Weight: {:.*}, Packed Items: {}, Packed Weight: {:.*}",
+        totals.input.items,
+        precision,
+        totals.input.weight,
+        totals.packed.items,
+        precision,
+        totals.packed.weight
+    )?;
+    if totals.left_out != Totals::default() {
+        write!(
+            writer,
+            ", Left Out Items: {}, Left Out Weight: {:.*}",
+            totals.left_out.items, precision, totals.left_out.weight
+        )?;
+    }
+
+    writeln!(writer)?;
+    writeln!(writer)
+}
+
+/// Returns the effective configuration of the attestation as a TOML document.
+fn attested_configuration(attestation: &Attestation) -> io::Result<String> {
+    attestation
+        .configuration
+        .to_toml()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// Writes the attestation of the plan, followed by the lines of the effective configuration, as TOML, each
+/// indented by two spaces. The empty lines of the configuration are left out, so that the attestation ends
+/// at the first empty line.
+pub fn write_attestation<W: Write>(
+    writer: &mut W,
+    attestation: &Attestation,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{} Tool Version: {}, Input SHA-256: {}, Timestamp: {}",
+        Style::Header.apply("Attestation:", render_options.color),
+        attestation.tool_version,
+        attestation.input_sha256,
+        attestation.timestamp
+    )?;
+    for line in attested_configuration(attestation)?.lines() {
+        if !line.is_empty() {
+            writeln!(writer, "  {}", line)?;
+        }
+    }
+
+    writeln!(writer)
+}
+
+/// Writes a line for each item of which fewer items are available than were ordered.
+pub fn write_backorders<W: Write>(
+    writer: &mut W,
+    backorders: &[Backorder],
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    for backorder in backorders {
+        writeln!(
+            writer,
+            "{} {}, Ordered: {}, Available: {}, Short: {}",
+            Style::Warning.apply("Backorder:", render_options.color),
+            backorder.item_id,
+            backorder.ordered,
+            backorder.available,
+            backorder.shortfall()
+        )?;
+    }
+
+    if backorders.is_empty() {
+        Ok(())
+    } else {
+        writeln!(writer)
+    }
+}
+
+/// Writes a line for each number of items that is not packed, with the reason why the items are not packed.
+pub fn write_unpacked_items<W: Write>(
+    writer: &mut W,
+    unpacked_items: &[UnpackedItem],
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    for unpacked_item in unpacked_items {
+        writeln!(
+            writer,
+            "{} {}, Count: {}, Reason: {}",
+            Style::Warning.apply("Unpacked:", render_options.color),
+            unpacked_item.item_id,
+            unpacked_item.count,
+            unpacked_item.reason
+        )?;
+    }
+
+    if unpacked_items.is_empty() {
+        Ok(())
+    } else {
+        writeln!(writer)
+    }
+}
+
+pub fn write_fit_report<W: Write>(
+    writer: &mut W,
+    report: &FitReport,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    let color = render_options.color;
+    if report.fits() {
+        writeln!(writer, "Additional Items: FIT")?;
+    } else {
+        writeln!(
+            writer,
+            "Additional Items: {}",
+            Style::OverCapacity.apply("DO NOT FIT", color)
+        )?;
+    }
+
+    for placement in &report.placements {
+        write!(writer, "Pack Number: {}, ", placement.pack_number)?;
+        write_item_line(writer, &placement.item, render_options.precision)?;
+    }
+
+    for item in &report.unplaced_items {
+        write!(writer, "{} ", Style::OverCapacity.apply("Unplaced:", color))?;
+        write_item_line(writer, item, render_options.precision)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// A batch of items in the JSON format.
+struct ItemDocument<'a> {
+    /// The ID of the item.
+    id: &'a str,
+
+    /// The length of a single item.
+    length: f64,
+
+    /// The number of items.
+    count: i32,
+
+    /// The weight of a single item, or the weight of the bulk for a bulk item.
+    weight: f64,
+
+    /// Indicates if the item is a bulk item, which is measured by its weight. Only present for bulk items.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    bulk: bool,
+
+    /// The ID with which the item was provided, if that ID was an alias of the ID of the item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias_of: Option<&'a str>,
+}
+
+impl<'a> From<&'a ItemTemplate> for ItemDocument<'a> {
+    fn from(item: &'a ItemTemplate) -> Self {
+        ItemDocument {
+            id: &item.id,
+            length: item.length,
+            count: item.count,
+            weight: item.weight,
+            bulk: item.attributes.bulk,
+            alias_of: item.attributes.alias_of.as_deref(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// The items of a batch that were placed in a pack.
+struct PlacementDocument<'a> {
+    #[serde(flatten)]
+    item: ItemDocument<'a>,
+
+    /// The limit that determined the number of items that were placed, if the placements are explained.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit: Option<String>,
+
+    /// The weight of the bulk that still needs to be placed after this placement, for a bulk item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bulk_remaining: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// The position of an item on the sheet of a pack.
+struct SheetPlacementDocument<'a> {
+    /// The ID of the item.
+    id: &'a str,
+
+    /// The position of the item along the length of the sheet.
+    x: f64,
+
+    /// The position of the item along the width of the sheet.
+    y: f64,
+
+    /// The length of the item on the sheet.
+    length: f64,
+
+    /// The width of the item on the sheet.
+    width: f64,
+
+    /// Indicates if the item is rotated on the sheet. Only present for rotated items.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    rotated: bool,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// A pack of the plan.
+struct PackDocument<'a> {
+    /// The number of the pack.
+    number: usize,
+
+    /// The items in the pack, in the order in which they were placed.
+    items: Vec<PlacementDocument<'a>>,
+
+    /// The length of the pack.
+    length: f64,
+
+    /// The net weight of the items in the pack.
+    weight: f64,
+
+    /// The weight of the items and the packaging, if the packaging has a weight.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gross_weight: Option<f64>,
+
+    /// The name of the weight class of the pack, if weight bands are provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weight_class: Option<&'a str>,
+
+    /// The shipping cost of the weight class of the pack, if weight bands are provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_cost: Option<f64>,
+
+    /// The name of the smallest carton that holds the pack, if cartons are provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    carton: Option<&'a str>,
+
+    /// The fraction of the capacity of the pack that is used, if the packs are graded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    utilization: Option<f64>,
+
+    /// The grade of the pack, if the packs are graded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grade: Option<&'a str>,
+
+    /// The time it takes to pack the pack, in seconds, if the handling time is provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    packing_time: Option<f64>,
+
+    /// Indicates if the pack is loaded beyond the maximum weight. Only present for overweight packs.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    overweight: bool,
+
+    /// The total value of the items in the pack, if any of the items has a value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<f64>,
+
+    /// Indicates if the value of the items exceeds the maximum value of the pack. Only present when it does.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    exceeds_maximum_value: bool,
+
+    /// The storage class of the items, if the items are chilled or frozen.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storage_class: Option<String>,
+
+    /// The destination of the items, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<&'a str>,
+
+    /// Indicates if the pack holds an item that doesn't fit in an empty pack. Only present for oversize packs.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    oversize: bool,
+
+    /// The positions of the items on the sheet, if the items are laid out on sheets.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sheet_placements: Vec<SheetPlacementDocument<'a>>,
+
+    /// The SHA-256 hash of the contents of the pack.
+    hash: String,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// An additional item that fits in the remaining space of a pack.
+struct FitPlacementDocument<'a> {
+    /// The number of the pack in which the items fit.
+    pack_number: usize,
+
+    #[serde(flatten)]
+    item: ItemDocument<'a>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// Describes if the additional items fit in the remaining space of the packs.
+struct FitReportDocument<'a> {
+    /// Indicates if all the additional items fit.
+    fits: bool,
+
+    /// The packs in which the additional items fit.
+    placements: Vec<FitPlacementDocument<'a>>,
+
+    /// The additional items that don't fit.
+    unplaced_items: Vec<ItemDocument<'a>>,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// An item of which fewer items are available than were ordered.
+struct BackorderDocument<'a> {
+    /// The ID of the item.
+    id: &'a str,
+
+    /// The number of items that was ordered.
+    ordered: i32,
+
+    /// The number of items that is available.
+    available: i32,
+
+    /// The number of items that is missing.
+    shortfall: i32,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// An item that isn't in any of the packs.
+struct UnpackedItemDocument<'a> {
+    /// The ID of the item.
+    id: &'a str,
+
+    /// The number of items that isn't packed.
+    count: i32,
+
+    /// The reason why the items aren't packed.
+    reason: UnpackedReason,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// The number of packs with a grade.
+struct GradeCountDocument<'a> {
+    /// The name of the grade.
+    grade: &'a str,
+
+    /// The number of packs with the grade.
+    packs: usize,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+/// The plan in the JSON and the CBOR formats.
+struct PlanDocument<'a> {
+    /// Describes how the plan was made, if the plan is attested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attestation: Option<&'a Attestation>,
+
+    /// The packs of the plan.
+    packs: Vec<PackDocument<'a>>,
+
+    /// The items of which fewer items are available than were ordered.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    backorders: Vec<BackorderDocument<'a>>,
+
+    /// The items that aren't in any of the packs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unpacked: Vec<UnpackedItemDocument<'a>>,
+
+    /// Describes if the additional items fit, if additional items are checked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    additional_items: Option<FitReportDocument<'a>>,
+
+    /// The number of packs with each grade, if the packs are graded.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    grades: Vec<GradeCountDocument<'a>>,
+
+    /// The time it takes to pack all the packs, in seconds, if the handling time is provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    packing_time: Option<f64>,
+
+    /// The totals of the items in the input and in the packs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    totals: Option<&'a PlanTotals>,
+}
+
+/// Returns the JSON Schema of the plan in the JSON format, which is either a single plan or, for an input with
+/// several sections, an array of plans.
+#[cfg(feature = "schema")]
+pub(crate) fn plan_schema() -> serde_json::Value {
+    let mut generator = crate::schema::schema_generator();
+    let plan = generator.subschema_for::<PlanDocument>();
+    let definitions = generator.take_definitions(true);
+
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Plan",
+        "description": "The plan in the JSON format, or the array of the plans of the sections of the input.",
+        "anyOf": [plan, { "type": "array", "items": plan }],
+        "$defs": definitions,
+    })
+}
+
+/// Describes a single pack in the JSON format.
+fn pack_document<'a>(pack: &'a Pack, render_options: &'a RenderOptions) -> PackDocument<'a> {
+    let band = find_weight_band(&render_options.weight_bands, pack);
+    let has_grades = !render_options.grades.is_empty();
+    PackDocument {
+        number: pack.number,
+        items: pack
+            .items
+            .iter()
+            .map(|p| PlacementDocument {
+                item: ItemDocument::from(&p.item),
+                limit: render_options.explain.then(|| p.limit.to_string()),
+                bulk_remaining: p.bulk_remaining_after(),
+            })
+            .collect(),
+        length: pack.length,
+        weight: pack.weight,
+        gross_weight: (pack.tare_weight > 0.0).then(|| pack.gross_weight()),
+        weight_class: band.map(|b| b.name.as_str()),
+        estimated_cost: band.map(|b| b.cost),
+        carton: find_carton(&render_options.cartons, pack).map(|c| c.name.as_str()),
+        utilization: has_grades.then_some(pack.utilization),
+        grade: has_grades.then(|| {
+            find_grade(&render_options.grades, pack.utilization)
+                .map_or(NO_GRADE, |g| g.name.as_str())
+        }),
+        packing_time: render_options.handling_time.map(|t| pack.packing_time(t)),
+        overweight: pack.overweight,
+        value: (pack.value > 0.0).then_some(pack.value),
+        exceeds_maximum_value: pack.exceeds_maximum_value,
+        storage_class: special_storage_class(pack).map(|c| c.to_string()),
+        destination: pack.destination.as_deref(),
+        oversize: pack.oversize,
+        sheet_placements: pack
+            .sheet_placements
+            .iter()
+            .map(|p| SheetPlacementDocument {
+                id: &p.item_id,
+                x: p.x,
+                y: p.y,
+                length: p.length,
+                width: p.width,
+                rotated: p.rotated,
+            })
+            .collect(),
+        hash: pack.content_hash(),
+    }
+}
+
+/// Describes the plan in the JSON format, which is also used for the CBOR format.
+fn plan_document<'a>(
+    packs: &'a [Pack],
+    fit_report: Option<&'a FitReport>,
+    backorders: &'a [Backorder],
+    unpacked_items: &'a [UnpackedItem],
+    attestation: Option<&'a Attestation>,
+    totals: Option<&'a PlanTotals>,
+    render_options: &'a RenderOptions,
+) -> PlanDocument<'a> {
+    let packing_time = total_packing_time(packs, render_options);
+    let grades = count_grades(packs, render_options)
+        .into_iter()
+        .map(|(grade, packs)| GradeCountDocument { grade, packs })
+        .collect();
+    let packs = packs
+        .iter()
+        .map(|pack| pack_document(pack, render_options))
+        .collect();
+
+    let additional_items = fit_report.map(|report| FitReportDocument {
+        fits: report.fits(),
+        placements: report
+            .placements
+            .iter()
+            .map(|p| FitPlacementDocument {
+                pack_number: p.pack_number,
+                item: ItemDocument::from(&p.item),
+            })
+            .collect(),
+        unplaced_items: report
+            .unplaced_items
+            .iter()
+            .map(ItemDocument::from)
+            .collect(),
+    });
+
+    let backorders = backorders
+        .iter()
+        .map(|b| BackorderDocument {
+            id: &b.item_id,
+            ordered: b.ordered,
+            available: b.available,
+            shortfall: b.shortfall(),
+        })
+        .collect();
+
+    let unpacked = unpacked_items
+        .iter()
+        .map(|u| UnpackedItemDocument {
+            id: &u.item_id,
+            count: u.count,
+            reason: u.reason,
+        })
+        .collect();
+
+    PlanDocument {
+        attestation,
+        packs,
+        backorders,
+        unpacked,
+        additional_items,
+        grades,
+        packing_time,
+        totals,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_json_plan<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    backorders: &[Backorder],
+    unpacked_items: &[UnpackedItem],
+    attestation: Option<&Attestation>,
+    totals: Option<&PlanTotals>,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    let document = plan_document(
+        packs,
+        fit_report,
+        backorders,
+        unpacked_items,
+        attestation,
+        totals,
+        render_options,
+    );
+    serde_json::to_writer_pretty(&mut *writer, &document)?;
+    writeln!(writer)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "cbor")]
+fn write_cbor_plan<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    backorders: &[Backorder],
+    unpacked_items: &[UnpackedItem],
+    attestation: Option<&Attestation>,
+    totals: Option<&PlanTotals>,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    let document = plan_document(
+        packs,
+        fit_report,
+        backorders,
+        unpacked_items,
+        attestation,
+        totals,
+        render_options,
+    );
+    ciborium::into_writer(&document, writer).map_err(|e| io::Error::other(e.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(feature = "cbor"))]
+fn write_cbor_plan<W: Write>(
+    _writer: &mut W,
+    _packs: &[Pack],
+    _fit_report: Option<&FitReport>,
+    _backorders: &[Backorder],
+    _unpacked_items: &[UnpackedItem],
+    _attestation: Option<&Attestation>,
+    _totals: Option<&PlanTotals>,
+    _render_options: &RenderOptions,
+) -> io::Result<()> {
+    Err(unsupported_cbor())
+}
+
+#[cfg(not(feature = "cbor"))]
+fn unsupported_cbor() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "CBOR isn't supported by this build. Enable the 'cbor' feature to support it.",
+    )
+}
+
+/// Writes a single pack as a JSON document, in the same way as the packs of a plan in the JSON format, e.g.
+/// to report the pack the moment it is closed.
+pub fn write_json_pack<W: Write>(
+    writer: &mut W,
+    pack: &Pack,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, &pack_document(pack, render_options))?;
+    Ok(())
+}
+
+/// Escapes the characters that have a meaning in a Markdown table.
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Writes a Markdown table row for an item, with the placement limit if explanations are requested.
+fn write_markdown_item_row<W: Write>(
+    writer: &mut W,
+    item: &ItemTemplate,
+    limit: Option<PlacementLimit>,
+    precision: usize,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "| {} | {:.*} | {} | {:.*} |",
+        escape_markdown(&item.id),
+        precision,
+        item.length,
+        item.count,
+        precision,
+        item.weight
+    )?;
+    match limit {
+        Some(l) => writeln!(writer, " {} |", l),
+        None => writeln!(writer),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_markdown_plan<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    backorders: &[Backorder],
+    unpacked_items: &[UnpackedItem],
+    attestation: Option<&Attestation>,
+    totals: Option<&PlanTotals>,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    writeln!(writer, "# Packing plan")?;
+    if let Some(attestation) = attestation {
+        writeln!(writer)?;
+        writeln!(writer, "## Attestation")?;
+        writeln!(writer)?;
+        writeln!(writer, "| Tool version | Input SHA-256 | Timestamp |")?;
+        writeln!(writer, "| --- | --- | ---: |")?;
+        writeln!(
+            writer,
+            "| {} | {} | {} |",
+            attestation.tool_version, attestation.input_sha256, attestation.timestamp
+        )?;
+        writeln!(writer)?;
+        writeln!(writer, "```toml")?;
+        write!(writer, "{}", attested_configuration(attestation)?)?;
+        writeln!(writer, "```")?;
+    }
+
+    for pack in packs {
+        writeln!(writer)?;
+        let details: Vec<String> = special_storage_class(pack)
+            .map(|c| c.to_string())
+            .into_iter()
+            .chain(pack.destination.clone())
+            .collect();
+        if details.is_empty() {
+            writeln!(writer, "## Pack {}", pack.number)?;
+        } else {
+            writeln!(writer, "## Pack {} ({})", pack.number, details.join(", "))?;
+        }
+        writeln!(writer)?;
+        if render_options.explain {
+            writeln!(
+                writer,
+                "| Item | Length | Quantity | Piece weight | Limited by |"
+            )?;
+            writeln!(writer, "| --- | ---: | ---: | ---: | --- |")?;
+        } else {
+            writeln!(writer, "| Item | Length | Quantity | Piece weight |")?;
+            writeln!(writer, "| --- | ---: | ---: | ---: |")?;
+        }
+
+        for packed_item in listed_items(pack, render_options) {
+            let limit = render_options.explain.then_some(packed_item.limit);
+            write_markdown_item_row(writer, &packed_item.item, limit, render_options.precision)?;
+        }
+
+        writeln!(writer)?;
+        write_footer(writer, pack, render_options)?;
+    }
+
+    writeln!(writer)?;
+    writeln!(writer, "## Summary")?;
+    writeln!(writer)?;
+    let packing_time = total_packing_time(packs, render_options);
+    if packing_time.is_some() {
+        writeln!(writer, "| Packs | Items | Total weight | Packing time |")?;
+        writeln!(writer, "| ---: | ---: | ---: | ---: |")?;
+    } else {
+        writeln!(writer, "| Packs | Items | Total weight |")?;
+        writeln!(writer, "| ---: | ---: | ---: |")?;
+    }
+    write!(
+        writer,
+        "| {} | {} | {:.*} |",
+        packs.len(),
+        packs.iter().map(|p| p.item_count as i64).sum::<i64>(),
+        render_options.precision,
+        packs.iter().map(|p| p.weight).sum::<f64>()
+    )?;
+    match packing_time {
+        Some(seconds) => writeln!(writer, " {} |", format_packing_time(seconds))?,
+        None => writeln!(writer)?,
+    }
+
+    let grade_counts = count_grades(packs, render_options);
+    if !grade_counts.is_empty() {
+        writeln!(writer)?;
+        writeln!(writer, "## Grades")?;
+        writeln!(writer)?;
+        writeln!(writer, "| Grade | Packs |")?;
+        writeln!(writer, "| --- | ---: |")?;
+        for (name, count) in grade_counts {
+            writeln!(writer, "| {} | {} |", escape_markdown(name), count)?;
+        }
+    }
+
+    if let Some(totals) = totals {
+        writeln!(writer)?;
+        writeln!(writer, "## Totals")?;
+        writeln!(writer)?;
+        writeln!(writer, "| | Items | Weight |")?;
+        writeln!(writer, "| --- | ---: | ---: |")?;
+        for (name, t) in [
+            ("Input", &totals.input),
+            ("Packed", &totals.packed),
+            ("Left out", &totals.left_out),
+        ] {
+            writeln!(
+                writer,
+                "| {} | {} | {:.*} |",
+                name, t.items, render_options.precision, t.weight
+            )?;
+        }
+    }
+
+    if !backorders.is_empty() {
+        writeln!(writer)?;
+        writeln!(writer, "## Backorders")?;
+        writeln!(writer)?;
+        writeln!(writer, "| Item | Ordered | Available | Short |")?;
+        writeln!(writer, "| --- | ---: | ---: | ---: |")?;
+        for backorder in backorders {
+            writeln!(
+                writer,
+                "| {} | {} | {} | {} |",
+                escape_markdown(&backorder.item_id),
+                backorder.ordered,
+                backorder.available,
+                backorder.shortfall()
+            )?;
+        }
+    }
+
+    if !unpacked_items.is_empty() {
+        writeln!(writer)?;
+        writeln!(writer, "## Unpacked items")?;
+        writeln!(writer)?;
+        writeln!(writer, "| Item | Quantity | Reason |")?;
+        writeln!(writer, "| --- | ---: | --- |")?;
+        for unpacked_item in unpacked_items {
+            writeln!(
+                writer,
+                "| {} | {} | {} |",
+                escape_markdown(&unpacked_item.item_id),
+                unpacked_item.count,
+                unpacked_item.reason
+            )?;
+        }
+    }
+
+    if let Some(report) = fit_report {
+        writeln!(writer)?;
+        writeln!(writer, "## Additional items")?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "The additional items {}.",
+            if report.fits() { "fit" } else { "do not fit" }
+        )?;
+        writeln!(writer)?;
+        writeln!(writer, "| Pack | Item | Length | Quantity | Piece weight |")?;
+        writeln!(writer, "| --- | --- | ---: | ---: | ---: |")?;
+        for placement in &report.placements {
+            write!(writer, "| {} ", placement.pack_number)?;
+            write_markdown_item_row(writer, &placement.item, None, render_options.precision)?;
+        }
+
+        for item in &report.unplaced_items {
+            write!(writer, "| Unplaced ")?;
+            write_markdown_item_row(writer, item, None, render_options.precision)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a pick-to-pack worksheet, which lists for each item, in the order in which the items first appear in
+/// the plan, the number of items in each pack. Items are identified by their ID, so batches with the same ID
+/// are combined. The quantity of a bulk item is its weight.
+pub fn write_pick_worksheet<W: Write>(writer: &mut W, packs: &[Pack]) -> io::Result<()> {
+    // The item ID, and for each pack that contains the item the pack number and the quantity
+    let mut items: Vec<(&str, Vec<(usize, f64)>)> = Vec::new();
+    let mut item_indices: HashMap<&str, usize> = HashMap::new();
+    for pack in packs {
+        for packed_item in &pack.items {
+            let item = &packed_item.item;
+            let quantity = if item.attributes.bulk {
+                item.weight
+            } else {
+                item.count as f64
+            };
+
+            let index = *item_indices.entry(&item.id).or_insert_with(|| {
+                items.push((&item.id, Vec::new()));
+                items.len() - 1
+            });
+            let packs_with_item = &mut items[index].1;
+            match packs_with_item.last_mut() {
+                Some((number, total)) if *number == pack.number => *total += quantity,
+                _ => packs_with_item.push((pack.number, quantity)),
+            }
+        }
+    }
+
+    for (id, packs_with_item) in items {
+        let total: f64 = packs_with_item.iter().map(|(_, q)| q).sum();
+        writeln!(writer, "Item: {}, Total: {}", id, total)?;
+        for (pack_number, quantity) in packs_with_item {
+            writeln!(writer, "  Pack {}: {}", pack_number, quantity)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the plans of the sections of the input, each of which is already written in the given format. Each
+/// plan is preceded by the number of its section, except in the JSON and the CBOR formats, in which the plans
+/// are written as an array, and in the Arrow IPC and the Parquet formats, in which the plans are written as a
+/// single table with the number of the section in each row.
+pub fn write_sections<W: Write>(
+    writer: &mut W,
+    plans: &[Vec<u8>],
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Cbor => return write_cbor_sections(writer, plans),
+        OutputFormat::Arrow => return write_table_sections(writer, plans, TableFormat::Arrow),
+        OutputFormat::Parquet => return write_table_sections(writer, plans, TableFormat::Parquet),
+        _ => {}
+    }
+
+    if format == OutputFormat::Json {
+        writeln!(writer, "[")?;
+        for (index, plan) in plans.iter().enumerate() {
+            if index > 0 {
+                writeln!(writer, ",")?;
+            }
+
+            writer.write_all(plan.trim_ascii_end())?;
+        }
+
+        writeln!(writer)?;
+        return writeln!(writer, "]");
+    }
+
+    for (index, plan) in plans.iter().enumerate() {
+        match format {
+            OutputFormat::Markdown => writeln!(writer, "# Section {}", index + 1)?,
+            _ => writeln!(writer, "Section: {}", index + 1)?,
+        }
+
+        writeln!(writer)?;
+        writer.write_all(plan)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the CBOR plans of the sections as a single CBOR array.
+#[cfg(feature = "cbor")]
+fn write_cbor_sections<W: Write>(writer: &mut W, plans: &[Vec<u8>]) -> io::Result<()> {
+    let to_io_error = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+    let plans = plans
+        .iter()
+        .map(|plan| {
+            ciborium::from_reader::<ciborium::Value, _>(plan.as_slice())
+                .map_err(|e| to_io_error(e.to_string()))
+        })
+        .collect::<io::Result<Vec<ciborium::Value>>>()?;
+    ciborium::into_writer(&plans, writer).map_err(|e| to_io_error(e.to_string()))
+}
+
+#[cfg(not(feature = "cbor"))]
+fn write_cbor_sections<W: Write>(_writer: &mut W, _plans: &[Vec<u8>]) -> io::Result<()> {
+    Err(unsupported_cbor())
+}
+
+/// Writes the packs, and the fit report if there is one, in the format given by the render options. The
+/// Arrow IPC and the Parquet formats only contain the packs.
+pub fn write_plan<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    write_plan_with_backorders(writer, packs, fit_report, &[], render_options)
+}
+
+/// Writes the packs, the backorders and the fit report if there is one, in the format given by the render
+/// options. The pick-to-pack worksheet and the tables don't contain the backorders.
+pub fn write_plan_with_backorders<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    backorders: &[Backorder],
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    write_plan_with_unpacked_items(writer, packs, fit_report, backorders, &[], render_options)
+}
+
+/// Writes the packs, the backorders, the items that are not packed and the fit report if there is one, in the
+/// format given by the render options. The pick-to-pack worksheet doesn't contain the backorders, but it does
+/// list the items that are not packed after the items of the packs. The tables only contain the packs.
+pub fn write_plan_with_unpacked_items<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    backorders: &[Backorder],
+    unpacked_items: &[UnpackedItem],
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    write_plan_with_attestation(
+        writer,
+        packs,
+        fit_report,
+        backorders,
+        unpacked_items,
+        None,
+        render_options,
+    )
+}
+
+/// Writes the attestation if there is one, followed by the packs, the backorders, the items that are not
+/// packed and the fit report if there is one, in the format given by the render options. The pick-to-pack
+/// worksheet doesn't contain the backorders, but it does list the items that are not packed after the items
+/// of the packs.
+pub fn write_plan_with_attestation<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    backorders: &[Backorder],
+    unpacked_items: &[UnpackedItem],
+    attestation: Option<&Attestation>,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    write_plan_with_totals(
+        writer,
+        packs,
+        fit_report,
+        backorders,
+        unpacked_items,
+        attestation,
+        None,
+        render_options,
+    )
+}
+
+/// Writes the plan in the same way as [`write_plan_with_attestation`], with the totals of the items that were
+/// planned, packed and left out after the items that are not packed, if the totals are known. The totals are
+/// part of the summary of the Markdown format and of the JSON and CBOR documents.
+#[allow(clippy::too_many_arguments)]
+pub fn write_plan_with_totals<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    fit_report: Option<&FitReport>,
+    backorders: &[Backorder],
+    unpacked_items: &[UnpackedItem],
+    attestation: Option<&Attestation>,
+    totals: Option<&PlanTotals>,
+    render_options: &RenderOptions,
+) -> io::Result<()> {
+    match render_options.format {
+        OutputFormat::Text => {
+            if let Some(attestation) = attestation {
+                write_attestation(writer, attestation, render_options)?;
+            }
+            write_packs(writer, packs, render_options)?;
+            write_backorders(writer, backorders, render_options)?;
+            write_unpacked_items(writer, unpacked_items, render_options)?;
+            if let Some(totals) = totals {
+                write_totals(writer, totals, render_options)?;
+            }
+            match fit_report {
+                Some(report) => write_fit_report(writer, report, render_options),
+                None => Ok(()),
+            }
+        }
+        OutputFormat::Json => write_json_plan(
+            writer,
+            packs,
+            fit_report,
+            backorders,
+            unpacked_items,
+            attestation,
+            totals,
+            render_options,
+        ),
+        OutputFormat::Markdown => write_markdown_plan(
+            writer,
+            packs,
+            fit_report,
+            backorders,
+            unpacked_items,
+            attestation,
+            totals,
+            render_options,
+        ),
+        OutputFormat::Worksheet => {
+            if let Some(attestation) = attestation {
+                write_attestation(writer, attestation, render_options)?;
+            }
+            write_pick_worksheet(writer, packs)?;
+            write_unpacked_items(writer, unpacked_items, render_options)?;
+            match totals {
+                Some(totals) => {
+                    if unpacked_items.is_empty() {
+                        writeln!(writer)?;
+                    }
+                    write_totals(writer, totals, render_options)
+                }
+                None => Ok(()),
+            }
+        }
+        OutputFormat::Cbor => write_cbor_plan(
+            writer,
+            packs,
+            fit_report,
+            backorders,
+            unpacked_items,
+            attestation,
+            totals,
+            render_options,
+        ),
+        OutputFormat::Arrow => write_table_plan(writer, packs, TableFormat::Arrow),
+        OutputFormat::Parquet => write_table_plan(writer, packs, TableFormat::Parquet),
+    }
+}
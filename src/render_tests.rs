@@ -0,0 +1,863 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::*;
+use crate::model::{ItemAttributes, PackSortOrder, PackTemplate};
+use crate::planner::plan_packs;
+
+// write_packs()
+
+#[test]
+fn when_writing_packs_with_explanations_it_should_write_the_reason_for_each_placement() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 4.0,
+        count: 15,
+        attributes: Arc::default(),
+    }];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        explain: true,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &render_options).expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains(
+        "  Placed 10 of 15 remaining items, limited by: piece count. Remaining weight: 50.0 -> 10.0, remaining pieces: 10 -> 0"
+    ));
+    assert!(text.contains("limited by: none, all remaining items fit."));
+}
+
+#[test]
+fn when_writing_packs_without_explanations_it_should_only_write_the_items() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 4.0,
+        count: 5,
+        attributes: Arc::default(),
+    }];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &RenderOptions::default())
+        .expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert_eq!(
+        text,
+        "Pack Number: 1\nitem1,10.0,5,4.0\nPack Length: 10.0, Pack Weight: 20.0\n\n"
+    );
+}
+
+#[test]
+fn when_writing_an_overweight_pack_it_should_flag_the_pack_for_review() {
+    let mut pack = Pack::new(1);
+    pack.weight = 52.0;
+    pack.overweight = true;
+
+    let mut output = Vec::new();
+    write_packs(&mut output, &[pack], &RenderOptions::default())
+        .expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert_eq!(
+        text,
+        "Pack Number: 1\nPack Length: 0.0, Pack Weight: 52.0, Overweight: REVIEW\n\n"
+    );
+}
+
+#[test]
+fn when_writing_a_pack_with_valued_items_it_should_write_the_value() {
+    let mut pack = Pack::new(1);
+    pack.value = 1500.0;
+    pack.exceeds_maximum_value = true;
+
+    let mut output = Vec::new();
+    write_packs(&mut output, &[pack], &RenderOptions::default())
+        .expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert_eq!(
+        text,
+        "Pack Number: 1\nPack Length: 0.0, Pack Weight: 0.0, Pack Value: 1500.00, Maximum Value: EXCEEDED\n\n"
+    );
+}
+
+#[test]
+fn when_writing_a_pack_with_bulk_it_should_write_the_remaining_bulk() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("3001,-,TOTAL_KG=150,1.0/kg").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        explain: true,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &render_options).expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.starts_with(
+        "Pack Number: 1\n3001,-,TOTAL_KG=100.0,REMAINING_KG=50.0\n  Placed 100.0 of 150.0 remaining bulk, limited by: weight."
+    ));
+    assert!(text.contains("Pack Number: 2\n3001,-,TOTAL_KG=50.0,REMAINING_KG=0.0\n"));
+}
+
+#[test]
+fn when_writing_packs_of_different_storage_classes_it_should_group_the_packs_by_class() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 1,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,10,2,1.0").unwrap(),
+        ItemTemplate::from_str("2,10,1,1.0,storage=frozen").unwrap(),
+    ];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &RenderOptions::default())
+        .expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.starts_with("Storage Class: ambient\n\nPack Number: 1\n"));
+    assert!(text.contains("\n\nStorage Class: frozen\n\nPack Number: 3\n2,10.0,1,1.0\n"));
+    assert_eq!(text.matches("Storage Class:").count(), 2);
+}
+
+#[test]
+fn when_writing_packs_with_a_destination_it_should_write_the_destination_in_the_header() {
+    let pack = Pack {
+        destination: Some("north".to_string()),
+        ..Pack::new(1)
+    };
+
+    let mut output = Vec::new();
+    write_packs(&mut output, &[pack], &RenderOptions::default())
+        .expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.starts_with("Pack Number: 1, Destination: north\n"));
+}
+
+#[test]
+fn when_writing_packs_with_a_precision_and_a_weight_unit_it_should_use_them() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1001,6200,3,9.653").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        precision: 3,
+        weight_unit: Some("kg".to_string()),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &render_options).expect("Failed to write the packs.");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Pack Number: 1\n1001,6200.000,3,9.653\nPack Length: 6200.000, Pack Weight: 28.959 kg\n\n"
+    );
+}
+
+#[test]
+fn when_writing_packs_with_colors_it_should_color_the_headers_and_the_overweight_packs() {
+    let mut pack = Pack::new(1);
+    pack.overweight = true;
+
+    let render_options = RenderOptions {
+        color: true,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &[pack], &render_options).expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.starts_with("\x1b[1;36mPack Number: 1\x1b[0m\n"));
+    assert!(text.contains(", \x1b[1;31mOverweight: REVIEW\x1b[0m"));
+}
+
+#[test]
+fn when_writing_packs_without_colors_it_should_not_write_escape_codes() {
+    let mut pack = Pack::new(1);
+    pack.overweight = true;
+
+    let mut output = Vec::new();
+    write_packs(&mut output, &[pack], &RenderOptions::default())
+        .expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(!text.contains('\x1b'));
+}
+
+#[test]
+fn when_writing_packs_with_cartons_it_should_write_the_smallest_carton_that_fits() {
+    let cartons = vec![
+        Carton {
+            name: "SMALL".to_string(),
+            maximum_length: 600.0,
+            maximum_weight: 5.0,
+        },
+        Carton {
+            name: "LARGE".to_string(),
+            maximum_length: 1200.0,
+            maximum_weight: 20.0,
+        },
+    ];
+    let small = Pack {
+        length: 500.0,
+        weight: 4.0,
+        ..Pack::new(1)
+    };
+    let large = Pack {
+        length: 500.0,
+        weight: 12.0,
+        ..Pack::new(2)
+    };
+    let too_long = Pack {
+        length: 1500.0,
+        weight: 4.0,
+        ..Pack::new(3)
+    };
+
+    let render_options = RenderOptions {
+        cartons,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &[small, large, too_long], &render_options)
+        .expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("Pack Length: 500.0, Pack Weight: 4.0, Carton: SMALL\n"));
+    assert!(text.contains("Pack Length: 500.0, Pack Weight: 12.0, Carton: LARGE\n"));
+    assert!(text.contains("Pack Length: 1500.0, Pack Weight: 4.0, Carton: NONE\n"));
+}
+
+#[test]
+fn when_writing_packs_heaviest_first_it_should_list_the_heaviest_items_first() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let create_item = |id: &str, weight| ItemTemplate {
+        id: id.into(),
+        length: 10.0,
+        weight,
+        count: 1,
+        attributes: Arc::default(),
+    };
+    let items = vec![
+        create_item("light", 1.0),
+        create_item("heavy", 9.0),
+        create_item("medium", 4.0),
+        create_item("other-medium", 4.0),
+    ];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        heaviest_first: true,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &render_options).expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    let ids: Vec<&str> = text
+        .lines()
+        .skip(1)
+        .take(4)
+        .map(|l| l.split(',').next().unwrap())
+        .collect();
+    assert_eq!(ids, ["heavy", "medium", "other-medium", "light"]);
+
+    // The packs themselves keep the order in which the items were placed.
+    assert_eq!(&*packs[0].items[0].item.id, "light");
+}
+
+#[test]
+fn when_writing_packs_with_a_handling_time_it_should_estimate_the_packing_time() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("item1,10,15,1.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        handling_time: Some(30.0),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &render_options).expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("Pack Weight: 10.0, Packing Time: 5.0 min\n"));
+    assert!(text.contains("Pack Weight: 5.0, Packing Time: 2.5 min\n"));
+    assert!(text.ends_with("Total Packing Time: 7.5 min\n\n"));
+
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &RenderOptions::default())
+        .expect("Failed to write the packs.");
+    assert!(!String::from_utf8(output).unwrap().contains("Packing Time"));
+}
+
+#[test]
+fn when_writing_packs_with_grades_it_should_grade_each_pack_and_count_the_grades() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("item1,10,28,1.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        grades: vec![
+            Grade::from_str("B=70").unwrap(),
+            Grade::from_str("A=90").unwrap(),
+        ],
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &render_options).expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("Pack Weight: 10.0, Utilization: 100%, Grade: A\n"));
+    assert!(text.contains("Pack Weight: 8.0, Utilization: 80%, Grade: B\n"));
+    assert!(text.ends_with("Grades: A: 2, B: 1\n\n"));
+
+    let render_options = RenderOptions {
+        grades: vec![Grade::from_str("A=90").unwrap()],
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_packs(&mut output, &packs, &render_options).expect("Failed to write the packs.");
+
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("Pack Weight: 8.0, Utilization: 80%, Grade: NONE\n"));
+    assert!(text.ends_with("Grades: A: 2, NONE: 1\n\n"));
+}
+
+// ColorChoice
+
+#[test]
+fn when_choosing_colors_automatically_it_should_only_use_colors_on_a_terminal() {
+    assert!(ColorChoice::Auto.is_enabled(true));
+    assert!(!ColorChoice::Auto.is_enabled(false));
+    assert!(ColorChoice::Always.is_enabled(false));
+    assert!(!ColorChoice::Never.is_enabled(true));
+    assert_eq!(ColorChoice::from_str("never").unwrap(), ColorChoice::Never);
+    assert!(ColorChoice::from_str("sometimes").is_err());
+}
+
+// write_plan()
+
+#[test]
+fn when_writing_a_plan_as_json_it_should_include_the_hash_of_each_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 4.0,
+        count: 5,
+        attributes: Arc::default(),
+    }];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        format: OutputFormat::Json,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).expect("Failed to write the plan.");
+
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        document,
+        serde_json::json!({
+            "packs": [{
+                "number": 1,
+                "items": [{ "id": "item1", "length": 10.0, "count": 5, "weight": 4.0 }],
+                "length": 10.0,
+                "weight": 20.0,
+                "hash": "a0e0888a6c4f571cff8cbff6501c2d789ced91d74584c438075d4f12626ecfc4"
+            }]
+        })
+    );
+}
+
+#[test]
+fn when_writing_a_plan_as_json_with_an_aliased_item_it_should_include_the_original_id() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate {
+        id: "item1".into(),
+        length: 10.0,
+        weight: 4.0,
+        count: 5,
+        attributes: Arc::new(ItemAttributes {
+            alias_of: Some("legacy1".to_string()),
+            ..ItemAttributes::default()
+        }),
+    }];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        format: OutputFormat::Json,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).expect("Failed to write the plan.");
+
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        document["packs"][0]["items"][0],
+        serde_json::json!({
+            "id": "item1",
+            "length": 10.0,
+            "count": 5,
+            "weight": 4.0,
+            "alias_of": "legacy1"
+        })
+    );
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn when_writing_a_plan_as_cbor_it_should_contain_the_same_document_as_json() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("item1,10,5,4.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let mut json = Vec::new();
+    let render_options = RenderOptions {
+        format: OutputFormat::Json,
+        ..Default::default()
+    };
+    write_plan(&mut json, &packs, None, &render_options).expect("Failed to write the plan.");
+
+    let mut cbor = Vec::new();
+    let render_options = RenderOptions {
+        format: OutputFormat::Cbor,
+        ..Default::default()
+    };
+    write_plan(&mut cbor, &packs, None, &render_options).expect("Failed to write the plan.");
+
+    assert!(cbor.len() < json.len());
+    let document: serde_json::Value = ciborium::from_reader(cbor.as_slice()).unwrap();
+    assert_eq!(
+        document,
+        serde_json::from_slice::<serde_json::Value>(&json).unwrap()
+    );
+}
+
+#[test]
+fn when_writing_a_plan_as_json_with_a_fit_report_it_should_include_the_report() {
+    let packs = vec![Pack::new(1)];
+    let report = FitReport {
+        placements: Vec::new(),
+        unplaced_items: vec![ItemTemplate::from_str("item2,5,1,1").unwrap()],
+    };
+
+    let render_options = RenderOptions {
+        format: OutputFormat::Json,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, Some(&report), &render_options)
+        .expect("Failed to write the plan.");
+
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["additional_items"]["fits"], false);
+    assert_eq!(
+        document["additional_items"]["unplaced_items"][0]["id"],
+        "item2"
+    );
+}
+
+#[test]
+fn when_writing_a_plan_with_backorders_it_should_list_the_shortfalls() {
+    let backorders = vec![Backorder {
+        item_id: "item1".to_string(),
+        ordered: 10,
+        available: 7,
+    }];
+
+    let mut output = Vec::new();
+    write_plan_with_backorders(
+        &mut output,
+        &[],
+        None,
+        &backorders,
+        &RenderOptions::default(),
+    )
+    .expect("Failed to write the plan.");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Backorder: item1, Ordered: 10, Available: 7, Short: 3\n\n"
+    );
+
+    let render_options = RenderOptions {
+        format: OutputFormat::Json,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan_with_backorders(&mut output, &[], None, &backorders, &render_options)
+        .expect("Failed to write the plan.");
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        document["backorders"],
+        serde_json::json!([{ "id": "item1", "ordered": 10, "available": 7, "shortfall": 3 }])
+    );
+
+    let render_options = RenderOptions {
+        format: OutputFormat::Markdown,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan_with_backorders(&mut output, &[], None, &backorders, &render_options)
+        .expect("Failed to write the plan.");
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("## Backorders\n\n| Item | Ordered | Available | Short |\n| --- | ---: | ---: | ---: |\n| item1 | 10 | 7 | 3 |\n"));
+}
+
+#[test]
+fn when_writing_a_plan_with_unpacked_items_it_should_list_the_reasons_in_every_format() {
+    let unpacked_items = vec![
+        UnpackedItem {
+            item_id: "item1".to_string(),
+            count: 2,
+            reason: UnpackedReason::Excluded,
+        },
+        UnpackedItem {
+            item_id: "item2".to_string(),
+            count: 1,
+            reason: UnpackedReason::Infeasible,
+        },
+    ];
+    let write = |format: OutputFormat| {
+        let render_options = RenderOptions {
+            format,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        write_plan_with_unpacked_items(
+            &mut output,
+            &[],
+            None,
+            &[],
+            &unpacked_items,
+            &render_options,
+        )
+        .expect("Failed to write the plan.");
+        String::from_utf8(output).unwrap()
+    };
+
+    let text = "Unpacked: item1, Count: 2, Reason: excluded\n\
+                Unpacked: item2, Count: 1, Reason: infeasible\n\n";
+    assert_eq!(write(OutputFormat::Text), text);
+    assert_eq!(write(OutputFormat::Worksheet), text);
+
+    let document: serde_json::Value = serde_json::from_str(&write(OutputFormat::Json)).unwrap();
+    assert_eq!(
+        document["unpacked"],
+        serde_json::json!([
+            { "id": "item1", "count": 2, "reason": "excluded" },
+            { "id": "item2", "count": 1, "reason": "infeasible" }
+        ])
+    );
+
+    assert!(write(OutputFormat::Markdown).ends_with(
+        "## Unpacked items\n\n| Item | Quantity | Reason |\n| --- | ---: | --- |\n\
+         | item1 | 2 | excluded |\n| item2 | 1 | infeasible |\n"
+    ));
+}
+
+#[test]
+fn when_writing_a_plan_as_markdown_it_should_write_a_table_per_pack_and_a_summary() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("item|1,10,8,4.0").unwrap(),
+        ItemTemplate::from_str("item2,20,4,1.0").unwrap(),
+    ];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let render_options = RenderOptions {
+        format: OutputFormat::Markdown,
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).expect("Failed to write the plan.");
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "# Packing plan\n\
+         \n\
+         ## Pack 1\n\
+         \n\
+         | Item | Length | Quantity | Piece weight |\n\
+         | --- | ---: | ---: | ---: |\n\
+         | item\\|1 | 10.0 | 8 | 4.0 |\n\
+         | item2 | 20.0 | 2 | 1.0 |\n\
+         \n\
+         Pack Length: 20.0, Pack Weight: 34.0\n\
+         \n\
+         ## Pack 2\n\
+         \n\
+         | Item | Length | Quantity | Piece weight |\n\
+         | --- | ---: | ---: | ---: |\n\
+         | item2 | 20.0 | 2 | 1.0 |\n\
+         \n\
+         Pack Length: 20.0, Pack Weight: 2.0\n\
+         \n\
+         ## Summary\n\
+         \n\
+         | Packs | Items | Total weight |\n\
+         | ---: | ---: | ---: |\n\
+         | 2 | 12 | 36.0 |\n"
+    );
+}
+
+#[test]
+fn when_writing_a_plan_with_a_handling_time_it_should_report_the_packing_time_in_the_summary() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("item1,10,15,1.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let mut render_options = RenderOptions {
+        format: OutputFormat::Markdown,
+        handling_time: Some(12.0),
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).expect("Failed to write the plan.");
+    assert!(String::from_utf8(output).unwrap().ends_with(
+        "| Packs | Items | Total weight | Packing time |\n\
+         | ---: | ---: | ---: | ---: |\n\
+         | 2 | 15 | 15.0 | 3.0 min |\n"
+    ));
+
+    render_options.format = OutputFormat::Json;
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).expect("Failed to write the plan.");
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["packs"][0]["packing_time"], 120.0);
+    assert_eq!(document["packs"][1]["packing_time"], 60.0);
+    assert_eq!(document["packing_time"], 180.0);
+}
+
+#[test]
+fn when_writing_a_plan_with_totals_it_should_write_the_totals_in_every_format() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("item1,10,12,2.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+    let totals = PlanTotals {
+        input: Totals::of_items(&items),
+        packed: Totals::of_packs(&packs),
+        left_out: Totals {
+            items: 0,
+            weight: 0.0,
+        },
+    };
+
+    let write = |format| {
+        let render_options = RenderOptions {
+            format,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        write_plan_with_totals(
+            &mut output,
+            &packs,
+            None,
+            &[],
+            &[],
+            None,
+            Some(&totals),
+            &render_options,
+        )
+        .expect("Failed to write the plan.");
+        String::from_utf8(output).unwrap()
+    };
+
+    assert!(write(OutputFormat::Text).ends_with(
+        "Totals: Input Items: 12, Input Weight: 24.0, Packed Items: 12, Packed Weight: 24.0\n\n"
+    ));
+    assert!(write(OutputFormat::Worksheet).ends_with(
+        "  Pack 2: 2\n\nTotals: Input Items: 12, Input Weight: 24.0, Packed Items: 12, Packed Weight: 24.0\n\n"
+    ));
+    assert!(write(OutputFormat::Markdown).ends_with(
+        "## Totals\n\
+         \n\
+         | | Items | Weight |\n\
+         | --- | ---: | ---: |\n\
+         | Input | 12 | 24.0 |\n\
+         | Packed | 12 | 24.0 |\n\
+         | Left out | 0 | 0.0 |\n"
+    ));
+
+    let document: serde_json::Value = serde_json::from_str(&write(OutputFormat::Json)).unwrap();
+    assert_eq!(document["totals"]["input"]["items"], 12);
+    assert_eq!(document["totals"]["packed"]["weight"], 24.0);
+    assert_eq!(document["totals"]["left_out"]["items"], 0);
+}
+
+#[test]
+fn when_writing_a_plan_with_grades_it_should_count_the_grades_in_the_summary() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("item1,10,15,1.0").unwrap()];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let mut render_options = RenderOptions {
+        format: OutputFormat::Markdown,
+        grades: vec![
+            Grade::from_str("A=90").unwrap(),
+            Grade::from_str("C=0").unwrap(),
+        ],
+        ..Default::default()
+    };
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).expect("Failed to write the plan.");
+    let text = String::from_utf8(output).unwrap();
+    assert!(text.contains("Pack Weight: 5.0, Utilization: 50%, Grade: C\n"));
+    assert!(
+        text.ends_with("## Grades\n\n| Grade | Packs |\n| --- | ---: |\n| A | 1 |\n| C | 1 |\n")
+    );
+
+    render_options.format = OutputFormat::Json;
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).expect("Failed to write the plan.");
+    let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(document["packs"][0]["utilization"], 1.0);
+    assert_eq!(document["packs"][1]["grade"], "C");
+    assert_eq!(
+        document["grades"],
+        serde_json::json!([{ "grade": "A", "packs": 1 }, { "grade": "C", "packs": 1 }])
+    );
+}
+
+// write_pick_worksheet()
+
+#[test]
+fn when_writing_a_pick_worksheet_it_should_list_the_packs_for_each_item() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1001,10,6,1.0").unwrap(),
+        ItemTemplate::from_str("2001,10,12,1.0").unwrap(),
+        ItemTemplate::from_str("1001,10,3,1.0").unwrap(),
+    ];
+    let packs = plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs;
+
+    let mut output = Vec::new();
+    write_pick_worksheet(&mut output, &packs).expect("Failed to write the worksheet.");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Item: 1001, Total: 9\n  Pack 1: 6\n  Pack 2: 2\n  Pack 3: 1\nItem: 2001, Total: 12\n  Pack 1: 4\n  Pack 2: 8\n"
+    );
+}
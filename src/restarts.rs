@@ -0,0 +1,272 @@
+//! A packing algorithm that runs the greedy algorithm several times, with the items shuffled, and keeps the
+//! best plan.
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "text-format")]
+use strum::{Display, EnumString};
+
+use crate::config::StrategyConfig;
+use crate::errors::Error;
+use crate::model::{
+    ItemTemplate, Optimality, PackRule, PackSortOrder, PackTemplate, PlanOutcome, PlanWarning,
+};
+use crate::planner::{
+    analyze_pack_count, plan_packs, plan_packs_with_observer, NoObserver, PackingAlgorithm,
+    PlanObserver,
+};
+use crate::scoring::{compare_plans, Balance, PackCount, Score};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "restarts_tests.rs"]
+mod tests;
+
+/// Defines how the plans of the different attempts are compared.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartObjective {
+    /// The plan with the fewest packs is the best. Ties are broken by the difference between the heaviest
+    /// and the lightest pack.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "fewest-packs"))]
+    FewestPacks,
+
+    /// The plan with the smallest difference between the heaviest and the lightest pack is the best. Ties are
+    /// broken by the number of packs.
+    #[cfg_attr(feature = "text-format", strum(serialize = "balanced-weight"))]
+    BalancedWeight,
+}
+
+/// A small, fast pseudo-random number generator (SplitMix64). It is only used to shuffle and sample the
+/// items, so that the same seed always produces the same plan.
+pub(crate) struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in the range `0..bound`.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Shuffles the slice with the Fisher-Yates algorithm.
+    pub(crate) fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Runs the greedy algorithm once for the items in the order in which they are provided, and then once for
+/// each restart with the items shuffled, and keeps the best plan according to the objective. Only items that
+/// are equivalent for the sort order of the pack, i.e. items with the same length, storage class and
+/// destination, are swapped, so every attempt still respects the sort order. With the `NATURAL` sort order
+/// every item has its own position, so the items are never shuffled. Items that close the pack before them are
+/// never moved, and the components of a kit that is kept together are only swapped with each other. When there
+/// is nothing to shuffle every attempt makes the same plan, so no restarts are made.
+///
+/// The same seed always produces the same plan, unless a time limit stops the restarts early. When the time
+/// limit is reached the best plan found so far is returned. If that plan uses more packs than the theoretical
+/// minimum, it may not be optimal, which is reported as a warning. With [`UNLIMITED_RESTARTS`] the restarts
+/// stop at the time limit, or as soon as a plan reaches the theoretical minimum number of packs when the
+/// objective is the fewest packs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RestartAlgorithm {
+    /// The number of additional attempts with shuffled items.
+    pub restarts: u32,
+
+    /// The seed of the random number generator that shuffles the items.
+    pub seed: u64,
+
+    /// The objective by which the plans are compared.
+    pub objective: RestartObjective,
+
+    /// The time after which no more restarts are made. The first attempt is always completed.
+    pub time_limit: Option<Duration>,
+}
+
+/// The number of restarts that keeps restarting until the time limit is reached, or until no plan can be
+/// better.
+pub const UNLIMITED_RESTARTS: u32 = u32::MAX;
+
+impl RestartObjective {
+    /// Returns the scores by which the plans are compared, where the next score breaks a tie on the previous
+    /// score.
+    pub fn scores(self) -> [&'static dyn Score; 2] {
+        match self {
+            RestartObjective::FewestPacks => [&PackCount, &Balance],
+            RestartObjective::BalancedWeight => [&Balance, &PackCount],
+        }
+    }
+}
+
+/// Indicates if the item can be swapped with the item before it, i.e. if the items are equivalent for the sort
+/// order.
+fn is_equivalent_to_previous(previous: &ItemTemplate, item: &ItemTemplate) -> bool {
+    item.length == previous.length
+        && item.attributes.storage_class == previous.attributes.storage_class
+        && item.attributes.kit_instance == previous.attributes.kit_instance
+        && item.attributes.destination == previous.attributes.destination
+        && !item.attributes.close_pack_before
+}
+
+/// Indicates if shuffling can change the order of the items.
+fn has_equivalent_items(items: &[ItemTemplate], sort_order: PackSortOrder) -> bool {
+    sort_order != PackSortOrder::Natural
+        && items
+            .windows(2)
+            .any(|w| is_equivalent_to_previous(&w[0], &w[1]))
+}
+
+/// Shuffles each run of consecutive items that are equivalent for the sort order.
+fn shuffle_equivalent_items(
+    items: &mut [ItemTemplate],
+    sort_order: PackSortOrder,
+    random: &mut Random,
+) {
+    if sort_order == PackSortOrder::Natural {
+        return;
+    }
+
+    let mut start = 0;
+    while start < items.len() {
+        let mut end = start + 1;
+        while end < items.len() && is_equivalent_to_previous(&items[start], &items[end]) {
+            end += 1;
+        }
+
+        random.shuffle(&mut items[start..end]);
+        start = end;
+    }
+}
+
+impl RestartAlgorithm {
+    /// Returns the number of restarts to make. Without anything to shuffle every attempt makes the same plan,
+    /// so no restarts are made.
+    fn restarts_for(&self, items: &[ItemTemplate], sort_order: PackSortOrder) -> u32 {
+        if has_equivalent_items(items, sort_order) {
+            self.restarts
+        } else {
+            0
+        }
+    }
+
+    /// Makes the attempts and returns the best plan, together with the order of the items of that plan if the
+    /// items were shuffled for it. Only the first attempt is reported to the observer.
+    fn plan_attempts(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+        observer: &mut dyn PlanObserver,
+    ) -> Result<(PlanOutcome, Option<Vec<ItemTemplate>>), Error> {
+        let deadline = self.time_limit.map(|l| Instant::now() + l);
+        let mut best = plan_packs_with_observer(items, pack_template, rules, observer)?;
+        let mut best_items = None;
+        let mut analysis = analyze_pack_count(items, pack_template, &best.packs);
+        let mut attempts = 1;
+
+        // No plan has fewer packs than the theoretical minimum.
+        let stops_at_minimum =
+            self.restarts == UNLIMITED_RESTARTS && self.objective == RestartObjective::FewestPacks;
+
+        let mut random = Random::new(self.seed);
+        let mut shuffled_items = items.to_vec();
+        for _ in 0..self.restarts_for(items, pack_template.sort_order) {
+            if stops_at_minimum && analysis.reaches_minimum() {
+                break;
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                if !analysis.reaches_minimum() {
+                    best.warnings.push(PlanWarning::TimeLimitReached {
+                        attempts,
+                        number_of_packs: analysis.number_of_packs,
+                        minimum_number_of_packs: analysis.minimum_number_of_packs,
+                    });
+                }
+                break;
+            }
+
+            shuffle_equivalent_items(&mut shuffled_items, pack_template.sort_order, &mut random);
+            let outcome = plan_packs(&shuffled_items, pack_template, rules)?;
+            attempts = attempts.saturating_add(1);
+            if compare_plans(&outcome, &best, &self.objective.scores()) == Ordering::Less {
+                best = outcome;
+                best_items = Some(shuffled_items.clone());
+                analysis = analyze_pack_count(items, pack_template, &best.packs);
+            }
+        }
+
+        best.optimality = Some(Optimality {
+            attempts,
+            reaches_minimum: analysis.reaches_minimum(),
+        });
+        Ok((best, best_items))
+    }
+}
+
+impl PackingAlgorithm for RestartAlgorithm {
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error> {
+        let (best, _) = self.plan_attempts(items, pack_template, rules, &mut NoObserver)?;
+        Ok(best)
+    }
+
+    /// Only the plan that is kept is reported to the observer. When restarts are made, that plan is only known
+    /// once all the attempts are made, so the plan is then made again, in the order of the items of that plan,
+    /// while it is reported.
+    fn plan_with_observer(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+        observer: &mut dyn PlanObserver,
+    ) -> Result<PlanOutcome, Error> {
+        if self.restarts_for(items, pack_template.sort_order) == 0 {
+            let (best, _) = self.plan_attempts(items, pack_template, rules, observer)?;
+            return Ok(best);
+        }
+
+        let (best, best_items) =
+            self.plan_attempts(items, pack_template, rules, &mut NoObserver)?;
+        plan_packs_with_observer(
+            best_items.as_deref().unwrap_or(items),
+            pack_template,
+            rules,
+            observer,
+        )?;
+        Ok(best)
+    }
+
+    /// The time limit isn't part of the strategy, because a plan that depends on the time can't be made
+    /// again. A time limit can stop the restarts early, so [`crate::plan_items`] records the number of restarts
+    /// that were made, from the [`PlanOutcome::optimality`] of the plan, instead.
+    fn strategy(&self) -> StrategyConfig {
+        StrategyConfig {
+            restarts: Some(self.restarts),
+            seed: self.seed,
+            objective: self.objective,
+            ..StrategyConfig::default()
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::str::FromStr;
+
+use super::*;
+
+fn create_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::LongToShort,
+        ..PackTemplate::new()
+    }
+}
+
+/// Items of the same length for which the greedy algorithm needs 3 packs in the given order, while 2 packs
+/// are enough when the 20 kg item is placed right after the first 30 kg item.
+fn create_items() -> Vec<ItemTemplate> {
+    vec![
+        ItemTemplate::from_str("a,10,1,30.0").unwrap(),
+        ItemTemplate::from_str("b,10,1,30.0").unwrap(),
+        ItemTemplate::from_str("c,10,1,20.0").unwrap(),
+        ItemTemplate::from_str("d,10,1,20.0").unwrap(),
+    ]
+}
+
+// RestartAlgorithm
+
+#[test]
+fn when_planning_without_restarts_it_should_return_the_greedy_plan() {
+    let algorithm = RestartAlgorithm::default();
+    let outcome = algorithm
+        .plan(&create_items(), &create_pack_template(), &[])
+        .expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 3);
+}
+
+#[test]
+fn when_planning_with_restarts_it_should_keep_the_best_plan() {
+    let algorithm = RestartAlgorithm {
+        restarts: 20,
+        seed: 42,
+        objective: RestartObjective::FewestPacks,
+        time_limit: None,
+    };
+    let outcome = algorithm
+        .plan(&create_items(), &create_pack_template(), &[])
+        .expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 2);
+    assert!(outcome.packs.iter().all(|p| p.weight == 50.0));
+}
+
+#[test]
+fn when_the_time_limit_is_reached_it_should_return_the_best_plan_so_far_with_a_warning() {
+    let algorithm = RestartAlgorithm {
+        restarts: 20,
+        seed: 42,
+        time_limit: Some(Duration::ZERO),
+        ..RestartAlgorithm::default()
+    };
+    let outcome = algorithm
+        .plan(&create_items(), &create_pack_template(), &[])
+        .expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 3);
+    assert_eq!(
+        outcome.warnings,
+        vec![PlanWarning::TimeLimitReached {
+            attempts: 1,
+            number_of_packs: 3,
+            minimum_number_of_packs: 2,
+        }]
+    );
+}
+
+#[test]
+fn when_the_time_limit_is_reached_with_an_optimal_plan_it_should_not_warn() {
+    let items = vec![ItemTemplate::from_str("a,10,2,25.0").unwrap()];
+    let algorithm = RestartAlgorithm {
+        restarts: 20,
+        time_limit: Some(Duration::ZERO),
+        ..RestartAlgorithm::default()
+    };
+    let outcome = algorithm
+        .plan(&items, &create_pack_template(), &[])
+        .expect("Failed to plan the packs.");
+    assert_eq!(outcome.packs.len(), 1);
+    assert!(outcome.warnings.is_empty());
+}
+
+#[test]
+fn when_planning_with_the_same_seed_it_should_return_the_same_plan() {
+    let items: Vec<ItemTemplate> = (0..20)
+        .map(|i| ItemTemplate::from_str(&format!("{i},10,1,{}", 5 + i % 7)).unwrap())
+        .collect();
+    let algorithm = RestartAlgorithm {
+        restarts: 10,
+        seed: 7,
+        objective: RestartObjective::BalancedWeight,
+        time_limit: None,
+    };
+
+    let first = algorithm
+        .plan(&items, &create_pack_template(), &[])
+        .unwrap();
+    let second = algorithm
+        .plan(&items, &create_pack_template(), &[])
+        .unwrap();
+    let hashes = |o: &PlanOutcome| o.packs.iter().map(|p| p.content_hash()).collect::<Vec<_>>();
+    assert_eq!(hashes(&first), hashes(&second));
+}
+
+#[test]
+fn when_planning_with_the_natural_sort_order_it_should_not_shuffle_the_items() {
+    let pack_template = PackTemplate {
+        sort_order: PackSortOrder::Natural,
+        ..create_pack_template()
+    };
+    let algorithm = RestartAlgorithm {
+        restarts: 20,
+        seed: 42,
+        objective: RestartObjective::FewestPacks,
+        time_limit: None,
+    };
+    let outcome = algorithm
+        .plan(&create_items(), &pack_template, &[])
+        .unwrap();
+    assert_eq!(outcome.packs.len(), 3);
+}
+
+#[test]
+fn when_planning_with_restarts_it_should_report_the_attempts() {
+    let algorithm = RestartAlgorithm {
+        restarts: 20,
+        seed: 42,
+        objective: RestartObjective::FewestPacks,
+        time_limit: None,
+    };
+    let outcome = algorithm
+        .plan(&create_items(), &create_pack_template(), &[])
+        .unwrap();
+    assert_eq!(
+        outcome.optimality,
+        Some(Optimality {
+            attempts: 21,
+            reaches_minimum: true,
+        })
+    );
+}
+
+#[test]
+fn when_planning_with_unlimited_restarts_it_should_stop_at_the_minimum_number_of_packs() {
+    let algorithm = RestartAlgorithm {
+        restarts: UNLIMITED_RESTARTS,
+        seed: 42,
+        objective: RestartObjective::FewestPacks,
+        time_limit: Some(Duration::from_secs(600)),
+    };
+    let outcome = algorithm
+        .plan(&create_items(), &create_pack_template(), &[])
+        .unwrap();
+    assert_eq!(outcome.packs.len(), 2);
+    assert!(outcome.optimality.unwrap().reaches_minimum);
+    assert!(outcome.warnings.is_empty());
+}
+
+#[test]
+fn when_planning_with_unlimited_restarts_and_nothing_to_shuffle_it_should_make_a_single_attempt() {
+    let pack_template = PackTemplate {
+        sort_order: PackSortOrder::Natural,
+        ..create_pack_template()
+    };
+    let algorithm = RestartAlgorithm {
+        restarts: UNLIMITED_RESTARTS,
+        time_limit: Some(Duration::from_secs(600)),
+        ..RestartAlgorithm::default()
+    };
+    let outcome = algorithm
+        .plan(&create_items(), &pack_template, &[])
+        .unwrap();
+    assert_eq!(
+        outcome.optimality,
+        Some(Optimality {
+            attempts: 1,
+            reaches_minimum: false,
+        })
+    );
+}
+
+/// Records the numbers of the packs that are closed.
+#[derive(Default)]
+struct ClosedPacksObserver {
+    closed_packs: Vec<usize>,
+}
+
+impl PlanObserver for ClosedPacksObserver {
+    fn on_pack_closed(&mut self, pack: &crate::model::Pack) {
+        self.closed_packs.push(pack.number);
+    }
+}
+
+#[test]
+fn when_planning_with_restarts_and_an_observer_it_should_only_report_the_plan_that_is_kept() {
+    let algorithm = RestartAlgorithm {
+        restarts: 20,
+        seed: 42,
+        objective: RestartObjective::FewestPacks,
+        time_limit: None,
+    };
+    let mut observer = ClosedPacksObserver::default();
+    let outcome = algorithm
+        .plan_with_observer(&create_items(), &create_pack_template(), &[], &mut observer)
+        .unwrap();
+    assert_eq!(outcome.packs.len(), 2);
+    assert_eq!(observer.closed_packs, vec![1, 2]);
+}
+
+// shuffle_equivalent_items()
+
+#[test]
+fn when_shuffling_items_it_should_only_swap_items_with_the_same_length() {
+    let mut items: Vec<ItemTemplate> = (0..10)
+        .map(|i| ItemTemplate::from_str(&format!("{i},{},1,1.0", 100 - (i / 3) * 10)).unwrap())
+        .collect();
+
+    shuffle_equivalent_items(&mut items, PackSortOrder::LongToShort, &mut Random::new(1));
+    let lengths: Vec<f64> = items.iter().map(|i| i.length).collect();
+    assert_eq!(
+        lengths,
+        vec![100.0, 100.0, 100.0, 90.0, 90.0, 90.0, 80.0, 80.0, 80.0, 70.0]
+    );
+}
+
+// RestartObjective
+
+#[test]
+fn when_parsing_an_objective_it_should_return_the_objective() {
+    assert_eq!(
+        RestartObjective::from_str("fewest-packs").unwrap(),
+        RestartObjective::FewestPacks
+    );
+    assert_eq!(
+        RestartObjective::from_str("balanced-weight").unwrap(),
+        RestartObjective::BalancedWeight
+    );
+    assert!(RestartObjective::from_str("cheapest").is_err());
+}
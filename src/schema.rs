@@ -0,0 +1,93 @@
+//! The JSON Schemas of the machine-readable outputs, generated from the types that are serialized, so that
+//! integrators can validate the outputs, or generate the types that read them, instead of working from
+//! examples.
+//!
+//! The schemas are generated with `schemars`, which depends on the `schema` feature.
+
+use serde_json::Value;
+#[cfg(feature = "text-format")]
+use strum::{Display, EnumString};
+
+use crate::errors::Error;
+
+#[cfg(all(test, feature = "schema"))]
+#[path = "schema_tests.rs"]
+mod tests;
+
+/// Defines the outputs of which the schema is available.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+pub enum OutputSchema {
+    /// The plan in the JSON format, which is also the structure of the CBOR format. The plans of an input with
+    /// several sections are an array of plans.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "plan"))]
+    Plan,
+
+    /// A line of the statistics file, which holds one JSON document per plan.
+    #[cfg_attr(feature = "text-format", strum(serialize = "statistics"))]
+    Statistics,
+}
+
+/// Returns the generator for the schemas of the outputs, which describes the documents as they are written.
+#[cfg(feature = "schema")]
+pub(crate) fn schema_generator() -> schemars::SchemaGenerator {
+    schemars::generate::SchemaSettings::draft2020_12()
+        .for_serialize()
+        .with_transform(schemars::transform::RecursiveTransform(
+            strip_intra_doc_links,
+        ))
+        .into_generator()
+}
+
+/// Removes the intra-doc links from the description of the schema, which is taken from the doc comments, so
+/// that e.g. "[`Pack`]" is described as "`Pack`".
+#[cfg(feature = "schema")]
+fn strip_intra_doc_links(schema: &mut schemars::Schema) {
+    if let Some(Value::String(description)) = schema.get_mut("description") {
+        *description = without_intra_doc_links(description);
+    }
+}
+
+/// Returns the text without the brackets, and the targets, of the intra-doc links in the text, e.g.
+/// "[`Pack`]" or "[`Pack`](crate::model::Pack)".
+#[cfg(feature = "schema")]
+fn without_intra_doc_links(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("[`") {
+        let end = match rest[start..].find("`]") {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        result.push_str(&rest[..start]);
+        result.push_str(&rest[start + 1..end + 1]);
+        rest = &rest[end + 2..];
+        if rest.starts_with('(') {
+            if let Some(target_end) = rest.find(')') {
+                rest = &rest[target_end + 1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Returns the JSON Schema of the output.
+#[cfg(feature = "schema")]
+pub fn output_schema(schema: OutputSchema) -> Result<Value, Error> {
+    Ok(match schema {
+        OutputSchema::Plan => crate::render::plan_schema(),
+        OutputSchema::Statistics => schema_generator()
+            .into_root_schema_for::<crate::stats::PlanStatistics>()
+            .to_value(),
+    })
+}
+
+/// Returns the JSON Schema of the output.
+#[cfg(not(feature = "schema"))]
+pub fn output_schema(_schema: OutputSchema) -> Result<Value, Error> {
+    Err(Error::UnsupportedSchema)
+}
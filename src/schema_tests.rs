@@ -0,0 +1,104 @@
+use std::str::FromStr;
+
+use super::*;
+use crate::model::{ItemTemplate, PackSortOrder, PackTemplate};
+use crate::render::{write_plan, OutputFormat, RenderOptions};
+
+// output_schema()
+
+#[test]
+fn when_describing_the_plan_it_should_accept_a_plan_or_an_array_of_plans() {
+    let schema = output_schema(OutputSchema::Plan).unwrap();
+
+    assert_eq!(schema["title"], "Plan");
+    let any_of = schema["anyOf"].as_array().unwrap();
+    assert_eq!(any_of.len(), 2);
+    assert_eq!(any_of[1]["type"], "array");
+    assert_eq!(any_of[1]["items"], any_of[0]);
+
+    let pack = &schema["$defs"]["PackDocument"];
+    let required: Vec<&str> = pack["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r.as_str().unwrap())
+        .collect();
+    assert_eq!(
+        required,
+        vec!["number", "items", "length", "weight", "hash"]
+    );
+}
+
+#[test]
+fn when_describing_the_plan_it_should_describe_every_property_of_a_written_pack() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        tare_weight: 1.5,
+        ..PackTemplate::new()
+    };
+    let items = vec![ItemTemplate::from_str("1001,100,3,4.0,value=10").unwrap()];
+    let packs = crate::planner::plan_packs(&items, &pack_template, &[])
+        .unwrap()
+        .packs;
+    let render_options = RenderOptions {
+        format: OutputFormat::Json,
+        explain: true,
+        ..RenderOptions::default()
+    };
+    let mut output = Vec::new();
+    write_plan(&mut output, &packs, None, &render_options).unwrap();
+    let plan: Value = serde_json::from_slice(&output).unwrap();
+
+    let schema = output_schema(OutputSchema::Plan).unwrap();
+    let pack_properties = schema["$defs"]["PackDocument"]["properties"]
+        .as_object()
+        .unwrap();
+    for property in plan["packs"][0].as_object().unwrap().keys() {
+        assert!(
+            pack_properties.contains_key(property),
+            "The schema doesn't describe the {property:?} property of a pack."
+        );
+    }
+}
+
+#[test]
+fn when_describing_the_statistics_it_should_describe_a_line_of_the_statistics_file() {
+    let schema = output_schema(OutputSchema::Statistics).unwrap();
+
+    assert_eq!(schema["title"], "PlanStatistics");
+    assert!(schema["properties"]["timestamp"].is_object());
+    assert!(schema["$defs"]["PlanParameters"].is_object());
+}
+
+#[test]
+fn when_describing_the_outputs_it_should_describe_them_without_intra_doc_links() {
+    for output in [OutputSchema::Plan, OutputSchema::Statistics] {
+        let schema = output_schema(output).unwrap().to_string();
+        assert!(!schema.contains("[`"), "{schema}");
+    }
+
+    let schema = output_schema(OutputSchema::Plan).unwrap();
+    let description = schema["$defs"]["Attestation"]["properties"]["configuration"]["description"]
+        .as_str()
+        .unwrap();
+    assert!(
+        description.contains("`PlannerConfig::from_toml` to make"),
+        "{description}"
+    );
+}
+
+// without_intra_doc_links()
+
+#[test]
+fn when_removing_the_intra_doc_links_it_should_keep_the_text_of_the_links() {
+    assert_eq!(
+        without_intra_doc_links("See [`Pack`] and [`Pack::add`](crate::model::Pack::add)."),
+        "See `Pack` and `Pack::add`."
+    );
+    assert_eq!(
+        without_intra_doc_links("Formatted as `#VERSION [version]`, e.g. [1, 2]."),
+        "Formatted as `#VERSION [version]`, e.g. [1, 2]."
+    );
+}
@@ -0,0 +1,98 @@
+//! Scores of plans, so that alternative plans, e.g. the attempts of the restart strategy, are ranked in the
+//! same way by the planner and by the users of the library.
+
+use std::cmp::Ordering;
+
+use crate::model::{find_weight_band, PlanOutcome, WeightBand};
+
+#[cfg(test)]
+#[path = "scoring_tests.rs"]
+mod tests;
+
+/// Defines an objective by which plans are ranked. A plan with a lower score is a better plan.
+pub trait Score {
+    /// Returns the score of the plan.
+    fn score(&self, plan: &PlanOutcome) -> f64;
+}
+
+/// Scores a plan by the number of packs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PackCount;
+
+impl Score for PackCount {
+    fn score(&self, plan: &PlanOutcome) -> f64 {
+        plan.packs.len() as f64
+    }
+}
+
+/// Scores a plan by the variance of the fraction of the maximum weight that is used by each pack. Plans in
+/// which all the packs are filled equally score 0.
+#[derive(Clone, Copy, Debug)]
+pub struct UtilizationVariance {
+    /// The maximum weight of a pack.
+    pub maximum_weight: f64,
+}
+
+impl Score for UtilizationVariance {
+    fn score(&self, plan: &PlanOutcome) -> f64 {
+        if plan.packs.is_empty() || self.maximum_weight <= 0.0 {
+            return 0.0;
+        }
+
+        let utilizations: Vec<f64> = plan
+            .packs
+            .iter()
+            .map(|p| p.weight / self.maximum_weight)
+            .collect();
+        let mean = utilizations.iter().sum::<f64>() / utilizations.len() as f64;
+        utilizations.iter().map(|u| (u - mean).powi(2)).sum::<f64>() / utilizations.len() as f64
+    }
+}
+
+/// Scores a plan by the total shipping cost of the packs, according to the weight bands of the carrier. A
+/// plan with a pack that isn't in any of the bands can't be shipped, and scores infinity.
+#[derive(Clone, Debug, Default)]
+pub struct ShippingCost {
+    /// The weight bands of the carrier.
+    pub weight_bands: Vec<WeightBand>,
+}
+
+impl Score for ShippingCost {
+    fn score(&self, plan: &PlanOutcome) -> f64 {
+        plan.packs
+            .iter()
+            .map(|p| find_weight_band(&self.weight_bands, p).map_or(f64::INFINITY, |b| b.cost))
+            .sum()
+    }
+}
+
+/// Scores a plan by the difference between the weight of the heaviest and the lightest pack.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Balance;
+
+impl Score for Balance {
+    fn score(&self, plan: &PlanOutcome) -> f64 {
+        let heaviest = plan.packs.iter().map(|p| p.weight).fold(f64::MIN, f64::max);
+        let lightest = plan.packs.iter().map(|p| p.weight).fold(f64::MAX, f64::min);
+        if plan.packs.is_empty() {
+            0.0
+        } else {
+            heaviest - lightest
+        }
+    }
+}
+
+/// Returns the score of the plan.
+pub fn score_plan(plan: &PlanOutcome, score: &dyn Score) -> f64 {
+    score.score(plan)
+}
+
+/// Compares two plans by each of the scores in turn, where the next score breaks a tie on the previous
+/// score. A plan that is `Less` is better.
+pub fn compare_plans(a: &PlanOutcome, b: &PlanOutcome, scores: &[&dyn Score]) -> Ordering {
+    scores
+        .iter()
+        .map(|s| score_plan(a, *s).total_cmp(&score_plan(b, *s)))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,107 @@
+use super::*;
+use crate::model::Pack;
+
+fn create_plan(weights: &[f64]) -> PlanOutcome {
+    PlanOutcome {
+        packs: weights
+            .iter()
+            .enumerate()
+            .map(|(index, weight)| Pack {
+                weight: *weight,
+                ..Pack::new(index + 1)
+            })
+            .collect(),
+        warnings: Vec::new(),
+        optimality: None,
+    }
+}
+
+fn create_weight_bands() -> Vec<WeightBand> {
+    vec![
+        WeightBand {
+            name: "LIGHT".to_string(),
+            minimum_weight: 0.0,
+            maximum_weight: 10.0,
+            cost: 4.5,
+        },
+        WeightBand {
+            name: "HEAVY".to_string(),
+            minimum_weight: 10.0,
+            maximum_weight: 30.0,
+            cost: 9.0,
+        },
+    ]
+}
+
+// Score
+
+#[test]
+fn when_scoring_a_plan_it_should_return_the_score_of_each_objective() {
+    let plan = create_plan(&[40.0, 20.0, 30.0]);
+    assert_eq!(score_plan(&plan, &PackCount), 3.0);
+    assert_eq!(score_plan(&plan, &Balance), 20.0);
+
+    let variance = score_plan(
+        &plan,
+        &UtilizationVariance {
+            maximum_weight: 50.0,
+        },
+    );
+    assert!((variance - 0.0266667).abs() < 1e-6, "{variance}");
+}
+
+#[test]
+fn when_scoring_an_empty_plan_it_should_return_zero() {
+    let plan = create_plan(&[]);
+    assert_eq!(score_plan(&plan, &PackCount), 0.0);
+    assert_eq!(score_plan(&plan, &Balance), 0.0);
+    assert_eq!(
+        score_plan(
+            &plan,
+            &UtilizationVariance {
+                maximum_weight: 50.0
+            }
+        ),
+        0.0
+    );
+}
+
+#[test]
+fn when_scoring_the_shipping_cost_it_should_sum_the_cost_of_the_bands() {
+    let score = ShippingCost {
+        weight_bands: create_weight_bands(),
+    };
+    assert_eq!(score_plan(&create_plan(&[5.0, 25.0]), &score), 13.5);
+    assert_eq!(
+        score_plan(&create_plan(&[5.0, 35.0]), &score),
+        f64::INFINITY
+    );
+
+    // The bands apply to the gross weight, so the tare moves the pack into the heavier band.
+    let mut plan = create_plan(&[9.5]);
+    plan.packs[0].tare_weight = 1.0;
+    assert_eq!(score_plan(&plan, &score), 9.0);
+}
+
+// compare_plans()
+
+#[test]
+fn when_comparing_plans_it_should_break_ties_with_the_next_score() {
+    let balanced = create_plan(&[25.0, 25.0]);
+    let unbalanced = create_plan(&[40.0, 10.0]);
+    let single = create_plan(&[50.0]);
+
+    assert_eq!(
+        compare_plans(&balanced, &unbalanced, &[&PackCount, &Balance]),
+        Ordering::Less
+    );
+    assert_eq!(
+        compare_plans(&single, &balanced, &[&PackCount, &Balance]),
+        Ordering::Less
+    );
+    assert_eq!(
+        compare_plans(&balanced, &single, &[&Balance]),
+        Ordering::Equal
+    );
+    assert_eq!(compare_plans(&balanced, &unbalanced, &[]), Ordering::Equal);
+}
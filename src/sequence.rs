@@ -0,0 +1,102 @@
+//! The last pack number used by earlier plans, kept in a file so that the packs of consecutive plans continue
+//! the numbering and the pack labels stay unique, e.g. across a shipping day.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::Error;
+use crate::input::InputReader;
+
+#[cfg(test)]
+#[path = "sequence_tests.rs"]
+mod tests;
+
+/// Hands out the pack numbers for the plans, continuing from the last pack number in the sequence file. The
+/// file contains only the last pack number that was used, and a missing or empty file starts the numbering
+/// at 1. The file is locked while the numbers are handed out, so plans that run at the same time, in the
+/// same process or in different processes, never get the same pack numbers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackSequence {
+    /// The path of the sequence file.
+    pub path: PathBuf,
+}
+
+impl PackSequence {
+    /// Creates a sequence that keeps the last pack number in the file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> PackSequence {
+        PackSequence { path: path.into() }
+    }
+
+    /// Returns the last pack number that was handed out, or 0 if no pack numbers were handed out yet.
+    pub fn last_pack_number(&self) -> Result<usize, Error> {
+        let mut file = self.open()?;
+        self.lock(&file)?;
+        self.read(&mut file)
+    }
+
+    /// Hands out the given number of consecutive pack numbers and returns the first of these numbers. The
+    /// numbers are recorded in the sequence file before they are returned, so they are never handed out
+    /// again, even if the plan that uses them isn't written.
+    pub fn reserve(&self, number_of_packs: usize) -> Result<usize, Error> {
+        let mut file = self.open()?;
+        self.lock(&file)?;
+
+        let last_pack_number = self.read(&mut file)?;
+        if number_of_packs > 0 {
+            let to_write_error = |e: std::io::Error| Error::UnableToWriteOutput {
+                reason: e.to_string(),
+            };
+            file.seek(SeekFrom::Start(0)).map_err(to_write_error)?;
+            file.set_len(0).map_err(to_write_error)?;
+            writeln!(file, "{}", last_pack_number + number_of_packs).map_err(to_write_error)?;
+            file.sync_all().map_err(to_write_error)?;
+        }
+
+        // The lock is released when the file is closed.
+        Ok(last_pack_number + 1)
+    }
+
+    fn open(&self) -> Result<File, Error> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+            .map_err(|e| self.open_error(e))
+    }
+
+    fn lock(&self, file: &File) -> Result<(), Error> {
+        file.lock().map_err(|e| self.open_error(e))
+    }
+
+    fn read(&self, file: &mut File) -> Result<usize, Error> {
+        let mut content = String::new();
+        let mut reader = InputReader::new(file, &display_path(&self.path));
+        reader
+            .read_to_string(&mut content)
+            .map_err(|e| reader.error(e))?;
+
+        let content = content.trim();
+        if content.is_empty() {
+            return Ok(0);
+        }
+
+        content.parse().map_err(|_| Error::InvalidSequenceFile {
+            path: display_path(&self.path),
+            content: content.to_string(),
+        })
+    }
+
+    fn open_error(&self, e: std::io::Error) -> Error {
+        Error::UnableToOpenFile {
+            path: display_path(&self.path),
+            reason: e.to_string(),
+        }
+    }
+}
+
+fn display_path(path: &Path) -> String {
+    path.display().to_string()
+}
@@ -0,0 +1,58 @@
+use super::*;
+
+/// Returns the path of a sequence file, unique to the test, that doesn't exist yet.
+fn create_sequence_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-sequence-{}-{}.txt",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// PackSequence::reserve()
+
+#[test]
+fn when_reserving_without_a_sequence_file_it_should_start_at_one() {
+    let path = create_sequence_path("new");
+    let sequence = PackSequence::new(&path);
+
+    assert_eq!(sequence.reserve(37).unwrap(), 1);
+    assert_eq!(sequence.last_pack_number().unwrap(), 37);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "37\n");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn when_reserving_again_it_should_continue_the_numbering() {
+    let path = create_sequence_path("continue");
+    std::fs::write(&path, "37\n").unwrap();
+    let sequence = PackSequence::new(&path);
+
+    assert_eq!(sequence.reserve(5).unwrap(), 38);
+    assert_eq!(sequence.reserve(0).unwrap(), 43);
+    assert_eq!(sequence.reserve(1).unwrap(), 43);
+    assert_eq!(sequence.last_pack_number().unwrap(), 43);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn when_reserving_with_an_invalid_sequence_file_it_should_return_an_error() {
+    let path = create_sequence_path("invalid");
+    std::fs::write(&path, "pack 37\n").unwrap();
+    let sequence = PackSequence::new(&path);
+
+    assert_eq!(
+        sequence.reserve(1).err().unwrap(),
+        Error::InvalidSequenceFile {
+            path: path.display().to_string(),
+            content: "pack 37".to_string()
+        }
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "pack 37\n");
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,379 @@
+//! Pushing the packs of a plan, as shipments, to the API of a carrier, so that the packs don't have to be
+//! entered by hand.
+//!
+//! The packs are read from a plan in the JSON format. Each carrier API is a backend that maps a pack to the
+//! parcel schema of the API and finds the ID of the shipment in the response. The shipments are sent over
+//! HTTP, which depends on the `http` feature.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::errors::Error;
+
+#[cfg(test)]
+#[path = "shipping_tests.rs"]
+mod tests;
+
+/// Defines the carrier APIs to which the shipments can be sent.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CarrierApi {
+    /// The Shippo API, or an API with the same schema, to which a shipment is posted as `[url]/shipments/`.
+    Shippo,
+
+    /// The ShipStation API, or an API with the same schema, to which a shipment is posted as
+    /// `[url]/v2/shipments`.
+    ShipStation,
+}
+
+/// Defines the units in which the lengths of the packs are sent to the carrier.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    #[default]
+    Cm,
+    In,
+}
+
+/// Defines the units in which the weights of the packs are sent to the carrier.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WeightUnit {
+    #[default]
+    Kg,
+    G,
+    Lb,
+    Oz,
+}
+
+/// Describes the carrier API to which the shipments are sent, read from a carrier file in YAML format.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CarrierConfiguration {
+    /// The API to which the shipments are sent.
+    pub api: CarrierApi,
+
+    /// The base URL of the API, e.g. `https://api.goshippo.com`.
+    pub url: String,
+
+    /// The name of the environment variable that contains the API key, so that the key isn't stored in the
+    /// carrier file.
+    pub api_key_variable: String,
+
+    /// The unit of the lengths of the packs.
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+
+    /// The unit of the weights of the packs.
+    #[serde(default)]
+    pub weight_unit: WeightUnit,
+
+    /// The width of every parcel, because the plan only knows the length of a pack.
+    pub width: f64,
+
+    /// The height of every parcel, because the plan only knows the length of a pack.
+    pub height: f64,
+
+    /// The address from which the parcels are shipped, in the schema of the API.
+    pub address_from: Value,
+
+    /// The address to which the parcels without a destination, or with a destination that isn't in the
+    /// 'addresses', are shipped, in the schema of the API.
+    #[serde(default)]
+    pub address_to: Option<Value>,
+
+    /// The addresses to which the parcels are shipped, by the destination of their pack, in the schema of the
+    /// API.
+    #[serde(default)]
+    pub addresses: HashMap<String, Value>,
+}
+
+impl CarrierConfiguration {
+    /// Returns the address to which the parcels for the given destination are shipped.
+    pub fn address_to(&self, destination: Option<&str>) -> Option<&Value> {
+        destination
+            .and_then(|d| self.addresses.get(d))
+            .or(self.address_to.as_ref())
+    }
+
+    /// Returns the backend of the API.
+    pub fn backend(&self) -> Box<dyn CarrierBackend> {
+        match self.api {
+            CarrierApi::Shippo => Box::new(ShippoBackend),
+            CarrierApi::ShipStation => Box::new(ShipStationBackend),
+        }
+    }
+}
+
+/// Reads a carrier file in YAML format.
+pub fn parse_carrier_configuration<R: Read>(reader: R) -> Result<CarrierConfiguration, Error> {
+    let configuration: CarrierConfiguration =
+        serde_yaml::from_reader(reader).map_err(|e| Error::InvalidCarrierConfiguration {
+            reason: e.to_string(),
+        })?;
+    if !(configuration.width > 0.0 && configuration.height > 0.0) {
+        return Err(Error::InvalidCarrierConfiguration {
+            reason: "The width and the height of the parcels must be positive.".to_string(),
+        });
+    }
+
+    Ok(configuration)
+}
+
+/// Describes a single pack that is shipped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shipment {
+    pub pack_number: usize,
+    pub length: f64,
+
+    /// The weight of the pack, including the tare weight if the pack has one.
+    pub weight: f64,
+
+    pub destination: Option<String>,
+}
+
+/// A pack in a plan in the JSON format. The fields that aren't needed for a shipment are ignored.
+#[derive(Deserialize)]
+struct ShippedPackDocument {
+    number: usize,
+    length: f64,
+    weight: f64,
+    #[serde(default)]
+    gross_weight: Option<f64>,
+    #[serde(default)]
+    destination: Option<String>,
+}
+
+/// A plan in the JSON format.
+#[derive(Deserialize)]
+struct ShippedPlanDocument {
+    packs: Vec<ShippedPackDocument>,
+}
+
+/// A plan of a single section, or the plans of all the sections of the input.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ShippedPlansDocument {
+    Single(ShippedPlanDocument),
+    Sections(Vec<ShippedPlanDocument>),
+}
+
+/// Reads the shipments from a plan in the JSON format, or from the plans of the sections of the input. The
+/// 'path' is the origin of the plan, which is used in the errors.
+pub fn parse_shipments<R: Read>(reader: R, path: &str) -> Result<Vec<Shipment>, Error> {
+    let document: ShippedPlansDocument =
+        serde_json::from_reader(reader).map_err(|e| Error::InvalidJsonPlan {
+            path: path.to_string(),
+            reason: e.to_string(),
+        })?;
+    let plans = match document {
+        ShippedPlansDocument::Single(plan) => vec![plan],
+        ShippedPlansDocument::Sections(plans) => plans,
+    };
+
+    Ok(plans
+        .into_iter()
+        .flat_map(|plan| plan.packs)
+        .map(|pack| Shipment {
+            pack_number: pack.number,
+            length: pack.length,
+            weight: pack.gross_weight.unwrap_or(pack.weight),
+            destination: pack.destination,
+        })
+        .collect())
+}
+
+/// Describes the HTTP request that creates a shipment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShipmentRequest {
+    /// The path of the request, relative to the base URL of the API.
+    pub path: &'static str,
+
+    /// The headers of the request, other than the content type.
+    pub headers: Vec<(&'static str, String)>,
+
+    pub body: Value,
+}
+
+/// Maps the packs to the schema of a carrier API.
+pub trait CarrierBackend {
+    /// Returns the request that creates the shipment of the pack, shipped to the given address.
+    fn shipment_request(
+        &self,
+        shipment: &Shipment,
+        address_to: &Value,
+        configuration: &CarrierConfiguration,
+        api_key: &str,
+    ) -> ShipmentRequest;
+
+    /// Returns the ID of the created shipment from the response of the API.
+    fn shipment_id(&self, response: &Value) -> Option<String>;
+}
+
+/// The backend of the Shippo API.
+pub struct ShippoBackend;
+
+impl CarrierBackend for ShippoBackend {
+    fn shipment_request(
+        &self,
+        shipment: &Shipment,
+        address_to: &Value,
+        configuration: &CarrierConfiguration,
+        api_key: &str,
+    ) -> ShipmentRequest {
+        let distance_unit = match configuration.length_unit {
+            LengthUnit::Cm => "cm",
+            LengthUnit::In => "in",
+        };
+        let mass_unit = match configuration.weight_unit {
+            WeightUnit::Kg => "kg",
+            WeightUnit::G => "g",
+            WeightUnit::Lb => "lb",
+            WeightUnit::Oz => "oz",
+        };
+
+        // The dimensions and the weight of a Shippo parcel are strings.
+        ShipmentRequest {
+            path: "/shipments/",
+            headers: vec![("Authorization", format!("ShippoToken {api_key}"))],
+            body: json!({
+                "address_from": configuration.address_from,
+                "address_to": address_to,
+                "parcels": [{
+                    "length": shipment.length.to_string(),
+                    "width": configuration.width.to_string(),
+                    "height": configuration.height.to_string(),
+                    "distance_unit": distance_unit,
+                    "weight": shipment.weight.to_string(),
+                    "mass_unit": mass_unit,
+                }],
+                "metadata": format!("Pack {}", shipment.pack_number),
+            }),
+        }
+    }
+
+    fn shipment_id(&self, response: &Value) -> Option<String> {
+        response["object_id"].as_str().map(|id| id.to_string())
+    }
+}
+
+/// The backend of the ShipStation API.
+pub struct ShipStationBackend;
+
+impl CarrierBackend for ShipStationBackend {
+    fn shipment_request(
+        &self,
+        shipment: &Shipment,
+        address_to: &Value,
+        configuration: &CarrierConfiguration,
+        api_key: &str,
+    ) -> ShipmentRequest {
+        let length_unit = match configuration.length_unit {
+            LengthUnit::Cm => "centimeter",
+            LengthUnit::In => "inch",
+        };
+        let weight_unit = match configuration.weight_unit {
+            WeightUnit::Kg => "kilogram",
+            WeightUnit::G => "gram",
+            WeightUnit::Lb => "pound",
+            WeightUnit::Oz => "ounce",
+        };
+
+        ShipmentRequest {
+            path: "/v2/shipments",
+            headers: vec![("API-Key", api_key.to_string())],
+            body: json!({
+                "shipments": [{
+                    "external_shipment_id": format!("pack-{}", shipment.pack_number),
+                    "ship_from": configuration.address_from,
+                    "ship_to": address_to,
+                    "packages": [{
+                        "weight": { "value": shipment.weight, "unit": weight_unit },
+                        "dimensions": {
+                            "length": shipment.length,
+                            "width": configuration.width,
+                            "height": configuration.height,
+                            "unit": length_unit,
+                        },
+                    }],
+                }],
+            }),
+        }
+    }
+
+    fn shipment_id(&self, response: &Value) -> Option<String> {
+        response["shipments"][0]["shipment_id"]
+            .as_str()
+            .map(|id| id.to_string())
+    }
+}
+
+/// Returns the API key of the carrier from the environment.
+pub fn carrier_api_key(configuration: &CarrierConfiguration) -> Result<String, Error> {
+    std::env::var(&configuration.api_key_variable)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| Error::MissingCarrierApiKey {
+            variable: configuration.api_key_variable.clone(),
+        })
+}
+
+/// Creates the shipment of the pack with the carrier API, in the schema of the given backend, e.g. the
+/// backend of the configuration, and returns the ID of the shipment.
+#[cfg(feature = "http")]
+pub fn create_shipment(
+    backend: &dyn CarrierBackend,
+    configuration: &CarrierConfiguration,
+    api_key: &str,
+    shipment: &Shipment,
+) -> Result<String, Error> {
+    let address_to = configuration
+        .address_to(shipment.destination.as_deref())
+        .ok_or_else(|| Error::MissingShipmentAddress {
+            pack_number: shipment.pack_number,
+            destination: shipment.destination.clone(),
+        })?;
+    let request = backend.shipment_request(shipment, address_to, configuration, api_key);
+
+    let url = format!(
+        "{}{}",
+        configuration.url.trim_end_matches('/'),
+        request.path
+    );
+    let to_response_error = |reason: String| Error::RemoteRequestFailed {
+        url: url.clone(),
+        reason,
+    };
+
+    let body = serde_json::to_vec(&request.body).map_err(|e| to_response_error(e.to_string()))?;
+    let mut http_request = ureq::post(&url)
+        .set("Content-Type", "application/json")
+        .set("Accept", "application/json");
+    for (name, value) in &request.headers {
+        http_request = http_request.set(name, value);
+    }
+    let response = http_request
+        .send_bytes(&body)
+        .map_err(|e| crate::remote::to_request_error(&url, e))?;
+    let response: Value = serde_json::from_reader(response.into_reader())
+        .map_err(|e| to_response_error(e.to_string()))?;
+
+    backend.shipment_id(&response).ok_or_else(|| {
+        to_response_error("The response doesn't contain the ID of the shipment.".to_string())
+    })
+}
+
+#[cfg(not(feature = "http"))]
+pub fn create_shipment(
+    _backend: &dyn CarrierBackend,
+    configuration: &CarrierConfiguration,
+    _api_key: &str,
+    _shipment: &Shipment,
+) -> Result<String, Error> {
+    Err(Error::UnsupportedHttp {
+        url: configuration.url.clone(),
+    })
+}
@@ -0,0 +1,266 @@
+use super::*;
+
+#[cfg(feature = "http")]
+use crate::test_support::serve_single_request;
+
+const CARRIER: &str = r#"
+api: shippo
+url: https://api.example.com/
+api_key_variable: CARRIER_API_KEY
+length_unit: cm
+weight_unit: kg
+width: 40
+height: 30
+address_from: { name: Warehouse, city: Utrecht }
+address_to: { name: Customer, city: Leiden }
+addresses:
+  NORTH: { name: North depot, city: Groningen }
+"#;
+
+const PLAN: &str = r#"{
+    "packs": [
+        { "number": 1, "items": [], "length": 6200.0, "weight": 96.5, "hash": "a" },
+        { "number": 2, "items": [], "length": 7200.0, "weight": 50.0, "gross_weight": 52.5,
+          "destination": "NORTH", "hash": "b" }
+    ],
+    "totals": { "input": { "items": 2, "weight": 146.5 } }
+}"#;
+
+fn shipment() -> Shipment {
+    Shipment {
+        pack_number: 2,
+        length: 7200.0,
+        weight: 52.5,
+        destination: Some("NORTH".to_string()),
+    }
+}
+
+// parse_carrier_configuration()
+
+#[test]
+fn when_parsing_a_carrier_file_it_should_return_the_configuration() {
+    let configuration = parse_carrier_configuration(CARRIER.as_bytes()).unwrap();
+
+    assert_eq!(configuration.api, CarrierApi::Shippo);
+    assert_eq!(configuration.api_key_variable, "CARRIER_API_KEY");
+    assert_eq!(configuration.length_unit, LengthUnit::Cm);
+    assert_eq!(configuration.weight_unit, WeightUnit::Kg);
+    assert_eq!(configuration.address_from["city"], "Utrecht");
+}
+
+#[test]
+fn when_parsing_a_carrier_file_without_parcel_dimensions_it_should_return_an_error() {
+    let carrier = CARRIER.replace("height: 30", "height: 0");
+
+    let result = parse_carrier_configuration(carrier.as_bytes());
+    assert!(matches!(
+        result,
+        Err(Error::InvalidCarrierConfiguration { .. })
+    ));
+}
+
+#[test]
+fn when_parsing_a_carrier_file_with_an_unknown_api_it_should_return_an_error() {
+    let carrier = CARRIER.replace("api: shippo", "api: pigeon");
+
+    let result = parse_carrier_configuration(carrier.as_bytes());
+    assert!(matches!(
+        result,
+        Err(Error::InvalidCarrierConfiguration { .. })
+    ));
+}
+
+// CarrierConfiguration::address_to()
+
+#[test]
+fn when_finding_the_address_of_a_destination_it_should_fall_back_to_the_default_address() {
+    let configuration = parse_carrier_configuration(CARRIER.as_bytes()).unwrap();
+
+    assert_eq!(
+        configuration.address_to(Some("NORTH")).unwrap()["city"],
+        "Groningen"
+    );
+    assert_eq!(
+        configuration.address_to(Some("SOUTH")).unwrap()["city"],
+        "Leiden"
+    );
+    assert_eq!(configuration.address_to(None).unwrap()["city"], "Leiden");
+}
+
+// parse_shipments()
+
+#[test]
+fn when_parsing_the_shipments_it_should_use_the_gross_weight_of_the_packs() {
+    let shipments = parse_shipments(PLAN.as_bytes(), "plan.json").unwrap();
+
+    assert_eq!(
+        shipments,
+        vec![
+            Shipment {
+                pack_number: 1,
+                length: 6200.0,
+                weight: 96.5,
+                destination: None,
+            },
+            shipment(),
+        ]
+    );
+}
+
+#[test]
+fn when_parsing_the_shipments_of_sections_it_should_return_the_packs_of_every_section() {
+    let plans = format!("[{PLAN},{PLAN}]");
+
+    let shipments = parse_shipments(plans.as_bytes(), "plan.json").unwrap();
+    assert_eq!(shipments.len(), 4);
+}
+
+#[test]
+fn when_parsing_the_shipments_of_a_text_plan_it_should_return_an_error() {
+    let result = parse_shipments("Pack Number: 1\n".as_bytes(), "plan.txt");
+    assert!(matches!(
+        result,
+        Err(Error::InvalidJsonPlan { path, .. }) if path == "plan.txt"
+    ));
+}
+
+// ShippoBackend
+
+#[test]
+fn when_mapping_a_pack_to_a_shippo_shipment_it_should_describe_the_parcel() {
+    let configuration = parse_carrier_configuration(CARRIER.as_bytes()).unwrap();
+    let address_to = configuration.address_to(Some("NORTH")).unwrap();
+
+    let request = ShippoBackend.shipment_request(&shipment(), address_to, &configuration, "key");
+    assert_eq!(request.path, "/shipments/");
+    assert_eq!(
+        request.headers,
+        vec![("Authorization", "ShippoToken key".to_string())]
+    );
+    assert_eq!(
+        request.body["parcels"][0],
+        json!({
+            "length": "7200",
+            "width": "40",
+            "height": "30",
+            "distance_unit": "cm",
+            "weight": "52.5",
+            "mass_unit": "kg",
+        })
+    );
+    assert_eq!(request.body["address_to"]["city"], "Groningen");
+    assert_eq!(request.body["metadata"], "Pack 2");
+    assert_eq!(
+        ShippoBackend.shipment_id(&json!({ "object_id": "abc" })),
+        Some("abc".to_string())
+    );
+}
+
+// ShipStationBackend
+
+#[test]
+fn when_mapping_a_pack_to_a_shipstation_shipment_it_should_describe_the_package() {
+    let carrier = CARRIER
+        .replace("api: shippo", "api: shipstation")
+        .replace("length_unit: cm", "length_unit: in")
+        .replace("weight_unit: kg", "weight_unit: lb");
+    let configuration = parse_carrier_configuration(carrier.as_bytes()).unwrap();
+    let address_to = configuration.address_to(None).unwrap();
+
+    let request =
+        ShipStationBackend.shipment_request(&shipment(), address_to, &configuration, "key");
+    assert_eq!(request.path, "/v2/shipments");
+    assert_eq!(request.headers, vec![("API-Key", "key".to_string())]);
+    assert_eq!(
+        request.body["shipments"][0]["packages"][0],
+        json!({
+            "weight": { "value": 52.5, "unit": "pound" },
+            "dimensions": { "length": 7200.0, "width": 40.0, "height": 30.0, "unit": "inch" },
+        })
+    );
+    assert_eq!(
+        request.body["shipments"][0]["external_shipment_id"],
+        "pack-2"
+    );
+    assert_eq!(
+        ShipStationBackend.shipment_id(&json!({ "shipments": [{ "shipment_id": "se-1" }] })),
+        Some("se-1".to_string())
+    );
+}
+
+// create_shipment()
+
+#[cfg(feature = "http")]
+#[test]
+fn when_creating_a_shipment_it_should_post_the_parcel_and_return_the_id() {
+    let (url, server) = serve_single_request("201 Created", r#"{"object_id":"abc123"}"#);
+    let carrier = CARRIER.replace("https://api.example.com/", &url);
+    let configuration = parse_carrier_configuration(carrier.as_bytes()).unwrap();
+
+    let id = create_shipment(&ShippoBackend, &configuration, "key", &shipment()).unwrap();
+    assert_eq!(id, "abc123");
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /shipments/ HTTP/1.1"));
+    assert!(request
+        .to_ascii_lowercase()
+        .contains("authorization: shippotoken key"));
+    assert!(request.contains("\"metadata\":\"Pack 2\""));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn when_creating_a_shipment_it_should_use_the_schema_of_the_given_backend() {
+    let (url, server) = serve_single_request("200 OK", r#"{"shipments":[{"shipment_id":"se-1"}]}"#);
+    let carrier = CARRIER.replace("https://api.example.com/", &url);
+    let configuration = parse_carrier_configuration(carrier.as_bytes()).unwrap();
+
+    let id = create_shipment(&ShipStationBackend, &configuration, "key", &shipment()).unwrap();
+    assert_eq!(id, "se-1");
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /v2/shipments HTTP/1.1"));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn when_the_response_has_no_shipment_id_it_should_return_an_error() {
+    let (url, server) = serve_single_request("200 OK", "{}");
+    let carrier = CARRIER.replace("https://api.example.com/", &url);
+    let configuration = parse_carrier_configuration(carrier.as_bytes()).unwrap();
+
+    let result = create_shipment(&ShippoBackend, &configuration, "key", &shipment());
+    assert!(matches!(result, Err(Error::RemoteRequestFailed { .. })));
+    server.join().unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn when_a_pack_has_no_address_it_should_return_an_error() {
+    let carrier = CARRIER.replace("address_to: { name: Customer, city: Leiden }", "");
+    let configuration = parse_carrier_configuration(carrier.as_bytes()).unwrap();
+    let shipment = Shipment {
+        destination: None,
+        ..shipment()
+    };
+
+    let result = create_shipment(&ShippoBackend, &configuration, "key", &shipment);
+    assert!(matches!(
+        result,
+        Err(Error::MissingShipmentAddress { pack_number: 2, .. })
+    ));
+}
+
+#[cfg(not(feature = "http"))]
+#[test]
+fn when_http_is_not_supported_it_should_return_an_error() {
+    let configuration = parse_carrier_configuration(CARRIER.as_bytes()).unwrap();
+
+    let result = create_shipment(
+        configuration.backend().as_ref(),
+        &configuration,
+        "key",
+        &shipment(),
+    );
+    assert!(matches!(result, Err(Error::UnsupportedHttp { .. })));
+}
@@ -0,0 +1,95 @@
+//! Detached ed25519 signatures of the plans, so that the systems that use a plan, e.g. label printers, can
+//! verify that the plan wasn't changed after it was made.
+//!
+//! The key is an ed25519 private key in a PKCS#8 PEM file, e.g. created with
+//! `openssl genpkey -algorithm ed25519`. The support for signatures depends on the `sign` feature.
+
+#[cfg(feature = "sign")]
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+#[cfg(feature = "sign")]
+use rustls_pki_types::pem::PemObject;
+#[cfg(feature = "sign")]
+use rustls_pki_types::PrivatePkcs8KeyDer;
+
+use crate::errors::Error;
+
+#[cfg(all(test, feature = "sign"))]
+#[path = "signing_tests.rs"]
+mod tests;
+
+/// The length, in bytes, of an ed25519 signature.
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// The private key with which the plans are signed.
+#[cfg(feature = "sign")]
+#[derive(Debug)]
+pub struct SigningKey {
+    key_pair: Ed25519KeyPair,
+}
+
+#[cfg(feature = "sign")]
+impl SigningKey {
+    /// Reads the key from the contents of a PEM file. The 'path' is the origin of the key, which is used in
+    /// the errors.
+    pub fn from_pem(pem: &[u8], path: &str) -> Result<SigningKey, Error> {
+        let to_key_error = |reason: String| Error::InvalidSigningKey {
+            path: path.to_string(),
+            reason,
+        };
+
+        let der =
+            PrivatePkcs8KeyDer::from_pem_slice(pem).map_err(|e| to_key_error(e.to_string()))?;
+        // Keys created by OpenSSL don't contain the public key, which is derived from the private key.
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der.secret_pkcs8_der())
+            .map_err(|e| to_key_error(format!("The key is not an ed25519 private key ({e}).")))?;
+
+        Ok(SigningKey { key_pair })
+    }
+
+    /// Returns the public key with which the signatures are verified.
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+
+    /// Returns the signature of the plan.
+    pub fn sign(&self, plan: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+        let mut signature = [0; SIGNATURE_LENGTH];
+        signature.copy_from_slice(self.key_pair.sign(plan).as_ref());
+        signature
+    }
+}
+
+/// A placeholder for the private key in a build without support for signatures.
+#[cfg(not(feature = "sign"))]
+#[derive(Debug)]
+pub struct SigningKey;
+
+#[cfg(not(feature = "sign"))]
+impl SigningKey {
+    pub fn from_pem(_pem: &[u8], path: &str) -> Result<SigningKey, Error> {
+        Err(Error::UnsupportedSigning {
+            path: path.to_string(),
+        })
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &[]
+    }
+
+    pub fn sign(&self, _plan: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+        [0; SIGNATURE_LENGTH]
+    }
+}
+
+/// Indicates if the signature of the plan was made with the private key of the given public key.
+#[cfg(feature = "sign")]
+pub fn verify_plan(public_key: &[u8], plan: &[u8], signature: &[u8]) -> bool {
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(plan, signature)
+        .is_ok()
+}
+
+#[cfg(not(feature = "sign"))]
+pub fn verify_plan(_public_key: &[u8], _plan: &[u8], _signature: &[u8]) -> bool {
+    false
+}
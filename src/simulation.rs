@@ -0,0 +1,220 @@
+//! Repeated plans of random samples of the items, which show how the number of packs varies with the mix of
+//! the items, e.g. to forecast the staff that is needed to pack a day of orders.
+
+use std::fmt;
+
+#[cfg(feature = "text-format")]
+use strum::{Display, EnumString};
+
+use crate::errors::Error;
+use crate::model::{InfeasibleItemPolicy, ItemTemplate, PackTemplate};
+use crate::planner::{
+    place_infeasible_items, prefer_homogeneous_packs, separate_infeasible_items, PackingAlgorithm,
+};
+use crate::restarts::Random;
+use crate::strip::StripAlgorithm;
+use crate::{prepare_items, Configuration};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "simulation_tests.rs"]
+mod tests;
+
+/// Defines how the items of each run of a simulation are selected.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "text-format", derive(Display, EnumString))]
+pub enum SampleMode {
+    /// As many batches as the input has are drawn at random, with replacement, so a batch may be planned
+    /// more than once, or not at all.
+    #[default]
+    #[cfg_attr(feature = "text-format", strum(serialize = "subset"))]
+    Subset,
+
+    /// All the batches are planned, in a random order. With a sort order other than `NATURAL` only the order
+    /// of the batches that the sort order considers equal changes.
+    #[cfg_attr(feature = "text-format", strum(serialize = "permutation"))]
+    Permutation,
+}
+
+/// The settings of a simulation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Simulation {
+    /// The number of plans that are made.
+    pub runs: usize,
+
+    /// The way the items of each run are selected.
+    pub mode: SampleMode,
+
+    /// The seed of the random number generator that selects the items. The same seed always produces the
+    /// same report.
+    pub seed: u64,
+}
+
+/// The number of packs of each run of a simulation, and the statistics of their distribution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    /// The number of packs of each run, from the fewest to the most packs.
+    pub pack_counts: Vec<usize>,
+}
+
+impl SimulationReport {
+    /// Creates the report of the given numbers of packs, in any order.
+    pub fn new(mut pack_counts: Vec<usize>) -> SimulationReport {
+        pack_counts.sort_unstable();
+        SimulationReport { pack_counts }
+    }
+
+    pub fn minimum(&self) -> usize {
+        self.pack_counts.first().copied().unwrap_or_default()
+    }
+
+    pub fn maximum(&self) -> usize {
+        self.pack_counts.last().copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.pack_counts.is_empty() {
+            return 0.0;
+        }
+
+        self.pack_counts.iter().sum::<usize>() as f64 / self.pack_counts.len() as f64
+    }
+
+    /// Returns the population standard deviation of the numbers of packs.
+    pub fn standard_deviation(&self) -> f64 {
+        if self.pack_counts.is_empty() {
+            return 0.0;
+        }
+
+        let mean = self.mean();
+        let variance = self
+            .pack_counts
+            .iter()
+            .map(|&c| (c as f64 - mean).powi(2))
+            .sum::<f64>()
+            / self.pack_counts.len() as f64;
+        variance.sqrt()
+    }
+
+    /// Returns the number of packs that at least the given percentage of the runs didn't exceed, by the
+    /// nearest-rank method.
+    pub fn percentile(&self, percentage: f64) -> usize {
+        if self.pack_counts.is_empty() {
+            return 0;
+        }
+
+        let rank = (percentage / 100.0 * self.pack_counts.len() as f64).ceil() as usize;
+        self.pack_counts[rank.clamp(1, self.pack_counts.len()) - 1]
+    }
+
+    /// Returns each number of packs with the number of runs that needed that number of packs, from the fewest
+    /// to the most packs.
+    pub fn histogram(&self) -> Vec<(usize, usize)> {
+        let mut histogram: Vec<(usize, usize)> = Vec::new();
+        for &count in &self.pack_counts {
+            match histogram.last_mut() {
+                Some((packs, runs)) if *packs == count => *runs += 1,
+                _ => histogram.push((count, 1)),
+            }
+        }
+
+        histogram
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Runs: {}", self.pack_counts.len())?;
+        writeln!(
+            f,
+            "Packs: Minimum: {}, Maximum: {}, Mean: {:.2}, Standard Deviation: {:.2}",
+            self.minimum(),
+            self.maximum(),
+            self.mean(),
+            self.standard_deviation()
+        )?;
+        writeln!(
+            f,
+            "Percentiles: P50: {}, P90: {}, P95: {}",
+            self.percentile(50.0),
+            self.percentile(90.0),
+            self.percentile(95.0)
+        )?;
+        for (packs, runs) in self.histogram() {
+            writeln!(f, "Packs: {packs}, Runs: {runs}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the items of a single run of the simulation.
+fn sample(
+    item_templates: &[ItemTemplate],
+    mode: SampleMode,
+    random: &mut Random,
+) -> Vec<ItemTemplate> {
+    match mode {
+        SampleMode::Subset => (0..item_templates.len())
+            .map(|_| item_templates[random.below(item_templates.len())].clone())
+            .collect(),
+        SampleMode::Permutation => {
+            let mut items = item_templates.to_vec();
+            random.shuffle(&mut items);
+            items
+        }
+    }
+}
+
+/// Plans the items in the same way as [`crate::plan_items`], without writing the plan, and returns the number
+/// of packs.
+fn count_packs(
+    pack_template: &PackTemplate,
+    item_templates: Vec<ItemTemplate>,
+    configuration: &Configuration,
+) -> Result<usize, Error> {
+    let (pack_template, items, _, _) = prepare_items(pack_template, item_templates, configuration)?;
+    let rules = &configuration.rules;
+    let (items, infeasible_items) = match configuration.on_infeasible {
+        InfeasibleItemPolicy::Error => (items, Vec::new()),
+        _ => separate_infeasible_items(items, &pack_template, rules),
+    };
+    let items = if configuration.prefer_homogeneous {
+        prefer_homogeneous_packs(items, &pack_template, rules)
+    } else {
+        items
+    };
+    let algorithm: &dyn PackingAlgorithm = match pack_template.sheet {
+        Some(_) => &StripAlgorithm,
+        None => configuration.algorithm.as_ref(),
+    };
+    let outcome = algorithm.plan(&items, &pack_template, rules)?;
+    let outcome = place_infeasible_items(
+        outcome,
+        &infeasible_items,
+        &pack_template,
+        configuration.on_infeasible,
+    )?;
+
+    Ok(outcome.packs.len())
+}
+
+/// Plans a random sample of the items for each run of the simulation, and reports the distribution of the
+/// numbers of packs. The plans aren't written, and the settings of the configuration that only apply to
+/// the output, e.g. the pack sequence and the statistics file, are ignored.
+///
+/// Returns an error if one of the samples can't be planned.
+pub fn simulate(
+    pack_template: &PackTemplate,
+    item_templates: &[ItemTemplate],
+    configuration: &Configuration,
+    simulation: &Simulation,
+) -> Result<SimulationReport, Error> {
+    let mut random = Random::new(simulation.seed);
+    let mut pack_counts = Vec::with_capacity(simulation.runs);
+    for _ in 0..simulation.runs {
+        let items = sample(item_templates, simulation.mode, &mut random);
+        pack_counts.push(count_packs(pack_template, items, configuration)?);
+    }
+
+    Ok(SimulationReport::new(pack_counts))
+}
@@ -0,0 +1,124 @@
+use std::str::FromStr;
+
+use super::*;
+
+// SampleMode
+
+#[test]
+fn when_parsing_a_sample_mode_it_should_accept_the_known_modes() {
+    assert_eq!(SampleMode::from_str("subset").unwrap(), SampleMode::Subset);
+    assert_eq!(
+        SampleMode::from_str("permutation").unwrap(),
+        SampleMode::Permutation
+    );
+    assert!(SampleMode::from_str("all").is_err());
+}
+
+// SimulationReport
+
+#[test]
+fn when_reporting_the_pack_counts_it_should_return_the_statistics_of_the_distribution() {
+    let report = SimulationReport::new(vec![5, 3, 4, 4, 6, 4, 5, 3, 4, 7]);
+
+    assert_eq!(report.minimum(), 3);
+    assert_eq!(report.maximum(), 7);
+    assert_eq!(report.mean(), 4.5);
+    assert!((report.standard_deviation() - 1.204).abs() < 0.001);
+    assert_eq!(report.percentile(50.0), 4);
+    assert_eq!(report.percentile(90.0), 6);
+    assert_eq!(report.percentile(95.0), 7);
+    assert_eq!(
+        report.histogram(),
+        vec![(3, 2), (4, 4), (5, 2), (6, 1), (7, 1)]
+    );
+}
+
+#[test]
+fn when_writing_a_report_it_should_list_the_statistics_and_the_runs_per_pack_count() {
+    let report = SimulationReport::new(vec![2, 3, 2, 2]);
+
+    assert_eq!(
+        report.to_string(),
+        "Runs: 4\n\
+         Packs: Minimum: 2, Maximum: 3, Mean: 2.25, Standard Deviation: 0.43\n\
+         Percentiles: P50: 2, P90: 3, P95: 3\n\
+         Packs: 2, Runs: 3\n\
+         Packs: 3, Runs: 1\n"
+    );
+}
+
+#[test]
+fn when_reporting_no_runs_it_should_return_zero_statistics() {
+    let report = SimulationReport::new(Vec::new());
+
+    assert_eq!(report.minimum(), 0);
+    assert_eq!(report.mean(), 0.0);
+    assert_eq!(report.percentile(50.0), 0);
+    assert!(report.histogram().is_empty());
+}
+
+// simulate()
+
+#[test]
+fn when_simulating_permutations_it_should_plan_all_the_items_in_each_run() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: crate::model::PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,100,6,1.0").unwrap(),
+        ItemTemplate::from_str("2,100,6,1.0").unwrap(),
+        ItemTemplate::from_str("3,100,8,1.0").unwrap(),
+    ];
+    let simulation = Simulation {
+        runs: 20,
+        mode: SampleMode::Permutation,
+        seed: 7,
+    };
+
+    let report = simulate(
+        &pack_template,
+        &items,
+        &Configuration::default(),
+        &simulation,
+    )
+    .unwrap();
+
+    assert_eq!(report.pack_counts.len(), 20);
+    assert_eq!(report.minimum(), 2);
+    assert_eq!(report.maximum(), 2);
+}
+
+#[test]
+fn when_simulating_subsets_with_the_same_seed_it_should_return_the_same_report() {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        sort_order: crate::model::PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = vec![
+        ItemTemplate::from_str("1,100,2,1.0").unwrap(),
+        ItemTemplate::from_str("2,100,9,1.0").unwrap(),
+        ItemTemplate::from_str("3,100,15,1.0").unwrap(),
+    ];
+    let simulation = Simulation {
+        runs: 50,
+        mode: SampleMode::Subset,
+        seed: 11,
+    };
+    let configuration = Configuration::default();
+
+    let report = simulate(&pack_template, &items, &configuration, &simulation).unwrap();
+
+    assert_eq!(
+        report,
+        simulate(&pack_template, &items, &configuration, &simulation).unwrap()
+    );
+    // The smallest sample holds three batches of 2 items, and the largest three batches of 15 items.
+    assert!(report.minimum() >= 1);
+    assert!(report.maximum() <= 6);
+    assert!(report.minimum() < report.maximum());
+}
@@ -0,0 +1,87 @@
+//! Writing each pack of a plan to its own file, e.g. for a label printer that prints one manifest per carton.
+
+use std::path::{Path, PathBuf};
+
+use crate::compression::{ExistingOutput, OutputFile};
+use crate::errors::Error;
+use crate::model::Pack;
+use crate::render::{write_plan, OutputFormat, RenderOptions};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "split_tests.rs"]
+mod tests;
+
+/// The directory to which each pack is written as a separate file, named after the number of the pack, e.g.
+/// `pack-12.json`. The directory is created if it doesn't exist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitOutput {
+    /// The directory that contains the files of the packs.
+    pub directory: PathBuf,
+
+    /// Defines what happens when the file of a pack already exists.
+    pub existing_output: ExistingOutput,
+}
+
+impl SplitOutput {
+    /// Creates a split output that writes the packs to the given directory, and replaces existing files.
+    pub fn new(directory: impl Into<PathBuf>) -> SplitOutput {
+        SplitOutput {
+            directory: directory.into(),
+            existing_output: ExistingOutput::Overwrite,
+        }
+    }
+
+    /// Returns the path of the file of the pack with the given number.
+    pub fn path_of(&self, pack_number: usize, format: OutputFormat) -> PathBuf {
+        let extension = match format {
+            OutputFormat::Text | OutputFormat::Worksheet => "txt",
+            OutputFormat::Json => "json",
+            OutputFormat::Markdown => "md",
+            OutputFormat::Cbor => "cbor",
+            OutputFormat::Arrow => "arrow",
+            OutputFormat::Parquet => "parquet",
+        };
+
+        self.directory
+            .join(format!("pack-{pack_number}.{extension}"))
+    }
+
+    /// Writes each pack to its own file, in the format of the render options. The files are never colored.
+    pub fn write(&self, packs: &[Pack], render_options: &RenderOptions) -> Result<(), Error> {
+        std::fs::create_dir_all(&self.directory).map_err(|e| Error::UnableToWriteOutput {
+            reason: format!("{}: {}", self.directory.display(), e),
+        })?;
+
+        let render_options = RenderOptions {
+            color: false,
+            ..render_options.clone()
+        };
+        for pack in packs {
+            let path = self.path_of(pack.number, render_options.format);
+            write_pack(&path, pack, &render_options, self.existing_output)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_pack(
+    path: &Path,
+    pack: &Pack,
+    render_options: &RenderOptions,
+    existing_output: ExistingOutput,
+) -> Result<(), Error> {
+    let to_write_error = |e: std::io::Error| Error::UnableToWriteOutput {
+        reason: format!("{}: {}", path.display(), e),
+    };
+
+    let mut output = OutputFile::create_with(path, existing_output)?;
+    write_plan(
+        &mut output,
+        std::slice::from_ref(pack),
+        None,
+        render_options,
+    )
+    .map_err(to_write_error)?;
+    output.finish().map_err(to_write_error)
+}
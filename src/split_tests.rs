@@ -0,0 +1,69 @@
+use super::*;
+
+use crate::test_support::create_test_directory;
+
+// SplitOutput::path_of()
+
+#[test]
+fn when_naming_the_file_of_a_pack_it_should_use_the_pack_number_and_the_format() {
+    let split_output = SplitOutput::new("labels");
+
+    assert_eq!(
+        split_output.path_of(12, OutputFormat::Text),
+        Path::new("labels").join("pack-12.txt")
+    );
+    assert_eq!(
+        split_output.path_of(3, OutputFormat::Json),
+        Path::new("labels").join("pack-3.json")
+    );
+    assert_eq!(
+        split_output.path_of(3, OutputFormat::Markdown),
+        Path::new("labels").join("pack-3.md")
+    );
+}
+
+// SplitOutput::write()
+
+#[test]
+fn when_writing_the_packs_it_should_write_a_file_per_pack() {
+    let directory = create_test_directory("split-write");
+    let split_output = SplitOutput::new(directory.join("labels"));
+    let mut second_pack = Pack::new(2);
+    second_pack.weight = 12.0;
+    let render_options = RenderOptions {
+        color: true,
+        ..RenderOptions::default()
+    };
+
+    split_output
+        .write(&[Pack::new(1), second_pack], &render_options)
+        .unwrap();
+
+    let text = std::fs::read_to_string(directory.join("labels").join("pack-2.txt")).unwrap();
+    assert!(text.starts_with("Pack Number: 2\n"));
+    assert!(text.contains("Pack Weight: 12.0"));
+    assert!(!text.contains('\x1b'));
+    assert!(directory.join("labels").join("pack-1.txt").exists());
+    assert_eq!(
+        std::fs::read_dir(directory.join("labels")).unwrap().count(),
+        2
+    );
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_keeping_existing_files_it_should_return_an_error_for_an_existing_pack_file() {
+    let directory = create_test_directory("split-no-clobber");
+    std::fs::write(directory.join("pack-1.txt"), "Pack Number: 1\n").unwrap();
+    let split_output = SplitOutput {
+        existing_output: ExistingOutput::Keep,
+        ..SplitOutput::new(&directory)
+    };
+
+    let result = split_output.write(&[Pack::new(1)], &RenderOptions::default());
+
+    assert!(matches!(result, Err(Error::OutputFileExists { .. })));
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
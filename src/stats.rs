@@ -0,0 +1,184 @@
+//! Statistics of the plans, appended to a file with one JSON document per line, so that the quality of the
+//! plans, e.g. how full the packs are, can be followed over months without a separate database.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::errors::Error;
+use crate::model::{
+    DuplicateIdPolicy, InfeasibleItemPolicy, PackSortOrder, PackTemplate, PlanOutcome,
+};
+use crate::scoring::{score_plan, UtilizationVariance};
+use crate::Configuration;
+
+#[cfg(test)]
+#[path = "stats_tests.rs"]
+mod tests;
+
+/// The pack limits and the settings with which a plan was made.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlanParameters {
+    pub sort_order: PackSortOrder,
+    pub maximum_number_of_pieces: i32,
+    pub maximum_weight: f64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hard_maximum_weight: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum_length: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum_value: Option<f64>,
+
+    /// The number of pack rules.
+    pub number_of_rules: usize,
+
+    pub keep_kits_together: bool,
+    pub prefer_homogeneous: bool,
+    pub on_infeasible: InfeasibleItemPolicy,
+    pub duplicate_ids: DuplicateIdPolicy,
+}
+
+/// Describes a single plan and its quality.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlanStatistics {
+    /// The time at which the plan was made, in seconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// The time it took to make the plan, in seconds.
+    pub duration: f64,
+
+    pub parameters: PlanParameters,
+
+    /// The number of batches of items that were planned, after the kits and the substitution groups were
+    /// replaced.
+    pub number_of_batches: usize,
+
+    /// The number of pieces in the packs. A placement of bulk counts as a single piece.
+    pub number_of_items: i64,
+
+    pub number_of_packs: usize,
+    pub total_weight: f64,
+
+    /// The mean fraction of the maximum weight that is used by the packs.
+    pub weight_fill_rate: f64,
+
+    /// The mean fraction of the maximum number of pieces that is used by the packs.
+    pub piece_fill_rate: f64,
+
+    /// The variance of the fraction of the maximum weight that is used by each pack.
+    pub utilization_variance: f64,
+
+    pub number_of_warnings: usize,
+}
+
+impl PlanStatistics {
+    /// Describes the plan that was made with the pack template and the configuration, from 'number_of_batches'
+    /// batches of items, in the given time.
+    pub fn new(
+        pack_template: &PackTemplate,
+        configuration: &Configuration,
+        number_of_batches: usize,
+        outcome: &PlanOutcome,
+        duration: Duration,
+    ) -> PlanStatistics {
+        let number_of_packs = outcome.packs.len();
+        let number_of_items: i64 = outcome.packs.iter().map(|p| p.item_count as i64).sum();
+        let total_weight: f64 = outcome.packs.iter().map(|p| p.weight).sum();
+        let fill_rate = |total: f64, maximum: f64| {
+            if number_of_packs == 0 || maximum <= 0.0 {
+                0.0
+            } else {
+                total / (maximum * number_of_packs as f64)
+            }
+        };
+
+        PlanStatistics {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            duration: duration.as_secs_f64(),
+            parameters: PlanParameters {
+                sort_order: pack_template.sort_order,
+                maximum_number_of_pieces: pack_template.maximum_number_of_pieces,
+                maximum_weight: pack_template.maximum_weight,
+                hard_maximum_weight: pack_template.hard_maximum_weight,
+                maximum_length: pack_template.maximum_length,
+                maximum_value: pack_template.maximum_value,
+                number_of_rules: configuration.rules.len(),
+                keep_kits_together: configuration.keep_kits_together,
+                prefer_homogeneous: configuration.prefer_homogeneous,
+                on_infeasible: configuration.on_infeasible,
+                duplicate_ids: configuration.duplicate_ids,
+            },
+            number_of_batches,
+            number_of_items,
+            number_of_packs,
+            total_weight,
+            weight_fill_rate: fill_rate(total_weight, pack_template.maximum_weight),
+            piece_fill_rate: fill_rate(
+                number_of_items as f64,
+                pack_template.maximum_number_of_pieces as f64,
+            ),
+            utilization_variance: score_plan(
+                outcome,
+                &UtilizationVariance {
+                    maximum_weight: pack_template.maximum_weight,
+                },
+            ),
+            number_of_warnings: outcome.warnings.len(),
+        }
+    }
+}
+
+/// The file to which the statistics of each plan are appended, as a single line of JSON. The file is created
+/// if it doesn't exist, and is locked while a line is appended, so plans that run at the same time never
+/// write their lines into each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatisticsFile {
+    /// The path of the statistics file.
+    pub path: PathBuf,
+}
+
+impl StatisticsFile {
+    /// Creates a statistics file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> StatisticsFile {
+        StatisticsFile { path: path.into() }
+    }
+
+    /// Appends the statistics of a plan to the file.
+    pub fn append(&self, statistics: &PlanStatistics) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(statistics).map_err(|e| self.write_error(e))?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)
+            .map_err(|e| Error::UnableToOpenFile {
+                path: display_path(&self.path),
+                reason: e.to_string(),
+            })?;
+        file.lock().map_err(|e| self.write_error(e))?;
+
+        // The lock is released when the file is closed.
+        file.write_all(&line).map_err(|e| self.write_error(e))
+    }
+
+    fn write_error(&self, e: impl std::fmt::Display) -> Error {
+        Error::UnableToWriteOutput {
+            reason: format!("{}: {}", display_path(&self.path), e),
+        }
+    }
+}
+
+fn display_path(path: &Path) -> String {
+    path.display().to_string()
+}
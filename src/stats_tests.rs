@@ -0,0 +1,136 @@
+use super::*;
+use crate::model::{Pack, PlanWarning};
+
+fn create_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 100.0,
+        ..PackTemplate::new()
+    }
+}
+
+fn create_plan(packs: &[(i32, f64)]) -> PlanOutcome {
+    PlanOutcome {
+        packs: packs
+            .iter()
+            .enumerate()
+            .map(|(index, (item_count, weight))| Pack {
+                item_count: *item_count,
+                weight: *weight,
+                ..Pack::new(index + 1)
+            })
+            .collect(),
+        warnings: Vec::new(),
+        optimality: None,
+    }
+}
+
+/// Returns the path of a statistics file, unique to the test, that doesn't exist yet.
+fn create_statistics_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-stats-{}-{}.jsonl",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// PlanStatistics::new()
+
+#[test]
+fn when_describing_a_plan_it_should_calculate_the_fill_rates() {
+    let mut outcome = create_plan(&[(10, 80.0), (5, 40.0)]);
+    outcome.warnings.push(PlanWarning::ExactFitNotReached {
+        minimum_number_of_packs: 1,
+        number_of_packs: 2,
+    });
+    let configuration = Configuration {
+        keep_kits_together: true,
+        ..Configuration::default()
+    };
+
+    let statistics = PlanStatistics::new(
+        &create_pack_template(),
+        &configuration,
+        3,
+        &outcome,
+        Duration::from_millis(250),
+    );
+
+    assert_eq!(statistics.duration, 0.25);
+    assert_eq!(statistics.number_of_batches, 3);
+    assert_eq!(statistics.number_of_items, 15);
+    assert_eq!(statistics.number_of_packs, 2);
+    assert_eq!(statistics.total_weight, 120.0);
+    assert_eq!(statistics.weight_fill_rate, 0.6);
+    assert_eq!(statistics.piece_fill_rate, 0.75);
+    assert!((statistics.utilization_variance - 0.04).abs() < 1e-9);
+    assert_eq!(statistics.number_of_warnings, 1);
+    assert_eq!(statistics.parameters.maximum_number_of_pieces, 10);
+    assert!(statistics.parameters.keep_kits_together);
+    assert!(statistics.timestamp > 0);
+}
+
+#[test]
+fn when_describing_a_plan_without_packs_it_should_have_no_fill() {
+    let statistics = PlanStatistics::new(
+        &create_pack_template(),
+        &Configuration::default(),
+        0,
+        &PlanOutcome::default(),
+        Duration::ZERO,
+    );
+
+    assert_eq!(statistics.weight_fill_rate, 0.0);
+    assert_eq!(statistics.piece_fill_rate, 0.0);
+}
+
+// StatisticsFile::append()
+
+#[test]
+fn when_appending_statistics_it_should_write_a_line_per_plan() {
+    let path = create_statistics_path("append");
+    let file = StatisticsFile::new(&path);
+    let statistics = PlanStatistics::new(
+        &create_pack_template(),
+        &Configuration::default(),
+        1,
+        &create_plan(&[(4, 50.0)]),
+        Duration::ZERO,
+    );
+
+    file.append(&statistics).unwrap();
+    file.append(&statistics).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(contents.ends_with("}\n"));
+    let line: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(line["number_of_packs"], 1);
+    assert_eq!(line["weight_fill_rate"], 0.5);
+    assert_eq!(line["parameters"]["sort_order"], "NOT_SET");
+    assert_eq!(line["parameters"]["on_infeasible"], "error");
+    assert!(line["parameters"].get("hard_maximum_weight").is_none());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn when_appending_to_a_file_in_a_missing_directory_it_should_return_an_error() {
+    let path = create_statistics_path("missing").join("stats.jsonl");
+    let file = StatisticsFile::new(&path);
+    let statistics = PlanStatistics::new(
+        &create_pack_template(),
+        &Configuration::default(),
+        0,
+        &PlanOutcome::default(),
+        Duration::ZERO,
+    );
+
+    assert!(matches!(
+        file.append(&statistics),
+        Err(Error::UnableToOpenFile { .. })
+    ));
+}
@@ -0,0 +1,430 @@
+//! Reading and writing objects in Amazon S3 and Google Cloud Storage, addressed by `s3://[bucket]/[key]` and
+//! `gs://[bucket]/[key]` URLs, so that plans can be made from, and written to, a bucket without separate
+//! download and upload steps.
+//!
+//! The requests are signed with AWS signature version 4. Google Cloud Storage is reached through its
+//! S3-compatible XML API, which accepts the same signatures when it is used with HMAC keys. The object store
+//! support depends on the `object-store` feature.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+use crate::errors::Error;
+
+#[cfg(test)]
+#[path = "storage_tests.rs"]
+mod tests;
+
+// The algorithm with which the requests are signed
+const SIGNING_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+// The last part of the scope of a signature
+const SIGNING_SCOPE_TERMINATOR: &str = "aws4_request";
+
+// The name of the service in the scope of a signature, for both S3 and Cloud Storage
+const SIGNING_SERVICE: &str = "s3";
+
+// The region of the S3 buckets when no region is configured
+const DEFAULT_S3_REGION: &str = "us-east-1";
+
+// The endpoint of the S3-compatible XML API of Cloud Storage, which accepts any region
+const GCS_ENDPOINT: &str = "https://storage.googleapis.com";
+const GCS_REGION: &str = "auto";
+
+// The block size of SHA-256, which is the size of the keys of the HMAC
+const HMAC_BLOCK_SIZE: usize = 64;
+
+/// Defines the object stores that can be addressed by a URL.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ObjectStoreService {
+    /// Amazon S3, or a compatible service, addressed by `s3://` URLs.
+    S3,
+
+    /// Google Cloud Storage, addressed by `gs://` URLs.
+    Gcs,
+}
+
+impl ObjectStoreService {
+    fn scheme(&self) -> &'static str {
+        match self {
+            ObjectStoreService::S3 => "s3://",
+            ObjectStoreService::Gcs => "gs://",
+        }
+    }
+}
+
+/// Returns `true` if the path refers to an object in an object store instead of a local file.
+pub fn is_object_url(path: &str) -> bool {
+    [ObjectStoreService::S3, ObjectStoreService::Gcs]
+        .iter()
+        .any(|s| path.starts_with(s.scheme()))
+}
+
+/// Identifies an object in an object store, e.g. `s3://plans/2024/05/14/plan.txt`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectUrl {
+    pub service: ObjectStoreService,
+    pub bucket: String,
+
+    /// The key of the object, without the leading '/'.
+    pub key: String,
+}
+
+impl FromStr for ObjectUrl {
+    type Err = Error;
+
+    /// Parses a URL formatted as `s3://[bucket]/[key]` or `gs://[bucket]/[key]`.
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        let to_error = || Error::InvalidObjectUrl {
+            url: url.to_string(),
+        };
+
+        let (service, location) = [ObjectStoreService::S3, ObjectStoreService::Gcs]
+            .into_iter()
+            .find_map(|s| url.strip_prefix(s.scheme()).map(|l| (s, l)))
+            .ok_or_else(to_error)?;
+        let (bucket, key) = location.split_once('/').ok_or_else(to_error)?;
+        if bucket.is_empty() || key.is_empty() || key.ends_with('/') {
+            return Err(to_error());
+        }
+
+        Ok(ObjectUrl {
+            service,
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for ObjectUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}/{}", self.service.scheme(), self.bucket, self.key)
+    }
+}
+
+/// The HMAC key with which the requests to the object store are signed.
+#[derive(Clone, PartialEq)]
+pub struct ObjectStoreCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+
+    /// The token of temporary credentials, if the credentials are temporary.
+    pub session_token: Option<String>,
+}
+
+impl fmt::Debug for ObjectStoreCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The secret never ends up in a log.
+        f.debug_struct("ObjectStoreCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The object store in which the objects of a service are read and written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectStore {
+    /// The URL of the service, e.g. `http://localhost:9000`, if the objects are addressed by their path, i.e.
+    /// `[endpoint]/[bucket]/[key]`. Without an endpoint the objects are addressed by the S3 host of the
+    /// bucket, i.e. `https://[bucket].s3.[region].amazonaws.com/[key]`, where the region is left out for
+    /// the default region.
+    pub endpoint: Option<String>,
+
+    /// The region of the buckets.
+    pub region: String,
+
+    pub credentials: ObjectStoreCredentials,
+}
+
+/// Returns the value of the environment variable, if it is set and not empty.
+fn environment_variable(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+impl ObjectStore {
+    /// Creates the object store of the service from the environment. For S3 the credentials are read from
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and the optional `AWS_SESSION_TOKEN`, the region from
+    /// `AWS_REGION` or `AWS_DEFAULT_REGION`, and the optional endpoint of a compatible service from
+    /// `AWS_ENDPOINT_URL`. For Cloud Storage the HMAC key is read from `GS_ACCESS_KEY_ID` and
+    /// `GS_SECRET_ACCESS_KEY`.
+    pub fn from_env(service: ObjectStoreService) -> Result<ObjectStore, Error> {
+        let (access_key_id_variable, secret_access_key_variable) = match service {
+            ObjectStoreService::S3 => ("AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"),
+            ObjectStoreService::Gcs => ("GS_ACCESS_KEY_ID", "GS_SECRET_ACCESS_KEY"),
+        };
+        let required = |name: &str| {
+            environment_variable(name).ok_or_else(|| Error::MissingObjectStoreCredentials {
+                variable: name.to_string(),
+            })
+        };
+        let credentials = ObjectStoreCredentials {
+            access_key_id: required(access_key_id_variable)?,
+            secret_access_key: required(secret_access_key_variable)?,
+            session_token: match service {
+                ObjectStoreService::S3 => environment_variable("AWS_SESSION_TOKEN"),
+                ObjectStoreService::Gcs => None,
+            },
+        };
+
+        Ok(match service {
+            ObjectStoreService::S3 => ObjectStore {
+                endpoint: environment_variable("AWS_ENDPOINT_URL"),
+                region: environment_variable("AWS_REGION")
+                    .or_else(|| environment_variable("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|| DEFAULT_S3_REGION.to_string()),
+                credentials,
+            },
+            ObjectStoreService::Gcs => ObjectStore {
+                endpoint: Some(GCS_ENDPOINT.to_string()),
+                region: GCS_REGION.to_string(),
+                credentials,
+            },
+        })
+    }
+
+    /// Returns the HTTP URL of the object, the host to which the request is sent and the path of the object
+    /// on that host.
+    fn location(&self, url: &ObjectUrl) -> (String, String, String) {
+        let key = uri_encode(&url.key, false);
+        match &self.endpoint {
+            Some(endpoint) => {
+                let endpoint = endpoint.trim_end_matches('/');
+                let host = endpoint
+                    .split_once("://")
+                    .map_or(endpoint, |(_, host)| host)
+                    .to_string();
+                let path = format!("/{}/{}", uri_encode(&url.bucket, true), key);
+                (format!("{endpoint}{path}"), host, path)
+            }
+            // The buckets in the default region are also reached without the region in the host.
+            None if self.region == DEFAULT_S3_REGION => {
+                let host = format!("{}.s3.amazonaws.com", url.bucket);
+                let path = format!("/{key}");
+                (format!("https://{host}{path}"), host, path)
+            }
+            None => {
+                let host = format!("{}.s3.{}.amazonaws.com", url.bucket, self.region);
+                let path = format!("/{key}");
+                (format!("https://{host}{path}"), host, path)
+            }
+        }
+    }
+
+    /// Returns the headers of a signed request for the object, including the `Authorization` header, for a
+    /// request with the given method and body that is made at the given time. The 'extra_headers' are signed
+    /// as well, and are returned with the other headers.
+    pub fn signed_headers(
+        &self,
+        method: &str,
+        url: &ObjectUrl,
+        body: &[u8],
+        extra_headers: &[(&str, &str)],
+        time: SystemTime,
+    ) -> Vec<(String, String)> {
+        let (_, host, path) = self.location(url);
+        let timestamp = format_timestamp(time);
+        let date = &timestamp[..8];
+        let payload_hash = hex(&Sha256::digest(body));
+
+        let mut headers: Vec<(String, String)> = vec![
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), timestamp.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        headers.extend(
+            extra_headers
+                .iter()
+                .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string())),
+        );
+        headers.sort();
+
+        let signed_header_names = headers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{value}\n"))
+            .collect();
+        let canonical_request = format!(
+            "{method}\n{path}\n\n{canonical_headers}\n{signed_header_names}\n{payload_hash}"
+        );
+
+        let scope = format!(
+            "{date}/{}/{SIGNING_SERVICE}/{SIGNING_SCOPE_TERMINATOR}",
+            self.region
+        );
+        let string_to_sign = format!(
+            "{SIGNING_ALGORITHM}\n{timestamp}\n{scope}\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac_sha256(
+            format!("AWS4{}", self.credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [
+            self.region.as_str(),
+            SIGNING_SERVICE,
+            SIGNING_SCOPE_TERMINATOR,
+        ] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        headers.push((
+            "authorization".to_string(),
+            format!(
+                "{SIGNING_ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_header_names}, Signature={signature}",
+                self.credentials.access_key_id
+            ),
+        ));
+
+        // The HTTP client sets the host from the URL.
+        headers.retain(|(name, _)| name != "host");
+        headers
+    }
+
+    /// Reads the object.
+    #[cfg(feature = "object-store")]
+    pub fn get(&self, url: &ObjectUrl) -> Result<Vec<u8>, Error> {
+        use std::io::Read;
+
+        let response = self
+            .request("GET", url, &[])
+            .call()
+            .map_err(|e| crate::remote::to_request_error(&url.to_string(), e))?;
+
+        let mut data = Vec::new();
+        crate::input::InputReader::new(response.into_reader(), &url.to_string())
+            .read_to_end(&mut data)
+            .map_err(crate::input::read_error)?;
+        Ok(data)
+    }
+
+    /// Writes the object, replacing the object if it exists.
+    #[cfg(feature = "object-store")]
+    pub fn put(&self, url: &ObjectUrl, data: &[u8]) -> Result<(), Error> {
+        self.request("PUT", url, data)
+            .send_bytes(data)
+            .map_err(|e| crate::remote::to_request_error(&url.to_string(), e))?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "object-store")]
+    fn request(&self, method: &str, url: &ObjectUrl, body: &[u8]) -> ureq::Request {
+        let (http_url, _, _) = self.location(url);
+        self.signed_headers(method, url, body, &[], SystemTime::now())
+            .iter()
+            .fold(
+                ureq::request(method, &http_url),
+                |request, (name, value)| request.set(name, value),
+            )
+    }
+}
+
+/// Reads the object at the URL, with the credentials from the environment.
+#[cfg(feature = "object-store")]
+pub fn read_object(url: &str) -> Result<Vec<u8>, Error> {
+    let url = ObjectUrl::from_str(url)?;
+    ObjectStore::from_env(url.service)?.get(&url)
+}
+
+#[cfg(not(feature = "object-store"))]
+pub fn read_object(url: &str) -> Result<Vec<u8>, Error> {
+    Err(Error::UnsupportedObjectStore {
+        url: url.to_string(),
+    })
+}
+
+/// Writes the object at the URL, with the credentials from the environment.
+#[cfg(feature = "object-store")]
+pub fn write_object(url: &str, data: &[u8]) -> Result<(), Error> {
+    let url = ObjectUrl::from_str(url)?;
+    ObjectStore::from_env(url.service)?.put(&url, data)
+}
+
+#[cfg(not(feature = "object-store"))]
+pub fn write_object(url: &str, _data: &[u8]) -> Result<(), Error> {
+    Err(Error::UnsupportedObjectStore {
+        url: url.to_string(),
+    })
+}
+
+/// Encodes every byte of the value except the unreserved characters, and the '/' if 'encode_slash' is
+/// `false`, as required for the path of a signed request.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+/// Returns the bytes as a lowercase hexadecimal string.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Returns the HMAC-SHA256 of the data with the given key.
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut block = [0u8; HMAC_BLOCK_SIZE];
+    if key.len() > HMAC_BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().to_vec()
+}
+
+/// Formats the time as the UTC timestamp of a signature, e.g. `20240514T093000Z`.
+fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // The civil date of the number of days since 1970-01-01, in the proleptic Gregorian calendar.
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
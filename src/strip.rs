@@ -0,0 +1,218 @@
+//! A packing algorithm for flat items, e.g. cut sheets and panels, which lays out the items on sheets with
+//! a shelf heuristic instead of only counting their length.
+
+use crate::errors::Error;
+use crate::model::{
+    ItemTemplate, Pack, PackRule, PackTemplate, PlacementLimit, PlanOutcome, PlanWarning,
+    SheetPlacement, SheetSize,
+};
+use crate::planner::{number_of_items_to_place, plan_packs, PackingAlgorithm};
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "strip_tests.rs"]
+mod tests;
+
+/// The algorithm that lays out flat items on sheets. See [`plan_sheets`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StripAlgorithm;
+
+impl PackingAlgorithm for StripAlgorithm {
+    fn plan(
+        &self,
+        items: &[ItemTemplate],
+        pack_template: &PackTemplate,
+        rules: &[PackRule],
+    ) -> Result<PlanOutcome, Error> {
+        plan_sheets(items, pack_template, rules)
+    }
+}
+
+/// Describes the orientation of an item on a sheet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Orientation {
+    /// The size of the item along the length of the sheet.
+    pub length: f64,
+
+    /// The size of the item along the width of the sheet.
+    pub width: f64,
+
+    /// Indicates if the item is turned a quarter.
+    pub rotated: bool,
+}
+
+/// Returns the orientation in which the item takes up the least width of the sheet, or `None` if the item
+/// doesn't fit on the sheet in either orientation.
+pub(crate) fn orient_on_sheet(length: f64, width: f64, sheet: &SheetSize) -> Option<Orientation> {
+    let fits = |o: &Orientation| o.length <= sheet.length && o.width <= sheet.width;
+    let as_provided = Orientation {
+        length,
+        width,
+        rotated: false,
+    };
+    let rotated = Orientation {
+        length: width,
+        width: length,
+        rotated: true,
+    };
+
+    match (fits(&as_provided), fits(&rotated)) {
+        (true, true) if rotated.width < as_provided.width => Some(rotated),
+        (true, _) => Some(as_provided),
+        (false, true) => Some(rotated),
+        (false, false) => None,
+    }
+}
+
+/// A row of items along the length of the sheet. The height of the shelf is the width of the first item on
+/// the shelf.
+struct Shelf {
+    y: f64,
+    height: f64,
+    used_length: f64,
+}
+
+/// Finds the position for an item on the sheet. The item is placed on the first shelf that has space for it,
+/// or on a new shelf above the existing shelves. Returns `None` if the sheet has no space for the item.
+fn find_position(
+    shelves: &mut Vec<Shelf>,
+    orientation: &Orientation,
+    sheet: &SheetSize,
+) -> Option<(f64, f64)> {
+    if let Some(shelf) = shelves.iter_mut().find(|s| {
+        orientation.width <= s.height && s.used_length + orientation.length <= sheet.length
+    }) {
+        let position = (shelf.used_length, shelf.y);
+        shelf.used_length += orientation.length;
+        return Some(position);
+    }
+
+    let y = shelves.last().map_or(0.0, |s| s.y + s.height);
+    if y + orientation.width > sheet.width {
+        return None;
+    }
+
+    shelves.push(Shelf {
+        y,
+        height: orientation.width,
+        used_length: orientation.length,
+    });
+    Some((0.0, y))
+}
+
+/// Lays out the given items on sheets with a first-fit shelf heuristic, if the pack template has a sheet
+/// size. Otherwise the items are placed with [`plan_packs`]. Each pack is a single sheet, on which the items
+/// are placed in rows, called shelves, along the length of the sheet. The widest items are placed first, and
+/// each item is turned a quarter if that takes up less width of the sheet. The items still have to fit in
+/// the weight, the piece count and the other limits of the pack, and the rules. Batches without any items
+/// are skipped, which is reported as a warning.
+///
+/// Returns an error if an item doesn't have a width, doesn't fit on an empty sheet, or is a bulk item or an
+/// item that is pinned to a pack.
+pub fn plan_sheets(
+    items: &[ItemTemplate],
+    pack_template: &PackTemplate,
+    rules: &[PackRule],
+) -> Result<PlanOutcome, Error> {
+    let sheet = match &pack_template.sheet {
+        Some(s) => s,
+        None => return plan_packs(items, pack_template, rules),
+    };
+
+    let mut warnings = Vec::new();
+    let mut oriented_items = Vec::with_capacity(items.len());
+    for template in items {
+        if template.count <= 0 {
+            warnings.push(PlanWarning::ItemSkipped {
+                item_id: template.id.to_string(),
+                count: template.count,
+            });
+            continue;
+        }
+
+        if template.attributes.bulk || template.attributes.pinned_pack.is_some() {
+            return Err(Error::UnsupportedSheetItem {
+                item_id: template.id.to_string(),
+            });
+        }
+
+        let width = template
+            .attributes
+            .width
+            .ok_or_else(|| Error::MissingItemWidth {
+                item_id: template.id.to_string(),
+            })?;
+        let orientation = orient_on_sheet(template.length, width, sheet).ok_or_else(|| {
+            Error::ItemDoesNotFitInEmptyPack {
+                item_id: template.id.to_string(),
+            }
+        })?;
+        oriented_items.push((template, orientation));
+    }
+
+    // The items are already grouped by storage class and destination, which is kept, so that the sheets
+    // aren't closed early. Within a group the widest items are placed first.
+    let group = |t: &ItemTemplate| (t.attributes.storage_class, t.attributes.destination.clone());
+    oriented_items.sort_by(|(a, a_orientation), (b, b_orientation)| {
+        group(a)
+            .cmp(&group(b))
+            .then_with(|| b_orientation.width.total_cmp(&a_orientation.width))
+    });
+
+    let mut packs = vec![Pack::new(1)];
+    let mut shelves = Vec::new();
+    for (template, orientation) in oriented_items {
+        let mut items_left = template.count;
+        while items_left > 0 {
+            let pack = packs.last_mut().expect("There is always a current sheet.");
+            let (items_allowed, limit) =
+                number_of_items_to_place(pack_template, pack, template, rules, items_left);
+
+            let mut positions = Vec::new();
+            while positions.len() < items_allowed as usize {
+                match find_position(&mut shelves, &orientation, sheet) {
+                    Some(position) => positions.push(position),
+                    None => break,
+                }
+            }
+
+            let items_placed = positions.len() as i32;
+            if items_placed > 0 {
+                let limit = if items_placed < items_allowed {
+                    PlacementLimit::Sheet
+                } else {
+                    limit
+                };
+                pack.add(pack_template, template, items_placed, items_left, limit);
+                pack.sheet_placements
+                    .extend(positions.into_iter().map(|(x, y)| SheetPlacement {
+                        item_id: template.id.clone(),
+                        x,
+                        y,
+                        length: orientation.length,
+                        width: orientation.width,
+                        rotated: orientation.rotated,
+                    }));
+                items_left -= items_placed;
+            } else if pack.is_empty() {
+                return Err(Error::ItemDoesNotFitInEmptyPack {
+                    item_id: template.id.to_string(),
+                });
+            }
+
+            if items_left > 0 {
+                packs.push(Pack::new(packs.len() + 1));
+                shelves.clear();
+            }
+        }
+    }
+
+    while packs.last().is_some_and(|p| p.is_empty()) {
+        packs.pop();
+    }
+
+    Ok(PlanOutcome {
+        packs,
+        warnings,
+        optimality: None,
+    })
+}
@@ -0,0 +1,129 @@
+use std::str::FromStr;
+
+use super::*;
+use crate::model::PackSortOrder;
+
+fn create_pack_template() -> PackTemplate {
+    PackTemplate {
+        maximum_number_of_pieces: 100,
+        maximum_weight: 1000.0,
+        sort_order: PackSortOrder::Natural,
+        sheet: Some(SheetSize {
+            length: 3000.0,
+            width: 1500.0,
+        }),
+        ..PackTemplate::new()
+    }
+}
+
+// orient_on_sheet()
+
+#[test]
+fn when_orienting_an_item_it_should_use_the_least_width_of_the_sheet() {
+    let sheet = SheetSize {
+        length: 3000.0,
+        width: 1500.0,
+    };
+    assert_eq!(
+        orient_on_sheet(500.0, 1000.0, &sheet),
+        Some(Orientation {
+            length: 1000.0,
+            width: 500.0,
+            rotated: true
+        })
+    );
+    assert_eq!(
+        orient_on_sheet(1000.0, 500.0, &sheet),
+        Some(Orientation {
+            length: 1000.0,
+            width: 500.0,
+            rotated: false
+        })
+    );
+    assert_eq!(
+        orient_on_sheet(1400.0, 2000.0, &sheet).map(|o| o.rotated),
+        Some(true)
+    );
+    assert_eq!(orient_on_sheet(2000.0, 2000.0, &sheet), None);
+}
+
+// plan_sheets()
+
+#[test]
+fn when_planning_sheets_it_should_place_the_items_on_shelves() {
+    let items = vec![
+        ItemTemplate::from_str("small,1000,3,1.0,width=500").unwrap(),
+        ItemTemplate::from_str("large,2000,1,4.0,width=1000").unwrap(),
+    ];
+    let outcome =
+        plan_sheets(&items, &create_pack_template(), &[]).expect("Failed to plan the sheets.");
+
+    assert_eq!(outcome.packs.len(), 1);
+    let positions: Vec<(&str, f64, f64)> = outcome.packs[0]
+        .sheet_placements
+        .iter()
+        .map(|p| (p.item_id.as_ref(), p.x, p.y))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![
+            ("large", 0.0, 0.0),
+            ("small", 2000.0, 0.0),
+            ("small", 0.0, 1000.0),
+            ("small", 1000.0, 1000.0),
+        ]
+    );
+    assert_eq!(outcome.packs[0].item_count, 4);
+}
+
+#[test]
+fn when_planning_more_items_than_fit_on_a_sheet_it_should_start_a_new_sheet() {
+    let items = vec![ItemTemplate::from_str("panel,1500,5,1.0,width=1500").unwrap()];
+    let outcome =
+        plan_sheets(&items, &create_pack_template(), &[]).expect("Failed to plan the sheets.");
+
+    let counts: Vec<i32> = outcome.packs.iter().map(|p| p.item_count).collect();
+    assert_eq!(counts, vec![2, 2, 1]);
+    assert_eq!(outcome.packs[0].items[0].limit, PlacementLimit::Sheet);
+    assert_eq!(outcome.packs[2].items[0].limit, PlacementLimit::None);
+}
+
+#[test]
+fn when_planning_sheets_with_a_weight_limit_it_should_start_a_new_sheet() {
+    let pack_template = PackTemplate {
+        maximum_weight: 2.0,
+        ..create_pack_template()
+    };
+    let items = vec![ItemTemplate::from_str("tile,100,3,1.0,width=100").unwrap()];
+    let outcome = plan_sheets(&items, &pack_template, &[]).expect("Failed to plan the sheets.");
+
+    let counts: Vec<i32> = outcome.packs.iter().map(|p| p.item_count).collect();
+    assert_eq!(counts, vec![2, 1]);
+    assert_eq!(outcome.packs[0].items[0].limit, PlacementLimit::Weight);
+}
+
+#[test]
+fn when_planning_sheets_with_an_item_without_a_width_it_should_return_an_error() {
+    let items = vec![ItemTemplate::from_str("rod,1000,1,1.0").unwrap()];
+    assert_eq!(
+        plan_sheets(&items, &create_pack_template(), &[])
+            .err()
+            .unwrap(),
+        Error::MissingItemWidth {
+            item_id: "rod".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_planning_sheets_with_an_item_larger_than_the_sheet_it_should_return_an_error() {
+    let items = vec![ItemTemplate::from_str("wall,4000,1,1.0,width=2000").unwrap()];
+    assert_eq!(
+        plan_sheets(&items, &create_pack_template(), &[])
+            .err()
+            .unwrap(),
+        Error::ItemDoesNotFitInEmptyPack {
+            item_id: "wall".to_string()
+        }
+    );
+}
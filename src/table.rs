@@ -0,0 +1,473 @@
+//! Reading the items from, and writing the plans to, Arrow IPC and Parquet files.
+//!
+//! The files are detected by their magic bytes. A table of items has the columns `id`, `length`, `count` and
+//! `weight`, and optionally `attributes`, with the attributes of an item in the same form as on an item line,
+//! e.g. `tags=FRAGILE,value=12.5`. The pack information is stored in the `pack` key of the metadata of the
+//! schema, in the same form as the first line of the text input, e.g. `NATURAL,40,500.0`. The support for
+//! Arrow IPC files depends on the `arrow` feature, the support for Parquet files on the `parquet` feature.
+
+use std::fmt;
+#[cfg(feature = "text-format")]
+use std::io::BufRead;
+use std::io::{self, Write};
+
+#[cfg(feature = "text-format")]
+use crate::errors::Error;
+#[cfg(feature = "text-format")]
+use crate::model::InputSection;
+use crate::model::Pack;
+
+#[cfg(all(test, feature = "text-format"))]
+#[path = "table_tests.rs"]
+mod tests;
+
+// The magic bytes at the start of the files
+const ARROW_MAGIC_BYTES: &[u8] = b"ARROW1";
+const PARQUET_MAGIC_BYTES: &[u8] = b"PAR1";
+
+// The key of the schema metadata that contains the pack information
+pub const PACK_METADATA_KEY: &str = "pack";
+
+/// Defines the formats of the tables that can be read and written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableFormat {
+    /// The Arrow IPC file format, e.g. the `.arrow` files written by pyarrow.
+    Arrow,
+
+    /// The Parquet format.
+    Parquet,
+}
+
+impl fmt::Display for TableFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TableFormat::Arrow => "arrow",
+            TableFormat::Parquet => "parquet",
+        })
+    }
+}
+
+impl TableFormat {
+    /// Returns the table format that starts with the given bytes, or `None` if the bytes don't start a table.
+    pub fn from_magic_bytes(bytes: &[u8]) -> Option<TableFormat> {
+        if bytes.starts_with(ARROW_MAGIC_BYTES) {
+            Some(TableFormat::Arrow)
+        } else if bytes.starts_with(PARQUET_MAGIC_BYTES) {
+            Some(TableFormat::Parquet)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if this build can read and write tables in this format.
+    pub fn is_supported(self) -> bool {
+        match self {
+            TableFormat::Arrow => cfg!(feature = "arrow"),
+            TableFormat::Parquet => cfg!(feature = "parquet"),
+        }
+    }
+}
+
+#[cfg(feature = "text-format")]
+fn to_unsupported_error(origin: &str, format: TableFormat) -> Error {
+    Error::UnsupportedTableFormat {
+        origin: origin.to_string(),
+        format: format.to_string(),
+    }
+}
+
+fn unsupported_output(format: TableFormat) -> io::Error {
+    let name = match format {
+        TableFormat::Arrow => "Arrow IPC",
+        TableFormat::Parquet => "Parquet",
+    };
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "{name} isn't supported by this build. Enable the '{format}' feature to support it."
+        ),
+    )
+}
+
+/// Reads the sections of the input, which is either a table of items or the text input, see
+/// [`crate::parser::parse_input_sections`]. A table always has a single section. The 'origin' describes the
+/// input in the errors, e.g. the path of the file.
+#[cfg(feature = "text-format")]
+pub fn read_sections<R: BufRead>(reader: &mut R, origin: &str) -> Result<Vec<InputSection>, Error> {
+    let start = reader.fill_buf().map_err(crate::input::read_error)?;
+    let Some(format) = TableFormat::from_magic_bytes(start) else {
+        return crate::parser::parse_input_sections(reader);
+    };
+
+    #[cfg(feature = "arrow")]
+    if format.is_supported() {
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .map_err(crate::input::read_error)?;
+        return read_table_sections(data, format, origin);
+    }
+
+    Err(to_unsupported_error(origin, format))
+}
+
+#[cfg(all(feature = "text-format", feature = "arrow"))]
+fn read_table_sections(
+    data: Vec<u8>,
+    format: TableFormat,
+    origin: &str,
+) -> Result<Vec<InputSection>, Error> {
+    let invalid_table = |reason: String| Error::InvalidTable {
+        origin: origin.to_string(),
+        reason,
+    };
+    let (schema, batches) = arrow_table::read_batches(data, format).map_err(invalid_table)?;
+    let input = arrow_table::items_input(&schema, &batches).map_err(invalid_table)?;
+
+    crate::parser::parse_input_sections(&mut input.as_bytes()).map_err(|error| match error {
+        // The first line is the pack information, the other lines are the rows of the table.
+        Error::InvalidInputLine {
+            line_number,
+            source,
+            ..
+        } => {
+            let location = match line_number {
+                1 => format!("the '{PACK_METADATA_KEY}' metadata"),
+                _ => format!("row {}", line_number - 1),
+            };
+            invalid_table(format!("{location}: {source}"))
+        }
+        error => error,
+    })
+}
+
+/// Writes the packs as a table with a row per placement. The table contains the item of the placement and
+/// the properties of its pack.
+#[cfg_attr(not(feature = "arrow"), allow(unused_variables))]
+pub fn write_table_plan<W: Write>(
+    writer: &mut W,
+    packs: &[Pack],
+    format: TableFormat,
+) -> io::Result<()> {
+    #[cfg(feature = "arrow")]
+    if format.is_supported() {
+        let batch = arrow_table::placement_batch(packs).map_err(io::Error::other)?;
+        return arrow_table::write_batches(writer, batch.schema(), &[batch], format);
+    }
+
+    Err(unsupported_output(format))
+}
+
+/// Writes the tables of the sections of the input, each of which is already written in the given format, as
+/// a single table. The rows of each section start with the number of the section in the `section` column.
+#[cfg_attr(not(feature = "arrow"), allow(unused_variables))]
+pub fn write_table_sections<W: Write>(
+    writer: &mut W,
+    plans: &[Vec<u8>],
+    format: TableFormat,
+) -> io::Result<()> {
+    #[cfg(feature = "arrow")]
+    if format.is_supported() {
+        let (schema, batches) = arrow_table::section_batches(plans, format)?;
+        return arrow_table::write_batches(writer, schema, &batches, format);
+    }
+
+    Err(unsupported_output(format))
+}
+
+#[cfg(feature = "arrow")]
+mod arrow_table {
+    use std::io::{self, Write};
+    use std::sync::Arc;
+
+    use arrow_array::builder::{
+        BooleanBuilder, Float64Builder, Int32Builder, StringBuilder, UInt64Builder,
+    };
+    #[cfg(feature = "text-format")]
+    use arrow_array::Array;
+    use arrow_array::{ArrayRef, RecordBatch, UInt32Array};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+
+    use super::TableFormat;
+    use crate::model::Pack;
+
+    /// Reads the schema and the record batches of a table.
+    pub(super) fn read_batches(
+        data: Vec<u8>,
+        format: TableFormat,
+    ) -> Result<(SchemaRef, Vec<RecordBatch>), String> {
+        match format {
+            TableFormat::Arrow => {
+                let reader = arrow_ipc::reader::FileReader::try_new(io::Cursor::new(data), None)
+                    .map_err(|e| e.to_string())?;
+                let schema = reader.schema();
+                let batches = reader
+                    .collect::<Result<Vec<RecordBatch>, _>>()
+                    .map_err(|e| e.to_string())?;
+                Ok((schema, batches))
+            }
+            #[cfg(feature = "parquet")]
+            TableFormat::Parquet => read_parquet_batches(data),
+            #[allow(unreachable_patterns)]
+            format => Err(format!("the {format} format isn't supported by this build")),
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    fn read_parquet_batches(data: Vec<u8>) -> Result<(SchemaRef, Vec<RecordBatch>), String> {
+        // The key-value metadata of the Parquet file is part of the metadata of the schema.
+        let builder = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            bytes::Bytes::from(data),
+        )
+        .map_err(|e| e.to_string())?;
+        let schema = builder.schema().clone();
+        let batches = builder
+            .build()
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<RecordBatch>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok((schema, batches))
+    }
+
+    /// Returns the column with the given name, converted to the given type.
+    #[cfg(feature = "text-format")]
+    fn column(
+        batch: &RecordBatch,
+        name: &str,
+        data_type: &DataType,
+    ) -> Result<Option<ArrayRef>, String> {
+        batch
+            .column_by_name(name)
+            .map(|c| {
+                arrow_cast::cast(c, data_type)
+                    .map_err(|e| format!("the column '{name}' can't be read as {data_type}: {e}"))
+            })
+            .transpose()
+    }
+
+    #[cfg(feature = "text-format")]
+    fn required_column(
+        batch: &RecordBatch,
+        name: &str,
+        data_type: &DataType,
+    ) -> Result<ArrayRef, String> {
+        column(batch, name, data_type)?
+            .ok_or_else(|| format!("the table doesn't contain the column '{name}'"))
+    }
+
+    /// Returns the value of the column in the given row as it would be written on an item line.
+    #[cfg(feature = "text-format")]
+    fn value(
+        column: &ArrayRef,
+        name: &str,
+        row: usize,
+        row_number: usize,
+    ) -> Result<String, String> {
+        if column.is_null(row) {
+            return Err(format!("row {row_number}: the column '{name}' is empty"));
+        }
+
+        let value = arrow_cast::display::array_value_to_string(column, row)
+            .map_err(|e| format!("row {row_number}: {e}"))?;
+        if value.contains(['\n', '\r']) {
+            return Err(format!(
+                "row {row_number}: the column '{name}' contains a line break"
+            ));
+        }
+
+        Ok(value)
+    }
+
+    /// Converts the table into the text input, with the pack information on the first line and an item line
+    /// per row, so that the items are checked in the same way as the items of the text input.
+    #[cfg(feature = "text-format")]
+    pub(super) fn items_input(schema: &Schema, batches: &[RecordBatch]) -> Result<String, String> {
+        let pack = schema
+            .metadata()
+            .get(super::PACK_METADATA_KEY)
+            .ok_or_else(|| {
+                format!(
+                    "the metadata of the schema doesn't contain the pack information in the '{}' key",
+                    super::PACK_METADATA_KEY
+                )
+            })?;
+        if pack.is_empty() || pack.contains(['\n', '\r']) {
+            return Err(format!(
+                "the '{}' metadata should contain a single line with the pack information",
+                super::PACK_METADATA_KEY
+            ));
+        }
+
+        let mut input = format!("{pack}\n");
+        let mut row_number = 0;
+        for batch in batches {
+            let id = required_column(batch, "id", &DataType::Utf8)?;
+            let length = required_column(batch, "length", &DataType::Float64)?;
+            let count = required_column(batch, "count", &DataType::Int64)?;
+            let weight = required_column(batch, "weight", &DataType::Float64)?;
+            let attributes = column(batch, "attributes", &DataType::Utf8)?;
+
+            for row in 0..batch.num_rows() {
+                row_number += 1;
+                let id_value = value(&id, "id", row, row_number)?;
+                if id_value.contains(',') {
+                    return Err(format!(
+                        "row {row_number}: the column 'id' contains a comma"
+                    ));
+                }
+
+                input.push_str(&format!(
+                    "{},{},{},{}",
+                    id_value,
+                    value(&length, "length", row, row_number)?,
+                    value(&count, "count", row, row_number)?,
+                    value(&weight, "weight", row, row_number)?,
+                ));
+                if let Some(attributes) = attributes.as_ref().filter(|a| !a.is_null(row)) {
+                    let attributes = value(attributes, "attributes", row, row_number)?;
+                    if !attributes.is_empty() {
+                        input.push(',');
+                        input.push_str(&attributes);
+                    }
+                }
+                input.push('\n');
+            }
+        }
+
+        Ok(input)
+    }
+
+    fn placement_schema() -> Schema {
+        Schema::new(vec![
+            Field::new("pack_number", DataType::UInt64, false),
+            Field::new("item_id", DataType::Utf8, false),
+            Field::new("length", DataType::Float64, false),
+            Field::new("count", DataType::Int32, false),
+            Field::new("weight", DataType::Float64, false),
+            Field::new("pack_length", DataType::Float64, false),
+            Field::new("pack_weight", DataType::Float64, false),
+            Field::new("pack_gross_weight", DataType::Float64, false),
+            Field::new("destination", DataType::Utf8, true),
+            Field::new("overweight", DataType::Boolean, false),
+            Field::new("oversize", DataType::Boolean, false),
+            Field::new("hash", DataType::Utf8, false),
+        ])
+    }
+
+    /// Returns the record batch with a row per placement in the packs.
+    pub(super) fn placement_batch(packs: &[Pack]) -> Result<RecordBatch, arrow_schema::ArrowError> {
+        let mut pack_number = UInt64Builder::new();
+        let mut item_id = StringBuilder::new();
+        let mut length = Float64Builder::new();
+        let mut count = Int32Builder::new();
+        let mut weight = Float64Builder::new();
+        let mut pack_length = Float64Builder::new();
+        let mut pack_weight = Float64Builder::new();
+        let mut pack_gross_weight = Float64Builder::new();
+        let mut destination = StringBuilder::new();
+        let mut overweight = BooleanBuilder::new();
+        let mut oversize = BooleanBuilder::new();
+        let mut hash = StringBuilder::new();
+
+        for pack in packs {
+            let pack_hash = pack.content_hash();
+            for placement in &pack.items {
+                pack_number.append_value(pack.number as u64);
+                item_id.append_value(&*placement.item.id);
+                length.append_value(placement.item.length);
+                count.append_value(placement.item.count);
+                weight.append_value(placement.item.weight);
+                pack_length.append_value(pack.length);
+                pack_weight.append_value(pack.weight);
+                pack_gross_weight.append_value(pack.gross_weight());
+                destination.append_option(pack.destination.as_deref());
+                overweight.append_value(pack.overweight);
+                oversize.append_value(pack.oversize);
+                hash.append_value(&pack_hash);
+            }
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(pack_number.finish()),
+            Arc::new(item_id.finish()),
+            Arc::new(length.finish()),
+            Arc::new(count.finish()),
+            Arc::new(weight.finish()),
+            Arc::new(pack_length.finish()),
+            Arc::new(pack_weight.finish()),
+            Arc::new(pack_gross_weight.finish()),
+            Arc::new(destination.finish()),
+            Arc::new(overweight.finish()),
+            Arc::new(oversize.finish()),
+            Arc::new(hash.finish()),
+        ];
+        RecordBatch::try_new(Arc::new(placement_schema()), columns)
+    }
+
+    /// Reads the tables of the sections and adds the number of the section to each row.
+    pub(super) fn section_batches(
+        plans: &[Vec<u8>],
+        format: TableFormat,
+    ) -> io::Result<(SchemaRef, Vec<RecordBatch>)> {
+        let mut fields = vec![Arc::new(Field::new("section", DataType::UInt32, false))];
+        fields.extend(placement_schema().fields().iter().cloned());
+        let schema = Arc::new(Schema::new(fields));
+
+        let mut batches = Vec::new();
+        for (index, plan) in plans.iter().enumerate() {
+            let (_, plan_batches) = read_batches(plan.clone(), format)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            for batch in plan_batches {
+                let section = UInt32Array::from(vec![index as u32 + 1; batch.num_rows()]);
+                let mut columns: Vec<ArrayRef> = vec![Arc::new(section)];
+                columns.extend(batch.columns().iter().cloned());
+                batches.push(
+                    RecordBatch::try_new(Arc::clone(&schema), columns).map_err(io::Error::other)?,
+                );
+            }
+        }
+
+        Ok((schema, batches))
+    }
+
+    /// Writes the record batches as a single table.
+    pub(super) fn write_batches<W: Write>(
+        writer: &mut W,
+        schema: SchemaRef,
+        batches: &[RecordBatch],
+        format: TableFormat,
+    ) -> io::Result<()> {
+        match format {
+            TableFormat::Arrow => {
+                let mut table_writer = arrow_ipc::writer::FileWriter::try_new(writer, &schema)
+                    .map_err(io::Error::other)?;
+                for batch in batches {
+                    table_writer.write(batch).map_err(io::Error::other)?;
+                }
+                table_writer.finish().map_err(io::Error::other)
+            }
+            #[cfg(feature = "parquet")]
+            TableFormat::Parquet => write_parquet_batches(writer, schema, batches),
+            #[allow(unreachable_patterns)]
+            format => Err(super::unsupported_output(format)),
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    fn write_parquet_batches<W: Write>(
+        writer: &mut W,
+        schema: SchemaRef,
+        batches: &[RecordBatch],
+    ) -> io::Result<()> {
+        // The Parquet writer requires a writer that can be sent to another thread, which the output may not
+        // be, so the file is written to memory first.
+        let mut data = Vec::new();
+        let mut table_writer = parquet::arrow::ArrowWriter::try_new(&mut data, schema, None)
+            .map_err(io::Error::other)?;
+        for batch in batches {
+            table_writer.write(batch).map_err(io::Error::other)?;
+        }
+        table_writer.close().map_err(io::Error::other)?;
+        writer.write_all(&data)
+    }
+}
@@ -0,0 +1,364 @@
+use super::*;
+
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+
+#[cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+#[cfg(feature = "arrow")]
+use arrow_schema::{DataType, Field, Schema};
+
+#[cfg(feature = "arrow")]
+use crate::model::{PackSortOrder, PackTemplate};
+
+/// Writes a table of items with the given pack information and the given rows of ID, length, count, weight
+/// and attributes.
+#[cfg(feature = "arrow")]
+fn write_items_table(
+    pack: Option<&str>,
+    rows: &[(&str, f64, i64, f64, Option<&str>)],
+    format: TableFormat,
+) -> Vec<u8> {
+    let metadata = pack
+        .map(|p| [(PACK_METADATA_KEY.to_string(), p.to_string())].into())
+        .unwrap_or_default();
+    let schema = Arc::new(Schema::new_with_metadata(
+        vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("length", DataType::Float64, false),
+            Field::new("count", DataType::Int64, false),
+            Field::new("weight", DataType::Float64, false),
+            Field::new("attributes", DataType::Utf8, true),
+        ],
+        metadata,
+    ));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+        Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.1))),
+        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.2))),
+        Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.3))),
+        Arc::new(StringArray::from_iter(rows.iter().map(|r| r.4))),
+    ];
+    let batch = RecordBatch::try_new(Arc::clone(&schema), columns).unwrap();
+
+    let mut data = Vec::new();
+    arrow_table::write_batches(&mut data, schema, &[batch], format)
+        .expect("Failed to write the table.");
+    data
+}
+
+#[cfg(feature = "arrow")]
+fn create_packs() -> Vec<Pack> {
+    let pack_template = PackTemplate {
+        maximum_number_of_pieces: 10,
+        maximum_weight: 50.0,
+        sort_order: PackSortOrder::Natural,
+        ..PackTemplate::new()
+    };
+    let items = crate::parser::parse_items(&mut "item1,10,12,4.0\nitem2,20,3,2.5\n".as_bytes())
+        .expect("Failed to parse the items.");
+    crate::planner::plan_packs(&items, &pack_template, &[])
+        .expect("Failed to plan the packs.")
+        .packs
+}
+
+/// Reads the rows of a table of placements as (section, pack number, item ID, count).
+#[cfg(feature = "arrow")]
+fn read_placements(data: &[u8], format: TableFormat) -> Vec<(Option<u32>, u64, String, i32)> {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int32Type, UInt32Type, UInt64Type};
+
+    let (_, batches) =
+        arrow_table::read_batches(data.to_vec(), format).expect("Failed to read the table.");
+    let mut rows = Vec::new();
+    for batch in batches {
+        let section = batch
+            .column_by_name("section")
+            .map(|c| c.as_primitive::<UInt32Type>().clone());
+        let pack_number = batch["pack_number"].as_primitive::<UInt64Type>();
+        let item_id = batch["item_id"].as_string::<i32>();
+        let count = batch["count"].as_primitive::<Int32Type>();
+        for row in 0..batch.num_rows() {
+            rows.push((
+                section.as_ref().map(|s| s.value(row)),
+                pack_number.value(row),
+                item_id.value(row).to_string(),
+                count.value(row),
+            ));
+        }
+    }
+    rows
+}
+
+// TableFormat
+
+#[test]
+fn when_detecting_the_table_format_from_magic_bytes_it_should_return_the_format() {
+    assert_eq!(
+        TableFormat::from_magic_bytes(b"ARROW1\0\0"),
+        Some(TableFormat::Arrow)
+    );
+    assert_eq!(
+        TableFormat::from_magic_bytes(b"PAR1\x15\x04"),
+        Some(TableFormat::Parquet)
+    );
+    assert_eq!(TableFormat::from_magic_bytes(b"NATURAL,40,500.0"), None);
+    assert_eq!(TableFormat::from_magic_bytes(&[]), None);
+}
+
+// read_sections()
+
+#[test]
+fn when_reading_the_text_input_it_should_parse_the_lines() {
+    let sections = read_sections(
+        &mut "NATURAL,40,500.0\n1001,6200,30,9.653\n".as_bytes(),
+        "test",
+    )
+    .expect("Failed to read the input.");
+
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].pack_template.maximum_number_of_pieces, 40);
+    assert_eq!(&*sections[0].items[0].id, "1001");
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn when_reading_an_arrow_table_of_items_it_should_return_the_pack_and_the_items() {
+    let data = write_items_table(
+        Some("NATURAL,40,500.0"),
+        &[
+            ("1001", 6200.0, 30, 9.653, Some("tags=FRAGILE|COLD")),
+            ("2001", 7200.0, 50, 11.21, None),
+        ],
+        TableFormat::Arrow,
+    );
+
+    let sections =
+        read_sections(&mut data.as_slice(), "items.arrow").expect("Failed to read the table.");
+
+    assert_eq!(sections.len(), 1);
+    let section = &sections[0];
+    assert_eq!(section.pack_template.maximum_number_of_pieces, 40);
+    assert_eq!(section.pack_template.maximum_weight, 500.0);
+    assert_eq!(section.items.len(), 2);
+    assert_eq!(&*section.items[0].id, "1001");
+    assert_eq!(section.items[0].length, 6200.0);
+    assert_eq!(section.items[0].count, 30);
+    assert_eq!(section.items[0].weight, 9.653);
+    assert_eq!(section.items[0].attributes.tags, vec!["FRAGILE", "COLD"]);
+    assert_eq!(&*section.items[1].id, "2001");
+    assert!(section.items[1].attributes.tags.is_empty());
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn when_reading_a_parquet_table_of_items_it_should_return_the_pack_and_the_items() {
+    let data = write_items_table(
+        Some("SHORT_TO_LONG,10,100.0"),
+        &[("3001", 100.0, 4, 2.5, Some("value=12.5"))],
+        TableFormat::Parquet,
+    );
+
+    let sections =
+        read_sections(&mut data.as_slice(), "items.parquet").expect("Failed to read the table.");
+
+    let section = &sections[0];
+    assert_eq!(section.pack_template.sort_order, PackSortOrder::ShortToLong);
+    assert_eq!(section.items.len(), 1);
+    assert_eq!(&*section.items[0].id, "3001");
+    assert_eq!(section.items[0].count, 4);
+    assert_eq!(section.items[0].attributes.value, Some(12.5));
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn when_reading_a_table_without_the_pack_information_it_should_return_an_error() {
+    let data = write_items_table(
+        None,
+        &[("1001", 6200.0, 30, 9.653, None)],
+        TableFormat::Arrow,
+    );
+
+    let result = read_sections(&mut data.as_slice(), "items.arrow");
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidTable {
+            origin: "items.arrow".to_string(),
+            reason:
+                "the metadata of the schema doesn't contain the pack information in the 'pack' key"
+                    .to_string(),
+        }
+    );
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn when_reading_a_table_with_an_invalid_item_it_should_return_an_error_with_the_row() {
+    let data = write_items_table(
+        Some("NATURAL,40,500.0"),
+        &[
+            ("1001", 6200.0, 30, 9.653, None),
+            ("2001", 7200.0, 50, 11.21, Some("storage=warm")),
+        ],
+        TableFormat::Arrow,
+    );
+
+    let result = read_sections(&mut data.as_slice(), "items.arrow");
+
+    match result.err().unwrap() {
+        Error::InvalidTable { origin, reason } => {
+            assert_eq!(origin, "items.arrow");
+            assert!(reason.starts_with("row 2: "), "{reason}");
+        }
+        error => panic!("Unexpected error: {error:?}"),
+    }
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn when_reading_a_table_with_a_comma_in_an_id_it_should_return_an_error() {
+    let data = write_items_table(
+        Some("NATURAL,40,500.0"),
+        &[("10,01", 6200.0, 30, 9.653, None)],
+        TableFormat::Arrow,
+    );
+
+    let result = read_sections(&mut data.as_slice(), "items.arrow");
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::InvalidTable {
+            origin: "items.arrow".to_string(),
+            reason: "row 1: the column 'id' contains a comma".to_string(),
+        }
+    );
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn when_reading_a_truncated_table_it_should_return_an_error() {
+    let data = write_items_table(
+        Some("NATURAL,40,500.0"),
+        &[("1001", 6200.0, 30, 9.653, None)],
+        TableFormat::Arrow,
+    );
+
+    let result = read_sections(&mut &data[..data.len() / 2], "items.arrow");
+
+    assert!(matches!(
+        result.err().unwrap(),
+        Error::InvalidTable { origin, .. } if origin == "items.arrow"
+    ));
+}
+
+#[cfg(not(feature = "arrow"))]
+#[test]
+fn when_reading_an_arrow_table_without_the_arrow_feature_it_should_return_an_error() {
+    let result = read_sections(&mut &b"ARROW1\0\0"[..], "items.arrow");
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnsupportedTableFormat {
+            origin: "items.arrow".to_string(),
+            format: "arrow".to_string(),
+        }
+    );
+}
+
+#[cfg(not(feature = "parquet"))]
+#[test]
+fn when_reading_a_parquet_table_without_the_parquet_feature_it_should_return_an_error() {
+    let result = read_sections(&mut &b"PAR1\x15\x04"[..], "items.parquet");
+
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnsupportedTableFormat {
+            origin: "items.parquet".to_string(),
+            format: "parquet".to_string(),
+        }
+    );
+}
+
+// write_table_plan()
+
+#[cfg(feature = "arrow")]
+#[test]
+fn when_writing_a_plan_as_an_arrow_table_it_should_write_a_row_per_placement() {
+    let packs = create_packs();
+
+    let mut data = Vec::new();
+    write_table_plan(&mut data, &packs, TableFormat::Arrow).expect("Failed to write the plan.");
+
+    assert_eq!(
+        TableFormat::from_magic_bytes(&data),
+        Some(TableFormat::Arrow)
+    );
+    assert_eq!(
+        read_placements(&data, TableFormat::Arrow),
+        vec![
+            (None, 1, "item1".to_string(), 10),
+            (None, 2, "item1".to_string(), 2),
+            (None, 2, "item2".to_string(), 3),
+        ]
+    );
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn when_writing_a_plan_as_a_parquet_table_it_should_write_the_same_rows_as_the_arrow_table() {
+    let packs = create_packs();
+
+    let mut arrow = Vec::new();
+    write_table_plan(&mut arrow, &packs, TableFormat::Arrow).expect("Failed to write the plan.");
+    let mut parquet = Vec::new();
+    write_table_plan(&mut parquet, &packs, TableFormat::Parquet)
+        .expect("Failed to write the plan.");
+
+    assert_eq!(
+        TableFormat::from_magic_bytes(&parquet),
+        Some(TableFormat::Parquet)
+    );
+    assert_eq!(
+        read_placements(&parquet, TableFormat::Parquet),
+        read_placements(&arrow, TableFormat::Arrow)
+    );
+}
+
+#[cfg(not(feature = "arrow"))]
+#[test]
+fn when_writing_a_plan_as_an_arrow_table_without_the_arrow_feature_it_should_return_an_error() {
+    let result = write_table_plan(&mut Vec::new(), &[], TableFormat::Arrow);
+
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Unsupported);
+}
+
+// write_table_sections()
+
+#[cfg(feature = "arrow")]
+#[test]
+fn when_writing_the_tables_of_sections_it_should_add_the_section_to_each_row() {
+    let packs = create_packs();
+    let plans: Vec<Vec<u8>> = [&packs[..1], &packs[1..]]
+        .iter()
+        .map(|packs| {
+            let mut plan = Vec::new();
+            write_table_plan(&mut plan, packs, TableFormat::Arrow)
+                .expect("Failed to write the plan.");
+            plan
+        })
+        .collect();
+
+    let mut data = Vec::new();
+    write_table_sections(&mut data, &plans, TableFormat::Arrow)
+        .expect("Failed to write the sections.");
+
+    assert_eq!(
+        read_placements(&data, TableFormat::Arrow),
+        vec![
+            (Some(1), 1, "item1".to_string(), 10),
+            (Some(2), 2, "item1".to_string(), 2),
+            (Some(2), 2, "item2".to_string(), 3),
+        ]
+    );
+}
@@ -0,0 +1,62 @@
+//! Helpers that are shared by the tests of several modules.
+
+use std::path::PathBuf;
+
+/// Creates an empty directory, unique to the test, in which the test can create its files.
+pub(crate) fn create_test_directory(name: &str) -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("pack-planner-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).expect("Failed to create the test directory.");
+    directory
+}
+
+/// Starts a server that answers a single HTTP request with the given response and returns the address of
+/// the server, e.g. `http://127.0.0.1:8080`, and a handle that returns the raw request once it has been
+/// answered.
+#[cfg(feature = "http")]
+pub(crate) fn serve_single_request(
+    status: &'static str,
+    body: &'static str,
+) -> (String, std::thread::JoinHandle<String>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind the test server.");
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("Failed to accept the request.");
+        let mut reader = BufReader::new(&stream);
+
+        let mut request = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap();
+            }
+            request.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+
+        let mut payload = vec![0u8; content_length];
+        reader.read_exact(&mut payload).unwrap();
+        request.push_str(&String::from_utf8(payload).unwrap());
+
+        let mut writer = &stream;
+        write!(
+            writer,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+        .unwrap();
+        request
+    });
+
+    (address, handle)
+}
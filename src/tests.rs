@@ -1,287 +1,1314 @@
 use std::io::Cursor;
 
 use crate::*;
+use pack_planner::model::PackSortOrder;
 
-// PackSortOrder
+// parse_arguments()
 
 #[test]
-fn when_parsing_sort_order_it_should_return_the_correct_type() {
-    let natural_sort_order =
-        PackSortOrder::from_str("NATURAL").expect("Failed to parse the natural sort order.");
-    assert_eq!(PackSortOrder::Natural, natural_sort_order);
+fn when_parsing_the_weight_bands_argument_it_should_store_the_path() {
+    let args = vec!["--weight-bands".to_string(), "bands.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.weight_bands_file, Some("bands.csv".to_string()));
+}
+
+#[test]
+fn when_parsing_the_cartons_argument_it_should_store_the_path() {
+    let args = vec!["--cartons".to_string(), "cartons.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.cartons_file, Some("cartons.csv".to_string()));
+}
+
+#[test]
+fn when_parsing_the_explain_argument_it_should_enable_explanations() {
+    let args = vec!["--explain".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.explain);
+}
 
-    let short_to_long_sort_order = PackSortOrder::from_str("SHORT_TO_LONG")
-        .expect("Failed to parse the short-to-long sort order.");
-    assert_eq!(PackSortOrder::ShortToLong, short_to_long_sort_order);
+#[test]
+fn when_parsing_the_quick_check_argument_it_should_enable_the_quick_check() {
+    let args = vec!["--quick-check".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.quick_check);
+}
 
-    let long_to_short_sort_order = PackSortOrder::from_str("LONG_TO_SHORT")
-        .expect("Failed to parse the long-to-short sort order");
-    assert_eq!(PackSortOrder::LongToShort, long_to_short_sort_order);
+#[test]
+fn when_parsing_the_restart_arguments_it_should_store_the_values() {
+    let args = vec![
+        "--restarts".to_string(),
+        "25".to_string(),
+        "--seed".to_string(),
+        "1234".to_string(),
+        "--objective".to_string(),
+        "balanced-weight".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.restarts, Some(25));
+    assert_eq!(options.seed, Some(1234));
+    assert_eq!(options.objective, Some(RestartObjective::BalancedWeight));
 }
 
 #[test]
-fn when_creating_a_string_representation_of_the_sort_order_it_should_return_the_correct_value() {
-    assert_eq!("NATURAL", PackSortOrder::Natural.to_string());
-    assert_eq!("SHORT_TO_LONG", PackSortOrder::ShortToLong.to_string());
-    assert_eq!("LONG_TO_SHORT", PackSortOrder::LongToShort.to_string());
+fn when_parsing_the_color_argument_it_should_store_the_choice() {
+    let args = vec!["--color".to_string(), "always".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.color, ColorChoice::Always);
+
+    let options = parse_arguments(std::iter::empty()).expect("Failed to parse the arguments.");
+    assert_eq!(options.color, ColorChoice::Auto);
 }
 
-// ItemTemplate
+#[test]
+fn when_parsing_the_on_infeasible_argument_it_should_store_the_policy() {
+    let args = vec!["--on-infeasible".to_string(), "own-pack".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.on_infeasible, InfeasibleItemPolicy::OwnPack);
+
+    let args = vec!["--on-infeasible".to_string(), "ignore".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::InvalidArgumentValue { .. })
+    ));
+}
 
 #[test]
-fn when_parsing_a_valid_item_input_string_it_should_return_an_item_template() {
-    let input = "item1,10.5,20,3.0";
-    let result = ItemTemplate::from_str(input);
-    assert!(result.is_ok());
+fn when_parsing_the_filter_and_map_arguments_it_should_store_the_expressions_in_order() {
+    let args = vec![
+        "--filter",
+        "weight < 30",
+        "--map",
+        "count *= 2",
+        "--filter",
+        "length >= 100",
+    ]
+    .into_iter()
+    .map(String::from);
+    let options = parse_arguments(args).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.item_filters,
+        vec![
+            ItemFilter::from_str("weight < 30").unwrap(),
+            ItemFilter::from_str("length >= 100").unwrap(),
+        ]
+    );
+    assert_eq!(
+        options.item_maps,
+        vec![ItemMap::from_str("count *= 2").unwrap()]
+    );
 
-    let item = result.unwrap();
-    assert_eq!(item.id, "item1");
-    assert_eq!(item.length, 10.5);
-    assert_eq!(item.count, 20);
-    assert_eq!(item.weight, 3.0);
+    let args = vec!["--map".to_string(), "count ^= 2".to_string()];
+    assert_eq!(
+        parse_arguments(args.into_iter()),
+        Err(Error::InvalidItemMap {
+            input: "count ^= 2".to_string()
+        })
+    );
 }
 
 #[test]
-fn when_parsing_an_item_input_with_too_few_properties_it_should_return_an_error() {
-    let input = "item1,10.5,20";
-    let result = ItemTemplate::from_str(input);
-    assert!(result.is_err());
+fn when_parsing_an_invalid_number_of_restarts_it_should_return_an_error() {
+    let args = vec!["--restarts".to_string(), "many".to_string()];
+    let result = parse_arguments(args.into_iter());
     assert_eq!(
         result.err().unwrap(),
-        Error::InvalidNumberOfPropertiesForItem {
-            input: input.to_string(),
-            property_count: 3
+        Error::InvalidArgumentValue {
+            argument: "--restarts".to_string(),
+            value: "many".to_string()
         }
     );
 }
 
 #[test]
-fn when_parsing_an_item_input_with_too_many_properties_it_should_return_an_error() {
-    let input = "item1,10.5,20,3.0,10.0";
-    let result = ItemTemplate::from_str(input);
-    assert!(result.is_err());
+fn when_parsing_the_time_limit_argument_it_should_accept_the_units() {
+    let args = vec!["--time-limit".to_string(), "5s".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.time_limit, Some(Duration::from_secs(5)));
+
+    assert_eq!(parse_time_limit("250ms"), Some(Duration::from_millis(250)));
+    assert_eq!(parse_time_limit("2m"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_time_limit("1.5"), Some(Duration::from_millis(1500)));
+    assert_eq!(parse_time_limit("-1s"), None);
+    assert_eq!(parse_time_limit("soon"), None);
+}
+
+#[test]
+fn when_signing_without_an_output_file_it_should_return_an_error() {
+    let args = vec!["--sign".to_string(), "key.pem".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.signing_key_file, Some("key.pem".to_string()));
+
     assert_eq!(
-        result.err().unwrap(),
-        Error::InvalidNumberOfPropertiesForItem {
-            input: input.to_string(),
-            property_count: 5
+        run(&options).err().unwrap(),
+        Error::MissingRequiredArgument {
+            argument: "--sign".to_string(),
+            required_argument: "--output".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_the_partition_argument_it_should_partition_the_items() {
+    let args = vec![
+        "--partition".to_string(),
+        "--algorithm".to_string(),
+        "bin-completion".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.partition);
+
+    let strategy = load_configuration(&options).unwrap().algorithm.strategy();
+    assert!(strategy.partition);
+    assert!(strategy.bin_completion);
+}
+
+#[test]
+fn when_parsing_the_prefer_homogeneous_argument_it_should_enable_the_mode() {
+    let args = vec!["--prefer-homogeneous".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.prefer_homogeneous);
+    assert!(load_configuration(&options).unwrap().prefer_homogeneous);
+}
+
+#[test]
+fn when_parsing_the_checkpoint_arguments_it_should_store_the_checkpoint() {
+    let args = vec!["--checkpoint".to_string(), "plan.ckpt".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.checkpoint,
+        Some(PlanCheckpoint::new("plan.ckpt", false))
+    );
+
+    let args = vec!["--resume".to_string(), "plan.ckpt".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.checkpoint,
+        Some(PlanCheckpoint::new("plan.ckpt", true))
+    );
+}
+
+#[test]
+fn when_parsing_the_tie_break_argument_it_should_store_the_tie_break() {
+    let args = vec!["--tie-break".to_string(), "weight".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.tie_break, TieBreak::Weight);
+
+    let args = vec!["--tie-break".to_string(), "random".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::InvalidArgumentValue { argument, .. }) if argument == "--tie-break"
+    ));
+}
+
+#[test]
+fn when_parsing_the_min_packs_argument_it_should_store_the_minimum_number_of_packs() {
+    let args = vec!["--min-packs".to_string(), "4".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.minimum_packs, Some(4));
+
+    let args = vec!["--min-packs".to_string(), "0".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::InvalidArgumentValue { argument, .. }) if argument == "--min-packs"
+    ));
+}
+
+#[test]
+fn when_parsing_the_schema_command_it_should_default_to_the_schema_of_the_plan() {
+    let args = vec!["schema".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.schema, Some(OutputSchema::Plan));
+
+    let args = vec!["schema".to_string(), "statistics".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.schema, Some(OutputSchema::Statistics));
+
+    let args = vec!["schema".to_string(), "labels".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::InvalidArgumentValue { argument, .. }) if argument == "schema"
+    ));
+}
+
+#[test]
+fn when_parsing_the_duplicate_ids_argument_it_should_store_the_policy() {
+    let args = vec!["--duplicate-ids".to_string(), "merge".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.duplicate_ids, DuplicateIdPolicy::Merge);
+
+    let args = vec!["--duplicate-ids".to_string(), "ignore".to_string()];
+    assert!(parse_arguments(args.into_iter()).is_err());
+}
+
+#[test]
+fn when_parsing_the_grades_argument_it_should_store_the_grades() {
+    let args = vec!["--grades".to_string(), "A=90,B=70,C=0".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.grades.len(), 3);
+    assert_eq!(options.grades[1].name, "B");
+
+    let args = vec!["--grades".to_string(), "A90".to_string()];
+    assert_eq!(
+        parse_arguments(args.into_iter()).err().unwrap(),
+        Error::InvalidGrade {
+            input: "A90".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_the_handling_time_argument_it_should_store_the_time() {
+    let args = vec!["--handling-time".to_string(), "4.5".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.handling_time, Some(4.5));
+
+    let args = vec!["--handling-time".to_string(), "-1".to_string()];
+    assert_eq!(
+        parse_arguments(args.into_iter()).err().unwrap(),
+        Error::InvalidArgumentValue {
+            argument: "--handling-time".to_string(),
+            value: "-1".to_string()
         }
     );
 }
 
 #[test]
-fn when_parsing_an_item_input_with_an_invalid_item_length_it_should_return_an_error() {
-    let input = "item1,abc,20,3.0";
-    let result = ItemTemplate::from_str(input);
-    assert!(result.is_err());
+fn when_parsing_the_pack_arguments_it_should_store_the_pack_information() {
+    let args = vec![
+        "--sort".to_string(),
+        "LONG_TO_SHORT".to_string(),
+        "--max-pieces".to_string(),
+        "40".to_string(),
+        "--max-weight".to_string(),
+        "500.5".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.sort_order, Some(PackSortOrder::LongToShort));
+    assert_eq!(options.maximum_number_of_pieces, Some(40));
+    assert_eq!(options.maximum_weight, Some(500.5));
+
+    for (argument, value) in [
+        ("--sort", "NotSet"),
+        ("--max-pieces", "0"),
+        ("--max-weight", "-1"),
+    ] {
+        let args = vec![argument.to_string(), value.to_string()];
+        assert_eq!(
+            parse_arguments(args.into_iter()).err().unwrap(),
+            Error::InvalidArgumentValue {
+                argument: argument.to_string(),
+                value: value.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn when_parsing_the_daemon_argument_it_should_store_the_socket_path() {
+    let args = vec!["--daemon".to_string(), "/tmp/pack-planner.sock".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.daemon_socket,
+        Some("/tmp/pack-planner.sock".to_string())
+    );
+}
+
+#[test]
+fn when_parsing_the_rules_argument_it_should_store_the_path() {
+    let args = vec!["--rules".to_string(), "rules.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.rules_file, Some("rules.csv".to_string()));
+}
+
+#[test]
+fn when_parsing_the_check_fit_argument_it_should_store_the_path() {
+    let args = vec!["--check-fit".to_string(), "order.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.fit_check_file, Some("order.csv".to_string()));
+}
+
+#[test]
+fn when_parsing_the_kit_arguments_it_should_store_the_bill_of_materials() {
+    let args = vec![
+        "--bom".to_string(),
+        "kits.csv".to_string(),
+        "--keep-kits-together".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.bill_of_materials_file, Some("kits.csv".to_string()));
+    assert!(options.keep_kits_together);
+}
+
+#[test]
+fn when_parsing_the_nesting_argument_it_should_store_the_path() {
+    let args = vec!["--nesting".to_string(), "nesting.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.nesting_rules_file, Some("nesting.csv".to_string()));
 }
 
 #[test]
-fn when_parsing_an_item_input_with_an_invalid_item_weight_it_should_return_an_error() {
-    let input = "item1,10.5,20,xyz";
-    let result = ItemTemplate::from_str(input);
-    assert!(result.is_err());
+fn when_parsing_the_stops_argument_it_should_store_the_path() {
+    let args = vec!["--stops".to_string(), "stops.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.delivery_stops_file, Some("stops.csv".to_string()));
 }
 
 #[test]
-fn when_parsing_an_item_input_with_an_invalid_item_amount_it_should_return_an_error() {
-    let input = "item1,10.5,abc,3.0";
-    let result = ItemTemplate::from_str(input);
-    assert!(result.is_err());
+fn when_parsing_the_destination_limits_argument_it_should_store_the_path() {
+    let args = vec!["--destination-limits".to_string(), "limits.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.destination_limits_file,
+        Some("limits.csv".to_string())
+    );
+}
+
+#[test]
+fn when_parsing_the_sequence_file_argument_it_should_store_the_path() {
+    let args = vec!["--sequence-file".to_string(), "packs.seq".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.sequence_file, Some("packs.seq".to_string()));
 }
 
-// PackTemplate
+#[test]
+fn when_parsing_the_class_limits_argument_it_should_store_the_path() {
+    let args = vec!["--class-limits".to_string(), "classes.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.class_limits_file, Some("classes.csv".to_string()));
+}
 
 #[test]
-fn when_creating_a_new_pack_template_it_should_initialize_properly() {
-    let pack = PackTemplate::new();
-    assert_eq!(pack.maximum_number_of_pieces, 0);
-    assert_eq!(pack.maximum_weight, 0.0);
-    assert_eq!(pack.sort_order, PackSortOrder::NotSet);
+fn when_parsing_the_stats_file_argument_it_should_store_the_path() {
+    let args = vec!["--stats-file".to_string(), "stats.jsonl".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.stats_file, Some("stats.jsonl".to_string()));
 }
 
 #[test]
-fn when_parsing_a_valid_pack_input_string_it_should_return_a_pack_template() {
-    let mut pack = PackTemplate::new();
-    let input = "NATURAL,10,20.0";
-    let result = pack.from_line(input);
-    assert!(result.is_ok());
-    assert_eq!(pack.maximum_number_of_pieces, 10);
-    assert_eq!(pack.maximum_weight, 20.0);
-    assert_eq!(pack.sort_order, PackSortOrder::Natural);
+fn when_parsing_the_event_log_argument_it_should_store_the_path() {
+    let args = vec!["--event-log".to_string(), "events.jsonl".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.event_log_file, Some("events.jsonl".to_string()));
 }
 
 #[test]
-fn when_parsing_a_pack_input_with_too_few_properties_it_should_return_an_error() {
-    let mut pack = PackTemplate::new();
-    let input = "NATURAL,10";
-    let result = pack.from_line(input);
-    assert!(result.is_err());
+fn when_parsing_an_argument_without_a_value_it_should_return_an_error() {
+    let args = vec!["--weight-bands".to_string()];
+    let result = parse_arguments(args.into_iter());
     assert_eq!(
         result.err().unwrap(),
-        Error::InvalidNumberOfPropertiesForPacks {
-            input: input.to_string(),
-            property_count: 2
+        Error::MissingArgumentValue {
+            argument: "--weight-bands".to_string()
         }
     );
 }
 
 #[test]
-fn when_parsing_a_pack_input_with_too_many_properties_it_should_return_an_error() {
-    let mut pack = PackTemplate::new();
-    let input = "NATURAL,10,20.0,Extra";
-    let result = pack.from_line(input);
-    assert!(result.is_err());
+fn when_parsing_an_unknown_argument_it_should_return_an_error() {
+    let args = vec!["--unknown".to_string()];
+    let result = parse_arguments(args.into_iter());
     assert_eq!(
         result.err().unwrap(),
-        Error::InvalidNumberOfPropertiesForPacks {
-            input: input.to_string(),
-            property_count: 4
+        Error::UnknownArgument {
+            argument: "--unknown".to_string()
         }
     );
 }
 
+// daemon
+
+fn create_daemon_request(payload: &str) -> Vec<u8> {
+    let mut request = (payload.len() as u32).to_be_bytes().to_vec();
+    request.extend_from_slice(payload.as_bytes());
+    request
+}
+
+fn read_daemon_response(response: &mut &[u8]) -> (u8, String) {
+    let status = response[0];
+    let length = u32::from_be_bytes([response[1], response[2], response[3], response[4]]) as usize;
+    let payload = String::from_utf8(response[5..5 + length].to_vec()).unwrap();
+    *response = &response[5 + length..];
+    (status, payload)
+}
+
+#[test]
+fn when_reading_a_daemon_request_it_should_return_the_payload() {
+    let request = create_daemon_request("NATURAL,10,20.0");
+    let mut cursor = Cursor::new(request);
+    let payload = read_daemon_request(&mut cursor)
+        .expect("Failed to read the request.")
+        .expect("Expected a request.");
+    assert_eq!(payload, b"NATURAL,10,20.0");
+}
+
+#[test]
+fn when_reading_a_daemon_request_from_a_closed_connection_it_should_return_none() {
+    let mut cursor = Cursor::new(Vec::new());
+    let result = read_daemon_request(&mut cursor).expect("Failed to read the request.");
+    assert!(result.is_none());
+}
+
+#[test]
+fn when_reading_a_daemon_request_that_is_too_large_it_should_return_an_error() {
+    let request = (u32::MAX).to_be_bytes().to_vec();
+    let mut cursor = Cursor::new(request);
+    let result = read_daemon_request(&mut cursor);
+    assert!(matches!(result, Err(Error::DaemonRequestTooLarge { .. })));
+}
+
+#[test]
+fn when_serving_a_daemon_connection_it_should_answer_each_request() {
+    let mut input = create_daemon_request("NATURAL,10,20.0\n100,10.5,2,3.0");
+    input.extend(create_daemon_request("100,10.5,2,3.0"));
+
+    let mut reader = Cursor::new(input);
+    let mut output = Vec::new();
+    let health = Arc::new(Health::new());
+    serve_daemon_connection(
+        &mut reader,
+        &mut output,
+        &Configuration::default(),
+        &health,
+        health.start_request(),
+        None,
+    )
+    .expect("Failed to serve the connection.");
+
+    assert_eq!(health.requests_in_flight(), 0);
+
+    let mut response = output.as_slice();
+    let (status, payload) = read_daemon_response(&mut response);
+    assert_eq!(status, DAEMON_RESPONSE_SUCCESS);
+    assert_eq!(
+        payload,
+        "Pack Number: 1\n100,10.5,2,3.0\nPack Length: 10.5, Pack Weight: 6.0\n\n\
+         Totals: Input Items: 2, Input Weight: 6.0, Packed Items: 2, Packed Weight: 6.0\n\n"
+    );
+
+    let (status, payload) = read_daemon_response(&mut response);
+    assert_eq!(status, DAEMON_RESPONSE_FAILURE);
+    assert_eq!(payload, Error::MissingPackInformation.to_string());
+    assert!(response.is_empty());
+}
+
+#[cfg(feature = "cbor")]
 #[test]
-fn when_parsing_a_pack_input_with_an_invalid_sort_order_it_should_return_an_error() {
-    let mut pack = PackTemplate::new();
-    let input = "InvalidSortOrder,10,20.0";
-    let result = pack.from_line(input);
-    assert!(result.is_err());
+fn when_serving_a_daemon_connection_in_cbor_it_should_answer_cbor_requests_with_cbor_plans() {
+    let order = serde_json::json!({
+        "pack": { "sort_order": "NATURAL", "maximum_number_of_pieces": 10, "maximum_weight": 20.0 },
+        "items": [{ "id": "100", "length": 10.5, "quantity": 2, "weight": 3.0 }]
+    });
+    let mut payload = Vec::new();
+    ciborium::into_writer(&order, &mut payload).unwrap();
+    let mut input = (payload.len() as u32).to_be_bytes().to_vec();
+    input.extend(payload);
+    let configuration = Configuration {
+        render_options: RenderOptions {
+            format: OutputFormat::Cbor,
+            ..RenderOptions::default()
+        },
+        ..Configuration::default()
+    };
+
+    let mut output = Vec::new();
+    let health = Arc::new(Health::new());
+    serve_daemon_connection(
+        &mut Cursor::new(input),
+        &mut output,
+        &configuration,
+        &health,
+        health.start_request(),
+        None,
+    )
+    .expect("Failed to serve the connection.");
+
+    assert_eq!(output[0], DAEMON_RESPONSE_SUCCESS);
+    let plan: serde_json::Value = ciborium::from_reader(&output[5..]).unwrap();
+    assert_eq!(plan["packs"][0]["items"][0]["id"], "100");
+    assert_eq!(plan["packs"][0]["items"][0]["count"], 2);
+}
+
+#[test]
+fn when_serving_a_daemon_connection_with_metrics_it_should_count_the_requests() {
+    let mut input = create_daemon_request("NATURAL,10,20.0\n100,10.5,2,3.0");
+    input.extend(create_daemon_request("100,10.5,2,3.0"));
+    let metrics = Arc::new(Metrics::new());
+    let configuration = Configuration {
+        metrics: Some(Arc::clone(&metrics)),
+        ..Configuration::default()
+    };
+
+    let mut reader = Cursor::new(input);
+    let mut output = Vec::new();
+    let health = Arc::new(Health::new());
+    serve_daemon_connection(
+        &mut reader,
+        &mut output,
+        &configuration,
+        &health,
+        health.start_request(),
+        None,
+    )
+    .expect("Failed to serve the connection.");
+
+    let mut text = Vec::new();
+    metrics.write_prometheus(&mut text).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(text.contains("pack_planner_requests_total 2\n"));
+    assert!(text.contains("pack_planner_failed_requests_total 1\n"));
+    assert!(text.contains("pack_planner_items_planned_total 2\n"));
+    assert!(text.contains("pack_planner_packs_produced_total 1\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn when_the_daemon_is_asked_to_stop_it_should_finish_the_requests_in_flight() {
+    use std::os::unix::net::UnixStream;
+
+    let socket_path =
+        std::env::temp_dir().join(format!("pack-planner-daemon-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&socket_path);
+    let health = Arc::new(Health::new());
+    let shutdown = Arc::new(AtomicBool::new(false));
+
+    let daemon = {
+        let socket_path = socket_path.to_str().unwrap().to_string();
+        let health = Arc::clone(&health);
+        let shutdown = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            run_daemon(
+                &socket_path,
+                Configuration::default(),
+                health,
+                None,
+                &shutdown,
+            )
+        })
+    };
+    while !health.is_ready() {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    stream
+        .write_all(&create_daemon_request("NATURAL,10,20.0\n100,10.5,2,3.0"))
+        .unwrap();
+    let mut response = vec![0u8; 5];
+    stream.read_exact(&mut response).unwrap();
+    assert_eq!(response[0], DAEMON_RESPONSE_SUCCESS);
+
+    // A request that is still being handled keeps the daemon running.
+    let request_in_flight = health.start_request();
+    shutdown.store(true, Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(200));
+    assert!(!health.is_ready());
+    assert!(!daemon.is_finished());
+
+    drop(request_in_flight);
+    daemon.join().unwrap().expect("Failed to run the daemon.");
+    assert!(!socket_path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn when_the_daemon_is_asked_to_stop_it_should_answer_the_accepted_connections() {
+    use std::os::unix::net::UnixStream;
+
+    let socket_path = std::env::temp_dir().join(format!(
+        "pack-planner-daemon-accepted-{}.sock",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&socket_path);
+    let health = Arc::new(Health::new());
+    let shutdown = Arc::new(AtomicBool::new(false));
+
+    let daemon = {
+        let socket_path = socket_path.to_str().unwrap().to_string();
+        let health = Arc::clone(&health);
+        let shutdown = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            run_daemon(
+                &socket_path,
+                Configuration::default(),
+                health,
+                None,
+                &shutdown,
+            )
+        })
+    };
+    while !health.is_ready() {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // The connection is accepted before the daemon is asked to stop, but its request is sent afterwards.
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    while health.requests_in_flight() == 0 {
+        thread::sleep(Duration::from_millis(10));
+    }
+    shutdown.store(true, Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(200));
+    assert!(!daemon.is_finished());
+
+    stream
+        .write_all(&create_daemon_request("NATURAL,10,20.0\n100,10.5,2,3.0"))
+        .unwrap();
+    let mut response = vec![0u8; 5];
+    stream.read_exact(&mut response).unwrap();
+    assert_eq!(response[0], DAEMON_RESPONSE_SUCCESS);
+
+    daemon.join().unwrap().expect("Failed to run the daemon.");
+    assert!(!socket_path.exists());
 }
 
 #[test]
-fn when_parsing_a_pack_input_with_an_invalid_quantity_it_should_return_an_error() {
-    let mut pack = PackTemplate::new();
-    let input = "NATURAL,abc,20.0";
-    let result = pack.from_line(input);
-    assert!(result.is_err());
+fn when_parsing_the_metrics_argument_it_should_store_the_address() {
+    let args = vec!["--metrics".to_string(), "127.0.0.1:9100".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.metrics_address, Some("127.0.0.1:9100".to_string()));
 }
 
 #[test]
-fn when_parsing_a_pack_input_with_an_invalid_weight_it_should_return_an_error() {
-    let mut pack = PackTemplate::new();
-    let input = "NATURAL,10,abc";
-    let result = pack.from_line(input);
-    assert!(result.is_err());
+fn when_parsing_the_format_argument_it_should_store_the_format() {
+    let args = vec!["--format".to_string(), "json".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.format, Some(OutputFormat::Json));
 }
 
-// parse_input()
+#[test]
+fn when_parsing_an_unknown_format_it_should_return_an_error() {
+    let args = vec!["--format".to_string(), "xml".to_string()];
+    let result = parse_arguments(args.into_iter());
+    assert_eq!(
+        result.err().unwrap(),
+        Error::UnknownOutputFormat {
+            format: "xml".to_string()
+        }
+    );
+}
 
 #[test]
-fn when_parsing_a_valid_input_it_should_return_the_templates() {
-    let input = "NATURAL,10,20.0\n100,10.5,20,3.0\n110,8.0,15,5.0";
-    let mut cursor = Cursor::new(input);
-    let result = parse_input(&mut cursor);
-    assert!(result.is_ok());
+fn when_reporting_an_unknown_format_it_should_list_all_the_formats() {
+    let message = Error::UnknownOutputFormat {
+        format: "xml".to_string(),
+    }
+    .to_string();
+    for format in [
+        OutputFormat::Text,
+        OutputFormat::Json,
+        OutputFormat::Markdown,
+        OutputFormat::Worksheet,
+        OutputFormat::Cbor,
+        OutputFormat::Arrow,
+        OutputFormat::Parquet,
+    ] {
+        assert!(message.contains(&format.to_string()), "{message}");
+    }
+}
 
-    let (pack_template, item_templates) = result.unwrap();
-    assert_eq!(pack_template.maximum_number_of_pieces, 10);
-    assert_eq!(pack_template.maximum_weight, 20.0);
-    assert_eq!(pack_template.sort_order, PackSortOrder::Natural);
-    assert_eq!(item_templates.len(), 2);
-    assert_eq!(item_templates[0].id, "100");
-    assert_eq!(item_templates[1].id, "110");
+#[test]
+fn when_parsing_the_batch_command_it_should_store_the_manifest_path() {
+    let args = vec!["batch".to_string(), "jobs.yaml".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.batch_manifest, Some("jobs.yaml".to_string()));
 }
 
 #[test]
-fn when_parsing_input_with_invalid_pack_information_it_should_return_an_error() {
-    let input = "INVALID_KEYWORD,10,20.0\n100,10.5,20,3.0";
-    let mut cursor = Cursor::new(input);
-    let result = parse_input(&mut cursor);
-    assert!(result.is_err());
+fn when_parsing_the_merge_plans_command_it_should_store_the_plan_paths() {
+    let args = vec![
+        "merge-plans".to_string(),
+        "morning.txt".to_string(),
+        "evening.txt.gz".to_string(),
+        "--output".to_string(),
+        "day.txt".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.plans_to_merge,
+        vec!["morning.txt".to_string(), "evening.txt.gz".to_string()]
+    );
+    assert_eq!(options.output_file, Some("day.txt".to_string()));
+
+    let args = vec!["merge-plans".to_string(), "--output".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::MissingArgumentValue { argument }) if argument == "merge-plans"
+    ));
 }
 
 #[test]
-fn when_parsing_input_with_duplicate_pack_information_it_should_return_an_error() {
-    let input = "NATURAL,10,20.0\nNATURAL,8,15.0\n100,10.5,20,3.0";
-    let mut cursor = Cursor::new(input);
-    let result = parse_input(&mut cursor);
-    assert!(result.is_err());
+fn when_parsing_the_ship_command_it_should_store_the_plan_and_the_carrier_file() {
+    let args = vec![
+        "ship".to_string(),
+        "plan.json".to_string(),
+        "--carrier".to_string(),
+        "carrier.yaml".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.plan_to_ship, Some("plan.json".to_string()));
+    assert_eq!(options.carrier_file, Some("carrier.yaml".to_string()));
+
+    let args = vec!["ship".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::MissingArgumentValue { argument }) if argument == "ship"
+    ));
 }
 
 #[test]
-fn when_parsing_input_with_invalid_item_information_it_should_return_an_error() {
-    let input = "NATURAL,10,20.0\ninvalid_item_format\n100,10.5,20,3.0";
-    let mut cursor = Cursor::new(input);
-    let result = parse_input(&mut cursor);
-    assert!(result.is_err());
+fn when_shipping_without_a_carrier_file_it_should_return_an_error() {
+    let args = vec!["ship".to_string(), "plan.json".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+
+    assert!(matches!(
+        run(&options),
+        Err(Error::MissingRequiredArgument { argument, required_argument })
+            if argument == "ship" && required_argument == "--carrier"
+    ));
 }
 
-// maximum_number_of_items_to_add
 #[test]
-fn when_finding_the_maximum_items_with_a_weight_limit_it_should_return_the_correct_count() {
-    let pack_template = PackTemplate {
-        maximum_number_of_pieces: 10,
-        maximum_weight: 50.0,
-        sort_order: PackSortOrder::NotSet,
+fn when_parsing_the_simulate_command_it_should_store_the_number_of_runs() {
+    let args = vec![
+        "simulate".to_string(),
+        "200".to_string(),
+        "--sample".to_string(),
+        "permutation".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.simulation_runs, Some(200));
+    assert_eq!(options.sample_mode, SampleMode::Permutation);
+
+    for runs in ["0", "many"] {
+        let args = vec!["simulate".to_string(), runs.to_string()];
+        assert!(matches!(
+            parse_arguments(args.into_iter()),
+            Err(Error::InvalidArgumentValue { argument, .. }) if argument == "simulate"
+        ));
+    }
+}
+
+#[test]
+fn when_simulating_it_should_write_the_report_instead_of_the_plan() {
+    let directory =
+        std::env::temp_dir().join(format!("pack-planner-simulate-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let input = directory.join("items.csv");
+    let output = directory.join("report.txt");
+    std::fs::write(&input, "NATURAL,10,100.0\n1001,100,6,1.0\n1002,100,6,1.0\n").unwrap();
+    let args = vec![
+        "simulate".to_string(),
+        "5".to_string(),
+        "--sample".to_string(),
+        "permutation".to_string(),
+        "--input".to_string(),
+        input.display().to_string(),
+        "--output".to_string(),
+        output.display().to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+
+    assert_eq!(run(&options).unwrap(), ExitStatus::Success);
+    let report = std::fs::read_to_string(&output).unwrap();
+    assert!(report.starts_with("Runs: 5\nPacks: Minimum: 2, Maximum: 2,"));
+    assert!(report.ends_with("Packs: 2, Runs: 5\n"));
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_parsing_the_input_argument_it_should_store_the_path() {
+    let args = vec!["--input".to_string(), "s3://orders/orders.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.input_file,
+        Some("s3://orders/orders.csv".to_string())
+    );
+}
+
+#[test]
+fn when_parsing_the_output_argument_it_should_store_the_path() {
+    let args = vec!["--output".to_string(), "plan.txt.gz".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.output_file, Some("plan.txt.gz".to_string()));
+}
+
+#[test]
+fn when_parsing_the_fetch_and_callback_arguments_it_should_store_the_urls() {
+    let args = vec![
+        "--fetch-url".to_string(),
+        "http://orders/next".to_string(),
+        "--callback-url".to_string(),
+        "http://orders/plans".to_string(),
+        "--notify-url".to_string(),
+        "http://ops/hooks".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.fetch_url, Some("http://orders/next".to_string()));
+    assert_eq!(
+        options.callback_url,
+        Some("http://orders/plans".to_string())
+    );
+    assert_eq!(options.notify_url, Some("http://ops/hooks".to_string()));
+}
+
+#[test]
+fn when_parsing_the_pack_closed_url_argument_it_should_store_the_url() {
+    let args = vec![
+        "--pack-closed-url".to_string(),
+        "http://labels/packs".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(
+        options.pack_closed_url,
+        Some("http://labels/packs".to_string())
+    );
+}
+
+#[test]
+fn when_parsing_the_strict_argument_it_should_enable_strict_mode() {
+    let args = vec!["--strict".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.strict);
+}
+
+#[test]
+fn when_parsing_the_exclude_argument_it_should_store_the_path() {
+    let args = vec!["--exclude".to_string(), "exclude.txt".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.exclude_file, Some("exclude.txt".to_string()));
+}
+
+#[test]
+fn when_parsing_the_aliases_argument_it_should_store_the_path() {
+    let args = vec!["--aliases".to_string(), "aliases.csv".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.aliases_file, Some("aliases.csv".to_string()));
+}
+
+#[test]
+fn when_parsing_the_heaviest_first_argument_it_should_list_the_heaviest_items_first() {
+    let args = vec!["--heaviest-first".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.heaviest_first);
+}
+
+#[test]
+fn when_parsing_the_split_output_argument_it_should_store_the_directory() {
+    let args = vec!["--split-output".to_string(), "labels/".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.split_output, Some("labels/".to_string()));
+}
+
+#[test]
+fn when_parsing_the_diagnostics_argument_it_should_set_the_format() {
+    let args = vec!["--diagnostics".to_string(), "pretty".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.diagnostics, DiagnosticsFormat::Pretty);
+
+    let args = vec!["--diagnostics".to_string(), "fancy".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::InvalidArgumentValue { .. })
+    ));
+}
+
+#[test]
+fn when_parsing_the_no_clobber_argument_it_should_keep_existing_output_files() {
+    let options = parse_arguments(std::iter::empty()).expect("Failed to parse the arguments.");
+    assert_eq!(options.existing_output, ExistingOutput::Overwrite);
+
+    let args = vec!["--no-clobber".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.existing_output, ExistingOutput::Keep);
+
+    let args = vec!["--no-clobber".to_string(), "--overwrite".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.existing_output, ExistingOutput::Overwrite);
+}
+
+#[test]
+fn when_parsing_the_attest_argument_it_should_enable_the_attestation() {
+    let args = vec!["--attest".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.attest);
+}
+
+// parse_attested_input()
+
+#[test]
+fn when_parsing_an_attested_input_it_should_add_the_hash_of_the_input() {
+    let input = "NATURAL,10,50.0\n1,10,2,10.0\n";
+    let mut configuration = Configuration::default();
+    let sections = parse_attested_input(&mut input.as_bytes(), "test", &mut configuration, true)
+        .expect("Failed to parse the input.");
+
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].items.len(), 1);
+    assert_eq!(
+        configuration.input_sha256,
+        Some(pack_planner::attestation::input_hash(input.as_bytes()))
+    );
+}
+
+#[test]
+fn when_parsing_the_suggest_relaxations_argument_it_should_enable_the_suggestions() {
+    let args = vec!["--suggest-relaxations".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.suggest_relaxations);
+}
+
+// has_infeasible_items()
+
+#[test]
+fn when_checking_for_infeasible_items_it_should_match_the_errors_and_the_warnings() {
+    let skipped = PlanWarning::InfeasibleItemSkipped {
+        item_id: "1".to_string(),
+        count: 2,
     };
-    let current_pack_weight = 30.0;
-    let current_pack_item_count = 5;
-    let template = ItemTemplate {
-        id: "item1".to_string(),
-        length: 10.0,
-        weight: 5.0,
-        count: 1,
+    assert!(has_infeasible_items(&Ok(vec![skipped])));
+    assert!(!has_infeasible_items(&Ok(Vec::new())));
+    assert!(has_infeasible_items(&Err(
+        Error::ItemDoesNotFitInEmptyPack {
+            item_id: "1".to_string()
+        }
+    )));
+    assert!(has_infeasible_items(&Err(Error::NoItemFitsInEmptyPack {
+        item_id: "1".to_string(),
+        limit: "weight".to_string(),
+        reason: "a single piece weighs 10 while a pack holds at most 5".to_string()
+    })));
+    assert!(!has_infeasible_items(&Err(Error::MissingPackInformation)));
+}
+
+#[test]
+fn when_parsing_the_profile_arguments_it_should_store_the_profile() {
+    let args = vec![
+        "--config".to_string(),
+        "teams.yaml".to_string(),
+        "--profile".to_string(),
+        "export-eu".to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert_eq!(options.configuration_file, Some("teams.yaml".to_string()));
+    assert_eq!(options.profile, Some("export-eu".to_string()));
+}
+
+// load_configuration()
+
+#[test]
+fn when_loading_the_configuration_with_a_profile_it_should_prefer_the_command_line() {
+    let path =
+        std::env::temp_dir().join(format!("pack-planner-profiles-{}.yaml", std::process::id()));
+    std::fs::write(
+        &path,
+        "profiles:\n  export-eu:\n    format: json\n    precision: 3\n    weight_unit: kg\n    explain: true\n    sort_order: SHORT_TO_LONG\n",
+    )
+    .unwrap();
+
+    let mut options = Options {
+        configuration_file: Some(path.to_string_lossy().to_string()),
+        profile: Some("export-eu".to_string()),
+        ..Options::default()
     };
+    let configuration = load_configuration(&options).expect("Failed to load the configuration.");
+    assert_eq!(configuration.render_options.format, OutputFormat::Json);
+    assert_eq!(configuration.render_options.precision, 3);
+    assert_eq!(
+        configuration.render_options.weight_unit,
+        Some("kg".to_string())
+    );
+    assert!(configuration.render_options.explain);
+    assert_eq!(configuration.sort_order, Some(PackSortOrder::ShortToLong));
+
+    options.format = Some(OutputFormat::Markdown);
+    let configuration = load_configuration(&options).expect("Failed to load the configuration.");
+    assert_eq!(configuration.render_options.format, OutputFormat::Markdown);
+
+    options.profile = Some("export-us".to_string());
     assert_eq!(
-        maximum_number_of_items_to_add(
-            &pack_template,
-            current_pack_weight,
-            current_pack_item_count,
-            &template
-        ),
-        4
+        load_configuration(&options).err().unwrap(),
+        Error::UnknownProfile {
+            profile: "export-us".to_string()
+        }
     );
+
+    let _ = std::fs::remove_file(&path);
 }
 
 #[test]
-fn when_finding_the_maximum_items_with_an_item_limit_it_should_return_the_correct_count() {
-    let pack_template = PackTemplate {
-        maximum_number_of_pieces: 10,
-        maximum_weight: 50.0,
-        sort_order: PackSortOrder::NotSet,
+fn when_loading_the_configuration_with_a_checkpoint_and_restarts_it_should_return_an_error() {
+    let options = Options {
+        checkpoint: Some(PlanCheckpoint::new("plan.ckpt", true)),
+        time_limit: Some(Duration::from_secs(5)),
+        ..Options::default()
     };
-    let current_pack_weight = 20.0;
-    let current_pack_item_count = 9;
-    let template = ItemTemplate {
-        id: "item1".to_string(),
-        length: 10.0,
-        weight: 5.0,
-        count: 1,
+    assert_eq!(
+        load_configuration(&options).err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--resume".to_string(),
+            other_argument: "--time-limit".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_parsing_the_algorithm_argument_it_should_select_bin_completion() {
+    let args = vec!["--algorithm".to_string(), "bin-completion".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.bin_completion);
+
+    let args = vec!["--algorithm".to_string(), "simplex".to_string()];
+    assert!(matches!(
+        parse_arguments(args.into_iter()),
+        Err(Error::InvalidArgumentValue { argument, .. }) if argument == "--algorithm"
+    ));
+}
+
+#[test]
+fn when_parsing_the_auto_algorithm_argument_it_should_choose_the_strategy_from_the_items() {
+    let args = vec!["--algorithm".to_string(), "auto".to_string()];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+    assert!(options.auto_strategy);
+    assert!(!options.bin_completion);
+
+    let strategy = load_configuration(&options).unwrap().algorithm.strategy();
+    assert!(strategy.auto);
+}
+
+#[test]
+fn when_loading_the_configuration_with_the_auto_algorithm_and_a_time_limit_it_should_return_an_error(
+) {
+    let options = Options {
+        auto_strategy: true,
+        time_limit: Some(Duration::from_secs(5)),
+        ..Options::default()
+    };
+    assert_eq!(
+        load_configuration(&options).err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--algorithm".to_string(),
+            other_argument: "--time-limit".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_loading_the_configuration_with_bin_completion_and_restarts_it_should_return_an_error() {
+    let options = Options {
+        bin_completion: true,
+        restarts: Some(10),
+        ..Options::default()
+    };
+    assert_eq!(
+        load_configuration(&options).err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--algorithm".to_string(),
+            other_argument: "--restarts".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_loading_the_configuration_with_a_pack_closed_url_and_restarts_it_should_return_an_error() {
+    let options = Options {
+        pack_closed_url: Some("http://labels/packs".to_string()),
+        restarts: Some(10),
+        ..Options::default()
     };
     assert_eq!(
-        maximum_number_of_items_to_add(
-            &pack_template,
-            current_pack_weight,
-            current_pack_item_count,
-            &template
-        ),
-        1
+        load_configuration(&options).err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--pack-closed-url".to_string(),
+            other_argument: "--restarts".to_string()
+        }
     );
+
+    let options = Options {
+        pack_closed_url: Some("http://labels/packs".to_string()),
+        ..Options::default()
+    };
+    let configuration = load_configuration(&options).expect("Failed to load the configuration.");
+    assert!(configuration.pack_closed_hook.is_some());
 }
 
 #[test]
-fn when_finding_the_maximum_items_with_no_limit_it_should_return_the_correct_count() {
-    let pack_template = PackTemplate {
-        maximum_number_of_pieces: 10,
-        maximum_weight: 50.0,
-        sort_order: PackSortOrder::NotSet,
+fn when_loading_the_configuration_with_a_pack_closed_url_and_stops_it_should_return_an_error() {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-main-stops-{}.txt",
+        std::process::id()
+    ));
+    std::fs::write(&path, "").unwrap();
+    let options = Options {
+        pack_closed_url: Some("http://labels/packs".to_string()),
+        delivery_stops_file: Some(path.display().to_string()),
+        ..Options::default()
     };
-    let current_pack_weight = 45.0;
-    let current_pack_item_count = 9;
-    let template = ItemTemplate {
-        id: "item1".to_string(),
-        length: 10.0,
-        weight: 5.0,
-        count: 1,
+    let result = load_configuration(&options);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        result.err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--pack-closed-url".to_string(),
+            other_argument: "--stops".to_string()
+        }
+    );
+
+    let options = Options {
+        pack_closed_url: Some("http://labels/packs".to_string()),
+        sequence_file: Some("sequence.txt".to_string()),
+        ..Options::default()
     };
     assert_eq!(
-        maximum_number_of_items_to_add(
-            &pack_template,
-            current_pack_weight,
-            current_pack_item_count,
-            &template
-        ),
-        1
+        load_configuration(&options).err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--pack-closed-url".to_string(),
+            other_argument: "--sequence-file".to_string()
+        }
+    );
+}
+
+#[test]
+fn when_loading_the_configuration_with_an_event_log_and_a_sequence_file_it_should_return_an_error()
+{
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-main-sequence-events-{}.jsonl",
+        std::process::id()
+    ));
+    let options = Options {
+        event_log_file: Some(path.display().to_string()),
+        sequence_file: Some("sequence.txt".to_string()),
+        ..Options::default()
+    };
+    assert_eq!(
+        load_configuration(&options).err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--event-log".to_string(),
+            other_argument: "--sequence-file".to_string()
+        }
+    );
+    assert!(!path.exists());
+}
+
+#[test]
+fn when_loading_the_configuration_with_an_event_log_and_bin_completion_it_should_return_an_error() {
+    let path = std::env::temp_dir().join(format!(
+        "pack-planner-main-events-{}.jsonl",
+        std::process::id()
+    ));
+    let options = Options {
+        event_log_file: Some(path.display().to_string()),
+        bin_completion: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        load_configuration(&options).err().unwrap(),
+        Error::IncompatibleArguments {
+            argument: "--event-log".to_string(),
+            other_argument: "--algorithm".to_string()
+        }
+    );
+    assert!(!path.exists());
+
+    let options = Options {
+        event_log_file: Some(path.display().to_string()),
+        ..Options::default()
+    };
+    let configuration = load_configuration(&options).expect("Failed to load the configuration.");
+    assert!(configuration.event_log.is_some());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+// exit_status_for()
+
+#[test]
+fn when_a_kit_does_not_fit_in_an_empty_pack_it_should_exit_with_the_infeasible_status() {
+    let directory =
+        std::env::temp_dir().join(format!("pack-planner-kit-status-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let input = directory.join("items.csv");
+    let bill_of_materials = directory.join("kits.csv");
+    std::fs::write(&input, "NATURAL,10,2.0\nKIT:gift-set-1,1\n").unwrap();
+    std::fs::write(
+        &bill_of_materials,
+        "gift-set-1,1001,200,2,0.5\ngift-set-1,1002,300,1,1.5\n",
+    )
+    .unwrap();
+    let args = vec![
+        "--input".to_string(),
+        input.display().to_string(),
+        "--bom".to_string(),
+        bill_of_materials.display().to_string(),
+        "--keep-kits-together".to_string(),
+        "--output".to_string(),
+        directory.join("plan.txt").display().to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+
+    let error = run(&options).err().unwrap();
+    assert_eq!(
+        error,
+        Error::KitDoesNotFitInEmptyPack {
+            kit_id: "gift-set-1".to_string()
+        }
+    );
+    assert_eq!(exit_status_for(&error) as u8, 3);
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_items_that_dont_fit_are_skipped_it_should_write_the_plan_and_exit_with_the_infeasible_status(
+) {
+    let directory =
+        std::env::temp_dir().join(format!("pack-planner-skip-status-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let input = directory.join("items.csv");
+    let output = directory.join("plan.txt");
+    std::fs::write(&input, "NATURAL,10,5.0\n1001,100,2,1.0\n1002,200,1,6.0\n").unwrap();
+    let args = vec![
+        "--on-infeasible".to_string(),
+        "skip".to_string(),
+        "--input".to_string(),
+        input.display().to_string(),
+        "--output".to_string(),
+        output.display().to_string(),
+    ];
+    let options = parse_arguments(args.into_iter()).expect("Failed to parse the arguments.");
+
+    assert_eq!(run(&options).unwrap(), ExitStatus::Infeasible);
+    assert!(std::fs::read_to_string(&output).unwrap().contains("1001"));
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn when_determining_the_exit_status_it_should_match_the_kind_of_error() {
+    assert_eq!(
+        exit_status_for(&Error::MissingPackInformation),
+        ExitStatus::InvalidInput
+    );
+    assert_eq!(
+        exit_status_for(&Error::ItemDoesNotFitInEmptyPack {
+            item_id: "1".to_string()
+        }),
+        ExitStatus::Infeasible
+    );
+    assert_eq!(
+        exit_status_for(&Error::UnableToOpenFile {
+            path: "items.csv".to_string(),
+            reason: "not found".to_string()
+        }),
+        ExitStatus::Io
+    );
+    assert_eq!(
+        exit_status_for(&Error::TotalsNotConserved {
+            input_items: 10,
+            input_weight: 10.0,
+            output_items: 9,
+            output_weight: 9.0
+        }) as u8,
+        5
     );
+    assert_eq!(ExitStatus::Warnings as u8, 1);
 }
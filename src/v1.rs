@@ -0,0 +1,48 @@
+//! The stable API of the library. The types and the functions in this module keep their meaning and their
+//! signatures for all the 1.x releases, while the other modules contain the internals of the planner, which
+//! may change between releases.
+//!
+//! The parsing of the line based input formats is only available with the `text-format` feature, which is
+//! enabled by default. Without it the items and the pack template are created in code, e.g.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use pack_planner::v1::{plan_items, Configuration, ItemTemplate, PackSortOrder, PackTemplate};
+//!
+//! let pack_template = PackTemplate {
+//!     sort_order: PackSortOrder::Natural,
+//!     maximum_number_of_pieces: 10,
+//!     maximum_weight: 50.0,
+//!     ..PackTemplate::new()
+//! };
+//! let items = vec![ItemTemplate {
+//!     id: "1001".into(),
+//!     length: 6200.0,
+//!     weight: 9.653,
+//!     count: 3,
+//!     attributes: Arc::default(),
+//! }];
+//!
+//! let mut output = Vec::new();
+//! plan_items(&pack_template, items, &mut output, &Configuration::default()).unwrap();
+//! assert!(String::from_utf8(output).unwrap().starts_with("Pack Number: 1\n"));
+//! ```
+
+pub use crate::errors::Error;
+pub use crate::model::{
+    Carton, DuplicateIdPolicy, InfeasibleItemPolicy, ItemAttributes, ItemTemplate, LengthMode,
+    Pack, PackCapacity, PackRule, PackSortOrder, PackTemplate, PackedItem, PackedItemRef,
+    PlacementLimit, PlanOutcome, PlanWarning, RemainingCapacity, SheetSize, StorageClass,
+};
+pub use crate::planner::{plan_packs, GreedyAlgorithm, PackingAlgorithm};
+pub use crate::render::{write_plan, OutputFormat, RenderOptions};
+pub use crate::scoring::{
+    compare_plans, score_plan, Balance, PackCount, Score, ShippingCost, UtilizationVariance,
+};
+pub use crate::{plan_items, Configuration};
+
+#[cfg(feature = "text-format")]
+pub use crate::parser::{parse_input, parse_items, parse_pack_rules};
+#[cfg(feature = "text-format")]
+pub use crate::plan_input;